@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+use anstream::println;
+use clap::Parser;
+use icy_engine_edit::collaboration::{AdminCommand, RoomHost, RoomSettings};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::TcpListener;
+
+#[derive(Parser)]
+#[command(version, about = "Headless collaboration server hosting several Moebius compatible rooms")]
+struct Cli {
+    #[arg(help = "Address to bind to", long, default_value = "0.0.0.0")]
+    bind: String,
+
+    #[arg(help = "Port to listen on", long, short, default_value_t = 8000)]
+    port: u16,
+
+    #[arg(help = "Folder with rooms.toml and one autosave folder per room", long, default_value = ".")]
+    data_dir: PathBuf,
+
+    #[arg(help = "Room created (and made the default) when the data folder has no rooms yet", long, default_value = "main")]
+    initial_room: String,
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let _logger = flexi_logger::Logger::try_with_env_or_str("info").and_then(|logger| logger.start());
+
+    let host = match RoomHost::open(&cli.data_dir).await {
+        Ok(host) => host,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    if host.rooms().is_empty() {
+        let created = host.create_room(RoomSettings::new(cli.initial_room.clone())).await;
+        if let Err(e) = created.and_then(|_| host.set_default_room(Some(cli.initial_room.clone()))) {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    }
+
+    let listener = match TcpListener::bind((cli.bind.as_str(), cli.port)).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to bind {}:{}: {e}", cli.bind, cli.port);
+            std::process::exit(1);
+        }
+    };
+    let local_addr = listener
+        .local_addr()
+        .map_or_else(|_| format!("{}:{}", cli.bind, cli.port), |addr| addr.to_string());
+    println!("Collaboration server listening on {local_addr}, data in {}", host.data_dir().display());
+    for room in host.rooms() {
+        println!("  ws://{local_addr}/{}", room.name());
+    }
+    println!("Type 'help' for the admin commands.");
+
+    let mut server = tokio::spawn(host.clone().serve(listener));
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut console_open = true;
+    loop {
+        tokio::select! {
+            line = lines.next_line(), if console_open => {
+                let Ok(Some(line)) = line else {
+                    // running without a terminal, only Ctrl+C stops the server
+                    console_open = false;
+                    continue;
+                };
+                match line.parse::<AdminCommand>() {
+                    Ok(AdminCommand::Quit) => break,
+                    Ok(command) => match host.execute(&command).await {
+                        Ok(out) => println!("{out}"),
+                        Err(e) => println!("Error: {e}"),
+                    },
+                    Err(e) if e.is_empty() => {}
+                    Err(e) => println!("{e}"),
+                }
+            }
+            _ = &mut ctrl_c => break,
+            result = &mut server => {
+                match result {
+                    Ok(Err(e)) => eprintln!("{e}"),
+                    Err(e) => eprintln!("{e}"),
+                    Ok(Ok(())) => {}
+                }
+                break;
+            }
+        }
+    }
+
+    println!("Saving rooms...");
+    let failed = host.save_all().await;
+    std::process::exit(i32::from(failed > 0));
+}
@@ -1,4 +1,5 @@
 font-editor-table = Char table 0-{ $length }:
+font-editor-codepage = Codepage:
 
 unsaved-title=Untitled
 
@@ -75,6 +76,8 @@ file-settings-format-unrestricted=Full RGB colors, unlimited fonts
 menu-close=Close
 menu-save-as=Save As…
 menu-connect-to-server=Connect to Server…
+menu-record_journal=Record Edit Journal
+menu-timelapse=Timelapse…
 menu-close-editor=Close Editor
 menu-quit-app=Quit
 menu-toggle-chat=Toggle Chat Panel
@@ -104,6 +107,7 @@ collab-user-joined={ $nick } has joined
 collab-user-joined-group={ $nick } <{ $group }> has joined
 collab-user-left={ $nick } has left
 collab-user-left-group={ $nick } <{ $group }> has left
+collab-notice-read-only=You are read-only in this session, your changes are not shared.
 collab-no-other-users=No other users
 collab-you=You
 collab-guest=Guest
@@ -156,6 +160,10 @@ menu-justifyleft=Justify Left
 menu-justifyright=Justify Right
 menu-justifycenter=Center
 menu-crop=Crop
+menu-rotate_cw=Rotate 90° Clockwise
+menu-rotate_ccw=Rotate 90° Counter-Clockwise
+menu-rotate_180=Rotate 180°
+menu-scale=Scale…
 menu-justify_line_center=Center Line
 menu-justify_line_left=Left Justify Line
 menu-justify_line_right=Right Justify Line
@@ -173,8 +181,16 @@ menu-scroll_area_up=Scroll Area Up
 menu-scroll_area_down=Scroll Area Down
 menu-scroll_area_left=Scroll Area Left
 menu-scroll_area_right=Scroll Area Right
-menu-mirror_mode=Mirror Mode
+menu-mirror_mode=Symmetry
+menu-symmetry_off=Off
+menu-symmetry_horizontal=Horizontal
+menu-symmetry_vertical=Vertical
+menu-symmetry_both=Four-way
+menu-symmetry_axis_at_caret=Axis at Caret
+menu-symmetry_center_axis=Center Axis
 menu-area_operations=Area
+menu-find_replace=Find and Replace…
+menu-recolor=Recolor…
 
 menu-selection=&Selection
 menu-select-all=Select All
@@ -214,6 +230,7 @@ menu-fonts=Fonts
 menu-open_font_selector=Select Font…
 menu-open_font_slot_manager=Manage Font Slots…
 menu-add_fonts=Add Fonts…
+menu-generate_font=Generate Font…
 menu-open_font_manager=Edit Buffer Fonts…
 menu-open_font_directoy=Open Text-Art Fonts Directory…
 
@@ -255,6 +272,11 @@ menu-update-available=⬆ Update available: { $version }
 # Plugin errors
 error-plugin-title=Plugin Error
 
+# Edit journal errors
+journal-error-title=Edit Journal
+journal-error-exists={ $file } holds a finished edit journal that doesn't lead to this document. Move or delete it to record a new journal.
+journal-error-start=Failed to start the edit journal: { $error }
+
 # BitFont Editor menu items
 menu-clear-glyph=Clear Glyph
 menu-inverse-glyph=Inverse Glyph
@@ -300,6 +322,22 @@ cmd-select-justify_right-action = Justify Right
 cmd-select-justify_right-desc = Align selection to the right
 cmd-select-justify_right-menu = Justify Right
 
+cmd-select-rotate_cw-action = Rotate Clockwise
+cmd-select-rotate_cw-desc = Rotate the selection or layer 90° clockwise
+cmd-select-rotate_cw-menu = Rotate 90° Clockwise
+
+cmd-select-rotate_ccw-action = Rotate Counter-Clockwise
+cmd-select-rotate_ccw-desc = Rotate the selection or layer 90° counter-clockwise
+cmd-select-rotate_ccw-menu = Rotate 90° Counter-Clockwise
+
+cmd-select-rotate_180-action = Rotate 180°
+cmd-select-rotate_180-desc = Rotate the selection or layer by 180°
+cmd-select-rotate_180-menu = Rotate 180°
+
+cmd-select-scale-action = Scale
+cmd-select-scale-desc = Scale the selection or layer to a new size
+cmd-select-scale-menu = Scale…
+
 # Area Operations Commands
 cmd-area-justify_line_left-action = Justify Line Left
 cmd-area-justify_line_left-desc = Left justify current line
@@ -608,6 +646,78 @@ edit-layer-dialog-is-y-offset-label=Y offset
 edit-layer-dialog-has-alpha-checkbox=Has alpha
 edit-layer-dialog-is-alpha-locked-checkbox=Alpha locked
 
+scale-dialog-title=Scale
+scale-dialog-current-size=Current size: { $width }×{ $height }
+scale-dialog-half-block=Half block resampling
+
+find-replace-dialog-title=Find and Replace
+find-replace-dialog-find=Find
+find-replace-dialog-replace=Replace with
+find-replace-dialog-char=Character
+find-replace-dialog-foreground=Foreground
+find-replace-dialog-background=Background
+find-replace-dialog-blink=Blink / Ice
+find-replace-dialog-font_page=Font page
+find-replace-dialog-any=Any
+find-replace-dialog-keep=Keep
+find-replace-dialog-on=On
+find-replace-dialog-off=Off
+find-replace-dialog-scope=Search in
+find-replace-dialog-scope-selection=Selection
+find-replace-dialog-scope-layer=Current layer
+find-replace-dialog-scope-document=Whole document
+find-replace-dialog-matches={ $count ->
+    [one] 1 match
+   *[other] { $count } matches
+}
+find-replace-dialog-hint=Colors are palette indices or #rrggbb, empty fields match any value.
+find-replace-dialog-replace_button=Replace All
+
+recolor-dialog-title=Recolor
+recolor-dialog-preset=Preset
+recolor-dialog-preset-custom=Custom
+recolor-dialog-preset-swap_hue=Swap hues
+recolor-dialog-preset-grayscale=Grayscale
+recolor-dialog-preset-invert_brightness=Invert brightness
+recolor-dialog-preset-nearest_in_palette=Nearest in palette file
+recolor-dialog-hue-red=Red
+recolor-dialog-hue-yellow=Yellow
+recolor-dialog-hue-green=Green
+recolor-dialog-hue-cyan=Cyan
+recolor-dialog-hue-blue=Blue
+recolor-dialog-hue-magenta=Magenta
+recolor-dialog-load_palette=Load…
+recolor-dialog-no_palette=No palette loaded
+recolor-dialog-unsupported_palette=Unsupported or empty palette file
+recolor-dialog-scope=Recolor in
+recolor-dialog-foreground=Foreground
+recolor-dialog-background=Background
+recolor-dialog-no_colors=No colors used
+recolor-dialog-hint=Targets are palette indices or #rrggbb. A palette file replaces the document palette.
+recolor-dialog-apply=Recolor
+
+timelapse-dialog-title=Timelapse
+timelapse-dialog-empty=No edits have been recorded for this document. Enable File → Record Edit Journal to record them.
+timelapse-dialog-position=Edit { $current } of { $total } ({ $time } editing time)
+timelapse-dialog-play=Play
+timelapse-dialog-pause=Pause
+timelapse-dialog-frame_every=Frame every
+timelapse-dialog-unit-edits=edits
+timelapse-dialog-unit-seconds=seconds of editing
+timelapse-dialog-delay=Delay (ms)
+timelapse-dialog-frames={ $frames } frames
+timelapse-dialog-format=Format
+timelapse-dialog-format-icyanim=Animation script (.icyanim)
+timelapse-dialog-format-gif=GIF animation
+timelapse-dialog-format-png=PNG frame sequence
+timelapse-dialog-exporting=Exporting frame { $current } of { $total }
+timelapse-dialog-exported=Timelapse exported.
+timelapse-dialog-write-failed=Failed to write { $file }: { $error }
+timelapse-dialog-gif-too-large=The timelapse is too large for a GIF.
+timelapse-dialog-invalid-size=The timelapse has no frames with a valid size.
+timelapse-dialog-gif-failed=GIF encoding failed: { $error }
+timelapse-dialog-export=Export…
+
 error-load-file=Error loading file: { $error }
 
 select-font-dialog-title=Select Font ({ $fontcount} available)
@@ -632,6 +742,10 @@ layer_tool_menu_duplicate_layer=Duplicate layer
 layer_tool_menu_merge_layer=Merge layer
 layer_tool_menu_delete_layer=Delete layer
 layer_tool_menu_clear_layer=Clear layer
+layer_tool_menu_group_layer=Group layer
+layer_tool_menu_ungroup_layer=Ungroup
+layer_tool_menu_collapse_group=Collapse group
+layer_tool_menu_expand_group=Expand group
 
 channel_tool_title=Channels
 channel_tool_fg=Foreground
@@ -847,6 +961,20 @@ tdf-dialog-edit-settings-title=Edit Font Settings
 tdf-dialog-font-type=Font Type:
 tdf-dialog-font-name=Name:
 tdf-dialog-spacing=Spacing:
+tdf-dialog-figlet-layout=FIGlet layout:
+tdf-dialog-figlet-full-width=Full width
+tdf-dialog-figlet-fitting=Fitting
+tdf-dialog-figlet-smushing=Smushing
+tdf-dialog-generate-font-title=Generate Font
+tdf-dialog-generate-source=Source:
+tdf-dialog-generate-source-placeholder=TrueType font or image sheet…
+tdf-dialog-generate-info=Image sheets are read as a 16×16 character grid
+tdf-dialog-generate-cell-height=Height:
+tdf-dialog-generate-threshold=Threshold:
+tdf-dialog-generate-shade=Shade partially covered cells
+tdf-dialog-generate-outline=Outline
+tdf-dialog-generate-button=Generate
+tdf-dialog-generate-failed=The font could not be generated: { $error }
 
 settings-heading=Settings
 settings-reset_button=Reset
@@ -927,6 +1055,10 @@ font-import-preview=Preview
 font-import-no-preview=No preview available
 font-import-native-info=Native font file
 font-import-xb-info=XBin file with embedded font(s)
+font-import-unicode-info=Unicode bitmap font, glyphs are picked by codepage
+font-import-codepage=Codepage:
+font-import-raster-info=Raster font with one or more sizes
+font-import-select-size=Select Size:
 font-import-xb-font-1=Font 1
 font-import-xb-font-2=Font 2
 font-import-select-font=Select Font:
@@ -937,6 +1069,9 @@ font-import-dithering=Use Dithering
 menu-export-font=Export Font…
 font-export-format=Format
 font-export-com-format=COM Type
+font-export-codepage=Codepage
+font-export-nine-dot=9 pixel wide cells (VGA)
+font-export-bitmap-strike=Embed bitmap strike
 font-export-path=Export to
 font-export-no-path=No path selected
 font-export-button=Export
@@ -989,6 +1124,19 @@ tag-list-replacement=Replacement
 tag-list-no-tags=No tags
 tag-list-in-text=In text
 tag-list-with-gotoxy=With GotoXY
+tag-list-bbs=BBS
+tag-list-sample-preview=Preview sample data
+tag-list-no-issues=All tags fit.
+tag-list-issue-out-of-bounds=reaches past the line end
+tag-list-issue-overlap=overlaps tag #{ $other }
+tag-list-issue-syntax=not a { $bbs } code
+tag-list-issue-unknown=unknown to { $bbs }
+tag-list-issue-truncated=sample needs { $sample } chars, tag is { $width } wide
+tag-list-issue-no-width={ $bbs } can't pad the field, use GotoXY placement
+tag-list-convert-undo=Convert tags to { $bbs }
+tag-list-convert=Convert from { $bbs }
+tag-list-convert-failed={ $count } tag(s) could not be converted: { $error }
+tag-list-delete-failed=The tag could not be deleted: { $error }
 
 # Tag Edit Dialog
 tag-edit-preview=Preview
@@ -1007,7 +1155,9 @@ tool-hint-ellipse=Ellipse  •  { $brush }
 tool-hint-filled_ellipse=Filled ellipse  •  { $brush }
 tool-hint-pipette=Color picker  •  Click to sample fg/bg/char
 tool-hint-fill=Fill  •  { $brush }
+tool-hint-gradient=Gradient  •  Drag from start to end over a selection or flood region
 tool-hint-font=Font  •  Place a TDF/Figlet caret, then type
+tool-hint-text-box=Text Box  •  Drag a rectangle, type or paste; drag the corner to reflow, Ctrl+Enter commits, Esc cancels
 tool-hint-tag=Tag  •  Click to place an expandable tag
 tool-hint-paste=Paste  •  Click to commit, Esc to cancel
 brush-hint-char=Char mode — paints '{ $ch }'
@@ -1016,3 +1166,4 @@ brush-hint-shading=Shading mode (LMB lighter, RMB darker)
 brush-hint-replace=Replace mode (recolors existing characters)
 brush-hint-blink=Blink mode (toggles blink attribute)
 brush-hint-colorize=Colorize mode (changes only colors)
+brush-hint-box_line=Box line mode (joins single/double lines of the outline style)
@@ -180,129 +180,10 @@ fn get_log_dir() -> Option<PathBuf> {
     None
 }
 
-/// Returns the default EGA 16-color palette (8-bit RGB values).
-fn default_ega_palette() -> [[u8; 3]; 16] {
-    [
-        [0x00, 0x00, 0x00], // 0: Black
-        [0x00, 0x00, 0xAA], // 1: Blue
-        [0x00, 0xAA, 0x00], // 2: Green
-        [0x00, 0xAA, 0xAA], // 3: Cyan
-        [0xAA, 0x00, 0x00], // 4: Red
-        [0xAA, 0x00, 0xAA], // 5: Magenta
-        [0xAA, 0x55, 0x00], // 6: Brown/Yellow
-        [0xAA, 0xAA, 0xAA], // 7: Light Gray
-        [0x55, 0x55, 0x55], // 8: Dark Gray
-        [0x55, 0x55, 0xFF], // 9: Light Blue
-        [0x55, 0xFF, 0x55], // 10: Light Green
-        [0x55, 0xFF, 0xFF], // 11: Light Cyan
-        [0xFF, 0x55, 0x55], // 12: Light Red
-        [0xFF, 0x55, 0xFF], // 13: Light Magenta
-        [0xFF, 0xFF, 0x55], // 14: Yellow
-        [0xFF, 0xFF, 0xFF], // 15: White
-    ]
-}
-
 /// Run the collaboration server in headless mode.
 fn run_server(bind: String, port: u16, password: Option<String>, max_users: usize, file: Option<PathBuf>, backup_folder: Option<PathBuf>, interval: u64) {
-    use icy_engine::{FileFormat, IceMode, TextPane};
-    use icy_engine_edit::collaboration::{run_server as run_collab_server, AutosaveConfig, Block, ServerConfig};
-    use icy_engine_edit::SauceMetaData;
-
-    // Load document from file or create empty 80x25 canvas
-    let (columns, rows, initial_document, ice_colors, use_9px_font, font_name, sauce, palette) = if let Some(ref path) = file {
-        // Detect format and load file
-        let format = if let Some(f) = FileFormat::from_path(path) {
-            f
-        } else {
-            eprintln!(
-                "{}",
-                i18n_embed_fl::fl!(crate::LANGUAGE_LOADER, "server-error-unknown-format", path = path.display().to_string())
-            );
-            eprintln!("{}", i18n_embed_fl::fl!(crate::LANGUAGE_LOADER, "server-starting-empty-canvas"));
-            FileFormat::Ansi // Fallback, will likely fail to load
-        };
-        match format.load(path, None) {
-            Ok(loaded_doc) => {
-                let buffer = &loaded_doc.screen.buffer;
-                let cols = buffer.width() as u32;
-                let rws: u32 = buffer.height() as u32;
-
-                // Extract character data (column-major for server)
-                let mut doc = Vec::with_capacity(cols as usize);
-                for col in 0..cols as i32 {
-                    let mut column = Vec::with_capacity(rws as usize);
-                    for row in 0..rws as i32 {
-                        let ch = buffer.char_at((col, row).into());
-                        // Extract palette index from AttributeColor
-                        let fg = match ch.attribute.foreground_color() {
-                            icy_engine::AttributeColor::Palette(n) | icy_engine::AttributeColor::ExtendedPalette(n) => n,
-                            icy_engine::AttributeColor::Rgb(_, _, _) => 7, // Default to light gray
-                            icy_engine::AttributeColor::Transparent => 0,
-                        };
-                        let bg = match ch.attribute.background_color() {
-                            icy_engine::AttributeColor::Palette(n) | icy_engine::AttributeColor::ExtendedPalette(n) => n,
-                            icy_engine::AttributeColor::Rgb(_, _, _) => 0, // Default to black
-                            icy_engine::AttributeColor::Transparent => 0,
-                        };
-                        column.push(Block { code: ch.ch as u32, fg, bg });
-                    }
-                    doc.push(column);
-                }
-
-                // Extract metadata
-                let ice = matches!(buffer.ice_mode, IceMode::Ice);
-                let font = buffer.font(0).map_or_else(|| "IBM VGA".to_string(), |f| f.name().to_string());
-
-                // Get SAUCE data from the LoadedDocument
-                let sauce: SauceMetaData = if let Some(ref sauce_record) = loaded_doc.sauce_opt {
-                    sauce_record.metadata()
-                } else {
-                    SauceMetaData::default()
-                };
-
-                // Extract 16-color palette from buffer
-                let mut palette = [[0u8; 3]; 16];
-                for i in 0..16 {
-                    let (r, g, b) = buffer.palette.rgb(i as u32);
-                    palette[i] = [r, g, b];
-                }
-                (cols, rws, Some(doc), ice, false, font, sauce, palette)
-            }
-            Err(e) => {
-                eprintln!(
-                    "{}",
-                    i18n_embed_fl::fl!(
-                        crate::LANGUAGE_LOADER,
-                        "server-error-loading-file",
-                        path = path.display().to_string(),
-                        error = e.to_string()
-                    )
-                );
-                eprintln!("{}", i18n_embed_fl::fl!(crate::LANGUAGE_LOADER, "server-starting-empty-canvas"));
-                (
-                    80,
-                    25,
-                    None,
-                    false,
-                    false,
-                    "IBM VGA".to_string(),
-                    SauceMetaData::default(),
-                    default_ega_palette(),
-                )
-            }
-        }
-    } else {
-        (
-            80,
-            25,
-            None,
-            false,
-            false,
-            "IBM VGA".to_string(),
-            SauceMetaData::default(),
-            default_ega_palette(),
-        )
-    };
+    use icy_engine::FileFormat;
+    use icy_engine_edit::collaboration::{run_server as run_collab_server, AutosaveConfig, ServerConfig};
 
     let bind_addr = format!("{bind}:{port}");
     let bind_addr: std::net::SocketAddr = match bind_addr.parse() {
@@ -321,20 +202,10 @@ fn run_server(bind: String, port: u16, password: Option<String>, max_users: usiz
         }
     };
 
-    let config = ServerConfig {
+    let mut config = ServerConfig {
         bind_addr,
         password: password.unwrap_or_default(),
         max_users,
-        columns,
-        rows,
-        enable_extended_protocol: true,
-        status_message: String::new(),
-        initial_document,
-        ice_colors,
-        use_9px_font,
-        font_name,
-        palette,
-        sauce,
         autosave: AutosaveConfig {
             backup_folder: backup_folder.unwrap_or_else(|| std::path::PathBuf::from(".")),
             interval: if interval == 0 {
@@ -354,8 +225,41 @@ fn run_server(bind: String, port: u16, password: Option<String>, max_users: usiz
         ui_stop_hint: i18n_embed_fl::fl!(crate::LANGUAGE_LOADER, "server-stop-hint"),
         ui_none: i18n_embed_fl::fl!(crate::LANGUAGE_LOADER, "server-none"),
         ui_unlimited: i18n_embed_fl::fl!(crate::LANGUAGE_LOADER, "server-unlimited"),
+        ..Default::default()
     };
 
+    // Load document from file or start with an empty 80x25 canvas
+    if let Some(ref path) = file {
+        let format = if let Some(f) = FileFormat::from_path(path) {
+            f
+        } else {
+            eprintln!(
+                "{}",
+                i18n_embed_fl::fl!(crate::LANGUAGE_LOADER, "server-error-unknown-format", path = path.display().to_string())
+            );
+            eprintln!("{}", i18n_embed_fl::fl!(crate::LANGUAGE_LOADER, "server-starting-empty-canvas"));
+            FileFormat::Ansi // Fallback, will likely fail to load
+        };
+        match format.load(path, None) {
+            Ok(loaded_doc) => {
+                let sauce = loaded_doc.sauce_opt.as_ref().map(icy_sauce::SauceRecord::metadata).unwrap_or_default();
+                config.set_document(&loaded_doc.screen.buffer, sauce);
+            }
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    i18n_embed_fl::fl!(
+                        crate::LANGUAGE_LOADER,
+                        "server-error-loading-file",
+                        path = path.display().to_string(),
+                        error = e.to_string()
+                    )
+                );
+                eprintln!("{}", i18n_embed_fl::fl!(crate::LANGUAGE_LOADER, "server-starting-empty-canvas"));
+            }
+        }
+    }
+
     // Create tokio runtime and run the server
     let rt = tokio::runtime::Runtime::new().expect(&i18n_embed_fl::fl!(crate::LANGUAGE_LOADER, "server-error-runtime"));
     rt.block_on(async {
@@ -1,8 +1,8 @@
 //! MCP tool handlers for `icy_draw`
 
 use crate::mcp::types::{
-    AnimationGetScreenRequest, AnimationGetTextRequest, AnimationReplaceTextRequest, AnsiAddLayerRequest, AnsiDeleteLayerRequest, AnsiGetLayerRequest,
-    AnsiGetRegionRequest, AnsiGetScreenRequest, AnsiMergeDownLayerRequest, AnsiMoveLayerRequest, AnsiResizeRequest, AnsiRunScriptRequest,
+    AnimationGetScreenRequest, AnimationGetTextRequest, AnimationReplaceTextRequest, AnsiAddLayerRequest, AnsiDeleteLayerRequest, AnsiFindReplaceRequest,
+    AnsiGetLayerRequest, AnsiGetRegionRequest, AnsiGetScreenRequest, AnsiMergeDownLayerRequest, AnsiMoveLayerRequest, AnsiResizeRequest, AnsiRunScriptRequest,
     AnsiSelectionActionRequest, AnsiSetCaretRequest, AnsiSetCharRequest, AnsiSetColorRequest, AnsiSetLayerPropsRequest, AnsiSetRegionRequest,
     AnsiSetSelectionRequest, BitFontGetCharRequest, BitFontSetCharRequest, CharListResponse, EditorStatus, GetHelpRequest, LoadDocumentRequest,
     NewDocumentRequest,
@@ -794,6 +794,31 @@ impl IcyDrawMcpHandler {
             Err(e) => Ok(CallToolResult::error(vec![Content::text(e)])),
         }
     }
+
+    #[tool(
+        description = "[ANSI Editor] Find cells by character, colors, blink and font page (omitted fields are wildcards) and optionally replace their properties. One undo step, returns the number of matches."
+    )]
+    async fn ansi_find_replace(&self, params: Parameters<AnsiFindReplaceRequest>) -> Result<CallToolResult, McpError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        self.command_tx
+            .send(McpCommand::AnsiFindReplace {
+                find: params.0.find.clone(),
+                replace: params.0.replace.clone(),
+                scope: params.0.scope.clone(),
+                response: Arc::new(Mutex::new(Some(response_tx))),
+            })
+            .map_err(|e| McpError::internal_error(format!("Failed to send command: {e}"), None))?;
+
+        let result = tokio::time::timeout(Duration::from_secs(COMMAND_TIMEOUT_SECS), response_rx)
+            .await
+            .map_err(|_| McpError::internal_error("Timeout running find and replace", None))?
+            .map_err(|_| McpError::internal_error("Failed to run find and replace", None))?;
+
+        match result {
+            Ok(count) => Ok(CallToolResult::success(vec![Content::text(format!("{count} matches"))])),
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(e)])),
+        }
+    }
 }
 
 impl ServerHandler for IcyDrawMcpHandler {
@@ -244,4 +244,12 @@ pub enum McpCommand {
 
     /// Run a selection action
     AnsiSelectionAction { action: String, response: SenderType<Result<(), String>> },
+
+    /// Find (and optionally replace) cells, responds with the number of matches
+    AnsiFindReplace {
+        find: types::CellPatternInfo,
+        replace: Option<types::CellPatternInfo>,
+        scope: Option<String>,
+        response: SenderType<Result<usize, String>>,
+    },
 }
@@ -329,6 +329,38 @@ pub struct AnsiSelectionActionRequest {
     pub action: String,
 }
 
+/// Cell properties for find and replace, omitted fields match any cell (find) or are left unchanged (replace)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct CellPatternInfo {
+    /// Character as Unicode string (single char)
+    #[serde(default)]
+    pub ch: Option<String>,
+    /// Foreground color
+    #[serde(default)]
+    pub fg: Option<ColorInfo>,
+    /// Background color
+    #[serde(default)]
+    pub bg: Option<ColorInfo>,
+    /// Blink flag (high intensity background in ice color mode)
+    #[serde(default)]
+    pub blink: Option<bool>,
+    /// Font page
+    #[serde(default)]
+    pub font_page: Option<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct AnsiFindReplaceRequest {
+    /// Cells to search for
+    pub find: CellPatternInfo,
+    /// Properties written to every match, omit to only count the matches
+    #[serde(default)]
+    pub replace: Option<CellPatternInfo>,
+    /// "selection", "layer" (current layer) or "document", defaults to the selection if there is one, otherwise the current layer
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Response types
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -382,6 +414,21 @@ pub struct AnsiStatus {
     pub outline_style: usize,
     /// Mirror mode enabled
     pub mirror_mode: bool,
+    /// Symmetry mode and axis
+    pub symmetry: SymmetryInfo,
+}
+
+/// Symmetry settings of the editor
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SymmetryInfo {
+    /// Mirror mode ("none", "horizontal", "vertical" or "both")
+    pub mode: String,
+    /// Vertical mirror axis in half cells (`2 * column` is the line left of `column`), `None` for the layer center
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub axis_x: Option<i32>,
+    /// Horizontal mirror axis in half cells, `None` for the layer center
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub axis_y: Option<i32>,
 }
 
 /// Buffer dimensions and metadata
@@ -4,7 +4,7 @@
 //! The UI-free collaboration core lives in `icy_engine_edit` for unit testing.
 
 use crate::fl;
-use icy_engine_edit::collaboration::{ChatMessage, CollaborationCoreState, ConnectedDocument, ServerStatus, User};
+use icy_engine_edit::collaboration::{ChatMessage, ClientCommand, CollaborationCoreState, ConnectedDocument, ServerStatus, User};
 use icy_engine_edit::EditorUndoStack;
 use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -316,11 +316,15 @@ impl CollaborationState {
     /// Returns a Task that sends all collected commands to the server.
     pub fn sync_from_undo_stack(&mut self, undo_stack: &EditorUndoStack, caret_pos: (i32, i32), selecting: bool) -> Option<icy_ui::Task<()>> {
         // Skip if not connected
-        let client = self.client.as_ref()?;
-        let handle = client.handle().clone();
+        self.client.as_ref()?;
 
         let commands = self.core.sync_from_undo_stack(undo_stack, caret_pos, selecting);
+        self.send_commands(commands)
+    }
 
+    /// Send commands to the server, `None` if there is nothing to send or no connection
+    pub fn send_commands(&self, commands: Vec<ClientCommand>) -> Option<icy_ui::Task<()>> {
+        let handle = self.client.as_ref()?.handle().clone();
         if commands.is_empty() {
             return None;
         }
@@ -5,13 +5,15 @@
 //! - PSF files (.psf) - Linux console font format
 //! - Raw bitmap fonts (.fXX) - DOS bitmap font format
 //! - YAFF files (.yaff) - Yet Another Font Format (text-based)
+//! - BDF files (.bdf) - X11 bitmap font format with Unicode encodings
+//! - TrueType/OpenType files (.ttf, .otf) - pixel outlines, optionally with a bitmap strike
 
 mod image_export;
 
 use base64::{engine::general_purpose, Engine as _};
 use std::path::PathBuf;
 
-use icy_engine::BitFont;
+use icy_engine::{BitFont, FontCodepage, TtfExportOptions};
 use icy_engine_edit::bitfont::MAX_FONT_HEIGHT;
 use icy_engine_gui::ui::{
     browse_button, button_row, dialog_area, dialog_title, left_label_small, modal_container, primary_button, secondary_button, separator, Dialog, DialogAction,
@@ -19,7 +21,7 @@ use icy_engine_gui::ui::{
 };
 use icy_engine_gui::{settings::effect_box, ButtonType};
 use icy_ui::{
-    widget::{column, container, pick_list, row, text, text_input, Space},
+    widget::{checkbox, column, container, pick_list, row, text, text_input, Space},
     Alignment, Element, Length, Task,
 };
 
@@ -47,6 +49,12 @@ pub enum FontExportFormat {
     Raw,
     /// YAFF format (text-based)
     Yaff,
+    /// BDF format (X11, Unicode encodings)
+    Bdf,
+    /// TrueType font with pixel outlines
+    Ttf,
+    /// OpenType font with pixel outlines
+    Otf,
     /// ANSI DCS sequence (`CTerm` format, copies to clipboard)
     AnsiDcs,
     /// DOS COM executable (Fontraption Non-TSR format)
@@ -98,6 +106,9 @@ impl FontExportFormat {
             Self::Psf => "psf".to_string(),
             Self::Raw => format!("f{font_height:02}"),
             Self::Yaff => "yaff".to_string(),
+            Self::Bdf => "bdf".to_string(),
+            Self::Ttf => "ttf".to_string(),
+            Self::Otf => "otf".to_string(),
             Self::AnsiDcs => "ans".to_string(),
             Self::Com => "com".to_string(),
         }
@@ -111,6 +122,9 @@ impl FontExportFormat {
             Self::Psf => "PSF (Linux Console)",
             Self::Raw => "Raw Binary (.fXX)",
             Self::Yaff => "YAFF (Text-based)",
+            Self::Bdf => "BDF (X11)",
+            Self::Ttf => "TrueType (.ttf)",
+            Self::Otf => "OpenType (.otf)",
             Self::AnsiDcs => "ANSI DCS",
             Self::Com => "DOS COM Executable",
         }
@@ -118,7 +132,18 @@ impl FontExportFormat {
 
     /// All available export formats
     pub fn all() -> Vec<Self> {
-        vec![Self::Png, Self::Bmp, Self::Psf, Self::Raw, Self::Yaff, Self::AnsiDcs, Self::Com]
+        vec![
+            Self::Png,
+            Self::Bmp,
+            Self::Psf,
+            Self::Raw,
+            Self::Yaff,
+            Self::Bdf,
+            Self::Ttf,
+            Self::Otf,
+            Self::AnsiDcs,
+            Self::Com,
+        ]
     }
 }
 
@@ -135,6 +160,12 @@ pub enum FontExportMessage {
     SetFormat(FontExportFormat),
     /// COM subformat selection changed
     SetComFormat(ComExportFormat),
+    /// BDF/TTF/OTF: codepage used to map the slots to Unicode
+    SetCodepage(FontCodepage),
+    /// TTF/OTF: use a 9 pixel cell
+    SetNineDot(bool),
+    /// TTF/OTF: embed a bitmap strike
+    SetBitmapStrike(bool),
     /// Path text input changed
     SetPath(String),
     /// Browse for export location
@@ -155,6 +186,8 @@ pub struct FontExportDialog {
     pub format: FontExportFormat,
     /// Selected COM subformat (when format is Com)
     pub com_format: ComExportFormat,
+    /// TTF/OTF options
+    pub ttf_options: TtfExportOptions,
     /// Selected export path (if any)
     pub export_path: Option<PathBuf>,
     /// Error message (if any)
@@ -164,12 +197,16 @@ pub struct FontExportDialog {
 }
 
 impl FontExportDialog {
-    /// Create a new Font Export dialog
-    pub fn new(font: BitFont) -> Self {
+    /// Create a new Font Export dialog, `codepage` preselects the BDF/TTF/OTF codepage
+    pub fn new(font: BitFont, codepage: FontCodepage) -> Self {
         Self {
             font,
             format: FontExportFormat::Png,
             com_format: ComExportFormat::default(),
+            ttf_options: TtfExportOptions {
+                codepage,
+                ..Default::default()
+            },
             export_path: None,
             error: None,
             success: None,
@@ -208,6 +245,14 @@ impl FontExportDialog {
                 let yaff_string = libyaff::to_yaff_string(&yaff_font);
                 std::fs::write(path, yaff_string).map_err(|e| e.to_string())
             }
+            FontExportFormat::Bdf => {
+                let bytes = self.font.to_bdf_bytes(self.ttf_options.codepage);
+                std::fs::write(path, bytes).map_err(|e| e.to_string())
+            }
+            FontExportFormat::Ttf | FontExportFormat::Otf => {
+                let bytes = self.font.to_ttf_bytes(&self.ttf_options).map_err(|e| e.to_string())?;
+                std::fs::write(path, bytes).map_err(|e| e.to_string())
+            }
             FontExportFormat::AnsiDcs => {
                 let ansi_string = encode_font_as_ansi(&self.font, 0);
                 std::fs::write(path, ansi_string).map_err(|e| e.to_string())
@@ -239,6 +284,18 @@ impl FontExportDialog {
                 self.success = None;
                 Some(DialogAction::None)
             }
+            FontExportMessage::SetCodepage(codepage) => {
+                self.ttf_options.codepage = *codepage;
+                Some(DialogAction::None)
+            }
+            FontExportMessage::SetNineDot(nine_dot) => {
+                self.ttf_options.nine_dot = *nine_dot;
+                Some(DialogAction::None)
+            }
+            FontExportMessage::SetBitmapStrike(bitmap_strike) => {
+                self.ttf_options.bitmap_strike = *bitmap_strike;
+                Some(DialogAction::None)
+            }
             FontExportMessage::SetPath(path) => {
                 if path.is_empty() {
                     self.export_path = None;
@@ -341,6 +398,39 @@ impl Dialog<Message> for FontExportDialog {
             Space::new().height(0).into()
         };
 
+        // === BDF/TTF/OTF CODEPAGE ===
+        let codepage_label = left_label_small(fl!("font-export-codepage"));
+        let codepage_picker = pick_list(FontCodepage::ALL.as_slice(), Some(self.ttf_options.codepage), |c| {
+            msg(FontExportMessage::SetCodepage(c))
+        })
+        .width(Length::Fill);
+        let codepage_row = row![codepage_label, codepage_picker].spacing(DIALOG_SPACING).align_y(Alignment::Center);
+
+        // === TTF/OTF OPTIONS ===
+        let ttf_options_element: Element<'_, Message> = if matches!(self.format, FontExportFormat::Ttf | FontExportFormat::Otf) {
+            let nine_dot_checkbox = checkbox(self.ttf_options.nine_dot)
+                .on_toggle(|b| msg(FontExportMessage::SetNineDot(b)))
+                .size(16);
+            let strike_checkbox = checkbox(self.ttf_options.bitmap_strike)
+                .on_toggle(|b| msg(FontExportMessage::SetBitmapStrike(b)))
+                .size(16);
+            column![
+                codepage_row,
+                row![nine_dot_checkbox, text(fl!("font-export-nine-dot")).size(TEXT_SIZE_NORMAL)]
+                    .spacing(6)
+                    .align_y(Alignment::Center),
+                row![strike_checkbox, text(fl!("font-export-bitmap-strike")).size(TEXT_SIZE_NORMAL)]
+                    .spacing(6)
+                    .align_y(Alignment::Center),
+            ]
+            .spacing(4)
+            .into()
+        } else if self.format == FontExportFormat::Bdf {
+            codepage_row.into()
+        } else {
+            Space::new().height(0).into()
+        };
+
         // === FILE PATH ROW ===
         let path_label = left_label_small(fl!("font-export-path"));
         let path_text = self.export_path.as_ref().map(|p| p.display().to_string()).unwrap_or_default();
@@ -389,6 +479,7 @@ impl Dialog<Message> for FontExportDialog {
         let content_column = column![
             format_row,
             com_format_element,
+            ttf_options_element,
             file_row,
             Space::new().height(DIALOG_SPACING),
             preview_row,
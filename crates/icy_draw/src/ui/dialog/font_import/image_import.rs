@@ -2,10 +2,13 @@
 //!
 //! Converts a raster image (assumed to be a 16x16 grid of characters) to a `BitFont`.
 //! Optionally uses Floyd-Steinberg dithering via quantette for high-quality 2-color conversion.
+//! The same sheets can be cut into grayscale glyphs for the TDF font generator.
 
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use icy_engine::BitFont;
+use icy_engine_edit::charset::CoverageMap;
 use quantette::{deps::palette::Srgb, dither::FloydSteinberg, Image, PaletteSize, Pipeline};
 
 /// Import a font from an image file
@@ -128,3 +131,53 @@ pub fn import_font_from_image(path: &Path, font_width: i32, font_height: i32, us
 
     Ok(font)
 }
+
+/// Cut the TDF characters `!` to `~` out of a 16x16 character sheet as grayscale glyphs
+///
+/// Cells are scaled to `pixel_height` keeping their aspect ratio. Bright pixels are ink,
+/// sheets that are mostly bright are inverted so dark ink on paper works as well.
+pub fn image_sheet_coverage(path: &Path, pixel_height: usize) -> Result<BTreeMap<char, CoverageMap>, String> {
+    if pixel_height < 2 {
+        return Err(format!("Glyph height must be at least 2 pixels, got {pixel_height}"));
+    }
+
+    let img = image::open(path).map_err(|e| format!("Failed to load image: {e}"))?.to_luma8();
+    let (img_width, img_height) = (img.width() as usize, img.height() as usize);
+    let cell_width = img_width / 16;
+    let cell_height = img_height / 16;
+    if cell_width < 1 || cell_height < 1 {
+        return Err("Image too small (must be at least 16x16 pixels)".to_string());
+    }
+
+    let average = img.pixels().map(|p| u64::from(p.0[0])).sum::<u64>() / (img_width * img_height) as u64;
+    let invert = average > 127;
+    let pixel_width = (cell_width * pixel_height).div_ceil(cell_height).max(1);
+
+    let mut glyphs = BTreeMap::new();
+    for ch in '!'..='~' {
+        let code = ch as usize;
+        let src_x = (code % 16) * cell_width;
+        let src_y = (code / 16) * cell_height;
+
+        // Box filter every target pixel over its source rectangle
+        let mut map = CoverageMap::new(pixel_width, pixel_height);
+        for y in 0..pixel_height {
+            let y0 = src_y + y * cell_height / pixel_height;
+            let y1 = (src_y + (y + 1) * cell_height / pixel_height).max(y0 + 1);
+            for x in 0..pixel_width {
+                let x0 = src_x + x * cell_width / pixel_width;
+                let x1 = (src_x + (x + 1) * cell_width / pixel_width).max(x0 + 1);
+                let mut sum = 0u32;
+                for sy in y0..y1 {
+                    for sx in x0..x1 {
+                        sum += u32::from(img.get_pixel(sx as u32, sy as u32).0[0]);
+                    }
+                }
+                let luma = (sum / ((y1 - y0) * (x1 - x0)) as u32) as u8;
+                map.set(x, y, if invert { 255 - luma } else { luma });
+            }
+        }
+        glyphs.insert(ch, map);
+    }
+    Ok(glyphs)
+}
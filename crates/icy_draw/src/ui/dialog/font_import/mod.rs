@@ -2,19 +2,24 @@
 //!
 //! Provides a dialog for importing fonts from various file formats:
 //! - Native font files (.yaff, .psf, .f08, etc.) - direct import
+//! - Unicode bitmap fonts (.bdf, .pcf) - glyphs mapped to slots through a codepage
 //! - XB files - import font from `XBin` with font selection (1 or 2 fonts)
+//! - Windows raster fonts (.fon, .fnt) and Amiga diskfonts (.font) - with size selection
 //! - Image files - convert raster image to bitmap font
 //! - TTF/OTF files - rasterize TrueType/OpenType fonts to bitmap
 
 mod canvas;
 mod image_import;
+mod raster_import;
 mod ttf_import;
 
 pub use canvas::*;
+pub use image_import::image_sheet_coverage;
+pub use ttf_import::rasterize_ttf_coverage;
 
 use std::path::PathBuf;
 
-use icy_engine::BitFont;
+use icy_engine::{BitFont, FontCodepage};
 use icy_engine_edit::bitfont::MAX_FONT_HEIGHT;
 use icy_engine_gui::ui::{
     browse_button, button_row, dialog_area, dialog_title, left_label_small, modal_container, primary_button, secondary_button, separator, Dialog, DialogAction,
@@ -40,8 +45,12 @@ fn msg(m: FontImportMessage) -> Message {
 pub enum FontSourceType {
     /// Native font file (.yaff, .psf, .fXX)
     NativeFont,
+    /// BDF/PCF font, glyphs are picked by codepage
+    UnicodeFont,
     /// XB file with embedded font(s)
     XBin { has_second_font: bool },
+    /// Windows raster font or Amiga diskfont with one or more sizes
+    RasterFont,
     /// Image file to convert to font
     Image,
 }
@@ -57,6 +66,10 @@ pub enum FontImportMessage {
     FileSelected(Option<PathBuf>),
     /// For XB files: select which font to import (0 or 1)
     SelectXBFont(usize),
+    /// For raster fonts: select which size to import
+    SelectSize(usize),
+    /// For BDF/PCF files: codepage used to map glyphs to slots
+    SetCodepage(FontCodepage),
     /// For image import: set font width
     SetFontWidth(String),
     /// For image import: set font height
@@ -81,6 +94,12 @@ pub struct FontImportDialog {
     pub xb_fonts: Vec<BitFont>,
     /// For XB files: selected font index
     pub xb_selected_font: usize,
+    /// For BDF/PCF files: codepage used to map glyphs to slots
+    pub codepage: FontCodepage,
+    /// For raster fonts: available sizes
+    pub sizes: Vec<BitFont>,
+    /// For raster fonts: selected size index
+    pub selected_size: usize,
     /// For image import: target font width
     pub image_width: String,
     /// For image import: target font height
@@ -106,6 +125,9 @@ impl FontImportDialog {
             preview_font: None,
             xb_fonts: Vec::new(),
             xb_selected_font: 0,
+            codepage: FontCodepage::default(),
+            sizes: Vec::new(),
+            selected_size: 0,
             image_width: "8".to_string(),
             image_height: "16".to_string(),
             use_dithering: true,
@@ -126,8 +148,9 @@ impl FontImportDialog {
     /// Check if the dialog is ready for import
     fn can_import(&self) -> bool {
         match &self.source_type {
-            Some(FontSourceType::NativeFont) => self.preview_font.is_some(),
+            Some(FontSourceType::NativeFont | FontSourceType::UnicodeFont) => self.preview_font.is_some(),
             Some(FontSourceType::XBin { .. }) => !self.xb_fonts.is_empty(),
+            Some(FontSourceType::RasterFont) => self.preview_font.is_some(),
             Some(FontSourceType::Image) => self.parsed_font_width().is_some() && self.parsed_font_height().is_some() && self.preview_font.is_some(),
             None => false,
         }
@@ -139,6 +162,8 @@ impl FontImportDialog {
         self.preview_font = None;
         self.xb_fonts.clear();
         self.xb_selected_font = 0;
+        self.sizes.clear();
+        self.selected_size = 0;
 
         let ext = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).unwrap_or_default();
 
@@ -146,8 +171,15 @@ impl FontImportDialog {
         if is_native_font_extension(&ext) {
             self.source_type = Some(FontSourceType::NativeFont);
             self.load_native_font(path);
+        } else if is_unicode_font_extension(&ext) {
+            self.source_type = Some(FontSourceType::UnicodeFont);
+            self.load_unicode_font(path);
         } else if ext == "xb" {
             self.load_xb_file(path);
+        } else if ext == "fon" || ext == "fnt" {
+            self.load_raster_fonts(raster_import::import_windows_fonts(path));
+        } else if ext == "font" {
+            self.load_raster_fonts(raster_import::import_amiga_fonts(path));
         } else if ext == "com" {
             self.source_type = Some(FontSourceType::NativeFont);
             self.load_com_file(path);
@@ -158,6 +190,9 @@ impl FontImportDialog {
             self.source_type = Some(FontSourceType::Image);
             self.auto_detect_image_dimensions(path);
             self.load_image_file(path);
+        } else if raster_import::is_amiga_hunk_file(path) {
+            // Amiga font size files like `topaz/8` have no extension
+            self.load_raster_fonts(raster_import::import_amiga_fonts(path));
         } else {
             self.error = Some(format!("Unsupported file type: .{ext}"));
             self.source_type = None;
@@ -184,6 +219,32 @@ impl FontImportDialog {
         }
     }
 
+    /// Load a BDF or PCF font, mapping its glyphs through the selected codepage
+    fn load_unicode_font(&mut self, path: &std::path::Path) {
+        match std::fs::read(path) {
+            Ok(data) => {
+                let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("Font").to_string();
+                let ext = path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).unwrap_or_default();
+                let result = if ext == "pcf" {
+                    BitFont::from_pcf(name, &data, self.codepage)
+                } else {
+                    BitFont::from_bdf(name, &data, self.codepage)
+                };
+                match result {
+                    Ok(font) => {
+                        self.preview_font = Some(font);
+                    }
+                    Err(e) => {
+                        self.error = Some(format!("Failed to parse font: {e}"));
+                    }
+                }
+            }
+            Err(e) => {
+                self.error = Some(format!("Failed to read file: {e}"));
+            }
+        }
+    }
+
     /// Load a DOS COM file and extract font
     ///
     /// Supports multiple COM font formats:
@@ -251,6 +312,21 @@ impl FontImportDialog {
         }
     }
 
+    /// Show the sizes of a Windows raster font or Amiga diskfont
+    fn load_raster_fonts(&mut self, result: Result<Vec<BitFont>, String>) {
+        match result {
+            Ok(fonts) => {
+                self.source_type = Some(FontSourceType::RasterFont);
+                self.preview_font = fonts.first().cloned();
+                self.sizes = fonts;
+            }
+            Err(e) => {
+                self.error = Some(e);
+                self.source_type = None;
+            }
+        }
+    }
+
     /// Load a TTF/OTF file and rasterize to bitmap font
     fn load_ttf_file(&mut self, path: &std::path::Path) {
         let width = self.parsed_font_width().unwrap_or(8);
@@ -332,7 +408,9 @@ impl FontImportDialog {
                                 &[
                                     // Native fonts
                                     "yaff", "psf", "psfu", "f08", "f14", "f16", "f19", "f06", "f07", "f09", "f10", "f11", "f12", "f13", "f15", "f17", "f18",
-                                    "f20", "f22", "f24", "f26", "f28", "f30", "f32", // TrueType/OpenType
+                                    "f20", "f22", "f24", "f26", "f28", "f30", "f32", // BDF/PCF
+                                    "bdf", "pcf", // Windows/Amiga raster fonts
+                                    "fon", "fnt", "font", // TrueType/OpenType
                                     "ttf", "otf", "ttc", "otc", // XBin
                                     "xb",  // DOS COM
                                     "com", // Images
@@ -340,6 +418,9 @@ impl FontImportDialog {
                                 ],
                             )
                             .add_filter("Font Files", &["yaff", "psf", "psfu", "f08", "f14", "f16", "f19"])
+                            .add_filter("BDF/PCF Fonts", &["bdf", "pcf"])
+                            .add_filter("Windows Raster Fonts", &["fon", "fnt"])
+                            .add_filter("Amiga Fonts", &["font"])
                             .add_filter("TrueType/OpenType", &["ttf", "otf", "ttc", "otc"])
                             .add_filter("XBin Files", &["xb"])
                             .add_filter("DOS COM Fonts", &["com"])
@@ -367,6 +448,22 @@ impl FontImportDialog {
                 }
                 Some(DialogAction::None)
             }
+            FontImportMessage::SelectSize(index) => {
+                if let Some(font) = self.sizes.get(*index) {
+                    self.selected_size = *index;
+                    self.preview_font = Some(font.clone());
+                }
+                Some(DialogAction::None)
+            }
+            FontImportMessage::SetCodepage(codepage) => {
+                self.codepage = *codepage;
+                if let Some(FontSourceType::UnicodeFont) = &self.source_type {
+                    self.error = None;
+                    self.preview_font = None;
+                    self.load_unicode_font(&PathBuf::from(&self.file_path));
+                }
+                Some(DialogAction::None)
+            }
             FontImportMessage::SetFontWidth(w) => {
                 self.image_width = w.clone();
                 self.reload_image();
@@ -530,6 +627,18 @@ impl FontImportDialog {
                     Space::new().into()
                 }
             }
+            Some(FontSourceType::UnicodeFont) => {
+                let picker = pick_list(FontCodepage::ALL.as_slice(), Some(self.codepage), |cp| msg(FontImportMessage::SetCodepage(cp))).width(Length::Fill);
+
+                column![
+                    text(fl!("font-import-unicode-info")).size(TEXT_SIZE_SMALL),
+                    Space::new().height(8),
+                    left_label_small(fl!("font-import-codepage")),
+                    picker,
+                ]
+                .spacing(4)
+                .into()
+            }
             Some(FontSourceType::XBin { has_second_font }) => {
                 // XB font selector
                 let options: Vec<String> = if *has_second_font {
@@ -555,6 +664,27 @@ impl FontImportDialog {
                 .spacing(4)
                 .into()
             }
+            Some(FontSourceType::RasterFont) => {
+                let options: Vec<String> = self
+                    .sizes
+                    .iter()
+                    .map(|font| format!("{} ({}×{})", font.name(), font.size().width, font.size().height))
+                    .collect();
+                let selected = options.get(self.selected_size).cloned();
+                let picker = pick_list(options.clone(), selected, move |s: String| {
+                    msg(FontImportMessage::SelectSize(options.iter().position(|o| *o == s).unwrap_or(0)))
+                })
+                .width(Length::Fill);
+
+                column![
+                    text(fl!("font-import-raster-info")).size(TEXT_SIZE_SMALL),
+                    Space::new().height(8),
+                    left_label_small(fl!("font-import-select-size")),
+                    picker,
+                ]
+                .spacing(4)
+                .into()
+            }
             Some(FontSourceType::Image) => {
                 // Image import options: width and height
                 let width_valid = self.parsed_font_width().is_some();
@@ -613,6 +743,11 @@ impl FontImportDialog {
     }
 }
 
+/// Check if extension is a Unicode bitmap font that needs a codepage
+fn is_unicode_font_extension(ext: &str) -> bool {
+    matches!(ext, "bdf" | "pcf")
+}
+
 /// Check if extension is a native font format
 fn is_native_font_extension(ext: &str) -> bool {
     matches!(
@@ -0,0 +1,59 @@
+//! Windows raster font and Amiga diskfont to `BitFont` conversion
+//!
+//! Both containers hold several sizes, every size becomes its own `BitFont` and the
+//! dialog lets the user pick one.
+
+use std::path::Path;
+
+use icy_engine::{read_amiga_font_contents, BitFont};
+
+/// Import all sizes of a Windows `.fon` or `.fnt` file
+pub fn import_windows_fonts(path: &Path) -> Result<Vec<BitFont>, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read file: {e}"))?;
+    BitFont::from_windows_font(&data).map_err(|e| format!("Failed to parse font: {e}"))
+}
+
+/// Import an Amiga diskfont
+///
+/// `.font` contents files load every size they list from the directory next to them,
+/// any other file is read as a single size file like `topaz/8`.
+pub fn import_amiga_fonts(path: &Path) -> Result<Vec<BitFont>, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read file: {e}"))?;
+    if !is_amiga_contents(path) {
+        return BitFont::from_amiga_font(&data)
+            .map(|font| vec![font])
+            .map_err(|e| format!("Failed to parse font: {e}"));
+    }
+
+    let entries = read_amiga_font_contents(&data).map_err(|e| format!("Failed to parse font: {e}"))?;
+    let fonts_dir = path.parent().unwrap_or(Path::new("."));
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("Font");
+    let mut fonts = Vec::new();
+    for entry in &entries {
+        // Entries name the size file relative to FONTS:, fall back to the size directory next to the contents file
+        let candidates = [fonts_dir.join(&entry.file_name), fonts_dir.join(stem).join(entry.y_size.to_string())];
+        let Some(size_data) = candidates.iter().find_map(|p| std::fs::read(p).ok()) else {
+            continue;
+        };
+        if let Ok(mut font) = BitFont::from_amiga_font(&size_data) {
+            font.set_name(&format!("{stem} {}", entry.y_size));
+            fonts.push(font);
+        }
+    }
+    if fonts.is_empty() {
+        return Err(format!("No font sizes found in {}", fonts_dir.join(stem).display()));
+    }
+    Ok(fonts)
+}
+
+fn is_amiga_contents(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("font"))
+}
+
+/// Check if the file starts with an Amiga hunk header, size files have no extension
+pub fn is_amiga_hunk_file(path: &Path) -> bool {
+    use std::io::Read;
+
+    let mut magic = [0u8; 4];
+    std::fs::File::open(path).and_then(|mut f| f.read_exact(&mut magic)).is_ok() && magic == [0, 0, 0x03, 0xF3]
+}
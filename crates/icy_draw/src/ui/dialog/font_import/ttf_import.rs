@@ -8,11 +8,15 @@
 //! - Proper em-square scaling to fill the target cell
 //! - Sub-pixel positioning trials: tests multiple x/y offsets
 //! - Adaptive thresholding with stroke preservation
+//!
+//! Also renders grayscale glyphs at their natural width for the TDF font generator.
 
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use codepages::tables::CP437_TO_UNICODE_NO_CTRL_CODES;
 use icy_engine::BitFont;
+use icy_engine_edit::charset::CoverageMap;
 
 /// Import a font from a TTF/OTF file
 ///
@@ -73,6 +77,46 @@ pub fn import_font_from_ttf(path: &Path, font_width: i32, font_height: i32) -> R
     Ok(BitFont::create_8(&font_name, font_width as u8, font_height as u8, &all_data))
 }
 
+/// Render the TDF characters `!` to `~` as grayscale glyphs `pixel_height` pixels high
+///
+/// Glyphs keep their advance width, all of them share the same baseline.
+pub fn rasterize_ttf_coverage(path: &Path, pixel_height: usize) -> Result<BTreeMap<char, CoverageMap>, String> {
+    if !(2..=128).contains(&pixel_height) {
+        return Err(format!("Glyph height must be 2-128 pixels, got {pixel_height}"));
+    }
+
+    let font_data = std::fs::read(path).map_err(|e| format!("Failed to read font file: {e}"))?;
+    let font = fontdue::Font::from_bytes(font_data, fontdue::FontSettings::default()).map_err(|e| format!("Failed to parse font: {e}"))?;
+
+    let px_size = calculate_px_size_for_cell(&font, pixel_height as i32);
+    let descender_space = (pixel_height as f32 * 0.125).round().max(1.0);
+    let baseline_y = pixel_height as f32 - descender_space;
+
+    let mut glyphs = BTreeMap::new();
+    for ch in '!'..='~' {
+        let (metrics, bitmap) = font.rasterize(ch, px_size);
+        if bitmap.is_empty() {
+            continue;
+        }
+        let x_offset = metrics.xmin.max(0) as usize;
+        let width = (metrics.advance_width.round() as usize).max(x_offset + metrics.width);
+        let top = (baseline_y - metrics.ymin as f32 - metrics.height as f32).round() as i32;
+
+        let mut map = CoverageMap::new(width, pixel_height);
+        for gy in 0..metrics.height {
+            let y = gy as i32 + top;
+            if y < 0 {
+                continue;
+            }
+            for gx in 0..metrics.width {
+                map.set(x_offset + gx, y as usize, bitmap[gy * metrics.width + gx]);
+            }
+        }
+        glyphs.insert(ch, map);
+    }
+    Ok(glyphs)
+}
+
 /// Calculate the pixel size needed to properly fill the cell
 fn calculate_px_size_for_cell(font: &fontdue::Font, target_height: i32) -> f32 {
     // Start with target height as px_size and measure actual glyph dimensions
@@ -34,9 +34,13 @@ fn brush_mode_label(mode: BrushPrimaryMode, paint_char: char) -> String {
         BrushPrimaryMode::Replace => crate::fl!("brush-hint-replace"),
         BrushPrimaryMode::Blink => crate::fl!("brush-hint-blink"),
         BrushPrimaryMode::Colorize => crate::fl!("brush-hint-colorize"),
+        BrushPrimaryMode::BoxLine => crate::fl!("brush-hint-box_line"),
     }
 }
 
+/// Highlight color of the find and replace matches
+const FIND_MATCH_COLOR: (u8, u8, u8) = (255, 200, 0);
+
 /// Core ANSI editor logic/state (tools, dispatching, canvas, etc).
 ///
 /// This is the lower-level editor that handles tools, canvas, and buffer operations.
@@ -794,6 +798,34 @@ impl AnsiEditorCore {
         self.canvas.set_tool_overlay_mask(None, None);
     }
 
+    /// Frames the text box that is being edited
+    pub(super) fn update_text_box_overlay(&mut self) {
+        let Some(text_box) = self.current_tool.as_any().downcast_ref::<tools::TextBoxTool>() else {
+            return;
+        };
+        let (rect, size) = {
+            let mut screen = self.screen.lock();
+            let size = screen.font_dimensions();
+            let rect = screen.as_any_mut().downcast_ref::<EditState>().and_then(|state| text_box.box_rect(state));
+            (rect, size)
+        };
+        match rect {
+            Some(rect) => {
+                let (mask, rect) = tools::TextBoxTool::overlay_mask(size.width as f32, size.height as f32, rect);
+                self.canvas.set_tool_overlay_mask(mask, rect);
+            }
+            None => self.clear_tool_overlay(),
+        }
+    }
+
+    /// Highlights the matches of the find and replace dialog, `cells` are in document coordinates
+    pub(super) fn show_find_matches(&mut self, cells: &[icy_engine::Position]) {
+        let size = self.screen.lock().font_dimensions();
+        let cells: Vec<icy_engine::Position> = cells.iter().copied().filter(|p| p.x >= 0 && p.y >= 0).collect();
+        let (mask, rect) = tools::ShapeTool::overlay_mask_for_cells(size.width as f32, size.height as f32, &cells, FIND_MATCH_COLOR);
+        self.canvas.set_tool_overlay_mask(mask, rect);
+    }
+
     fn task_none_with_markers_update(&mut self) -> Task<AnsiEditorCoreMessage> {
         self.update_markers();
         Task::none()
@@ -832,8 +864,14 @@ impl AnsiEditorCore {
         let brush_settings = shape.brush_settings();
 
         // Get font dimensions and compute preview color using shared helper
-        let (font_width, font_height, paint_color) = {
-            let screen = self.screen.lock();
+        let (font_width, font_height, paint_color, symmetry, layer_offset) = {
+            let mut screen = self.screen.lock();
+            let edit_state = screen.as_any_mut().downcast_ref::<EditState>();
+            let layer_offset = edit_state
+                .and_then(|state| state.get_cur_layer().map(icy_engine_edit::Layer::offset))
+                .unwrap_or_default();
+            // Symmetry with the axes in layer local half cells
+            let symmetry = edit_state.and_then(|state| Some((state.symmetry(), state.symmetry_axis()?)).filter(|(s, _)| s.is_enabled()));
             let size = screen.font_dimensions();
             let (fw, fh) = (size.width as f32, size.height as f32);
 
@@ -843,7 +881,7 @@ impl AnsiEditorCore {
             let palette = screen.palette();
             let rgb = tools::paint::compute_preview_color(&brush_settings, caret_fg, caret_bg, palette, snapshot.draw_button);
 
-            (fw, fh, rgb)
+            (fw, fh, rgb, symmetry, layer_offset)
         };
 
         // Get the current tool variant and brush settings from the shape tool
@@ -856,9 +894,23 @@ impl AnsiEditorCore {
                 self.clear_tool_overlay();
                 return;
             };
-            tools::ShapeTool::overlay_mask_for_drag_half_block(tool, font_width, font_height, start_hb, end_hb, paint_color)
+            // The overlay is drawn in document space, Y has 2x resolution
+            let hb_offset = icy_engine::Position::new(layer_offset.x, 2 * layer_offset.y);
+            let symmetry = symmetry.map(|(s, axis)| (s, icy_engine::Position::new(axis.x, 2 * axis.y) + hb_offset + hb_offset));
+            tools::ShapeTool::overlay_mask_for_drag_half_block(tool, font_width, font_height, start_hb + hb_offset, end_hb + hb_offset, paint_color, symmetry)
         } else {
-            tools::ShapeTool::overlay_mask_for_drag(tool, font_width, font_height, snapshot.start_pos, snapshot.current_pos, paint_color)
+            let symmetry = symmetry.map(|(s, axis)| (s, axis + layer_offset + layer_offset));
+            let box_lines = brush_settings.primary == BrushPrimaryMode::BoxLine;
+            tools::ShapeTool::overlay_mask_for_drag(
+                tool,
+                font_width,
+                font_height,
+                snapshot.start_pos,
+                snapshot.current_pos,
+                paint_color,
+                symmetry,
+                box_lines,
+            )
         };
         self.canvas.set_tool_overlay_mask(mask, rect);
     }
@@ -974,6 +1026,7 @@ impl AnsiEditorCore {
             selected_tool,
             tool_state_blob: Vec::new(),
             outline_style: edit_state.get_outline_style(),
+            // Mode and axis are stored in the tool session blob, this flag is kept for older readers
             mirror_mode: edit_state.get_mirror_mode(),
             current_tag: edit_state.get_current_tag().unwrap_or(0),
             layer_visibility,
@@ -1027,10 +1080,16 @@ impl AnsiEditorCore {
 
             // Get buffer and save with appropriate options for the format
             let buffer = edit_state.get_buffer();
-            let options = match &format {
+            let mut options = match &format {
                 FileFormat::IcyDraw => icy_engine::SaveOptions::icy_draw(),
                 _ => icy_engine::SaveOptions::default(),
             };
+            // Display codes are written in the syntax of the selected BBS profile
+            if buffer.tags.iter().any(|tag| tag.tag_role == icy_engine::TagRole::Displaycode) {
+                let selected_taglist = self.options.read().selected_taglist.clone();
+                let taglist = crate::util::load_taglist(&selected_taglist, crate::Settings::taglists_dir().as_deref());
+                options.display_codes = Some(taglist.display_code_profile());
+            }
             let bytes = format.to_bytes(buffer, &options).map_err(|e| e.to_string())?;
 
             std::fs::write(path, bytes).map_err(|e| e.to_string())?;
@@ -1042,22 +1101,21 @@ impl AnsiEditorCore {
         }
     }
 
-    /// Get the current mirror mode state
-    pub fn get_mirror_mode(&self) -> bool {
+    /// Get the current symmetry settings
+    pub fn get_symmetry(&self) -> icy_engine_edit::Symmetry {
         let mut screen = self.screen.lock();
         if let Some(state) = screen.as_any_mut().downcast_ref::<EditState>() {
-            state.get_mirror_mode()
+            state.symmetry()
         } else {
-            false
+            icy_engine_edit::Symmetry::default()
         }
     }
 
-    /// Toggle mirror mode
-    pub fn toggle_mirror_mode(&mut self) {
+    fn update_symmetry(&mut self, f: impl FnOnce(&EditState, icy_engine_edit::Symmetry) -> icy_engine_edit::Symmetry) {
         let mut screen = self.screen.lock();
         if let Some(state) = screen.as_any_mut().downcast_mut::<EditState>() {
-            let current = state.get_mirror_mode();
-            state.set_mirror_mode(!current);
+            let symmetry = f(state, state.symmetry());
+            state.set_symmetry(symmetry);
         }
     }
 
@@ -1191,7 +1249,7 @@ impl AnsiEditorCore {
                         screen_guard.as_any_mut().downcast_mut::<EditState>(),
                         self.current_tool.as_any_mut().downcast_mut::<tools::TagTool>(),
                     ) {
-                        tag_tool.state_mut().handle_list_dialog_message(state, msg)
+                        tag_tool.state_mut().handle_list_dialog_message(state, msg, Some(&self.options))
                     } else {
                         tools::ToolResult::None
                     }
@@ -1441,6 +1499,9 @@ impl AnsiEditorCore {
                         }
                     }
                 }
+                if self.current_tool.id() == tools::ToolId::TextBox && !matches!(processed, tools::ToolResult::None) {
+                    self.update_text_box_overlay();
+                }
 
                 Task::none()
             }
@@ -1573,6 +1634,30 @@ impl AnsiEditorCore {
                 }
                 Task::none()
             }
+            AnsiEditorCoreMessage::GroupLayer(idx) => {
+                let result = self.with_edit_state(|state| state.group_layer(idx));
+                match result {
+                    Ok(()) => self.is_modified = true,
+                    Err(e) => log::error!("GroupLayer failed: {e}"),
+                }
+                Task::none()
+            }
+            AnsiEditorCoreMessage::UngroupLayer(idx) => {
+                let result = self.with_edit_state(|state| state.ungroup_layer(idx));
+                match result {
+                    Ok(()) => self.is_modified = true,
+                    Err(e) => log::error!("UngroupLayer failed: {e}"),
+                }
+                Task::none()
+            }
+            AnsiEditorCoreMessage::ToggleGroupCollapsed(idx) => {
+                let result = self.with_edit_state(|state| state.toggle_group_collapsed(idx));
+                match result {
+                    Ok(()) => self.is_modified = true,
+                    Err(e) => log::error!("ToggleGroupCollapsed failed: {e}"),
+                }
+                Task::none()
+            }
             AnsiEditorCoreMessage::ScrollViewport(dx, dy) => {
                 self.canvas.scroll_by(dx, dy);
                 Task::none()
@@ -1627,8 +1712,19 @@ impl AnsiEditorCore {
                 self.options.read().store_persistent();
                 self.task_none_with_markers_update()
             }
-            AnsiEditorCoreMessage::ToggleMirrorMode => {
-                self.toggle_mirror_mode();
+            AnsiEditorCoreMessage::SetSymmetryMode(mode) => {
+                self.update_symmetry(|_, symmetry| icy_engine_edit::Symmetry { mode, ..symmetry });
+                Task::none()
+            }
+            AnsiEditorCoreMessage::SetSymmetryAxisToCaret => {
+                self.update_symmetry(|state, symmetry| {
+                    let offset = state.get_cur_layer().map(icy_engine_edit::Layer::offset).unwrap_or_default();
+                    symmetry.with_axis_at_cell(state.get_caret().position() + offset)
+                });
+                Task::none()
+            }
+            AnsiEditorCoreMessage::CenterSymmetryAxis => {
+                self.update_symmetry(|_, symmetry| icy_engine_edit::Symmetry { axis: None, ..symmetry });
                 Task::none()
             }
 
@@ -1838,6 +1934,30 @@ impl AnsiEditorCore {
                 });
                 Task::none()
             }
+            AnsiEditorCoreMessage::RotateCw => {
+                self.with_edit_state(|state| {
+                    if let Err(e) = state.rotate_cw() {
+                        log::error!("RotateCw failed: {e}");
+                    }
+                });
+                Task::none()
+            }
+            AnsiEditorCoreMessage::RotateCcw => {
+                self.with_edit_state(|state| {
+                    if let Err(e) = state.rotate_ccw() {
+                        log::error!("RotateCcw failed: {e}");
+                    }
+                });
+                Task::none()
+            }
+            AnsiEditorCoreMessage::Rotate180 => {
+                self.with_edit_state(|state| {
+                    if let Err(e) = state.rotate_180() {
+                        log::error!("Rotate180 failed: {e}");
+                    }
+                });
+                Task::none()
+            }
             // ═══════════════════════════════════════════════════════════════════════════
             // Color operations
             // ═══════════════════════════════════════════════════════════════════════════
@@ -2141,9 +2261,10 @@ impl AnsiEditorCore {
                         let screen = Arc::clone(&self.screen);
                         let mut screen_guard = screen.lock();
                         if let Some(state) = screen_guard.as_any_mut().downcast_mut::<EditState>() {
-                            let result = tag_tool
-                                .state_mut()
-                                .handle_list_dialog_message(state, dialog::tag_list::TagListDialogMessage::Close);
+                            let result =
+                                tag_tool
+                                    .state_mut()
+                                    .handle_list_dialog_message(state, dialog::tag_list::TagListDialogMessage::Close, Some(&self.options));
                             let _ = self.process_tool_result(result);
                             self.update_tag_overlays();
                         }
@@ -2186,6 +2307,9 @@ impl AnsiEditorCore {
                             self.update_selection_mask_display();
                         }
                     }
+                    tools::ToolId::TextBox if !matches!(result, tools::ToolResult::None) => {
+                        self.update_text_box_overlay();
+                    }
                     tools::ToolId::Tag if !matches!(result, tools::ToolResult::None) => {
                         self.update_tag_overlays();
                         if let Some(tag_tool) = self.current_tool.as_any().downcast_ref::<tools::TagTool>() {
@@ -2239,6 +2363,9 @@ impl AnsiEditorCore {
                         tools::ToolId::Tag => {
                             self.update_tag_overlays();
                         }
+                        tools::ToolId::TextBox => {
+                            self.update_text_box_overlay();
+                        }
                         _ => {}
                     }
                 }
@@ -2277,6 +2404,9 @@ impl AnsiEditorCore {
                                 // Clear shape preview overlay after release
                                 self.clear_tool_overlay();
                             }
+                            tools::ToolId::TextBox => {
+                                self.update_text_box_overlay();
+                            }
                             _ => {}
                         }
 
@@ -2344,6 +2474,9 @@ impl AnsiEditorCore {
                                 // Update shape preview overlay during drag
                                 self.update_shape_preview();
                             }
+                            tools::ToolId::TextBox => {
+                                self.update_text_box_overlay();
+                            }
                             _ => {}
                         }
 
@@ -2575,7 +2708,9 @@ impl AnsiEditorCore {
             ToolId::EllipseFilled => crate::fl!("tool-hint-filled_ellipse", brush = mode),
             ToolId::Pipette => crate::fl!("tool-hint-pipette"),
             ToolId::Fill => crate::fl!("tool-hint-fill", brush = mode),
+            ToolId::Gradient => crate::fl!("tool-hint-gradient"),
             ToolId::Font => crate::fl!("tool-hint-font"),
+            ToolId::TextBox => crate::fl!("tool-hint-text-box"),
             ToolId::Tag => crate::fl!("tool-hint-tag"),
             ToolId::Paste => crate::fl!("tool-hint-paste"),
         };
@@ -2679,6 +2814,24 @@ impl AnsiEditorCore {
         // Cancels any in-progress shape preview/drag when switching tools.
         let _ = self.cancel_shape_drag();
 
+        // An open text box is written to the layer before its tool goes away.
+        if let Some(text_box) = self.current_tool.as_any_mut().downcast_mut::<tools::TextBoxTool>() {
+            let committed = {
+                let mut screen = self.screen.lock();
+                match screen.as_any_mut().downcast_mut::<EditState>().map(|state| text_box.commit(state)) {
+                    Some(Ok(committed)) => committed,
+                    Some(Err(e)) => {
+                        log::warn!("Text box commit failed: {e}");
+                        false
+                    }
+                    None => false,
+                }
+            };
+            if committed {
+                let _ = self.process_tool_result(tools::ToolResult::Commit("Text box".to_string()));
+            }
+        }
+
         let mut is_visble = matches!(tool, tools::ToolId::Click | tools::ToolId::Font);
         is_visble &= self.with_edit_state(|state: &mut EditState| {
             state.set_caret_visible(is_visble && state.selection().is_none());
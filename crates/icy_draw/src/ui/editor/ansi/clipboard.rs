@@ -7,7 +7,7 @@ use icy_engine_edit::EditState;
 use icy_ui::Task;
 
 use super::ansi_editor::AnsiEditorCore;
-use super::SelectionDrag;
+use super::{tools, SelectionDrag};
 
 impl AnsiEditorCore {
     /// Check if cut operation is available (selection exists)
@@ -237,11 +237,27 @@ impl AnsiEditorCore {
     }
 
     /// Paste plain text
+    ///
+    /// With an open text box the text goes into the box instead of a floating layer.
     pub fn paste_text(&mut self, text: &str) -> Result<(), String> {
         if self.is_paste_mode() {
             return Ok(());
         }
 
+        if let Some(text_box) = self.current_tool.as_any_mut().downcast_mut::<tools::TextBoxTool>() {
+            let pasted = {
+                let mut screen = self.screen.lock();
+                screen
+                    .as_any_mut()
+                    .downcast_mut::<EditState>()
+                    .is_some_and(|state| text_box.paste_text(state, text))
+            };
+            if pasted {
+                self.update_text_box_overlay();
+                return Ok(());
+            }
+        }
+
         let previous_tool = self.current_tool.id();
 
         let mut screen_guard = self.screen.lock();
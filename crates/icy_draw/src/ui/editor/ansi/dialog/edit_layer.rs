@@ -34,6 +34,11 @@ impl std::fmt::Display for ModeOption {
             Mode::Normal => write!(f, "Normal"),
             Mode::Chars => write!(f, "Chars only"),
             Mode::Attributes => write!(f, "Attributes only"),
+            Mode::Foreground => write!(f, "Foreground only"),
+            Mode::Background => write!(f, "Background only"),
+            Mode::Darken => write!(f, "Darken"),
+            Mode::Lighten => write!(f, "Lighten"),
+            Mode::Mask => write!(f, "Mask"),
         }
     }
 }
@@ -280,7 +285,16 @@ impl Dialog<Message> for EditLayerDialog {
         .align_y(Alignment::Center);
 
         // Mode picker with wrapper type
-        let mode_options = vec![ModeOption(Mode::Normal), ModeOption(Mode::Chars), ModeOption(Mode::Attributes)];
+        let mode_options = vec![
+            ModeOption(Mode::Normal),
+            ModeOption(Mode::Chars),
+            ModeOption(Mode::Attributes),
+            ModeOption(Mode::Foreground),
+            ModeOption(Mode::Background),
+            ModeOption(Mode::Darken),
+            ModeOption(Mode::Lighten),
+            ModeOption(Mode::Mask),
+        ];
         let mode_picker = pick_list(mode_options, Some(ModeOption(self.properties.mode)), |m| {
             msg(EditLayerDialogMessage::SetMode(m.0))
         })
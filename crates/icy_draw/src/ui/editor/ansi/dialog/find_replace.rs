@@ -0,0 +1,385 @@
+//! Find and Replace Dialog
+//!
+//! Searches the canvas for characters, colors, blink and font page and replaces them in one undo step.
+//! Every change of the search criteria is previewed by highlighting the matches on the canvas.
+
+use icy_engine::{AttributeColor, AttributedChar, BufferType};
+use icy_engine_edit::{CellPattern, ReplaceScope};
+use icy_engine_gui::settings::effect_box;
+use icy_engine_gui::ui::{
+    button_row, dialog_area, dialog_title, left_label_small, modal_container, primary_button, secondary_button, separator, Dialog, DialogAction,
+    DIALOG_SPACING, DIALOG_WIDTH_LARGE, TEXT_SIZE_NORMAL, TEXT_SIZE_SMALL,
+};
+use icy_engine_gui::ButtonType;
+use icy_ui::{
+    widget::{column, container, pick_list, row, text, text_input, Space},
+    Alignment, Element, Length,
+};
+
+use crate::fl;
+use crate::ui::editor::ansi::AnsiEditorMessage;
+use crate::ui::Message;
+
+fn msg(m: FindReplaceDialogMessage) -> Message {
+    Message::AnsiEditor(AnsiEditorMessage::FindReplaceDialog(m))
+}
+
+/// Text fields of a cell description
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellField {
+    Char,
+    Foreground,
+    Background,
+    FontPage,
+}
+
+/// Pick list entry for the blink attribute, `None` is "any" when searching and "keep" when replacing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlinkChoice {
+    value: Option<bool>,
+    is_replace: bool,
+}
+
+impl std::fmt::Display for BlinkChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.value {
+            None if self.is_replace => write!(f, "{}", fl!("find-replace-dialog-keep")),
+            None => write!(f, "{}", fl!("find-replace-dialog-any")),
+            Some(true) => write!(f, "{}", fl!("find-replace-dialog-on")),
+            Some(false) => write!(f, "{}", fl!("find-replace-dialog-off")),
+        }
+    }
+}
+
+/// Wrapper type for `ReplaceScope` to implement Display (orphan rule workaround)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScopeOption(pub ReplaceScope);
+
+impl std::fmt::Display for ScopeOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            ReplaceScope::Selection => write!(f, "{}", fl!("find-replace-dialog-scope-selection")),
+            ReplaceScope::Layer => write!(f, "{}", fl!("find-replace-dialog-scope-layer")),
+            ReplaceScope::Document => write!(f, "{}", fl!("find-replace-dialog-scope-document")),
+        }
+    }
+}
+
+/// Messages for the Find and Replace dialog
+#[derive(Debug, Clone)]
+pub enum FindReplaceDialogMessage {
+    /// Search criteria input changed
+    SetFind(CellField, String),
+    /// Replacement input changed
+    SetReplace(CellField, String),
+    SetFindBlink(BlinkChoice),
+    SetReplaceBlink(BlinkChoice),
+    SetScope(ReplaceScope),
+    /// Number of matches of the current criteria, sent back by the editor after a preview
+    SetMatchCount(usize),
+    /// Replace all matches
+    Replace,
+    /// Cancel the dialog
+    Cancel,
+}
+
+/// Text inputs describing one cell, empty inputs are wildcards
+#[derive(Debug, Clone, Default)]
+struct CellInputs {
+    ch: String,
+    foreground: String,
+    background: String,
+    blink: Option<bool>,
+    font_page: String,
+}
+
+impl CellInputs {
+    fn from_char(ch: AttributedChar) -> Self {
+        Self {
+            ch: ch.ch.to_string(),
+            foreground: color_to_string(ch.attribute.foreground_color()),
+            background: color_to_string(ch.attribute.background_color()),
+            blink: None,
+            font_page: String::new(),
+        }
+    }
+
+    fn field_mut(&mut self, field: CellField) -> &mut String {
+        match field {
+            CellField::Char => &mut self.ch,
+            CellField::Foreground => &mut self.foreground,
+            CellField::Background => &mut self.background,
+            CellField::FontPage => &mut self.font_page,
+        }
+    }
+
+    fn to_pattern(&self, buffer_type: BufferType) -> Option<CellPattern> {
+        Some(CellPattern {
+            ch: self.ch.chars().next().map(|ch| buffer_type.convert_from_unicode(ch)),
+            foreground: parse_color(&self.foreground)?,
+            background: parse_color(&self.background)?,
+            blink: self.blink,
+            font_page: if self.font_page.trim().is_empty() {
+                None
+            } else {
+                Some(self.font_page.trim().parse::<u8>().ok()?)
+            },
+        })
+    }
+}
+
+pub(super) fn color_to_string(color: AttributeColor) -> String {
+    match color {
+        AttributeColor::Palette(idx) | AttributeColor::ExtendedPalette(idx) => idx.to_string(),
+        AttributeColor::Rgb(r, g, b) => format!("#{r:02x}{g:02x}{b:02x}"),
+        AttributeColor::Transparent => String::new(),
+    }
+}
+
+/// Parses a palette index or `#rrggbb`, `Some(None)` for an empty input and `None` if invalid
+pub(super) fn parse_color(input: &str) -> Option<Option<AttributeColor>> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Some(None);
+    }
+    if let Some(hex) = input.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        return Some(Some(AttributeColor::Rgb((value >> 16) as u8, (value >> 8) as u8, value as u8)));
+    }
+    input.parse::<u8>().ok().map(|idx| Some(AttributeColor::Palette(idx)))
+}
+
+/// State for the Find and Replace dialog
+#[derive(Debug, Clone)]
+pub struct FindReplaceDialog {
+    buffer_type: BufferType,
+    find: CellInputs,
+    replace: CellInputs,
+    scope: ReplaceScope,
+    match_count: usize,
+}
+
+impl FindReplaceDialog {
+    /// Create a new dialog searching for `template` (usually the character under the caret)
+    pub fn new(buffer_type: BufferType, template: AttributedChar, scope: ReplaceScope) -> Self {
+        Self {
+            buffer_type,
+            find: CellInputs::from_char(template),
+            replace: CellInputs::default(),
+            scope,
+            match_count: 0,
+        }
+    }
+
+    pub fn pattern(&self) -> Option<CellPattern> {
+        self.find.to_pattern(self.buffer_type)
+    }
+
+    pub fn scope(&self) -> ReplaceScope {
+        self.scope
+    }
+
+    fn replacement(&self) -> Option<CellPattern> {
+        self.replace.to_pattern(self.buffer_type)
+    }
+
+    fn can_replace(&self) -> bool {
+        self.match_count > 0 && self.pattern().is_some() && self.replacement().is_some()
+    }
+
+    fn preview(&self) -> DialogAction<Message> {
+        match self.pattern() {
+            Some(pattern) => DialogAction::SendMessage(Message::AnsiEditor(AnsiEditorMessage::FindReplacePreview(pattern, self.scope))),
+            None => DialogAction::None,
+        }
+    }
+
+    fn apply(&self) -> DialogAction<Message> {
+        match (self.pattern(), self.replacement()) {
+            (Some(pattern), Some(replacement)) if self.can_replace() => {
+                DialogAction::CloseWith(Message::AnsiEditor(AnsiEditorMessage::ApplyFindReplace(pattern, replacement, self.scope)))
+            }
+            _ => DialogAction::None,
+        }
+    }
+
+    fn cell_column(&self, is_replace: bool) -> Element<'_, Message> {
+        let inputs = if is_replace { &self.replace } else { &self.find };
+        let placeholder = if is_replace {
+            fl!("find-replace-dialog-keep")
+        } else {
+            fl!("find-replace-dialog-any")
+        };
+        let field = move |label: String, field: CellField, value: &str, valid: bool| {
+            let input = text_input(&placeholder, value)
+                .on_input(move |s| {
+                    if is_replace {
+                        msg(FindReplaceDialogMessage::SetReplace(field, s))
+                    } else {
+                        msg(FindReplaceDialogMessage::SetFind(field, s))
+                    }
+                })
+                .size(TEXT_SIZE_NORMAL)
+                .width(Length::Fixed(90.0));
+            let error = if valid {
+                text("").size(TEXT_SIZE_SMALL)
+            } else {
+                text("!").size(TEXT_SIZE_SMALL).style(|theme: &icy_ui::Theme| icy_ui::widget::text::Style {
+                    color: Some(theme.destructive.base),
+                })
+            };
+            row![left_label_small(label), input, error].spacing(DIALOG_SPACING).align_y(Alignment::Center)
+        };
+
+        let blink_options: Vec<BlinkChoice> = [None, Some(true), Some(false)]
+            .into_iter()
+            .map(|value| BlinkChoice { value, is_replace })
+            .collect();
+        let blink_picker = pick_list(
+            blink_options,
+            Some(BlinkChoice {
+                value: inputs.blink,
+                is_replace,
+            }),
+            move |choice| {
+                if is_replace {
+                    msg(FindReplaceDialogMessage::SetReplaceBlink(choice))
+                } else {
+                    msg(FindReplaceDialogMessage::SetFindBlink(choice))
+                }
+            },
+        )
+        .width(Length::Fixed(90.0));
+
+        let header = if is_replace {
+            fl!("find-replace-dialog-replace")
+        } else {
+            fl!("find-replace-dialog-find")
+        };
+        column![
+            text(header).size(TEXT_SIZE_NORMAL),
+            field(fl!("find-replace-dialog-char"), CellField::Char, &inputs.ch, true),
+            field(
+                fl!("find-replace-dialog-foreground"),
+                CellField::Foreground,
+                &inputs.foreground,
+                parse_color(&inputs.foreground).is_some()
+            ),
+            field(
+                fl!("find-replace-dialog-background"),
+                CellField::Background,
+                &inputs.background,
+                parse_color(&inputs.background).is_some()
+            ),
+            row![left_label_small(fl!("find-replace-dialog-blink")), blink_picker]
+                .spacing(DIALOG_SPACING)
+                .align_y(Alignment::Center),
+            field(
+                fl!("find-replace-dialog-font_page"),
+                CellField::FontPage,
+                &inputs.font_page,
+                inputs.font_page.trim().is_empty() || inputs.font_page.trim().parse::<u8>().is_ok()
+            ),
+        ]
+        .spacing(DIALOG_SPACING)
+        .into()
+    }
+}
+
+impl Dialog<Message> for FindReplaceDialog {
+    fn view(&self) -> Element<'_, Message> {
+        let title = dialog_title(fl!("find-replace-dialog-title"));
+
+        let cells_row = row![self.cell_column(false), self.cell_column(true)].spacing(DIALOG_SPACING * 2.0);
+
+        let scope_options = vec![
+            ScopeOption(ReplaceScope::Selection),
+            ScopeOption(ReplaceScope::Layer),
+            ScopeOption(ReplaceScope::Document),
+        ];
+        let scope_picker =
+            pick_list(scope_options, Some(ScopeOption(self.scope)), |s| msg(FindReplaceDialogMessage::SetScope(s.0))).width(Length::Fixed(180.0));
+        let scope_row = row![left_label_small(fl!("find-replace-dialog-scope")), scope_picker]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center);
+
+        let matches = text(fl!("find-replace-dialog-matches", count = self.match_count)).size(TEXT_SIZE_NORMAL);
+        let hint = text(fl!("find-replace-dialog-hint")).size(TEXT_SIZE_SMALL);
+
+        let content_column = column![
+            cells_row,
+            Space::new().height(DIALOG_SPACING),
+            scope_row,
+            Space::new().height(DIALOG_SPACING),
+            matches,
+            hint
+        ]
+        .spacing(DIALOG_SPACING);
+
+        let content_box = effect_box(content_column.into());
+
+        let buttons = button_row(vec![
+            secondary_button(format!("{}", ButtonType::Cancel), Some(msg(FindReplaceDialogMessage::Cancel))).into(),
+            primary_button(
+                fl!("find-replace-dialog-replace_button"),
+                self.can_replace().then(|| msg(FindReplaceDialogMessage::Replace)),
+            )
+            .into(),
+        ]);
+
+        let dialog_content = dialog_area(column![title, Space::new().height(DIALOG_SPACING), content_box].into());
+
+        let button_area = dialog_area(buttons);
+
+        modal_container(
+            column![container(dialog_content).height(Length::Shrink), separator(), button_area].into(),
+            DIALOG_WIDTH_LARGE,
+        )
+        .into()
+    }
+
+    fn update(&mut self, message: &Message) -> Option<DialogAction<Message>> {
+        let Message::AnsiEditor(AnsiEditorMessage::FindReplaceDialog(dialog_msg)) = message else {
+            return None;
+        };
+        match dialog_msg {
+            FindReplaceDialogMessage::SetFind(field, value) => {
+                *self.find.field_mut(*field) = value.clone();
+                Some(self.preview())
+            }
+            FindReplaceDialogMessage::SetReplace(field, value) => {
+                *self.replace.field_mut(*field) = value.clone();
+                Some(DialogAction::None)
+            }
+            FindReplaceDialogMessage::SetFindBlink(choice) => {
+                self.find.blink = choice.value;
+                Some(self.preview())
+            }
+            FindReplaceDialogMessage::SetReplaceBlink(choice) => {
+                self.replace.blink = choice.value;
+                Some(DialogAction::None)
+            }
+            FindReplaceDialogMessage::SetScope(scope) => {
+                self.scope = *scope;
+                Some(self.preview())
+            }
+            FindReplaceDialogMessage::SetMatchCount(count) => {
+                self.match_count = *count;
+                Some(DialogAction::None)
+            }
+            FindReplaceDialogMessage::Replace => Some(self.apply()),
+            FindReplaceDialogMessage::Cancel => Some(self.request_cancel()),
+        }
+    }
+
+    fn request_cancel(&mut self) -> DialogAction<Message> {
+        DialogAction::CloseWith(Message::AnsiEditor(AnsiEditorMessage::FindReplaceClosed))
+    }
+
+    fn request_confirm(&mut self) -> DialogAction<Message> {
+        self.apply()
+    }
+}
@@ -529,11 +529,9 @@ impl SetFontDialog {
         // Try loading based on extension
         match ext.as_str() {
             "psf" | "psf2" | "psfu" => {
-                // PSF font file
+                // PSF font file, 512 glyph fonts load as two pages
                 let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("Font").to_string();
-                BitFont::from_bytes(name, &data)
-                    .map(|f| vec![f])
-                    .map_err(|e| format!("{}: {}", fl!("set-font-load-error"), e))
+                BitFont::pages_from_bytes(name, &data).map_err(|e| format!("{}: {}", fl!("set-font-load-error"), e))
             }
             "yaff" => {
                 // YAFF font file
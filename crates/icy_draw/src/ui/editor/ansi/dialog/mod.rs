@@ -2,9 +2,13 @@
 
 pub mod edit_layer;
 pub mod file_settings;
+pub mod find_replace;
 pub mod font_selector;
 pub mod font_slot_manager;
+pub mod recolor;
 pub mod reference_image;
+pub mod scale;
 pub mod tag;
 pub mod tag_list;
 pub mod tdf_font_selector;
+pub mod timelapse;
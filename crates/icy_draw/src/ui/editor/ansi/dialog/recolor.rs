@@ -0,0 +1,495 @@
+//! Recolor Dialog
+//!
+//! Maps the foreground and background colors used in the selection, the current layer or the whole
+//! document to new colors. Presets fill in the targets, which can be edited afterwards, and every
+//! change is previewed on the canvas.
+
+use std::path::{Path, PathBuf};
+
+use icy_engine::formats::PaletteFormat;
+use icy_engine::{AttributeColor, FileFormat, Palette, XTERM_256_PALETTE};
+use icy_engine_edit::{ColorRemap, HueFamily, RemapPreset, ReplaceScope};
+use icy_engine_gui::settings::effect_box;
+use icy_engine_gui::ui::{
+    button_row, dialog_area, dialog_title, left_label_small, modal_container, primary_button, secondary_button, separator, validated_input_style, Dialog,
+    DialogAction, DIALOG_SPACING, DIALOG_WIDTH_LARGE, TEXT_SIZE_NORMAL, TEXT_SIZE_SMALL,
+};
+use icy_engine_gui::ButtonType;
+use icy_ui::{
+    widget::{checkbox, column, container, pick_list, row, scrollable, text, text_input, Space},
+    Alignment, Element, Length, Task,
+};
+
+use super::find_replace::{color_to_string, parse_color, ScopeOption};
+use crate::fl;
+use crate::ui::editor::ansi::AnsiEditorMessage;
+use crate::ui::Message;
+
+fn msg(m: RecolorDialogMessage) -> Message {
+    Message::AnsiEditor(AnsiEditorMessage::RecolorDialog(m))
+}
+
+/// Preset that fills in the target colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresetChoice {
+    /// Targets are only edited by hand
+    Custom,
+    SwapHue,
+    Grayscale,
+    InvertBrightness,
+    NearestInPalette,
+}
+
+impl std::fmt::Display for PresetChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PresetChoice::Custom => write!(f, "{}", fl!("recolor-dialog-preset-custom")),
+            PresetChoice::SwapHue => write!(f, "{}", fl!("recolor-dialog-preset-swap_hue")),
+            PresetChoice::Grayscale => write!(f, "{}", fl!("recolor-dialog-preset-grayscale")),
+            PresetChoice::InvertBrightness => write!(f, "{}", fl!("recolor-dialog-preset-invert_brightness")),
+            PresetChoice::NearestInPalette => write!(f, "{}", fl!("recolor-dialog-preset-nearest_in_palette")),
+        }
+    }
+}
+
+/// Wrapper type for `HueFamily` to implement Display (orphan rule workaround)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HueOption(pub HueFamily);
+
+impl std::fmt::Display for HueOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            HueFamily::Red => write!(f, "{}", fl!("recolor-dialog-hue-red")),
+            HueFamily::Yellow => write!(f, "{}", fl!("recolor-dialog-hue-yellow")),
+            HueFamily::Green => write!(f, "{}", fl!("recolor-dialog-hue-green")),
+            HueFamily::Cyan => write!(f, "{}", fl!("recolor-dialog-hue-cyan")),
+            HueFamily::Blue => write!(f, "{}", fl!("recolor-dialog-hue-blue")),
+            HueFamily::Magenta => write!(f, "{}", fl!("recolor-dialog-hue-magenta")),
+        }
+    }
+}
+
+/// Messages for the Recolor dialog
+#[derive(Debug, Clone)]
+pub enum RecolorDialogMessage {
+    SetPreset(PresetChoice),
+    SetSwapFrom(HueFamily),
+    SetSwapTo(HueFamily),
+    /// Pick the palette file for [`PresetChoice::NearestInPalette`]
+    LoadPalette,
+    PaletteFileSelected(Option<PathBuf>),
+    /// Whether the preset changes the foreground / background colors
+    SetRemapForeground(bool),
+    SetRemapBackground(bool),
+    /// Target input of a foreground (false) or background (true) color changed
+    SetTarget(bool, usize, String),
+    SetScope(ReplaceScope),
+    /// Apply the remap
+    Apply,
+    /// Cancel the dialog
+    Cancel,
+}
+
+/// A used color and the input for its replacement
+#[derive(Debug, Clone)]
+struct ColorEntry {
+    from: AttributeColor,
+    to: String,
+}
+
+/// Distinct foreground and background colors of a scope
+pub type UsedColors = (Vec<AttributeColor>, Vec<AttributeColor>);
+
+/// State for the Recolor dialog
+#[derive(Debug, Clone)]
+pub struct RecolorDialog {
+    palette: Palette,
+    /// Used colors for each scope, collected before any preview changed the buffer
+    used_colors: Vec<(ReplaceScope, UsedColors)>,
+    scope: ReplaceScope,
+    preset: PresetChoice,
+    swap: (HueFamily, HueFamily),
+    /// File name and colors of the palette for [`PresetChoice::NearestInPalette`]
+    target_palette: Option<(String, Palette)>,
+    remap_foreground: bool,
+    remap_background: bool,
+    foreground: Vec<ColorEntry>,
+    background: Vec<ColorEntry>,
+    error: Option<String>,
+}
+
+impl RecolorDialog {
+    pub fn new(palette: Palette, used_colors: Vec<(ReplaceScope, UsedColors)>, scope: ReplaceScope) -> Self {
+        let mut dialog = Self {
+            palette,
+            used_colors,
+            scope,
+            preset: PresetChoice::Custom,
+            swap: (HueFamily::Red, HueFamily::Blue),
+            target_palette: None,
+            remap_foreground: true,
+            remap_background: true,
+            foreground: Vec::new(),
+            background: Vec::new(),
+            error: None,
+        };
+        dialog.fill_targets();
+        dialog
+    }
+
+    /// A palette switch affects every layer, so the nearest palette preset always covers the document
+    fn effective_scope(&self) -> ReplaceScope {
+        if self.uses_target_palette() {
+            ReplaceScope::Document
+        } else {
+            self.scope
+        }
+    }
+
+    fn uses_target_palette(&self) -> bool {
+        self.preset == PresetChoice::NearestInPalette && self.target_palette.is_some()
+    }
+
+    fn remap_preset(&self) -> Option<RemapPreset> {
+        match self.preset {
+            PresetChoice::Custom => None,
+            PresetChoice::SwapHue => Some(RemapPreset::SwapHue(self.swap.0, self.swap.1)),
+            PresetChoice::Grayscale => Some(RemapPreset::Grayscale),
+            PresetChoice::InvertBrightness => Some(RemapPreset::InvertBrightness),
+            PresetChoice::NearestInPalette => self.target_palette.as_ref().map(|(_, palette)| RemapPreset::NearestInPalette(palette.clone())),
+        }
+    }
+
+    /// Rebuilds the color lists for the current scope and fills in the targets of the preset
+    fn fill_targets(&mut self) {
+        let scope = self.effective_scope();
+        let (foreground, background) = self
+            .used_colors
+            .iter()
+            .find(|(s, _)| *s == scope)
+            .map(|(_, colors)| colors.clone())
+            .unwrap_or_default();
+        let preset = self.remap_preset();
+        // indices into the old palette make no sense after the palette switch
+        let forced = self.uses_target_palette();
+        let entries = |colors: Vec<AttributeColor>, enabled: bool| {
+            colors
+                .into_iter()
+                .map(|from| {
+                    let to = match &preset {
+                        Some(preset) if enabled || forced => preset.map_color(&self.palette, from),
+                        _ => from,
+                    };
+                    ColorEntry { from, to: color_to_string(to) }
+                })
+                .collect()
+        };
+        self.foreground = entries(foreground, self.remap_foreground);
+        self.background = entries(background, self.remap_background);
+    }
+
+    /// Palette the target indices refer to
+    fn target_colors(&self) -> &Palette {
+        match &self.target_palette {
+            Some((_, palette)) if self.uses_target_palette() => palette,
+            _ => &self.palette,
+        }
+    }
+
+    /// Parses a target input, palette indices keep the kind of palette of the source color
+    fn parse_target(&self, entry: &ColorEntry) -> Option<AttributeColor> {
+        match parse_color(&entry.to)? {
+            None => Some(entry.from),
+            Some(AttributeColor::Palette(idx)) if matches!(entry.from, AttributeColor::ExtendedPalette(_)) && !self.uses_target_palette() => {
+                Some(AttributeColor::ExtendedPalette(idx))
+            }
+            Some(AttributeColor::Palette(idx)) if idx as usize >= self.target_colors().len() => None,
+            Some(color) => Some(color),
+        }
+    }
+
+    fn remap(&self) -> Option<ColorRemap> {
+        let map = |entries: &[ColorEntry]| entries.iter().map(|e| self.parse_target(e).map(|to| (e.from, to))).collect::<Option<Vec<_>>>();
+        Some(ColorRemap {
+            foreground: map(&self.foreground)?,
+            background: map(&self.background)?,
+            palette: self.uses_target_palette().then(|| self.target_colors().clone()),
+        })
+    }
+
+    fn preview(&self) -> DialogAction<Message> {
+        match self.remap() {
+            Some(remap) => DialogAction::SendMessage(Message::AnsiEditor(AnsiEditorMessage::RecolorPreview(remap, self.effective_scope()))),
+            None => DialogAction::None,
+        }
+    }
+
+    fn apply(&self) -> DialogAction<Message> {
+        match self.remap() {
+            Some(remap) if !remap.is_empty() => DialogAction::CloseWith(Message::AnsiEditor(AnsiEditorMessage::ApplyRecolor(remap, self.effective_scope()))),
+            _ => DialogAction::None,
+        }
+    }
+
+    fn pick_palette_file() -> DialogAction<Message> {
+        let task = Task::perform(
+            async move {
+                rfd::AsyncFileDialog::new()
+                    .add_filter("Palette", &["gpl", "pal", "hex", "txt", "ice", "icepal", "ase"])
+                    .pick_file()
+                    .await
+                    .map(|h| h.path().to_path_buf())
+            },
+            |path| msg(RecolorDialogMessage::PaletteFileSelected(path)),
+        );
+        DialogAction::RunTask(task)
+    }
+
+    fn load_palette(path: &Path) -> Result<Palette, String> {
+        let Some(format) = PaletteFormat::from_path(path) else {
+            return Err(fl!("recolor-dialog-unsupported_palette"));
+        };
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+        let palette = FileFormat::Palette(format).load_palette(&bytes).map_err(|e| e.to_string())?;
+        if palette.is_empty() {
+            return Err(fl!("recolor-dialog-unsupported_palette"));
+        }
+        Ok(palette)
+    }
+
+    fn rgb(palette: &Palette, color: AttributeColor) -> Option<(u8, u8, u8)> {
+        match color {
+            AttributeColor::Palette(idx) if (idx as usize) < palette.len() => Some(palette.rgb(idx as u32)),
+            AttributeColor::ExtendedPalette(idx) => Some(XTERM_256_PALETTE[idx as usize].1.rgb()),
+            AttributeColor::Rgb(r, g, b) => Some((r, g, b)),
+            _ => None,
+        }
+    }
+
+    fn swatch<'a>(rgb: Option<(u8, u8, u8)>) -> Element<'a, Message> {
+        container(Space::new().width(Length::Fixed(16.0)).height(Length::Fixed(16.0)))
+            .style(move |_theme: &icy_ui::Theme| icy_ui::widget::container::Style {
+                background: rgb.map(|(r, g, b)| icy_ui::Background::Color(icy_ui::Color::from_rgb8(r, g, b))),
+                border: icy_ui::Border {
+                    color: icy_ui::Color::from_rgb8(50, 50, 50),
+                    width: 1.0,
+                    radius: 3.0.into(),
+                },
+                ..Default::default()
+            })
+            .into()
+    }
+
+    fn color_column(&self, is_background: bool) -> Element<'_, Message> {
+        let entries = if is_background { &self.background } else { &self.foreground };
+        let (header, enabled) = if is_background {
+            (fl!("recolor-dialog-background"), self.remap_background)
+        } else {
+            (fl!("recolor-dialog-foreground"), self.remap_foreground)
+        };
+        let toggle = checkbox(enabled)
+            .on_toggle(move |v| {
+                if is_background {
+                    msg(RecolorDialogMessage::SetRemapBackground(v))
+                } else {
+                    msg(RecolorDialogMessage::SetRemapForeground(v))
+                }
+            })
+            .size(16);
+        let header_row = row![toggle, text(header).size(TEXT_SIZE_NORMAL)]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center);
+
+        let mut rows = column![].spacing(4);
+        if entries.is_empty() {
+            rows = rows.push(text(fl!("recolor-dialog-no_colors")).size(TEXT_SIZE_SMALL));
+        }
+        for (i, entry) in entries.iter().enumerate() {
+            let target = self.parse_target(entry);
+            let input = text_input("", &entry.to)
+                .on_input(move |s| msg(RecolorDialogMessage::SetTarget(is_background, i, s)))
+                .size(TEXT_SIZE_NORMAL)
+                .width(Length::Fixed(90.0))
+                .style(validated_input_style(target.is_some()));
+            rows = rows.push(
+                row![
+                    Self::swatch(Self::rgb(&self.palette, entry.from)),
+                    text(color_to_string(entry.from)).size(TEXT_SIZE_SMALL).width(Length::Fixed(60.0)),
+                    text("→").size(TEXT_SIZE_SMALL),
+                    input,
+                    Self::swatch(target.and_then(|color| Self::rgb(self.target_colors(), color))),
+                ]
+                .spacing(DIALOG_SPACING)
+                .align_y(Alignment::Center),
+            );
+        }
+
+        column![header_row, scrollable(rows).height(Length::Fixed(220.0))]
+            .spacing(DIALOG_SPACING)
+            .width(Length::Fill)
+            .into()
+    }
+
+    fn preset_row(&self) -> Element<'_, Message> {
+        let presets = vec![
+            PresetChoice::Custom,
+            PresetChoice::SwapHue,
+            PresetChoice::Grayscale,
+            PresetChoice::InvertBrightness,
+            PresetChoice::NearestInPalette,
+        ];
+        let preset_picker = pick_list(presets, Some(self.preset), |p| msg(RecolorDialogMessage::SetPreset(p))).width(Length::Fixed(180.0));
+        let mut preset_row = row![left_label_small(fl!("recolor-dialog-preset")), preset_picker]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center);
+
+        match self.preset {
+            PresetChoice::SwapHue => {
+                let hues: Vec<HueOption> = HueFamily::ALL.into_iter().map(HueOption).collect();
+                preset_row = preset_row
+                    .push(pick_list(hues.clone(), Some(HueOption(self.swap.0)), |h| msg(RecolorDialogMessage::SetSwapFrom(h.0))).width(Length::Fixed(100.0)))
+                    .push(text("↔").size(TEXT_SIZE_NORMAL))
+                    .push(pick_list(hues, Some(HueOption(self.swap.1)), |h| msg(RecolorDialogMessage::SetSwapTo(h.0))).width(Length::Fixed(100.0)));
+            }
+            PresetChoice::NearestInPalette => {
+                let name = self
+                    .target_palette
+                    .as_ref()
+                    .map_or_else(|| fl!("recolor-dialog-no_palette"), |(name, palette)| format!("{name} ({})", palette.len()));
+                preset_row = preset_row
+                    .push(secondary_button(
+                        fl!("recolor-dialog-load_palette"),
+                        Some(msg(RecolorDialogMessage::LoadPalette)),
+                    ))
+                    .push(text(name).size(TEXT_SIZE_SMALL));
+            }
+            _ => {}
+        }
+        preset_row.into()
+    }
+}
+
+impl Dialog<Message> for RecolorDialog {
+    fn view(&self) -> Element<'_, Message> {
+        let title = dialog_title(fl!("recolor-dialog-title"));
+
+        let scope_options = vec![
+            ScopeOption(ReplaceScope::Selection),
+            ScopeOption(ReplaceScope::Layer),
+            ScopeOption(ReplaceScope::Document),
+        ];
+        let scope_picker = pick_list(scope_options, Some(ScopeOption(self.effective_scope())), |s| {
+            msg(RecolorDialogMessage::SetScope(s.0))
+        })
+        .width(Length::Fixed(180.0));
+        let scope_row = row![left_label_small(fl!("recolor-dialog-scope")), scope_picker]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center);
+
+        let colors_row = row![self.color_column(false), self.color_column(true)].spacing(DIALOG_SPACING * 2.0);
+
+        let mut content_column = column![self.preset_row(), scope_row, Space::new().height(DIALOG_SPACING), colors_row].spacing(DIALOG_SPACING);
+        if let Some(error) = &self.error {
+            content_column = content_column.push(text(error).size(TEXT_SIZE_SMALL).style(|theme: &icy_ui::Theme| icy_ui::widget::text::Style {
+                color: Some(theme.destructive.base),
+            }));
+        }
+        content_column = content_column.push(text(fl!("recolor-dialog-hint")).size(TEXT_SIZE_SMALL));
+
+        let content_box = effect_box(content_column.into());
+
+        let can_apply = self.remap().is_some_and(|remap| !remap.is_empty());
+        let buttons = button_row(vec![
+            secondary_button(format!("{}", ButtonType::Cancel), Some(msg(RecolorDialogMessage::Cancel))).into(),
+            primary_button(fl!("recolor-dialog-apply"), can_apply.then(|| msg(RecolorDialogMessage::Apply))).into(),
+        ]);
+
+        let dialog_content = dialog_area(column![title, Space::new().height(DIALOG_SPACING), content_box].into());
+
+        let button_area = dialog_area(buttons);
+
+        modal_container(
+            column![container(dialog_content).height(Length::Shrink), separator(), button_area].into(),
+            DIALOG_WIDTH_LARGE,
+        )
+        .into()
+    }
+
+    fn update(&mut self, message: &Message) -> Option<DialogAction<Message>> {
+        let Message::AnsiEditor(AnsiEditorMessage::RecolorDialog(dialog_msg)) = message else {
+            return None;
+        };
+        match dialog_msg {
+            RecolorDialogMessage::SetPreset(preset) => {
+                self.preset = *preset;
+                if self.preset == PresetChoice::NearestInPalette && self.target_palette.is_none() {
+                    return Some(Self::pick_palette_file());
+                }
+                self.fill_targets();
+                Some(self.preview())
+            }
+            RecolorDialogMessage::SetSwapFrom(hue) => {
+                self.swap.0 = *hue;
+                self.fill_targets();
+                Some(self.preview())
+            }
+            RecolorDialogMessage::SetSwapTo(hue) => {
+                self.swap.1 = *hue;
+                self.fill_targets();
+                Some(self.preview())
+            }
+            RecolorDialogMessage::LoadPalette => Some(Self::pick_palette_file()),
+            RecolorDialogMessage::PaletteFileSelected(path) => {
+                let Some(path) = path else {
+                    return Some(DialogAction::None);
+                };
+                match Self::load_palette(path) {
+                    Ok(palette) => {
+                        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                        self.target_palette = Some((name, palette));
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                self.fill_targets();
+                Some(self.preview())
+            }
+            RecolorDialogMessage::SetRemapForeground(enabled) => {
+                self.remap_foreground = *enabled;
+                self.fill_targets();
+                Some(self.preview())
+            }
+            RecolorDialogMessage::SetRemapBackground(enabled) => {
+                self.remap_background = *enabled;
+                self.fill_targets();
+                Some(self.preview())
+            }
+            RecolorDialogMessage::SetTarget(is_background, i, value) => {
+                let entries = if *is_background { &mut self.background } else { &mut self.foreground };
+                if let Some(entry) = entries.get_mut(*i) {
+                    entry.to = value.clone();
+                }
+                Some(self.preview())
+            }
+            RecolorDialogMessage::SetScope(scope) => {
+                self.scope = *scope;
+                if self.preset == PresetChoice::NearestInPalette {
+                    // the palette switch can't be limited to a scope
+                    self.preset = PresetChoice::Custom;
+                }
+                self.fill_targets();
+                Some(self.preview())
+            }
+            RecolorDialogMessage::Apply => Some(self.apply()),
+            RecolorDialogMessage::Cancel => Some(self.request_cancel()),
+        }
+    }
+
+    fn request_cancel(&mut self) -> DialogAction<Message> {
+        DialogAction::CloseWith(Message::AnsiEditor(AnsiEditorMessage::RecolorClosed))
+    }
+
+    fn request_confirm(&mut self) -> DialogAction<Message> {
+        self.apply()
+    }
+}
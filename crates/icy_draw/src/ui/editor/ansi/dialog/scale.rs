@@ -0,0 +1,172 @@
+//! Scale Dialog
+//!
+//! Scales the selection or, without a selection, the current layer to a new size in cells.
+
+use icy_engine::Size;
+use icy_engine_edit::ScaleMode;
+use icy_engine_gui::settings::effect_box;
+use icy_engine_gui::ui::{
+    button_row, dialog_area, dialog_title, left_label_small, modal_container, primary_button, secondary_button, separator, Dialog, DialogAction,
+    DIALOG_SPACING, DIALOG_WIDTH_MEDIUM, TEXT_SIZE_NORMAL, TEXT_SIZE_SMALL,
+};
+use icy_engine_gui::ButtonType;
+use icy_ui::{
+    widget::{checkbox, column, container, row, text, text_input, Space},
+    Alignment, Element, Length,
+};
+
+use crate::fl;
+use crate::ui::editor::ansi::AnsiEditorMessage;
+use crate::ui::Message;
+
+/// Helper function to wrap scale dialog messages
+fn msg(m: ScaleDialogMessage) -> Message {
+    Message::AnsiEditor(AnsiEditorMessage::ScaleDialog(m))
+}
+
+/// Messages for the Scale dialog
+#[derive(Debug, Clone)]
+pub enum ScaleDialogMessage {
+    /// Width input changed
+    SetWidth(String),
+    /// Height input changed
+    SetHeight(String),
+    /// Half block resampling checkbox changed
+    SetHalfBlock(bool),
+    /// Apply the scaling
+    Apply,
+    /// Cancel the dialog
+    Cancel,
+}
+
+/// State for the Scale dialog
+#[derive(Debug, Clone)]
+pub struct ScaleDialog {
+    /// Size of the area that gets scaled
+    original_size: Size,
+    /// Width input string
+    width: String,
+    /// Height input string
+    height: String,
+    /// Resampling mode
+    mode: ScaleMode,
+}
+
+impl ScaleDialog {
+    /// Create a new Scale dialog for an area of `size` cells
+    pub fn new(size: Size) -> Self {
+        Self {
+            original_size: size,
+            width: size.width.to_string(),
+            height: size.height.to_string(),
+            mode: ScaleMode::default(),
+        }
+    }
+
+    /// Parse the current width value
+    pub fn parsed_width(&self) -> Option<i32> {
+        self.width.parse::<i32>().ok().filter(|&w| w >= 1)
+    }
+
+    /// Parse the current height value
+    pub fn parsed_height(&self) -> Option<i32> {
+        self.height.parse::<i32>().ok().filter(|&h| h >= 1)
+    }
+
+    /// The apply message if the inputs are valid
+    fn apply_message(&self) -> Option<Message> {
+        let size = Size::new(self.parsed_width()?, self.parsed_height()?);
+        Some(Message::AnsiEditor(AnsiEditorMessage::ApplyScale(size, self.mode)))
+    }
+}
+
+impl Dialog<Message> for ScaleDialog {
+    fn view(&self) -> Element<'_, Message> {
+        let title = dialog_title(fl!("scale-dialog-title"));
+
+        let current_size = text(fl!(
+            "scale-dialog-current-size",
+            width = self.original_size.width,
+            height = self.original_size.height
+        ))
+        .size(TEXT_SIZE_SMALL);
+
+        let width_input = text_input("", &self.width)
+            .on_input(|s| msg(ScaleDialogMessage::SetWidth(s)))
+            .size(TEXT_SIZE_NORMAL)
+            .width(Length::Fixed(80.0));
+        let width_row = row![left_label_small(fl!("edit-canvas-size-width-label")), width_input]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center);
+
+        let height_input = text_input("", &self.height)
+            .on_input(|s| msg(ScaleDialogMessage::SetHeight(s)))
+            .size(TEXT_SIZE_NORMAL)
+            .width(Length::Fixed(80.0));
+        let height_row = row![left_label_small(fl!("edit-canvas-size-height-label")), height_input]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center);
+
+        let half_block_row = row![
+            left_label_small(fl!("scale-dialog-half-block")),
+            checkbox(self.mode == ScaleMode::HalfBlock)
+                .on_toggle(|v| msg(ScaleDialogMessage::SetHalfBlock(v)))
+                .size(16)
+        ]
+        .spacing(DIALOG_SPACING)
+        .align_y(Alignment::Center);
+
+        let content_box = effect_box(
+            column![current_size, Space::new().height(DIALOG_SPACING), width_row, height_row, half_block_row]
+                .spacing(DIALOG_SPACING)
+                .into(),
+        );
+
+        let buttons = button_row(vec![
+            secondary_button(format!("{}", ButtonType::Cancel), Some(msg(ScaleDialogMessage::Cancel))).into(),
+            primary_button(format!("{}", ButtonType::Ok), self.apply_message().map(|_| msg(ScaleDialogMessage::Apply))).into(),
+        ]);
+
+        let dialog_content = dialog_area(column![title, Space::new().height(DIALOG_SPACING), content_box].into());
+        let button_area = dialog_area(buttons);
+
+        modal_container(
+            column![container(dialog_content).height(Length::Shrink), separator(), button_area].into(),
+            DIALOG_WIDTH_MEDIUM,
+        )
+        .into()
+    }
+
+    fn update(&mut self, message: &Message) -> Option<DialogAction<Message>> {
+        let Message::AnsiEditor(AnsiEditorMessage::ScaleDialog(dialog_msg)) = message else {
+            return None;
+        };
+        match dialog_msg {
+            ScaleDialogMessage::SetWidth(w) => {
+                self.width = w.clone();
+                Some(DialogAction::None)
+            }
+            ScaleDialogMessage::SetHeight(h) => {
+                self.height = h.clone();
+                Some(DialogAction::None)
+            }
+            ScaleDialogMessage::SetHalfBlock(half_block) => {
+                self.mode = if *half_block { ScaleMode::HalfBlock } else { ScaleMode::Nearest };
+                Some(DialogAction::None)
+            }
+            ScaleDialogMessage::Apply => Some(self.request_confirm()),
+            ScaleDialogMessage::Cancel => Some(DialogAction::Close),
+        }
+    }
+
+    fn request_cancel(&mut self) -> DialogAction<Message> {
+        DialogAction::Close
+    }
+
+    fn request_confirm(&mut self) -> DialogAction<Message> {
+        match self.apply_message() {
+            Some(message) => DialogAction::CloseWith(message),
+            None => DialogAction::None,
+        }
+    }
+}
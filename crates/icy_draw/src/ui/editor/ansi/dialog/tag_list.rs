@@ -1,19 +1,26 @@
-use icy_engine::{Position, TagPlacement};
-use icy_engine_gui::settings::effect_box;
+use icy_engine::{DisplayCodeIssue, Position, TagPlacement};
+use icy_engine_gui::settings::{effect_box, left_label};
 use icy_engine_gui::ui::{
     button_row, dialog_area, dialog_title, modal_container, secondary_button, separator, DIALOG_SPACING, DIALOG_WIDTH_LARGE, TEXT_SIZE_NORMAL, TEXT_SIZE_SMALL,
 };
 use icy_ui::{
-    widget::{button, column, container, row, scrollable, text, Space},
+    widget::{button, checkbox, column, container, pick_list, row, scrollable, text, Space},
     Element, Length, Theme,
 };
 
 use crate::fl;
+use crate::util::TaglistInfo;
 
 #[derive(Clone, Debug)]
 pub enum TagListDialogMessage {
     Close,
     Delete(usize),
+    /// Switch the BBS profile used for preview, validation and export
+    SelectTaglist(TaglistInfo),
+    /// Rewrite the display codes from the source profile into the selected one
+    ConvertTags,
+    /// Show sample values instead of the tag previews
+    TogglePreview(bool),
 }
 
 #[derive(Clone, Debug)]
@@ -29,16 +36,67 @@ pub struct TagListItem {
 #[derive(Clone, Debug)]
 pub struct TagListDialog {
     pub items: Vec<TagListItem>,
+    /// Available BBS profiles
+    pub available_taglists: Vec<TaglistInfo>,
+    /// Profile used for preview, validation and export
+    pub selected_taglist: TaglistInfo,
+    /// Profile the display codes are written for, [`TagListDialogMessage::ConvertTags`] starts from it
+    pub source_taglist: TaglistInfo,
+    pub show_preview: bool,
+    /// Validation result as (tag index, issue)
+    pub issues: Vec<(usize, DisplayCodeIssue)>,
+    /// Last failed delete or conversion
+    pub error: Option<String>,
 }
 
 impl TagListDialog {
-    pub fn new(items: Vec<TagListItem>) -> Self {
-        Self { items }
+    pub fn new(items: Vec<TagListItem>, available_taglists: Vec<TaglistInfo>, selected_taglist: TaglistInfo) -> Self {
+        Self {
+            items,
+            available_taglists,
+            source_taglist: selected_taglist.clone(),
+            selected_taglist,
+            show_preview: false,
+            issues: Vec::new(),
+            error: None,
+        }
+    }
+
+    fn issue_text(&self, index: usize, issue: &DisplayCodeIssue) -> String {
+        let code = self.items.get(index).map(|item| item.replacement_value.clone()).unwrap_or_default();
+        let message = match issue {
+            DisplayCodeIssue::OutOfBounds => fl!("tag-list-issue-out-of-bounds"),
+            DisplayCodeIssue::Overlap(other) => fl!("tag-list-issue-overlap", other = other + 1),
+            DisplayCodeIssue::InvalidSyntax => fl!("tag-list-issue-syntax", bbs = self.selected_taglist.name.clone()),
+            DisplayCodeIssue::UnknownCode => fl!("tag-list-issue-unknown", bbs = self.selected_taglist.name.clone()),
+            DisplayCodeIssue::SampleTruncated { sample_len, width } => fl!("tag-list-issue-truncated", sample = sample_len, width = width),
+            DisplayCodeIssue::WidthNotSupported => fl!("tag-list-issue-no-width", bbs = self.selected_taglist.name.clone()),
+        };
+        format!("#{} {code}: {message}", index + 1)
     }
 
     pub fn view(&self) -> Element<'_, TagListDialogMessage> {
         let title = dialog_title(fl!("tag-list-title"));
 
+        let profile_row = row![
+            left_label(fl!("tag-list-bbs")),
+            pick_list(
+                self.available_taglists.clone(),
+                Some(self.selected_taglist.clone()),
+                TagListDialogMessage::SelectTaglist
+            )
+            .width(Length::Fixed(180.0)),
+            secondary_button(
+                fl!("tag-list-convert", bbs = self.source_taglist.name.clone()),
+                (self.source_taglist != self.selected_taglist).then_some(TagListDialogMessage::ConvertTags)
+            ),
+            Space::new().width(Length::Fill),
+            checkbox(self.show_preview).on_toggle(TagListDialogMessage::TogglePreview).size(16),
+            text(fl!("tag-list-sample-preview")).size(TEXT_SIZE_NORMAL),
+        ]
+        .spacing(DIALOG_SPACING)
+        .align_y(icy_ui::Alignment::Center);
+
         let header = row![
             text(fl!("tag-list-preview")).size(TEXT_SIZE_SMALL).width(Length::Fixed(140.0)),
             text(fl!("tag-list-pos")).size(TEXT_SIZE_SMALL).width(Length::Fixed(80.0)),
@@ -106,10 +164,31 @@ impl TagListDialog {
 
         let list = scrollable(rows).width(Length::Fill).height(Length::Fill);
 
+        let issue_text = if self.issues.is_empty() {
+            text(fl!("tag-list-no-issues")).size(TEXT_SIZE_SMALL)
+        } else {
+            let lines: Vec<String> = self.issues.iter().map(|(index, issue)| self.issue_text(*index, issue)).collect();
+            text(lines.join("\n")).size(TEXT_SIZE_SMALL)
+        };
+        let mut issue_column = column![].spacing(4);
+        if let Some(error) = &self.error {
+            issue_column = issue_column.push(text(error).size(TEXT_SIZE_SMALL).style(|theme: &Theme| text::Style {
+                color: Some(theme.destructive.base),
+            }));
+        }
+        issue_column = issue_column.push(issue_text);
+        let issues = scrollable(container(issue_column).padding(4).width(Length::Fill))
+            .width(Length::Fill)
+            .height(Length::Fixed(80.0));
+
         let content = column![
             title,
             Space::new().height(DIALOG_SPACING),
+            effect_box(profile_row.into()),
+            Space::new().height(DIALOG_SPACING),
             effect_box(column![header, container(list).height(Length::Fill).width(Length::Fill)].spacing(6).into()),
+            Space::new().height(DIALOG_SPACING),
+            effect_box(issues.into()),
         ]
         .spacing(0);
 
@@ -0,0 +1,553 @@
+//! Timelapse Dialog
+//!
+//! Replays the edit journal of a document. The slider scrubs through the recorded edits, playback
+//! shows the frames the export would contain. Timelapses are exported as `.icyanim` script with
+//! one `.icy` file per frame, as GIF animation or as PNG frame sequence.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use icy_engine::gif_encoder::{GifEncoder, GifFrame, GifStream, RepeatCount};
+use icy_engine::{FileFormat, ImageFormat, Rectangle, RenderOptions, SaveOptions, TextBuffer, TextPane};
+use icy_engine_edit::{timelapse_script, EditJournal, JournalPlayer, TimelapseStep};
+use icy_engine_gui::settings::effect_box;
+use icy_engine_gui::ui::{
+    button_row, dialog_area, dialog_title, left_label_small, modal_container, primary_button, secondary_button, separator, validated_input_style, Dialog,
+    DialogAction, DIALOG_SPACING, DIALOG_WIDTH_XARGLE, TEXT_SIZE_NORMAL, TEXT_SIZE_SMALL,
+};
+use icy_engine_gui::ButtonType;
+use icy_ui::{
+    widget::{column, container, image, pick_list, progress_bar, row, slider, text, text_input, Space},
+    Alignment, Element, Length, Task,
+};
+use parking_lot::Mutex;
+
+use crate::fl;
+use crate::ui::editor::ansi::AnsiEditorMessage;
+use crate::ui::Message;
+
+/// Size the preview is scaled down to
+const PREVIEW_WIDTH: f32 = 640.0;
+const PREVIEW_HEIGHT: f32 = 320.0;
+
+fn msg(m: TimelapseDialogMessage) -> Message {
+    Message::AnsiEditor(AnsiEditorMessage::TimelapseDialog(m))
+}
+
+/// Unit of the distance between timelapse frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepUnit {
+    Edits,
+    Seconds,
+}
+
+impl std::fmt::Display for StepUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepUnit::Edits => write!(f, "{}", fl!("timelapse-dialog-unit-edits")),
+            StepUnit::Seconds => write!(f, "{}", fl!("timelapse-dialog-unit-seconds")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimelapseFormat {
+    IcyAnim,
+    Gif,
+    PngSequence,
+}
+
+impl TimelapseFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            TimelapseFormat::IcyAnim => "icyanim",
+            TimelapseFormat::Gif => "gif",
+            TimelapseFormat::PngSequence => "png",
+        }
+    }
+}
+
+impl std::fmt::Display for TimelapseFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TimelapseFormat::IcyAnim => write!(f, "{}", fl!("timelapse-dialog-format-icyanim")),
+            TimelapseFormat::Gif => write!(f, "{}", fl!("timelapse-dialog-format-gif")),
+            TimelapseFormat::PngSequence => write!(f, "{}", fl!("timelapse-dialog-format-png")),
+        }
+    }
+}
+
+/// Messages for the Timelapse dialog
+#[derive(Debug, Clone)]
+pub enum TimelapseDialogMessage {
+    /// Move to a journal position
+    Seek(usize),
+    TogglePlay,
+    /// Show the next frame while playing
+    PlayTick,
+    SetStepUnit(StepUnit),
+    SetStepValue(String),
+    SetDelay(String),
+    SetFormat(TimelapseFormat),
+    /// Pick the export file
+    Export,
+    ExportPathSelected(Option<PathBuf>),
+    /// Poll the running export
+    ExportTick,
+    Close,
+}
+
+/// Export state shared with the export thread
+struct ExportProgress {
+    current_frame: AtomicUsize,
+    total_frames: usize,
+    result: Mutex<Option<Result<(), String>>>,
+}
+
+/// State for the Timelapse dialog
+pub struct TimelapseDialog {
+    player: JournalPlayer,
+    /// Rendered document at the current position and its size
+    preview: Option<(image::Handle, u32, u32)>,
+    /// Used as file name suggestion for the export
+    document_name: String,
+    playing: bool,
+    step_unit: StepUnit,
+    step_value: String,
+    delay: String,
+    format: TimelapseFormat,
+    export: Option<Arc<ExportProgress>>,
+    status: Option<Result<String, String>>,
+}
+
+impl TimelapseDialog {
+    pub fn new(journal: EditJournal, document_name: String) -> Result<Self, String> {
+        let player = JournalPlayer::new(journal).map_err(|e| e.to_string())?;
+        let mut dialog = Self {
+            player,
+            preview: None,
+            document_name,
+            playing: false,
+            step_unit: StepUnit::Edits,
+            step_value: "10".to_string(),
+            delay: "100".to_string(),
+            format: TimelapseFormat::Gif,
+            export: None,
+            status: None,
+        };
+        dialog.update_preview();
+        Ok(dialog)
+    }
+
+    fn step(&self) -> Option<TimelapseStep> {
+        let value = self.step_value.trim().parse::<u64>().ok().filter(|v| *v > 0)?;
+        match self.step_unit {
+            StepUnit::Edits => usize::try_from(value).ok().map(TimelapseStep::Edits),
+            StepUnit::Seconds => Some(TimelapseStep::Millis(value.saturating_mul(1000))),
+        }
+    }
+
+    fn delay_ms(&self) -> Option<u32> {
+        self.delay.trim().parse::<u32>().ok().filter(|v| *v > 0)
+    }
+
+    fn positions(&self) -> Option<Vec<usize>> {
+        self.step().map(|step| self.player.journal().timelapse_positions(step))
+    }
+
+    fn is_exporting(&self) -> bool {
+        self.export.is_some()
+    }
+
+    fn update_preview(&mut self) {
+        let (size, rgba) = render_frame(self.player.buffer());
+        self.preview = match (u32::try_from(size.width), u32::try_from(size.height)) {
+            (Ok(width), Ok(height)) if width > 0 && height > 0 && !rgba.is_empty() => Some((image::Handle::from_rgba(width, height, rgba), width, height)),
+            _ => None,
+        };
+    }
+
+    fn seek(&mut self, position: usize) {
+        self.player.seek(position);
+        self.update_preview();
+    }
+
+    fn play_tick_task(&self) -> Task<Message> {
+        let delay = u64::from(self.delay_ms().unwrap_or(100));
+        Task::perform(
+            async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+            },
+            |()| msg(TimelapseDialogMessage::PlayTick),
+        )
+    }
+
+    fn export_tick_task() -> Task<Message> {
+        Task::perform(
+            async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            },
+            |()| msg(TimelapseDialogMessage::ExportTick),
+        )
+    }
+
+    fn pick_export_file(&self) -> DialogAction<Message> {
+        let format = self.format;
+        let file_name = format!("{}_timelapse.{}", self.document_name, format.extension());
+        let task = Task::perform(
+            async move {
+                rfd::AsyncFileDialog::new()
+                    .add_filter(format.to_string(), &[format.extension()])
+                    .set_file_name(file_name)
+                    .save_file()
+                    .await
+                    .map(|h| h.path().to_path_buf())
+            },
+            |path| msg(TimelapseDialogMessage::ExportPathSelected(path)),
+        );
+        DialogAction::RunTask(task)
+    }
+
+    fn start_export(&mut self, path: PathBuf) -> DialogAction<Message> {
+        let (Some(positions), Some(delay_ms)) = (self.positions(), self.delay_ms()) else {
+            return DialogAction::None;
+        };
+        self.playing = false;
+        self.status = None;
+        let progress = Arc::new(ExportProgress {
+            current_frame: AtomicUsize::new(0),
+            total_frames: positions.len(),
+            result: Mutex::new(None),
+        });
+        self.export = Some(progress.clone());
+
+        let journal = self.player.journal().clone();
+        let format = self.format;
+        thread::spawn(move || {
+            let result = export_timelapse(journal, &positions, format, &path, delay_ms, &progress);
+            *progress.result.lock() = Some(result);
+        });
+        DialogAction::RunTask(Self::export_tick_task())
+    }
+
+    fn preview_view(&self) -> Element<'_, Message> {
+        let preview: Element<'_, Message> = match &self.preview {
+            Some((handle, width, height)) => {
+                let (width, height) = (*width as f32, *height as f32);
+                let scale = (PREVIEW_WIDTH / width).min(PREVIEW_HEIGHT / height).min(1.0);
+                image(handle.clone())
+                    .width(Length::Fixed(width * scale))
+                    .height(Length::Fixed(height * scale))
+                    .into()
+            }
+            None => Space::new().into(),
+        };
+        container(preview).center_x(Length::Fill).center_y(Length::Fixed(PREVIEW_HEIGHT)).into()
+    }
+
+    fn playback_view(&self) -> Element<'_, Message> {
+        let len = u32::try_from(self.player.len()).unwrap_or(u32::MAX);
+        let position = u32::try_from(self.player.position()).unwrap_or(u32::MAX);
+        let scrub = slider(0..=len, position, |v| msg(TimelapseDialogMessage::Seek(v as usize))).width(Length::Fill);
+        let play_label = if self.playing {
+            fl!("timelapse-dialog-pause")
+        } else {
+            fl!("timelapse-dialog-play")
+        };
+        let can_play = !self.player.is_empty() && self.positions().is_some() && !self.is_exporting();
+        let play = secondary_button(play_label, can_play.then(|| msg(TimelapseDialogMessage::TogglePlay)));
+        let info = if self.player.is_empty() {
+            fl!("timelapse-dialog-empty")
+        } else {
+            fl!(
+                "timelapse-dialog-position",
+                current = self.player.position(),
+                total = self.player.len(),
+                time = format_time(self.player.time_ms())
+            )
+        };
+        column![
+            row![play, scrub].spacing(DIALOG_SPACING).align_y(Alignment::Center),
+            text(info).size(TEXT_SIZE_SMALL)
+        ]
+        .spacing(DIALOG_SPACING)
+        .into()
+    }
+
+    fn export_settings_view(&self) -> Element<'_, Message> {
+        let step_input = text_input("", &self.step_value)
+            .on_input(|s| msg(TimelapseDialogMessage::SetStepValue(s)))
+            .size(TEXT_SIZE_NORMAL)
+            .width(Length::Fixed(70.0))
+            .style(validated_input_style(self.step().is_some()));
+        let units = vec![StepUnit::Edits, StepUnit::Seconds];
+        let unit_picker = pick_list(units, Some(self.step_unit), |u| msg(TimelapseDialogMessage::SetStepUnit(u))).width(Length::Fixed(180.0));
+        let step_row = row![left_label_small(fl!("timelapse-dialog-frame_every")), step_input, unit_picker]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center);
+
+        let delay_input = text_input("", &self.delay)
+            .on_input(|s| msg(TimelapseDialogMessage::SetDelay(s)))
+            .size(TEXT_SIZE_NORMAL)
+            .width(Length::Fixed(70.0))
+            .style(validated_input_style(self.delay_ms().is_some()));
+        let frames = self.positions().map_or(0, |p| p.len());
+        let delay_row = row![
+            left_label_small(fl!("timelapse-dialog-delay")),
+            delay_input,
+            text(fl!("timelapse-dialog-frames", frames = frames)).size(TEXT_SIZE_SMALL)
+        ]
+        .spacing(DIALOG_SPACING)
+        .align_y(Alignment::Center);
+
+        let formats = vec![TimelapseFormat::IcyAnim, TimelapseFormat::Gif, TimelapseFormat::PngSequence];
+        let format_picker = pick_list(formats, Some(self.format), |f| msg(TimelapseDialogMessage::SetFormat(f))).width(Length::Fixed(260.0));
+        let format_row = row![left_label_small(fl!("timelapse-dialog-format")), format_picker]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center);
+
+        let mut settings = column![step_row, delay_row, format_row].spacing(DIALOG_SPACING);
+        if let Some(progress) = &self.export {
+            let current = progress.current_frame.load(Ordering::Relaxed);
+            let total = progress.total_frames.max(1);
+            settings = settings.push(text(fl!("timelapse-dialog-exporting", current = current, total = progress.total_frames)).size(TEXT_SIZE_SMALL));
+            settings = settings.push(container(progress_bar(0.0..=1.0, current as f32 / total as f32)).height(Length::Fixed(8.0)));
+        }
+        match &self.status {
+            Some(Ok(message)) => settings = settings.push(text(message).size(TEXT_SIZE_SMALL)),
+            Some(Err(error)) => {
+                settings = settings.push(text(error).size(TEXT_SIZE_SMALL).style(|theme: &icy_ui::Theme| icy_ui::widget::text::Style {
+                    color: Some(theme.destructive.base),
+                }));
+            }
+            None => {}
+        }
+        settings.into()
+    }
+}
+
+impl Dialog<Message> for TimelapseDialog {
+    fn view(&self) -> Element<'_, Message> {
+        let title = dialog_title(fl!("timelapse-dialog-title"));
+
+        let content_column = column![
+            self.preview_view(),
+            self.playback_view(),
+            Space::new().height(DIALOG_SPACING),
+            self.export_settings_view()
+        ]
+        .spacing(DIALOG_SPACING);
+        let content_box = effect_box(content_column.into());
+
+        let can_export = !self.player.is_empty() && self.positions().is_some() && self.delay_ms().is_some() && !self.is_exporting();
+        let buttons = button_row(vec![
+            secondary_button(format!("{}", ButtonType::Close), Some(msg(TimelapseDialogMessage::Close))).into(),
+            primary_button(fl!("timelapse-dialog-export"), can_export.then(|| msg(TimelapseDialogMessage::Export))).into(),
+        ]);
+
+        let dialog_content = dialog_area(column![title, Space::new().height(DIALOG_SPACING), content_box].into());
+        let button_area = dialog_area(buttons);
+
+        modal_container(
+            column![container(dialog_content).height(Length::Shrink), separator(), button_area].into(),
+            DIALOG_WIDTH_XARGLE,
+        )
+        .into()
+    }
+
+    fn update(&mut self, message: &Message) -> Option<DialogAction<Message>> {
+        let Message::AnsiEditor(AnsiEditorMessage::TimelapseDialog(dialog_msg)) = message else {
+            return None;
+        };
+        match dialog_msg {
+            TimelapseDialogMessage::Seek(position) => {
+                self.playing = false;
+                self.seek(*position);
+                Some(DialogAction::None)
+            }
+            TimelapseDialogMessage::TogglePlay => {
+                self.playing = !self.playing;
+                if !self.playing {
+                    return Some(DialogAction::None);
+                }
+                if self.player.is_at_end() {
+                    self.seek(0);
+                }
+                Some(DialogAction::RunTask(self.play_tick_task()))
+            }
+            TimelapseDialogMessage::PlayTick => {
+                if !self.playing {
+                    return Some(DialogAction::None);
+                }
+                let current = self.player.position();
+                match self.positions().and_then(|positions| positions.into_iter().find(|p| *p > current)) {
+                    Some(next) => {
+                        self.seek(next);
+                        Some(DialogAction::RunTask(self.play_tick_task()))
+                    }
+                    None => {
+                        self.playing = false;
+                        Some(DialogAction::None)
+                    }
+                }
+            }
+            TimelapseDialogMessage::SetStepUnit(unit) => {
+                if *unit != self.step_unit {
+                    self.step_unit = *unit;
+                    self.step_value = match unit {
+                        StepUnit::Edits => "10".to_string(),
+                        StepUnit::Seconds => "5".to_string(),
+                    };
+                }
+                Some(DialogAction::None)
+            }
+            TimelapseDialogMessage::SetStepValue(value) => {
+                self.step_value = value.clone();
+                Some(DialogAction::None)
+            }
+            TimelapseDialogMessage::SetDelay(value) => {
+                self.delay = value.clone();
+                Some(DialogAction::None)
+            }
+            TimelapseDialogMessage::SetFormat(format) => {
+                self.format = *format;
+                Some(DialogAction::None)
+            }
+            TimelapseDialogMessage::Export => {
+                if self.is_exporting() {
+                    return Some(DialogAction::None);
+                }
+                Some(self.pick_export_file())
+            }
+            TimelapseDialogMessage::ExportPathSelected(path) => match path {
+                Some(path) if !self.is_exporting() => Some(self.start_export(path.clone())),
+                _ => Some(DialogAction::None),
+            },
+            TimelapseDialogMessage::ExportTick => {
+                let result = self.export.as_ref().and_then(|progress| progress.result.lock().take());
+                match result {
+                    Some(result) => {
+                        self.export = None;
+                        self.status = Some(result.map(|()| fl!("timelapse-dialog-exported")));
+                        Some(DialogAction::None)
+                    }
+                    None if self.is_exporting() => Some(DialogAction::RunTask(Self::export_tick_task())),
+                    None => Some(DialogAction::None),
+                }
+            }
+            TimelapseDialogMessage::Close => Some(self.request_cancel()),
+        }
+    }
+
+    fn request_cancel(&mut self) -> DialogAction<Message> {
+        self.playing = false;
+        DialogAction::Close
+    }
+
+    fn request_confirm(&mut self) -> DialogAction<Message> {
+        DialogAction::None
+    }
+}
+
+/// Editing time as `m:ss`
+fn format_time(ms: u64) -> String {
+    let seconds = ms / 1000;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn render_frame(buffer: &TextBuffer) -> (icy_engine::Size, Vec<u8>) {
+    let options = RenderOptions {
+        rect: Rectangle::from_coords(0, 0, buffer.width(), buffer.height()).into(),
+        blink_on: true,
+        ..Default::default()
+    };
+    buffer.render_to_rgba(&options, false)
+}
+
+/// Crops or pads a rendered frame to the size of the first frame, the canvas may have been resized
+fn fit_frame(rgba: Vec<u8>, width: usize, height: usize, target_width: usize, target_height: usize) -> Vec<u8> {
+    if width == target_width && height == target_height {
+        return rgba;
+    }
+    let mut result = vec![0; target_width * target_height * 4];
+    let copy_width = width.min(target_width) * 4;
+    for y in 0..height.min(target_height) {
+        let src = y * width * 4;
+        let dst = y * target_width * 4;
+        result[dst..dst + copy_width].copy_from_slice(&rgba[src..src + copy_width]);
+    }
+    result
+}
+
+/// Path of frame `index` for a timelapse exported to `path`
+fn frame_path(path: &Path, index: usize, extension: &str) -> PathBuf {
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    path.with_file_name(format!("{stem}_{:04}.{extension}", index + 1))
+}
+
+fn export_timelapse(
+    journal: EditJournal,
+    positions: &[usize],
+    format: TimelapseFormat,
+    path: &Path,
+    delay_ms: u32,
+    progress: &ExportProgress,
+) -> Result<(), String> {
+    let write_error =
+        |file: &Path, error: &dyn std::fmt::Display| fl!("timelapse-dialog-write-failed", file = file.display().to_string(), error = error.to_string());
+    let mut player = JournalPlayer::new(journal).map_err(|e| e.to_string())?;
+    let mut frame_files = Vec::new();
+    // Frames are encoded as they are rendered, the GIF takes the size of the first one
+    let mut gif: Option<(GifStream<std::fs::File>, usize, usize)> = None;
+
+    for (i, position) in positions.iter().enumerate() {
+        player.seek(*position);
+        let buffer = player.buffer();
+        match format {
+            TimelapseFormat::IcyAnim => {
+                let frame = frame_path(path, i, "icy");
+                let bytes = FileFormat::IcyDraw.to_bytes(buffer, &SaveOptions::icy_draw()).map_err(|e| e.to_string())?;
+                std::fs::write(&frame, bytes).map_err(|e| write_error(&frame, &e))?;
+                frame_files.push(frame.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default());
+            }
+            TimelapseFormat::Gif => {
+                let (size, rgba) = render_frame(buffer);
+                let (width, height) = (usize::try_from(size.width).unwrap_or(0), usize::try_from(size.height).unwrap_or(0));
+                if gif.is_none() {
+                    let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height)) else {
+                        return Err(fl!("timelapse-dialog-gif-too-large"));
+                    };
+                    if gif_width == 0 || gif_height == 0 {
+                        return Err(fl!("timelapse-dialog-invalid-size"));
+                    }
+                    let mut encoder = GifEncoder::new(gif_width, gif_height);
+                    encoder.set_repeat(RepeatCount::Infinite);
+                    let stream = encoder.start_file(path).map_err(|e| write_error(path, &e))?;
+                    gif = Some((stream, width, height));
+                }
+                if let Some((stream, target_width, target_height)) = &mut gif {
+                    let frame = GifFrame::new(fit_frame(rgba, width, height, *target_width, *target_height), delay_ms);
+                    stream
+                        .write_frame(&frame)
+                        .map_err(|e| fl!("timelapse-dialog-gif-failed", error = e.to_string()))?;
+                }
+            }
+            TimelapseFormat::PngSequence => {
+                ImageFormat::Png.save_buffer(buffer, &frame_path(path, i, "png")).map_err(|e| e.to_string())?;
+            }
+        }
+        progress.current_frame.store(i + 1, Ordering::Relaxed);
+    }
+
+    match format {
+        TimelapseFormat::IcyAnim => std::fs::write(path, timelapse_script(&frame_files, delay_ms)).map_err(|e| write_error(path, &e)),
+        TimelapseFormat::Gif => match gif {
+            Some((stream, _, _)) => stream.finish().map_err(|e| write_error(path, &e)),
+            None => Err(fl!("timelapse-dialog-invalid-size")),
+        },
+        TimelapseFormat::PngSequence => Ok(()),
+    }
+}
@@ -7,15 +7,16 @@ use std::{
 use icy_engine::formats::{FileFormat, LoadData};
 use icy_engine::{BitFont, TextPane};
 use icy_engine_edit::tools::Tool;
-use icy_engine_edit::EditState;
+use icy_engine_edit::{CellPattern, EditJournal, EditState, JournalData, JournalEvent, JournalRecorder, ReplaceScope};
 use icy_engine_gui::theme::main_area_background;
-use icy_engine_gui::ui::DialogStack;
+use icy_engine_gui::ui::{error_dialog, DialogStack};
 use icy_ui::{
     widget::{column, container, pane_grid, row},
     Alignment, Element, Length, Task, Theme,
 };
 use parking_lot::RwLock;
 
+use crate::fl;
 use crate::ui::editor::palette::PaletteEditorDialog;
 use crate::ui::main_window::Message;
 use crate::ui::{LayerMessage, MinimapMessage};
@@ -27,8 +28,9 @@ use crate::ui::widget::paste_controls::{PasteControls, PasteControlsMessage};
 
 use super::{
     constants, tool_registry, tool_session, tools, widget, AnsiEditorCore, AnsiEditorCoreMessage, AnsiEditorMessage, AnsiStatusInfo, ColorSwitcherMessage,
-    EditLayerDialog, FontSlotManagerDialog, PaletteGrid, PaletteGridMessage, ReferenceImageDialog, RightPanel, RightPanelMessage, SetFontDialog,
-    TdfFontSelectorDialog, TdfFontSelectorMessage, ToolPanel, ToolPanelMessage, TopToolbarMessage, RIGHT_PANEL_BASE_WIDTH,
+    EditLayerDialog, FindReplaceDialog, FindReplaceDialogMessage, FontSlotManagerDialog, PaletteGrid, PaletteGridMessage, RecolorDialog, ReferenceImageDialog,
+    RightPanel, RightPanelMessage, ScaleDialog, SetFontDialog, TdfFontSelectorDialog, TdfFontSelectorMessage, TimelapseDialog, ToolPanel, ToolPanelMessage,
+    TopToolbarMessage, RIGHT_PANEL_BASE_WIDTH,
 };
 
 /// Parse a `Tool` enum variant name (as produced by `format!("{tool:?}")`).
@@ -44,7 +46,9 @@ fn parse_tool_name(name: &str) -> Option<Tool> {
         "EllipseFilled" => Some(Tool::EllipseFilled),
         "Pipette" => Some(Tool::Pipette),
         "Fill" => Some(Tool::Fill),
+        "Gradient" => Some(Tool::Gradient),
         "Font" => Some(Tool::Font),
+        "TextBox" => Some(Tool::TextBox),
         "Tag" => Some(Tool::Tag),
         _ => None,
     }
@@ -71,6 +75,10 @@ pub struct AnsiEditorMainArea {
     slot_double_click: RefCell<icy_engine_gui::DoubleClickDetector<usize>>,
     /// Paste controls widget
     paste_controls: PasteControls,
+    /// Whether the recolor dialog shows a preview
+    recolor_preview: bool,
+    /// Edit journal, while recording
+    journal: Option<JournalRecorder>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
@@ -143,13 +151,31 @@ impl AnsiEditorMainArea {
             },
             slot_double_click: RefCell::new(icy_engine_gui::DoubleClickDetector::new()),
             paste_controls: PasteControls::new(),
+            recolor_preview: false,
+            journal: None,
         }
     }
 
     pub fn with_file(path: PathBuf, options: Arc<RwLock<Settings>>, font_library: SharedFontLibrary) -> Result<Self, String> {
         let format = FileFormat::from_path(&path).unwrap_or(FileFormat::Ansi);
         let loaded_doc = format.load(&path, Some(LoadData::default())).map_err(|e| e.to_string())?;
-        Ok(Self::with_buffer(loaded_doc.screen.buffer, Some(path), options, font_library))
+        // Continue an unfinished journal next to the document, if replaying it leads to the loaded document
+        let journal = crate::load_journal(&crate::journal_path(&path)).filter(|journal| {
+            if journal.is_finished() {
+                return false;
+            }
+            let leads_to_document = journal.leads_to(&loaded_doc.screen.buffer);
+            if !leads_to_document {
+                log::warn!("Edit journal of {path:?} doesn't lead to the document, not continuing it");
+            }
+            leads_to_document
+        });
+        let mut editor = Self::with_buffer(loaded_doc.screen.buffer, Some(path), options, font_library);
+        if let Some(journal) = journal {
+            editor.journal = Some(JournalRecorder::new(journal));
+            editor.sync_journal();
+        }
+        Ok(editor)
     }
 
     /// Load from an autosave file, using the original path for file association
@@ -308,6 +334,8 @@ impl AnsiEditorMainArea {
         // Recent paint-char MRU history (#9).
         out.recent_chars = self.core.recent_chars().read().as_slice().to_vec();
 
+        out.symmetry = Some(self.core.get_symmetry());
+
         out
     }
 
@@ -359,10 +387,92 @@ impl AnsiEditorMainArea {
 
         // Recent paint-char MRU history (#9).
         self.core.recent_chars().write().restore(state.recent_chars.clone());
+
+        // Older blobs leave the mirror mode restored from the editor session
+        if let Some(symmetry) = state.symmetry {
+            self.core.with_edit_state(|edit_state| edit_state.set_symmetry(symmetry));
+        }
     }
 
     pub fn save(&mut self, path: &Path) -> Result<(), String> {
-        self.core.save(path)
+        self.core.save(path)?;
+        self.sync_journal();
+        if let Some(recorder) = &mut self.journal {
+            self.core.with_edit_state_readonly(|state| recorder.set_end_document(state.get_buffer()));
+            crate::save_journal(&crate::journal_path(path), recorder.journal())?;
+        }
+        Ok(())
+    }
+
+    pub fn is_recording_journal(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Journal the edits made since the last call, called after each editor update
+    pub fn sync_journal(&mut self) {
+        let Some(recorder) = &mut self.journal else {
+            return;
+        };
+        let mut screen = self.core.screen.lock();
+        if let Some(state) = screen.as_any_mut().downcast_ref::<EditState>() {
+            let undo_stack = state.get_undo_stack();
+            // Use try_lock to avoid potential deadlocks
+            if let Ok(undo_stack) = undo_stack.try_lock() {
+                recorder.sync_from_undo_stack(&undo_stack);
+            }
+        }
+    }
+
+    /// Journal a change of another user, after the local edits made before it
+    fn journal_remote(&mut self, event: impl FnOnce() -> icy_engine::Result<JournalEvent>) {
+        if self.journal.is_none() {
+            return;
+        }
+        self.sync_journal();
+        match event() {
+            Ok(event) => self.journal.as_mut().unwrap().record(event),
+            Err(e) => log::error!("Failed to journal a remote change: {e}"),
+        }
+    }
+
+    /// Starts or stops recording, fails when starting would overwrite a finished journal
+    fn toggle_journal_recording(&mut self) -> Result<(), String> {
+        self.sync_journal();
+        if let Some(recorder) = self.journal.take() {
+            // Finish a saved journal, so opening the document doesn't continue recording
+            if let Some(path) = self.file_path.as_ref().map(|path| crate::journal_path(path)).filter(|path| path.exists()) {
+                let mut journal = recorder.into_journal();
+                journal.finish();
+                if let Err(e) = crate::save_journal(&path, &journal) {
+                    log::error!("Failed to finish the edit journal: {e}");
+                }
+            }
+            return Ok(());
+        }
+        // A finished journal next to the document is continued when it still leads to it, never replaced
+        let journal = match self.file_path.as_ref().map(|path| crate::journal_path(path)).filter(|path| path.exists()) {
+            Some(path) => match crate::load_journal(&path) {
+                Some(mut journal) if self.with_edit_state_readonly(|state| journal.leads_to(state.get_buffer())) => {
+                    journal.resume();
+                    journal
+                }
+                _ => return Err(fl!("journal-error-exists", file = path.display().to_string())),
+            },
+            None => self
+                .with_edit_state_readonly(|state| EditJournal::new(state.get_buffer()))
+                .map_err(|e| fl!("journal-error-start", error = e.to_string()))?,
+        };
+        self.journal = Some(JournalRecorder::new(journal));
+        self.sync_journal();
+        Ok(())
+    }
+
+    /// The journal being recorded, or the one saved next to the document
+    fn current_journal(&self) -> Option<EditJournal> {
+        match &self.journal {
+            Some(recorder) => Some(recorder.journal().clone()),
+            None => self.file_path.as_ref().and_then(|path| crate::load_journal(&crate::journal_path(path))),
+        }
     }
 
     /// Get bytes for autosave (saves in ICY format with thumbnail skipped for performance)
@@ -383,10 +493,6 @@ impl AnsiEditorMainArea {
         }
     }
 
-    pub fn get_mirror_mode(&self) -> bool {
-        self.core.get_mirror_mode()
-    }
-
     /// Get the undo description for menu display
     pub fn undo_description(&self) -> Option<String> {
         self.with_edit_state_readonly(icy_engine_edit::UndoState::undo_description)
@@ -397,9 +503,9 @@ impl AnsiEditorMainArea {
         self.with_edit_state_readonly(icy_engine_edit::UndoState::redo_description)
     }
 
-    /// Get mirror mode state for menu display
-    pub fn mirror_mode(&self) -> bool {
-        self.get_mirror_mode()
+    /// Get symmetry settings for menu display
+    pub fn symmetry(&self) -> icy_engine_edit::Symmetry {
+        self.core.get_symmetry()
     }
 
     pub fn view_menu_state(&self) -> AnsiViewMenuState {
@@ -430,19 +536,166 @@ impl AnsiEditorMainArea {
     }
 
     /// Apply a remote draw operation from collaboration
-    pub fn apply_remote_draw(&mut self, x: i32, y: i32, code: u32, fg: u8, bg: u8) {
+    pub fn apply_remote_draw(&mut self, layer: usize, x: i32, y: i32, block: &icy_engine_edit::collaboration::Block) {
         use icy_engine::TextPane;
         self.core.with_edit_state(|state| {
             let buffer = state.get_buffer_mut();
+            let Some(layer) = buffer.layers.get_mut(layer) else {
+                log::warn!("[COLLAB] Draw on missing layer {layer}");
+                return;
+            };
             let pos = (x, y);
-            let ch = buffer.layers[0].char_at(pos.into());
-            let mut new_ch = ch;
-            new_ch.ch = char::from_u32(code).unwrap_or(' ');
-            new_ch.attribute.set_foreground(fg as u32);
-            new_ch.attribute.set_background(bg as u32);
-            buffer.layers[0].set_char(pos, new_ch);
+            let mut new_ch = layer.char_at(pos.into());
+            new_ch.ch = char::from_u32(block.code).unwrap_or(' ');
+            new_ch.attribute.set_foreground(u32::from(block.fg));
+            new_ch.attribute.set_background(u32::from(block.bg));
+            layer.set_char(pos, new_ch);
+            buffer.mark_dirty();
+        });
+        self.journal_remote(|| {
+            Ok(JournalEvent::SetCell {
+                layer,
+                col: x,
+                row: y,
+                block: block.clone(),
+            })
+        });
+    }
+
+    /// Apply a palette replaced by a remote user (V3).
+    pub fn apply_remote_palette(&mut self, colors: &[[u8; 3]]) {
+        use icy_engine::{Color, Palette};
+        self.core.with_edit_state(|state| {
+            let colors: Vec<Color> = colors.iter().map(|c| (*c).into()).collect();
+            let buffer = state.get_buffer_mut();
+            buffer.palette = Palette::from_slice(&colors);
+            buffer.mark_dirty();
+        });
+        self.journal_remote(|| Ok(JournalEvent::SetPalette { colors: colors.to_vec() }));
+    }
+
+    /// Insert a layer added by a remote user (V3), the current layer stays selected.
+    pub fn apply_remote_layer_added(&mut self, index: usize, layer: icy_engine::Layer) {
+        self.journal_remote(|| {
+            Ok(JournalEvent::AddLayer {
+                index,
+                layer: JournalData::new(&layer)?,
+            })
+        });
+        self.core.with_edit_state(|state| {
+            let current = state.get_current_layer().unwrap_or(0);
+            let buffer = state.get_buffer_mut();
+            let index = index.min(buffer.layers.len());
+            buffer.layers.insert(index, layer);
+            buffer.mark_dirty();
+            state.set_current_layer(if index <= current { current + 1 } else { current });
+        });
+    }
+
+    /// Remove a layer removed by a remote user (V3).
+    pub fn apply_remote_layer_removed(&mut self, index: usize) {
+        self.core.with_edit_state(|state| {
+            let current = state.get_current_layer().unwrap_or(0);
+            let buffer = state.get_buffer_mut();
+            if index >= buffer.layers.len() {
+                return;
+            }
+            buffer.layers.remove(index);
+            buffer.mark_dirty();
+            state.set_current_layer(if index < current { current - 1 } else { current });
+        });
+        self.journal_remote(|| Ok(JournalEvent::RemoveLayer { index }));
+    }
+
+    /// Move a layer reordered by a remote user (V3).
+    pub fn apply_remote_layer_reordered(&mut self, from: usize, to: usize) {
+        self.core.with_edit_state(|state| {
+            let current = state.get_current_layer().unwrap_or(0);
+            let buffer = state.get_buffer_mut();
+            if from >= buffer.layers.len() || to >= buffer.layers.len() {
+                return;
+            }
+            let layer = buffer.layers.remove(from);
+            buffer.layers.insert(to, layer);
+            buffer.mark_dirty();
+            let current = if current == from {
+                to
+            } else if from < current && current <= to {
+                current - 1
+            } else if to <= current && current < from {
+                current + 1
+            } else {
+                current
+            };
+            state.set_current_layer(current);
+        });
+        self.journal_remote(|| Ok(JournalEvent::ReorderLayer { from, to }));
+    }
+
+    /// Apply layer properties changed by a remote user (V3).
+    pub fn apply_remote_layer_properties(&mut self, index: usize, properties: icy_engine::LayerProperties) {
+        self.journal_remote(|| {
+            Ok(JournalEvent::SetLayerProperties {
+                index,
+                properties: JournalData::new(&properties)?,
+            })
+        });
+        self.core.with_edit_state(|state| {
+            let buffer = state.get_buffer_mut();
+            if let Some(layer) = buffer.layers.get_mut(index) {
+                layer.properties = properties;
+                buffer.mark_dirty();
+            }
+        });
+    }
+
+    /// Apply a font slot uploaded or cleared by a remote user (V3).
+    pub fn apply_remote_font_slot(&mut self, slot: u8, font: Option<icy_engine::BitFont>) {
+        self.journal_remote(|| {
+            Ok(JournalEvent::SetFontSlot {
+                slot,
+                font: font.as_ref().map(JournalData::new).transpose()?,
+            })
+        });
+        self.core.with_edit_state(|state| {
+            let buffer = state.get_buffer_mut();
+            match font {
+                Some(font) => buffer.set_font(slot, font),
+                None => {
+                    buffer.remove_font(slot);
+                }
+            }
+            buffer.mark_dirty();
+        });
+    }
+
+    /// Apply tags replaced by a remote user (V3).
+    pub fn apply_remote_tags(&mut self, tags: Vec<icy_engine::Tag>) {
+        self.journal_remote(|| {
+            Ok(JournalEvent::SetTags {
+                tags: JournalData::new(&tags)?,
+            })
+        });
+        self.core.with_edit_state(|state| {
+            let buffer = state.get_buffer_mut();
+            buffer.tags = tags;
+            buffer.mark_dirty();
+        });
+    }
+
+    /// Apply `.icy` document attributes changed by a remote user (V3).
+    pub fn apply_remote_attributes(&mut self, attributes: &icy_engine_edit::collaboration::IcyAttributes) {
+        self.core.with_edit_state(|state| {
+            let buffer = state.get_buffer_mut();
+            attributes.apply_to(buffer);
             buffer.mark_dirty();
         });
+        self.journal_remote(|| {
+            Ok(JournalEvent::SetAttributes {
+                attributes: JournalData::new(attributes)?,
+            })
+        });
+        self.core.update_viewport_size();
     }
 
     /// Apply a full remote document snapshot from collaboration.
@@ -491,18 +744,39 @@ impl AnsiEditorMainArea {
         let colors: Vec<Color> = doc.palette.iter().map(|[r, g, b]| Color::new(*r, *g, *b)).collect();
         buffer.palette = Palette::from_slice(&colors);
 
-        for col in 0..(doc.columns as usize) {
-            for row in 0..(doc.rows as usize) {
-                let block = doc.document.get(col).and_then(|c| c.get(row)).cloned().unwrap_or_default();
+        // V3 servers send the full palette, font slots, tags and attributes
+        if let Some(extended) = &doc.extended {
+            if !extended.palette.is_empty() {
+                let colors: Vec<Color> = extended.palette.iter().map(|c| (*c).into()).collect();
+                buffer.palette = Palette::from_slice(&colors);
+            }
+            for slot in &extended.fonts {
+                buffer.set_font(slot.slot, slot.font.clone());
+            }
+            buffer.tags.clone_from(&extended.tags);
+            if let Some(attributes) = &extended.attributes {
+                attributes.apply_to(&mut buffer);
+            }
+        }
+
+        // A layer stack kept by the server replaces the Moebius document, which only has the first layer
+        let server_layers = doc.extended.as_ref().map(|extended| &extended.layers).filter(|layers| !layers.is_empty());
+        if let Some(layers) = server_layers {
+            buffer.layers.clone_from(layers);
+        } else {
+            for col in 0..(doc.columns as usize) {
+                for row in 0..(doc.rows as usize) {
+                    let block = doc.document.get(col).and_then(|c| c.get(row)).cloned().unwrap_or_default();
 
-                let mut ch = AttributedChar {
-                    ch: char::from_u32(block.code).unwrap_or(' '),
-                    ..Default::default()
-                };
-                ch.attribute.set_foreground(block.fg as u32);
-                ch.attribute.set_background(block.bg as u32);
+                    let mut ch = AttributedChar {
+                        ch: char::from_u32(block.code).unwrap_or(' '),
+                        ..Default::default()
+                    };
+                    ch.attribute.set_foreground(block.fg as u32);
+                    ch.attribute.set_background(block.bg as u32);
 
-                buffer.layers[0].set_char_unchecked(Position::new(col as i32, row as i32), ch);
+                    buffer.layers[0].set_char_unchecked(Position::new(col as i32, row as i32), ch);
+                }
             }
         }
 
@@ -1038,6 +1312,20 @@ impl AnsiEditorMainArea {
         }
     }
 
+    fn mcp_info_to_pattern(info: &crate::mcp::types::CellPatternInfo, buffer_type: icy_engine::BufferType) -> Result<icy_engine_edit::CellPattern, String> {
+        let ch = match &info.ch {
+            Some(ch) => Some(buffer_type.convert_from_unicode(ch.chars().next().ok_or_else(|| "Empty character string".to_string())?)),
+            None => None,
+        };
+        Ok(icy_engine_edit::CellPattern {
+            ch,
+            foreground: info.fg.as_ref().map(Self::mcp_info_to_color),
+            background: info.bg.as_ref().map(Self::mcp_info_to_color),
+            blink: info.blink,
+            font_page: info.font_page,
+        })
+    }
+
     fn mcp_attr_to_info(attr: &icy_engine::TextAttribute) -> crate::mcp::types::TextAttributeInfo {
         crate::mcp::types::TextAttributeInfo {
             foreground: Self::mcp_color_to_info(&attr.foreground_color()),
@@ -1434,6 +1722,55 @@ impl AnsiEditorMainArea {
         Ok(())
     }
 
+    pub fn find_replace(
+        &mut self,
+        find: &crate::mcp::types::CellPatternInfo,
+        replace: Option<&crate::mcp::types::CellPatternInfo>,
+        scope: Option<&str>,
+    ) -> Result<usize, String> {
+        let scope = match scope.map(|s| s.trim().to_lowercase()).as_deref() {
+            None => None,
+            Some("layer") => Some(ReplaceScope::Layer),
+            Some("selection") => Some(ReplaceScope::Selection),
+            Some("document") => Some(ReplaceScope::Document),
+            Some(other) => return Err(format!("Unknown scope '{other}' (selection, layer or document)")),
+        };
+        let count = self.with_edit_state(|state| {
+            let scope = scope.unwrap_or_else(|| state.default_replace_scope());
+            let buffer_type = state.get_buffer().buffer_type;
+            let pattern = Self::mcp_info_to_pattern(find, buffer_type)?;
+            match replace {
+                Some(replace) => {
+                    let replacement = Self::mcp_info_to_pattern(replace, buffer_type)?;
+                    state.replace_cells(&pattern, &replacement, scope).map_err(|e| e.to_string())
+                }
+                None => Ok(state.find_cells(&pattern, scope).len()),
+            }
+        })?;
+        self.sync_ui();
+        Ok(count)
+    }
+
+    /// Highlights the cells matching `pattern` on the canvas, returns the number of matches
+    fn preview_find(&mut self, pattern: &CellPattern, scope: ReplaceScope) -> usize {
+        let cells: Vec<icy_engine::Position> = self.with_edit_state_readonly(|state| {
+            state
+                .find_cells(pattern, scope)
+                .into_iter()
+                .map(|(layer, pos)| pos + state.get_buffer().layers[layer].offset())
+                .collect()
+        });
+        self.core.show_find_matches(&cells);
+        cells.len()
+    }
+
+    /// Removes the recolor dialog's preview
+    fn end_recolor_preview(&mut self) {
+        if std::mem::take(&mut self.recolor_preview) {
+            self.with_edit_state(|state| state.end_color_remap_preview());
+        }
+    }
+
     pub fn update(&mut self, message: AnsiEditorMessage, dialogs: &mut DialogStack<Message>, plugins: &Arc<Vec<Plugin>>) -> Task<AnsiEditorMessage> {
         match message {
             // ═══════════════════════════════════════════════════════════════════
@@ -1470,6 +1807,133 @@ impl AnsiEditorMainArea {
                 });
                 Task::none()
             }
+            AnsiEditorMessage::ShowScaleDialog => {
+                let size = self.with_edit_state_readonly(|state| state.transform_size());
+                dialogs.push(ScaleDialog::new(size));
+                Task::none()
+            }
+            AnsiEditorMessage::ScaleDialog(_) => {
+                // Handled by DialogStack
+                Task::none()
+            }
+            AnsiEditorMessage::ApplyScale(size, mode) => {
+                self.with_edit_state(|state| {
+                    if let Err(e) = state.scale_to_size(size, mode) {
+                        log::error!("Scale failed: {e}");
+                    }
+                });
+                self.sync_ui();
+                Task::none()
+            }
+            AnsiEditorMessage::ShowFindReplaceDialog => {
+                let dialog = self.with_edit_state_readonly(|state| {
+                    let template = state
+                        .get_cur_layer()
+                        .map(|layer| layer.char_at(state.get_caret().position()))
+                        .unwrap_or_default();
+                    let scope = state.default_replace_scope();
+                    FindReplaceDialog::new(state.get_buffer().buffer_type, template, scope)
+                });
+                let count = dialog.pattern().map_or(0, |pattern| self.preview_find(&pattern, dialog.scope()));
+                dialogs.push(dialog);
+                let _ = dialogs.update(&Message::AnsiEditor(AnsiEditorMessage::FindReplaceDialog(
+                    FindReplaceDialogMessage::SetMatchCount(count),
+                )));
+                Task::none()
+            }
+            AnsiEditorMessage::FindReplaceDialog(_) => {
+                // Handled by DialogStack
+                Task::none()
+            }
+            AnsiEditorMessage::FindReplacePreview(pattern, scope) => {
+                let count = self.preview_find(&pattern, scope);
+                let _ = dialogs.update(&Message::AnsiEditor(AnsiEditorMessage::FindReplaceDialog(
+                    FindReplaceDialogMessage::SetMatchCount(count),
+                )));
+                Task::none()
+            }
+            AnsiEditorMessage::ApplyFindReplace(pattern, replacement, scope) => {
+                self.core.clear_tool_overlay();
+                match self.with_edit_state(|state| state.replace_cells(&pattern, &replacement, scope)) {
+                    Ok(0) => {}
+                    Ok(_) => {
+                        self.mark_modified();
+                        self.sync_ui();
+                    }
+                    Err(e) => log::error!("Find and replace failed: {e}"),
+                }
+                Task::none()
+            }
+            AnsiEditorMessage::FindReplaceClosed => {
+                self.core.clear_tool_overlay();
+                Task::none()
+            }
+            AnsiEditorMessage::ShowRecolorDialog => {
+                let dialog = self.with_edit_state_readonly(|state| {
+                    let used_colors = [ReplaceScope::Selection, ReplaceScope::Layer, ReplaceScope::Document]
+                        .into_iter()
+                        .map(|scope| (scope, state.used_colors(scope)))
+                        .collect();
+                    let scope = state.default_replace_scope();
+                    RecolorDialog::new(state.get_buffer().palette.clone(), used_colors, scope)
+                });
+                dialogs.push(dialog);
+                Task::none()
+            }
+            AnsiEditorMessage::RecolorDialog(_) => {
+                // Handled by DialogStack
+                Task::none()
+            }
+            AnsiEditorMessage::RecolorPreview(remap, scope) => {
+                self.with_edit_state(|state| state.preview_color_remap(&remap, scope));
+                self.recolor_preview = true;
+                Task::none()
+            }
+            AnsiEditorMessage::ApplyRecolor(remap, scope) => {
+                self.end_recolor_preview();
+                match self.with_edit_state(|state| state.remap_colors(&remap, scope)) {
+                    Ok(count) if count > 0 || remap.palette.is_some() => {
+                        self.mark_modified();
+                        self.sync_ui();
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::error!("Recolor failed: {e}"),
+                }
+                Task::none()
+            }
+            AnsiEditorMessage::RecolorClosed => {
+                self.end_recolor_preview();
+                Task::none()
+            }
+            AnsiEditorMessage::ToggleJournalRecording => {
+                if let Err(err) = self.toggle_journal_recording() {
+                    dialogs.push(error_dialog(fl!("journal-error-title"), err, |_| Message::CloseDialog));
+                }
+                Task::none()
+            }
+            AnsiEditorMessage::ShowTimelapseDialog => {
+                let journal = match self.current_journal() {
+                    Some(journal) => Ok(journal),
+                    None => self.with_edit_state_readonly(|state| EditJournal::new(state.get_buffer())),
+                };
+                let document_name = self
+                    .file_path
+                    .as_ref()
+                    .and_then(|path| path.file_stem())
+                    .map_or_else(|| "untitled".to_string(), |stem| stem.to_string_lossy().to_string());
+                match journal
+                    .map_err(|e| e.to_string())
+                    .and_then(|journal| TimelapseDialog::new(journal, document_name))
+                {
+                    Ok(dialog) => dialogs.push(dialog),
+                    Err(e) => log::error!("Failed to open the edit journal: {e}"),
+                }
+                Task::none()
+            }
+            AnsiEditorMessage::TimelapseDialog(_) => {
+                // Handled by DialogStack
+                Task::none()
+            }
             AnsiEditorMessage::Core(AnsiEditorCoreMessage::TopToolbar(TopToolbarMessage::OpenFontSelector)) => {
                 // Open TDF font dialog from TopToolbar
                 let dialog = TdfFontSelectorDialog::new(self.font_tool_library(), self.font_tool_selected_font());
@@ -1561,8 +2025,6 @@ impl AnsiEditorMainArea {
                 if let Some(plugin) = plugins.get(id) {
                     let plugin = plugin.clone();
                     if let Err(err) = plugin.run_plugin(self.screen()) {
-                        use crate::fl;
-                        use icy_engine_gui::ui::error_dialog;
                         dialogs.push(error_dialog(fl!("error-plugin-title"), format!("{err}"), |_| Message::CloseDialog));
                     }
                 }
@@ -1733,6 +2195,11 @@ impl AnsiEditorMainArea {
                             LayerMessage::Duplicate(idx) => self.core.update(AnsiEditorCoreMessage::DuplicateLayer(idx)).map(AnsiEditorMessage::Core),
                             LayerMessage::MergeDown(idx) => self.core.update(AnsiEditorCoreMessage::MergeLayerDown(idx)).map(AnsiEditorMessage::Core),
                             LayerMessage::Clear(idx) => self.core.update(AnsiEditorCoreMessage::ClearLayer(idx)).map(AnsiEditorMessage::Core),
+                            LayerMessage::Group(idx) => self.core.update(AnsiEditorCoreMessage::GroupLayer(idx)).map(AnsiEditorMessage::Core),
+                            LayerMessage::Ungroup(idx) => self.core.update(AnsiEditorCoreMessage::UngroupLayer(idx)).map(AnsiEditorMessage::Core),
+                            LayerMessage::ToggleCollapsed(idx) => {
+                                self.core.update(AnsiEditorCoreMessage::ToggleGroupCollapsed(idx)).map(AnsiEditorMessage::Core)
+                            }
                             LayerMessage::Rename(_idx, _name) => Task::none(),
                             // Paste mode messages - forward to TopToolbar paste actions
                             LayerMessage::PasteKeepAsLayer => self
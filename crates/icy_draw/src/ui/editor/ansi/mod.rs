@@ -45,10 +45,14 @@ use icy_engine_gui::TerminalMessage;
 
 pub use dialog::edit_layer::*;
 pub use dialog::file_settings::*;
+pub use dialog::find_replace::*;
 pub use dialog::font_selector::*;
 pub use dialog::font_slot_manager::*;
+pub use dialog::recolor::*;
 pub use dialog::reference_image::*;
+pub use dialog::scale::*;
 pub use dialog::tdf_font_selector::{TdfFontSelectorDialog, TdfFontSelectorMessage};
+pub use dialog::timelapse::*;
 
 pub use main_area::AnsiEditorMainArea;
 pub use main_area::AnsiViewMenuState;
@@ -118,6 +122,12 @@ pub enum AnsiEditorCoreMessage {
     MergeLayerDown(usize),
     /// Clear layer contents
     ClearLayer(usize),
+    /// Put a layer into a new group
+    GroupLayer(usize),
+    /// Dissolve a group, keeping its members
+    UngroupLayer(usize),
+    /// Collapse or expand a group in the layer list
+    ToggleGroupCollapsed(usize),
     /// Scroll viewport
     ScrollViewport(f32, f32),
 
@@ -130,7 +140,10 @@ pub enum AnsiEditorCoreMessage {
     ToggleRaster,
     ToggleLineNumbers,
     ToggleLayerBorders,
-    ToggleMirrorMode,
+    SetSymmetryMode(icy_engine_edit::brushes::MirrorMode),
+    /// Move the symmetry axes through the caret cell
+    SetSymmetryAxisToCaret,
+    CenterSymmetryAxis,
 
     // --- Area Operations ---
     JustifyLineLeft,
@@ -163,6 +176,9 @@ pub enum AnsiEditorCoreMessage {
     JustifyCenter,
     JustifyLeft,
     JustifyRight,
+    RotateCw,
+    RotateCcw,
+    Rotate180,
 
     // --- Color Operations ---
     NextFgColor,
@@ -219,6 +235,33 @@ pub enum AnsiEditorMessage {
     EditLayerDialog(EditLayerDialogMessage),
     ApplyEditLayer(EditLayerResult),
 
+    // --- Scale Dialog ---
+    ShowScaleDialog,
+    ScaleDialog(ScaleDialogMessage),
+    ApplyScale(icy_engine::Size, icy_engine_edit::ScaleMode),
+
+    // --- Find and Replace Dialog ---
+    ShowFindReplaceDialog,
+    FindReplaceDialog(FindReplaceDialogMessage),
+    /// Highlight the matches of the dialog's search criteria
+    FindReplacePreview(icy_engine_edit::CellPattern, icy_engine_edit::ReplaceScope),
+    ApplyFindReplace(icy_engine_edit::CellPattern, icy_engine_edit::CellPattern, icy_engine_edit::ReplaceScope),
+    FindReplaceClosed,
+
+    // --- Recolor Dialog ---
+    ShowRecolorDialog,
+    RecolorDialog(RecolorDialogMessage),
+    /// Show the dialog's color remap on the canvas
+    RecolorPreview(icy_engine_edit::ColorRemap, icy_engine_edit::ReplaceScope),
+    ApplyRecolor(icy_engine_edit::ColorRemap, icy_engine_edit::ReplaceScope),
+    RecolorClosed,
+
+    // --- Edit Journal ---
+    /// Start or stop recording the edit journal
+    ToggleJournalRecording,
+    ShowTimelapseDialog,
+    TimelapseDialog(TimelapseDialogMessage),
+
     // --- Palette Dialog ---
     EditPalette,
     PaletteEditorDialog(crate::ui::editor::palette::PaletteEditorMessage),
@@ -14,9 +14,9 @@ pub const ANSI_TOOL_SLOTS: &[ToolPair] = &[
     ToolPair::single(Tool::Line),
     ToolPair::new(Tool::RectangleOutline, Tool::RectangleFilled),
     ToolPair::new(Tool::EllipseOutline, Tool::EllipseFilled),
-    ToolPair::single(Tool::Fill),
+    ToolPair::new(Tool::Fill, Tool::Gradient),
     ToolPair::single(Tool::Pipette),
-    ToolPair::single(Tool::Font),
+    ToolPair::new(Tool::Font, Tool::TextBox),
     ToolPair::single(Tool::Tag),
 ];
 
@@ -29,9 +29,9 @@ pub const NETWORK_TOOL_SLOTS: &[ToolPair] = &[
     ToolPair::single(Tool::Line),
     ToolPair::new(Tool::RectangleOutline, Tool::RectangleFilled),
     ToolPair::new(Tool::EllipseOutline, Tool::EllipseFilled),
-    ToolPair::single(Tool::Fill),
+    ToolPair::new(Tool::Fill, Tool::Gradient),
     ToolPair::single(Tool::Pipette),
-    ToolPair::single(Tool::Font),
+    ToolPair::new(Tool::Font, Tool::TextBox),
 ];
 
 /// Tool slots for the `CharFont` editor (no Tag tool)
@@ -42,7 +42,7 @@ pub const CHARFONT_TOOL_SLOTS: &[ToolPair] = &[
     ToolPair::single(Tool::Line),
     ToolPair::new(Tool::RectangleOutline, Tool::RectangleFilled),
     ToolPair::new(Tool::EllipseOutline, Tool::EllipseFilled),
-    ToolPair::single(Tool::Fill),
+    ToolPair::new(Tool::Fill, Tool::Gradient),
     ToolPair::single(Tool::Pipette),
     ToolPair::single(Tool::Font),
 ];
@@ -79,8 +79,10 @@ impl ToolRegistry {
         let mut needs_pencil = false;
         let mut needs_shape = false;
         let mut needs_fill = false;
+        let mut needs_gradient = false;
         let mut needs_pipette = false;
         let mut needs_font = false;
+        let mut needs_text_box = false;
         let mut needs_tag = false;
 
         for pair in slots {
@@ -91,8 +93,10 @@ impl ToolRegistry {
                     Tool::Pencil => needs_pencil = true,
                     Tool::Line | Tool::RectangleOutline | Tool::RectangleFilled | Tool::EllipseOutline | Tool::EllipseFilled => needs_shape = true,
                     Tool::Fill => needs_fill = true,
+                    Tool::Gradient => needs_gradient = true,
                     Tool::Pipette => needs_pipette = true,
                     Tool::Font => needs_font = true,
+                    Tool::TextBox => needs_text_box = true,
                     Tool::Tag => needs_tag = true,
                 }
             }
@@ -128,6 +132,11 @@ impl ToolRegistry {
             tools.insert(fill.as_any().type_id(), fill);
         }
 
+        if needs_gradient {
+            let gradient = Box::new(tools::GradientTool::new()) as Box<dyn ToolHandler>;
+            tools.insert(gradient.as_any().type_id(), gradient);
+        }
+
         if needs_pipette {
             let pipette = Box::new(tools::PipetteTool::new()) as Box<dyn ToolHandler>;
             tools.insert(pipette.as_any().type_id(), pipette);
@@ -138,6 +147,11 @@ impl ToolRegistry {
             tools.insert(font.as_any().type_id(), font);
         }
 
+        if needs_text_box {
+            let text_box = Box::new(tools::TextBoxTool::new()) as Box<dyn ToolHandler>;
+            tools.insert(text_box.as_any().type_id(), text_box);
+        }
+
         if needs_tag {
             let tag = Box::new(tools::TagTool::new()) as Box<dyn ToolHandler>;
             tools.insert(tag.as_any().type_id(), tag);
@@ -234,7 +248,9 @@ impl ToolRegistry {
             ToolId::Pencil => TypeId::of::<tools::PencilTool>(),
             ToolId::Pipette => TypeId::of::<tools::PipetteTool>(),
             ToolId::Fill => TypeId::of::<tools::FillTool>(),
+            ToolId::Gradient => TypeId::of::<tools::GradientTool>(),
             ToolId::Font => TypeId::of::<tools::FontTool>(),
+            ToolId::TextBox => TypeId::of::<tools::TextBoxTool>(),
             ToolId::Tag => TypeId::of::<tools::TagTool>(),
             ToolId::Line | ToolId::RectangleOutline | ToolId::RectangleFilled | ToolId::EllipseOutline | ToolId::EllipseFilled => {
                 TypeId::of::<tools::ShapeTool>()
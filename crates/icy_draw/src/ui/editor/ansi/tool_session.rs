@@ -8,7 +8,7 @@
 //! The state is serialized as a versioned bitcode enum and stored in
 //! `AnsiEditorSessionState::tool_state_blob`.
 
-use icy_engine_edit::tools::Tool;
+use icy_engine_edit::{tools::Tool, Symmetry};
 use serde::{Deserialize, Serialize};
 
 use super::tools::BrushSettings;
@@ -99,6 +99,47 @@ pub struct AnsiToolSessionState {
     /// keep working across runs.
     #[serde(default)]
     pub recent_chars: Vec<char>,
+
+    /// Symmetry mode and axis. `None` for migrated blobs, which only know the
+    /// `mirror_mode` flag of the editor session.
+    #[serde(default)]
+    pub symmetry: Option<Symmetry>,
+}
+
+/// Legacy V2 payload, written before the symmetry mode and axis were persisted.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct AnsiToolSessionStateV2 {
+    #[serde(default)]
+    pub selected_tool: Tool,
+
+    #[serde(default)]
+    pub brush: BrushSessionState,
+
+    #[serde(default)]
+    pub shape: ShapeSessionState,
+
+    #[serde(default)]
+    pub selection_mode: SelectionMode,
+
+    #[serde(default)]
+    pub font_slot: usize,
+
+    #[serde(default)]
+    pub recent_chars: Vec<char>,
+}
+
+impl From<AnsiToolSessionStateV2> for AnsiToolSessionState {
+    fn from(v2: AnsiToolSessionStateV2) -> Self {
+        Self {
+            selected_tool: v2.selected_tool,
+            brush: v2.brush,
+            shape: v2.shape,
+            selection_mode: v2.selection_mode,
+            font_slot: v2.font_slot,
+            recent_chars: v2.recent_chars,
+            symmetry: None,
+        }
+    }
 }
 
 /// Legacy V1 payload used before the explicit session-version wrapper existed.
@@ -135,6 +176,7 @@ impl From<AnsiToolSessionStateV1> for AnsiToolSessionState {
             selection_mode: v1.selection_mode,
             font_slot: v1.font_slot,
             recent_chars: vec![v1.brush.paint_char],
+            symmetry: None,
         }
     }
 }
@@ -144,22 +186,25 @@ impl From<AnsiToolSessionStateV1> for AnsiToolSessionState {
 /// New code always writes the newest variant. Decoding accepts every historical
 /// format we know about:
 ///
-/// 1. `SessionVersion::V2(current)` — current explicit wrapper.
-/// 2. `SessionVersion::V1(legacy)` — explicit V1 wrapper, migrated to V2.
-/// 3. Bare `AnsiToolSessionState` — short-lived unversioned V2 blobs created
+/// 1. `SessionVersion::V3(current)` — current explicit wrapper.
+/// 2. `SessionVersion::V2(legacy)` — explicit V2 wrapper without symmetry, migrated to V3.
+/// 3. `SessionVersion::V1(legacy)` — explicit V1 wrapper, migrated to V3.
+/// 4. Bare `AnsiToolSessionStateV2` — short-lived unversioned V2 blobs created
 ///    before #7.
-/// 4. Bare `AnsiToolSessionStateV1` — older unversioned blobs.
+/// 5. Bare `AnsiToolSessionStateV1` — older unversioned blobs.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SessionVersion {
     V1(AnsiToolSessionStateV1),
-    V2(AnsiToolSessionState),
+    V2(AnsiToolSessionStateV2),
+    V3(AnsiToolSessionState),
 }
 
 impl SessionVersion {
     fn into_current(self) -> AnsiToolSessionState {
         match self {
             SessionVersion::V1(v1) => v1.into(),
-            SessionVersion::V2(v2) => v2,
+            SessionVersion::V2(v2) => v2.into(),
+            SessionVersion::V3(v3) => v3,
         }
     }
 }
@@ -168,7 +213,7 @@ impl AnsiToolSessionState {
     /// Encode to a versioned bitcode blob suitable for
     /// `AnsiEditorSessionState::tool_state_blob`.
     pub fn encode(&self) -> Vec<u8> {
-        bitcode::serialize(&SessionVersion::V2(self.clone())).unwrap_or_default()
+        bitcode::serialize(&SessionVersion::V3(self.clone())).unwrap_or_default()
     }
 
     /// Decode from a versioned bitcode blob, with legacy unversioned fallback.
@@ -185,8 +230,8 @@ impl AnsiToolSessionState {
         // Backward compatibility for unversioned blobs produced before #7.
         // Try the richer short-lived V2 shape first so #9 `recent_chars` data
         // is preserved if present, then fall back to the older V1 shape.
-        if let Ok(current) = bitcode::deserialize::<AnsiToolSessionState>(blob) {
-            return Some(current);
+        if let Ok(v2) = bitcode::deserialize::<AnsiToolSessionStateV2>(blob) {
+            return Some(v2.into());
         }
 
         bitcode::deserialize::<AnsiToolSessionStateV1>(blob).ok().map(Into::into)
@@ -196,9 +241,20 @@ impl AnsiToolSessionState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use icy_engine_edit::{brushes::MirrorMode, Position};
 
-    fn sample_v2() -> AnsiToolSessionState {
+    fn sample_v3() -> AnsiToolSessionState {
         AnsiToolSessionState {
+            symmetry: Some(Symmetry {
+                mode: MirrorMode::Both,
+                axis: Some(Position::new(21, 9)),
+            }),
+            ..sample_v2().into()
+        }
+    }
+
+    fn sample_v2() -> AnsiToolSessionStateV2 {
+        AnsiToolSessionStateV2 {
             selected_tool: Tool::Pencil,
             brush: BrushSessionState {
                 primary: BrushPrimaryMode::Char,
@@ -246,27 +302,41 @@ mod tests {
     }
 
     #[test]
-    fn encode_writes_explicit_v2_envelope() {
-        let state = sample_v2();
+    fn encode_writes_explicit_v3_envelope() {
+        let state = sample_v3();
         let blob = state.encode();
 
         let versioned = bitcode::deserialize::<SessionVersion>(&blob).expect("expected versioned envelope");
         match versioned {
-            SessionVersion::V2(decoded) => {
+            SessionVersion::V3(decoded) => {
                 assert_same_core_fields(&decoded, &state);
                 assert_eq!(decoded.recent_chars, state.recent_chars);
+                assert_eq!(decoded.symmetry, state.symmetry);
             }
-            SessionVersion::V1(_) => panic!("new encoder must not write V1"),
+            SessionVersion::V1(_) | SessionVersion::V2(_) => panic!("new encoder must not write V1 or V2"),
         }
     }
 
     #[test]
-    fn decode_current_v2_round_trips_recent_chars() {
-        let state = sample_v2();
-        let decoded = AnsiToolSessionState::decode(&state.encode()).expect("decode current v2");
+    fn decode_current_v3_round_trips_symmetry_and_recent_chars() {
+        let state = sample_v3();
+        let decoded = AnsiToolSessionState::decode(&state.encode()).expect("decode current v3");
 
         assert_same_core_fields(&decoded, &state);
         assert_eq!(decoded.recent_chars, vec!['█', '▓', '▒']);
+        assert_eq!(decoded.symmetry, state.symmetry);
+    }
+
+    #[test]
+    fn decode_explicit_v2_migrates_to_current() {
+        let legacy = sample_v2();
+        let blob = bitcode::serialize(&SessionVersion::V2(legacy.clone())).expect("serialize explicit v2");
+
+        let decoded = AnsiToolSessionState::decode(&blob).expect("decode migrated v2");
+
+        assert_same_core_fields(&decoded, &legacy.clone().into());
+        assert_eq!(decoded.recent_chars, legacy.recent_chars);
+        assert_eq!(decoded.symmetry, None);
     }
 
     #[test]
@@ -291,7 +361,7 @@ mod tests {
 
         let decoded = AnsiToolSessionState::decode(&blob).expect("decode legacy bare v2");
 
-        assert_same_core_fields(&decoded, &state);
+        assert_same_core_fields(&decoded, &state.clone().into());
         assert_eq!(decoded.recent_chars, state.recent_chars);
     }
 
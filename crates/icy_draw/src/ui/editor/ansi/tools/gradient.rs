@@ -0,0 +1,269 @@
+//! Gradient Tool
+//!
+//! Drag from the start to the end point to fill the selection - or without one the
+//! flood region under the start point - with a linear, radial or angular gradient.
+//! The pattern mode repeats a tile copied from a selection instead.
+
+use icy_engine::{AttributedChar, Position};
+use icy_engine_edit::brushes::{Gradient, GradientShape, SHADE_RAMP};
+use icy_engine_gui::TerminalMessage;
+use icy_ui::widget::{button, container, row, text, toggler, Space};
+use icy_ui::{Element, Length, Theme};
+
+use super::{ToolContext, ToolHandler, ToolId, ToolMessage, ToolResult, ToolViewContext};
+use crate::ui::editor::ansi::widget::segmented_control::gpu::{Segment, SegmentedControlMessage, ShaderSegmentedControl};
+
+/// What the gradient tool fills with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GradientMode {
+    #[default]
+    Linear,
+    Radial,
+    Angular,
+    /// Repeat the captured tile
+    Pattern,
+}
+
+impl GradientMode {
+    fn shape(self) -> GradientShape {
+        match self {
+            GradientMode::Linear | GradientMode::Pattern => GradientShape::Linear,
+            GradientMode::Radial => GradientShape::Radial,
+            GradientMode::Angular => GradientShape::Angular,
+        }
+    }
+}
+
+/// Gradient tool state
+pub struct GradientTool {
+    mode: GradientMode,
+    /// Ramp colors (palette indices), empty uses caret foreground to background
+    colors: Vec<u32>,
+    /// Blend between the colors with the shade characters
+    shades: bool,
+    dither: bool,
+    /// Flood region matches attributes too
+    exact: bool,
+    /// Tile for the pattern mode
+    tile: Option<Vec<Vec<AttributedChar>>>,
+
+    /// Drag start and end (document coordinates)
+    start_pos: Option<Position>,
+    current_pos: Option<Position>,
+
+    mode_control: ShaderSegmentedControl,
+}
+
+impl GradientTool {
+    pub fn new() -> Self {
+        Self {
+            mode: GradientMode::default(),
+            colors: Vec::new(),
+            shades: true,
+            dither: false,
+            exact: false,
+            tile: None,
+            start_pos: None,
+            current_pos: None,
+            mode_control: ShaderSegmentedControl::new(),
+        }
+    }
+
+    fn gradient(&self, caret_fg: u32, caret_bg: u32) -> Gradient {
+        let colors = if self.colors.is_empty() {
+            vec![caret_bg, caret_fg]
+        } else {
+            self.colors.clone()
+        };
+        let chars: &[char] = if self.shades { &SHADE_RAMP } else { &[] };
+        Gradient::from_ramp(self.mode.shape(), &colors, chars, self.dither)
+    }
+
+    fn capture_tile(&mut self, ctx: &ToolContext) {
+        let Some(selection) = ctx.state.selection() else {
+            return;
+        };
+        let Some(layer) = ctx.state.get_cur_layer() else {
+            return;
+        };
+        let area = selection.as_rectangle() - layer.offset();
+        if !area.is_empty() {
+            self.tile = Some(icy_engine_edit::chars_from_area(layer, area));
+        }
+    }
+}
+
+impl ToolHandler for GradientTool {
+    fn id(&self) -> ToolId {
+        ToolId::Gradient
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn handle_message(&mut self, ctx: &mut ToolContext<'_>, msg: &ToolMessage) -> ToolResult {
+        match *msg {
+            ToolMessage::GradientSetMode(mode) => self.mode = mode,
+            ToolMessage::GradientToggleShades(v) => self.shades = v,
+            ToolMessage::GradientToggleDither(v) => self.dither = v,
+            ToolMessage::FillToggleExact(v) => self.exact = v,
+            ToolMessage::GradientAddColor => {
+                let fg = ctx.state.get_caret().attribute.foreground();
+                self.colors.push(fg);
+            }
+            ToolMessage::GradientClearColors => self.colors.clear(),
+            ToolMessage::GradientCaptureTile => self.capture_tile(ctx),
+            _ => {}
+        }
+        ToolResult::None
+    }
+
+    fn handle_terminal_message(&mut self, ctx: &mut ToolContext, msg: &TerminalMessage) -> ToolResult {
+        match msg {
+            TerminalMessage::Press(evt) => {
+                let Some(pos) = evt.text_position else {
+                    return ToolResult::None;
+                };
+                self.start_pos = Some(pos);
+                self.current_pos = Some(pos);
+                ToolResult::StartCapture
+            }
+
+            TerminalMessage::Drag(evt) => {
+                if self.start_pos.is_some() {
+                    if let Some(pos) = evt.text_position {
+                        self.current_pos = Some(pos);
+                    }
+                }
+                ToolResult::None
+            }
+
+            TerminalMessage::Release(evt) => {
+                let (Some(start), Some(end)) = (self.start_pos.take(), evt.text_position.or(self.current_pos.take())) else {
+                    return ToolResult::EndCapture;
+                };
+                self.current_pos = None;
+                let Some(offset) = ctx.state.get_cur_layer().map(|layer| layer.offset()) else {
+                    return ToolResult::EndCapture;
+                };
+                let (start, end) = (start - offset, end - offset);
+                let cells = ctx.state.fill_region(start, self.exact);
+
+                let result = if self.mode == GradientMode::Pattern {
+                    let Some(tile) = &self.tile else {
+                        return ToolResult::EndCapture.and(ToolResult::Status("Select a block and capture it as tile first".to_string()));
+                    };
+                    ctx.state.fill_pattern(&cells, tile, start)
+                } else {
+                    let caret = ctx.state.get_caret().attribute;
+                    let gradient = self.gradient(caret.foreground(), caret.background());
+                    ctx.state.fill_gradient(&cells, &gradient, start, end)
+                };
+                match result {
+                    Ok(()) => ToolResult::EndCapture.and(ToolResult::Commit("Gradient fill".to_string())),
+                    Err(e) => {
+                        log::warn!("Gradient fill failed: {e}");
+                        ToolResult::EndCapture.and(ToolResult::Status(format!("Gradient fill failed: {e}")))
+                    }
+                }
+            }
+
+            _ => ToolResult::None,
+        }
+    }
+
+    fn cancel_capture(&mut self) {
+        self.start_pos = None;
+        self.current_pos = None;
+    }
+
+    fn view_toolbar(&self, ctx: &ToolViewContext) -> Element<'_, ToolMessage> {
+        let segments = vec![
+            Segment::text("Linear", GradientMode::Linear),
+            Segment::text("Radial", GradientMode::Radial),
+            Segment::text("Angular", GradientMode::Angular),
+            Segment::text("Pattern", GradientMode::Pattern),
+        ];
+        let mode_control = self.mode_control.view(segments, self.mode, ctx.font.clone(), &ctx.theme).map(|msg| match msg {
+            SegmentedControlMessage::Selected(m) | SegmentedControlMessage::Toggled(m) | SegmentedControlMessage::CharClicked(m) => {
+                ToolMessage::GradientSetMode(m)
+            }
+        });
+
+        let mut content = row![Space::new().width(Length::Fill), mode_control, Space::new().width(Length::Fixed(16.0))]
+            .spacing(4)
+            .align_y(icy_ui::Alignment::Center);
+
+        if self.mode == GradientMode::Pattern {
+            let tile_info = match &self.tile {
+                Some(tile) => format!("Tile {}x{}", tile.first().map_or(0, Vec::len), tile.len()),
+                None => "No tile".to_string(),
+            };
+            content = content.push(text(tile_info).size(12)).push(
+                button(text("Use selection").size(12))
+                    .padding([2, 8])
+                    .style(icy_ui::widget::button::text_style)
+                    .on_press(ToolMessage::GradientCaptureTile),
+            );
+        } else {
+            let colors = if self.colors.is_empty() {
+                vec![ctx.caret_bg, ctx.caret_fg]
+            } else {
+                self.colors.clone()
+            };
+            for color in colors {
+                let (r, g, b) = ctx.palette.color(color).rgb();
+                content = content.push(
+                    container(Space::new().width(Length::Fixed(12.0)).height(Length::Fixed(12.0))).style(move |_theme: &Theme| container::Style {
+                        background: Some(icy_ui::Background::Color(icy_ui::Color::from_rgb8(r, g, b))),
+                        border: icy_ui::Border {
+                            color: icy_ui::Color::from_rgb8(80, 80, 80),
+                            width: 1.0,
+                            radius: 2.0.into(),
+                        },
+                        ..Default::default()
+                    }),
+                );
+            }
+            content = content
+                .push(
+                    button(text("+ FG").size(12))
+                        .padding([2, 8])
+                        .style(icy_ui::widget::button::text_style)
+                        .on_press(ToolMessage::GradientAddColor),
+                )
+                .push(
+                    button(text("Clear").size(12))
+                        .padding([2, 8])
+                        .style(icy_ui::widget::button::text_style)
+                        .on_press(ToolMessage::GradientClearColors),
+                )
+                .push(Space::new().width(Length::Fixed(16.0)))
+                .push(toggler(self.shades).label("Shades").on_toggle(ToolMessage::GradientToggleShades).text_size(11))
+                .push(toggler(self.dither).label("Dither").on_toggle(ToolMessage::GradientToggleDither).text_size(11));
+        }
+
+        content
+            .push(Space::new().width(Length::Fixed(16.0)))
+            .push(toggler(self.exact).label("Exact").on_toggle(ToolMessage::FillToggleExact).text_size(11))
+            .push(Space::new().width(Length::Fill))
+            .into()
+    }
+
+    fn cursor(&self) -> icy_ui::mouse::Interaction {
+        icy_ui::mouse::Interaction::Crosshair
+    }
+
+    fn show_caret(&self) -> bool {
+        false
+    }
+
+    fn show_selection(&self) -> bool {
+        true
+    }
+}
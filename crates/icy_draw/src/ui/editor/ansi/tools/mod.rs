@@ -38,6 +38,7 @@
 mod click;
 mod fill;
 mod font;
+mod gradient;
 mod outline_click;
 pub mod paint;
 mod paste;
@@ -46,10 +47,12 @@ mod pipette;
 mod select;
 mod shape;
 mod tag;
+mod text_box;
 
 pub use click::ClickTool;
 pub use fill::FillTool;
 pub use font::FontTool;
+pub use gradient::{GradientMode, GradientTool};
 pub use outline_click::OutlineClickTool;
 pub use paint::{new_shared_brush, new_shared_recent_chars, BrushSettings, RecentChars, SharedBrush, SharedRecentChars, RECENT_CHARS_CAPACITY};
 pub use paste::{PasteAction, PasteTool};
@@ -58,6 +61,7 @@ pub use pipette::PipetteTool;
 pub use select::SelectTool;
 pub use shape::ShapeTool;
 pub use tag::{TagTool, TagToolState};
+pub use text_box::TextBoxTool;
 
 use icy_engine::Position;
 use icy_engine::{BitFont, Palette, TextPane};
@@ -239,7 +243,9 @@ pub enum ToolId {
     EllipseFilled,
     Pipette,
     Fill,
+    Gradient,
     Font,
+    TextBox,
     Tag,
     /// Editor-only paste mode (no corresponding engine `Tool`).
     Paste,
@@ -260,7 +266,9 @@ impl ToolId {
             ToolId::EllipseFilled => Some(Tool::EllipseFilled),
             ToolId::Pipette => Some(Tool::Pipette),
             ToolId::Fill => Some(Tool::Fill),
+            ToolId::Gradient => Some(Tool::Gradient),
             ToolId::Font => Some(Tool::Font),
+            ToolId::TextBox => Some(Tool::TextBox),
             ToolId::Tag => Some(Tool::Tag),
             ToolId::Paste => None,
         }
@@ -280,7 +288,9 @@ impl From<Tool> for ToolId {
             Tool::EllipseFilled => ToolId::EllipseFilled,
             Tool::Pipette => ToolId::Pipette,
             Tool::Fill => ToolId::Fill,
+            Tool::Gradient => ToolId::Gradient,
             Tool::Font => ToolId::Font,
+            Tool::TextBox => ToolId::TextBox,
             Tool::Tag => ToolId::Tag,
         }
     }
@@ -328,6 +338,20 @@ pub enum ToolMessage {
     /// Toggle exact matching
     FillToggleExact(bool),
 
+    // === Gradient Tool ===
+    /// Set gradient shape or pattern mode
+    GradientSetMode(GradientMode),
+    /// Toggle blending with shade characters
+    GradientToggleShades(bool),
+    /// Toggle ordered dithering
+    GradientToggleDither(bool),
+    /// Append the caret foreground to the color ramp
+    GradientAddColor,
+    /// Clear the color ramp
+    GradientClearColors,
+    /// Use the selected block as pattern tile
+    GradientCaptureTile,
+
     // === Font Tool ===
     /// Select font slot (0-9)
     FontSelectSlot(usize),
@@ -340,6 +364,14 @@ pub enum ToolMessage {
     /// Open outline selector popup
     FontOpenOutlineSelector,
 
+    // === Text Box Tool ===
+    /// Set the alignment of the text box lines
+    TextBoxSetAlignment(icy_engine_edit::TextAlignment),
+    /// Write the text box to the layer
+    TextBoxCommit,
+    /// Discard the text box
+    TextBoxCancel,
+
     // === Click Tool / F-Key Toolbar ===
     ClickFKeyToolbar(FKeyToolbarMessage),
 
@@ -370,6 +402,18 @@ pub enum ToolMessage {
     SelectNone,
     /// Invert selection
     SelectInvert,
+    /// Set the magic wand match criteria
+    SelectSetWandMatch(icy_engine_edit::CellMatch),
+    /// Toggle magic wand between contiguous and global
+    SelectToggleWandGlobal(bool),
+    /// Select all cells using the caret foreground color
+    SelectCaretColor,
+    /// Grow the selection by one cell
+    SelectGrow,
+    /// Shrink the selection by one cell
+    SelectShrink,
+    /// Keep only the outer cells of the selection
+    SelectBorder,
 
     // === Paste Tool (floating layer) ===
     PasteStamp,
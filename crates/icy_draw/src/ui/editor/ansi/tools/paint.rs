@@ -122,6 +122,28 @@ pub fn new_shared_recent_chars() -> SharedRecentChars {
     Arc::new(RwLock::new(RecentChars::new()))
 }
 
+/// Draw target writing to the current layer of `state` (layer-local coordinates).
+pub(super) struct LayerTarget<'a> {
+    pub state: &'a mut EditState,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl icy_engine_edit::brushes::DrawTarget for LayerTarget<'_> {
+    fn width(&self) -> i32 {
+        self.width
+    }
+    fn height(&self) -> i32 {
+        self.height
+    }
+    fn char_at(&self, pos: icy_engine_edit::Position) -> Option<icy_engine_edit::AttributedChar> {
+        self.state.get_cur_layer().map(|l| l.char_at(pos))
+    }
+    fn set_char(&mut self, pos: icy_engine_edit::Position, ch: icy_engine_edit::AttributedChar) {
+        let _ = self.state.set_char_in_atomic(pos, ch);
+    }
+}
+
 pub fn begin_paint_undo(state: &mut EditState, desc: String) -> AtomicUndoGuard {
     state.begin_atomic_undo(desc)
 }
@@ -142,7 +164,8 @@ pub fn apply_stamp_at_doc_pos(state: &mut EditState, settings: BrushSettings, do
     // Shapes use Shift as erase/clear, so we don't use Shift-swap here.
     let shift_swap = false;
 
-    let swap_for_colors = (swap_colors || shift_swap) && !matches!(settings.primary, BrushPrimaryMode::Shading | BrushPrimaryMode::Char);
+    let swap_for_colors =
+        (swap_colors || shift_swap) && !matches!(settings.primary, BrushPrimaryMode::Shading | BrushPrimaryMode::Char | BrushPrimaryMode::BoxLine);
     let (fg, bg) = if swap_for_colors {
         (caret_attr.background(), caret_attr.foreground())
     } else {
@@ -175,7 +198,7 @@ pub fn apply_stamp_at_doc_pos(state: &mut EditState, settings: BrushSettings, do
             }
 
             let brush_mode = match settings.primary {
-                BrushPrimaryMode::Char => {
+                BrushPrimaryMode::Char | BrushPrimaryMode::BoxLine => {
                     if swap_colors {
                         EngineBrushMode::Char(' ')
                     } else {
@@ -207,26 +230,6 @@ pub fn apply_stamp_at_doc_pos(state: &mut EditState, settings: BrushSettings, do
             // ColorMode for engine: Both means it will apply both colors from template
             let color_mode = EngineColorMode::Both;
 
-            struct LayerTarget<'a> {
-                state: &'a mut EditState,
-                width: i32,
-                height: i32,
-            }
-            impl icy_engine_edit::brushes::DrawTarget for LayerTarget<'_> {
-                fn width(&self) -> i32 {
-                    self.width
-                }
-                fn height(&self) -> i32 {
-                    self.height
-                }
-                fn char_at(&self, pos: icy_engine_edit::Position) -> Option<icy_engine_edit::AttributedChar> {
-                    self.state.get_cur_layer().map(|l| l.char_at(pos))
-                }
-                fn set_char(&mut self, pos: icy_engine_edit::Position, ch: icy_engine_edit::AttributedChar) {
-                    let _ = self.state.set_char_in_atomic(pos, ch);
-                }
-            }
-
             let mut template = caret_attr;
             template.attr &= !icy_engine::attribute::INVISIBLE;
             template.set_foreground(effective_fg);
@@ -281,7 +284,7 @@ pub fn compute_preview_color(settings: &BrushSettings, caret_fg: u32, caret_bg:
 
     // For shape preview, use FG color (what will be painted)
     // In modes that swap colors on right-click, show the swapped color
-    let swap_for_colors = swap_colors && !matches!(settings.primary, BrushPrimaryMode::Shading | BrushPrimaryMode::Char);
+    let swap_for_colors = swap_colors && !matches!(settings.primary, BrushPrimaryMode::Shading | BrushPrimaryMode::Char | BrushPrimaryMode::BoxLine);
 
     let (fg_idx, bg_idx) = if swap_for_colors { (caret_bg, caret_fg) } else { (caret_fg, caret_bg) };
 
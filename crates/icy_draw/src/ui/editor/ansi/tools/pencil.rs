@@ -305,7 +305,11 @@ impl ToolHandler for PencilTool {
 
     fn view_toolbar(&self, _ctx: &ToolViewContext) -> Element<'_, ToolMessage> {
         let settings = *self.brush.read();
-        let primary = settings.primary;
+        // Box lines only exist for the shape tools, the pencil paints the char instead
+        let primary = match settings.primary {
+            BrushPrimaryMode::BoxLine => BrushPrimaryMode::Char,
+            primary => primary,
+        };
         let segments = vec![
             Segment::text("Half Block", BrushPrimaryMode::HalfBlock),
             Segment::char(settings.paint_char, BrushPrimaryMode::Char),
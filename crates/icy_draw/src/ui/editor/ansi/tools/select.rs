@@ -2,10 +2,11 @@
 //!
 //! Rectangle selection with move/resize support.
 //! Supports add (Shift), remove (Ctrl), and replace modes.
+//! The magic wand selects matching cells, either connected to the clicked one or everywhere.
 
 use icy_engine::{AddType, Position, Rectangle, Selection, TextPane};
 use icy_engine_gui::TerminalMessage;
-use icy_ui::widget::{row, text, Space};
+use icy_ui::widget::{button, row, text, toggler, Space};
 use icy_ui::Element;
 
 use super::{ToolContext, ToolHandler, ToolId, ToolMessage, ToolResult, ToolViewContext};
@@ -13,7 +14,10 @@ use crate::ui::editor::ansi::selection_drag::{compute_dragged_selection, hit_tes
 use crate::ui::editor::ansi::widget::segmented_control::gpu::{Segment, SegmentedControlMessage, ShaderSegmentedControl};
 use crate::ui::editor::ansi::widget::toolbar::top::{SelectionMode, SelectionModifier};
 use icy_engine_edit::tools::Tool;
-use icy_engine_edit::AtomicUndoGuard;
+use icy_engine_edit::{AtomicUndoGuard, CellMatch};
+
+/// Magic wand tolerance change per click
+const TOLERANCE_STEP: u8 = 16;
 
 /// Select tool state
 #[derive(Default)]
@@ -35,6 +39,10 @@ pub struct SelectTool {
     modifier: SelectionModifier,
     /// Atomic undo guard for selection drag operations
     selection_undo: Option<AtomicUndoGuard>,
+    /// What the magic wand compares
+    wand_match: CellMatch,
+    /// Magic wand selects all matching cells instead of the connected region
+    wand_global: bool,
 }
 
 impl SelectTool {
@@ -114,6 +122,31 @@ impl ToolHandler for SelectTool {
                 let _ = ctx.state.inverse_selection();
                 ToolResult::Redraw
             }
+            ToolMessage::SelectSetWandMatch(wand_match) => {
+                self.wand_match = wand_match;
+                ToolResult::None
+            }
+            ToolMessage::SelectToggleWandGlobal(global) => {
+                self.wand_global = global;
+                ToolResult::None
+            }
+            ToolMessage::SelectCaretColor => {
+                let color = ctx.state.get_caret().attribute.foreground_color();
+                let _ = ctx.state.select_color(color, AddType::Default);
+                ToolResult::Redraw
+            }
+            ToolMessage::SelectGrow => {
+                let _ = ctx.state.grow_selection(1);
+                ToolResult::Redraw
+            }
+            ToolMessage::SelectShrink => {
+                let _ = ctx.state.shrink_selection(1);
+                ToolResult::Redraw
+            }
+            ToolMessage::SelectBorder => {
+                let _ = ctx.state.border_selection(1);
+                ToolResult::Redraw
+            }
             _ => ToolResult::None,
         }
     }
@@ -163,6 +196,9 @@ impl ToolHandler for SelectTool {
                             ctx.state
                                 .enumerate_selections(|_, ch, _| self.modifier.get_response(ch.attribute.background() == cur_ch.attribute.background()));
                         }
+                        SelectionMode::MagicWand => {
+                            let _ = ctx.state.magic_wand_select(pos, self.wand_match, !self.wand_global, self.selection_add_type());
+                        }
                         SelectionMode::Normal => {}
                     }
                     return ToolResult::Redraw;
@@ -320,6 +356,7 @@ impl ToolHandler for SelectTool {
             Segment::text("Attr", SelectionMode::Attribute),
             Segment::text("Fg", SelectionMode::Foreground),
             Segment::text("Bg", SelectionMode::Background),
+            Segment::text("Wand", SelectionMode::MagicWand),
         ];
 
         let segmented_control = self
@@ -331,18 +368,88 @@ impl ToolHandler for SelectTool {
                 }
             });
 
-        row![
+        let mut content = row![
             Space::new().width(icy_ui::Length::Fill),
             segmented_control,
-            Space::new().width(icy_ui::Length::Fixed(16.0)),
-            text("⇧: add   ⌃/Ctrl: remove")
-                .size(14)
-                .style(|theme: &icy_ui::Theme| text::Style { color: Some(theme.button.on) }),
-            Space::new().width(icy_ui::Length::Fill),
+            Space::new().width(icy_ui::Length::Fixed(16.0))
         ]
         .spacing(8)
-        .align_y(icy_ui::Alignment::Center)
-        .into()
+        .align_y(icy_ui::Alignment::Center);
+
+        if mode == SelectionMode::MagicWand {
+            let m = self.wand_match;
+            content = content
+                .push(
+                    toggler(m.ch)
+                        .label("Char")
+                        .on_toggle(move |ch| ToolMessage::SelectSetWandMatch(CellMatch { ch, ..m }))
+                        .text_size(11),
+                )
+                .push(
+                    toggler(m.foreground)
+                        .label("Fg")
+                        .on_toggle(move |foreground| ToolMessage::SelectSetWandMatch(CellMatch { foreground, ..m }))
+                        .text_size(11),
+                )
+                .push(
+                    toggler(m.background)
+                        .label("Bg")
+                        .on_toggle(move |background| ToolMessage::SelectSetWandMatch(CellMatch { background, ..m }))
+                        .text_size(11),
+                )
+                .push(
+                    toggler(m.font_page)
+                        .label("Font")
+                        .on_toggle(move |font_page| ToolMessage::SelectSetWandMatch(CellMatch { font_page, ..m }))
+                        .text_size(11),
+                )
+                .push(
+                    button(text("-").size(12))
+                        .padding([2, 6])
+                        .style(icy_ui::widget::button::text_style)
+                        .on_press(ToolMessage::SelectSetWandMatch(CellMatch {
+                            tolerance: m.tolerance.saturating_sub(TOLERANCE_STEP),
+                            ..m
+                        })),
+                )
+                .push(text(format!("Tolerance {}", m.tolerance)).size(12))
+                .push(
+                    button(text("+").size(12))
+                        .padding([2, 6])
+                        .style(icy_ui::widget::button::text_style)
+                        .on_press(ToolMessage::SelectSetWandMatch(CellMatch {
+                            tolerance: m.tolerance.saturating_add(TOLERANCE_STEP),
+                            ..m
+                        })),
+                )
+                .push(
+                    toggler(self.wand_global)
+                        .label("Global")
+                        .on_toggle(ToolMessage::SelectToggleWandGlobal)
+                        .text_size(11),
+                );
+        } else {
+            content = content.push(
+                text("⇧: add   ⌃/Ctrl: remove")
+                    .size(14)
+                    .style(|theme: &icy_ui::Theme| text::Style { color: Some(theme.button.on) }),
+            );
+        }
+
+        let op_button = |label: &'static str, msg: ToolMessage| {
+            button(text(label).size(12))
+                .padding([2, 8])
+                .style(icy_ui::widget::button::text_style)
+                .on_press(msg)
+        };
+        content
+            .push(Space::new().width(icy_ui::Length::Fixed(16.0)))
+            .push(op_button("Color", ToolMessage::SelectCaretColor))
+            .push(op_button("Grow", ToolMessage::SelectGrow))
+            .push(op_button("Shrink", ToolMessage::SelectShrink))
+            .push(op_button("Border", ToolMessage::SelectBorder))
+            .push(Space::new().width(icy_ui::Length::Fill))
+            .into()
     }
 
     fn cursor(&self) -> icy_ui::mouse::Interaction {
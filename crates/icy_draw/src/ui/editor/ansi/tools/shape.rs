@@ -6,13 +6,14 @@
 //! This tool handles: Line, `RectangleOutline`, `RectangleFilled`, `EllipseOutline`, `EllipseFilled`
 
 use icy_engine::{MouseButton, Position, TextPane};
-use icy_engine_edit::AttributedChar;
+use icy_engine_edit::brushes::{self, BoxCharset, BoxLineStyle};
+use icy_engine_edit::{AttributedChar, Symmetry};
 use icy_engine_gui::TerminalMessage;
 use icy_ui::keyboard::key::Physical;
 use icy_ui::widget::{button, row, svg, text, toggler, Space};
 use icy_ui::{Element, Length, Theme};
 
-use super::paint::{apply_stamp_at_doc_pos, begin_paint_undo, BrushSettings, SharedBrush};
+use super::paint::{apply_stamp_at_doc_pos, begin_paint_undo, BrushSettings, LayerTarget, SharedBrush};
 use super::{ToolContext, ToolHandler, ToolId, ToolMessage, ToolResult, ToolViewContext, UiAction};
 use crate::ui::editor::ansi::shape_points::shape_points;
 use crate::ui::editor::ansi::widget::segmented_control::gpu::{Segment, SegmentedControlMessage, ShaderSegmentedControl};
@@ -130,8 +131,26 @@ impl ShapeTool {
         start: Position,
         end: Position,
         color: (u8, u8, u8), // RGB paint color
+        symmetry: Option<(Symmetry, Position)>,
+        box_lines: bool,
+    ) -> (Option<(Vec<u8>, u32, u32)>, Option<(f32, f32, f32, f32)>) {
+        let points = if box_lines && has_box_lines(tool) {
+            box_shape_points(tool, start, end)
+        } else {
+            shape_points(tool, start, end)
+        };
+        let points = with_mirrored(points, symmetry);
+        Self::overlay_mask_for_cells(font_width, font_height, &points, color)
+    }
+
+    /// Generate an overlay mask covering `points` (character cells) with a semi-transparent `color`.
+    /// Returns (`rgba_data`, `mask_rect`) for the overlay.
+    pub fn overlay_mask_for_cells(
+        font_width: f32,
+        font_height: f32,
+        points: &[Position],
+        color: (u8, u8, u8),
     ) -> (Option<(Vec<u8>, u32, u32)>, Option<(f32, f32, f32, f32)>) {
-        let points = shape_points(tool, start, end);
         if points.is_empty() {
             return (None, None);
         }
@@ -159,7 +178,7 @@ impl ShapeTool {
         let mut rgba = vec![0u8; (w * h * 4) as usize];
 
         // Fill cells that are part of the shape
-        for point in &points {
+        for point in points {
             // Cell position relative to bounding box
             let rel_x = point.x - min_x;
             let rel_y = point.y - min_y;
@@ -197,8 +216,9 @@ impl ShapeTool {
         start: Position,     // half-block coordinates (Y has 2x resolution)
         end: Position,       // half-block coordinates (Y has 2x resolution)
         color: (u8, u8, u8), // RGB paint color
+        symmetry: Option<(Symmetry, Position)>,
     ) -> (Option<(Vec<u8>, u32, u32)>, Option<(f32, f32, f32, f32)>) {
-        let points = shape_points(tool, start, end);
+        let points = with_mirrored(shape_points(tool, start, end), symmetry);
         if points.is_empty() {
             return (None, None);
         }
@@ -256,6 +276,64 @@ impl ShapeTool {
     }
 }
 
+/// Shapes that can be drawn as box drawing lines
+fn has_box_lines(tool: Tool) -> bool {
+    matches!(tool, Tool::Line | Tool::RectangleOutline)
+}
+
+/// Cell path of `tool` in box line mode, lines step orthogonally so consecutive cells join
+fn box_shape_points(tool: Tool, p0: Position, p1: Position) -> Vec<Position> {
+    if tool == Tool::Line {
+        brushes::get_box_line_points(p0, p1)
+    } else {
+        brushes::get_box_rectangle_points(p0, p1)
+    }
+}
+
+/// Draws (or erases) `points` in document coordinates as box drawing lines on the current layer.
+///
+/// The line weights come from the outline style, so the outline selector picks single or double frames.
+fn apply_box_lines(ctx: &mut ToolContext<'_>, points: Vec<Position>, erase: bool) {
+    let Some(charset) = BoxCharset::from_buffer_type(ctx.state.get_buffer().buffer_type) else {
+        return;
+    };
+    let Some((offset, width, height)) = ctx.state.get_cur_layer().map(|l| (l.offset(), l.width(), l.height())) else {
+        return;
+    };
+    let use_selection = ctx.state.is_something_selected();
+    // The selection cuts the path into pieces that are drawn separately, so the cells around a gap don't join
+    let paths: Vec<Vec<Position>> = points
+        .split(|p| use_selection && !ctx.state.is_selected(*p))
+        .filter(|path| !path.is_empty())
+        .map(|path| path.iter().map(|p| *p - offset).collect())
+        .collect();
+    let style = BoxLineStyle::from_outline_style(ctx.options.map_or(0, |o| o.read().font_outline_style) as u8);
+    let mut attribute = ctx.state.get_caret().attribute;
+    attribute.attr &= !icy_engine::attribute::INVISIBLE;
+
+    let mut target = LayerTarget {
+        state: ctx.state,
+        width,
+        height,
+    };
+    for path in paths {
+        if erase {
+            brushes::erase_box_lines(&mut target, charset, &path, AttributedChar::invisible());
+        } else {
+            brushes::draw_box_lines(&mut target, charset, style, &path, attribute);
+        }
+    }
+}
+
+/// Adds the symmetry copies of `points`, the axis is given in half units of the points
+fn with_mirrored(mut points: Vec<Position>, symmetry: Option<(Symmetry, Position)>) -> Vec<Position> {
+    if let Some((symmetry, axis)) = symmetry {
+        let mirrored: Vec<Position> = points.iter().flat_map(|p| symmetry.mirror_positions(*p, axis)).map(|(p, _, _)| p).collect();
+        points.extend(mirrored);
+    }
+    points
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct ShapeDragSnapshot {
     pub start_pos: Position,
@@ -345,8 +423,11 @@ impl ToolHandler for ShapeTool {
 
     fn view_toolbar(&self, ctx: &ToolViewContext) -> Element<'_, ToolMessage> {
         let settings = *self.brush.read();
-        let primary = settings.primary;
-        let segments = vec![
+        let primary = match settings.primary {
+            BrushPrimaryMode::BoxLine if !has_box_lines(self.tool) => BrushPrimaryMode::Char,
+            primary => primary,
+        };
+        let mut segments = vec![
             Segment::text("Half Block", BrushPrimaryMode::HalfBlock),
             Segment::char(settings.paint_char, BrushPrimaryMode::Char),
             Segment::text("Shade", BrushPrimaryMode::Shading),
@@ -354,6 +435,9 @@ impl ToolHandler for ShapeTool {
             Segment::text("Blink", BrushPrimaryMode::Blink),
             Segment::text("Colorize", BrushPrimaryMode::Colorize),
         ];
+        if has_box_lines(self.tool) {
+            segments.push(Segment::text("Box", BrushPrimaryMode::BoxLine));
+        }
 
         let font_for_color_filter = ctx.font.clone();
         let segmented_control = self
@@ -537,7 +621,9 @@ impl ToolHandler for ShapeTool {
                     let primary = settings.primary;
                     let is_half_block_mode = matches!(primary, BrushPrimaryMode::HalfBlock);
 
-                    if is_half_block_mode {
+                    if primary == BrushPrimaryMode::BoxLine && has_box_lines(self.tool) {
+                        apply_box_lines(_ctx, box_shape_points(self.tool, start, end), self.clear_mode);
+                    } else if is_half_block_mode {
                         let (Some(start_hb), Some(end_hb)) = (self.start_half_block, self.current_half_block) else {
                             self.undo = None;
                             return ToolResult::EndCapture;
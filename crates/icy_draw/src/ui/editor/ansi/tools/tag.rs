@@ -24,7 +24,7 @@ use crate::ui::editor::ansi::dialog::tag::TagDialogMessage;
 use crate::ui::editor::ansi::dialog::tag_list::TagListDialog;
 use crate::ui::editor::ansi::dialog::tag_list::TagListDialogMessage;
 use crate::ui::editor::ansi::dialog::tag_list::TagListItem;
-use crate::util::{get_available_taglists, load_taglist};
+use crate::util::{get_available_taglists, load_taglist, TagReplacementList};
 use icy_engine::{Tag, TagPlacement, TagRole, TextPane};
 /// Consolidated state for the Tag tool system.
 ///
@@ -118,10 +118,69 @@ impl TagToolState {
     pub fn open_list_dialog(&mut self, state: &EditState) {
         // Avoid stacked modals.
         self.dialog = None;
-        self.list_dialog = Some(TagListDialog::new(Self::snapshot_tags(state)));
+        let available_taglists = get_available_taglists(self.taglists_dir.as_deref());
+        let selected = available_taglists
+            .iter()
+            .find(|t| t.id.eq_ignore_ascii_case(&self.selected_taglist))
+            .or_else(|| available_taglists.first())
+            .cloned()
+            .unwrap_or_default();
+        let mut dialog = TagListDialog::new(Self::snapshot_tags(state), available_taglists, selected);
+        dialog.show_preview = state.get_buffer().display_code_preview().is_some();
+        self.list_dialog = Some(dialog);
+        self.refresh_list_dialog(state);
+    }
+
+    /// Re-reads the tags and validates them against the selected profile
+    fn refresh_list_dialog(&mut self, state: &EditState) {
+        let Some(dialog) = self.list_dialog.as_mut() else {
+            return;
+        };
+        dialog.items = Self::snapshot_tags(state);
+        let profile = load_taglist(&dialog.selected_taglist.id, self.taglists_dir.as_deref()).display_code_profile();
+        dialog.issues = profile.validate(state.get_buffer());
     }
 
-    pub fn handle_list_dialog_message(&mut self, state: &mut EditState, msg: TagListDialogMessage) -> ToolResult {
+    /// Translates all display code tags from the `from` taglist into the `to` taglist in one undo step
+    ///
+    /// Codes without an equivalent are kept and show up in the validation. Returns the number of
+    /// converted tags and the errors of the tags that failed.
+    fn convert_tags(state: &mut EditState, from: &TagReplacementList, to: &TagReplacementList) -> (usize, Vec<String>) {
+        let converted: Vec<(usize, Tag)> = state
+            .get_buffer()
+            .tags
+            .iter()
+            .enumerate()
+            .filter(|(_, tag)| tag.tag_role == TagRole::Displaycode)
+            .filter_map(|(index, tag)| {
+                let code = from.translate_code(&tag.replacement_value, to)?;
+                (code != tag.replacement_value).then(|| {
+                    let mut tag = tag.clone();
+                    tag.replacement_value = code;
+                    (index, tag)
+                })
+            })
+            .collect();
+        if converted.is_empty() {
+            return (0, Vec::new());
+        }
+        let converted_count = converted.len();
+        let _undo = state.begin_atomic_undo(fl!("tag-list-convert-undo", bbs = to.name.clone()));
+        let mut errors = Vec::new();
+        for (index, tag) in converted {
+            if let Err(err) = state.update_tag(tag, index) {
+                errors.push(format!("#{} {err}", index + 1));
+            }
+        }
+        (converted_count - errors.len(), errors)
+    }
+
+    pub fn handle_list_dialog_message(
+        &mut self,
+        state: &mut EditState,
+        msg: TagListDialogMessage,
+        settings: Option<&Arc<RwLock<crate::Settings>>>,
+    ) -> ToolResult {
         match msg {
             TagListDialogMessage::Close => {
                 self.list_dialog = None;
@@ -130,7 +189,10 @@ impl TagToolState {
             TagListDialogMessage::Delete(index) => {
                 if let Err(err) = state.remove_tag(index) {
                     log::warn!("Failed to remove tag: {err}");
-                    return ToolResult::None;
+                    if let Some(dialog) = self.list_dialog.as_mut() {
+                        dialog.error = Some(fl!("tag-list-delete-failed", error = err.to_string()));
+                    }
+                    return ToolResult::Redraw;
                 }
 
                 // Keep selection consistent.
@@ -142,12 +204,62 @@ impl TagToolState {
                 }
 
                 // Refresh dialog contents.
-                if let Some(dialog) = self.list_dialog.as_mut() {
-                    dialog.items = Self::snapshot_tags(state);
-                }
+                self.refresh_list_dialog(state);
 
                 ToolResult::Commit("Remove tag".to_string())
             }
+            TagListDialogMessage::SelectTaglist(info) => {
+                let Some(dialog) = self.list_dialog.as_mut() else {
+                    return ToolResult::None;
+                };
+                let taglist = load_taglist(&info.id, self.taglists_dir.as_deref());
+                dialog.selected_taglist = info;
+                dialog.error = None;
+
+                // Store in state so it persists for the next dialog and the export
+                self.selected_taglist = taglist.id.clone();
+                if let Some(settings) = settings {
+                    settings.write().selected_taglist = taglist.id.clone();
+                    settings.read().store_persistent();
+                }
+
+                if state.get_buffer().display_code_preview().is_some() {
+                    state.get_buffer_mut().set_display_code_preview(Some(taglist.display_code_profile()));
+                }
+                self.refresh_list_dialog(state);
+                ToolResult::Redraw
+            }
+            TagListDialogMessage::ConvertTags => {
+                let Some(dialog) = self.list_dialog.as_mut() else {
+                    return ToolResult::None;
+                };
+                let from = load_taglist(&dialog.source_taglist.id, self.taglists_dir.as_deref());
+                let to = load_taglist(&dialog.selected_taglist.id, self.taglists_dir.as_deref());
+                let (converted, errors) = Self::convert_tags(state, &from, &to);
+                if errors.is_empty() {
+                    dialog.source_taglist = dialog.selected_taglist.clone();
+                    dialog.error = None;
+                } else {
+                    log::warn!("Failed to convert tags: {}", errors.join(", "));
+                    dialog.error = Some(fl!("tag-list-convert-failed", count = errors.len(), error = errors.join(", ")));
+                }
+                self.refresh_list_dialog(state);
+                if converted > 0 {
+                    ToolResult::Commit(format!("Convert {converted} tag(s)"))
+                } else {
+                    ToolResult::Redraw
+                }
+            }
+            TagListDialogMessage::TogglePreview(show) => {
+                let Some(dialog) = self.list_dialog.as_mut() else {
+                    return ToolResult::None;
+                };
+                dialog.show_preview = show;
+                state
+                    .get_buffer_mut()
+                    .set_display_code_preview(show.then(|| load_taglist(&dialog.selected_taglist.id, self.taglists_dir.as_deref()).display_code_profile()));
+                ToolResult::Redraw
+            }
         }
     }
 
@@ -0,0 +1,313 @@
+//! Text Box Tool
+//!
+//! Drag a rectangle, then type or paste text into it. The text wraps to the box width and
+//! reflows when the box is resized with its bottom right corner cell. The box stays
+//! editable until it is committed with Ctrl+Enter, by starting a new box or by switching tools.
+
+use icy_engine::{Position, Rectangle};
+use icy_engine_edit::{EditState, TextAlignment, TextBox};
+use icy_engine_gui::TerminalMessage;
+use icy_ui::widget::{button, row, text, Space};
+use icy_ui::{Element, Length};
+
+use super::{ToolContext, ToolHandler, ToolId, ToolMessage, ToolResult, ToolViewContext};
+use crate::ui::editor::ansi::widget::segmented_control::gpu::{Segment, SegmentedControlMessage, ShaderSegmentedControl};
+
+/// What the current mouse drag does
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DragMode {
+    /// Spans a new box from the given layer position
+    Create(Position),
+    /// Moves the bottom right corner of the box
+    Resize,
+}
+
+/// Text box tool state
+pub struct TextBoxTool {
+    text_box: Option<TextBox>,
+    alignment: TextAlignment,
+    drag: Option<DragMode>,
+    alignment_control: ShaderSegmentedControl,
+}
+
+impl TextBoxTool {
+    pub fn new() -> Self {
+        Self {
+            text_box: None,
+            alignment: TextAlignment::default(),
+            drag: None,
+            alignment_control: ShaderSegmentedControl::new(),
+        }
+    }
+
+    /// The box area in document coordinates, if a box is being edited
+    pub fn box_rect(&self, state: &EditState) -> Option<Rectangle> {
+        let text_box = self.text_box.as_ref()?;
+        let offset = state.get_cur_layer().map(|layer| layer.offset()).unwrap_or_default();
+        Some(Rectangle::new(text_box.rect.start + offset, text_box.rect.size))
+    }
+
+    /// Writes the box to the layer, returns false if there was nothing to commit
+    pub fn commit(&mut self, state: &mut EditState) -> icy_engine::Result<bool> {
+        self.drag = None;
+        let Some(text_box) = self.text_box.take() else {
+            return Ok(false);
+        };
+        state.commit_text_box(&text_box)
+    }
+
+    /// Appends pasted text, returns false if no box is being edited
+    pub fn paste_text(&mut self, state: &mut EditState, text: &str) -> bool {
+        if self.text_box.is_none() {
+            return false;
+        }
+        self.edit(state, |text_box| text_box.text.push_str(&text.replace("\r\n", "\n").replace('\r', "\n")));
+        true
+    }
+
+    fn cancel(&mut self, state: &mut EditState) {
+        self.drag = None;
+        if self.text_box.take().is_some() {
+            state.end_text_box_preview();
+        }
+    }
+
+    fn commit_result(&mut self, state: &mut EditState) -> ToolResult {
+        match self.commit(state) {
+            Ok(true) => ToolResult::Commit("Text box".to_string()),
+            Ok(false) => ToolResult::Redraw,
+            Err(e) => {
+                log::warn!("Text box commit failed: {e}");
+                ToolResult::Status(format!("Text box commit failed: {e}"))
+            }
+        }
+    }
+
+    /// Changes the box and reflows the preview
+    fn edit(&mut self, state: &mut EditState, f: impl FnOnce(&mut TextBox)) {
+        let Some(text_box) = &mut self.text_box else {
+            return;
+        };
+        f(text_box);
+        if let Err(e) = state.preview_text_box(text_box) {
+            log::warn!("Text box preview failed: {e}");
+        }
+    }
+
+    /// Dashed frame around `rect` (document coordinates) with a handle in the resize corner
+    pub fn overlay_mask(font_width: f32, font_height: f32, rect: Rectangle) -> (Option<(Vec<u8>, u32, u32)>, Option<(f32, f32, f32, f32)>) {
+        let w = (rect.width() as f32 * font_width).ceil() as u32;
+        let h = (rect.height() as f32 * font_height).ceil() as u32;
+        if w == 0 || h == 0 {
+            return (None, None);
+        }
+        let handle_x = w.saturating_sub(font_width.ceil() as u32);
+        let handle_y = h.saturating_sub(font_height.ceil() as u32);
+
+        let mut rgba = vec![0u8; (w * h * 4) as usize];
+        for py in 0..h {
+            for px in 0..w {
+                let idx = ((py * w + px) * 4) as usize;
+                let is_border = px == 0 || px == w - 1 || py == 0 || py == h - 1;
+                if is_border && ((px + py) / 4) % 2 == 0 {
+                    rgba[idx..idx + 4].copy_from_slice(&[255, 255, 255, 200]);
+                } else if px >= handle_x && py >= handle_y {
+                    rgba[idx..idx + 4].copy_from_slice(&[100, 150, 255, 90]);
+                }
+            }
+        }
+        let x = rect.left() as f32 * font_width;
+        let y = rect.top() as f32 * font_height;
+        (Some((rgba, w, h)), Some((x, y, w as f32, h as f32)))
+    }
+}
+
+impl ToolHandler for TextBoxTool {
+    fn id(&self) -> ToolId {
+        ToolId::TextBox
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+
+    fn handle_message(&mut self, ctx: &mut ToolContext<'_>, msg: &ToolMessage) -> ToolResult {
+        match *msg {
+            ToolMessage::TextBoxSetAlignment(alignment) => {
+                self.alignment = alignment;
+                self.edit(ctx.state, |text_box| text_box.alignment = alignment);
+                ToolResult::Redraw
+            }
+            ToolMessage::TextBoxCommit => self.commit_result(ctx.state),
+            ToolMessage::TextBoxCancel => {
+                self.cancel(ctx.state);
+                ToolResult::Redraw
+            }
+            _ => ToolResult::None,
+        }
+    }
+
+    fn handle_event(&mut self, ctx: &mut ToolContext, event: &icy_ui::Event) -> ToolResult {
+        let icy_ui::Event::Keyboard(icy_ui::keyboard::Event::KeyPressed { key, modifiers, text, .. }) = event else {
+            return ToolResult::None;
+        };
+        if self.text_box.is_none() {
+            return ToolResult::None;
+        }
+        use icy_ui::keyboard::key::Named;
+
+        match key {
+            icy_ui::keyboard::Key::Named(Named::Escape) => {
+                self.cancel(ctx.state);
+                ToolResult::Redraw
+            }
+            icy_ui::keyboard::Key::Named(Named::Enter) if modifiers.control() => self.commit_result(ctx.state),
+            icy_ui::keyboard::Key::Named(Named::Enter) => {
+                self.edit(ctx.state, |text_box| text_box.text.push('\n'));
+                ToolResult::Redraw
+            }
+            icy_ui::keyboard::Key::Named(Named::Backspace) => {
+                self.edit(ctx.state, |text_box| {
+                    text_box.text.pop();
+                });
+                ToolResult::Redraw
+            }
+            icy_ui::keyboard::Key::Named(Named::Space) => {
+                self.edit(ctx.state, |text_box| text_box.text.push(' '));
+                ToolResult::Redraw
+            }
+            _ if modifiers.control() || modifiers.alt() => ToolResult::None,
+            _ => {
+                let Some(input) = text.as_ref().filter(|t| !t.is_empty() && t.chars().all(|c| !c.is_control())) else {
+                    return ToolResult::None;
+                };
+                self.edit(ctx.state, |text_box| text_box.text.push_str(input));
+                ToolResult::Redraw
+            }
+        }
+    }
+
+    fn handle_terminal_message(&mut self, ctx: &mut ToolContext, msg: &TerminalMessage) -> ToolResult {
+        let offset = ctx.state.get_cur_layer().map(|layer| layer.offset()).unwrap_or_default();
+        match msg {
+            TerminalMessage::Press(evt) => {
+                let Some(pos) = evt.text_position.map(|p| p - offset) else {
+                    return ToolResult::None;
+                };
+                if let Some(text_box) = &self.text_box {
+                    let corner = text_box.rect.bottom_right() - Position::new(1, 1);
+                    if pos == corner {
+                        self.drag = Some(DragMode::Resize);
+                        return ToolResult::StartCapture;
+                    }
+                    if text_box.rect.is_inside(pos) {
+                        return ToolResult::None;
+                    }
+                }
+                let result = if self.text_box.is_some() {
+                    self.commit_result(ctx.state)
+                } else {
+                    ToolResult::None
+                };
+                let mut text_box = TextBox::new(Rectangle::from(pos.x, pos.y, 1, 1), ctx.state.get_caret().attribute);
+                text_box.alignment = self.alignment;
+                self.text_box = Some(text_box);
+                self.drag = Some(DragMode::Create(pos));
+                result.and(ToolResult::StartCapture).and(ToolResult::Redraw)
+            }
+
+            TerminalMessage::Drag(evt) => {
+                let (Some(drag), Some(pos)) = (self.drag, evt.text_position.map(|p| p - offset)) else {
+                    return ToolResult::None;
+                };
+                self.edit(ctx.state, |text_box| {
+                    text_box.rect = match drag {
+                        DragMode::Create(start) => {
+                            let min = start.min(pos);
+                            let max = start.max(pos);
+                            Rectangle::from(min.x, min.y, max.x - min.x + 1, max.y - min.y + 1)
+                        }
+                        DragMode::Resize => {
+                            let start = text_box.rect.start;
+                            Rectangle::from(start.x, start.y, (pos.x - start.x + 1).max(1), (pos.y - start.y + 1).max(1))
+                        }
+                    };
+                });
+                ToolResult::Redraw
+            }
+
+            TerminalMessage::Release(_) => {
+                if self.drag.take().is_some() {
+                    ToolResult::EndCapture.and(ToolResult::Redraw)
+                } else {
+                    ToolResult::None
+                }
+            }
+
+            _ => ToolResult::None,
+        }
+    }
+
+    fn cancel_capture(&mut self) {
+        self.drag = None;
+    }
+
+    fn view_toolbar(&self, ctx: &ToolViewContext) -> Element<'_, ToolMessage> {
+        let segments = vec![
+            Segment::text("Left", TextAlignment::Left),
+            Segment::text("Center", TextAlignment::Center),
+            Segment::text("Right", TextAlignment::Right),
+            Segment::text("Justify", TextAlignment::Justify),
+        ];
+        let alignment_control = self
+            .alignment_control
+            .view(segments, self.alignment, ctx.font.clone(), &ctx.theme)
+            .map(|msg| match msg {
+                SegmentedControlMessage::Selected(a) | SegmentedControlMessage::Toggled(a) | SegmentedControlMessage::CharClicked(a) => {
+                    ToolMessage::TextBoxSetAlignment(a)
+                }
+            });
+
+        let editing = self.text_box.is_some();
+        let size_info = match &self.text_box {
+            Some(text_box) => format!("{}x{}, needs {} lines", text_box.rect.width(), text_box.rect.height(), text_box.needed_height()),
+            None => "Drag a rectangle to start".to_string(),
+        };
+
+        row![
+            Space::new().width(Length::Fill),
+            alignment_control,
+            Space::new().width(Length::Fixed(16.0)),
+            text(size_info).size(12),
+            Space::new().width(Length::Fixed(16.0)),
+            button(text("Commit").size(12))
+                .padding([2, 8])
+                .style(icy_ui::widget::button::text_style)
+                .on_press_maybe(editing.then_some(ToolMessage::TextBoxCommit)),
+            button(text("Cancel").size(12))
+                .padding([2, 8])
+                .style(icy_ui::widget::button::text_style)
+                .on_press_maybe(editing.then_some(ToolMessage::TextBoxCancel)),
+            Space::new().width(Length::Fill),
+        ]
+        .spacing(4)
+        .align_y(icy_ui::Alignment::Center)
+        .into()
+    }
+
+    fn cursor(&self) -> icy_ui::mouse::Interaction {
+        icy_ui::mouse::Interaction::Text
+    }
+
+    fn show_caret(&self) -> bool {
+        false
+    }
+
+    fn show_selection(&self) -> bool {
+        false
+    }
+}
@@ -89,6 +89,12 @@ pub enum LayerMessage {
     MergeDown(usize),
     /// Clear layer contents (context menu)
     Clear(usize),
+    /// Put a layer into a new group (context menu)
+    Group(usize),
+    /// Dissolve a group, keeping its members (context menu)
+    Ungroup(usize),
+    /// Collapse or expand a group in the list (context menu)
+    ToggleCollapsed(usize),
     // === Paste mode messages ===
     /// Keep paste as separate layer (exit paste mode without merging)
     PasteKeepAsLayer,
@@ -547,15 +553,28 @@ impl LayerView {
     }
 
     /// Build the context menu items for a layer
-    fn build_context_menu_items(index: usize, layer_count: usize) -> Vec<MenuNode<LayerMessage>> {
+    ///
+    /// `group_state` is the collapsed state if the layer is a group header.
+    fn build_context_menu_items(index: usize, layer_count: usize, group_state: Option<bool>) -> Vec<MenuNode<LayerMessage>> {
         let mut items = vec![
             menu_item!(fl!("layer_tool_menu_layer_properties"), LayerMessage::EditLayer(index)),
             menu_item!(fl!("layer_tool_menu_new_layer"), LayerMessage::Add),
             menu_item!(fl!("layer_tool_menu_duplicate_layer"), LayerMessage::Duplicate(index)),
+            menu_item!(fl!("layer_tool_menu_group_layer"), LayerMessage::Group(index)),
         ];
 
+        if let Some(is_collapsed) = group_state {
+            items.push(menu_item!(fl!("layer_tool_menu_ungroup_layer"), LayerMessage::Ungroup(index)));
+            let label = if is_collapsed {
+                fl!("layer_tool_menu_expand_group")
+            } else {
+                fl!("layer_tool_menu_collapse_group")
+            };
+            items.push(menu_item!(label, LayerMessage::ToggleCollapsed(index)));
+        }
+
         // Merge is only available if not the bottom layer
-        if index > 0 {
+        if index > 0 && group_state.is_none() {
             items.push(menu_item!(fl!("layer_tool_menu_merge_layer"), LayerMessage::MergeDown(index)));
         }
 
@@ -578,7 +597,7 @@ impl LayerView {
     /// - Delete cancels the paste operation
     pub fn view<'a>(&'a self, theme: &Theme, screen: &'a Arc<Mutex<Box<dyn Screen>>>, font_page: Option<usize>, paste_mode: bool) -> Element<'a, LayerMessage> {
         // Read layer data (no per-frame preview cloning)
-        let (rows, current_layer, layer_count, buffer_version, _font_key) = {
+        let (rows, current_layer, layer_count, group_state, buffer_version, _font_key) = {
             let mut screen_guard = screen.lock();
             let state: &mut EditState = screen_guard.as_any_mut().downcast_mut::<EditState>().expect("Screen should be EditState");
             let buffer = state.get_buffer();
@@ -587,27 +606,42 @@ impl LayerView {
             let current = if layer_count > 0 { state.get_current_layer().unwrap_or(0) } else { 0 };
             let buffer_version = buffer.version();
 
-            let rows: Vec<LayerRowInfo> = buffer
-                .layers
-                .iter()
-                .enumerate()
-                .rev()
-                .map(|(idx, layer)| LayerRowInfo {
+            // Top to bottom, members of collapsed groups are hidden
+            let mut rows: Vec<LayerRowInfo> = Vec::with_capacity(layer_count);
+            let mut collapsed_depth: Option<u8> = None;
+            for (idx, layer) in buffer.layers.iter().enumerate().rev() {
+                let depth = layer.properties.depth;
+                if collapsed_depth.is_some_and(|d| depth > d) {
+                    continue;
+                }
+                collapsed_depth = (layer.is_group() && layer.properties.is_collapsed).then_some(depth);
+                let title = if layer.title().is_empty() {
+                    format!("Layer {}", idx + 1)
+                } else {
+                    layer.title().to_string()
+                };
+                let marker = match (layer.is_group(), layer.properties.is_collapsed) {
+                    (false, _) => "",
+                    (true, false) => "▾ ",
+                    (true, true) => "▸ ",
+                };
+                rows.push(LayerRowInfo {
                     layer_index: idx,
-                    title: if layer.title().is_empty() {
-                        format!("Layer {}", idx + 1)
-                    } else {
-                        layer.title().to_string()
-                    },
+                    title: format!("{}{marker}{title}", "  ".repeat(depth as usize)),
                     is_visible: layer.is_visible(),
-                })
-                .collect();
+                });
+            }
+            let group_state = buffer
+                .layers
+                .get(current)
+                .filter(|layer| layer.is_group())
+                .map(|layer| layer.properties.is_collapsed);
 
             let font_key = font_page
                 .and_then(|fp| buffer.font(fp as u8).map(super::glyph_renderer::font_key))
                 .or_else(|| buffer.font(0).map(super::glyph_renderer::font_key));
 
-            (rows, current, layer_count, buffer_version, font_key)
+            (rows, current, layer_count, group_state, buffer_version, font_key)
         };
 
         // UI text in the layer list should use high-resolution TrueType rendering.
@@ -631,6 +665,8 @@ impl LayerView {
 
         let selected_list_idx: u32 = rows.iter().position(|r| r.layer_index == current_layer).map_or(u32::MAX, |idx| idx as u32);
 
+        let rows_len = rows.len();
+
         // Owner-rendered list widget (virtualized) + overlay scrollbar
         let list_widget: Element<'a, LayerMessage> = LayerListWidget {
             screen: Arc::clone(screen),
@@ -655,7 +691,7 @@ impl LayerView {
         let scroll_y = self.viewport.borrow().scroll_y();
         let icon_color = theme.background.on;
         let shader_bg: Element<'a, LayerMessage> = icy_ui::widget::shader(LayerListBackgroundProgram {
-            row_count: layer_count as u32,
+            row_count: rows_len as u32,
             row_height: LAYER_ROW_HEIGHT,
             scroll_y,
             selected_row: selected_list_idx,
@@ -723,7 +759,7 @@ impl LayerView {
 
         // Wrap with context menu (for current selection)
         if layer_count > 0 {
-            let menu_items = Self::build_context_menu_items(current_layer, layer_count);
+            let menu_items = Self::build_context_menu_items(current_layer, layer_count, group_state);
             context_menu(content, &menu_items).into()
         } else {
             content.into()
@@ -36,6 +36,8 @@ const TOOL_ICON_ORDER: &[Tool] = &[
     Tool::Pipette,          // 9
     Tool::Font,             // 10
     Tool::Tag,              // 11
+    Tool::Gradient,         // 12
+    Tool::TextBox,          // 13
 ];
 
 /// Map tool to atlas index
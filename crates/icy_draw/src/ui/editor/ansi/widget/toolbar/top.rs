@@ -40,6 +40,8 @@ pub enum SelectionMode {
     Foreground,
     /// Select all cells with the same background color
     Background,
+    /// Select matching cells with configurable criteria, contiguous or global
+    MagicWand,
 }
 
 /// Selection modifier based on keyboard modifiers
@@ -182,6 +184,8 @@ pub enum BrushPrimaryMode {
     Blink,
     /// Colorize mode (only affects attributes)
     Colorize,
+    /// Box drawing lines joining with existing frames (line / rectangle outline tools)
+    BoxLine,
 }
 
 /// Brush mode options
@@ -390,8 +394,10 @@ impl TopToolbar {
             Tool::RectangleOutline | Tool::RectangleFilled => self.view_shape_brush_panel(font, theme, caret_fg, caret_bg, palette, false),
             Tool::EllipseOutline | Tool::EllipseFilled => self.view_shape_brush_panel(font, theme, caret_fg, caret_bg, palette, false),
             Tool::Fill => self.view_fill_panel(font, theme, caret_fg, caret_bg, palette),
+            Tool::Gradient => self.view_gradient_panel(),
             Tool::Pipette => self.view_pipette_panel(pipette_info),
             Tool::Font => self.view_font_panel(font_panel_info),
+            Tool::TextBox => Space::new().into(),
             Tool::Tag => self.view_tag_panel(tag_add_mode, selected_tag, tag_selection_count),
         };
 
@@ -743,6 +749,17 @@ impl TopToolbar {
         .into()
     }
 
+    /// Gradient tool panel - the gradient options live in the tool itself, so only the usage is shown
+    fn view_gradient_panel(&self) -> Element<'_, TopToolbarMessage> {
+        row![
+            Space::new().width(Length::Fill),
+            text(fl!("tool-hint-gradient")).size(TEXT_SIZE_SMALL),
+            Space::new().width(Length::Fill),
+        ]
+        .align_y(icy_ui::Alignment::Center)
+        .into()
+    }
+
     /// Pipette tool panel - shows current character and colors being picked
     fn view_pipette_panel(&self, info: Option<&PipettePanelInfo>) -> Element<'_, TopToolbarMessage> {
         let info = info.cloned().unwrap_or_default();
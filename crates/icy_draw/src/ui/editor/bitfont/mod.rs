@@ -1,6 +1,6 @@
 //! `BitFont` Editor for `icy_draw`
 //!
-//! Provides a pixel-based editor for bitmap fonts (.psf, .fXX, .yaff files).
+//! Provides a pixel-based editor for bitmap fonts (.psf, .fXX, .yaff, .bdf, .pcf files).
 //! Features:
 //! - Glyph selector grid (256 characters)
 //! - Pixel edit grid with click/drag drawing
@@ -30,7 +30,7 @@ pub use top_toolbar::*;
 use std::{path::PathBuf, sync::Arc};
 
 use codepages::tables::CP437_TO_UNICODE;
-use icy_engine::BitFont;
+use icy_engine::{BitFont, TtfExportOptions};
 use icy_engine_edit::bitfont::{BitFontEditState, BitFontFocusedPanel, BitFontUndoState};
 use icy_engine_gui::{
     theme::{self, main_area_background},
@@ -618,7 +618,7 @@ impl BitFontEditor {
             }
             BitFontEditorMessage::ShowExportFontDialog => {
                 let font = self.state.build_font();
-                dialogs.push(crate::ui::dialog::font_export::FontExportDialog::new(font));
+                dialogs.push(crate::ui::dialog::font_export::FontExportDialog::new(font, self.state.codepage()));
                 return Task::none();
             }
             BitFontEditorMessage::FontExportDialog(_) => {
@@ -899,6 +899,12 @@ impl BitFontEditor {
                         self.edit_cache.clear();
                         return Task::none();
                     }
+                    BitFontTopToolbarMessage::SetCodepage(codepage) => {
+                        if self.state.set_codepage(codepage).is_ok() {
+                            self.invalidate_caches();
+                        }
+                        return Task::none();
+                    }
                     BitFontTopToolbarMessage::PrevChar => {
                         // PrevChar logic inline
                         let current = self.selected_char();
@@ -1440,7 +1446,7 @@ impl BitFontEditor {
         // === TOP TOOLBAR (color switcher + tool options) ===
         let color_switcher = self.top_toolbar.view_color_switcher().map(BitFontEditorMessage::TopToolbar);
 
-        let top_toolbar_panel = self.top_toolbar.view().map(BitFontEditorMessage::TopToolbar);
+        let top_toolbar_panel = self.top_toolbar.view(self.state.codepage()).map(BitFontEditorMessage::TopToolbar);
 
         let toolbar_height = SWITCHER_SIZE;
 
@@ -1699,6 +1705,16 @@ impl BitFontEditor {
             // YAFF format is read-only for now, save as text representation
             // Note: libyaff doesn't have a to_yaff_bytes method yet
             return Err("YAFF export is not yet supported. Please save as .psf instead.".to_string());
+        } else if ext == "pcf" {
+            return Err("PCF export is not supported. Please save as .bdf instead.".to_string());
+        } else if ext == "bdf" {
+            font.to_bdf_bytes(self.state.codepage())
+        } else if ext == "ttf" || ext == "otf" {
+            let options = TtfExportOptions {
+                codepage: self.state.codepage(),
+                ..Default::default()
+            };
+            font.to_ttf_bytes(&options).map_err(|e| e.to_string())?
         } else {
             // Default to PSF2 binary format
             font.to_psf2_bytes().map_err(|e| e.to_string())?
@@ -1,12 +1,15 @@
 //! Top toolbar component for `BitFont` Editor
 //!
-//! Shows keyboard shortcut hints for actions not in the menu.
+//! Shows the codepage picker and keyboard shortcut hints for actions not in the menu.
 
+use icy_engine::FontCodepage;
 use icy_ui::{
-    widget::{container, row, text},
+    widget::{container, pick_list, row, text},
     Element, Length, Task,
 };
 
+use crate::fl;
+
 use crate::ui::editor::ansi::{ColorSwitcher, ColorSwitcherMessage};
 
 /// Messages from the top toolbar
@@ -19,6 +22,8 @@ pub enum BitFontTopToolbarMessage {
     NextChar,
     /// Go to previous character
     PrevChar,
+    /// Codepage that maps the slots to Unicode (used for BDF/TTF and Unicode fonts)
+    SetCodepage(FontCodepage),
     /// Color switcher message
     ColorSwitcher(ColorSwitcherMessage),
 }
@@ -55,7 +60,7 @@ impl BitFontTopToolbar {
     pub fn update(&mut self, message: BitFontTopToolbarMessage) -> Task<BitFontTopToolbarMessage> {
         match message {
             BitFontTopToolbarMessage::ToggleFilled(v) => self.filled = v,
-            BitFontTopToolbarMessage::NextChar | BitFontTopToolbarMessage::PrevChar => {
+            BitFontTopToolbarMessage::NextChar | BitFontTopToolbarMessage::PrevChar | BitFontTopToolbarMessage::SetCodepage(_) => {
                 // Handled by parent
             }
             BitFontTopToolbarMessage::ColorSwitcher(msg) => match msg {
@@ -87,10 +92,13 @@ impl BitFontTopToolbar {
             .map(BitFontTopToolbarMessage::ColorSwitcher)
     }
 
-    /// Render the top toolbar with keyboard shortcut hints
+    /// Render the top toolbar with the codepage picker and keyboard shortcut hints
     /// Shows only non-obvious shortcuts that are not in the menu
-    pub fn view(&self) -> Element<'_, BitFontTopToolbarMessage> {
+    pub fn view(&self, codepage: FontCodepage) -> Element<'_, BitFontTopToolbarMessage> {
         let hints = row![
+            text(fl!("font-editor-codepage")).size(14),
+            pick_list(FontCodepage::ALL.as_slice(), Some(codepage), BitFontTopToolbarMessage::SetCodepage).width(Length::Fixed(140.0)),
+            Self::sep(),
             Self::hint("Ctrl+Arrow", "Slide"),
             Self::sep(),
             Self::hint("Alt+Arrow", "Ins/Del Line/Col"),
@@ -2,7 +2,7 @@
 //!
 //! Provides dialogs for:
 //! - Adding a new font (selecting type, name, spacing)
-//! - Editing font settings (name, spacing)
+//! - Editing font settings (name, spacing, FIGlet layout)
 
 use std::fmt;
 
@@ -20,7 +20,7 @@ use icy_ui::{
 use super::CharFontEditorMessage;
 use crate::fl;
 use crate::ui::Message;
-use icy_engine_edit::charset::TdfFontType;
+use icy_engine_edit::charset::{FigletLayout, TdfFontType};
 
 /// Wrapper for `TdfFontType` that implements Display for use in `pick_list`
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -48,6 +48,24 @@ impl From<FontTypeOption> for TdfFontType {
     }
 }
 
+/// Wrapper for `FigletLayout` that implements Display for use in `pick_list`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FigletLayoutOption(pub FigletLayout);
+
+impl fmt::Display for FigletLayoutOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            FigletLayout::FullWidth => write!(f, "{}", fl!("tdf-dialog-figlet-full-width")),
+            FigletLayout::Fitting => write!(f, "{}", fl!("tdf-dialog-figlet-fitting")),
+            FigletLayout::Smushing(_) => write!(f, "{}", fl!("tdf-dialog-figlet-smushing")),
+        }
+    }
+}
+
+/// Smushing rules offered for fonts that don't have their own: equal character,
+/// underscore, hierarchy and opposite pair
+const DEFAULT_SMUSHING_RULES: u8 = 15;
+
 // ═══════════════════════════════════════════════════════════════════════════
 // Add Font Dialog
 // ═══════════════════════════════════════════════════════════════════════════
@@ -259,6 +277,8 @@ pub enum EditFontSettingsDialogMessage {
     SetName(String),
     /// Spacing input changed
     SetSpacing(String),
+    /// FIGlet layout changed
+    SetFigletLayout(FigletLayout),
     /// Apply the changes
     Apply,
     /// Cancel the dialog
@@ -272,14 +292,24 @@ pub struct EditFontSettingsDialog {
     pub name: String,
     /// Spacing value as string
     pub spacing: String,
+    /// Layout used when saving as FIGlet font
+    pub figlet_layout: FigletLayout,
+    /// Smushing rules offered in the layout picker, the font's own if it smushes
+    smushing_rules: u8,
 }
 
 impl EditFontSettingsDialog {
     /// Create a new Edit Font Settings dialog with current font values
-    pub fn new(name: String, spacing: i32) -> Self {
+    pub fn new(name: String, spacing: i32, figlet_layout: FigletLayout) -> Self {
+        let smushing_rules = match figlet_layout {
+            FigletLayout::Smushing(rules) => rules,
+            _ => DEFAULT_SMUSHING_RULES,
+        };
         Self {
             name,
             spacing: spacing.to_string(),
+            figlet_layout,
+            smushing_rules,
         }
     }
 
@@ -334,8 +364,26 @@ impl Dialog<Message> for EditFontSettingsDialog {
         .spacing(DIALOG_SPACING)
         .align_y(Alignment::Center);
 
+        // FIGlet layout
+        let layouts = [FigletLayout::FullWidth, FigletLayout::Fitting, FigletLayout::Smushing(self.smushing_rules)].map(FigletLayoutOption);
+        let layout_picker = pick_list(layouts, Some(FigletLayoutOption(self.figlet_layout)), |l: FigletLayoutOption| {
+            edit_msg(EditFontSettingsDialogMessage::SetFigletLayout(l.0))
+        })
+        .width(Length::Fixed(150.0));
+
+        let layout_row = row![left_label_small(fl!("tdf-dialog-figlet-layout")), layout_picker,]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center);
+
         // Content wrapped in effect_box
-        let content_column = column![name_row, Space::new().height(DIALOG_SPACING), spacing_row,].spacing(0);
+        let content_column = column![
+            name_row,
+            Space::new().height(DIALOG_SPACING),
+            spacing_row,
+            Space::new().height(DIALOG_SPACING),
+            layout_row,
+        ]
+        .spacing(0);
 
         let content_box = effect_box(content_column.into());
 
@@ -370,11 +418,16 @@ impl Dialog<Message> for EditFontSettingsDialog {
                 self.spacing = s.clone();
                 Some(DialogAction::None)
             }
+            EditFontSettingsDialogMessage::SetFigletLayout(layout) => {
+                self.figlet_layout = *layout;
+                Some(DialogAction::None)
+            }
             EditFontSettingsDialogMessage::Apply => {
                 if let Some(spacing) = self.parsed_spacing() {
                     Some(DialogAction::CloseWith(Message::CharFontEditor(CharFontEditorMessage::EditFontSettingsApply(
                         self.name.clone(),
                         spacing,
+                        self.figlet_layout,
                     ))))
                 } else {
                     Some(DialogAction::None)
@@ -393,6 +446,7 @@ impl Dialog<Message> for EditFontSettingsDialog {
             DialogAction::CloseWith(Message::CharFontEditor(CharFontEditorMessage::EditFontSettingsApply(
                 self.name.clone(),
                 spacing,
+                self.figlet_layout,
             )))
         } else {
             DialogAction::None
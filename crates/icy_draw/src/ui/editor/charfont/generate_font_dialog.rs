@@ -0,0 +1,343 @@
+//! Generate Font Dialog for `CharFont` (TDF) Editor
+//!
+//! Renders a TrueType/OpenType font or a 16x16 image sheet with half blocks
+//! into a new block or color font, so letters don't have to be drawn by hand.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use icy_engine_edit::charset::{generate_tdf_font, CoverageMap, TdfFontType, TdfGeneratorOptions};
+use icy_engine_gui::settings::effect_box;
+use icy_engine_gui::ui::{
+    browse_button, button_row, dialog_area, dialog_title, left_label_small, modal_container, primary_button, secondary_button, separator, Dialog, DialogAction,
+    DIALOG_SPACING, DIALOG_WIDTH_MEDIUM, TEXT_SIZE_NORMAL, TEXT_SIZE_SMALL,
+};
+use icy_engine_gui::ButtonType;
+use icy_ui::{
+    widget::{checkbox, column, container, pick_list, row, slider, text, text_input, Space},
+    Alignment, Element, Length, Task,
+};
+
+use super::{CharFontEditorMessage, FontTypeOption};
+use crate::fl;
+use crate::ui::dialog::font_import::{image_sheet_coverage, rasterize_ttf_coverage};
+use crate::ui::Message;
+
+/// Extensions rasterized as TrueType/OpenType, everything else is read as an image sheet
+const TTF_EXTENSIONS: [&str; 4] = ["ttf", "otf", "ttc", "otc"];
+
+/// Helper to wrap `GenerateFontDialogMessage` in Message
+fn msg(m: GenerateFontDialogMessage) -> Message {
+    Message::CharFontEditor(CharFontEditorMessage::GenerateFontDialog(m))
+}
+
+/// Messages for the Generate Font dialog
+#[derive(Debug, Clone)]
+pub enum GenerateFontDialogMessage {
+    /// File path input changed
+    SetFilePath(String),
+    /// Browse button clicked
+    Browse,
+    /// File selected from browser
+    FileSelected(Option<PathBuf>),
+    /// Font type changed
+    SetFontType(TdfFontType),
+    /// Name input changed
+    SetName(String),
+    /// Cell height input changed
+    SetCellHeight(String),
+    /// Threshold slider moved
+    SetThreshold(u8),
+    /// Toggle shading of partially covered cells
+    SetShade(bool),
+    /// Toggle the outline
+    SetOutline(bool),
+    /// Generate the font
+    Generate,
+    /// Cancel the dialog
+    Cancel,
+}
+
+/// State for the Generate Font dialog
+#[derive(Debug, Clone)]
+pub struct GenerateFontDialog {
+    /// Source TTF/OTF font or image sheet
+    pub file_path: String,
+    /// Name of the generated font
+    pub name: String,
+    /// Height of the glyphs in lines as string
+    pub cell_height: String,
+    /// Generator options, `cell_height` is taken from the input
+    pub options: TdfGeneratorOptions,
+    /// Error message (if any)
+    pub error: Option<String>,
+}
+
+impl Default for GenerateFontDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GenerateFontDialog {
+    /// Create a new Generate Font dialog with default values
+    pub fn new() -> Self {
+        let options = TdfGeneratorOptions::default();
+        Self {
+            file_path: String::new(),
+            name: String::new(),
+            cell_height: options.cell_height.to_string(),
+            options,
+            error: None,
+        }
+    }
+
+    /// Parse the cell height
+    pub fn parsed_cell_height(&self) -> Option<usize> {
+        self.cell_height.parse::<usize>().ok().filter(|h| (1..=16).contains(h))
+    }
+
+    /// Check if the input is valid
+    pub fn is_valid(&self) -> bool {
+        !self.file_path.trim().is_empty() && !self.name.trim().is_empty() && self.parsed_cell_height().is_some()
+    }
+
+    fn set_file(&mut self, path: &Path) {
+        self.file_path = path.to_string_lossy().to_string();
+        if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+            self.name = stem.to_string();
+        }
+        self.error = None;
+    }
+
+    /// Rasterize the source and close with the generated font, errors stay in the dialog
+    fn generate(&mut self) -> DialogAction<Message> {
+        let Some(cell_height) = self.parsed_cell_height() else {
+            return DialogAction::None;
+        };
+        let path = PathBuf::from(&self.file_path);
+        let is_ttf = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| TTF_EXTENSIONS.iter().any(|ext| e.eq_ignore_ascii_case(ext)));
+        let glyphs: Result<BTreeMap<char, CoverageMap>, String> = if is_ttf {
+            rasterize_ttf_coverage(&path, cell_height * 2)
+        } else {
+            image_sheet_coverage(&path, cell_height * 2)
+        };
+        let options = TdfGeneratorOptions { cell_height, ..self.options };
+        let font = glyphs.and_then(|glyphs| {
+            generate_tdf_font(self.name.trim(), &glyphs, &options).map_err(|err| fl!("tdf-dialog-generate-failed", error = err.to_string()))
+        });
+        match font {
+            Ok(font) => DialogAction::CloseWith(Message::CharFontEditor(CharFontEditorMessage::GenerateFontApply(font))),
+            Err(err) => {
+                self.error = Some(err);
+                DialogAction::None
+            }
+        }
+    }
+}
+
+impl Dialog<Message> for GenerateFontDialog {
+    fn view(&self) -> Element<'_, Message> {
+        let title = dialog_title(fl!("tdf-dialog-generate-font-title"));
+
+        // Source file
+        let placeholder = fl!("tdf-dialog-generate-source-placeholder");
+        let file_input = text_input(&placeholder, &self.file_path)
+            .on_input(|s| msg(GenerateFontDialogMessage::SetFilePath(s)))
+            .size(TEXT_SIZE_NORMAL)
+            .width(Length::Fill);
+        let file_row = row![
+            left_label_small(fl!("tdf-dialog-generate-source")),
+            file_input,
+            browse_button(msg(GenerateFontDialogMessage::Browse)),
+        ]
+        .spacing(DIALOG_SPACING)
+        .align_y(Alignment::Center);
+
+        // Font type, only block and color fonts can be generated
+        let font_types = vec![FontTypeOption(TdfFontType::Color), FontTypeOption(TdfFontType::Block)];
+        let type_picker = pick_list(font_types, Some(FontTypeOption(self.options.font_type)), |t: FontTypeOption| {
+            msg(GenerateFontDialogMessage::SetFontType(t.0))
+        })
+        .width(Length::Fixed(120.0));
+        let type_row = row![left_label_small(fl!("tdf-dialog-font-type")), type_picker]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center);
+
+        let name_input = text_input("", &self.name)
+            .on_input(|s| msg(GenerateFontDialogMessage::SetName(s)))
+            .size(TEXT_SIZE_NORMAL)
+            .width(Length::Fixed(150.0));
+        let name_row = row![left_label_small(fl!("tdf-dialog-font-name")), name_input]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center);
+
+        // Cell height
+        let height_input = text_input("1-16", &self.cell_height)
+            .on_input(|s| msg(GenerateFontDialogMessage::SetCellHeight(s)))
+            .size(TEXT_SIZE_NORMAL)
+            .width(Length::Fixed(80.0));
+        let height_error = if self.parsed_cell_height().is_none() && !self.cell_height.is_empty() {
+            text("1 to 16")
+                .size(TEXT_SIZE_SMALL)
+                .style(|theme: &icy_ui::Theme| icy_ui::widget::text::Style {
+                    color: Some(theme.destructive.base),
+                })
+        } else {
+            text("").size(TEXT_SIZE_SMALL)
+        };
+        let height_row = row![
+            left_label_small(fl!("tdf-dialog-generate-cell-height")),
+            height_input,
+            Space::new().width(4.0),
+            height_error,
+        ]
+        .spacing(DIALOG_SPACING)
+        .align_y(Alignment::Center);
+
+        // Threshold
+        let threshold_slider = slider(1..=255, self.options.threshold, |v| msg(GenerateFontDialogMessage::SetThreshold(v))).width(Length::Fixed(200.0));
+        let threshold_row = row![
+            left_label_small(fl!("tdf-dialog-generate-threshold")),
+            threshold_slider,
+            text(self.options.threshold.to_string()).size(TEXT_SIZE_SMALL).width(Length::Fixed(40.0)),
+        ]
+        .spacing(DIALOG_SPACING)
+        .align_y(Alignment::Center);
+
+        let shade_row = row![
+            checkbox(self.options.shade).on_toggle(|b| msg(GenerateFontDialogMessage::SetShade(b))).size(16),
+            text(fl!("tdf-dialog-generate-shade")).size(TEXT_SIZE_NORMAL),
+        ]
+        .spacing(6)
+        .align_y(Alignment::Center);
+
+        let outline_row = row![
+            checkbox(self.options.outline)
+                .on_toggle(|b| msg(GenerateFontDialogMessage::SetOutline(b)))
+                .size(16),
+            text(fl!("tdf-dialog-generate-outline")).size(TEXT_SIZE_NORMAL),
+        ]
+        .spacing(6)
+        .align_y(Alignment::Center);
+
+        let error_element: Element<'_, Message> = if let Some(err) = &self.error {
+            text(err)
+                .size(TEXT_SIZE_SMALL)
+                .style(|theme: &icy_ui::Theme| icy_ui::widget::text::Style {
+                    color: Some(theme.destructive.base),
+                })
+                .into()
+        } else {
+            Space::new().height(0).into()
+        };
+
+        let content_column = column![
+            file_row,
+            text(fl!("tdf-dialog-generate-info")).size(TEXT_SIZE_SMALL),
+            type_row,
+            name_row,
+            height_row,
+            threshold_row,
+            shade_row,
+            outline_row,
+            error_element,
+        ]
+        .spacing(DIALOG_SPACING);
+
+        let content_box = effect_box(content_column.into());
+
+        let buttons = button_row(vec![
+            secondary_button(format!("{}", ButtonType::Cancel), Some(msg(GenerateFontDialogMessage::Cancel))).into(),
+            primary_button(
+                fl!("tdf-dialog-generate-button"),
+                self.is_valid().then(|| msg(GenerateFontDialogMessage::Generate)),
+            )
+            .into(),
+        ]);
+
+        let dialog_content = dialog_area(column![title, Space::new().height(DIALOG_SPACING), content_box].into());
+        let button_area = dialog_area(buttons);
+
+        modal_container(
+            column![container(dialog_content).height(Length::Shrink), separator(), button_area].into(),
+            DIALOG_WIDTH_MEDIUM,
+        )
+        .into()
+    }
+
+    fn update(&mut self, message: &Message) -> Option<DialogAction<Message>> {
+        let Message::CharFontEditor(CharFontEditorMessage::GenerateFontDialog(dialog_message)) = message else {
+            return None;
+        };
+        match dialog_message {
+            GenerateFontDialogMessage::SetFilePath(path) => {
+                self.file_path = path.clone();
+                self.error = None;
+                Some(DialogAction::None)
+            }
+            GenerateFontDialogMessage::Browse => {
+                let title = fl!("tdf-dialog-generate-font-title");
+                Some(DialogAction::RunTask(Task::perform(
+                    async move {
+                        rfd::AsyncFileDialog::new()
+                            .add_filter("TrueType/OpenType", &TTF_EXTENSIONS)
+                            .add_filter("Image Files", &["png", "jpg", "jpeg", "gif", "bmp", "webp"])
+                            .set_title(title)
+                            .pick_file()
+                            .await
+                            .map(|f| f.path().to_path_buf())
+                    },
+                    |path| msg(GenerateFontDialogMessage::FileSelected(path)),
+                )))
+            }
+            GenerateFontDialogMessage::FileSelected(path) => {
+                if let Some(path) = path {
+                    self.set_file(path);
+                }
+                Some(DialogAction::None)
+            }
+            GenerateFontDialogMessage::SetFontType(t) => {
+                self.options.font_type = *t;
+                Some(DialogAction::None)
+            }
+            GenerateFontDialogMessage::SetName(n) => {
+                self.name = n.clone();
+                Some(DialogAction::None)
+            }
+            GenerateFontDialogMessage::SetCellHeight(h) => {
+                self.cell_height = h.clone();
+                Some(DialogAction::None)
+            }
+            GenerateFontDialogMessage::SetThreshold(t) => {
+                self.options.threshold = *t;
+                Some(DialogAction::None)
+            }
+            GenerateFontDialogMessage::SetShade(shade) => {
+                self.options.shade = *shade;
+                Some(DialogAction::None)
+            }
+            GenerateFontDialogMessage::SetOutline(outline) => {
+                self.options.outline = *outline;
+                Some(DialogAction::None)
+            }
+            GenerateFontDialogMessage::Generate => Some(if self.is_valid() { self.generate() } else { DialogAction::None }),
+            GenerateFontDialogMessage::Cancel => Some(DialogAction::Close),
+        }
+    }
+
+    fn request_cancel(&mut self) -> DialogAction<Message> {
+        DialogAction::Close
+    }
+
+    fn request_confirm(&mut self) -> DialogAction<Message> {
+        if self.is_valid() {
+            self.generate()
+        } else {
+            DialogAction::None
+        }
+    }
+}
@@ -8,10 +8,12 @@
 
 mod charset_canvas;
 mod font_dialogs;
+mod generate_font_dialog;
 mod outline_style_preview;
 
 pub use charset_canvas::*;
 pub use font_dialogs::*;
+pub use generate_font_dialog::*;
 
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -19,7 +21,7 @@ use std::sync::Arc;
 use icy_engine::char_set::TdfBufferRenderer;
 use icy_engine::Screen;
 use icy_engine::{AttributedChar, BitFont, Layer, Size, TextAttribute, TextBuffer, TextPane};
-use icy_engine_edit::charset::{load_tdf_fonts, CharSetEditState, CharSetFocusedPanel, TdfFontType};
+use icy_engine_edit::charset::{load_tdf_fonts, CharSetEditState, CharSetFocusedPanel, FigletLayout, FlfFont, TdfFont, TdfFontType};
 use icy_engine_edit::EditState;
 use icy_engine_gui::theme::main_area_background;
 use icy_engine_gui::ui::{add_icon, arrow_downward_icon, arrow_upward_icon, content_copy_icon, delete_icon, edit_icon, DialogStack};
@@ -76,6 +78,8 @@ pub enum CharFontEditorMessage {
     MoveFontDown,
     /// Open add font dialog
     OpenAddFontDialog,
+    /// Open the dialog generating a font from a TTF font or image sheet
+    OpenGenerateFontDialog,
     /// Open edit font settings dialog
     OpenEditSettingsDialog,
     /// Clear the current character
@@ -151,8 +155,12 @@ pub enum CharFontEditorMessage {
     AddFontApply(TdfFontType, String, i32),
     /// Edit font settings dialog messages
     EditFontSettingsDialog(EditFontSettingsDialogMessage),
-    /// Apply the edit font settings dialog result
-    EditFontSettingsApply(String, i32),
+    /// Apply the edit font settings dialog result (name, spacing, FIGlet layout)
+    EditFontSettingsApply(String, i32, FigletLayout),
+    /// Generate font dialog messages
+    GenerateFontDialog(GenerateFontDialogMessage),
+    /// Add the font generated by the generate font dialog
+    GenerateFontApply(TdfFont),
 
     // ═══════════════════════════════════════════════════════════════════════════
     // Import/Export
@@ -163,7 +171,7 @@ pub enum CharFontEditorMessage {
     ImportFontsComplete(usize),
     /// Import fonts with the loaded file data (raw bytes)
     ImportFontsData(Vec<u8>),
-    /// Export current font as single TDF file
+    /// Export current font as single TDF or FIGlet file
     ExportFont,
     /// No-op message
     Nop,
@@ -184,6 +192,7 @@ impl std::fmt::Debug for CharFontEditorMessage {
             Self::MoveFontUp => f.write_str("MoveFontUp"),
             Self::MoveFontDown => f.write_str("MoveFontDown"),
             Self::OpenAddFontDialog => f.write_str("OpenAddFontDialog"),
+            Self::OpenGenerateFontDialog => f.write_str("OpenGenerateFontDialog"),
             Self::OpenEditSettingsDialog => f.write_str("OpenEditSettingsDialog"),
             Self::ClearChar => f.write_str("ClearChar"),
             Self::FontNameChanged(_) => f.write_str("FontNameChanged(..)"),
@@ -213,7 +222,9 @@ impl std::fmt::Debug for CharFontEditorMessage {
             Self::AddFontDialog(_) => f.write_str("AddFontDialog(..)"),
             Self::AddFontApply(t, n, s) => f.debug_tuple("AddFontApply").field(t).field(n).field(s).finish(),
             Self::EditFontSettingsDialog(_) => f.write_str("EditFontSettingsDialog(..)"),
-            Self::EditFontSettingsApply(n, s) => f.debug_tuple("EditFontSettingsApply").field(n).field(s).finish(),
+            Self::EditFontSettingsApply(n, s, l) => f.debug_tuple("EditFontSettingsApply").field(n).field(s).field(l).finish(),
+            Self::GenerateFontDialog(_) => f.write_str("GenerateFontDialog(..)"),
+            Self::GenerateFontApply(font) => f.debug_tuple("GenerateFontApply").field(&font.name).finish(),
             Self::ImportFonts => f.write_str("ImportFonts"),
             Self::ImportFontsComplete(c) => f.debug_tuple("ImportFontsComplete").field(c).finish(),
             Self::ExportFont => f.write_str("ExportFont"),
@@ -483,7 +494,12 @@ impl CharFontEditor {
             anyhow::bail!("No fonts found in autosave file");
         }
 
+        // The autosave is a TDF bundle, FIGlet fonts get their source back from the original file
+        let figlet_source = std::fs::read(&original_path).ok().and_then(|data| FlfFont::from_bytes(&data).ok());
         let mut charset_state = CharSetEditState::with_fonts(fonts, Some(original_path));
+        if let Some(source) = figlet_source {
+            charset_state.set_figlet_source(source);
+        }
         charset_state.set_dirty(true);
         Ok(Self::with_charset_state(charset_state, options, font_library))
     }
@@ -778,9 +794,13 @@ impl CharFontEditor {
                 dialogs.push(AddFontDialog::new());
                 Task::none()
             }
+            CharFontEditorMessage::OpenGenerateFontDialog => {
+                dialogs.push(GenerateFontDialog::new());
+                Task::none()
+            }
             CharFontEditorMessage::OpenEditSettingsDialog => {
                 if let Some(font) = self.charset_state.selected_font() {
-                    dialogs.push(EditFontSettingsDialog::new(font.name.clone(), font.spacing));
+                    dialogs.push(EditFontSettingsDialog::new(font.name.clone(), font.spacing, self.charset_state.figlet_layout()));
                 }
                 Task::none()
             }
@@ -798,15 +818,31 @@ impl CharFontEditor {
                 // Handled by DialogStack
                 Task::none()
             }
-            CharFontEditorMessage::EditFontSettingsApply(name, spacing) => {
+            CharFontEditorMessage::EditFontSettingsApply(name, spacing, layout) => {
                 self.charset_state.set_font_name(name);
                 self.charset_state.set_font_spacing(spacing);
+                self.charset_state.set_figlet_layout(layout);
+                self.undostack_len += 1;
+                Task::none()
+            }
+            CharFontEditorMessage::GenerateFontDialog(_) => {
+                // Handled by DialogStack
+                Task::none()
+            }
+            CharFontEditorMessage::GenerateFontApply(font) => {
+                self.charset_state.insert_font(font);
+                self.update_selected_char();
                 self.undostack_len += 1;
                 Task::none()
             }
             CharFontEditorMessage::ImportFonts => Task::perform(
                 async move {
-                    if let Some(handle) = rfd::AsyncFileDialog::new().add_filter("TDF Font", &["tdf"]).pick_file().await {
+                    if let Some(handle) = rfd::AsyncFileDialog::new()
+                        .add_filter("TDF Font", &["tdf"])
+                        .add_filter("FIGlet Font", &["flf"])
+                        .pick_file()
+                        .await
+                    {
                         handle.read().await
                     } else {
                         Vec::new()
@@ -840,20 +876,31 @@ impl CharFontEditor {
             CharFontEditorMessage::ExportFont => {
                 if let Some(font) = self.charset_state.selected_font() {
                     let font_clone = font.clone();
+                    let figlet = self.charset_state.selected_figlet_font();
                     let default_name = format!("{}.tdf", font.name);
                     Task::perform(
                         async move {
                             rfd::AsyncFileDialog::new()
                                 .set_file_name(&default_name)
                                 .add_filter("TDF Font", &["tdf"])
+                                .add_filter("FIGlet Font", &["flf"])
                                 .save_file()
                                 .await
                                 .map(|h| h.path().to_path_buf())
                         },
                         move |path| {
                             if let Some(path) = path {
-                                if let Ok(data) = font_clone.to_bytes() {
-                                    let _ = std::fs::write(&path, data);
+                                let is_figlet = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("flf"));
+                                let data = if is_figlet {
+                                    figlet.map(|flf| flf.to_flf_string().into_bytes())
+                                } else {
+                                    font_clone.to_bytes().map_err(anyhow::Error::from)
+                                };
+                                match data {
+                                    Ok(data) => {
+                                        let _ = std::fs::write(&path, data);
+                                    }
+                                    Err(err) => log::error!("Failed to export font: {err}"),
                                 }
                             }
                             CharFontEditorMessage::Nop
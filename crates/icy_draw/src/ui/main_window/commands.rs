@@ -77,6 +77,10 @@ pub mod selection_cmd {
         SELECT_JUSTIFY_LEFT = "select.justify_left",
         SELECT_JUSTIFY_CENTER = "select.justify_center",
         SELECT_JUSTIFY_RIGHT = "select.justify_right",
+        SELECT_ROTATE_CW = "select.rotate_cw",
+        SELECT_ROTATE_CCW = "select.rotate_ccw",
+        SELECT_ROTATE_180 = "select.rotate_180",
+        SELECT_SCALE = "select.scale",
     }
 }
 
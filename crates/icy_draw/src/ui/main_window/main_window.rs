@@ -115,6 +115,10 @@ command_handler!(MainWindowCommands, create_draw_commands(), => Message {
     selection_cmd::SELECT_JUSTIFY_LEFT => Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::JustifyLeft)),
     selection_cmd::SELECT_JUSTIFY_CENTER => Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::JustifyCenter)),
     selection_cmd::SELECT_JUSTIFY_RIGHT => Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::JustifyRight)),
+    selection_cmd::SELECT_ROTATE_CW => Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::RotateCw)),
+    selection_cmd::SELECT_ROTATE_CCW => Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::RotateCcw)),
+    selection_cmd::SELECT_ROTATE_180 => Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::Rotate180)),
+    selection_cmd::SELECT_SCALE => Message::AnsiEditor(AnsiEditorMessage::ShowScaleDialog),
     // Area operations (forwarded to ANSI editor)
     area_cmd::JUSTIFY_LINE_LEFT => Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::JustifyLineLeft)),
     area_cmd::JUSTIFY_LINE_CENTER => Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::JustifyLineCenter)),
@@ -1052,9 +1056,16 @@ impl MainWindow {
         }
     }
 
-    pub fn ansi_mirror_mode(&self) -> Option<bool> {
+    pub fn ansi_symmetry(&self) -> Option<icy_engine_edit::Symmetry> {
         match &self.mode_state {
-            ModeState::Ansi(editor) => Some(editor.mirror_mode()),
+            ModeState::Ansi(editor) => Some(editor.symmetry()),
+            _ => None,
+        }
+    }
+
+    pub fn ansi_journal_recording(&self) -> Option<bool> {
+        match &self.mode_state {
+            ModeState::Ansi(editor) => Some(editor.is_recording_journal()),
             _ => None,
         }
     }
@@ -1246,6 +1257,7 @@ impl MainWindow {
                         });
                         // Sync UI after undo (palette may have changed)
                         editor.sync_ui();
+                        editor.sync_journal();
                     }
                     ModeState::BitFont(editor) => {
                         editor.undo();
@@ -1271,6 +1283,7 @@ impl MainWindow {
                         });
                         // Sync UI after redo (palette may have changed)
                         editor.sync_ui();
+                        editor.sync_journal();
                     }
                     ModeState::BitFont(editor) => {
                         editor.redo();
@@ -1611,6 +1624,9 @@ impl MainWindow {
                         }
                     }
 
+                    // Journal first, undo/redo restores below are recorded as cell changes after the undo
+                    editor.sync_journal();
+
                     // Sync collaboration state from undo stack after editor updates
                     if self.collaboration_state.is_connected() {
                         if let Some((undo_stack_arc, caret_pos, selecting)) = editor.get_collab_sync_info() {
@@ -1621,6 +1637,14 @@ impl MainWindow {
                                 }
                             }
                         }
+                        // Undo/redo skipped cells other users changed since, put their content back
+                        for restore in self.collaboration_state.core.take_pending_restores() {
+                            editor.apply_remote_draw(restore.layer, restore.col, restore.row, &restore.block);
+                        }
+                        let commands = editor.with_edit_state_readonly(|state| self.collaboration_state.core.sync_document(state.get_buffer()));
+                        if let Some(collab_task) = self.collaboration_state.send_commands(commands) {
+                            collab_tasks.push(collab_task.map(|()| Message::Noop));
+                        }
                     }
 
                     if collab_tasks.is_empty() {
@@ -2016,7 +2040,7 @@ impl MainWindow {
             }
             Message::Collaboration(ref collab_msg) => {
                 use crate::ui::collaboration::CollaborationMessage;
-                use icy_engine_edit::collaboration::CollaborationEvent;
+                use icy_engine_edit::collaboration::{CollaborationEvent, ServerNotice};
 
                 match collab_msg {
                     CollaborationMessage::Ready(client) => {
@@ -2026,6 +2050,8 @@ impl MainWindow {
                         return Task::done(Message::ShowToast(toast));
                     }
                     CollaborationMessage::Event(event) => {
+                        // Per-user undo needs to know who changed which cell
+                        self.collaboration_state.core.record_remote_event(event);
                         match event {
                             CollaborationEvent::Connected(doc) => {
                                 self.collaboration_state.start_session(doc);
@@ -2099,12 +2125,67 @@ impl MainWindow {
                                     self.collaboration_state.add_system_message(&format!("{nick} changed the SAUCE record"));
                                 }
                             }
-                            CollaborationEvent::Draw { col, row, block } => {
+                            CollaborationEvent::Draw { layer, col, row, block, .. } => {
                                 // Apply remote draw to our buffer
                                 if let ModeState::Ansi(editor) = &mut self.mode_state {
-                                    editor.apply_remote_draw(*col, *row, block.code, block.fg, block.bg);
+                                    editor.apply_remote_draw(*layer, *col, *row, block);
                                 }
                             }
+                            CollaborationEvent::PaletteChanged { user_id, colors } => {
+                                if let ModeState::Ansi(editor) = &mut self.mode_state {
+                                    editor.apply_remote_palette(colors);
+                                }
+                                self.remember_remote_document_change();
+                                if let Some(user) = self.collaboration_state.get_user(*user_id) {
+                                    self.collaboration_state.add_system_message(&format!("{} changed the palette", user.user.nick));
+                                }
+                            }
+                            CollaborationEvent::LayerAdded { index, layer, .. } => {
+                                if let ModeState::Ansi(editor) = &mut self.mode_state {
+                                    editor.apply_remote_layer_added(*index, (**layer).clone());
+                                }
+                                self.remember_remote_document_change();
+                            }
+                            CollaborationEvent::LayerRemoved { index, .. } => {
+                                if let ModeState::Ansi(editor) = &mut self.mode_state {
+                                    editor.apply_remote_layer_removed(*index);
+                                }
+                                self.remember_remote_document_change();
+                            }
+                            CollaborationEvent::LayerReordered { from, to, .. } => {
+                                if let ModeState::Ansi(editor) = &mut self.mode_state {
+                                    editor.apply_remote_layer_reordered(*from, *to);
+                                }
+                                self.remember_remote_document_change();
+                            }
+                            CollaborationEvent::LayerPropertiesChanged { index, properties, .. } => {
+                                if let ModeState::Ansi(editor) = &mut self.mode_state {
+                                    editor.apply_remote_layer_properties(*index, properties.clone());
+                                }
+                                self.remember_remote_document_change();
+                            }
+                            CollaborationEvent::FontSlotChanged { user_id, slot, font } => {
+                                if let ModeState::Ansi(editor) = &mut self.mode_state {
+                                    editor.apply_remote_font_slot(*slot, font.as_deref().cloned());
+                                }
+                                self.remember_remote_document_change();
+                                if let Some(user) = self.collaboration_state.get_user(*user_id) {
+                                    self.collaboration_state
+                                        .add_system_message(&format!("{} changed font slot {}", user.user.nick, slot));
+                                }
+                            }
+                            CollaborationEvent::TagsChanged { tags, .. } => {
+                                if let ModeState::Ansi(editor) = &mut self.mode_state {
+                                    editor.apply_remote_tags(tags.clone());
+                                }
+                                self.remember_remote_document_change();
+                            }
+                            CollaborationEvent::AttributesChanged { attributes, .. } => {
+                                if let ModeState::Ansi(editor) = &mut self.mode_state {
+                                    editor.apply_remote_attributes(attributes);
+                                }
+                                self.remember_remote_document_change();
+                            }
                             CollaborationEvent::CanvasResized { user_id, columns, rows } => {
                                 self.collaboration_state.update_canvas_size(*columns, *rows);
 
@@ -2181,6 +2262,9 @@ impl MainWindow {
                             CollaborationEvent::Chat(msg) => {
                                 self.collaboration_state.add_chat_message(msg.clone());
                             }
+                            CollaborationEvent::Notice(notice) => match notice {
+                                ServerNotice::ReadOnly => self.collaboration_state.add_system_message(&fl!("collab-notice-read-only")),
+                            },
                             CollaborationEvent::Disconnected => {
                                 log::info!("Disconnected from collaboration server");
                                 self.collaboration_state.end_session();
@@ -2264,6 +2348,14 @@ impl MainWindow {
         ToastManager::new(with_dialogs, &self.toasts, Message::CloseToast).into()
     }
 
+    /// Take a remote document change as synced so it isn't sent back to the server
+    fn remember_remote_document_change(&mut self) {
+        if let ModeState::Ansi(editor) = &mut self.mode_state {
+            editor.sync_ui();
+            editor.with_edit_state_readonly(|state| self.collaboration_state.core.remember_document(state.get_buffer()));
+        }
+    }
+
     /// Sync remote cursor positions from collaboration state to the ANSI editor
     fn sync_remote_cursors_to_editor(&mut self) {
         use crate::ui::collaboration::state::CursorMode;
@@ -3047,13 +3139,28 @@ impl MainWindow {
                     let _ = tx.send(result);
                 }
             }
+
+            McpCommand::AnsiFindReplace {
+                find,
+                replace,
+                scope,
+                response,
+            } => {
+                let result = match &mut self.mode_state {
+                    ModeState::Ansi(editor) => editor.find_replace(find, replace.as_ref(), scope.as_deref()),
+                    _ => Err("Not in ANSI editor mode".to_string()),
+                };
+                if let Some(tx) = response.lock().take() {
+                    let _ = tx.send(result);
+                }
+            }
         }
     }
 
     /// Build editor status for MCP `get_status` command
     fn build_editor_status(&self) -> crate::mcp::types::EditorStatus {
         use crate::mcp::types::{
-            AnimationStatus, AnsiStatus, BitFontStatus, BufferInfo, CaretInfo, ColorInfo, EditorStatus, LayerInfo, RectangleInfo, SelectionInfo,
+            AnimationStatus, AnsiStatus, BitFontStatus, BufferInfo, CaretInfo, ColorInfo, EditorStatus, LayerInfo, RectangleInfo, SelectionInfo, SymmetryInfo,
             TextAttributeInfo,
         };
 
@@ -3097,10 +3204,16 @@ impl MainWindow {
                             icy_engine::Mode::Normal => "normal",
                             icy_engine::Mode::Chars => "chars",
                             icy_engine::Mode::Attributes => "attributes",
+                            icy_engine::Mode::Foreground => "foreground",
+                            icy_engine::Mode::Background => "background",
+                            icy_engine::Mode::Darken => "darken",
+                            icy_engine::Mode::Lighten => "lighten",
+                            icy_engine::Mode::Mask => "mask",
                         };
                         let role = match layer.role {
                             icy_engine::Role::Normal => "normal",
                             icy_engine::Role::Image => "image",
+                            icy_engine::Role::Group => "group",
                         };
                         LayerInfo {
                             index,
@@ -3140,6 +3253,19 @@ impl MainWindow {
                     }
                 });
 
+                let symmetry = state.symmetry();
+                let symmetry = SymmetryInfo {
+                    mode: match symmetry.mode {
+                        icy_engine_edit::brushes::MirrorMode::None => "none",
+                        icy_engine_edit::brushes::MirrorMode::Horizontal => "horizontal",
+                        icy_engine_edit::brushes::MirrorMode::Vertical => "vertical",
+                        icy_engine_edit::brushes::MirrorMode::Both => "both",
+                    }
+                    .to_string(),
+                    axis_x: symmetry.axis.map(|axis| axis.x),
+                    axis_y: symmetry.axis.map(|axis| axis.y),
+                };
+
                 // Font mode string
                 let font_mode = match buffer.font_mode {
                     icy_engine::FontMode::Sauce => "sauce",
@@ -3192,6 +3318,7 @@ impl MainWindow {
                     format_mode: state.get_format_mode().to_string(),
                     outline_style: state.get_outline_style(),
                     mirror_mode: state.get_mirror_mode(),
+                    symmetry,
                 }
             }))
         } else {
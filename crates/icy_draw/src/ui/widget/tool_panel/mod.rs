@@ -536,8 +536,8 @@ const STANDARD_ICONS: &[&[u8]] = &[
     include_bytes!("../../../../data/icons/dropper.svg"),           // 9: Pipette
     include_bytes!("../../../../data/icons/font.svg"),              // 10: Font
     include_bytes!("../../../../data/icons/tag.svg"),               // 11: Tag
-    include_bytes!("../../../../data/icons/cursor.svg"),            // 12: Placeholder (duplicate)
-    include_bytes!("../../../../data/icons/cursor.svg"),            // 13: Placeholder (duplicate)
+    include_bytes!("../../../../data/icons/gradient.svg"),          // 12: Gradient
+    include_bytes!("../../../../data/icons/text_box.svg"),          // 13: TextBox
     include_bytes!("../../../../data/icons/cursor.svg"),            // 14: Placeholder (duplicate)
     include_bytes!("../../../../data/icons/cursor.svg"),            // 15: Placeholder (duplicate)
 ];
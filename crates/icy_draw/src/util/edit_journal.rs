@@ -0,0 +1,61 @@
+//! Edit journal files
+//!
+//! Journals are stored next to their document as `<document>.icyjournal` and use bitcode,
+//! like the session data. The bitcode data is preceded by the journal format version as a
+//! little endian u32, so journals of other versions are recognized before decoding them.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use icy_engine_edit::{EditJournal, JOURNAL_EXTENSION, JOURNAL_VERSION};
+
+/// Path of the journal belonging to `document`
+pub fn journal_path(document: &Path) -> PathBuf {
+    let mut path = document.as_os_str().to_owned();
+    path.push(".");
+    path.push(JOURNAL_EXTENSION);
+    PathBuf::from(path)
+}
+
+pub fn save_journal(path: &Path, journal: &EditJournal) -> Result<(), String> {
+    let mut bytes = JOURNAL_VERSION.to_le_bytes().to_vec();
+    bytes.extend(bitcode::serialize(journal).map_err(|e| format!("Failed to serialize edit journal: {e}"))?);
+
+    // Atomic write
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, &bytes).map_err(|e| format!("Failed to write edit journal: {e}"))?;
+    fs::rename(&temp_path, path).map_err(|e| format!("Failed to rename edit journal: {e}"))?;
+
+    log::debug!("Edit journal saved to {:?} ({} entries)", path, journal.len());
+    Ok(())
+}
+
+pub fn load_journal(path: &Path) -> Option<EditJournal> {
+    if !path.exists() {
+        return None;
+    }
+    match fs::read(path) {
+        Ok(bytes) => {
+            let Some((version, data)) = bytes.split_first_chunk::<4>() else {
+                log::warn!("Edit journal {path:?} is truncated");
+                return None;
+            };
+            let version = u32::from_le_bytes(*version);
+            if version != JOURNAL_VERSION {
+                log::warn!("Edit journal {path:?} has version {version}, expected {JOURNAL_VERSION}");
+                return None;
+            }
+            match bitcode::deserialize::<EditJournal>(data) {
+                Ok(journal) => Some(journal),
+                Err(e) => {
+                    log::warn!("Failed to deserialize edit journal from {path:?}: {e}");
+                    None
+                }
+            }
+        }
+        Err(e) => {
+            log::warn!("Failed to read edit journal from {path:?}: {e}");
+            None
+        }
+    }
+}
@@ -6,3 +6,6 @@ pub use plugins::*;
 
 pub mod tag_replacements;
 pub use tag_replacements::*;
+
+pub mod edit_journal;
+pub use edit_journal::*;
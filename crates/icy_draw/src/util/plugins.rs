@@ -6,9 +6,9 @@
 use std::{collections::HashMap, fs, path::Path};
 
 use i18n_embed_fl::fl;
-use icy_engine::{attribute, AttributedChar, Position, TextPane};
-use icy_engine_edit::EditState;
-use mlua::{Lua, UserData};
+use icy_engine::{attribute, AttributeColor, AttributedChar, Position, TextPane};
+use icy_engine_edit::{CellPattern, EditState, ReplaceScope};
+use mlua::{Lua, Table, UserData};
 use parking_lot::Mutex;
 use regex::Regex;
 use std::sync::Arc;
@@ -333,6 +333,43 @@ impl LuaBufferView {
         // Buffer type-specific conversion could be added later if needed.
         ch.ch.to_string()
     }
+
+    /// Reads a find / replace table `{ ch = "░", fg = 9, bg = 0, blink = false, font_page = 0 }`, missing fields are wildcards
+    fn cell_pattern_from_table(&self, table: &Table) -> mlua::Result<CellPattern> {
+        let ch = match table.get::<Option<String>>("ch")? {
+            Some(ch) => Some(self.convert_from_unicode(ch)?),
+            None => None,
+        };
+        Ok(CellPattern {
+            ch,
+            foreground: palette_color(table.get("fg")?, "fg")?,
+            background: palette_color(table.get("bg")?, "bg")?,
+            blink: table.get("blink")?,
+            font_page: table.get("font_page")?,
+        })
+    }
+}
+
+/// Palette index of a find / replace table field, indices above 255 are an error
+fn palette_color(color: Option<u32>, field: &str) -> mlua::Result<Option<AttributeColor>> {
+    color
+        .map(|c| {
+            u8::try_from(c)
+                .map(AttributeColor::Palette)
+                .map_err(|_| mlua::Error::RuntimeError(format!("{field} color {c} out of range (0..=255)")))
+        })
+        .transpose()
+}
+
+/// "selection", "layer" or "document" - without a scope the selection is used if there is one, otherwise the current layer
+fn replace_scope(state: &EditState, scope: Option<&str>) -> mlua::Result<ReplaceScope> {
+    match scope {
+        None => Ok(state.default_replace_scope()),
+        Some("layer") => Ok(ReplaceScope::Layer),
+        Some("selection") => Ok(ReplaceScope::Selection),
+        Some("document") => Ok(ReplaceScope::Document),
+        Some(scope) => Err(mlua::Error::RuntimeError(format!("Unknown scope '{scope}' (selection, layer or document)"))),
+    }
 }
 
 impl UserData for LuaBufferView {
@@ -614,6 +651,26 @@ impl UserData for LuaBufferView {
             })?
         });
 
+        methods.add_method_mut("find", |_, this, (pattern, scope): (Table, Option<String>)| {
+            let pattern = this.cell_pattern_from_table(&pattern)?;
+            this.with_edit_state(|state| -> mlua::Result<usize> {
+                let scope = replace_scope(state, scope.as_deref())?;
+                Ok(state.find_cells(&pattern, scope).len())
+            })?
+        });
+
+        methods.add_method_mut("replace", |_, this, (pattern, replacement, scope): (Table, Table, Option<String>)| {
+            let pattern = this.cell_pattern_from_table(&pattern)?;
+            let replacement = this.cell_pattern_from_table(&replacement)?;
+            this.with_edit_state(|state| {
+                let scope = replace_scope(state, scope.as_deref())?;
+                state.replace_cells(&pattern, &replacement, scope).map_err(|err| mlua::Error::SyntaxError {
+                    message: format!("Error replacing chars: {err}"),
+                    incomplete_input: false,
+                })
+            })?
+        });
+
         methods.add_method_mut("clear", |_, this, ()| {
             this.with_edit_state(|state| {
                 state.get_buffer_mut().reset_terminal();
@@ -10,6 +10,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use icy_engine::{BbsDialect, DisplayCodeProfile};
 use serde::Deserialize;
 
 /// A single tag replacement entry.
@@ -21,6 +22,19 @@ pub struct TagReplacement {
     pub tag: String,
     /// Description of what the tag does
     pub description: String,
+    /// Equivalent `PCBoard` macro, used to translate the tag between BBS dialects (defaults to `tag`)
+    #[serde(default)]
+    pub key: String,
+}
+
+impl TagReplacement {
+    fn key(&self) -> &str {
+        if self.key.is_empty() {
+            &self.tag
+        } else {
+            &self.key
+        }
+    }
 }
 
 /// A loaded tag replacement list.
@@ -36,6 +50,8 @@ pub struct TagReplacementList {
     pub comments: String,
     /// Taglist format/content version (free-form)
     pub version: String,
+    /// Display code syntax of the BBS the list is for
+    pub dialect: BbsDialect,
     /// The replacement entries
     pub entries: Vec<TagReplacement>,
 }
@@ -63,6 +79,8 @@ struct TaglistToml {
     #[serde(default)]
     pub version: String,
     #[serde(default)]
+    pub dialect: BbsDialect,
+    #[serde(default)]
     pub entries: Vec<TagReplacement>,
 }
 
@@ -74,9 +92,43 @@ impl TagReplacementList {
             description: toml.description,
             comments: toml.comments,
             version: toml.version,
+            dialect: toml.dialect,
             entries: toml.entries,
         }
     }
+
+    /// Sample values of all entries for the live preview, validation and export
+    pub fn display_code_profile(&self) -> DisplayCodeProfile {
+        let mut profile = DisplayCodeProfile::new(self.dialect);
+        for entry in &self.entries {
+            let code = self.dialect.base_code(&entry.tag).unwrap_or_else(|| entry.tag.clone());
+            profile.samples.insert(code, entry.example.clone());
+        }
+        profile
+    }
+
+    fn find_entry(&self, code: &str) -> Option<&TagReplacement> {
+        let base = self.dialect.base_code(code);
+        self.entries
+            .iter()
+            .find(|e| e.tag == code || (base.is_some() && self.dialect.base_code(&e.tag) == base))
+    }
+
+    /// Translates `code` into the dialect of `target`, keeping its field width
+    ///
+    /// Returns `None` if `target` has no equivalent entry.
+    pub fn translate_code(&self, code: &str, target: &TagReplacementList) -> Option<String> {
+        let key = self.find_entry(code)?.key();
+        let entry = target.entries.iter().find(|e| e.key() == key)?;
+        let width = self.dialect.parse_code(code).and_then(|c| c.width);
+        match target.dialect.parse_code(&entry.tag) {
+            Some(mut translated) if width.is_some() => {
+                translated.width = width;
+                Some(target.dialect.format_code(&translated))
+            }
+            _ => Some(entry.tag.clone()),
+        }
+    }
 }
 
 fn parse_taglist_toml(id: &str, text: &str) -> Result<TagReplacementList, toml::de::Error> {
@@ -103,56 +155,43 @@ fn load_taglist_toml_from_path(id: &str, path: &Path) -> Option<TagReplacementLi
     }
 }
 
+/// Built-in lists as (id, display name, TOML content)
+const BUILTIN_TAGLISTS: [(&str, &str, &str); 5] = [
+    ("pcboard", "PCBoard", include_str!("../../data/tags/pcboard.toml")),
+    ("icyboard", "IcyBoard", include_str!("../../data/tags/icyboard.toml")),
+    ("synchronet", "Synchronet", include_str!("../../data/tags/synchronet.toml")),
+    ("mystic", "Mystic / Renegade", include_str!("../../data/tags/mystic.toml")),
+    ("wwiv", "WWIV", include_str!("../../data/tags/wwiv.toml")),
+];
+
+fn is_builtin_taglist(id: &str) -> bool {
+    BUILTIN_TAGLISTS.iter().any(|(builtin, _, _)| builtin.eq_ignore_ascii_case(id))
+}
+
 fn load_builtin_taglist(id: &str) -> Option<TagReplacementList> {
-    match id.to_ascii_lowercase().as_str() {
-        "pcboard" => {
-            let content = include_str!("../../data/tags/pcboard.toml");
-            match parse_taglist_toml("pcboard", content) {
-                Ok(mut list) => {
-                    // Keep legacy display name for compatibility.
-                    if list.name.trim().is_empty() {
-                        list.name = "PCBoard".to_string();
-                    }
-                    if list.name == "pcboard" {
-                        list.name = "PCBoard".to_string();
-                    }
-                    Some(list)
-                }
-                Err(err) => {
-                    log::error!("Failed to parse built-in taglist '{id}': {err}");
-                    None
-                }
+    let (id, name, content) = BUILTIN_TAGLISTS.iter().find(|(builtin, _, _)| builtin.eq_ignore_ascii_case(id))?;
+    match parse_taglist_toml(id, content) {
+        Ok(mut list) => {
+            // Keep legacy display names for compatibility.
+            if list.name.trim().is_empty() || list.name == *id {
+                list.name = (*name).to_string();
             }
+            Some(list)
         }
-        "icyboard" => {
-            let content = include_str!("../../data/tags/icyboard.toml");
-            match parse_taglist_toml("icyboard", content) {
-                Ok(mut list) => {
-                    if list.name.trim().is_empty() {
-                        list.name = "IcyBoard".to_string();
-                    }
-                    Some(list)
-                }
-                Err(err) => {
-                    log::error!("Failed to parse built-in taglist '{id}': {err}");
-                    None
-                }
-            }
+        Err(err) => {
+            log::error!("Failed to parse built-in taglist '{id}': {err}");
+            None
         }
-        _ => None,
     }
 }
 
 fn builtin_taglists() -> Vec<TaglistInfo> {
     // Keep built-ins explicit; they are compiled into the binary.
-    let mut lists = Vec::new();
-    if let Some(list) = load_builtin_taglist("pcboard") {
-        lists.push(TaglistInfo { id: list.id, name: list.name });
-    }
-    if let Some(list) = load_builtin_taglist("icyboard") {
-        lists.push(TaglistInfo { id: list.id, name: list.name });
-    }
-    lists
+    BUILTIN_TAGLISTS
+        .iter()
+        .filter_map(|(id, _, _)| load_builtin_taglist(id))
+        .map(|list| TaglistInfo { id: list.id, name: list.name })
+        .collect()
 }
 
 /// Get a list of available tag replacement lists.
@@ -192,7 +231,7 @@ pub fn get_available_taglists(taglists_dir: Option<&Path>) -> Vec<TaglistInfo> {
             continue;
         };
         let id = stem.to_string_lossy().to_string();
-        if id.eq_ignore_ascii_case("pcboard") || id.eq_ignore_ascii_case("icyboard") {
+        if is_builtin_taglist(&id) {
             continue;
         }
         if let Some(list) = load_taglist_toml_from_path(&id, &path) {
@@ -218,18 +257,20 @@ pub fn load_taglist(id: &str, taglists_dir: Option<&Path>) -> TagReplacementList
             description: String::new(),
             comments: String::new(),
             version: String::new(),
+            dialect: BbsDialect::default(),
             entries: Vec::new(),
         });
     }
 
     let id_lower = id.to_ascii_lowercase();
-    if id_lower == "pcboard" || id_lower == "icyboard" {
+    if is_builtin_taglist(&id_lower) {
         return load_builtin_taglist(&id_lower).unwrap_or_else(|| TagReplacementList {
             id: id_lower,
             name: String::new(),
             description: String::new(),
             comments: String::new(),
             version: String::new(),
+            dialect: BbsDialect::default(),
             entries: Vec::new(),
         });
     }
@@ -248,6 +289,7 @@ pub fn load_taglist(id: &str, taglists_dir: Option<&Path>) -> TagReplacementList
         description: String::new(),
         comments: String::new(),
         version: String::new(),
+        dialect: BbsDialect::default(),
         entries: Vec::new(),
     })
 }
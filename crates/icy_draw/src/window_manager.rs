@@ -1183,7 +1183,8 @@ impl WindowManager {
         let undo_info = focused_window.get_undo_info();
         let is_connected = focused_window.is_connected();
         let ansi_view_state: AnsiViewMenuState = focused_window.ansi_view_menu_state().unwrap_or_default();
-        let ansi_mirror_mode: bool = focused_window.ansi_mirror_mode().unwrap_or(false);
+        let ansi_symmetry = focused_window.ansi_symmetry().unwrap_or_default();
+        let ansi_journal_recording = focused_window.ansi_journal_recording().unwrap_or_default();
         let plugins = focused_window.plugins().clone();
 
         // Helper to wrap Message in WindowManagerMessage
@@ -1238,6 +1239,16 @@ impl WindowManager {
         if edit_mode == crate::ui::EditMode::Ansi {
             file_nodes.push(menu::separator!());
             file_nodes.push(menu::item!(fl!("menu-import-font"), wrap(Message::ShowImportFontDialog)));
+            file_nodes.push(menu::separator!());
+            file_nodes.push(menu::check_item!(
+                fl!("menu-record_journal"),
+                Some(ansi_journal_recording),
+                wrap(Message::AnsiEditor(AnsiEditorMessage::ToggleJournalRecording)),
+            ));
+            file_nodes.push(menu::item!(
+                fl!("menu-timelapse"),
+                wrap(Message::AnsiEditor(AnsiEditorMessage::ShowTimelapseDialog))
+            ));
         }
 
         file_nodes.extend(vec![
@@ -1364,17 +1375,47 @@ impl WindowManager {
                 ],
             );
             edit_nodes.push(area_ops);
+            edit_nodes.push(menu::item!(
+                fl!("menu-find_replace"),
+                wrap(Message::AnsiEditor(AnsiEditorMessage::ShowFindReplaceDialog))
+            ));
+            edit_nodes.push(menu::item!(
+                fl!("menu-recolor"),
+                wrap(Message::AnsiEditor(AnsiEditorMessage::ShowRecolorDialog))
+            ));
             edit_nodes.push(menu::separator!());
             edit_nodes.push(menu::item!(
                 fl!("menu-open_font_selector"),
                 wrap(Message::AnsiEditor(AnsiEditorMessage::OpenFontSelector))
             ));
             edit_nodes.push(menu::separator!());
-            edit_nodes.push(menu::check_item!(
+            let symmetry_item = |label: String, mode: icy_engine_edit::brushes::MirrorMode| {
+                menu::check_item!(
+                    label,
+                    (ansi_symmetry.mode == mode).then_some(true),
+                    wrap(Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::SetSymmetryMode(mode)))),
+                )
+            };
+            let symmetry = menu::MenuNode::submenu_with_id(
+                menu::MenuId::from_str("menu.symmetry"),
                 fl!("menu-mirror_mode"),
-                Some(ansi_mirror_mode),
-                wrap(Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::ToggleMirrorMode))),
-            ));
+                vec![
+                    symmetry_item(fl!("menu-symmetry_off"), icy_engine_edit::brushes::MirrorMode::None),
+                    symmetry_item(fl!("menu-symmetry_horizontal"), icy_engine_edit::brushes::MirrorMode::Horizontal),
+                    symmetry_item(fl!("menu-symmetry_vertical"), icy_engine_edit::brushes::MirrorMode::Vertical),
+                    symmetry_item(fl!("menu-symmetry_both"), icy_engine_edit::brushes::MirrorMode::Both),
+                    menu::separator!(),
+                    menu::item!(
+                        fl!("menu-symmetry_axis_at_caret"),
+                        wrap(Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::SetSymmetryAxisToCaret)))
+                    ),
+                    menu::item!(
+                        fl!("menu-symmetry_center_axis"),
+                        wrap(Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::CenterSymmetryAxis)))
+                    ),
+                ],
+            );
+            edit_nodes.push(symmetry);
             edit_nodes.push(menu::separator!());
             edit_nodes.push(menu::item!(fl!("menu-file-settings"), wrap(Message::ShowFileSettingsDialog)));
         }
@@ -1402,6 +1443,10 @@ impl WindowManager {
                 fl!("menu-add_fonts"),
                 wrap(Message::CharFontEditor(CharFontEditorMessage::OpenAddFontDialog))
             ));
+            edit_nodes.push(menu::item!(
+                fl!("menu-generate_font"),
+                wrap(Message::CharFontEditor(CharFontEditorMessage::OpenGenerateFontDialog))
+            ));
             edit_nodes.push(menu::item!(
                 fl!("tdf-dialog-edit-settings-title"),
                 wrap(Message::CharFontEditor(CharFontEditorMessage::OpenEditSettingsDialog))
@@ -1494,6 +1539,20 @@ impl WindowManager {
                         fl!("menu-justifycenter"),
                         wrap(Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::JustifyCenter)))
                     ),
+                    menu::separator!(),
+                    menu::item!(
+                        fl!("menu-rotate_cw"),
+                        wrap(Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::RotateCw)))
+                    ),
+                    menu::item!(
+                        fl!("menu-rotate_ccw"),
+                        wrap(Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::RotateCcw)))
+                    ),
+                    menu::item!(
+                        fl!("menu-rotate_180"),
+                        wrap(Message::AnsiEditor(AnsiEditorMessage::Core(AnsiEditorCoreMessage::Rotate180)))
+                    ),
+                    menu::item!(fl!("menu-scale"), wrap(Message::AnsiEditor(AnsiEditorMessage::ShowScaleDialog))),
                 ],
             );
             extra_top_level.push(selection_menu);
@@ -1826,6 +1885,10 @@ impl WindowManager {
                 fl!("menu-fonts"),
                 vec![
                     menu::item!(fl!("menu-add_fonts"), wrap(Message::CharFontEditor(CharFontEditorMessage::OpenAddFontDialog))),
+                    menu::item!(
+                        fl!("menu-generate_font"),
+                        wrap(Message::CharFontEditor(CharFontEditorMessage::OpenGenerateFontDialog))
+                    ),
                     menu::item!(
                         fl!("tdf-dialog-edit-settings-title"),
                         wrap(Message::CharFontEditor(CharFontEditorMessage::OpenEditSettingsDialog))
@@ -0,0 +1,338 @@
+//! BDF (Glyph Bitmap Distribution Format) reader and writer.
+//!
+//! BDF is the text format of X11 bitmap fonts, most Unicode console fonts (Terminus,
+//! Unifont, Spleen, Tamzen) ship as BDF or as its compiled form PCF. Both load into
+//! [`BdfFont`]: glyphs already placed in the character cell, keyed by their encoding.
+//!
+//! Glyphs wider than 8 pixels are cut at the right, cells taller than 32 pixels at the bottom.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use super::codepage::FontCodepage;
+use super::compact_glyph::{CompactGlyph, MAX_GLYPH_HEIGHT};
+use super::{BitFont, BitFontType};
+use crate::EngineError;
+
+/// A glyph and the code point it is encoded at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BdfGlyph {
+    pub encoding: u32,
+    pub glyph: CompactGlyph,
+}
+
+/// Bitmap font with encoded glyphs, loaded from BDF or PCF
+#[derive(Debug, Clone)]
+pub struct BdfFont {
+    /// Family name (`FAMILY_NAME`) or the XLFD font name
+    pub name: Option<String>,
+    /// Cell width in pixels
+    pub width: u8,
+    /// Cell height in pixels
+    pub height: u8,
+    /// Pixels above the baseline
+    pub ascent: i32,
+    /// True if the encodings are Unicode code points (`CHARSET_REGISTRY "ISO10646"`)
+    pub unicode: bool,
+    pub glyphs: Vec<BdfGlyph>,
+}
+
+/// Cell geometry shared by the BDF and PCF loaders.
+pub(crate) struct CellLayout {
+    pub width: u8,
+    pub height: u8,
+    pub ascent: i32,
+    /// Horizontal offset of the cell from the glyph origin
+    pub left: i32,
+}
+
+impl CellLayout {
+    pub fn new(width: i32, ascent: i32, descent: i32, left: i32) -> crate::Result<Self> {
+        let height = ascent
+            .checked_add(descent)
+            .ok_or_else(|| EngineError::Generic(format!("font ascent {ascent} and descent {descent} out of range")))?;
+        Ok(Self {
+            width: width.clamp(1, 8) as u8,
+            height: height.clamp(1, MAX_GLYPH_HEIGHT as i32) as u8,
+            ascent,
+            left,
+        })
+    }
+
+    /// Place a glyph bitmap in the cell.
+    ///
+    /// `rows` holds MSB-first bitmap rows of a glyph whose box starts `x_offset` pixels right of the origin
+    /// and whose top row is `top` pixels above the baseline.
+    pub fn place(&self, rows: &[u8], x_offset: i32, top: i32) -> CompactGlyph {
+        let mut glyph = CompactGlyph::new(self.width, self.height);
+        let shift = x_offset - self.left;
+        let mask = 0xFFu8 << (8 - self.width);
+        for (i, &row) in rows.iter().enumerate() {
+            let y = self.ascent - top + i as i32;
+            if y < 0 || y >= self.height as i32 {
+                continue;
+            }
+            let row = match shift {
+                0 => row,
+                1..=7 => row >> shift,
+                -7..=-1 => row << -shift,
+                _ => 0,
+            };
+            glyph.data[y as usize] = row & mask;
+        }
+        glyph
+    }
+}
+
+impl BdfFont {
+    /// Parse a BDF font.
+    pub fn from_bytes(bytes: &[u8]) -> crate::Result<Self> {
+        let text = String::from_utf8_lossy(bytes);
+        let mut lines = text.lines().map(str::trim);
+        if !lines.next().is_some_and(|l| l.starts_with("STARTFONT")) {
+            return Err(EngineError::Generic("BDF: missing STARTFONT".into()));
+        }
+
+        let mut font_name = None;
+        let mut properties: HashMap<String, String> = HashMap::new();
+        let mut bounding_box = None;
+        let mut raw_glyphs = Vec::new();
+
+        while let Some(line) = lines.next() {
+            let (keyword, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            match keyword {
+                "FONT" => font_name = Some(args.trim().to_string()),
+                "FONTBOUNDINGBOX" => bounding_box = Some(parse_numbers::<4>(args, "FONTBOUNDINGBOX")?),
+                "STARTPROPERTIES" => {
+                    for line in lines.by_ref() {
+                        if line.starts_with("ENDPROPERTIES") {
+                            break;
+                        }
+                        if let Some((key, value)) = line.split_once(char::is_whitespace) {
+                            properties.insert(key.to_string(), value.trim().trim_matches('"').to_string());
+                        }
+                    }
+                }
+                "STARTCHAR" => raw_glyphs.push(RawGlyph::parse(&mut lines)?),
+                "ENDFONT" => break,
+                _ => {}
+            }
+        }
+
+        let [bbx_width, bbx_height, bbx_x, bbx_y] = bounding_box.ok_or_else(|| EngineError::Generic("BDF: missing FONTBOUNDINGBOX".into()))?;
+        let property = |key: &str| properties.get(key).and_then(|v| v.parse::<i32>().ok());
+        let ascent = property("FONT_ASCENT").unwrap_or(bbx_height.saturating_add(bbx_y));
+        let descent = property("FONT_DESCENT").unwrap_or(bbx_y.saturating_neg());
+        let layout = CellLayout::new(bbx_width, ascent, descent, bbx_x)?;
+
+        let registry = properties.get("CHARSET_REGISTRY").map(|r| r.to_ascii_uppercase());
+        let unicode = match registry {
+            Some(registry) => registry.contains("10646") || registry.contains("UNICODE"),
+            None => raw_glyphs.iter().any(|g| g.encoding > 0xFF),
+        };
+
+        let glyphs = raw_glyphs
+            .into_iter()
+            .filter(|g| g.encoding >= 0)
+            .map(|g| BdfGlyph {
+                encoding: g.encoding as u32,
+                glyph: layout.place(&g.rows, g.bbx[2], g.bbx[1] + g.bbx[3]),
+            })
+            .collect();
+
+        Ok(Self {
+            name: properties.get("FAMILY_NAME").cloned().or(font_name),
+            width: layout.width,
+            height: layout.height,
+            ascent: layout.ascent,
+            unicode,
+            glyphs,
+        })
+    }
+
+    /// Encode the glyphs of `font` at the Unicode code points of `codepage`.
+    ///
+    /// Slots that map to a code point used by an earlier slot are skipped, Unicode glyphs
+    /// of the font are written after the slots.
+    pub fn from_bitfont(font: &BitFont, codepage: FontCodepage) -> Self {
+        let mut glyphs: Vec<BdfGlyph> = Vec::with_capacity(256);
+        for (slot, glyph) in font.glyphs().iter().enumerate() {
+            let encoding = codepage.to_unicode(slot as u8) as u32;
+            if glyphs.iter().all(|g| g.encoding != encoding) {
+                glyphs.push(BdfGlyph { encoding, glyph: *glyph });
+            }
+        }
+        // Unicode glyphs the slots don't cover
+        let slot_encodings: HashSet<u32> = glyphs.iter().map(|g| g.encoding).collect();
+        let mut unicode: Vec<BdfGlyph> = font
+            .unicode_glyphs
+            .iter()
+            .map(|(ch, glyph)| BdfGlyph {
+                encoding: *ch as u32,
+                glyph: *glyph,
+            })
+            .filter(|g| !slot_encodings.contains(&g.encoding))
+            .collect();
+        unicode.sort_unstable_by_key(|g| g.encoding);
+        glyphs.extend(unicode);
+        let descent = font.height as i32 / 4;
+        Self {
+            name: Some(font.name().to_string()),
+            width: font.width,
+            height: font.height,
+            ascent: font.height as i32 - descent,
+            unicode: true,
+            glyphs,
+        }
+    }
+
+    /// Fill the 256 slots of a `BitFont`.
+    ///
+    /// Unicode fonts are mapped through `codepage` and keep all glyphs as Unicode glyphs,
+    /// other fonts use their encoding as slot.
+    pub fn to_bitfont(&self, name: impl Into<String>, codepage: FontCodepage) -> BitFont {
+        let by_encoding: HashMap<u32, &CompactGlyph> = self.glyphs.iter().map(|g| (g.encoding, &g.glyph)).collect();
+        let glyphs = std::array::from_fn(|slot| {
+            let encoding = if self.unicode { codepage.to_unicode(slot as u8) as u32 } else { slot as u32 };
+            by_encoding.get(&encoding).map_or(CompactGlyph::new(self.width, self.height), |g| **g)
+        });
+        let unicode_glyphs = if self.unicode {
+            self.glyphs.iter().filter_map(|g| Some((char::from_u32(g.encoding)?, g.glyph))).collect()
+        } else {
+            HashMap::new()
+        };
+        BitFont {
+            name: name.into(),
+            width: self.width,
+            height: self.height,
+            glyphs,
+            unicode_glyphs: Arc::new(unicode_glyphs),
+            path_opt: None,
+            font_type: BitFontType::Custom,
+        }
+    }
+
+    /// Write the font as BDF 2.1.
+    pub fn to_bdf_bytes(&self) -> Vec<u8> {
+        use std::fmt::Write;
+
+        let name = self.name.clone().unwrap_or_else(|| "Unnamed".to_string());
+        let (width, height) = (self.width as i32, self.height as i32);
+        let descent = height - self.ascent;
+        let (registry, encoding) = if self.unicode { ("ISO10646", "1") } else { ("FontSpecific", "0") };
+        let xlfd_family = name.replace(['-', '"'], " ");
+
+        let mut out = String::new();
+        let _ = writeln!(out, "STARTFONT 2.1");
+        let _ = writeln!(
+            out,
+            "FONT -icy-{xlfd_family}-Medium-R-Normal--{height}-{}-75-75-C-{}-{registry}-{encoding}",
+            height * 10,
+            width * 10
+        );
+        let _ = writeln!(out, "SIZE {height} 75 75");
+        let _ = writeln!(out, "FONTBOUNDINGBOX {width} {height} 0 {}", -descent);
+        let _ = writeln!(out, "STARTPROPERTIES 10");
+        let _ = writeln!(out, "FAMILY_NAME \"{}\"", name.replace('"', "'"));
+        let _ = writeln!(out, "WEIGHT_NAME \"Medium\"");
+        let _ = writeln!(out, "SLANT \"R\"");
+        let _ = writeln!(out, "SPACING \"C\"");
+        let _ = writeln!(out, "PIXEL_SIZE {height}");
+        let _ = writeln!(out, "AVERAGE_WIDTH {}", width * 10);
+        let _ = writeln!(out, "FONT_ASCENT {}", self.ascent);
+        let _ = writeln!(out, "FONT_DESCENT {descent}");
+        let _ = writeln!(out, "CHARSET_REGISTRY \"{registry}\"");
+        let _ = writeln!(out, "CHARSET_ENCODING \"{encoding}\"");
+        let _ = writeln!(out, "ENDPROPERTIES");
+        let _ = writeln!(out, "CHARS {}", self.glyphs.len());
+
+        for g in &self.glyphs {
+            if self.unicode {
+                let _ = writeln!(out, "STARTCHAR uni{:04X}", g.encoding);
+            } else {
+                let _ = writeln!(out, "STARTCHAR char{}", g.encoding);
+            }
+            let _ = writeln!(out, "ENCODING {}", g.encoding);
+            let _ = writeln!(out, "SWIDTH {} 0", width * 1000 / height.max(1));
+            let _ = writeln!(out, "DWIDTH {width} 0");
+            let _ = writeln!(out, "BBX {width} {height} 0 {}", -descent);
+            let _ = writeln!(out, "BITMAP");
+            for row in &g.glyph.data[..height as usize] {
+                let _ = writeln!(out, "{row:02X}");
+            }
+            let _ = writeln!(out, "ENDCHAR");
+        }
+        let _ = writeln!(out, "ENDFONT");
+        out.into_bytes()
+    }
+}
+
+/// Glyph as stored in the file, before it is placed in the cell
+struct RawGlyph {
+    encoding: i64,
+    /// Width, height, x offset and y offset of the glyph box
+    bbx: [i32; 4],
+    /// First byte of each bitmap row
+    rows: Vec<u8>,
+}
+
+impl RawGlyph {
+    fn parse<'a>(lines: &mut impl Iterator<Item = &'a str>) -> crate::Result<Self> {
+        let mut encoding = -1;
+        let mut bbx = None;
+        let mut rows = Vec::new();
+        while let Some(line) = lines.next() {
+            let (keyword, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            match keyword {
+                // "ENCODING -1 <index>" is unencoded, the optional second number is a font specific code
+                "ENCODING" => encoding = args.split_whitespace().next().and_then(|e| e.parse().ok()).unwrap_or(-1),
+                "BBX" => bbx = Some(parse_numbers::<4>(args, "BBX")?),
+                "BITMAP" => {
+                    for line in lines.by_ref() {
+                        if line.starts_with("ENDCHAR") {
+                            break;
+                        }
+                        let byte = line.get(..2.min(line.len())).and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                        rows.push(byte.ok_or_else(|| EngineError::Generic(format!("BDF: invalid bitmap row '{line}'")))?);
+                    }
+                    break;
+                }
+                "ENDCHAR" => break,
+                _ => {}
+            }
+        }
+        let bbx = bbx.ok_or_else(|| EngineError::Generic("BDF: glyph without BBX".into()))?;
+        Ok(Self { encoding, bbx, rows })
+    }
+}
+
+fn parse_numbers<const N: usize>(args: &str, keyword: &str) -> crate::Result<[i32; N]> {
+    let mut result = [0; N];
+    let mut values = args.split_whitespace();
+    for value in &mut result {
+        *value = values
+            .next()
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| EngineError::Generic(format!("BDF: invalid {keyword} '{args}'")))?;
+    }
+    Ok(result)
+}
+
+impl BitFont {
+    /// Load a BDF font, Unicode fonts are mapped to the slots through `codepage`.
+    pub fn from_bdf(name: impl Into<String>, data: &[u8], codepage: FontCodepage) -> crate::Result<Self> {
+        Ok(BdfFont::from_bytes(data)?.to_bitfont(name, codepage))
+    }
+
+    /// Load a PCF font, Unicode fonts are mapped to the slots through `codepage`.
+    pub fn from_pcf(name: impl Into<String>, data: &[u8], codepage: FontCodepage) -> crate::Result<Self> {
+        Ok(BdfFont::from_pcf_bytes(data)?.to_bitfont(name, codepage))
+    }
+
+    /// Save as BDF with the Unicode encodings of `codepage`.
+    pub fn to_bdf_bytes(&self, codepage: FontCodepage) -> Vec<u8> {
+        BdfFont::from_bitfont(self, codepage).to_bdf_bytes()
+    }
+}
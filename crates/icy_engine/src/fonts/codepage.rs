@@ -0,0 +1,82 @@
+//! Codepage tables for mapping Unicode fonts to the 256 glyph slots of a `BitFont`.
+//!
+//! Unicode bitmap fonts (BDF, PCF) have no slot order, the codepage picks the glyph
+//! for every slot. All DOS codepages share the CP437 lower half including the
+//! control code glyphs (☺, ♥, …).
+
+use codepages::tables::CP437_TO_UNICODE;
+use serde::{Deserialize, Serialize};
+
+/// Codepage used to map between glyph slots and Unicode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum FontCodepage {
+    /// IBM PC, US
+    #[default]
+    Cp437,
+    /// DOS Latin-1, Western Europe
+    Cp850,
+    /// DOS Cyrillic
+    Cp866,
+    /// ISO 8859-1, slot n is U+00nn
+    Latin1,
+}
+
+/// Upper half of CP850
+const CP850_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', //
+    'É', 'æ', 'Æ', 'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', 'ø', '£', 'Ø', '×', 'ƒ', //
+    'á', 'í', 'ó', 'ú', 'ñ', 'Ñ', 'ª', 'º', '¿', '®', '¬', '½', '¼', '¡', '«', '»', //
+    '░', '▒', '▓', '│', '┤', 'Á', 'Â', 'À', '©', '╣', '║', '╗', '╝', '¢', '¥', '┐', //
+    '└', '┴', '┬', '├', '─', '┼', 'ã', 'Ã', '╚', '╔', '╩', '╦', '╠', '═', '╬', '¤', //
+    'ð', 'Ð', 'Ê', 'Ë', 'È', 'ı', 'Í', 'Î', 'Ï', '┘', '┌', '█', '▄', '¦', 'Ì', '▀', //
+    'Ó', 'ß', 'Ô', 'Ò', 'õ', 'Õ', 'µ', 'þ', 'Þ', 'Ú', 'Û', 'Ù', 'ý', 'Ý', '¯', '´', //
+    '\u{AD}', '±', '‗', '¾', '¶', '§', '÷', '¸', '°', '¨', '·', '¹', '³', '²', '■', '\u{A0}',
+];
+
+/// Upper half of CP866
+const CP866_HIGH: [char; 128] = [
+    'А', 'Б', 'В', 'Г', 'Д', 'Е', 'Ж', 'З', 'И', 'Й', 'К', 'Л', 'М', 'Н', 'О', 'П', //
+    'Р', 'С', 'Т', 'У', 'Ф', 'Х', 'Ц', 'Ч', 'Ш', 'Щ', 'Ъ', 'Ы', 'Ь', 'Э', 'Ю', 'Я', //
+    'а', 'б', 'в', 'г', 'д', 'е', 'ж', 'з', 'и', 'й', 'к', 'л', 'м', 'н', 'о', 'п', //
+    '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕', '╣', '║', '╗', '╝', '╜', '╛', '┐', //
+    '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦', '╠', '═', '╬', '╧', //
+    '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐', '▀', //
+    'р', 'с', 'т', 'у', 'ф', 'х', 'ц', 'ч', 'ш', 'щ', 'ъ', 'ы', 'ь', 'э', 'ю', 'я', //
+    'Ё', 'ё', 'Є', 'є', 'Ї', 'ї', 'Ў', 'ў', '°', '∙', '·', '√', '№', '¤', '■', '\u{A0}',
+];
+
+impl FontCodepage {
+    pub const ALL: [FontCodepage; 4] = [FontCodepage::Cp437, FontCodepage::Cp850, FontCodepage::Cp866, FontCodepage::Latin1];
+
+    /// Human-readable name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Cp437 => "CP437 (US)",
+            Self::Cp850 => "CP850 (Western Europe)",
+            Self::Cp866 => "CP866 (Cyrillic)",
+            Self::Latin1 => "ISO 8859-1",
+        }
+    }
+
+    /// Unicode character shown in glyph slot `slot`.
+    pub fn to_unicode(&self, slot: u8) -> char {
+        match self {
+            Self::Latin1 => char::from(slot),
+            _ if slot < 0x80 => CP437_TO_UNICODE[slot as usize],
+            Self::Cp437 => CP437_TO_UNICODE[slot as usize],
+            Self::Cp850 => CP850_HIGH[slot as usize - 0x80],
+            Self::Cp866 => CP866_HIGH[slot as usize - 0x80],
+        }
+    }
+
+    /// Glyph slot of a Unicode character, `None` if the codepage doesn't contain it.
+    pub fn from_unicode(&self, ch: char) -> Option<u8> {
+        (0..=255u8).find(|&slot| self.to_unicode(slot) == ch)
+    }
+}
+
+impl std::fmt::Display for FontCodepage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
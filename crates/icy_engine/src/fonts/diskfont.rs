@@ -0,0 +1,179 @@
+//! Amiga diskfont reader.
+//!
+//! An Amiga font is a `name.font` contents file listing the available sizes and a `name/`
+//! directory with one file per size (`topaz/8`, `topaz/11`, …). The size files are loadable
+//! hunk executables whose code hunk holds a `DiskFontHeader` with the `TextFont` structure.
+//! Pointers inside the hunk are offsets from its start. Amiga fonts use ISO 8859-1, so the
+//! glyphs are stored in their Latin-1 slots and as Unicode glyphs.
+
+use std::{collections::HashMap, sync::Arc};
+
+use super::compact_glyph::{CompactGlyph, MAX_GLYPH_HEIGHT};
+use super::{BitFont, BitFontType};
+use crate::EngineError;
+
+const HUNK_HEADER: u32 = 0x3F3;
+const HUNK_NAME: u32 = 0x3E8;
+const HUNK_CODE: u32 = 0x3E9;
+const HUNK_DATA: u32 = 0x3EA;
+
+/// `fch_FileID` of contents files without and with tags, scalable font contents
+const FCH_IDS: [u16; 3] = [0x0F00, 0x0F02, 0x0F03];
+const DFH_ID: u16 = 0x0F80;
+const FONT_CONTENTS_SIZE: usize = 260;
+
+/// `tf_Flags` bit for proportional fonts
+const FPF_PROPORTIONAL: u8 = 0x20;
+
+/// Offset of the `TextFont` structure in the code hunk
+const TEXT_FONT: usize = 58;
+
+fn u16_at(data: &[u8], offset: usize) -> crate::Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| EngineError::Generic("Amiga font: unexpected end of data".into()))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> crate::Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| EngineError::Generic("Amiga font: unexpected end of data".into()))
+}
+
+/// One size listed in a `.font` contents file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmigaFontEntry {
+    /// Path of the size file relative to the fonts directory, e.g. `topaz/8`
+    pub file_name: String,
+    pub y_size: u16,
+}
+
+/// Parse a `.font` contents file.
+pub fn read_amiga_font_contents(data: &[u8]) -> crate::Result<Vec<AmigaFontEntry>> {
+    let file_id = u16_at(data, 0)?;
+    if !FCH_IDS.contains(&file_id) {
+        return Err(EngineError::Generic(format!("Amiga font: invalid contents id {file_id:#06x}")));
+    }
+    let count = u16_at(data, 2)? as usize;
+    (0..count)
+        .map(|i| {
+            let entry = 4 + i * FONT_CONTENTS_SIZE;
+            let name = data
+                .get(entry..entry + 256)
+                .ok_or_else(|| EngineError::Generic("Amiga font: contents truncated".into()))?;
+            let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+            Ok(AmigaFontEntry {
+                file_name: String::from_utf8_lossy(&name[..end]).to_string(),
+                y_size: u16_at(data, entry + 256)?,
+            })
+        })
+        .collect()
+}
+
+/// The first code or data hunk of a hunk executable.
+fn first_hunk(data: &[u8]) -> crate::Result<&[u8]> {
+    if u32_at(data, 0)? != HUNK_HEADER {
+        return Err(EngineError::Generic("Amiga font: not a hunk file".into()));
+    }
+    let mut pos = 4;
+    // Resident library names
+    loop {
+        let longs = u32_at(data, pos)? as usize;
+        pos += 4;
+        if longs == 0 {
+            break;
+        }
+        pos += longs * 4;
+    }
+    let first = u32_at(data, pos + 4)? as usize;
+    let last = u32_at(data, pos + 8)? as usize;
+    pos += 12 + (last.saturating_sub(first) + 1) * 4;
+
+    loop {
+        let id = u32_at(data, pos)? & 0x3FFF_FFFF;
+        let longs = u32_at(data, pos + 4)? as usize & 0x3FFF_FFFF;
+        pos += 8;
+        match id {
+            HUNK_CODE | HUNK_DATA => {
+                return data
+                    .get(pos..pos + longs * 4)
+                    .ok_or_else(|| EngineError::Generic("Amiga font: hunk truncated".into()));
+            }
+            HUNK_NAME => pos += longs * 4,
+            _ => return Err(EngineError::Generic(format!("Amiga font: unexpected hunk {id:#x}"))),
+        }
+    }
+}
+
+impl BitFont {
+    /// Load one size of an Amiga diskfont (a size file like `topaz/8`).
+    ///
+    /// Proportional glyphs are placed at their kerning offset in a cell as wide as the widest
+    /// glyph, glyphs wider than 8 pixels are cut.
+    pub fn from_amiga_font(data: &[u8]) -> crate::Result<Self> {
+        let hunk = first_hunk(data)?;
+        if u16_at(hunk, 18)? != DFH_ID {
+            return Err(EngineError::Generic("Amiga font: missing DiskFontHeader".into()));
+        }
+        let name_bytes = hunk.get(26..58).unwrap_or_default();
+        let name_end = name_bytes.iter().position(|&b| b == 0).unwrap_or(name_bytes.len());
+        let name = String::from_utf8_lossy(&name_bytes[..name_end]).trim_end_matches(".font").to_string();
+
+        let y_size = u16_at(hunk, TEXT_FONT + 20)? as usize;
+        let flags = *hunk.get(TEXT_FONT + 23).unwrap_or(&0);
+        let x_size = u16_at(hunk, TEXT_FONT + 24)? as i32;
+        let lo_char = *hunk.get(TEXT_FONT + 32).unwrap_or(&0) as usize;
+        let hi_char = *hunk.get(TEXT_FONT + 33).unwrap_or(&0) as usize;
+        let char_data = u32_at(hunk, TEXT_FONT + 34)? as usize;
+        let modulo = u16_at(hunk, TEXT_FONT + 38)? as usize;
+        let char_loc = u32_at(hunk, TEXT_FONT + 40)? as usize;
+        let char_kern = u32_at(hunk, TEXT_FONT + 48)? as usize;
+        let proportional = flags & FPF_PROPORTIONAL != 0;
+
+        let chars = lo_char..=hi_char.max(lo_char);
+        let mut locations = Vec::new();
+        for i in 0..chars.clone().count() {
+            let bit_offset = u16_at(hunk, char_loc + i * 4)? as usize;
+            let bit_width = u16_at(hunk, char_loc + i * 4 + 2)? as i32;
+            let kern = if proportional && char_kern != 0 {
+                u16_at(hunk, char_kern + i * 2)? as i16 as i32
+            } else {
+                0
+            };
+            locations.push((bit_offset, bit_width, kern.max(0)));
+        }
+
+        let cell_width = if proportional {
+            locations.iter().map(|(_, width, kern)| width + kern).max().unwrap_or(x_size)
+        } else {
+            x_size
+        }
+        .clamp(1, 8) as u8;
+        let height = y_size.clamp(1, MAX_GLYPH_HEIGHT) as u8;
+
+        let pixel = |x: usize, y: usize| hunk.get(char_data + y * modulo + x / 8).is_some_and(|b| b & (0x80 >> (x % 8)) != 0);
+        let mut glyphs: [CompactGlyph; 256] = std::array::from_fn(|_| CompactGlyph::new(cell_width, height));
+        for (code, &(bit_offset, bit_width, kern)) in chars.zip(&locations) {
+            let glyph = &mut glyphs[code];
+            for y in 0..height as usize {
+                for x in 0..bit_width.max(0) as usize {
+                    let cx = kern as usize + x;
+                    if cx < cell_width as usize && pixel(bit_offset + x, y) {
+                        glyph.data[y] |= 0x80 >> cx;
+                    }
+                }
+            }
+        }
+
+        let unicode_glyphs: HashMap<char, CompactGlyph> = (lo_char..=hi_char).map(|code| (char::from(code as u8), glyphs[code])).collect();
+        Ok(Self {
+            name: if name.is_empty() { format!("{y_size}") } else { format!("{name} {y_size}") },
+            width: cell_width,
+            height,
+            glyphs,
+            unicode_glyphs: Arc::new(unicode_glyphs),
+            path_opt: None,
+            font_type: BitFontType::Custom,
+        })
+    }
+}
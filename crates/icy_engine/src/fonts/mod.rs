@@ -5,18 +5,29 @@ use libyaff::YaffFont;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::{EngineError, FontError};
-use std::{path::PathBuf, str::FromStr};
+use codepages::tables::UNICODE_TO_CP437;
+use std::{collections::HashMap, path::PathBuf, str::FromStr, sync::Arc};
 
 pub mod ansi;
+pub mod bdf;
+pub mod codepage;
 pub mod compact_glyph;
+pub mod diskfont;
 pub mod legacy;
+mod pcf;
 pub mod psf_parser;
 pub mod rip;
 pub mod sauce;
 pub mod skypix;
+mod truetype;
+mod winfnt;
 
+pub use bdf::{BdfFont, BdfGlyph};
+pub use codepage::FontCodepage;
 pub use compact_glyph::CompactGlyph;
-pub use psf_parser::PsfFont;
+pub use diskfont::{read_amiga_font_contents, AmigaFontEntry};
+pub use psf_parser::{PsfFont, UnicodeMapping};
+pub use truetype::TtfExportOptions;
 
 use super::Size;
 
@@ -44,6 +55,9 @@ pub struct BitFont {
     pub height: u8,
     /// All 256 glyphs stored as compact bitmaps
     pub glyphs: [CompactGlyph; 256],
+    /// Glyphs keyed by Unicode character, used to render Unicode buffers.
+    /// Empty for fonts without Unicode information, shared between clones.
+    pub unicode_glyphs: Arc<HashMap<char, CompactGlyph>>,
     /// Optional file path for custom fonts
     pub path_opt: Option<PathBuf>,
     /// Font type (built-in, library, or custom)
@@ -59,6 +73,9 @@ struct BitFontSerde {
     data: Vec<u8>,
     font_type: BitFontType,
     path: Option<PathBuf>,
+    /// Unicode glyphs as code point and rows
+    #[serde(default)]
+    unicode: Vec<(u32, Vec<u8>)>,
 }
 
 impl Serialize for BitFont {
@@ -66,6 +83,12 @@ impl Serialize for BitFont {
     where
         S: Serializer,
     {
+        let mut unicode: Vec<(u32, Vec<u8>)> = self
+            .unicode_glyphs
+            .iter()
+            .map(|(ch, glyph)| (*ch as u32, glyph.data[..self.height as usize].to_vec()))
+            .collect();
+        unicode.sort_unstable_by_key(|(cp, _)| *cp);
         BitFontSerde {
             name: self.name.clone(),
             width: self.width,
@@ -73,6 +96,7 @@ impl Serialize for BitFont {
             data: self.convert_to_u8_data(),
             font_type: self.font_type,
             path: self.path_opt.clone(),
+            unicode,
         }
         .serialize(serializer)
     }
@@ -87,13 +111,27 @@ impl<'de> Deserialize<'de> for BitFont {
         let mut font = BitFont::create_8(&serde_font.name, serde_font.width, serde_font.height, &serde_font.data);
         font.font_type = serde_font.font_type;
         font.path_opt = serde_font.path;
+        if !serde_font.unicode.is_empty() {
+            let (width, height) = (font.width, font.height);
+            font.set_unicode_glyphs(
+                serde_font
+                    .unicode
+                    .iter()
+                    .filter_map(|(cp, rows)| Some((char::from_u32(*cp)?, CompactGlyph::from_rows(width, height, rows))))
+                    .collect(),
+            );
+        }
         Ok(font)
     }
 }
 
 impl PartialEq for BitFont {
     fn eq(&self, other: &Self) -> bool {
-        self.name == other.name && self.width == other.width && self.height == other.height && self.glyphs == other.glyphs
+        self.name == other.name
+            && self.width == other.width
+            && self.height == other.height
+            && self.glyphs == other.glyphs
+            && self.unicode_glyphs == other.unicode_glyphs
     }
 }
 
@@ -137,6 +175,33 @@ impl BitFont {
         }
     }
 
+    /// Get the glyph for a character of a Unicode buffer
+    ///
+    /// Looks up the Unicode glyphs first, then the CP437 slot of the character.
+    #[inline]
+    pub fn unicode_glyph(&self, ch: char) -> &CompactGlyph {
+        self.find_unicode_glyph(ch).unwrap_or_else(|| self.glyph(ch))
+    }
+
+    /// Like [`Self::unicode_glyph`], but `None` if the font has no glyph for the character
+    #[inline]
+    pub fn find_unicode_glyph(&self, ch: char) -> Option<&CompactGlyph> {
+        if let Some(glyph) = self.unicode_glyphs.get(&ch) {
+            return Some(glyph);
+        }
+        UNICODE_TO_CP437.get(&ch).map(|slot| &self.glyphs[*slot as usize])
+    }
+
+    /// True if the font carries glyphs keyed by Unicode character
+    pub fn has_unicode_glyphs(&self) -> bool {
+        !self.unicode_glyphs.is_empty()
+    }
+
+    /// Replace the Unicode glyphs
+    pub fn set_unicode_glyphs(&mut self, glyphs: HashMap<char, CompactGlyph>) {
+        self.unicode_glyphs = Arc::new(glyphs);
+    }
+
     /// Get mutable reference to a glyph
     #[inline]
     pub fn glyph_mut(&mut self, ch: char) -> &mut CompactGlyph {
@@ -230,6 +295,7 @@ impl BitFont {
             width,
             height,
             glyphs,
+            unicode_glyphs: Arc::default(),
             path_opt: None,
             font_type: BitFontType::Custom,
         }
@@ -268,10 +334,8 @@ impl BitFont {
         let old_height = self.height.max(1) as usize;
         let target_height = (new_height.max(1) as usize).min(32);
 
-        let mut new_glyphs: [CompactGlyph; 256] = std::array::from_fn(|_| CompactGlyph::new(self.width, target_height as u8));
-
-        for (i, old_glyph) in self.glyphs.iter().enumerate() {
-            let new_glyph = &mut new_glyphs[i];
+        let scale = |old_glyph: &CompactGlyph| {
+            let mut new_glyph = CompactGlyph::new(self.width, target_height as u8);
             let mut err: isize = 0;
             let mut src_row: usize = 0;
 
@@ -286,13 +350,22 @@ impl BitFont {
                     src_row = (src_row + 1).min(old_height.saturating_sub(1));
                 }
             }
-        }
+            new_glyph
+        };
+
+        let new_glyphs: [CompactGlyph; 256] = std::array::from_fn(|i| scale(&self.glyphs[i]));
+        let unicode_glyphs = if self.has_unicode_glyphs() {
+            Arc::new(self.unicode_glyphs.iter().map(|(ch, glyph)| (*ch, scale(glyph))).collect())
+        } else {
+            Arc::default()
+        };
 
         Ok(Self {
             name: self.name.clone(),
             width: self.width,
             height: target_height as u8,
             glyphs: new_glyphs,
+            unicode_glyphs,
             path_opt: None,
             font_type: self.font_type,
         })
@@ -300,25 +373,28 @@ impl BitFont {
 }
 
 impl BitFont {
-    /// Load font from bytes (PSF1, PSF2, YAFF, or plain format)
+    /// Load font from bytes (PSF1, PSF2, BDF, PCF, YAFF, or plain format)
+    ///
+    /// Unicode BDF and PCF fonts are mapped to the slots through CP437. PSF Unicode tables,
+    /// YAFF Unicode labels and Unicode BDF/PCF encodings fill the Unicode glyphs.
     pub fn from_bytes(name: impl Into<String>, data: &[u8]) -> crate::Result<Self> {
+        Self::from_bytes_with_codepage(name, data, FontCodepage::Cp437)
+    }
+
+    /// Like [`Self::from_bytes`], Unicode BDF and PCF fonts are mapped to the slots through `codepage`.
+    pub fn from_bytes_with_codepage(name: impl Into<String>, data: &[u8], codepage: FontCodepage) -> crate::Result<Self> {
         let name = name.into();
 
         // Try to parse as PSF font first
         if let Ok(psf) = PsfFont::from_bytes(data) {
-            // Convert Vec<CompactGlyph> to [CompactGlyph; 256], taking first 256 glyphs
-            let mut glyphs: [CompactGlyph; 256] = std::array::from_fn(|_| CompactGlyph::new(psf.width, psf.height));
-            for (i, glyph) in psf.glyphs.iter().take(256).enumerate() {
-                glyphs[i] = *glyph;
-            }
-            return Ok(Self {
-                name,
-                width: psf.width,
-                height: psf.height,
-                glyphs,
-                path_opt: None,
-                font_type: BitFontType::BuiltIn,
-            });
+            return Ok(Self::from_psf_page(name, &psf, 0));
+        }
+
+        if data.starts_with(b"STARTFONT") {
+            return Self::from_bdf(name, data, codepage);
+        }
+        if data.starts_with(b"\x01fcp") {
+            return Self::from_pcf(name, data, codepage);
         }
 
         // Try as YAFF format
@@ -335,6 +411,51 @@ impl BitFont {
         Err(FontError::UnknownFontFormat(data.len()).into())
     }
 
+    /// Load the font pages of a font file
+    ///
+    /// 512 glyph PSF fonts without Unicode table are VGA 512 character mode fonts, they are
+    /// split into two pages. The second page is selected by the bright foreground bit, like
+    /// the second font of an `XBin` file. All other fonts load as a single page.
+    pub fn pages_from_bytes(name: impl Into<String>, data: &[u8]) -> crate::Result<Vec<Self>> {
+        let name = name.into();
+        if let Ok(psf) = PsfFont::from_bytes(data) {
+            if psf.glyph_count() >= 512 && !psf.has_unicode_table() {
+                return Ok(vec![
+                    Self::from_psf_page(format!("{name} (1)"), &psf, 0),
+                    Self::from_psf_page(format!("{name} (2)"), &psf, 256),
+                ]);
+            }
+        }
+        Ok(vec![Self::from_bytes(name, data)?])
+    }
+
+    /// 256 glyphs of a PSF font starting at glyph `first`, the Unicode table maps all glyphs
+    fn from_psf_page(name: String, psf: &PsfFont, first: usize) -> Self {
+        let glyphs = std::array::from_fn(|i| psf.glyphs.get(first + i).copied().unwrap_or(CompactGlyph::new(psf.width, psf.height)));
+
+        let mut unicode_glyphs = HashMap::new();
+        for (glyph, mappings) in psf.glyphs.iter().zip(&psf.unicode_table) {
+            for mapping in mappings {
+                // Sequences (combining characters) can't be looked up by a single char
+                if let UnicodeMapping::Single(cp) = mapping {
+                    if let Some(ch) = char::from_u32(*cp) {
+                        unicode_glyphs.entry(ch).or_insert(*glyph);
+                    }
+                }
+            }
+        }
+
+        Self {
+            name,
+            width: psf.width,
+            height: psf.height,
+            glyphs,
+            unicode_glyphs: Arc::new(unicode_glyphs),
+            path_opt: None,
+            font_type: BitFontType::BuiltIn,
+        }
+    }
+
     /// Load font from ANSI font slot (0-42)
     ///
     /// # Arguments
@@ -412,10 +533,17 @@ impl BitFont {
             }
         }
 
-        // Second pass: Fill remaining slots with Unicode labels
+        // Second pass: Fill remaining slots with Unicode labels, all labeled glyphs go to the Unicode glyphs
+        let mut unicode_glyphs = HashMap::new();
         for glyph_def in &yaff_font.glyphs {
             for label in &glyph_def.labels {
                 if let Label::Unicode(codes) = label {
+                    if let [code] = codes.as_slice() {
+                        if let Some(ch) = char::from_u32(*code) {
+                            let glyph = CompactGlyph::from_bitmap_pixels(&glyph_def.bitmap.pixels, width as usize, height as usize);
+                            unicode_glyphs.entry(ch).or_insert(glyph);
+                        }
+                    }
                     for &code in codes {
                         if (code as usize) < 256 {
                             let target = &mut glyphs[code as usize];
@@ -448,6 +576,7 @@ impl BitFont {
             width,
             height,
             glyphs,
+            unicode_glyphs: Arc::new(unicode_glyphs),
             path_opt: None,
             font_type: BitFontType::Custom,
         }
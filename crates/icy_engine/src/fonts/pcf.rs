@@ -0,0 +1,294 @@
+//! PCF (Portable Compiled Format) reader.
+//!
+//! PCF is the binary form `bdftopcf` compiles BDF fonts into. The file is a table of
+//! contents followed by tables, every table starts with its own format word that decides
+//! byte order, bit order and row padding. Gzipped fonts (`.pcf.gz`) need to be unpacked first.
+
+use std::collections::HashMap;
+
+use super::bdf::{BdfFont, BdfGlyph, CellLayout};
+use crate::EngineError;
+
+const PCF_MAGIC: &[u8; 4] = b"\x01fcp";
+
+const PCF_PROPERTIES: u32 = 1 << 0;
+const PCF_ACCELERATORS: u32 = 1 << 1;
+const PCF_METRICS: u32 = 1 << 2;
+const PCF_BITMAPS: u32 = 1 << 3;
+const PCF_BDF_ENCODINGS: u32 = 1 << 5;
+const PCF_BDF_ACCELERATORS: u32 = 1 << 8;
+
+const PCF_COMPRESSED_METRICS: u32 = 0x100;
+const PCF_BYTE_MASK: u32 = 1 << 2;
+const PCF_BIT_MASK: u32 = 1 << 3;
+
+const NO_GLYPH: u16 = 0xFFFF;
+
+/// Reads the values of one table in the byte order of its format word
+struct TableReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    big_endian: bool,
+}
+
+impl<'a> TableReader<'a> {
+    /// Reader positioned after the format word of the table at `offset`.
+    fn new(data: &'a [u8], offset: usize) -> crate::Result<(Self, u32)> {
+        let mut reader = Self {
+            data,
+            pos: offset,
+            big_endian: false,
+        };
+        let format = reader.u32()?;
+        reader.big_endian = format & PCF_BYTE_MASK != 0;
+        Ok((reader, format))
+    }
+
+    fn bytes(&mut self, len: usize) -> crate::Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.pos..self.pos.saturating_add(len))
+            .ok_or_else(|| EngineError::Generic("PCF: table data truncated".into()))?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> crate::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> crate::Result<u16> {
+        let b = self.bytes(2)?;
+        Ok(if self.big_endian {
+            u16::from_be_bytes([b[0], b[1]])
+        } else {
+            u16::from_le_bytes([b[0], b[1]])
+        })
+    }
+
+    fn i16(&mut self) -> crate::Result<i16> {
+        Ok(self.u16()? as i16)
+    }
+
+    fn u32(&mut self) -> crate::Result<u32> {
+        let b = self.bytes(4)?;
+        let b = [b[0], b[1], b[2], b[3]];
+        Ok(if self.big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) })
+    }
+
+    fn i32(&mut self) -> crate::Result<i32> {
+        Ok(self.u32()? as i32)
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.pos)
+    }
+
+    fn count(&mut self) -> crate::Result<usize> {
+        usize::try_from(self.i32()?).map_err(|_| EngineError::Generic("PCF: negative count".into()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Metrics {
+    left_bearing: i32,
+    right_bearing: i32,
+    width: i32,
+    ascent: i32,
+    descent: i32,
+}
+
+impl Metrics {
+    fn read(reader: &mut TableReader, compressed: bool) -> crate::Result<Self> {
+        if compressed {
+            let mut value = || Ok::<_, EngineError>(reader.u8()? as i32 - 0x80);
+            Ok(Self {
+                left_bearing: value()?,
+                right_bearing: value()?,
+                width: value()?,
+                ascent: value()?,
+                descent: value()?,
+            })
+        } else {
+            let metrics = Self {
+                left_bearing: reader.i16()? as i32,
+                right_bearing: reader.i16()? as i32,
+                width: reader.i16()? as i32,
+                ascent: reader.i16()? as i32,
+                descent: reader.i16()? as i32,
+            };
+            reader.u16()?; // attributes
+            Ok(metrics)
+        }
+    }
+}
+
+impl BdfFont {
+    /// Parse a PCF font.
+    pub fn from_pcf_bytes(bytes: &[u8]) -> crate::Result<Self> {
+        if bytes.len() < 8 || &bytes[..4] != PCF_MAGIC {
+            return Err(EngineError::Generic("PCF: invalid magic".into()));
+        }
+        let mut toc = TableReader {
+            data: bytes,
+            pos: 4,
+            big_endian: false,
+        };
+        let mut tables = HashMap::new();
+        for _ in 0..toc.count()? {
+            let kind = toc.u32()?;
+            let _format = toc.u32()?;
+            let _size = toc.u32()?;
+            let offset = toc.u32()? as usize;
+            tables.insert(kind, offset);
+        }
+        let table = |kind: u32| tables.get(&kind).copied();
+        let require = |kind: u32, name: &str| table(kind).ok_or_else(|| EngineError::Generic(format!("PCF: missing {name} table")));
+
+        let properties = match table(PCF_PROPERTIES) {
+            Some(offset) => read_properties(bytes, offset)?,
+            None => HashMap::new(),
+        };
+        let metrics = read_metrics(bytes, require(PCF_METRICS, "metrics")?)?;
+        let bitmaps = read_bitmaps(bytes, require(PCF_BITMAPS, "bitmaps")?, &metrics)?;
+        let encodings = read_encodings(bytes, require(PCF_BDF_ENCODINGS, "encodings")?)?;
+
+        let accelerator = match table(PCF_BDF_ACCELERATORS).or(table(PCF_ACCELERATORS)) {
+            Some(offset) => Some(read_accelerator(bytes, offset)?),
+            None => None,
+        };
+        let property = |key: &str| properties.get(key).and_then(|v| v.parse::<i32>().ok());
+        let ascent = property("FONT_ASCENT")
+            .or(accelerator.map(|a| a.0))
+            .unwrap_or_else(|| metrics.iter().map(|m| m.ascent).max().unwrap_or(0));
+        let descent = property("FONT_DESCENT")
+            .or(accelerator.map(|a| a.1))
+            .unwrap_or_else(|| metrics.iter().map(|m| m.descent).max().unwrap_or(0));
+        let width = metrics.iter().map(|m| m.width).max().unwrap_or(8);
+        let left = metrics.iter().map(|m| m.left_bearing).min().unwrap_or(0).min(0);
+        let layout = CellLayout::new(width, ascent, descent, left)?;
+
+        let glyphs = encodings
+            .into_iter()
+            .filter_map(|(encoding, index)| {
+                let (m, rows) = (metrics.get(index)?, bitmaps.get(index)?);
+                Some(BdfGlyph {
+                    encoding,
+                    glyph: layout.place(rows, m.left_bearing, m.ascent),
+                })
+            })
+            .collect();
+
+        let registry = properties.get("CHARSET_REGISTRY").map(|r| r.to_ascii_uppercase());
+        let unicode = registry.is_some_and(|r| r.contains("10646") || r.contains("UNICODE"));
+        Ok(Self {
+            name: properties.get("FAMILY_NAME").or(properties.get("FONT")).cloned(),
+            width: layout.width,
+            height: layout.height,
+            ascent: layout.ascent,
+            unicode,
+            glyphs,
+        })
+    }
+}
+
+/// Properties as strings, numeric values are formatted.
+fn read_properties(bytes: &[u8], offset: usize) -> crate::Result<HashMap<String, String>> {
+    let (mut reader, _) = TableReader::new(bytes, offset)?;
+    let count = reader.count()?;
+    // Every entry takes 9 bytes, don't trust the count for the allocation
+    let mut entries = Vec::with_capacity(count.min(reader.remaining() / 9));
+    for _ in 0..count {
+        let name = reader.u32()? as usize;
+        let is_string = reader.u8()? != 0;
+        let value = reader.i32()?;
+        entries.push((name, is_string, value));
+    }
+    if count % 4 != 0 {
+        reader.bytes(4 - count % 4)?;
+    }
+    let string_size = reader.count()?;
+    let strings = reader.bytes(string_size)?;
+    let string_at = |offset: usize| {
+        let tail = strings.get(offset..).unwrap_or_default();
+        let end = tail.iter().position(|&b| b == 0).unwrap_or(tail.len());
+        String::from_utf8_lossy(&tail[..end]).to_string()
+    };
+    Ok(entries
+        .into_iter()
+        .map(|(name, is_string, value)| {
+            let value = if is_string { string_at(value as usize) } else { value.to_string() };
+            (string_at(name), value)
+        })
+        .collect())
+}
+
+fn read_metrics(bytes: &[u8], offset: usize) -> crate::Result<Vec<Metrics>> {
+    let (mut reader, format) = TableReader::new(bytes, offset)?;
+    let compressed = format & PCF_COMPRESSED_METRICS != 0;
+    let count = if compressed { reader.u16()? as usize } else { reader.count()? };
+    (0..count).map(|_| Metrics::read(&mut reader, compressed)).collect()
+}
+
+/// First byte of every bitmap row, MSB first.
+fn read_bitmaps(bytes: &[u8], offset: usize, metrics: &[Metrics]) -> crate::Result<Vec<Vec<u8>>> {
+    let (mut reader, format) = TableReader::new(bytes, offset)?;
+    let count = reader.count()?;
+    let offsets = (0..count).map(|_| reader.count()).collect::<crate::Result<Vec<_>>>()?;
+    let mut sizes = [0; 4];
+    for size in &mut sizes {
+        *size = reader.count()?;
+    }
+    let pad = 1usize << (format & 3);
+    let scan_unit = 1usize << ((format >> 4) & 3);
+    let data = reader.bytes(sizes[(format & 3) as usize])?;
+    let lsb_bits = format & PCF_BIT_MASK == 0;
+    // Units are stored in byte order, bytes inside a unit need swapping if that differs from the bit order
+    let swap_units = scan_unit > 1 && ((format & PCF_BYTE_MASK != 0) != (format & PCF_BIT_MASK != 0));
+
+    offsets
+        .iter()
+        .zip(metrics)
+        .map(|(&start, m)| {
+            let row_bytes = ((m.right_bearing - m.left_bearing).max(0) as usize).div_ceil(8).max(1).next_multiple_of(pad);
+            let height = (m.ascent + m.descent).max(0) as usize;
+            (0..height)
+                .map(|y| {
+                    let row = start + y * row_bytes;
+                    // With swapped units the first pixel byte sits at the end of the first unit
+                    let index = if swap_units { row + scan_unit.min(row_bytes) - 1 } else { row };
+                    let byte = *data.get(index).ok_or_else(|| EngineError::Generic("PCF: bitmap data truncated".into()))?;
+                    Ok(if lsb_bits { byte.reverse_bits() } else { byte })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Encoding and glyph index of all encoded glyphs.
+fn read_encodings(bytes: &[u8], offset: usize) -> crate::Result<Vec<(u32, usize)>> {
+    let (mut reader, _) = TableReader::new(bytes, offset)?;
+    let min_byte2 = reader.i16()? as u32;
+    let max_byte2 = reader.i16()? as u32;
+    let min_byte1 = reader.i16()? as u32;
+    let max_byte1 = reader.i16()? as u32;
+    let _default_char = reader.i16()?;
+    let mut encodings = Vec::new();
+    for byte1 in min_byte1..=max_byte1 {
+        for byte2 in min_byte2..=max_byte2 {
+            let index = reader.u16()?;
+            if index != NO_GLYPH {
+                encodings.push(((byte1 << 8) | byte2, index as usize));
+            }
+        }
+    }
+    Ok(encodings)
+}
+
+/// Font ascent and descent
+fn read_accelerator(bytes: &[u8], offset: usize) -> crate::Result<(i32, i32)> {
+    let (mut reader, _) = TableReader::new(bytes, offset)?;
+    // no_overlap, constant_metrics, terminal_font, constant_width, ink_inside, ink_metrics, draw_direction, padding
+    reader.bytes(8)?;
+    Ok((reader.i32()?, reader.i32()?))
+}
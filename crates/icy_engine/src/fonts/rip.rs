@@ -19,6 +19,7 @@ lazy_static::lazy_static! {
             width: 7,
             height: 14,
             glyphs,
+            unicode_glyphs: base.unicode_glyphs.clone(),
             path_opt: None,
             font_type: BitFontType::BuiltIn,
         }
@@ -33,6 +34,7 @@ lazy_static::lazy_static! {
             width: 8,
             height: 14,
             glyphs: *base.glyphs(),
+            unicode_glyphs: base.unicode_glyphs.clone(),
             path_opt: None,
             font_type: BitFontType::BuiltIn,
         }
@@ -0,0 +1,508 @@
+//! TrueType writer for bitmap fonts.
+//!
+//! Every pixel becomes a square, the squares of a glyph are merged into contours so the
+//! outlines stay clean when scaled. One pixel is `UNITS_PER_PIXEL` font units and the em is
+//! the font height, the font renders pixel perfect at a size of `height` pixels. Optionally
+//! the font gets an embedded bitmap strike (`EBLC`/`EBDT`) for that size.
+//!
+//! The outlines are TrueType (`glyf`), the result is valid as `.ttf` and as `.otf`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{BitFont, CompactGlyph, FontCodepage};
+use crate::EngineError;
+
+/// Font units per pixel
+const UNITS_PER_PIXEL: i32 = 128;
+
+/// Options for `BitFont::to_ttf_bytes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TtfExportOptions {
+    /// Codepage mapping the 256 slots to Unicode, fonts with Unicode glyphs add those on top
+    pub codepage: FontCodepage,
+    /// Use a 9 pixel cell like VGA text mode, line graphics extend their 8th column
+    pub nine_dot: bool,
+    /// Embed a bitmap strike at the native size
+    pub bitmap_strike: bool,
+}
+
+/// Glyph of the exported font, already widened to the advance width
+struct OutputGlyph {
+    ch: char,
+    /// Pixel rows, MSB first in a 16 bit word
+    rows: Vec<u16>,
+}
+
+impl OutputGlyph {
+    fn pixel(&self, x: i32, y: i32) -> bool {
+        self.rows[y as usize] & (0x8000 >> x) != 0
+    }
+}
+
+/// Big endian table writer
+#[derive(Default)]
+struct Table(Vec<u8>);
+
+impl Table {
+    fn u8(&mut self, v: u8) -> &mut Self {
+        self.0.push(v);
+        self
+    }
+    fn u16(&mut self, v: u16) -> &mut Self {
+        self.0.extend(v.to_be_bytes());
+        self
+    }
+    fn i16(&mut self, v: i16) -> &mut Self {
+        self.0.extend(v.to_be_bytes());
+        self
+    }
+    fn u32(&mut self, v: u32) -> &mut Self {
+        self.0.extend(v.to_be_bytes());
+        self
+    }
+    fn bytes(&mut self, v: &[u8]) -> &mut Self {
+        self.0.extend(v);
+        self
+    }
+}
+
+/// Merged outline of the set pixels, clockwise contours in font units with y up.
+fn trace_contours(glyph: &OutputGlyph, width: i32, ascent: i32) -> Vec<Vec<(i32, i32)>> {
+    // Clockwise edges of every pixel square, edges shared by two pixels cancel out
+    let mut edges = BTreeSet::new();
+    for y in 0..glyph.rows.len() as i32 {
+        for x in 0..width {
+            if !glyph.pixel(x, y) {
+                continue;
+            }
+            let bottom = ascent - y - 1;
+            let corners = [(x, bottom), (x, bottom + 1), (x + 1, bottom + 1), (x + 1, bottom)];
+            for i in 0..4 {
+                let (from, to) = (corners[i], corners[(i + 1) % 4]);
+                if !edges.remove(&(to, from)) {
+                    edges.insert((from, to));
+                }
+            }
+        }
+    }
+
+    let mut outgoing: BTreeMap<(i32, i32), Vec<(i32, i32)>> = BTreeMap::new();
+    for (from, to) in edges {
+        outgoing.entry(from).or_default().push(to);
+    }
+
+    let mut contours = Vec::new();
+    while let Some(&start) = outgoing.keys().next() {
+        let mut points = vec![start];
+        let mut cur = start;
+        // Every point has as many incoming as outgoing edges, the walk ends at its start
+        loop {
+            let Some(targets) = outgoing.get_mut(&cur) else {
+                break;
+            };
+            let Some(next) = targets.pop() else {
+                break;
+            };
+            if targets.is_empty() {
+                outgoing.remove(&cur);
+            }
+            if next == start {
+                break;
+            }
+            points.push(next);
+            cur = next;
+        }
+
+        // Drop the points in the middle of straight lines
+        let n = points.len();
+        let corners: Vec<(i32, i32)> = (0..n)
+            .filter(|&i| {
+                let (prev, cur, next) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+                !((prev.0 == cur.0 && cur.0 == next.0) || (prev.1 == cur.1 && cur.1 == next.1))
+            })
+            .map(|i| (points[i].0 * UNITS_PER_PIXEL, points[i].1 * UNITS_PER_PIXEL))
+            .collect();
+        contours.push(corners);
+    }
+    contours
+}
+
+/// Simple `glyf` entry with on-curve points only, returns the data and the bounding box.
+fn encode_glyf(contours: &[Vec<(i32, i32)>]) -> (Vec<u8>, [i16; 4]) {
+    if contours.is_empty() {
+        return (Vec::new(), [0; 4]);
+    }
+    let points: Vec<(i32, i32)> = contours.iter().flatten().copied().collect();
+    let bbox = [
+        points.iter().map(|p| p.0).min().unwrap_or(0) as i16,
+        points.iter().map(|p| p.1).min().unwrap_or(0) as i16,
+        points.iter().map(|p| p.0).max().unwrap_or(0) as i16,
+        points.iter().map(|p| p.1).max().unwrap_or(0) as i16,
+    ];
+
+    let mut t = Table::default();
+    t.i16(contours.len() as i16);
+    for v in bbox {
+        t.i16(v);
+    }
+    let mut end = 0;
+    for contour in contours {
+        end += contour.len();
+        t.u16(end as u16 - 1);
+    }
+    t.u16(0); // no instructions
+    for _ in &points {
+        t.u8(0x01); // on curve, 16 bit deltas
+    }
+    let (mut last_x, mut last_y) = (0, 0);
+    for &(x, _) in &points {
+        t.i16((x - last_x) as i16);
+        last_x = x;
+    }
+    for &(_, y) in &points {
+        t.i16((y - last_y) as i16);
+        last_y = y;
+    }
+    // Keep glyphs 4 byte aligned for the long loca format
+    t.0.resize(t.0.len().next_multiple_of(4), 0);
+    (t.0, bbox)
+}
+
+/// Most format 4 segments, including the closing `0xFFFF` one, before its 16 bit length overflows
+const MAX_CMAP_SEGMENTS: usize = (u16::MAX as usize - 16) / 8;
+
+/// Runs of consecutive code points, they map to consecutive glyph ids: `(start, end, first glyph id)`.
+/// Glyph `i + 1` is `chars[i]`, `chars` is sorted.
+fn char_runs(chars: &[char]) -> Vec<(u32, u32, u32)> {
+    let mut runs: Vec<(u32, u32, u32)> = Vec::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        let (cp, gid) = (ch as u32, i as u32 + 1);
+        match runs.last_mut() {
+            Some((_, end, _)) if *end + 1 == cp => *end = cp,
+            _ => runs.push((cp, cp, gid)),
+        }
+    }
+    runs
+}
+
+/// Number of format 4 segments for `runs`, the BMP runs and the closing one
+fn cmap_segment_count(runs: &[(u32, u32, u32)]) -> usize {
+    runs.iter().filter(|(start, _, _)| *start <= 0xFFFE).count() + 1
+}
+
+/// `cmap` with a format 4 subtable for the BMP and a format 12 subtable for all characters.
+/// Glyph `i + 1` is `chars[i]`, `chars` is sorted and has at most [`MAX_CMAP_SEGMENTS`] BMP segments.
+fn encode_cmap(chars: &[char]) -> Vec<u8> {
+    let runs = char_runs(chars);
+
+    let mut segments: Vec<(u16, u16, u16)> = runs
+        .iter()
+        .filter(|(start, _, _)| *start <= 0xFFFE)
+        .map(|&(start, end, gid)| (start as u16, end.min(0xFFFE) as u16, (gid as u16).wrapping_sub(start as u16)))
+        .collect();
+    segments.push((0xFFFF, 0xFFFF, 1));
+
+    let seg_count = segments.len() as u16;
+    let search_range = 2 * (1 << seg_count.ilog2());
+    let mut format4 = Table::default();
+    format4
+        .u16(4)
+        .u16(16 + 8 * seg_count)
+        .u16(0)
+        .u16(seg_count * 2)
+        .u16(search_range)
+        .u16(seg_count.ilog2() as u16)
+        .u16(seg_count * 2 - search_range);
+    for (_, end, _) in &segments {
+        format4.u16(*end);
+    }
+    format4.u16(0);
+    for (start, _, _) in &segments {
+        format4.u16(*start);
+    }
+    for (_, _, delta) in &segments {
+        format4.u16(*delta);
+    }
+    for _ in &segments {
+        format4.u16(0);
+    }
+
+    let mut format12 = Table::default();
+    format12.u16(12).u16(0).u32(16 + 12 * runs.len() as u32).u32(0).u32(runs.len() as u32);
+    for (start, end, gid) in &runs {
+        format12.u32(*start).u32(*end).u32(*gid);
+    }
+
+    let mut cmap = Table::default();
+    let format4_offset = 4 + 2 * 8;
+    cmap.u16(0).u16(2);
+    cmap.u16(3).u16(1).u32(format4_offset);
+    cmap.u16(3).u16(10).u32(format4_offset + format4.0.len() as u32);
+    cmap.bytes(&format4.0).bytes(&format12.0);
+    cmap.0
+}
+
+fn encode_name(family: &str) -> Vec<u8> {
+    let postscript: String = family.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+    let postscript = if postscript.is_empty() { "BitFont".to_string() } else { postscript };
+    let names = [
+        (1, family.to_string()),
+        (2, "Regular".to_string()),
+        (3, format!("{family} Regular")),
+        (4, family.to_string()),
+        (5, "Version 1.000".to_string()),
+        (6, postscript),
+    ];
+    let strings: Vec<Vec<u8>> = names.iter().map(|(_, s)| s.encode_utf16().flat_map(u16::to_be_bytes).collect()).collect();
+
+    let mut t = Table::default();
+    t.u16(0).u16(names.len() as u16).u16(6 + 12 * names.len() as u16);
+    let mut offset = 0;
+    for ((id, _), string) in names.iter().zip(&strings) {
+        t.u16(3).u16(1).u16(0x409).u16(*id).u16(string.len() as u16).u16(offset);
+        offset += string.len() as u16;
+    }
+    for string in &strings {
+        t.bytes(string);
+    }
+    t.0
+}
+
+/// `ulCodePageRange` bits of the codepage
+fn code_page_range(codepage: FontCodepage) -> (u32, u32) {
+    match codepage {
+        FontCodepage::Cp437 => (0, 1 << 31),
+        FontCodepage::Cp850 => (0, 1 << 30),
+        FontCodepage::Cp866 => (0, 1 << 17),
+        FontCodepage::Latin1 => (1, 0),
+    }
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// Assemble the font file, `tables` must be sorted by tag.
+fn write_sfnt(tables: &[([u8; 4], Vec<u8>)]) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let entry_selector = num_tables.ilog2() as u16;
+    let search_range = 16 * (1 << entry_selector);
+
+    let mut font = Table::default();
+    font.u32(0x0001_0000)
+        .u16(num_tables)
+        .u16(search_range)
+        .u16(entry_selector)
+        .u16(num_tables * 16 - search_range);
+    let mut offset = 12 + 16 * tables.len() as u32;
+    for (tag, data) in tables {
+        font.bytes(tag).u32(checksum(data)).u32(offset).u32(data.len() as u32);
+        offset += data.len().next_multiple_of(4) as u32;
+    }
+    let mut head_offset = 0;
+    for (tag, data) in tables {
+        if tag == b"head" {
+            head_offset = font.0.len();
+        }
+        font.bytes(data);
+        font.0.resize(font.0.len().next_multiple_of(4), 0);
+    }
+    let adjustment = 0xB1B0_AFBAu32.wrapping_sub(checksum(&font.0));
+    font.0[head_offset + 8..head_offset + 12].copy_from_slice(&adjustment.to_be_bytes());
+    font.0
+}
+
+impl BitFont {
+    /// Convert to a TrueType font with one square outline per pixel.
+    ///
+    /// The slots are mapped to Unicode through `options.codepage`, Unicode glyphs of the font
+    /// (e.g. from a PSF Unicode table) are added and win over the slots.
+    ///
+    /// Fails if the font has more glyphs than a TrueType font can hold.
+    pub fn to_ttf_bytes(&self, options: &TtfExportOptions) -> crate::Result<Vec<u8>> {
+        let height = self.height as i32;
+        let nine_dot = options.nine_dot && self.width == 8;
+        let width = if nine_dot { 9 } else { self.width as i32 };
+        let descent = height / 4;
+        let ascent = height - descent;
+
+        let widen = |ch: char, glyph: &CompactGlyph| {
+            let line_graphics = nine_dot && FontCodepage::Cp437.from_unicode(ch).is_some_and(|slot| (0xC0..=0xDF).contains(&slot));
+            let extended = glyph.extend_to_9px(line_graphics);
+            // `extend_to_9px` shifts the row one bit left, put it back to MSB first in 16 bits
+            OutputGlyph {
+                ch,
+                rows: extended[..height as usize].iter().map(|row| row << 7).collect(),
+            }
+        };
+
+        let mut by_char: BTreeMap<char, OutputGlyph> = BTreeMap::new();
+        for (slot, glyph) in self.glyphs.iter().enumerate() {
+            let ch = options.codepage.to_unicode(slot as u8);
+            if !ch.is_control() && !by_char.contains_key(&ch) {
+                by_char.insert(ch, widen(ch, glyph));
+            }
+        }
+        for (ch, glyph) in self.unicode_glyphs.iter() {
+            if !ch.is_control() {
+                by_char.insert(*ch, widen(*ch, glyph));
+            }
+        }
+        let glyphs: Vec<OutputGlyph> = by_char.into_values().collect();
+        // Glyph ids are 16 bit and id 0 is the .notdef glyph
+        if glyphs.len() >= u16::MAX as usize {
+            return Err(EngineError::Generic(format!(
+                "TrueType fonts hold at most {} glyphs, got {}",
+                u16::MAX - 1,
+                glyphs.len()
+            )));
+        }
+        let chars: Vec<char> = glyphs.iter().map(|g| g.ch).collect();
+        let segment_count = cmap_segment_count(&char_runs(&chars));
+        if segment_count > MAX_CMAP_SEGMENTS {
+            return Err(EngineError::Generic(format!(
+                "TrueType character maps hold at most {} ranges of consecutive characters, got {}",
+                MAX_CMAP_SEGMENTS - 1,
+                segment_count - 1
+            )));
+        }
+        let num_glyphs = glyphs.len() as u16 + 1;
+        let advance = (width * UNITS_PER_PIXEL) as u16;
+
+        // glyf, loca, hmtx, glyph 0 is an empty .notdef
+        let mut glyf = Vec::new();
+        let mut loca = Table::default();
+        let mut hmtx = Table::default();
+        let mut font_bbox = [i16::MAX, i16::MAX, i16::MIN, i16::MIN];
+        let (mut max_points, mut max_contours) = (0, 0);
+        loca.u32(0).u32(0);
+        hmtx.u16(advance).i16(0);
+        for glyph in &glyphs {
+            let contours = trace_contours(glyph, width, ascent);
+            max_points = max_points.max(contours.iter().map(Vec::len).sum::<usize>());
+            max_contours = max_contours.max(contours.len());
+            let (data, bbox) = encode_glyf(&contours);
+            if !data.is_empty() {
+                font_bbox = [
+                    font_bbox[0].min(bbox[0]),
+                    font_bbox[1].min(bbox[1]),
+                    font_bbox[2].max(bbox[2]),
+                    font_bbox[3].max(bbox[3]),
+                ];
+            }
+            glyf.extend(data);
+            loca.u32(glyf.len() as u32);
+            hmtx.u16(advance).i16(bbox[0]);
+        }
+        if font_bbox[0] > font_bbox[2] {
+            font_bbox = [0; 4];
+        }
+
+        let units_per_em = (height * UNITS_PER_PIXEL) as u16;
+        let ascender = (ascent * UNITS_PER_PIXEL) as i16;
+        let descender = -(descent * UNITS_PER_PIXEL) as i16;
+
+        let mut head = Table::default();
+        head.u32(0x0001_0000).u32(0x0001_0000).u32(0).u32(0x5F0F_3CF5);
+        // Baseline at y=0, left sidebearing at x=0, integer scaling
+        head.u16(0x000B).u16(units_per_em).bytes(&[0; 16]);
+        for v in font_bbox {
+            head.i16(v);
+        }
+        head.u16(0).u16(self.height as u16).i16(2).i16(1).i16(0);
+
+        let mut hhea = Table::default();
+        hhea.u32(0x0001_0000).i16(ascender).i16(descender).i16(0).u16(advance);
+        hhea.i16(0).i16(advance as i16 - font_bbox[2]).i16(font_bbox[2]);
+        hhea.i16(1).i16(0).i16(0).bytes(&[0; 8]).i16(0).u16(num_glyphs);
+
+        let mut maxp = Table::default();
+        maxp.u32(0x0001_0000).u16(num_glyphs).u16(max_points as u16).u16(max_contours as u16);
+        maxp.u16(0).u16(0).u16(2).bytes(&[0; 16]);
+
+        let (range1, range2) = code_page_range(options.codepage);
+        let first_char = chars.first().map_or(0, |c| (*c as u32).min(0xFFFF) as u16);
+        let last_char = chars.last().map_or(0, |c| (*c as u32).min(0xFFFF) as u16);
+        let pixel = UNITS_PER_PIXEL as i16;
+        let mut os2 = Table::default();
+        os2.u16(4).i16(advance as i16).u16(400).u16(5).u16(0);
+        // Subscript and superscript size and offset
+        for v in [advance as i16 / 2, ascender / 2, 0, pixel, advance as i16 / 2, ascender / 2, 0, ascender / 2] {
+            os2.i16(v);
+        }
+        os2.i16(pixel).i16(ascender / 2).i16(0);
+        // PANOSE: Latin text, monospaced
+        os2.bytes(&[2, 0, 0, 9, 0, 0, 0, 0, 0, 0]);
+        os2.u32(0).u32(0).u32(0).u32(0).bytes(b"ICY ");
+        os2.u16(0x0040).u16(first_char).u16(last_char);
+        os2.i16(ascender).i16(descender).i16(0).u16(ascender as u16).u16(-descender as u16);
+        os2.u32(range1).u32(range2);
+        os2.i16(ascender / 2).i16(ascender).u16(0).u16(0x20).u16(1);
+
+        let mut post = Table::default();
+        post.u32(0x0003_0000).u32(0).i16(descender / 2).i16(pixel).u32(1).bytes(&[0; 16]);
+
+        let mut tables = vec![
+            (*b"OS/2", os2.0),
+            (*b"cmap", encode_cmap(&chars)),
+            (*b"glyf", glyf),
+            (*b"head", head.0),
+            (*b"hhea", hhea.0),
+            (*b"hmtx", hmtx.0),
+            (*b"loca", loca.0),
+            (*b"maxp", maxp.0),
+            (*b"name", encode_name(&self.name)),
+            (*b"post", post.0),
+        ];
+        if options.bitmap_strike {
+            let (eblc, ebdt) = encode_bitmap_strike(&glyphs, width, height, ascent);
+            tables.push((*b"EBDT", ebdt));
+            tables.push((*b"EBLC", eblc));
+        }
+        tables.sort_by_key(|(tag, _)| *tag);
+        Ok(write_sfnt(&tables))
+    }
+}
+
+/// `EBLC` and `EBDT` for a single strike at `height` pixels per em.
+///
+/// All glyphs share their metrics, so one index subtable of format 2 (constant size) with
+/// bit aligned images of format 5 covers glyphs 1 to n.
+fn encode_bitmap_strike(glyphs: &[OutputGlyph], width: i32, height: i32, ascent: i32) -> (Vec<u8>, Vec<u8>) {
+    let image_size = ((width * height) as usize).div_ceil(8);
+    let mut ebdt = Table::default();
+    ebdt.u16(2).u16(0);
+    for glyph in glyphs {
+        let mut image = vec![0u8; image_size];
+        for y in 0..height {
+            for x in 0..width {
+                if glyph.pixel(x, y) {
+                    let bit = (y * width + x) as usize;
+                    image[bit / 8] |= 0x80 >> (bit % 8);
+                }
+            }
+        }
+        ebdt.bytes(&image);
+    }
+
+    let (w, h, ascent, descent) = (width as u8, height as u8, ascent as i8, (ascent - height) as i8);
+    let line_metrics = [ascent as u8, descent as u8, w, 1, 0, 0, 0, 0, ascent as u8, descent as u8, 0, 0];
+    let last_glyph = glyphs.len() as u16;
+
+    let mut eblc = Table::default();
+    eblc.u16(2).u16(0).u32(1);
+    // BitmapSize: index subtable array right after it, 8 bytes array + 20 bytes subtable
+    eblc.u32(8 + 48).u32(28).u32(1).u32(0);
+    eblc.bytes(&line_metrics).bytes(&line_metrics);
+    eblc.u16(1).u16(last_glyph).u8(h).u8(h).u8(1).u8(0x01);
+    // IndexSubTableArray
+    eblc.u16(1).u16(last_glyph).u32(8);
+    // IndexSubTable format 2, image format 5 starting behind the EBDT header
+    eblc.u16(2).u16(5).u32(4).u32(image_size as u32);
+    eblc.u8(h).u8(w).u8(0).u8(ascent as u8).u8(w).u8(0).u8(0).u8(h);
+    (eblc.0, ebdt.0)
+}
@@ -0,0 +1,167 @@
+//! Windows raster font reader (`.FNT` and `.FON`).
+//!
+//! A `.FNT` file holds one font size. A `.FON` file is a 16-bit NE executable with one
+//! `RT_FONT` resource per size, each resource is a complete `.FNT`. Glyphs are stored
+//! column by column, every column 8 pixels wide. Character codes are kept as slots,
+//! OEM fonts are CP437 while ANSI fonts use the Windows codepage. ANSI fonts also get
+//! their Latin-1 glyphs as Unicode glyphs.
+
+use std::{collections::HashMap, sync::Arc};
+
+use super::compact_glyph::{CompactGlyph, MAX_GLYPH_HEIGHT};
+use super::{BitFont, BitFontType};
+use crate::EngineError;
+
+const RT_FONT: u16 = 0x8008;
+/// `dfType` bit for vector fonts
+const FNT_TYPE_VECTOR: u16 = 0x0001;
+const ANSI_CHARSET: u8 = 0;
+
+fn u16_at(data: &[u8], offset: usize) -> crate::Result<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| EngineError::Generic("FNT: unexpected end of data".into()))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> crate::Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| EngineError::Generic("FNT: unexpected end of data".into()))
+}
+
+/// Parse a single `.FNT` font (version 2 or 3).
+fn parse_fnt(data: &[u8]) -> crate::Result<BitFont> {
+    let version = u16_at(data, 0)?;
+    if version != 0x0200 && version != 0x0300 {
+        return Err(EngineError::Generic(format!("FNT: unsupported version {version:#06x}")));
+    }
+    if u16_at(data, 66)? & FNT_TYPE_VECTOR != 0 {
+        return Err(EngineError::Generic("FNT: vector fonts are not supported".into()));
+    }
+    let pix_width = u16_at(data, 86)?;
+    let pix_height = u16_at(data, 88)?;
+    let charset = *data.get(85).unwrap_or(&0);
+    let max_width = u16_at(data, 93)?;
+    let first_char = *data.get(95).unwrap_or(&0) as usize;
+    let last_char = *data.get(96).unwrap_or(&0) as usize;
+    let face_offset = u32_at(data, 105)? as usize;
+
+    let face = data
+        .get(face_offset..)
+        .map(|tail| {
+            let end = tail.iter().position(|&b| b == 0).unwrap_or(tail.len());
+            String::from_utf8_lossy(&tail[..end]).trim().to_string()
+        })
+        .unwrap_or_default();
+
+    // Proportional fonts have no pixel width, they get a cell as wide as their widest glyph
+    let cell_width = (if pix_width == 0 { max_width } else { pix_width }).clamp(1, 8) as u8;
+    let height = (pix_height as usize).clamp(1, MAX_GLYPH_HEIGHT) as u8;
+
+    let (table_offset, entry_size) = if version == 0x0300 { (148, 6) } else { (118, 4) };
+    let mut glyphs: [CompactGlyph; 256] = std::array::from_fn(|_| CompactGlyph::new(cell_width, height));
+    for (i, code) in (first_char..=last_char.min(255)).enumerate() {
+        let entry = table_offset + i * entry_size;
+        let width = u16_at(data, entry)?;
+        let offset = if version == 0x0300 {
+            u32_at(data, entry + 2)?
+        } else {
+            u16_at(data, entry + 2)? as u32
+        } as usize;
+        if width == 0 {
+            continue;
+        }
+        // The first byte column holds the leftmost 8 pixels of every row
+        let rows = data
+            .get(offset..offset + pix_height as usize)
+            .ok_or_else(|| EngineError::Generic(format!("FNT: bitmap of character {code} out of range")))?;
+        let mask = 0xFFu8 << (8 - cell_width.min(width.min(8) as u8));
+        let glyph = &mut glyphs[code];
+        for (y, row) in rows.iter().take(height as usize).enumerate() {
+            glyph.data[y] = row & mask;
+        }
+    }
+
+    // Windows-1252 matches Latin-1 outside of 0x80..0xA0
+    let unicode_glyphs: HashMap<char, CompactGlyph> = if charset == ANSI_CHARSET {
+        (first_char..=last_char.min(255))
+            .filter(|code| !(0x80..0xA0).contains(code))
+            .map(|code| (char::from(code as u8), glyphs[code]))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    Ok(BitFont {
+        name: if face.is_empty() { format!("{height}") } else { format!("{face} {height}") },
+        width: cell_width,
+        height,
+        glyphs,
+        unicode_glyphs: Arc::new(unicode_glyphs),
+        path_opt: None,
+        font_type: BitFontType::Custom,
+    })
+}
+
+/// Offsets and lengths of all `RT_FONT` resources of an NE executable.
+fn ne_font_resources(data: &[u8]) -> crate::Result<Vec<(usize, usize)>> {
+    let ne = u32_at(data, 0x3C)? as usize;
+    match data.get(ne..ne + 2) {
+        Some(b"NE") => {}
+        Some(b"PE") => return Err(EngineError::Generic("FON: 32-bit PE font files are not supported".into())),
+        _ => return Err(EngineError::Generic("FON: missing NE header".into())),
+    }
+    let mut pos = ne + u16_at(data, ne + 0x24)? as usize;
+    let shift = u16_at(data, pos)?;
+    if shift > 16 {
+        return Err(EngineError::Generic(format!("FON: invalid alignment shift {shift}")));
+    }
+    pos += 2;
+
+    let mut fonts = Vec::new();
+    loop {
+        let type_id = u16_at(data, pos)?;
+        if type_id == 0 {
+            break;
+        }
+        let count = u16_at(data, pos + 2)? as usize;
+        pos += 8;
+        for _ in 0..count {
+            if type_id == RT_FONT {
+                let offset = (u16_at(data, pos)? as usize) << shift;
+                let length = (u16_at(data, pos + 2)? as usize) << shift;
+                fonts.push((offset, length));
+            }
+            pos += 12;
+        }
+    }
+    Ok(fonts)
+}
+
+impl BitFont {
+    /// Load a Windows raster font, one `BitFont` per size.
+    ///
+    /// `.FNT` files hold a single size, `.FON` files one or more. Proportional glyphs are placed
+    /// left aligned in a cell as wide as the widest glyph, glyphs wider than 8 pixels are cut.
+    pub fn from_windows_font(data: &[u8]) -> crate::Result<Vec<Self>> {
+        if data.starts_with(b"MZ") {
+            let resources = ne_font_resources(data)?;
+            if resources.is_empty() {
+                return Err(EngineError::Generic("FON: no font resources".into()));
+            }
+            let mut fonts = resources
+                .into_iter()
+                .map(|(offset, length)| {
+                    let fnt = data
+                        .get(offset..(offset + length).min(data.len()))
+                        .ok_or_else(|| EngineError::Generic("FON: font resource out of range".into()))?;
+                    parse_fnt(fnt)
+                })
+                .collect::<crate::Result<Vec<_>>>()?;
+            fonts.sort_by_key(|font| (font.height, font.width));
+            Ok(fonts)
+        } else {
+            Ok(vec![parse_fnt(data)?])
+        }
+    }
+}
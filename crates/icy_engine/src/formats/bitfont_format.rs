@@ -15,6 +15,14 @@ pub enum BitFontFormat {
     /// Extension: .psf
     Psf,
 
+    /// BDF (Glyph Bitmap Distribution Format) - X11 text bitmap font format
+    /// Extension: .bdf
+    Bdf,
+
+    /// PCF (Portable Compiled Format) - compiled X11 bitmap font, read only
+    /// Extension: .pcf
+    Pcf,
+
     /// Raw bitmap font with specified height (8 pixels wide)
     /// Extension: .fXX where XX is the font height (e.g., .f08, .f14, .f16)
     Raw(u8),
@@ -28,6 +36,8 @@ impl BitFontFormat {
         match self {
             Self::Yaff => "yaff".to_string(),
             Self::Psf => "psf".to_string(),
+            Self::Bdf => "bdf".to_string(),
+            Self::Pcf => "pcf".to_string(),
             Self::Raw(height) => format!("f{height:02}"),
         }
     }
@@ -53,6 +63,8 @@ impl BitFontFormat {
         match ext.as_str() {
             "yaff" => Some(Self::Yaff),
             "psf" => Some(Self::Psf),
+            "bdf" => Some(Self::Bdf),
+            "pcf" => Some(Self::Pcf),
             _ if ext.starts_with('f') && ext.len() > 1 => {
                 // Parse .fXX format (e.g., f08, f14, f16, f8)
                 let height_str = &ext[1..];
@@ -85,7 +97,7 @@ impl BitFontFormat {
     /// Get all common `BitFont` extensions for file dialogs.
     pub fn all_extensions() -> &'static [&'static str] {
         &[
-            "yaff", "psf", "f04", "f05", "f06", "f07", "f08", "f09", "f10", "f12", "f14", "f16", "f19", "f20", "f24", "f32",
+            "yaff", "psf", "bdf", "pcf", "f04", "f05", "f06", "f07", "f08", "f09", "f10", "f12", "f14", "f16", "f19", "f20", "f24", "f32",
         ]
     }
 
@@ -94,6 +106,8 @@ impl BitFontFormat {
         match self {
             Self::Yaff => "YAFF",
             Self::Psf => "PSF",
+            Self::Bdf => "BDF",
+            Self::Pcf => "PCF",
             Self::Raw(_) => "Raw Bitmap Font",
         }
     }
@@ -103,6 +117,8 @@ impl BitFontFormat {
         match self {
             Self::Yaff => "Yet Another Font Format (text-based)".to_string(),
             Self::Psf => "PC Screen Font (Linux console)".to_string(),
+            Self::Bdf => "Glyph Bitmap Distribution Format (X11)".to_string(),
+            Self::Pcf => "Portable Compiled Format (X11)".to_string(),
             Self::Raw(height) => format!("Raw bitmap font ({height}px height)"),
         }
     }
@@ -113,6 +129,8 @@ impl std::fmt::Display for BitFontFormat {
         match self {
             Self::Yaff => write!(f, "YAFF"),
             Self::Psf => write!(f, "PSF"),
+            Self::Bdf => write!(f, "BDF"),
+            Self::Pcf => write!(f, "PCF"),
             Self::Raw(height) => write!(f, "Raw ({height}px)"),
         }
     }
@@ -153,6 +153,8 @@ impl FileFormat {
         FileFormat::Palette(PaletteFormat::Ase),
         FileFormat::BitFont(BitFontFormat::Yaff),
         FileFormat::BitFont(BitFontFormat::Psf),
+        FileFormat::BitFont(BitFontFormat::Bdf),
+        FileFormat::BitFont(BitFontFormat::Pcf),
         FileFormat::BitFont(BitFontFormat::Raw(4)),
         FileFormat::BitFont(BitFontFormat::Raw(5)),
         FileFormat::BitFont(BitFontFormat::Raw(6)),
@@ -392,6 +394,8 @@ impl FileFormat {
             FileFormat::BitFont(font_fmt) => match font_fmt {
                 BitFontFormat::Yaff => "yaff",
                 BitFontFormat::Psf => "psf",
+                BitFontFormat::Bdf => "bdf",
+                BitFontFormat::Pcf => "pcf",
                 BitFontFormat::Raw(4) => "f04",
                 BitFontFormat::Raw(5) => "f05",
                 BitFontFormat::Raw(6) => "f06",
@@ -467,6 +471,8 @@ impl FileFormat {
             FileFormat::Palette(fmt) => fmt.all_extensions(),
             FileFormat::BitFont(BitFontFormat::Yaff) => &["yaff"],
             FileFormat::BitFont(BitFontFormat::Psf) => &["psf"],
+            FileFormat::BitFont(BitFontFormat::Bdf) => &["bdf"],
+            FileFormat::BitFont(BitFontFormat::Pcf) => &["pcf"],
             FileFormat::BitFont(BitFontFormat::Raw(_)) => &["f04", "f05", "f06", "f07", "f08", "f09", "f10", "f12", "f14", "f16", "f19", "f20", "f24", "f32"],
             FileFormat::CharacterFont(CharacterFontFormat::Figlet) => &["flf"],
             FileFormat::CharacterFont(CharacterFontFormat::Tdf) => &["tdf"],
@@ -1219,7 +1225,7 @@ impl FileFormat {
         }
 
         // Apply color optimizer if not lossless output
-        let buffer = if self == &FileFormat::IcyDraw {
+        let mut buffer = if self == &FileFormat::IcyDraw {
             // IcyDraw native format
             buffer.clone()
         } else if options.is_lossless() {
@@ -1232,6 +1238,11 @@ impl FileFormat {
             buffer.show_tags = false;
             buffer
         };
+        if let Some(profile) = &options.display_codes {
+            if self != &FileFormat::IcyDraw {
+                profile.apply_export(&mut buffer);
+            }
+        }
 
         match self {
             FileFormat::Ansi | FileFormat::AnsiMusic => io::save_ansi(&buffer, options),
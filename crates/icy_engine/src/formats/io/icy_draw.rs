@@ -24,6 +24,10 @@ mod constants {
         pub const EDIT_LOCK: u32 = 0b0000_0100;
         pub const HAS_ALPHA: u32 = 0b0000_1000;
         pub const ALPHA_LOCKED: u32 = 0b0001_0000;
+        pub const IS_COLLAPSED: u32 = 0b0010_0000;
+        /// Group depth is stored in bits 8..16, older readers ignore it
+        pub const DEPTH_SHIFT: u32 = 8;
+        pub const DEPTH_MASK: u32 = 0xFF << DEPTH_SHIFT;
     }
 }
 
@@ -90,6 +94,10 @@ fn encode_layer_flags(props: &LayerProperties) -> u32 {
     if props.is_alpha_channel_locked {
         flags |= constants::layer::ALPHA_LOCKED;
     }
+    if props.is_collapsed {
+        flags |= constants::layer::IS_COLLAPSED;
+    }
+    flags |= (props.depth as u32) << constants::layer::DEPTH_SHIFT;
     flags
 }
 
@@ -100,6 +108,35 @@ fn decode_layer_flags(flags: u32, props: &mut LayerProperties) {
     props.is_position_locked = (flags & constants::layer::POS_LOCK) != 0;
     props.has_alpha_channel = (flags & constants::layer::HAS_ALPHA) != 0;
     props.is_alpha_channel_locked = (flags & constants::layer::ALPHA_LOCKED) != 0;
+    props.is_collapsed = (flags & constants::layer::IS_COLLAPSED) != 0;
+    props.depth = ((flags & constants::layer::DEPTH_MASK) >> constants::layer::DEPTH_SHIFT) as u8;
+}
+
+/// Layer mode byte - unknown values (from newer versions) fall back to normal
+fn encode_layer_mode(mode: crate::Mode) -> u8 {
+    match mode {
+        crate::Mode::Normal => 0,
+        crate::Mode::Chars => 1,
+        crate::Mode::Attributes => 2,
+        crate::Mode::Foreground => 3,
+        crate::Mode::Background => 4,
+        crate::Mode::Darken => 5,
+        crate::Mode::Lighten => 6,
+        crate::Mode::Mask => 7,
+    }
+}
+
+fn decode_layer_mode(mode: u8) -> crate::Mode {
+    match mode {
+        1 => crate::Mode::Chars,
+        2 => crate::Mode::Attributes,
+        3 => crate::Mode::Foreground,
+        4 => crate::Mode::Background,
+        5 => crate::Mode::Darken,
+        6 => crate::Mode::Lighten,
+        7 => crate::Mode::Mask,
+        _ => crate::Mode::Normal,
+    }
 }
 
 /// Encode layer color (RGBA where A=0xFF means color is set)
@@ -458,11 +495,7 @@ fn process_icy_draw_v1_decoded_chunk(
             }
 
             // Read mode
-            let mode = match bytes[o] {
-                1 => crate::Mode::Chars,
-                2 => crate::Mode::Attributes,
-                _ => crate::Mode::Normal,
-            };
+            let mode = decode_layer_mode(bytes[o]);
             o += 1;
 
             // Read color (RGBA, where A=0xFF means color is set)
@@ -533,6 +566,19 @@ fn process_icy_draw_v1_decoded_chunk(
             result.layers.push(layer);
         }
 
+        "GROUP" => {
+            let (title, o) = read_utf8_encoded_string(bytes)?;
+            // color(4) + flags(4)
+            if bytes.len() < o + 8 {
+                return Err(IcedError::DataTruncated(o + 8));
+            }
+            let mut layer = Layer::new_group(title);
+            layer.properties.color = decode_layer_color(&bytes[o..o + 4]);
+            let flags = u32::from_le_bytes(bytes[o + 4..o + 8].try_into().unwrap());
+            decode_layer_flags(flags, &mut layer.properties);
+            result.layers.push(layer);
+        }
+
         "SIXEL" => {
             let mut o: usize = 0;
 
@@ -704,7 +750,14 @@ pub(crate) fn save_icy_draw(buf: &TextBuffer, options: &SaveOptions) -> Result<V
     }
 
     for layer in &buf.layers {
-        if layer.role == crate::Role::Image {
+        if layer.role == crate::Role::Group {
+            // GROUP chunk - header without content, the members follow as LAYER/SIXEL chunks
+            let mut group_data = Vec::new();
+            write_utf8_encoded_string(&mut group_data, &layer.properties.title);
+            encode_layer_color(&layer.properties.color, &mut group_data);
+            group_data.extend(u32::to_le_bytes(encode_layer_flags(&layer.properties)));
+            write_compressed_chunk(&mut writer, "GROUP", file_compression, &group_data)?;
+        } else if layer.role == crate::Role::Image {
             // SIXEL chunk - separate format for image layers
             let sixel = layer
                 .sixels
@@ -744,12 +797,7 @@ pub(crate) fn save_icy_draw(buf: &TextBuffer, options: &SaveOptions) -> Result<V
             let mut layer_data = Vec::new();
             write_utf8_encoded_string(&mut layer_data, &layer.properties.title);
 
-            let mode = match layer.properties.mode {
-                crate::Mode::Normal => 0,
-                crate::Mode::Chars => 1,
-                crate::Mode::Attributes => 2,
-            };
-            layer_data.push(mode);
+            layer_data.push(encode_layer_mode(layer.properties.mode));
 
             encode_layer_color(&layer.properties.color, &mut layer_data);
 
@@ -492,6 +492,8 @@ fn process_icy_draw_v0_decoded_chunk(
                         layer.sixels[0].picture_data.extend(bytes);
                         return Ok(true);
                     }
+                    // v0 files have no groups
+                    crate::Role::Group => return Ok(true),
                 }
             }
 
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 
 use super::ScreenPreperation;
+use crate::DisplayCodeProfile;
 pub use icy_sauce::MetaData as SauceMetaData;
 
 /// Main save options structure for all file formats.
@@ -23,6 +24,10 @@ pub struct SaveOptions {
     /// Format-specific options.
     #[serde(default)]
     pub format: FormatOptions,
+
+    /// BBS profile used to write the display code tags, native formats keep the tags as they are.
+    #[serde(skip)]
+    pub display_codes: Option<DisplayCodeProfile>,
 }
 
 impl fmt::Debug for SaveOptions {
@@ -31,6 +36,7 @@ impl fmt::Debug for SaveOptions {
             .field("sauce", &self.sauce.as_ref().map(|_| "SauceMetaData"))
             .field("preprocess", &self.preprocess)
             .field("format", &self.format)
+            .field("display_codes", &self.display_codes.as_ref().map(|p| p.dialect))
             .finish()
     }
 }
@@ -70,6 +70,7 @@ impl GifFrame {
 }
 
 /// GIF encoder with support for animation and color quantization.
+#[derive(Clone)]
 pub struct GifEncoder {
     /// Image width in pixels
     pub width: u16,
@@ -149,6 +150,28 @@ impl GifEncoder {
             return Err(EngineError::Generic("Export cancelled".to_string()));
         }
 
+        let mut encoder = self.clone().start(writer)?;
+
+        for (i, frame) in frames.iter().enumerate() {
+            // Check for cancellation
+            if is_cancelled() {
+                return Err(EngineError::Generic("Export cancelled".to_string()));
+            }
+
+            encoder.write_frame(frame)?;
+
+            // Report progress after each frame
+            progress_callback(i + 1, total_frames);
+        }
+
+        encoder.finish()
+    }
+
+    /// Start a GIF on a writer that takes the frames one at a time.
+    ///
+    /// Unlike the `encode_*` methods the frames don't need to be in memory at once,
+    /// the GIF is complete after [`GifStream::finish`].
+    pub fn start<W: Write>(self, writer: W) -> crate::Result<GifStream<W>> {
         // Create encoder with empty global palette - we use local palettes per frame
         let mut encoder =
             gif::Encoder::new(writer, self.width, self.height, &[]).map_err(|e| EngineError::Generic(format!("GIF encoder creation failed: {e}")))?;
@@ -164,19 +187,13 @@ impl GifEncoder {
                 .map_err(|e| EngineError::Generic(format!("Failed to set repeat: {e}")))?,
         }
 
-        for (i, frame) in frames.iter().enumerate() {
-            // Check for cancellation
-            if is_cancelled() {
-                return Err(EngineError::Generic("Export cancelled".to_string()));
-            }
-
-            self.encode_frame_with_local_palette(&mut encoder, frame)?;
-
-            // Report progress after each frame
-            progress_callback(i + 1, total_frames);
-        }
+        Ok(GifStream { settings: self, encoder })
+    }
 
-        Ok(())
+    /// Start a GIF file that takes the frames one at a time, see [`GifEncoder::start`].
+    pub fn start_file(self, path: impl AsRef<Path>) -> crate::Result<GifStream<std::fs::File>> {
+        let file = std::fs::File::create(path.as_ref())?;
+        self.start(file)
     }
 
     /// Quantize frame colors to 256-color palette using quantette
@@ -263,6 +280,25 @@ impl GifEncoder {
     }
 }
 
+/// A GIF being written frame by frame, created by [`GifEncoder::start`].
+pub struct GifStream<W: Write> {
+    settings: GifEncoder,
+    encoder: gif::Encoder<W>,
+}
+
+impl<W: Write> GifStream<W> {
+    /// Quantize and write the next frame (`width * height * 4` bytes of RGBA data).
+    pub fn write_frame(&mut self, frame: &GifFrame) -> crate::Result<()> {
+        self.settings.encode_frame_with_local_palette(&mut self.encoder, frame)
+    }
+
+    /// Write the GIF trailer, reports write errors that dropping the stream would ignore.
+    pub fn finish(self) -> crate::Result<()> {
+        self.encoder.into_inner()?;
+        Ok(())
+    }
+}
+
 /// Encode a single static image as a GIF (no animation).
 ///
 /// # Arguments
@@ -10,6 +10,18 @@ enum RenderLayer {
 }
 
 impl TextBuffer {
+    /// The document char at `pos` with the preview layer on top, `visibility` is [`Self::layer_visibility`]
+    fn rendered_char_at(&self, pos: Position, visibility: Option<&[bool]>) -> crate::AttributedChar {
+        let mut ch = self.char_at_with_visibility(pos, visibility);
+        if let Some(layer) = &self.preview_layer {
+            let layer_pos = pos - layer.offset();
+            if layer_pos.x >= 0 && layer_pos.y >= 0 && layer_pos.x < layer.width() && layer_pos.y < layer.height() {
+                self.merge_layer_char(&mut ch, layer, layer_pos);
+            }
+        }
+        ch
+    }
+
     pub fn render_to_rgba(&self, options: &RenderOptions, scan_lines: bool) -> (Size, Vec<u8>) {
         self.render_to_rgba_raw(options, scan_lines)
     }
@@ -292,6 +304,8 @@ impl TextBuffer {
     }
 
     fn render_optimized_u32(&self, options: &RenderOptions, pixels: &mut [u32], font_size: Size, rect: Rectangle, line_width: i32) {
+        let visibility = self.layer_visibility();
+        let visibility = visibility.as_deref();
         use crate::Palette;
 
         // Palette cache as u32 for direct pixel writes
@@ -323,7 +337,7 @@ impl TextBuffer {
             // Process this character row
             for x in 0..rect.width() {
                 let pos = Position::new(x + rect.start.x, y + rect.start.y);
-                let ch = self.char_at(pos);
+                let ch = self.rendered_char_at(pos, visibility);
 
                 // Resolve font - use get_font_for_render for 9px font support
                 let font = self.font_for_render(ch.font_page()).unwrap_or_else(|| self.font_for_render(0).unwrap());
@@ -438,7 +452,7 @@ impl TextBuffer {
 
                 // Foreground glyph overlay
                 if !fg_is_transparent {
-                    let glyph = font.glyph(ch.ch);
+                    let glyph = self.font_glyph(font, ch.ch);
                     let max_cy = (glyph.height as usize).min(cell_pixel_h as usize);
                     unsafe {
                         for cy in 0..max_cy {
@@ -505,6 +519,8 @@ impl TextBuffer {
     /// Separate render method for 9px letter spacing mode.
     /// Box drawing chars (0xC0-0xDF in CP437) extend 8th pixel to 9th, mimicking VGA hardware.
     fn render_optimized_9px_u32(&self, options: &RenderOptions, pixels: &mut [u32], font_size: Size, rect: Rectangle, line_width: i32) {
+        let visibility = self.layer_visibility();
+        let visibility = visibility.as_deref();
         use crate::Palette;
 
         let palette_cache = self.palette.palette_cache_rgba();
@@ -529,7 +545,7 @@ impl TextBuffer {
 
             for x in 0..rect.width() {
                 let pos = Position::new(x + rect.start.x, y + rect.start.y);
-                let ch = self.char_at(pos);
+                let ch = self.rendered_char_at(pos, visibility);
 
                 let font = self.font_for_render(ch.font_page()).unwrap_or_else(|| self.font_for_render(0).unwrap());
 
@@ -640,7 +656,7 @@ impl TextBuffer {
 
                 // Foreground glyph with 9px extension for box drawing
                 if !fg_is_transparent {
-                    let glyph = font.glyph(ch.ch);
+                    let glyph = self.font_glyph(font, ch.ch);
                     let max_cy = (glyph.height as usize).min(cell_pixel_h as usize);
                     let is_box_drawing = (0xC0..=0xDF).contains(&(ch.ch as u16));
 
@@ -712,6 +728,8 @@ impl TextBuffer {
     }
 
     fn render_viewdata_u32(&self, options: &RenderOptions, pixels: &mut [u32], font_size: Size, rect: Rectangle, line_width: i32) {
+        let visibility = self.layer_visibility();
+        let visibility = visibility.as_deref();
         use crate::Palette;
 
         // Palette cache (u32 version for faster writes)
@@ -737,7 +755,7 @@ impl TextBuffer {
             // Check if any character in this line has double-height
             for x in 0..rect.width() {
                 let pos = Position::new(x + rect.start.x, abs_y);
-                let ch = self.char_at(pos);
+                let ch = self.rendered_char_at(pos, visibility);
                 if ch.attribute.is_double_height() {
                     is_double_height_line[y as usize] = true;
                     // Mark the next line as bottom half (if it exists)
@@ -762,7 +780,7 @@ impl TextBuffer {
                 // Get the character from the line above
                 if pos.y > 0 {
                     let above_pos = Position::new(pos.x, pos.y - 1);
-                    let above_ch = self.char_at(above_pos);
+                    let above_ch = self.rendered_char_at(above_pos, visibility);
 
                     // Only render bottom half if the character above has double-height flag
                     if !above_ch.attribute.is_double_height() {
@@ -807,14 +825,14 @@ impl TextBuffer {
                 }
             }
 
-            let ch = self.char_at(pos);
+            let ch = self.rendered_char_at(pos, visibility);
 
             // Determine what to render and how
             let is_in_double_height_line = is_double_height_line[y as usize];
             let is_rendering_bottom_half = is_bottom_half_line[y as usize];
             let render_ch = if is_rendering_bottom_half {
                 // We already checked above that this character has double-height
-                self.char_at(Position::new(pos.x, pos.y - 1))
+                self.rendered_char_at(Position::new(pos.x, pos.y - 1), visibility)
             } else {
                 ch
             };
@@ -1041,7 +1059,11 @@ impl TextBuffer {
         }
 
         let font_dims = font_size;
-        for layer in &self.layers {
+        for (i, layer) in self.layers.iter().enumerate() {
+            // Hidden layers and layers in hidden groups don't show their images either
+            if !self.is_layer_visible(i) {
+                continue;
+            }
             for sixel in &layer.sixels {
                 // Calculate sixel position in character coordinates
                 let sx_char = layer.offset().x + sixel.position.x;
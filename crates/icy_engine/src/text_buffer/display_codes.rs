@@ -0,0 +1,353 @@
+//! BBS display code dialects
+//!
+//! Display code tags keep their code in the syntax of the BBS they were written for. A `BbsDialect`
+//! knows how that BBS spells a code and its field width / justification, a `DisplayCodeProfile`
+//! adds sample values used for the live preview, for validation and for the export of field widths.
+
+use std::collections::HashMap;
+use std::fmt::Alignment;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Tag, TagPlacement, TagRole, TextBuffer};
+use crate::TextPane;
+
+/// Display code syntax of a BBS family
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BbsDialect {
+    /// PCBoard / IcyBoard `@CODE@`, width as `@CODE:30@`, `@CODE:30C@`, `@CODE:30R@`
+    #[default]
+    PCBoard,
+    /// Synchronet `@CODE@`, width as `@CODE-L30@`, `@CODE-C30@`, `@CODE-R30@`
+    Synchronet,
+    /// Mystic / Renegade pipe MCI codes `|XX`, width as `|$R30|XX` (pad right), `|$C30|XX`, `|$L30|XX` (pad left)
+    Mystic,
+    /// WWIV `|@X` codes, these can't be padded
+    Wwiv,
+}
+
+/// A display code split into its name and optional field formatting
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisplayCode {
+    pub name: String,
+    pub width: Option<(usize, Alignment)>,
+}
+
+impl BbsDialect {
+    pub const ALL: [BbsDialect; 4] = [BbsDialect::PCBoard, BbsDialect::Synchronet, BbsDialect::Mystic, BbsDialect::Wwiv];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BbsDialect::PCBoard => "PCBoard",
+            BbsDialect::Synchronet => "Synchronet",
+            BbsDialect::Mystic => "Mystic / Renegade",
+            BbsDialect::Wwiv => "WWIV",
+        }
+    }
+
+    /// Whether the BBS can pad / justify a code to a field width
+    pub fn supports_width(self) -> bool {
+        !matches!(self, BbsDialect::Wwiv)
+    }
+
+    /// Splits `code` into name and field formatting, `None` if it isn't written in this dialect
+    pub fn parse_code(self, code: &str) -> Option<DisplayCode> {
+        match self {
+            BbsDialect::PCBoard => {
+                let inner = code.strip_prefix('@')?.strip_suffix('@')?;
+                let (name, width) = match inner.split_once(':') {
+                    Some((name, spec)) => {
+                        let (digits, alignment) = match spec.chars().last()? {
+                            'C' | 'c' => (&spec[..spec.len() - 1], Alignment::Center),
+                            'R' | 'r' => (&spec[..spec.len() - 1], Alignment::Right),
+                            _ => (spec, Alignment::Left),
+                        };
+                        (name, Some((digits.parse().ok()?, alignment)))
+                    }
+                    None => (inner, None),
+                };
+                is_code_name(name).then(|| DisplayCode { name: name.to_string(), width })
+            }
+            BbsDialect::Synchronet => {
+                let inner = code.strip_prefix('@')?.strip_suffix('@')?;
+                let (name, width) = match inner.rsplit_once('-').and_then(|(name, spec)| Some((name, parse_prefixed_width(spec)?))) {
+                    Some((name, width)) => (name, Some(width)),
+                    None => (inner, None),
+                };
+                is_code_name(name).then(|| DisplayCode { name: name.to_string(), width })
+            }
+            BbsDialect::Mystic => {
+                let mut rest = code;
+                let mut width = None;
+                if let Some(spec) = rest.strip_prefix("|$") {
+                    let (spec, tail) = spec.split_once('|')?;
+                    // Mystic pads on the named side, so `$L` right justifies the value
+                    let (w, alignment) = parse_prefixed_width(spec)?;
+                    let alignment = match alignment {
+                        Alignment::Left => Alignment::Right,
+                        Alignment::Right => Alignment::Left,
+                        Alignment::Center => Alignment::Center,
+                    };
+                    width = Some((w, alignment));
+                    rest = tail;
+                } else {
+                    rest = rest.strip_prefix('|')?;
+                }
+                (rest.chars().count() == 2 && is_code_name(rest)).then(|| DisplayCode { name: rest.to_string(), width })
+            }
+            BbsDialect::Wwiv => {
+                let name = code.strip_prefix("|@")?;
+                (name.chars().count() == 1 && is_code_name(name)).then(|| DisplayCode {
+                    name: name.to_string(),
+                    width: None,
+                })
+            }
+        }
+    }
+
+    /// Writes `code` in this dialect, the width is dropped if the dialect can't pad
+    pub fn format_code(self, code: &DisplayCode) -> String {
+        let name = &code.name;
+        match (self, code.width) {
+            (BbsDialect::PCBoard, None) | (BbsDialect::Synchronet, None) => format!("@{name}@"),
+            (BbsDialect::PCBoard, Some((width, alignment))) => match alignment {
+                Alignment::Left => format!("@{name}:{width}@"),
+                Alignment::Center => format!("@{name}:{width}C@"),
+                Alignment::Right => format!("@{name}:{width}R@"),
+            },
+            (BbsDialect::Synchronet, Some((width, alignment))) => format!("@{name}-{}{width}@", alignment_letter(alignment)),
+            (BbsDialect::Mystic, None) => format!("|{name}"),
+            (BbsDialect::Mystic, Some((width, alignment))) => {
+                let pad = match alignment {
+                    Alignment::Left => 'R',
+                    Alignment::Right => 'L',
+                    Alignment::Center => 'C',
+                };
+                format!("|${pad}{width:02}|{name}")
+            }
+            (BbsDialect::Wwiv, _) => format!("|@{name}"),
+        }
+    }
+
+    /// The code without any field formatting, used as lookup key for sample values
+    pub fn base_code(self, code: &str) -> Option<String> {
+        let mut code = self.parse_code(code)?;
+        code.width = None;
+        Some(self.format_code(&code))
+    }
+}
+
+impl std::fmt::Display for BbsDialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+fn is_code_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '#' || c == '-')
+}
+
+fn alignment_letter(alignment: Alignment) -> char {
+    match alignment {
+        Alignment::Left => 'L',
+        Alignment::Center => 'C',
+        Alignment::Right => 'R',
+    }
+}
+
+/// Parses `L30`, `C30` or `R30`
+fn parse_prefixed_width(spec: &str) -> Option<(usize, Alignment)> {
+    let mut chars = spec.chars();
+    let alignment = match chars.next()?.to_ascii_uppercase() {
+        'L' => Alignment::Left,
+        'C' => Alignment::Center,
+        'R' => Alignment::Right,
+        _ => return None,
+    };
+    Some((chars.as_str().parse().ok()?, alignment))
+}
+
+/// Pads or truncates `value` to exactly `width` characters
+pub fn fit_display_value(value: &str, width: usize, alignment: Alignment) -> String {
+    let len = value.chars().count();
+    if len >= width {
+        return value.chars().take(width).collect();
+    }
+    let pad = width - len;
+    let (left, right) = match alignment {
+        Alignment::Left => (0, pad),
+        Alignment::Right => (pad, 0),
+        Alignment::Center => (pad / 2, pad - pad / 2),
+    };
+    format!("{}{value}{}", " ".repeat(left), " ".repeat(right))
+}
+
+/// Problems found by [`DisplayCodeProfile::validate`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DisplayCodeIssue {
+    /// The tag reaches past the right edge of the buffer
+    OutOfBounds,
+    /// The tag overlaps the tag with the given index
+    Overlap(usize),
+    /// The code isn't written in the syntax of the dialect
+    InvalidSyntax,
+    /// The code isn't part of the profile
+    UnknownCode,
+    /// The sample value is longer than the tag
+    SampleTruncated { sample_len: usize, width: usize },
+    /// The field is wider than its code but the dialect can't pad it
+    WidthNotSupported,
+}
+
+/// A BBS dialect together with the codes it knows and their sample values
+///
+/// Codes with an empty sample are actions (clear screen, pause, …) that don't expand to text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DisplayCodeProfile {
+    pub dialect: BbsDialect,
+    /// Sample values keyed by the code without field formatting
+    pub samples: HashMap<String, String>,
+}
+
+impl DisplayCodeProfile {
+    pub fn new(dialect: BbsDialect) -> Self {
+        Self {
+            dialect,
+            samples: HashMap::new(),
+        }
+    }
+
+    /// The sample value of the tag's code, if it is a field the profile knows
+    pub fn sample(&self, tag: &Tag) -> Option<&str> {
+        if tag.tag_role != TagRole::Displaycode {
+            return None;
+        }
+        let base = self.dialect.base_code(&tag.replacement_value)?;
+        self.samples.get(&base).map(String::as_str).filter(|s| !s.is_empty())
+    }
+
+    /// The sample value fitted to the tag width and alignment
+    pub fn preview_text(&self, tag: &Tag) -> Option<String> {
+        let sample = self.sample(tag)?;
+        let alignment = self
+            .dialect
+            .parse_code(&tag.replacement_value)
+            .and_then(|code| code.width)
+            .map_or(tag.alignment, |(_, alignment)| alignment);
+        Some(fit_display_value(sample, tag.len(), alignment))
+    }
+
+    /// The code as it should be written for the BBS
+    ///
+    /// Fields without explicit formatting get the tag width and alignment, everything else stays as is.
+    pub fn export_code(&self, tag: &Tag) -> String {
+        if self.dialect.supports_width() && self.sample(tag).is_some() {
+            if let Some(mut code) = self.dialect.parse_code(&tag.replacement_value) {
+                if code.width.is_none() && tag.len() > 0 {
+                    code.width = Some((tag.len(), tag.alignment));
+                    return self.dialect.format_code(&code);
+                }
+            }
+        }
+        tag.replacement_value.clone()
+    }
+
+    /// Rewrites the display code tags of `buffer` with [`Self::export_code`]
+    pub fn apply_export(&self, buffer: &mut TextBuffer) {
+        for i in 0..buffer.tags.len() {
+            if buffer.tags[i].tag_role == TagRole::Displaycode {
+                buffer.tags[i].replacement_value = self.export_code(&buffer.tags[i]);
+            }
+        }
+    }
+
+    /// Checks all enabled display code tags, returns the tag index with each issue found
+    pub fn validate(&self, buffer: &TextBuffer) -> Vec<(usize, DisplayCodeIssue)> {
+        let mut issues = Vec::new();
+        let width = buffer.width();
+        for (i, tag) in buffer.tags.iter().enumerate() {
+            if !tag.is_enabled || tag.tag_role != TagRole::Displaycode {
+                continue;
+            }
+            let len = tag.len();
+            if tag.position.x < 0 || tag.position.x + len as i32 > width {
+                issues.push((i, DisplayCodeIssue::OutOfBounds));
+            }
+            if let Some(other) = buffer.tags[..i]
+                .iter()
+                .position(|t| t.is_enabled && t.position.y == tag.position.y && ranges_overlap(t, tag))
+            {
+                issues.push((i, DisplayCodeIssue::Overlap(other)));
+            }
+
+            let Some(base) = self.dialect.base_code(&tag.replacement_value) else {
+                issues.push((i, DisplayCodeIssue::InvalidSyntax));
+                continue;
+            };
+            let Some(sample) = self.samples.get(&base) else {
+                if !self.samples.is_empty() {
+                    issues.push((i, DisplayCodeIssue::UnknownCode));
+                }
+                continue;
+            };
+            let sample_len = sample.chars().count();
+            if sample_len > len {
+                issues.push((i, DisplayCodeIssue::SampleTruncated { sample_len, width: len }));
+            }
+            if !self.dialect.supports_width() && tag.tag_placement == TagPlacement::InText && len > tag.replacement_value.chars().count() {
+                issues.push((i, DisplayCodeIssue::WidthNotSupported));
+            }
+        }
+        issues
+    }
+}
+
+fn ranges_overlap(a: &Tag, b: &Tag) -> bool {
+    let a_end = a.position.x + a.len() as i32;
+    let b_end = b.position.x + b.len() as i32;
+    a.position.x < b_end && b.position.x < a_end
+}
+
+/// [`DisplayCodeProfile::preview_text`] of every tag, filled on demand and cleared when the profile changes
+#[derive(Clone, Default)]
+pub(crate) struct TagPreviewCache {
+    entries: Vec<Option<TagPreviewEntry>>,
+}
+
+/// A preview text together with the tag fields it was computed from
+#[derive(Clone)]
+struct TagPreviewEntry {
+    replacement_value: String,
+    len: usize,
+    alignment: Alignment,
+    tag_role: TagRole,
+    text: Option<Vec<char>>,
+}
+
+impl TagPreviewEntry {
+    fn matches(&self, tag: &Tag) -> bool {
+        self.tag_role == tag.tag_role && self.alignment == tag.alignment && self.len == tag.len() && self.replacement_value == tag.replacement_value
+    }
+}
+
+impl TagPreviewCache {
+    /// The preview character at `x` of the tag with the given index, `None` if the profile has no sample for it
+    pub(crate) fn char_at(&mut self, index: usize, tag: &Tag, profile: &DisplayCodeProfile, x: i32) -> Option<char> {
+        if self.entries.len() <= index {
+            self.entries.resize(index + 1, None);
+        }
+        let entry = &mut self.entries[index];
+        if !entry.as_ref().is_some_and(|e| e.matches(tag)) {
+            *entry = Some(TagPreviewEntry {
+                replacement_value: tag.replacement_value.clone(),
+                len: tag.len(),
+                alignment: tag.alignment,
+                tag_role: tag.tag_role,
+                text: profile.preview_text(tag).map(|text| text.chars().collect()),
+            });
+        }
+        let text = entry.as_ref()?.text.as_ref()?;
+        Some(text.get(x as usize).copied().unwrap_or(' '))
+    }
+}
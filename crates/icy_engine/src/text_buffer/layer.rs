@@ -10,6 +10,16 @@ pub enum Mode {
     Normal,
     Chars,
     Attributes,
+    /// Only the foreground color replaces the layers below
+    Foreground,
+    /// Only the background color replaces the layers below
+    Background,
+    /// Every color keeps the darker one of this layer and the layers below
+    Darken,
+    /// Every color keeps the lighter one of this layer and the layers below
+    Lighten,
+    /// Not drawn itself - clips the layers above (within the same group) to its visible cells
+    Mask,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -17,6 +27,8 @@ pub enum Role {
     #[default]
     Normal,
     Image,
+    /// Empty group header, contains the layers directly below it that have a greater depth
+    Group,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -30,6 +42,12 @@ pub struct LayerProperties {
     pub has_alpha_channel: bool,
     pub mode: Mode,
     pub offset: Position,
+    /// Group nesting level, 0 for top level layers
+    #[serde(default)]
+    pub depth: u8,
+    /// Group shown collapsed in the layer list
+    #[serde(default)]
+    pub is_collapsed: bool,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
@@ -144,6 +162,17 @@ impl Layer {
         }
     }
 
+    /// Creates an empty group header, see [`crate::TextBuffer::group_layers`]
+    pub fn new_group(title: impl Into<String>) -> Self {
+        let mut layer = Layer::new(title, (0, 0));
+        layer.role = Role::Group;
+        layer
+    }
+
+    pub fn is_group(&self) -> bool {
+        self.role == Role::Group
+    }
+
     pub fn offset(&self) -> Position {
         if let Some(offset) = self.preview_offset {
             return offset;
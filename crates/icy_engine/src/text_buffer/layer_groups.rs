@@ -0,0 +1,200 @@
+//! Layer groups
+//!
+//! Layers stay one flat list (bottom to top). A group is a [`crate::Role::Group`] header layer,
+//! its children are the layers directly below the header with a greater
+//! [`crate::LayerProperties::depth`]. This keeps group members contiguous, so a group can be
+//! moved as a block.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::{Layer, Position, TextBuffer};
+
+/// [`TextBuffer::layer_visibility`] together with the layer properties it was computed from
+#[derive(Clone, Default)]
+pub(crate) struct LayerVisibilityCache {
+    /// (depth, is group, is visible) of every layer
+    layers: Vec<(u8, bool, bool)>,
+    visibility: Option<Arc<[bool]>>,
+}
+
+impl LayerVisibilityCache {
+    fn matches(&self, layers: &[Layer]) -> bool {
+        self.layers.len() == layers.len()
+            && self
+                .layers
+                .iter()
+                .zip(layers)
+                .all(|(&(depth, group, visible), l)| depth == l.properties.depth && group == l.is_group() && visible == l.properties.is_visible)
+    }
+}
+
+impl TextBuffer {
+    /// Index of the group header containing `layer`
+    pub fn parent_group(&self, layer: usize) -> Option<usize> {
+        let depth = self.layers.get(layer)?.properties.depth;
+        if depth == 0 {
+            return None;
+        }
+        (layer + 1..self.layers.len())
+            .find(|&i| self.layers[i].properties.depth < depth)
+            .filter(|&i| self.layers[i].is_group())
+    }
+
+    /// Indices of all layers inside a group (nested groups included), empty for non group layers
+    pub fn group_children(&self, group: usize) -> Range<usize> {
+        let Some(header) = self.layers.get(group).filter(|l| l.is_group()) else {
+            return group..group;
+        };
+        let depth = header.properties.depth;
+        let mut start = group;
+        while start > 0 && self.layers[start - 1].properties.depth > depth {
+            start -= 1;
+        }
+        start..group
+    }
+
+    /// The layer together with its children, if it's a group
+    pub fn layer_block(&self, layer: usize) -> Range<usize> {
+        self.group_children(layer).start..layer + 1
+    }
+
+    /// Whether the layer and all groups containing it are visible
+    pub fn is_layer_visible(&self, layer: usize) -> bool {
+        self.any_ancestor(layer, |l| !l.properties.is_visible).is_some_and(|hidden| !hidden)
+    }
+
+    /// Visibility of every layer together with the groups containing it, `None` if nothing is grouped
+    ///
+    /// One pass over the stack, so the renderer can compute it once per frame instead of for every cell.
+    pub fn layer_visibility(&self) -> Option<Vec<bool>> {
+        if self.layers.iter().all(|l| l.properties.depth == 0) {
+            return None;
+        }
+        let mut visibility = vec![false; self.layers.len()];
+        // (depth, is group, visibility) of the layers above the current one that may contain it
+        let mut above: Vec<(u8, bool, bool)> = Vec::new();
+        for i in (0..self.layers.len()).rev() {
+            let layer = &self.layers[i];
+            let depth = layer.properties.depth;
+            while above.last().is_some_and(|&(d, _, _)| d >= depth) {
+                above.pop();
+            }
+            let parent_visible = match above.last() {
+                Some(&(_, true, visible)) => visible,
+                _ => true,
+            };
+            visibility[i] = parent_visible && layer.properties.is_visible;
+            above.push((depth, layer.is_group(), visibility[i]));
+        }
+        Some(visibility)
+    }
+
+    /// [`Self::layer_visibility`], only recomputed when a layer was added, removed, moved, regrouped or shown/hidden
+    ///
+    /// `layers` is public, so the cache is checked against the stack instead of relying on every caller to invalidate it.
+    pub(crate) fn cached_layer_visibility(&self) -> Option<Arc<[bool]>> {
+        let mut cache = self.layer_visibility_cache.lock();
+        if !cache.matches(&self.layers) {
+            cache.layers = self
+                .layers
+                .iter()
+                .map(|l| (l.properties.depth, l.is_group(), l.properties.is_visible))
+                .collect();
+            cache.visibility = self.layer_visibility().map(Arc::from);
+        }
+        cache.visibility.clone()
+    }
+
+    /// Whether the layer or one of the groups containing it is locked
+    pub fn is_layer_locked(&self, layer: usize) -> bool {
+        self.any_ancestor(layer, |l| l.properties.is_locked).unwrap_or(true)
+    }
+
+    /// Checks `pred` for the layer and its enclosing groups, `None` for an invalid index
+    fn any_ancestor(&self, layer: usize, pred: impl Fn(&Layer) -> bool) -> Option<bool> {
+        let mut cur = layer;
+        loop {
+            if pred(self.layers.get(cur)?) {
+                return Some(true);
+            }
+            match self.parent_group(cur) {
+                Some(parent) => cur = parent,
+                None => return Some(false),
+            }
+        }
+    }
+
+    /// Puts the layers in `range` into a new group and returns the index of its header
+    ///
+    /// The range must not split an existing group. The header is inserted directly above the range.
+    pub fn group_layers(&mut self, range: Range<usize>, title: impl Into<String>) -> usize {
+        let range = range.start.min(self.layers.len())..range.end.min(self.layers.len());
+        let depth = self.layers[range.clone()].iter().map(|l| l.properties.depth).min().unwrap_or(0);
+        for layer in &mut self.layers[range.clone()] {
+            layer.properties.depth = layer.properties.depth.saturating_add(1);
+        }
+        let mut group = Layer::new_group(title);
+        group.properties.depth = depth;
+        self.layers.insert(range.end, group);
+        self.mark_dirty();
+        range.end
+    }
+
+    /// Removes a group header, its children move up one level
+    pub fn ungroup_layers(&mut self, group: usize) -> Option<Layer> {
+        if !self.layers.get(group)?.is_group() {
+            return None;
+        }
+        let children = self.group_children(group);
+        for layer in &mut self.layers[children] {
+            layer.properties.depth = layer.properties.depth.saturating_sub(1);
+        }
+        self.mark_dirty();
+        Some(self.layers.remove(group))
+    }
+
+    /// Moves a layer - for groups all children - by `delta` on the canvas
+    pub fn move_layer_block_offset(&mut self, layer: usize, delta: Position) {
+        if layer >= self.layers.len() {
+            return;
+        }
+        let block = self.layer_block(layer);
+        for layer in &mut self.layers[block] {
+            if !layer.is_group() {
+                let offset = layer.base_offset() + delta;
+                layer.set_offset(offset);
+            }
+        }
+        self.mark_dirty();
+    }
+
+    /// Swaps a layer (or a whole group) with the sibling above it. Returns the new index.
+    pub fn raise_layer_block(&mut self, layer: usize) -> Option<usize> {
+        let depth = self.layers.get(layer)?.properties.depth;
+        let block = self.layer_block(layer);
+        let above = (layer + 1..self.layers.len()).find(|&i| self.layers[i].properties.depth <= depth)?;
+        if self.layers[above].properties.depth != depth {
+            // top of its group
+            return None;
+        }
+        self.layers[block.start..above + 1].rotate_left(block.len());
+        self.mark_dirty();
+        Some(above)
+    }
+
+    /// Swaps a layer (or a whole group) with the sibling below it. Returns the new index.
+    pub fn lower_layer_block(&mut self, layer: usize) -> Option<usize> {
+        let depth = self.layers.get(layer)?.properties.depth;
+        let block = self.layer_block(layer);
+        let below = block.start.checked_sub(1)?;
+        if self.layers[below].properties.depth != depth {
+            // bottom of its group
+            return None;
+        }
+        let sibling = self.layer_block(below);
+        self.layers[sibling.start..block.end].rotate_right(block.len());
+        self.mark_dirty();
+        Some(sibling.start + block.len() - 1)
+    }
+}
@@ -16,10 +16,17 @@ pub use text_screen::*;
 pub mod layer;
 pub use layer::*;
 
+mod layer_groups;
+use layer_groups::LayerVisibilityCache;
+
+mod display_codes;
+use display_codes::TagPreviewCache;
+pub use display_codes::*;
+
 mod buffer_type;
 pub use buffer_type::*;
 
-use crate::{attribute, Color, HalfBlock, Position, Rectangle, TerminalState, TextAttribute, TextPane};
+use crate::{attribute, AttributeColor, Color, HalfBlock, Position, Rectangle, TerminalState, TextAttribute, TextPane, XTERM_256_PALETTE};
 
 use super::{AttributedChar, BitFont, Palette, Size};
 
@@ -209,6 +216,12 @@ pub struct TextBuffer {
 
     pub show_tags: bool,
     pub tags: Vec<Tag>,
+    /// Shows sample values instead of the tag previews, not saved
+    display_code_preview: Option<DisplayCodeProfile>,
+    /// Preview texts of the tags for `display_code_preview`
+    tag_preview_cache: parking_lot::Mutex<TagPreviewCache>,
+    /// Drawn above all layers while a tool previews its result, not part of the document and not saved
+    pub preview_layer: Option<Layer>,
     //    pub ansi_music: Vec<AnsiMusic>,
     /// Scrollback buffer storing lines that scrolled off the top
 
@@ -233,6 +246,9 @@ pub struct TextBuffer {
     /// Overlay dirty flag: set when only shader overlays need updating (selection, markers).
     /// This allows updating selection display without invalidating the tile cache.
     overlay_dirty: AtomicBool,
+
+    /// Group visibility used by [`TextPane::char_at`], rebuilt when the layer stack changes
+    layer_visibility_cache: parking_lot::Mutex<LayerVisibilityCache>,
 }
 
 impl std::fmt::Debug for TextBuffer {
@@ -278,6 +294,9 @@ impl Clone for TextBuffer {
             use_letter_spacing: self.use_letter_spacing,
             use_aspect_ratio: self.use_aspect_ratio,
             show_tags: self.show_tags,
+            display_code_preview: self.display_code_preview.clone(),
+            tag_preview_cache: parking_lot::Mutex::new(self.tag_preview_cache.lock().clone()),
+            preview_layer: self.preview_layer.clone(),
             tags: self.tags.clone(),
             max_scrollback_lines: self.max_scrollback_lines,
             font_cell_size: self.font_cell_size,
@@ -286,11 +305,24 @@ impl Clone for TextBuffer {
             dirty_line_start: AtomicI32::new(self.dirty_line_start.load(Ordering::Relaxed)),
             dirty_line_end: AtomicI32::new(self.dirty_line_end.load(Ordering::Relaxed)),
             overlay_dirty: AtomicBool::new(self.overlay_dirty.load(Ordering::Relaxed)),
+            layer_visibility_cache: parking_lot::Mutex::new(self.layer_visibility_cache.lock().clone()),
         }
     }
 }
 
 impl TextBuffer {
+    /// Profile whose sample values are shown instead of the tag previews
+    pub fn display_code_preview(&self) -> Option<&DisplayCodeProfile> {
+        self.display_code_preview.as_ref()
+    }
+
+    /// Show the sample values of `profile` instead of the tag previews, `None` shows the previews again
+    pub fn set_display_code_preview(&mut self, profile: Option<DisplayCodeProfile>) {
+        self.display_code_preview = profile;
+        *self.tag_preview_cache.get_mut() = TagPreviewCache::default();
+        self.mark_dirty();
+    }
+
     /// Check if a line contains only transparent/empty characters
     pub fn is_line_empty(&self, line: i32) -> bool {
         for i in 0..self.width() {
@@ -436,6 +468,54 @@ impl TextBuffer {
         false
     }
 
+    /// [`TextPane::char_at`] with the visibility from [`Self::layer_visibility`], computed once per frame by the renderer
+    pub(crate) fn char_at_with_visibility(&self, pos: Position, visibility: Option<&[bool]>) -> AttributedChar {
+        if self.show_tags {
+            for (i, tag) in self.tags.iter().enumerate() {
+                if tag.is_enabled && tag.contains(pos) {
+                    let x = pos.x - tag.position.x;
+                    if let Some(profile) = &self.display_code_preview {
+                        // Sample values are already fitted to the tag width
+                        if let Some(ch) = self.tag_preview_cache.lock().char_at(i, tag, profile, x) {
+                            return AttributedChar::new(ch, tag.attribute);
+                        }
+                    }
+                    return tag.get_char_at(x);
+                }
+            }
+        }
+        let mut found_char = AttributedChar::invisible();
+        // Bit n is set while a mask at group depth n hides the layers above it at this position
+        let mut clipped: u64 = 0;
+        for i in 0..self.layers.len() {
+            let cur_layer = &self.layers[i];
+            let depth = cur_layer.properties.depth.min(63) as u32;
+            // Leaving a group ends the masks inside of it
+            if depth < 63 {
+                clipped &= (1 << (depth + 1)) - 1;
+            }
+            let is_visible = visibility.map_or(cur_layer.properties.is_visible, |visibility| visibility[i]);
+            if cur_layer.is_group() || !is_visible {
+                continue;
+            }
+            let pos: Position = pos - cur_layer.offset();
+            let is_inside = pos.x >= 0 && pos.y >= 0 && pos.x < cur_layer.width() && pos.y < cur_layer.height();
+            if cur_layer.properties.mode == crate::Mode::Mask {
+                if is_inside && cur_layer.char_at(pos).is_visible() {
+                    clipped &= !(1 << depth);
+                } else {
+                    clipped |= 1 << depth;
+                }
+                continue;
+            }
+            if clipped == 0 && is_inside {
+                self.merge_layer_char(&mut found_char, cur_layer, pos);
+            }
+        }
+
+        found_char
+    }
+
     fn merge_layer_char(&self, found_char: &mut AttributedChar, cur_layer: &Layer, pos: Position) {
         let cur_char = cur_layer.char_at(pos);
         match cur_layer.properties.mode {
@@ -470,8 +550,51 @@ impl TextBuffer {
                     found_char.attribute = cur_char.attribute;
                 }
             }
+            crate::Mode::Foreground => {
+                if cur_char.is_visible() && !cur_char.attribute.is_foreground_transparent() {
+                    found_char.attribute.set_foreground_color(cur_char.attribute.foreground_color());
+                }
+            }
+            crate::Mode::Background => {
+                if cur_char.is_visible() && !cur_char.attribute.is_background_transparent() {
+                    found_char.attribute.set_background_color(cur_char.attribute.background_color());
+                }
+            }
+            crate::Mode::Darken | crate::Mode::Lighten => {
+                if !cur_char.is_visible() {
+                    return;
+                }
+                if !found_char.is_visible() {
+                    *found_char = cur_char;
+                    return;
+                }
+                let darken = cur_layer.properties.mode == crate::Mode::Darken;
+                let pick = |layer_color: AttributeColor, below: AttributeColor| match (self.color_luminance(layer_color), self.color_luminance(below)) {
+                    (Some(l), Some(b)) if (l < b) == darken || l == b => layer_color,
+                    (Some(_), None) => layer_color,
+                    _ => below,
+                };
+                let fg = pick(cur_char.attribute.foreground_color(), found_char.attribute.foreground_color());
+                let bg = pick(cur_char.attribute.background_color(), found_char.attribute.background_color());
+                *found_char = cur_char;
+                found_char.attribute.set_foreground_color(fg);
+                found_char.attribute.set_background_color(bg);
+            }
+            // Masks are handled in char_at, they only clip the layers above
+            crate::Mode::Mask => {}
         }
     }
+
+    /// Perceived brightness of a color, `None` for transparent
+    fn color_luminance(&self, color: AttributeColor) -> Option<u32> {
+        let (r, g, b) = match color {
+            AttributeColor::Palette(index) => self.palette.rgb(index as u32),
+            AttributeColor::ExtendedPalette(index) => XTERM_256_PALETTE[index as usize].1.rgb(),
+            AttributeColor::Rgb(r, g, b) => (r, g, b),
+            AttributeColor::Transparent => return None,
+        };
+        Some(299 * r as u32 + 587 * g as u32 + 114 * b as u32)
+    }
 }
 
 pub fn analyze_font_usage(buf: &TextBuffer) -> Vec<u8> {
@@ -580,6 +703,9 @@ impl TextBuffer {
             use_letter_spacing: false,
             use_aspect_ratio: false,
             show_tags: true,
+            display_code_preview: None,
+            tag_preview_cache: parking_lot::Mutex::new(TagPreviewCache::default()),
+            preview_layer: None,
             tags: Vec::new(),
             //            ansi_music: Vec::new(),
             max_scrollback_lines: 10000,      // Reasonable default
@@ -590,6 +716,7 @@ impl TextBuffer {
             dirty_line_start: AtomicI32::new(-1),
             dirty_line_end: AtomicI32::new(-1),
             overlay_dirty: AtomicBool::new(false),
+            layer_visibility_cache: parking_lot::Mutex::new(LayerVisibilityCache::default()),
         }
     }
 
@@ -941,7 +1068,19 @@ impl TextBuffer {
     /// Returns None if the font for the character's font page doesn't exist.
     #[must_use]
     pub fn glyph(&self, ch: &AttributedChar) -> Option<&crate::fonts::CompactGlyph> {
-        self.font(ch.font_page()).map(|font| font.glyph(ch.ch))
+        self.font(ch.font_page()).map(|font| self.font_glyph(font, ch.ch))
+    }
+
+    /// Glyph of `ch` in `font`. Unicode buffers look up the Unicode glyphs of the font,
+    /// all others index the 256 slots.
+    #[inline]
+    #[must_use]
+    pub fn font_glyph<'a>(&self, font: &'a BitFont, ch: char) -> &'a crate::fonts::CompactGlyph {
+        if self.buffer_type == BufferType::Unicode {
+            font.unicode_glyph(ch)
+        } else {
+            font.glyph(ch)
+        }
     }
 
     #[must_use]
@@ -1088,25 +1227,8 @@ impl TextPane for TextBuffer {
     }
 
     fn char_at(&self, pos: Position) -> AttributedChar {
-        if self.show_tags {
-            for tag in &self.tags {
-                if tag.is_enabled && tag.contains(pos) {
-                    return tag.get_char_at(pos.x - tag.position.x);
-                }
-            }
-        }
-        let mut found_char = AttributedChar::invisible();
-        for i in 0..self.layers.len() {
-            let cur_layer = &self.layers[i];
-            if cur_layer.properties.is_visible {
-                let pos: Position = pos - cur_layer.offset();
-                if pos.x >= 0 && pos.y >= 0 && pos.x < cur_layer.width() && pos.y < cur_layer.height() {
-                    self.merge_layer_char(&mut found_char, cur_layer, pos);
-                }
-            }
-        }
-
-        found_char
+        let visibility = self.cached_layer_visibility();
+        self.char_at_with_visibility(pos, visibility.as_deref())
     }
 
     fn line_length(&self, line: i32) -> i32 {
@@ -0,0 +1,130 @@
+use std::fmt::Alignment;
+
+use icy_engine::{
+    fit_display_value, AttributedChar, BbsDialect, DisplayCode, DisplayCodeIssue, DisplayCodeProfile, FileFormat, Position, SaveOptions, Tag, TagPlacement,
+    TagRole, TextAttribute, TextBuffer, TextPane,
+};
+
+fn display_tag(code: &str, preview: &str, x: i32, alignment: Alignment) -> Tag {
+    Tag {
+        is_enabled: true,
+        preview: preview.to_string(),
+        replacement_value: code.to_string(),
+        position: Position::new(x, 0),
+        length: 0,
+        alignment,
+        tag_placement: TagPlacement::InText,
+        tag_role: TagRole::Displaycode,
+        attribute: TextAttribute::default(),
+    }
+}
+
+fn profile(dialect: BbsDialect, samples: &[(&str, &str)]) -> DisplayCodeProfile {
+    let mut profile = DisplayCodeProfile::new(dialect);
+    for (code, sample) in samples {
+        profile.samples.insert((*code).to_string(), (*sample).to_string());
+    }
+    profile
+}
+
+#[test]
+fn test_parse_and_format_widths() {
+    let cases = [
+        (BbsDialect::PCBoard, "@USER:30C@"),
+        (BbsDialect::Synchronet, "@ALIAS-R12@"),
+        (BbsDialect::Mystic, "|$L08|UH"),
+    ];
+    for (dialect, code) in cases {
+        let parsed = dialect.parse_code(code).unwrap();
+        assert!(parsed.width.is_some(), "{code}");
+        assert_eq!(dialect.format_code(&parsed), code);
+    }
+
+    // Mystic pads on the named side
+    assert_eq!(
+        BbsDialect::Mystic.parse_code("|$L08|UH"),
+        Some(DisplayCode {
+            name: "UH".to_string(),
+            width: Some((8, Alignment::Right))
+        })
+    );
+    assert_eq!(BbsDialect::PCBoard.base_code("@USER:30R@"), Some("@USER@".to_string()));
+    assert_eq!(BbsDialect::PCBoard.parse_code("|UH"), None);
+    assert_eq!(BbsDialect::Wwiv.parse_code("|@N").map(|c| c.name), Some("N".to_string()));
+}
+
+#[test]
+fn test_fit_display_value() {
+    assert_eq!(fit_display_value("JOHN", 8, Alignment::Left), "JOHN    ");
+    assert_eq!(fit_display_value("JOHN", 8, Alignment::Right), "    JOHN");
+    assert_eq!(fit_display_value("JOHN", 7, Alignment::Center), " JOHN  ");
+    assert_eq!(fit_display_value("JOHN DOE", 4, Alignment::Left), "JOHN");
+}
+
+#[test]
+fn test_preview_shows_samples() {
+    let mut buf = TextBuffer::new((20, 2));
+    buf.tags.push(display_tag("@USER@", "@USER@@@", 2, Alignment::Right));
+    assert_eq!(buf.char_at(Position::new(2, 0)).ch, '@');
+
+    buf.set_display_code_preview(Some(profile(BbsDialect::PCBoard, &[("@USER@", "JOE")])));
+    let line: String = (2..10).map(|x| buf.char_at(Position::new(x, 0)).ch).collect();
+    assert_eq!(line, "     JOE");
+
+    // The cached preview follows changes of the tag and the profile
+    buf.tags[0].replacement_value = "@CITY@".to_string();
+    assert_eq!(buf.char_at(Position::new(2, 0)).ch, '@', "no sample for the new code");
+    buf.set_display_code_preview(Some(profile(BbsDialect::PCBoard, &[("@CITY@", "ROME")])));
+    let line: String = (2..10).map(|x| buf.char_at(Position::new(x, 0)).ch).collect();
+    assert_eq!(line, "    ROME");
+
+    buf.set_display_code_preview(None);
+    assert_eq!(buf.char_at(Position::new(2, 0)).ch, '@');
+}
+
+#[test]
+fn test_validate_display_codes() {
+    let mut buf = TextBuffer::new((10, 2));
+    buf.tags.push(display_tag("@USER@", "@USER@", 0, Alignment::Left));
+    buf.tags.push(display_tag("@CLS@", "@CLS@", 4, Alignment::Left));
+    buf.tags.push(display_tag("|UH", "|UH", 8, Alignment::Left));
+    let profile = profile(BbsDialect::PCBoard, &[("@USER@", "JOHN DOE"), ("@CLS@", "")]);
+
+    let issues = profile.validate(&buf);
+    assert!(issues.contains(&(0, DisplayCodeIssue::SampleTruncated { sample_len: 8, width: 6 })));
+    assert!(issues.contains(&(1, DisplayCodeIssue::Overlap(0))));
+    assert!(issues.contains(&(2, DisplayCodeIssue::OutOfBounds)));
+    assert!(issues.contains(&(2, DisplayCodeIssue::InvalidSyntax)));
+    assert!(!issues.iter().any(|(i, issue)| *i == 1 && *issue != DisplayCodeIssue::Overlap(0)));
+}
+
+#[test]
+fn test_wwiv_can_not_pad_in_text() {
+    let mut buf = TextBuffer::new((20, 2));
+    buf.tags.push(display_tag("|@N", "NAME______", 0, Alignment::Left));
+    let profile = profile(BbsDialect::Wwiv, &[("|@N", "JOE")]);
+    assert_eq!(profile.validate(&buf), vec![(0, DisplayCodeIssue::WidthNotSupported)]);
+    assert_eq!(profile.export_code(&buf.tags[0]), "|@N");
+}
+
+#[test]
+fn test_save_writes_dialect_widths() {
+    let mut buf = TextBuffer::new((20, 2));
+    for x in 0..20 {
+        buf.layers[0].set_char((x, 0), AttributedChar::new('.', TextAttribute::default()));
+    }
+    buf.tags.push(display_tag("@ALIAS@", "@ALIAS@___", 0, Alignment::Center));
+    buf.tags.push(display_tag("@CLS@", "@CLS@", 12, Alignment::Left));
+    let options = SaveOptions {
+        display_codes: Some(profile(BbsDialect::Synchronet, &[("@ALIAS@", "Joe"), ("@CLS@", "")])),
+        ..Default::default()
+    };
+
+    let bytes = FileFormat::PCBoard.to_bytes(&buf, &options).unwrap();
+    let text = String::from_utf8_lossy(&bytes);
+    assert!(text.contains("@ALIAS-C10@"), "{text}");
+    assert!(text.contains("@CLS@"), "{text}");
+
+    // The document itself keeps the codes as authored
+    assert_eq!(buf.tags[0].replacement_value, "@ALIAS@");
+}
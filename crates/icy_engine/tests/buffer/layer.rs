@@ -1,4 +1,4 @@
-use icy_engine::{AttributedChar, Layer, Line, TextAttribute, TextPane};
+use icy_engine::{AttributeColor, AttributedChar, Layer, Line, Mode, TextAttribute, TextBuffer, TextPane};
 
 #[test]
 fn test_get_char() {
@@ -46,6 +46,151 @@ fn test_insert_line() {
     layer.insert_line(11, Line::new());
     assert_eq!(12, layer.lines.len());
 }
+
+fn filled_layer(title: &str, ch: char, fg: u32, bg: u32) -> Layer {
+    let mut layer = Layer::new(title, (4, 1));
+    for x in 0..4 {
+        layer.set_char((x, 0), AttributedChar::new(ch, TextAttribute::new(fg, bg)));
+    }
+    layer
+}
+
+#[test]
+fn test_layer_groups() {
+    let mut buf = TextBuffer::new((4, 1));
+    buf.layers.push(filled_layer("a", 'a', 7, 0));
+    buf.layers.push(filled_layer("b", 'b', 7, 0));
+
+    let group = buf.group_layers(1..3, "group");
+    assert_eq!(3, group);
+    assert!(buf.layers[group].is_group());
+    assert_eq!(1..3, buf.group_children(group));
+    assert_eq!(Some(group), buf.parent_group(1));
+    assert_eq!(None, buf.parent_group(0));
+    assert_eq!('b', buf.char_at((0, 0).into()).ch);
+
+    // hiding and locking the group applies to its members
+    buf.layers[group].properties.is_visible = false;
+    assert!(!buf.is_layer_visible(2));
+    assert_ne!('b', buf.char_at((0, 0).into()).ch);
+    assert_ne!('a', buf.char_at((0, 0).into()).ch);
+    buf.layers[group].properties.is_visible = true;
+    buf.layers[group].properties.is_locked = true;
+    assert!(buf.is_layer_locked(1));
+    assert!(!buf.is_layer_locked(0));
+
+    // the group moves below layer 0 as one block
+    assert_eq!(Some(2), buf.lower_layer_block(group));
+    assert_eq!("a", buf.layers[0].title());
+    assert_eq!("b", buf.layers[1].title());
+    assert!(buf.layers[2].is_group());
+    assert_eq!(Some(3), buf.raise_layer_block(2));
+
+    buf.move_layer_block_offset(group, (1, 0).into());
+    assert_eq!(1, buf.layers[1].offset().x);
+    assert_eq!(1, buf.layers[2].offset().x);
+
+    assert!(buf.ungroup_layers(group).is_some());
+    assert_eq!(3, buf.layers.len());
+    assert_eq!(0, buf.layers[1].properties.depth);
+}
+
+#[test]
+fn test_layer_visibility_of_nested_groups() {
+    let mut buf = TextBuffer::new((4, 1));
+    assert_eq!(None, buf.layer_visibility());
+    buf.layers.push(filled_layer("a", 'a', 7, 0));
+    buf.layers.push(filled_layer("b", 'b', 7, 0));
+    let inner = buf.group_layers(2..3, "inner");
+    let outer = buf.group_layers(1..4, "outer");
+    assert_eq!(Some(vec![true; 5]), buf.layer_visibility());
+
+    buf.layers[inner].properties.is_visible = false;
+    assert_eq!(Some(vec![true, true, false, false, true]), buf.layer_visibility());
+    assert_eq!('a', buf.char_at((0, 0).into()).ch);
+
+    buf.layers[inner].properties.is_visible = true;
+    buf.layers[outer].properties.is_visible = false;
+    let visibility = buf.layer_visibility().unwrap();
+    assert_eq!(vec![true, false, false, false, false], visibility);
+    for (i, visible) in visibility.iter().enumerate() {
+        assert_eq!(*visible, buf.is_layer_visible(i));
+    }
+}
+
+#[test]
+fn test_cached_visibility_follows_layer_changes() {
+    let mut buf = TextBuffer::new((4, 1));
+    buf.layers.push(filled_layer("a", 'a', 7, 0));
+    let group = buf.group_layers(1..2, "group");
+    buf.layers[group].properties.is_visible = false;
+    assert_ne!('a', buf.char_at((0, 0).into()).ch);
+
+    // a clone keeps working with its own stack
+    let mut copy = buf.clone();
+    copy.layers[group].properties.is_visible = true;
+    assert_eq!('a', copy.char_at((0, 0).into()).ch);
+    assert_ne!('a', buf.char_at((0, 0).into()).ch);
+
+    // new layers and removed groups are picked up
+    buf.layers.push(filled_layer("b", 'b', 7, 0));
+    assert_eq!('b', buf.char_at((0, 0).into()).ch);
+    buf.layers.pop();
+    buf.ungroup_layers(group);
+    assert_eq!('a', buf.char_at((0, 0).into()).ch);
+}
+
+#[test]
+fn test_mask_layer_clips_group() {
+    let mut buf = TextBuffer::new((4, 1));
+    buf.layers[0] = filled_layer("base", '.', 7, 0);
+    let mut mask = Layer::new("mask", (4, 1));
+    mask.properties.mode = Mode::Mask;
+    mask.set_char((1, 0), AttributedChar::new('#', TextAttribute::default()));
+    buf.layers.push(mask);
+    buf.layers.push(filled_layer("top", 'x', 7, 0));
+    let group = buf.group_layers(1..3, "group");
+    // a layer above the group isn't clipped
+    buf.layers.push(Layer::new("above", (4, 1)));
+    buf.layers[group + 1].set_char((3, 0), AttributedChar::new('z', TextAttribute::default()));
+
+    assert_eq!('.', buf.char_at((0, 0).into()).ch);
+    assert_eq!('x', buf.char_at((1, 0).into()).ch);
+    assert_eq!('.', buf.char_at((2, 0).into()).ch);
+    assert_eq!('z', buf.char_at((3, 0).into()).ch);
+}
+
+#[test]
+fn test_color_blend_modes() {
+    let mut buf = TextBuffer::new((4, 1));
+    buf.layers[0] = filled_layer("base", 'a', 15, 1);
+
+    let mut fg_layer = filled_layer("fg", 'b', 4, 2);
+    fg_layer.properties.mode = Mode::Foreground;
+    buf.layers.push(fg_layer);
+    let ch = buf.char_at((0, 0).into());
+    assert_eq!('a', ch.ch);
+    assert_eq!(AttributeColor::Palette(4), ch.attribute.foreground_color());
+    assert_eq!(AttributeColor::Palette(1), ch.attribute.background_color());
+
+    buf.layers[1].properties.mode = Mode::Background;
+    let ch = buf.char_at((0, 0).into());
+    assert_eq!(AttributeColor::Palette(15), ch.attribute.foreground_color());
+    assert_eq!(AttributeColor::Palette(2), ch.attribute.background_color());
+
+    // white/blue below, red/green on top
+    buf.layers[1].properties.mode = Mode::Darken;
+    let ch = buf.char_at((0, 0).into());
+    assert_eq!('b', ch.ch);
+    assert_eq!(AttributeColor::Palette(4), ch.attribute.foreground_color());
+    assert_eq!(AttributeColor::Palette(1), ch.attribute.background_color());
+
+    buf.layers[1].properties.mode = Mode::Lighten;
+    let ch = buf.char_at((0, 0).into());
+    assert_eq!(AttributeColor::Palette(15), ch.attribute.foreground_color());
+    assert_eq!(AttributeColor::Palette(2), ch.attribute.background_color());
+}
+
 /*
 #[test]
 fn test_clipboard() {
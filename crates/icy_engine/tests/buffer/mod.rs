@@ -5,7 +5,9 @@ use icy_engine::{
 };
 use icy_sauce::MetaData as SauceMetaData;
 
+mod display_codes;
 mod layer;
+mod unicode_font;
 
 // FIXME: buffer.rs tests need to be updated to match current API
 // The tests reference deprecated APIs like caret.pos, caret.up(), get_char().unwrap()
@@ -0,0 +1,93 @@
+use icy_engine::{AttributedChar, BitFont, BufferType, CompactGlyph, PsfFont, TextAttribute, TextBuffer, UnicodeMapping};
+
+/// 8x8 PSF font with `count` glyphs, glyph n has n in its first row
+fn numbered_psf(count: usize) -> PsfFont {
+    let mut psf = PsfFont::new(8, 8, count);
+    for (i, glyph) in psf.glyphs.iter_mut().enumerate() {
+        glyph.data[0] = i as u8;
+        glyph.data[1] = (i >> 8) as u8;
+    }
+    psf
+}
+
+fn glyph_number(glyph: &CompactGlyph) -> usize {
+    glyph.data[0] as usize | (glyph.data[1] as usize) << 8
+}
+
+#[test]
+fn psf_unicode_table_fills_unicode_glyphs() {
+    let mut psf = numbered_psf(512);
+    psf.add_unicode_mapping(0x41, UnicodeMapping::Single('A' as u32));
+    psf.add_unicode_mapping(300, UnicodeMapping::Single('Ж' as u32));
+    psf.add_unicode_mapping(301, UnicodeMapping::Sequence(vec!['e' as u32, 0x301]));
+
+    let font = BitFont::from_bytes("uni", &psf.to_psf2_bytes()).unwrap();
+    assert!(font.has_unicode_glyphs());
+    assert_eq!(font.unicode_glyphs.len(), 2);
+    assert_eq!(glyph_number(font.unicode_glyph('Ж')), 300);
+    assert_eq!(glyph_number(font.unicode_glyph('A')), 0x41);
+    // Slots stay byte indexed
+    assert_eq!(glyph_number(font.glyph('\u{2C}')), 0x2C);
+}
+
+#[test]
+fn unicode_glyph_falls_back_to_cp437_slot() {
+    let font = BitFont::create_8("plain", 8, 8, &(0..=255u8).flat_map(|i| [i, 0, 0, 0, 0, 0, 0, 0]).collect::<Vec<_>>());
+    assert!(!font.has_unicode_glyphs());
+    assert_eq!(glyph_number(font.unicode_glyph('═')), 0xCD);
+    assert_eq!(glyph_number(font.unicode_glyph('é')), 0x82);
+    assert_eq!(glyph_number(font.unicode_glyph('A')), 0x41);
+}
+
+#[test]
+fn find_unicode_glyph_misses_unmapped_chars() {
+    let mut psf = numbered_psf(512);
+    psf.add_unicode_mapping(300, UnicodeMapping::Single('Ж' as u32));
+    let font = BitFont::from_bytes("uni", &psf.to_psf2_bytes()).unwrap();
+    assert_eq!(font.find_unicode_glyph('Ж').map(glyph_number), Some(300));
+    assert_eq!(font.find_unicode_glyph('═').map(glyph_number), Some(0xCD));
+    assert!(font.find_unicode_glyph('☃').is_none());
+}
+
+#[test]
+fn unicode_buffer_renders_unicode_glyphs() {
+    let mut psf = numbered_psf(512);
+    psf.add_unicode_mapping(400, UnicodeMapping::Single('☃' as u32));
+    let font = BitFont::from_bytes("uni", &psf.to_psf2_bytes()).unwrap();
+
+    let mut buf = TextBuffer::new((10, 2));
+    buf.set_font(0, font);
+    let snowman = AttributedChar::new('☃', TextAttribute::default());
+
+    buf.buffer_type = BufferType::Unicode;
+    assert_eq!(glyph_number(buf.glyph(&snowman).unwrap()), 400);
+
+    buf.buffer_type = BufferType::CP437;
+    assert_eq!(glyph_number(buf.glyph(&snowman).unwrap()), 0);
+}
+
+#[test]
+fn vga_512_font_loads_as_two_pages() {
+    let psf = numbered_psf(512);
+    let pages = BitFont::pages_from_bytes("vga", &psf.to_psf2_bytes()).unwrap();
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[0].name(), "vga (1)");
+    assert_eq!(glyph_number(&pages[0].glyphs()[5]), 5);
+    assert_eq!(glyph_number(&pages[1].glyphs()[5]), 261);
+
+    // Fonts with a Unicode table address all glyphs by character instead
+    let mut psf = numbered_psf(512);
+    psf.add_unicode_mapping(300, UnicodeMapping::Single('Ж' as u32));
+    assert_eq!(BitFont::pages_from_bytes("uni", &psf.to_psf2_bytes()).unwrap().len(), 1);
+}
+
+#[test]
+fn unicode_glyphs_are_scaled() {
+    let mut psf = numbered_psf(300);
+    psf.add_unicode_mapping(299, UnicodeMapping::Single('Ж' as u32));
+    let font = BitFont::from_bytes("uni", &psf.to_psf2_bytes()).unwrap();
+
+    let scaled = font.scale_to_height(16).unwrap();
+    assert_eq!(scaled.unicode_glyph('Ж').height, 16);
+    assert_eq!(scaled.unicode_glyph('Ж').data[..2], [font.unicode_glyph('Ж').data[0]; 2]);
+}
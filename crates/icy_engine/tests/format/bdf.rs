@@ -0,0 +1,154 @@
+use icy_engine::{BdfFont, BitFont, FontCodepage};
+
+const UNICODE_BDF: &str = "STARTFONT 2.1
+FONT -test-Tiny-Medium-R-Normal--4-40-75-75-C-40-ISO10646-1
+SIZE 4 75 75
+FONTBOUNDINGBOX 4 4 0 -1
+STARTPROPERTIES 4
+FAMILY_NAME \"Tiny\"
+FONT_ASCENT 3
+FONT_DESCENT 1
+CHARSET_REGISTRY \"ISO10646\"
+ENDPROPERTIES
+CHARS 2
+STARTCHAR A
+ENCODING 65
+SWIDTH 1000 0
+DWIDTH 4 0
+BBX 4 4 0 -1
+BITMAP
+60
+90
+F0
+90
+ENDCHAR
+STARTCHAR uni0416
+ENCODING 1046
+SWIDTH 1000 0
+DWIDTH 4 0
+BBX 3 2 1 0
+BITMAP
+A0
+40
+ENDCHAR
+ENDFONT
+";
+
+#[test]
+fn bdf_parse_places_glyphs_in_cell() {
+    let font = BdfFont::from_bytes(UNICODE_BDF.as_bytes()).unwrap();
+    assert_eq!(font.name.as_deref(), Some("Tiny"));
+    assert_eq!((font.width, font.height, font.ascent), (4, 4, 3));
+    assert!(font.unicode);
+    assert_eq!(font.glyphs.len(), 2);
+
+    let zhe = &font.glyphs[1];
+    assert_eq!(zhe.encoding, 0x416);
+    // 2 rows ending at the baseline, shifted one pixel right
+    assert_eq!(&zhe.glyph.data[..4], &[0x00, 0x50, 0x20, 0x00]);
+}
+
+#[test]
+fn bdf_maps_unicode_through_codepage() {
+    let cp437 = BitFont::from_bdf("tiny", UNICODE_BDF.as_bytes(), FontCodepage::Cp437).unwrap();
+    assert_eq!(&cp437.glyph('A').data[..4], &[0x60, 0x90, 0xF0, 0x90]);
+    assert!(cp437.glyphs().iter().skip(0x80).all(|g| g.data.iter().all(|&b| b == 0)));
+
+    let cp866 = BitFont::from_bdf("tiny", UNICODE_BDF.as_bytes(), FontCodepage::Cp866).unwrap();
+    assert_eq!(&cp866.glyphs()[0x86].data[..4], &[0x00, 0x50, 0x20, 0x00]);
+}
+
+#[test]
+fn bdf_roundtrip() {
+    let font = BitFont::default();
+    let bytes = font.to_bdf_bytes(FontCodepage::Cp437);
+    let text = String::from_utf8(bytes.clone()).unwrap();
+    assert!(text.contains("CHARSET_REGISTRY \"ISO10646\""));
+    // Slot 1 is ☺
+    assert!(text.contains("STARTCHAR uni263A\nENCODING 9786\n"));
+
+    let loaded = BitFont::from_bdf("roundtrip", &bytes, FontCodepage::Cp437).unwrap();
+    assert_eq!(loaded.size(), font.size());
+    for slot in 0..256 {
+        assert_eq!(loaded.glyphs()[slot].data, font.glyphs()[slot].data, "glyph {slot} differs");
+    }
+}
+
+#[test]
+fn bdf_rejects_overflowing_cell_height() {
+    let bdf = UNICODE_BDF.replace("FONT_ASCENT 3", "FONT_ASCENT 2147483647");
+    assert!(BdfFont::from_bytes(bdf.as_bytes()).is_err());
+}
+
+#[test]
+fn bdf_detected_by_from_bytes() {
+    let font = BitFont::from_bytes("tiny", UNICODE_BDF.as_bytes()).unwrap();
+    assert_eq!(font.size().height, 4);
+    assert_eq!(&font.glyph('A').data[..4], &[0x60, 0x90, 0xF0, 0x90]);
+}
+
+/// Minimal big endian PCF with one 4×4 glyph at 'A'
+fn tiny_pcf() -> Vec<u8> {
+    const FORMAT: u32 = 0x0C; // MSB byte and bit order, byte padding
+    let be16 = |v: i16| v.to_be_bytes();
+    let be32 = |v: i32| v.to_be_bytes();
+
+    let mut metrics = FORMAT.to_le_bytes().to_vec();
+    metrics.extend(be32(1));
+    for v in [0, 4, 4, 3, 1, 0] {
+        metrics.extend(be16(v));
+    }
+
+    let mut bitmaps = FORMAT.to_le_bytes().to_vec();
+    bitmaps.extend(be32(1));
+    bitmaps.extend(be32(0));
+    for size in [4, 8, 16, 16] {
+        bitmaps.extend(be32(size));
+    }
+    bitmaps.extend([0x60, 0x90, 0xF0, 0x90]);
+
+    let mut encodings = FORMAT.to_le_bytes().to_vec();
+    for v in [0x41, 0x41, 0, 0, 0] {
+        encodings.extend(be16(v));
+    }
+    encodings.extend(be16(0));
+
+    let tables = [(1 << 2, metrics), (1 << 3, bitmaps), (1 << 5, encodings)];
+    let mut offset = 8 + tables.len() * 16;
+    let mut data = b"\x01fcp".to_vec();
+    data.extend((tables.len() as u32).to_le_bytes());
+    for (kind, table) in &tables {
+        for v in [*kind, FORMAT, table.len() as u32, offset as u32] {
+            data.extend(v.to_le_bytes());
+        }
+        offset += table.len();
+    }
+    for (_, table) in &tables {
+        data.extend(table);
+    }
+    data
+}
+
+#[test]
+fn pcf_parse() {
+    let font = BdfFont::from_pcf_bytes(&tiny_pcf()).unwrap();
+    assert_eq!((font.width, font.height, font.ascent), (4, 4, 3));
+    assert!(!font.unicode);
+    assert_eq!(font.glyphs.len(), 1);
+    assert_eq!(font.glyphs[0].encoding, 0x41);
+    assert_eq!(&font.glyphs[0].glyph.data[..4], &[0x60, 0x90, 0xF0, 0x90]);
+
+    let bitfont = BitFont::from_bytes("tiny", &tiny_pcf()).unwrap();
+    assert_eq!(&bitfont.glyph('A').data[..4], &[0x60, 0x90, 0xF0, 0x90]);
+}
+
+#[test]
+fn bdf_export_keeps_glyphs_outside_codepage() {
+    let font = BitFont::from_bdf("tiny", UNICODE_BDF.as_bytes(), FontCodepage::Cp437).unwrap();
+    let text = String::from_utf8(font.to_bdf_bytes(FontCodepage::Cp437)).unwrap();
+    assert_eq!(text.matches("ENCODING 1046\n").count(), 1);
+    assert_eq!(text.matches("ENCODING 65\n").count(), 1);
+
+    let cp866 = BitFont::from_bytes_with_codepage("tiny", text.as_bytes(), FontCodepage::Cp866).unwrap();
+    assert_eq!(&cp866.glyphs()[0x86].data[..4], &[0x00, 0x50, 0x20, 0x00]);
+}
@@ -12,6 +12,10 @@ fn bitfont_format_from_extension() {
     assert_eq!(BitFontFormat::from_extension("psf"), Some(BitFontFormat::Psf));
     assert_eq!(BitFontFormat::from_extension("PSF"), Some(BitFontFormat::Psf));
 
+    // BDF / PCF
+    assert_eq!(BitFontFormat::from_extension("bdf"), Some(BitFontFormat::Bdf));
+    assert_eq!(BitFontFormat::from_extension("PCF"), Some(BitFontFormat::Pcf));
+
     // Raw formats
     assert_eq!(BitFontFormat::from_extension("f08"), Some(BitFontFormat::Raw(8)));
     assert_eq!(BitFontFormat::from_extension("f8"), Some(BitFontFormat::Raw(8)));
@@ -39,6 +43,8 @@ fn bitfont_format_from_path() {
 fn bitfont_format_extension() {
     assert_eq!(BitFontFormat::Yaff.extension(), "yaff");
     assert_eq!(BitFontFormat::Psf.extension(), "psf");
+    assert_eq!(BitFontFormat::Bdf.extension(), "bdf");
+    assert_eq!(BitFontFormat::Pcf.extension(), "pcf");
     assert_eq!(BitFontFormat::Raw(8).extension(), "f08");
     assert_eq!(BitFontFormat::Raw(14).extension(), "f14");
     assert_eq!(BitFontFormat::Raw(16).extension(), "f16");
@@ -854,3 +854,30 @@ fn test_invisisible_persistance_bug() {
     buf2.layers[0].properties.is_visible = true;
     buf2.layers[1].properties.is_visible = true;
 }
+
+#[test]
+fn test_layer_groups_and_modes_roundtrip() {
+    let mut buf = TextBuffer::new((4, 2));
+    buf.layers.push(Layer::new("mask", (4, 2)));
+    buf.layers[1].properties.mode = icy_engine::Mode::Mask;
+    buf.layers[1].set_char((0, 0), AttributedChar::new('#', TextAttribute::default()));
+    buf.layers.push(Layer::new("darken", (4, 2)));
+    buf.layers[2].properties.mode = icy_engine::Mode::Darken;
+    buf.layers[2].set_char((0, 0), AttributedChar::new('d', TextAttribute::new(4, 1)));
+    let group = buf.group_layers(1..3, "group");
+    buf.layers[group].properties.is_collapsed = true;
+    buf.layers[group].properties.is_locked = true;
+    buf.layers[group].properties.color = Some(Color::new(1, 2, 3));
+
+    let draw = FileFormat::IcyDraw;
+    let bytes = draw.to_bytes(&buf, &SaveOptions::default()).unwrap();
+    let buf2 = draw.from_bytes(&bytes, None).unwrap().screen.buffer;
+
+    assert_eq!(buf.layers.len(), buf2.layers.len());
+    for (a, b) in buf.layers.iter().zip(&buf2.layers) {
+        assert_eq!(a.role, b.role);
+        assert_eq!(a.properties, b.properties);
+    }
+    assert_eq!(1..3, buf2.group_children(group));
+    compare_buffers(&buf, &buf2, CompareOptions::ALL);
+}
@@ -4,7 +4,10 @@ mod artworx;
 
 mod avatar;
 
+mod bdf;
 mod bitfont_format;
+mod raster_fonts;
+mod truetype;
 mod file_format;
 mod image_format;
 
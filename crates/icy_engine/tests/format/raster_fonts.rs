@@ -0,0 +1,171 @@
+use icy_engine::{read_amiga_font_contents, AmigaFontEntry, BitFont};
+
+const A: [u8; 4] = [0x60, 0x90, 0xF0, 0x90];
+const B: [u8; 4] = [0xE0, 0x90, 0xE0, 0x90];
+
+/// Version 2 `.FNT` with 'A' and 'B' in a fixed 8×`height` cell
+fn tiny_fnt(height: u16) -> Vec<u8> {
+    let height_bytes = height as usize;
+    let table = 118;
+    let bitmaps = table + 2 * 4;
+    let face = bitmaps + 2 * height_bytes;
+
+    let mut data = vec![0u8; face];
+    data[0..2].copy_from_slice(&0x0200u16.to_le_bytes());
+    data[86..88].copy_from_slice(&8u16.to_le_bytes());
+    data[88..90].copy_from_slice(&height.to_le_bytes());
+    data[93..95].copy_from_slice(&8u16.to_le_bytes());
+    data[95] = b'A';
+    data[96] = b'B';
+    data[105..109].copy_from_slice(&(face as u32).to_le_bytes());
+    for (i, rows) in [A, B].iter().enumerate() {
+        let offset = bitmaps + i * height_bytes;
+        data[table + i * 4..table + i * 4 + 2].copy_from_slice(&8u16.to_le_bytes());
+        data[table + i * 4 + 2..table + i * 4 + 4].copy_from_slice(&(offset as u16).to_le_bytes());
+        data[offset..offset + 4].copy_from_slice(rows);
+    }
+    data.extend(b"Tiny\0");
+    data
+}
+
+/// NE executable with one `RT_FONT` resource per font, resources aligned to 16 bytes
+fn tiny_fon(fonts: &[Vec<u8>]) -> Vec<u8> {
+    const NE: usize = 0x40;
+    const RES_TABLE: usize = 0x40;
+    let table_len = 2 + 8 + fonts.len() * 12 + 2;
+    let mut offset = (NE + RES_TABLE + table_len).next_multiple_of(16);
+
+    let mut data = vec![0u8; NE + RES_TABLE];
+    data[0..2].copy_from_slice(b"MZ");
+    data[0x3C..0x40].copy_from_slice(&(NE as u32).to_le_bytes());
+    data[NE..NE + 2].copy_from_slice(b"NE");
+    data[NE + 0x24..NE + 0x26].copy_from_slice(&(RES_TABLE as u16).to_le_bytes());
+
+    data.extend(4u16.to_le_bytes());
+    data.extend(0x8008u16.to_le_bytes());
+    data.extend((fonts.len() as u16).to_le_bytes());
+    data.extend([0; 4]);
+    for font in fonts {
+        data.extend(((offset >> 4) as u16).to_le_bytes());
+        data.extend((font.len().div_ceil(16) as u16).to_le_bytes());
+        data.extend([0; 8]);
+        offset += font.len().next_multiple_of(16);
+    }
+    data.extend(0u16.to_le_bytes());
+    for font in fonts {
+        data.resize(data.len().next_multiple_of(16), 0);
+        data.extend(font);
+    }
+    data
+}
+
+#[test]
+fn fnt_parse() {
+    let fonts = BitFont::from_windows_font(&tiny_fnt(4)).unwrap();
+    assert_eq!(fonts.len(), 1);
+    let font = &fonts[0];
+    assert_eq!(font.name(), "Tiny 4");
+    assert_eq!((font.size().width, font.size().height), (8, 4));
+    assert_eq!(&font.glyph('A').data[..4], &A);
+    assert_eq!(&font.glyph('B').data[..4], &B);
+    assert!(font.glyph('C').data.iter().all(|&b| b == 0));
+
+    // ANSI fonts also provide their glyphs by character
+    assert!(font.has_unicode_glyphs());
+    assert_eq!(&font.unicode_glyph('B').data[..4], &B);
+}
+
+#[test]
+fn fon_loads_all_sizes_sorted() {
+    let fonts = BitFont::from_windows_font(&tiny_fon(&[tiny_fnt(6), tiny_fnt(4)])).unwrap();
+    assert_eq!(fonts.iter().map(|f| f.size().height).collect::<Vec<_>>(), vec![4, 6]);
+    assert_eq!(fonts[1].name(), "Tiny 6");
+    assert_eq!(&fonts[1].glyph('A').data[..6], &[0x60, 0x90, 0xF0, 0x90, 0, 0]);
+}
+
+#[test]
+fn fon_rejects_pe() {
+    let mut data = tiny_fon(&[tiny_fnt(4)]);
+    data[0x40..0x42].copy_from_slice(b"PE");
+    assert!(BitFont::from_windows_font(&data).is_err());
+}
+
+/// Amiga size file with 'A' and 'B' in 4×4, `kerns` makes it proportional
+fn tiny_amiga_font(kerns: Option<[i16; 2]>) -> Vec<u8> {
+    const TEXT_FONT: usize = 58;
+    let char_loc = 112;
+    let char_data = 120;
+    let char_kern = 124;
+
+    let mut hunk = vec![0u8; 128];
+    hunk[18..20].copy_from_slice(&0x0F80u16.to_be_bytes());
+    hunk[26..35].copy_from_slice(b"tiny.font");
+    hunk[TEXT_FONT + 20..TEXT_FONT + 22].copy_from_slice(&4u16.to_be_bytes());
+    hunk[TEXT_FONT + 24..TEXT_FONT + 26].copy_from_slice(&4u16.to_be_bytes());
+    hunk[TEXT_FONT + 32] = b'A';
+    hunk[TEXT_FONT + 33] = b'B';
+    hunk[TEXT_FONT + 34..TEXT_FONT + 38].copy_from_slice(&(char_data as u32).to_be_bytes());
+    hunk[TEXT_FONT + 38..TEXT_FONT + 40].copy_from_slice(&1u16.to_be_bytes());
+    hunk[TEXT_FONT + 40..TEXT_FONT + 44].copy_from_slice(&(char_loc as u32).to_be_bytes());
+    for (i, bit_offset) in [0u16, 4].iter().enumerate() {
+        hunk[char_loc + i * 4..char_loc + i * 4 + 2].copy_from_slice(&bit_offset.to_be_bytes());
+        hunk[char_loc + i * 4 + 2..char_loc + i * 4 + 4].copy_from_slice(&4u16.to_be_bytes());
+    }
+    // 'A' in the high, 'B' in the low nibble
+    for y in 0..4 {
+        hunk[char_data + y] = A[y] | (B[y] >> 4);
+    }
+    if let Some(kerns) = kerns {
+        hunk[TEXT_FONT + 23] = 0x20;
+        hunk[TEXT_FONT + 48..TEXT_FONT + 52].copy_from_slice(&(char_kern as u32).to_be_bytes());
+        for (i, kern) in kerns.iter().enumerate() {
+            hunk[char_kern + i * 2..char_kern + i * 2 + 2].copy_from_slice(&kern.to_be_bytes());
+        }
+    }
+
+    let longs = (hunk.len() / 4) as u32;
+    let mut data = Vec::new();
+    for v in [0x3F3, 0, 1, 0, 0, longs, 0x3E9, longs] {
+        data.extend(u32::to_be_bytes(v));
+    }
+    data.extend(hunk);
+    data.extend(0x3F2u32.to_be_bytes());
+    data
+}
+
+#[test]
+fn amiga_font_parse() {
+    let font = BitFont::from_amiga_font(&tiny_amiga_font(None)).unwrap();
+    assert_eq!(font.name(), "tiny 4");
+    assert_eq!((font.size().width, font.size().height), (4, 4));
+    assert_eq!(&font.glyph('A').data[..4], &A);
+    assert_eq!(&font.glyph('B').data[..4], &B);
+    assert_eq!(&font.unicode_glyph('A').data[..4], &A);
+}
+
+#[test]
+fn amiga_proportional_font_uses_kerning() {
+    let font = BitFont::from_amiga_font(&tiny_amiga_font(Some([1, 0]))).unwrap();
+    assert_eq!(font.size().width, 5);
+    assert_eq!(&font.glyph('A').data[..4], &[0x30, 0x48, 0x78, 0x48]);
+    assert_eq!(&font.glyph('B').data[..4], &B);
+}
+
+#[test]
+fn amiga_font_contents() {
+    let mut data = vec![0x0F, 0x00, 0, 1];
+    let mut name = b"tiny/4".to_vec();
+    name.resize(256, 0);
+    data.extend(name);
+    data.extend([0, 4, 0, 0]);
+
+    let entries = read_amiga_font_contents(&data).unwrap();
+    assert_eq!(
+        entries,
+        vec![AmigaFontEntry {
+            file_name: "tiny/4".to_string(),
+            y_size: 4
+        }]
+    );
+    assert!(read_amiga_font_contents(&[0x12, 0x34, 0, 0]).is_err());
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use icy_engine::{BitFont, CompactGlyph, FontCodepage, TtfExportOptions};
+
+fn be16(data: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([data[offset], data[offset + 1]])
+}
+
+fn be32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+fn table<'a>(font: &'a [u8], tag: &[u8; 4]) -> Option<&'a [u8]> {
+    (0..be16(font, 4) as usize)
+        .map(|i| 12 + i * 16)
+        .find(|&entry| &font[entry..entry + 4] == tag)
+        .map(|entry| {
+            let (offset, length) = (be32(font, entry + 8) as usize, be32(font, entry + 12) as usize);
+            &font[offset..offset + length]
+        })
+}
+
+/// Glyph id of `ch` through the format 4 cmap subtable
+fn glyph_id(font: &[u8], ch: char) -> u16 {
+    let cmap = table(font, b"cmap").unwrap();
+    let sub = &cmap[be32(cmap, 8) as usize..];
+    assert_eq!(be16(sub, 0), 4);
+    let seg_count = be16(sub, 6) as usize / 2;
+    let cp = ch as u16;
+    for seg in 0..seg_count {
+        let end = be16(sub, 14 + seg * 2);
+        let start = be16(sub, 16 + seg_count * 2 + seg * 2);
+        let delta = be16(sub, 16 + seg_count * 4 + seg * 2);
+        if (start..=end).contains(&cp) {
+            return cp.wrapping_add(delta);
+        }
+    }
+    0
+}
+
+/// Contour count and points of a glyph
+fn glyph_outline(font: &[u8], gid: u16) -> (i16, usize) {
+    let loca = table(font, b"loca").unwrap();
+    let glyf = table(font, b"glyf").unwrap();
+    let (start, end) = (be32(loca, gid as usize * 4) as usize, be32(loca, gid as usize * 4 + 4) as usize);
+    if start == end {
+        return (0, 0);
+    }
+    let contours = be16(glyf, start) as i16;
+    let points = be16(glyf, start + 10 + (contours as usize - 1) * 2) as usize + 1;
+    (contours, points)
+}
+
+/// Font with a solid block at 'A', a ring at 'O' and a full cell at slot 0xC4 (─)
+fn test_font() -> BitFont {
+    let mut font = BitFont::create_8("Test Font", 8, 8, &[0; 256 * 8]);
+    for y in 2..6 {
+        font.glyph_mut('A').data[y] = 0x3C;
+    }
+    font.glyph_mut('O').data[..5].copy_from_slice(&[0xF0, 0x90, 0x90, 0x90, 0xF0]);
+    font.glyph_mut('\u{C4}').data[3] = 0xFF;
+    font
+}
+
+#[test]
+fn ttf_structure() {
+    let data = test_font().to_ttf_bytes(&TtfExportOptions::default()).unwrap();
+    assert_eq!(be32(&data, 0), 0x0001_0000);
+
+    let tags: Vec<&[u8]> = (0..be16(&data, 4) as usize).map(|i| &data[12 + i * 16..16 + i * 16]).collect();
+    assert!(tags.windows(2).all(|w| w[0] < w[1]), "tables must be sorted");
+    for tag in [b"OS/2", b"cmap", b"glyf", b"head", b"hhea", b"hmtx", b"loca", b"maxp", b"name", b"post"] {
+        assert!(table(&data, tag).is_some(), "missing {}", String::from_utf8_lossy(tag));
+    }
+    assert!(table(&data, b"EBLC").is_none());
+
+    // The whole file sums up to the magic number through head.checkSumAdjustment
+    let sum = data
+        .chunks(4)
+        .fold(0u32, |sum, c| sum.wrapping_add(u32::from_be_bytes([c[0], c[1], c[2], c[3]])));
+    assert_eq!(sum, 0xB1B0_AFBA);
+
+    let head = table(&data, b"head").unwrap();
+    assert_eq!(be16(head, 18), 8 * 128);
+    let name = table(&data, b"name").unwrap();
+    let family: Vec<u16> = "Test Font".encode_utf16().collect();
+    let name_utf16: Vec<u16> = name.chunks(2).map(|c| u16::from_be_bytes([c[0], c[1]])).collect();
+    assert!(name_utf16.windows(family.len()).any(|w| w == family.as_slice()));
+}
+
+#[test]
+fn ttf_outlines_merge_pixels() {
+    let data = test_font().to_ttf_bytes(&TtfExportOptions::default()).unwrap();
+    // 4×4 block is a single square
+    assert_eq!(glyph_outline(&data, glyph_id(&data, 'A')), (1, 4));
+    // Ring has an outer and an inner contour
+    assert_eq!(glyph_outline(&data, glyph_id(&data, 'O')), (2, 8));
+    // Empty glyphs have no outline but are mapped
+    let space = glyph_id(&data, ' ');
+    assert_ne!(space, 0);
+    assert_eq!(glyph_outline(&data, space), (0, 0));
+}
+
+#[test]
+fn ttf_maps_slots_through_codepage() {
+    let cp437 = test_font().to_ttf_bytes(&TtfExportOptions::default()).unwrap();
+    assert_eq!(glyph_outline(&cp437, glyph_id(&cp437, '─')), (1, 4));
+
+    let latin1 = test_font()
+        .to_ttf_bytes(&TtfExportOptions {
+            codepage: FontCodepage::Latin1,
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(glyph_outline(&latin1, glyph_id(&latin1, 'Ä')), (1, 4));
+    assert_eq!(glyph_id(&latin1, '─'), 0);
+}
+
+#[test]
+fn ttf_nine_dot_cells() {
+    let data = test_font()
+        .to_ttf_bytes(&TtfExportOptions {
+            nine_dot: true,
+            ..Default::default()
+        })
+        .unwrap();
+    let hmtx = table(&data, b"hmtx").unwrap();
+    let gid = glyph_id(&data, '─') as usize;
+    assert_eq!(be16(hmtx, gid * 4), 9 * 128);
+
+    // Line graphics reach into the 9th column
+    let loca = table(&data, b"loca").unwrap();
+    let glyf = table(&data, b"glyf").unwrap();
+    let x_max = be16(glyf, be32(loca, gid * 4) as usize + 6);
+    assert_eq!(x_max, 9 * 128);
+    let gid_a = glyph_id(&data, 'A') as usize;
+    assert_eq!(be16(glyf, be32(loca, gid_a * 4) as usize + 6), 6 * 128);
+}
+
+#[test]
+fn ttf_bitmap_strike() {
+    let data = test_font()
+        .to_ttf_bytes(&TtfExportOptions {
+            bitmap_strike: true,
+            ..Default::default()
+        })
+        .unwrap();
+    let eblc = table(&data, b"EBLC").unwrap();
+    let ebdt = table(&data, b"EBDT").unwrap();
+    assert_eq!(be32(eblc, 4), 1);
+    // ppem of the strike is the font height
+    assert_eq!((eblc[8 + 44], eblc[8 + 45]), (8, 8));
+
+    // Glyph images are 8×8 bits, stored from glyph 1 on
+    let gid = glyph_id(&data, 'A') as usize;
+    let image = &ebdt[4 + (gid - 1) * 8..4 + gid * 8];
+    assert_eq!(image, &[0, 0, 0x3C, 0x3C, 0x3C, 0x3C, 0, 0]);
+}
+
+#[test]
+fn ttf_rejects_too_many_glyphs() {
+    let mut font = test_font();
+    let glyphs: HashMap<char, CompactGlyph> = (0x1_0000..0x2_0000)
+        .filter_map(char::from_u32)
+        .map(|ch| (ch, CompactGlyph::default()))
+        .collect();
+    font.set_unicode_glyphs(glyphs);
+    assert!(font.to_ttf_bytes(&TtfExportOptions::default()).is_err());
+}
+
+#[test]
+fn ttf_rejects_too_many_character_ranges() {
+    let mut font = test_font();
+    // Consecutive characters share a cmap segment
+    let glyphs: HashMap<char, CompactGlyph> = (0x4E00..0x7000).filter_map(char::from_u32).map(|ch| (ch, CompactGlyph::default())).collect();
+    font.set_unicode_glyphs(glyphs);
+    assert!(font.to_ttf_bytes(&TtfExportOptions::default()).is_ok());
+
+    // Every other character needs a segment of its own
+    let glyphs: HashMap<char, CompactGlyph> = (0x4E00..0xA000)
+        .step_by(2)
+        .filter_map(char::from_u32)
+        .map(|ch| (ch, CompactGlyph::default()))
+        .collect();
+    font.set_unicode_glyphs(glyphs);
+    assert!(font.to_ttf_bytes(&TtfExportOptions::default()).is_err());
+}
@@ -3,6 +3,8 @@ undo-remove_layer=Remove layer
 undo-raise_layer=Raise layer
 undo-lower_layer=Lower layer
 undo-merge_down_layer=Merge down
+undo-group_layer=Group layer
+undo-ungroup_layer=Ungroup layer
 undo-toggle_layer_visibility=Layer visibility
 undo-set_char=Character
 undo-delete-selection=Delete selection
@@ -29,11 +31,25 @@ undo-scroll_layer_down=Scroll Layer down
 undo-stamp-down=Stamp down
 undo-make_transparent=Make transparent
 undo-rotate_layer=Rotate layer
+undo-rotate_cw=Rotate clockwise
+undo-rotate_ccw=Rotate counter clockwise
+undo-rotate_180=Rotate 180°
+undo-scale=Scale
+undo-gradient_fill=Gradient fill
+undo-pattern_fill=Pattern fill
 undo-clear_layer=Clear layer
 undo-deselect=Deselect
 undo-select-nothing=Select nothing
 undo-set_selection=Selection
 undo-inverse_selection=Inverse selection
+undo-magic_wand=Magic wand
+undo-select_color=Select color
+undo-grow_selection=Grow selection
+undo-shrink_selection=Shrink selection
+undo-border_selection=Border selection
+undo-find_replace=Find and replace
+undo-remap_colors=Remap colors
+undo-text_box=Text box
 undo-switch_palette=Set Palette
 undo-change_sauce=Change SAUCE
 undo-switch_font_page=Set font page
@@ -59,5 +75,6 @@ undo-anchor=Anchor
 
 layer-duplicate-name={ $name } copy
 layer-new-name=Layer
+layer-group-name=Group
 layer-pasted-name=Floating selection
 layer-anchor=Anchor layer
\ No newline at end of file
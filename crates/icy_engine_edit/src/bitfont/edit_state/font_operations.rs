@@ -6,6 +6,9 @@
 //! - Insert/delete columns
 //! - Duplicate line
 //! - Swap characters
+//! - Change codepage
+
+use icy_engine::FontCodepage;
 
 use crate::bitfont::BitFontUndoOp;
 use crate::Result;
@@ -39,6 +42,23 @@ impl BitFontEditState {
         self.push_undo_action(op)
     }
 
+    /// Change the codepage that maps the 256 slots to Unicode
+    ///
+    /// If the font carries Unicode glyphs, the slots are refilled for the new
+    /// codepage: edited slots keep their glyph when the character is still in
+    /// the codepage, the others come from the loaded Unicode glyphs.
+    pub fn set_codepage(&mut self, codepage: FontCodepage) -> Result<()> {
+        if codepage == self.codepage {
+            return Ok(());
+        }
+        let op = BitFontUndoOp::ChangeCodepage {
+            old_codepage: self.codepage,
+            new_codepage: codepage,
+            old_glyph_data: self.glyph_data.clone(),
+        };
+        self.push_undo_action(op)
+    }
+
     // ═══════════════════════════════════════════════════════════════════════
     // Line Operations (affect all glyphs)
     // ═══════════════════════════════════════════════════════════════════════
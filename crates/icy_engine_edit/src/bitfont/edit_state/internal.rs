@@ -4,7 +4,9 @@
 //! without creating new undo entries. They should not be called directly
 //! by user code.
 
-use icy_engine::{Position, Selection};
+use std::collections::HashMap;
+
+use icy_engine::{FontCodepage, Position, Selection};
 
 use super::BitFontEditState;
 
@@ -89,6 +91,35 @@ impl BitFontEditState {
         self.is_dirty = true;
     }
 
+    /// Set the codepage without touching the glyphs (internal, no undo)
+    pub(crate) fn set_codepage_internal(&mut self, codepage: FontCodepage) {
+        self.codepage = codepage;
+        self.is_dirty = true;
+    }
+
+    /// Refill the slots for another codepage (internal, no undo)
+    pub(crate) fn remap_codepage_internal(&mut self, codepage: FontCodepage) {
+        if !self.unicode_glyphs.is_empty() {
+            let mut old_slots = HashMap::new();
+            for slot in 0..=255u8 {
+                old_slots.entry(self.codepage.to_unicode(slot)).or_insert(slot as usize);
+            }
+            self.glyph_data = (0..=255u8)
+                .map(|slot| {
+                    let ch = codepage.to_unicode(slot);
+                    if let Some(&old) = old_slots.get(&ch) {
+                        self.glyph_data[old].clone()
+                    } else if let Some(glyph) = self.unicode_glyphs.get(&ch) {
+                        Self::glyph_pixels(glyph, self.font_width, self.font_height)
+                    } else {
+                        vec![vec![false; self.font_width as usize]; self.font_height as usize]
+                    }
+                })
+                .collect();
+        }
+        self.set_codepage_internal(codepage);
+    }
+
     /// Resize all glyphs (internal, no undo)
     pub(crate) fn resize_glyphs_internal(&mut self, new_width: i32, new_height: i32) {
         for glyph in &mut self.glyph_data {
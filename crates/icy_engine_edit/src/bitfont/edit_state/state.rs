@@ -74,9 +74,9 @@
 //! - `undo.rs` - Undo/redo system
 //! - `internal.rs` - Internal setters for undo operations
 
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
-use icy_engine::{BitFont, Position, Selection};
+use icy_engine::{BitFont, CompactGlyph, FontCodepage, Position, Selection};
 
 use crate::bitfont::undo_stack::BitFontUndoStack;
 use crate::Result;
@@ -114,6 +114,13 @@ pub struct BitFontEditState {
     /// Font name
     pub(crate) font_name: String,
 
+    /// Codepage that maps the 256 slots to Unicode (BDF/PCF/TTF)
+    pub(crate) codepage: FontCodepage,
+
+    /// Unicode glyphs of the loaded font, as loaded. The edited slots are laid
+    /// over them by `build_font`, so glyphs outside the codepage survive a save.
+    pub(crate) unicode_glyphs: Arc<HashMap<char, CompactGlyph>>,
+
     // ═══════════════════════════════════════════════════════════════════════
     // Selection & Cursor
     // ═══════════════════════════════════════════════════════════════════════
@@ -184,6 +191,11 @@ impl BitFontEditState {
 
     /// Create a BitFontEditState from an existing BitFont
     pub fn from_font(font: BitFont) -> Self {
+        Self::from_font_with_codepage(font, FontCodepage::Cp437)
+    }
+
+    /// Create a BitFontEditState from a BitFont whose slots follow `codepage`
+    pub fn from_font_with_codepage(font: BitFont, codepage: FontCodepage) -> Self {
         let size = font.size();
         let glyph_data = Self::extract_glyph_data(&font, size.width, size.height);
         let font_name = font.name().to_string();
@@ -193,6 +205,8 @@ impl BitFontEditState {
             font_width: size.width,
             font_height: size.height,
             font_name,
+            codepage,
+            unicode_glyphs: font.unicode_glyphs.clone(),
             selected_char: 'A',
             cursor_pos: (0, 0),
             edit_selection: None,
@@ -223,26 +237,27 @@ impl BitFontEditState {
 
         for ch_code in 0..256u32 {
             let ch = char::from_u32(ch_code).unwrap_or(' ');
-            let mut pixels = vec![vec![false; width as usize]; height as usize];
+            glyphs.push(Self::glyph_pixels(font.glyph(ch), width, height));
+        }
 
-            let glyph = font.glyph(ch);
-            let bitmap_pixels = glyph.to_bitmap_pixels();
-            for (y, row) in bitmap_pixels.iter().enumerate() {
-                if y >= height as usize {
+        glyphs
+    }
+
+    /// Convert a glyph to pixel rows, cropped or padded to `width` × `height`
+    pub(crate) fn glyph_pixels(glyph: &CompactGlyph, width: i32, height: i32) -> Vec<Vec<bool>> {
+        let mut pixels = vec![vec![false; width as usize]; height as usize];
+        for (y, row) in glyph.to_bitmap_pixels().iter().enumerate() {
+            if y >= height as usize {
+                break;
+            }
+            for (x, &pixel) in row.iter().enumerate() {
+                if x >= width as usize {
                     break;
                 }
-                for (x, &pixel) in row.iter().enumerate() {
-                    if x >= width as usize {
-                        break;
-                    }
-                    pixels[y][x] = pixel;
-                }
+                pixels[y][x] = pixel;
             }
-
-            glyphs.push(pixels);
         }
-
-        glyphs
+        pixels
     }
 
     // ═══════════════════════════════════════════════════════════════════════
@@ -269,6 +284,16 @@ impl BitFontEditState {
         &self.font_name
     }
 
+    /// Get the codepage that maps the slots to Unicode
+    pub fn codepage(&self) -> FontCodepage {
+        self.codepage
+    }
+
+    /// Whether the font carries Unicode glyphs beyond the 256 slots
+    pub fn has_unicode_glyphs(&self) -> bool {
+        !self.unicode_glyphs.is_empty()
+    }
+
     /// Get currently selected character
     pub fn selected_char(&self) -> char {
         self.selected_char
@@ -364,6 +389,24 @@ impl BitFontEditState {
         }
 
         // Create font from raw data
-        BitFont::create_8(self.font_name.clone(), self.font_width as u8, self.font_height as u8, &raw_data)
+        let mut font = BitFont::create_8(self.font_name.clone(), self.font_width as u8, self.font_height as u8, &raw_data);
+
+        // Lay the edited slots over the loaded Unicode glyphs
+        if !self.unicode_glyphs.is_empty() {
+            let (width, height) = (self.font_width as usize, self.font_height as usize);
+            let mut unicode_glyphs: HashMap<char, CompactGlyph> = self
+                .unicode_glyphs
+                .iter()
+                .map(|(&ch, glyph)| {
+                    let pixels = Self::glyph_pixels(glyph, self.font_width, self.font_height);
+                    (ch, CompactGlyph::from_bitmap_pixels(&pixels, width, height))
+                })
+                .collect();
+            for (slot, glyph) in font.glyphs().iter().enumerate() {
+                unicode_glyphs.insert(self.codepage.to_unicode(slot as u8), *glyph);
+            }
+            font.set_unicode_glyphs(unicode_glyphs);
+        }
+        font
     }
 }
@@ -2,7 +2,7 @@
 //!
 //! Contains the serializable enum-based undo operation type.
 
-use icy_engine::{Position, Selection};
+use icy_engine::{FontCodepage, Position, Selection};
 use serde::{Deserialize, Serialize};
 
 use crate::bitfont::BitFontEditState;
@@ -122,6 +122,13 @@ pub enum BitFontUndoOp {
         old_glyph_data: Vec<Vec<Vec<bool>>>,
     },
 
+    /// Change the codepage that maps the slots to Unicode
+    ChangeCodepage {
+        old_codepage: FontCodepage,
+        new_codepage: FontCodepage,
+        old_glyph_data: Vec<Vec<Vec<bool>>>,
+    },
+
     /// Selection change (edit and charset selection, cursor positions)
     SelectionChange {
         old_edit_selection: Option<Selection>,
@@ -179,6 +186,7 @@ impl BitFontUndoOp {
                 _ => "Move glyph".to_string(),
             },
             BitFontUndoOp::ResizeFont { .. } => "Resize font".to_string(),
+            BitFontUndoOp::ChangeCodepage { .. } => "Change codepage".to_string(),
             BitFontUndoOp::SelectionChange { .. } => "Selection change".to_string(),
             BitFontUndoOp::SwapChars { .. } => "Swap characters".to_string(),
         }
@@ -201,6 +209,7 @@ impl BitFontUndoOp {
             BitFontUndoOp::InverseSelection { .. } => BitFontOperationType::Transform,
             BitFontUndoOp::MoveGlyph { .. } => BitFontOperationType::Transform,
             BitFontUndoOp::ResizeFont { .. } => BitFontOperationType::Resize,
+            BitFontUndoOp::ChangeCodepage { .. } => BitFontOperationType::Unknown,
             BitFontUndoOp::SelectionChange { .. } => BitFontOperationType::Unknown,
             BitFontUndoOp::SwapChars { .. } => BitFontOperationType::Transform,
         }
@@ -343,6 +352,18 @@ impl BitFontUndoOp {
                 Ok(())
             }
 
+            BitFontUndoOp::ChangeCodepage {
+                old_codepage, old_glyph_data, ..
+            } => {
+                state.set_codepage_internal(*old_codepage);
+                for (i, glyph_data) in old_glyph_data.iter().enumerate() {
+                    if let Some(ch) = char::from_u32(i as u32) {
+                        state.set_glyph_pixels_internal(ch, glyph_data.clone());
+                    }
+                }
+                Ok(())
+            }
+
             BitFontUndoOp::SelectionChange {
                 old_edit_selection,
                 old_charset_selection,
@@ -460,6 +481,11 @@ impl BitFontUndoOp {
                 Ok(())
             }
 
+            BitFontUndoOp::ChangeCodepage { new_codepage, .. } => {
+                state.remap_codepage_internal(*new_codepage);
+                Ok(())
+            }
+
             BitFontUndoOp::SelectionChange {
                 new_edit_selection,
                 new_charset_selection,
@@ -0,0 +1,413 @@
+//! Box drawing aware lines
+//!
+//! Every line glyph of a charset is described by its four arms (north, east, south, west) and
+//! their weight. Drawing merges the arms of the stroke with the lines already in and next to
+//! each cell, so crossings and T-junctions get the matching junction glyph. Erasing removes the
+//! arms pointing into the erased cells and repairs the junctions around them.
+
+use std::collections::{HashMap, HashSet};
+
+use icy_engine::{AttributedChar, BufferType, Position, TextAttribute};
+
+use super::{get_line_points, DrawTarget, PointRole};
+
+/// Weight of a single arm of a box drawing glyph
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LineWeight {
+    #[default]
+    None,
+    Single,
+    Double,
+}
+
+/// The arms of a box drawing glyph
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BoxArms {
+    pub north: LineWeight,
+    pub east: LineWeight,
+    pub south: LineWeight,
+    pub west: LineWeight,
+}
+
+impl BoxArms {
+    pub const fn new(north: LineWeight, east: LineWeight, south: LineWeight, west: LineWeight) -> Self {
+        Self { north, east, south, west }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn arm(&self, direction: Direction) -> LineWeight {
+        match direction {
+            Direction::North => self.north,
+            Direction::East => self.east,
+            Direction::South => self.south,
+            Direction::West => self.west,
+        }
+    }
+
+    fn arm_mut(&mut self, direction: Direction) -> &mut LineWeight {
+        match direction {
+            Direction::North => &mut self.north,
+            Direction::East => &mut self.east,
+            Direction::South => &mut self.south,
+            Direction::West => &mut self.west,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [Direction::North, Direction::East, Direction::South, Direction::West];
+
+    fn offset(self) -> Position {
+        match self {
+            Direction::North => Position::new(0, -1),
+            Direction::East => Position::new(1, 0),
+            Direction::South => Position::new(0, 1),
+            Direction::West => Position::new(-1, 0),
+        }
+    }
+
+    fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+
+    fn is_horizontal(self) -> bool {
+        matches!(self, Direction::East | Direction::West)
+    }
+}
+
+const O: LineWeight = LineWeight::None;
+const S: LineWeight = LineWeight::Single;
+const D: LineWeight = LineWeight::Double;
+
+/// CP437 line glyphs, horizontal and vertical arms always share their weight
+const CP437_BOX_GLYPHS: [(u8, BoxArms); 40] = [
+    (0xC4, BoxArms::new(O, S, O, S)), // ─
+    (0xB3, BoxArms::new(S, O, S, O)), // │
+    (0xDA, BoxArms::new(O, S, S, O)), // ┌
+    (0xBF, BoxArms::new(O, O, S, S)), // ┐
+    (0xC0, BoxArms::new(S, S, O, O)), // └
+    (0xD9, BoxArms::new(S, O, O, S)), // ┘
+    (0xC3, BoxArms::new(S, S, S, O)), // ├
+    (0xB4, BoxArms::new(S, O, S, S)), // ┤
+    (0xC2, BoxArms::new(O, S, S, S)), // ┬
+    (0xC1, BoxArms::new(S, S, O, S)), // ┴
+    (0xC5, BoxArms::new(S, S, S, S)), // ┼
+    (0xCD, BoxArms::new(O, D, O, D)), // ═
+    (0xBA, BoxArms::new(D, O, D, O)), // ║
+    (0xC9, BoxArms::new(O, D, D, O)), // ╔
+    (0xBB, BoxArms::new(O, O, D, D)), // ╗
+    (0xC8, BoxArms::new(D, D, O, O)), // ╚
+    (0xBC, BoxArms::new(D, O, O, D)), // ╝
+    (0xCC, BoxArms::new(D, D, D, O)), // ╠
+    (0xB9, BoxArms::new(D, O, D, D)), // ╣
+    (0xCB, BoxArms::new(O, D, D, D)), // ╦
+    (0xCA, BoxArms::new(D, D, O, D)), // ╩
+    (0xCE, BoxArms::new(D, D, D, D)), // ╬
+    (0xD5, BoxArms::new(O, D, S, O)), // ╒
+    (0xD6, BoxArms::new(O, S, D, O)), // ╓
+    (0xB8, BoxArms::new(O, O, S, D)), // ╕
+    (0xB7, BoxArms::new(O, O, D, S)), // ╖
+    (0xD4, BoxArms::new(S, D, O, O)), // ╘
+    (0xD3, BoxArms::new(D, S, O, O)), // ╙
+    (0xBE, BoxArms::new(S, O, O, D)), // ╛
+    (0xBD, BoxArms::new(D, O, O, S)), // ╜
+    (0xC6, BoxArms::new(S, D, S, O)), // ╞
+    (0xC7, BoxArms::new(D, S, D, O)), // ╟
+    (0xB5, BoxArms::new(S, O, S, D)), // ╡
+    (0xB6, BoxArms::new(D, O, D, S)), // ╢
+    (0xD1, BoxArms::new(O, D, S, D)), // ╤
+    (0xD2, BoxArms::new(O, S, D, S)), // ╥
+    (0xCF, BoxArms::new(S, D, O, D)), // ╧
+    (0xD0, BoxArms::new(D, S, O, S)), // ╨
+    (0xD8, BoxArms::new(S, D, S, D)), // ╪
+    (0xD7, BoxArms::new(D, S, D, S)), // ╫
+];
+
+/// PETSCII line glyphs as screen codes, PETSCII only knows single lines
+const PETSCII_BOX_GLYPHS: [(u8, BoxArms); 11] = [
+    (0x40, BoxArms::new(O, S, O, S)), // ─
+    (0x5D, BoxArms::new(S, O, S, O)), // │
+    (0x70, BoxArms::new(O, S, S, O)), // ┌
+    (0x6E, BoxArms::new(O, O, S, S)), // ┐
+    (0x6D, BoxArms::new(S, S, O, O)), // └
+    (0x7D, BoxArms::new(S, O, O, S)), // ┘
+    (0x6B, BoxArms::new(S, S, S, O)), // ├
+    (0x73, BoxArms::new(S, O, S, S)), // ┤
+    (0x72, BoxArms::new(O, S, S, S)), // ┬
+    (0x71, BoxArms::new(S, S, O, S)), // ┴
+    (0x5B, BoxArms::new(S, S, S, S)), // ┼
+];
+
+/// The set of line glyphs used for a buffer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoxCharset {
+    /// CP437 codepoints
+    Cp437,
+    /// The CP437 glyphs as unicode box drawing characters
+    Unicode,
+    /// PETSCII screen codes
+    Petscii,
+}
+
+impl BoxCharset {
+    /// `None` for buffer types without line glyphs
+    pub fn from_buffer_type(buffer_type: BufferType) -> Option<Self> {
+        match buffer_type {
+            BufferType::CP437 => Some(BoxCharset::Cp437),
+            BufferType::Unicode => Some(BoxCharset::Unicode),
+            BufferType::Petscii => Some(BoxCharset::Petscii),
+            BufferType::Atascii | BufferType::Viewdata => None,
+        }
+    }
+
+    fn glyphs(self) -> &'static [(u8, BoxArms)] {
+        match self {
+            BoxCharset::Cp437 | BoxCharset::Unicode => &CP437_BOX_GLYPHS,
+            BoxCharset::Petscii => &PETSCII_BOX_GLYPHS,
+        }
+    }
+
+    fn to_char(self, code: u8) -> char {
+        match self {
+            BoxCharset::Unicode => BufferType::CP437.convert_to_unicode(code as char),
+            BoxCharset::Cp437 | BoxCharset::Petscii => code as char,
+        }
+    }
+
+    /// The arms of `ch`, `None` if it isn't a line glyph
+    pub fn arms(self, ch: char) -> Option<BoxArms> {
+        self.glyphs().iter().find(|(code, _)| self.to_char(*code) == ch).map(|(_, arms)| *arms)
+    }
+
+    /// The glyph closest to `arms`
+    ///
+    /// Arms on the same axis are drawn with the heavier weight and a lone arm continues
+    /// through the cell, so every non empty arm set has a glyph.
+    pub fn glyph(self, arms: BoxArms) -> Option<char> {
+        let arms = self.normalize(arms)?;
+        self.glyphs().iter().find(|(_, a)| *a == arms).map(|(code, _)| self.to_char(*code))
+    }
+
+    fn normalize(self, arms: BoxArms) -> Option<BoxArms> {
+        if arms.is_empty() {
+            return None;
+        }
+        let clamp = |weight: LineWeight| {
+            if self == BoxCharset::Petscii {
+                weight.min(LineWeight::Single)
+            } else {
+                weight
+            }
+        };
+        let horizontal = clamp(arms.east.max(arms.west));
+        let vertical = clamp(arms.north.max(arms.south));
+        let has_vertical = vertical != LineWeight::None;
+        let has_horizontal = horizontal != LineWeight::None;
+        let pick = |weight: LineWeight, axis: LineWeight, lone: bool| if weight != LineWeight::None || lone { axis } else { LineWeight::None };
+        Some(BoxArms::new(
+            pick(arms.north, vertical, !has_horizontal),
+            pick(arms.east, horizontal, !has_vertical),
+            pick(arms.south, vertical, !has_horizontal),
+            pick(arms.west, horizontal, !has_vertical),
+        ))
+    }
+}
+
+/// Line weights of the strokes drawn in box drawing mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BoxLineStyle {
+    pub horizontal: LineWeight,
+    pub vertical: LineWeight,
+}
+
+impl BoxLineStyle {
+    pub const SINGLE: Self = Self {
+        horizontal: LineWeight::Single,
+        vertical: LineWeight::Single,
+    };
+
+    pub const DOUBLE: Self = Self {
+        horizontal: LineWeight::Double,
+        vertical: LineWeight::Double,
+    };
+
+    /// The line weights of the sides of a TheDraw outline style, sides that aren't lines are drawn single
+    pub fn from_outline_style(outline_style: u8) -> Self {
+        let weight = |role: PointRole| {
+            BoxCharset::Cp437
+                .arms(role.outline_char(outline_style))
+                .map(|arms| arms.north.max(arms.east))
+                .filter(|weight| *weight != LineWeight::None)
+                .unwrap_or(LineWeight::Single)
+        };
+        Self {
+            horizontal: weight(PointRole::TopSide),
+            vertical: weight(PointRole::LeftSide),
+        }
+    }
+
+    fn weight(&self, direction: Direction) -> LineWeight {
+        if direction.is_horizontal() {
+            self.horizontal
+        } else {
+            self.vertical
+        }
+    }
+}
+
+/// Generate the points of a box drawing line from p0 to p1
+///
+/// Consecutive points share an edge, diagonal steps of the Bresenham line become a corner.
+pub fn get_box_line_points(p0: Position, p1: Position) -> Vec<Position> {
+    let mut points: Vec<Position> = Vec::new();
+    for p in get_line_points(p0, p1) {
+        if let Some(&last) = points.last() {
+            if last.x != p.x && last.y != p.y {
+                points.push(Position::new(p.x, last.y));
+            }
+        }
+        points.push(p);
+    }
+    points
+}
+
+fn arms_at<T: DrawTarget>(target: &T, charset: BoxCharset, pos: Position) -> Option<BoxArms> {
+    target.char_at(pos).and_then(|ch| charset.arms(ch.ch))
+}
+
+/// Generate the outline of a rectangle as one closed path, clockwise from the top left corner
+///
+/// The path ends on its start, so [`draw_box_lines`] closes the frame. Unlike
+/// [`super::get_rectangle_points`] the cells are in drawing order, so the sides of a narrow
+/// rectangle don't join across.
+pub fn get_box_rectangle_points(p0: Position, p1: Position) -> Vec<Position> {
+    let (min_x, max_x) = (p0.x.min(p1.x), p0.x.max(p1.x));
+    let (min_y, max_y) = (p0.y.min(p1.y), p0.y.max(p1.y));
+    if min_x == max_x || min_y == max_y {
+        return get_box_line_points(Position::new(min_x, min_y), Position::new(max_x, max_y));
+    }
+    let mut points = Vec::with_capacity(2 * (max_x - min_x + max_y - min_y) as usize + 1);
+    points.extend((min_x..max_x).map(|x| Position::new(x, min_y)));
+    points.extend((min_y..max_y).map(|y| Position::new(max_x, y)));
+    points.extend((min_x + 1..=max_x).rev().map(|x| Position::new(x, max_y)));
+    points.extend((min_y..=max_y).rev().map(|y| Position::new(min_x, y)));
+    points
+}
+
+/// Draw `points` as a connected box drawing path
+///
+/// Consecutive points are joined, the stroke is merged with the line glyphs already in its cells
+/// and lines running into the stroke from outside get the matching junction on both sides.
+/// Cells of the path that are only next to each other - like the sides of a narrow rectangle -
+/// stay apart.
+pub fn draw_box_lines<T: DrawTarget>(target: &mut T, charset: BoxCharset, style: BoxLineStyle, points: &[Position], attribute: TextAttribute) {
+    let stroke: HashSet<Position> = points.iter().copied().filter(|p| target.is_valid(*p)).collect();
+    let mut links: HashMap<Position, Vec<Direction>> = HashMap::new();
+    for pair in points.windows(2) {
+        if let Some(direction) = Direction::ALL.into_iter().find(|d| pair[0] + d.offset() == pair[1]) {
+            links.entry(pair[0]).or_default().push(direction);
+            links.entry(pair[1]).or_default().push(direction.opposite());
+        }
+    }
+
+    let mut cells = Vec::with_capacity(stroke.len());
+    for &pos in &stroke {
+        let mut arms = arms_at(target, charset, pos).unwrap_or_default();
+        let linked = links.get(&pos).map_or(&[][..], Vec::as_slice);
+        for direction in Direction::ALL {
+            let next = pos + direction.offset();
+            let weight = if linked.contains(&direction) {
+                style.weight(direction)
+            } else if stroke.contains(&next) {
+                // Part of the stroke, but not connected to this cell
+                continue;
+            } else {
+                arms_at(target, charset, next).map_or(LineWeight::None, |a| a.arm(direction.opposite()))
+            };
+            if weight != LineWeight::None {
+                *arms.arm_mut(direction) = weight;
+            }
+        }
+        if linked.is_empty() && arms.is_empty() {
+            arms.east = style.horizontal;
+            arms.west = style.horizontal;
+        }
+        if let Some(glyph) = charset.glyph(arms) {
+            cells.push((pos, glyph));
+        }
+    }
+
+    for &(pos, glyph) in &cells {
+        target.set_char(pos, AttributedChar::new(glyph, attribute));
+    }
+
+    // Lines next to the stroke that the stroke now points into get their junction
+    for (pos, glyph) in cells {
+        let Some(arms) = charset.arms(glyph) else {
+            continue;
+        };
+        for direction in Direction::ALL {
+            let next = pos + direction.offset();
+            let weight = arms.arm(direction);
+            if weight == LineWeight::None || stroke.contains(&next) {
+                continue;
+            }
+            let Some(ch) = target.char_at(next) else {
+                continue;
+            };
+            let Some(mut next_arms) = charset.arms(ch.ch) else {
+                continue;
+            };
+            *next_arms.arm_mut(direction.opposite()) = weight;
+            if let Some(glyph) = charset.glyph(next_arms) {
+                target.set_char(next, AttributedChar::new(glyph, ch.attribute));
+            }
+        }
+    }
+}
+
+/// Replace `points` with `erased` and repair the lines that ran into them
+pub fn erase_box_lines<T: DrawTarget>(target: &mut T, charset: BoxCharset, points: &[Position], erased: AttributedChar) {
+    let stroke: HashSet<Position> = points.iter().copied().filter(|p| target.is_valid(*p)).collect();
+    for &pos in &stroke {
+        target.set_char(pos, erased);
+    }
+
+    let neighbours: HashSet<Position> = stroke
+        .iter()
+        .flat_map(|pos| Direction::ALL.map(|direction| *pos + direction.offset()))
+        .filter(|pos| !stroke.contains(pos))
+        .collect();
+    for pos in neighbours {
+        let Some(ch) = target.char_at(pos) else {
+            continue;
+        };
+        let Some(mut arms) = charset.arms(ch.ch) else {
+            continue;
+        };
+        for direction in Direction::ALL {
+            if stroke.contains(&(pos + direction.offset())) {
+                *arms.arm_mut(direction) = LineWeight::None;
+            }
+        }
+        if let Some(glyph) = charset.glyph(arms) {
+            target.set_char(pos, AttributedChar::new(glyph, ch.attribute));
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Gradient and pattern fills
+//!
+//! A gradient maps every cell to a value between 0.0 (start point) and 1.0 (end point)
+//! and picks one step of an ordered ramp for it. A ramp step can change the character,
+//! the colors or both, so the same code does color ramps, shade ramps (` ░▒▓█`) and
+//! the classic mix of both.
+
+use icy_engine::{AttributedChar, Position};
+
+/// Cells are about twice as high as wide, distances are measured with this y scale
+/// so radial gradients come out round.
+const CELL_ASPECT: f32 = 2.0;
+
+/// 4x4 ordered dither matrix
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Light to dark shade ramp, usable as gradient characters
+pub const SHADE_RAMP: [char; 5] = [' ', '\u{00B0}', '\u{00B1}', '\u{00B2}', 219 as char];
+
+/// Geometry of a gradient
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GradientShape {
+    /// Changes along the line from start to end
+    #[default]
+    Linear,
+    /// Circles around the start point, the end point lies on the outermost one
+    Radial,
+    /// Sweeps clockwise around the start point, beginning in the direction of the end point
+    Angular,
+}
+
+/// One step of a gradient ramp, `None` keeps the value of the filled cell
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GradientStep {
+    pub ch: Option<char>,
+    pub foreground: Option<u32>,
+    pub background: Option<u32>,
+}
+
+impl GradientStep {
+    pub fn apply(&self, mut ch: AttributedChar) -> AttributedChar {
+        if let Some(c) = self.ch {
+            ch.ch = c;
+        }
+        if let Some(fg) = self.foreground {
+            ch.attribute.set_foreground(fg);
+        }
+        if let Some(bg) = self.background {
+            ch.attribute.set_background(bg);
+        }
+        ch.attribute.attr &= !icy_engine::attribute::INVISIBLE;
+        ch
+    }
+}
+
+/// A gradient fill
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    /// Ramp from the start to the end point
+    pub steps: Vec<GradientStep>,
+    /// Ordered dithering between neighbouring steps instead of hard bands
+    pub dither: bool,
+}
+
+impl Gradient {
+    /// Builds the ramp from palette colors and/or characters
+    ///
+    /// - only colors: every color is a step, setting the foreground
+    /// - only characters: every character is a step, colors are kept
+    /// - both: between each pair of colors the characters blend from the first color
+    ///   (as background) to the next one (as foreground), e.g. with [`SHADE_RAMP`]
+    pub fn from_ramp(shape: GradientShape, colors: &[u32], chars: &[char], dither: bool) -> Self {
+        let steps = match (colors.len(), chars.is_empty()) {
+            (0, _) => chars
+                .iter()
+                .map(|&ch| GradientStep {
+                    ch: Some(ch),
+                    ..Default::default()
+                })
+                .collect(),
+            (_, true) => colors
+                .iter()
+                .map(|&fg| GradientStep {
+                    foreground: Some(fg),
+                    ..Default::default()
+                })
+                .collect(),
+            (1, false) => chars
+                .iter()
+                .map(|&ch| GradientStep {
+                    ch: Some(ch),
+                    foreground: Some(colors[0]),
+                    background: None,
+                })
+                .collect(),
+            _ => {
+                let mut steps = Vec::new();
+                for (i, pair) in colors.windows(2).enumerate() {
+                    // The first character of a pair looks like the last one of the previous pair
+                    let skip = usize::from(i > 0);
+                    steps.extend(chars.iter().skip(skip).map(|&ch| GradientStep {
+                        ch: Some(ch),
+                        foreground: Some(pair[1]),
+                        background: Some(pair[0]),
+                    }));
+                }
+                steps
+            }
+        };
+        Self { shape, steps, dither }
+    }
+
+    /// Position of `pos` on the gradient from `start` to `end` (0.0 - 1.0)
+    pub fn value_at(&self, start: Position, end: Position, pos: Position) -> f32 {
+        let (dx, dy) = ((end.x - start.x) as f32, (end.y - start.y) as f32 * CELL_ASPECT);
+        let (px, py) = ((pos.x - start.x) as f32, (pos.y - start.y) as f32 * CELL_ASPECT);
+        let len_sq = dx * dx + dy * dy;
+        if len_sq == 0.0 {
+            return 0.0;
+        }
+        let t = match self.shape {
+            GradientShape::Linear => (px * dx + py * dy) / len_sq,
+            GradientShape::Radial => ((px * px + py * py) / len_sq).sqrt(),
+            GradientShape::Angular => {
+                if px == 0.0 && py == 0.0 {
+                    return 0.0;
+                }
+                let angle = py.atan2(px) - dy.atan2(dx);
+                angle.rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU
+            }
+        };
+        t.clamp(0.0, 1.0)
+    }
+
+    /// The ramp step for `pos`, `None` for an empty ramp
+    pub fn step_at(&self, start: Position, end: Position, pos: Position) -> Option<&GradientStep> {
+        if self.steps.is_empty() {
+            return None;
+        }
+        let scaled = self.value_at(start, end, pos) * (self.steps.len() - 1) as f32;
+        let index = if self.dither {
+            let threshold = (BAYER_4X4[pos.y.rem_euclid(4) as usize][pos.x.rem_euclid(4) as usize] as f32 + 0.5) / 16.0;
+            let base = scaled.floor();
+            base as usize + usize::from(scaled - base > threshold)
+        } else {
+            scaled.round() as usize
+        };
+        self.steps.get(index.min(self.steps.len() - 1))
+    }
+
+    /// The filled cell for `ch` at `pos`
+    pub fn apply(&self, start: Position, end: Position, pos: Position, ch: AttributedChar) -> AttributedChar {
+        match self.step_at(start, end, pos) {
+            Some(step) => step.apply(ch),
+            None => ch,
+        }
+    }
+}
+
+/// Repeats a tile of cells, `origin` is where the tile's top left corner lands
+pub fn pattern_char_at(tile: &[Vec<AttributedChar>], origin: Position, pos: Position) -> Option<AttributedChar> {
+    let height = tile.len() as i32;
+    let width = tile.first().map_or(0, Vec::len) as i32;
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let y = (pos.y - origin.y).rem_euclid(height) as usize;
+    let x = (pos.x - origin.x).rem_euclid(width) as usize;
+    tile[y].get(x).copied()
+}
@@ -7,8 +7,10 @@
 //! - Line drawing (Bresenham algorithm)
 //! - Rectangle outline and fill
 //! - Ellipse outline and fill (midpoint algorithm)
+//! - Linear, radial and angular gradients, pattern tiles
 //! - Various brush modes (block, half-block, shade, colorize, etc.)
 //! - Outline character support for TheDraw fonts
+//! - Box drawing lines that join with existing frames (CP437, Unicode and PETSCII)
 //! - Brush size expansion utilities
 //!
 //! # Shape Functions
@@ -18,6 +20,7 @@
 //! - `get_line_points(p0, p1)` - Get all points on a line
 //! - `get_rectangle_points(p0, p1)` - Get outline points of a rectangle
 //! - `get_filled_rectangle_points(p0, p1)` - Get all points of a filled rectangle
+//! - `get_box_rectangle_points(p0, p1)` - Get the outline of a rectangle as a closed box drawing path
 //! - `get_ellipse_points(center, rx, ry)` - Get outline points of an ellipse
 //! - `get_ellipse_points_from_rect(p0, p1)` - Get outline points from bounding box
 //! - `get_filled_ellipse_points(center, rx, ry)` - Get all points of a filled ellipse
@@ -37,22 +40,27 @@
 //! draw_line(&mut target, &ctx, Position::new(0, 0), Position::new(10, 5));
 //! ```
 
+pub mod box_drawing;
 mod brush_mode;
 mod color_mode;
 pub mod ellipse;
+pub mod gradient;
 pub mod line;
 pub mod rectangle;
 
+pub use box_drawing::{draw_box_lines, erase_box_lines, get_box_line_points, get_box_rectangle_points, BoxArms, BoxCharset, BoxLineStyle, LineWeight};
 pub use brush_mode::{BrushMode, PointRole};
 pub use color_mode::ColorMode;
 pub use ellipse::{
     draw_ellipse, draw_ellipse_from_rect, fill_ellipse, fill_ellipse_from_rect, get_ellipse_points, get_ellipse_points_from_rect, get_filled_ellipse_points,
     get_filled_ellipse_points_from_rect,
 };
+pub use gradient::{pattern_char_at, Gradient, GradientShape, GradientStep, SHADE_RAMP};
 pub use line::{draw_line, get_line_points};
 pub use rectangle::{draw_rectangle, fill_rectangle, get_filled_rectangle_points, get_rectangle_points};
 
 use icy_engine::{AttributeColor, AttributedChar, Position, TextAttribute};
+use serde::{Deserialize, Serialize};
 
 /// Standard CP437 shade gradient characters (from light to dark)
 ///
@@ -147,12 +155,15 @@ impl Default for DrawContext {
 }
 
 /// Mirror mode for symmetrical drawing
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MirrorMode {
     #[default]
     None,
+    /// Mirrored left to right
     Horizontal,
+    /// Mirrored top to bottom
     Vertical,
+    /// Four-way symmetry
     Both,
 }
 
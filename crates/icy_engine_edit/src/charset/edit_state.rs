@@ -28,6 +28,7 @@
 //! - Characters range from '!' (0x21) to '~' (0x7E) = 94 characters
 //! - Grid is 16 columns × 6 rows (last row is partial)
 
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use icy_engine::Position;
@@ -36,7 +37,7 @@ use retrofont::{
     Glyph,
 };
 
-use super::{load_tdf_fonts_from_file, CharSetUndoOperation, CharSetUndoStack};
+use super::{generate_tdf_font, load_tdf_fonts, CharSetUndoOperation, CharSetUndoStack, CoverageMap, FigletLayout, FlfFont, TdfGeneratorOptions};
 
 /// Which panel currently has focus in the CharSet editor
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
@@ -93,6 +94,13 @@ pub struct CharSetEditState {
     /// Whether the font has been modified since last save
     is_dirty: bool,
 
+    /// FIGlet font the fonts were loaded from, keeps what TDF can't hold
+    /// (glyphs outside `!`..`~`, layout and comments) for saving as `.flf`
+    figlet_source: Option<FlfFont>,
+
+    /// Layout written when saving as `.flf`
+    figlet_layout: FigletLayout,
+
     // ═══════════════════════════════════════════════════════════════════════
     // Undo/Redo
     // ═══════════════════════════════════════════════════════════════════════
@@ -130,6 +138,8 @@ impl CharSetEditState {
             focused_panel: CharSetFocusedPanel::CharSet,
             file_path: None,
             is_dirty: false,
+            figlet_source: None,
+            figlet_layout: FigletLayout::default(),
             undo_stack: CharSetUndoStack::new(),
         }
     }
@@ -153,14 +163,20 @@ impl CharSetEditState {
             focused_panel: CharSetFocusedPanel::CharSet,
             file_path,
             is_dirty: false,
+            figlet_source: None,
+            figlet_layout: FigletLayout::default(),
             undo_stack: CharSetUndoStack::new(),
         }
     }
 
-    /// Load from a TDF file
+    /// Load from a TDF or FIGlet file
     pub fn load_from_file(path: PathBuf) -> anyhow::Result<Self> {
-        let fonts = load_tdf_fonts_from_file(&path)?;
-        Ok(Self::with_fonts(fonts, Some(path)))
+        let data = std::fs::read(&path)?;
+        let mut state = Self::with_fonts(load_tdf_fonts(&data)?, Some(path));
+        if data.starts_with(b"flf2a") {
+            state.set_figlet_source(FlfFont::from_bytes(&data)?);
+        }
+        Ok(state)
     }
 
     // ═══════════════════════════════════════════════════════════════════════
@@ -227,6 +243,11 @@ impl CharSetEditState {
         self.is_dirty
     }
 
+    /// Get the layout written when saving as `.flf`
+    pub fn figlet_layout(&self) -> FigletLayout {
+        self.figlet_layout
+    }
+
     /// Get undo stack length
     pub fn undo_stack_len(&self) -> usize {
         self.undo_stack.undo_len()
@@ -256,6 +277,20 @@ impl CharSetEditState {
     // Setters
     // ═══════════════════════════════════════════════════════════════════════
 
+    /// Keep the FIGlet font the fonts were converted from, its layout becomes the FIGlet layout
+    pub fn set_figlet_source(&mut self, source: FlfFont) {
+        self.figlet_layout = source.layout;
+        self.figlet_source = Some(source);
+    }
+
+    /// Set the layout written when saving as `.flf`
+    pub fn set_figlet_layout(&mut self, layout: FigletLayout) {
+        if self.figlet_layout != layout {
+            self.figlet_layout = layout;
+            self.is_dirty = true;
+        }
+    }
+
     /// Set the file path
     pub fn set_file_path(&mut self, path: Option<PathBuf>) {
         self.file_path = path;
@@ -397,6 +432,29 @@ impl CharSetEditState {
         self.is_dirty = true;
     }
 
+    /// Generate a font from rasterized glyphs and add it as the selected font
+    pub fn generate_font(&mut self, name: String, glyphs: &BTreeMap<char, CoverageMap>, options: &TdfGeneratorOptions) -> anyhow::Result<()> {
+        let new_font = generate_tdf_font(name, glyphs, options)?;
+        self.insert_font(new_font);
+        Ok(())
+    }
+
+    /// Add an already built font, e.g. a generated one, as the selected font
+    pub fn insert_font(&mut self, new_font: TdfFont) {
+        let new_index = self.fonts.len();
+
+        self.undo_stack.push(CharSetUndoOperation::FontAdded {
+            font_index: new_index,
+            font: new_font.clone(),
+        });
+
+        self.fonts.push(new_font);
+        self.selected_font = new_index;
+        self.selected_char = None;
+        self.old_selected_char = None;
+        self.is_dirty = true;
+    }
+
     /// Clone the current font
     pub fn clone_font(&mut self) {
         if self.selected_font < self.fonts.len() {
@@ -744,9 +802,27 @@ impl CharSetEditState {
     // Save/Load
     // ═══════════════════════════════════════════════════════════════════════
 
-    /// Save the document to the given path
+    /// The selected font as FIGlet font
+    ///
+    /// If the document was loaded from a FIGlet font, the edited glyphs are written into it,
+    /// so its other glyphs and comments are kept.
+    pub fn selected_figlet_font(&self) -> anyhow::Result<FlfFont> {
+        let font = self.selected_font().ok_or_else(|| anyhow::anyhow!("No font selected"))?;
+        let mut flf = match &self.figlet_source {
+            Some(source) => source.with_tdf_glyphs(font),
+            None => FlfFont::from_tdf(font, self.figlet_layout)?,
+        };
+        flf.layout = self.figlet_layout;
+        Ok(flf)
+    }
+
+    /// Save the document to the given path, `.flf` files get the selected font as FIGlet font
     pub fn save(&mut self, path: &std::path::Path) -> Result<(), String> {
-        let bytes = TdfFont::serialize_bundle(&self.fonts).map_err(|e| e.to_string())?;
+        let bytes = if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("flf")) {
+            self.selected_figlet_font().map_err(|e| e.to_string())?.to_flf_string().into_bytes()
+        } else {
+            TdfFont::serialize_bundle(&self.fonts).map_err(|e| e.to_string())?
+        };
         std::fs::write(path, bytes).map_err(|e| e.to_string())?;
         self.file_path = Some(path.to_path_buf());
         self.is_dirty = false;
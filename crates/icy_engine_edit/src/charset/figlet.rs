@@ -0,0 +1,345 @@
+//! FIGlet font (`.flf`) reading and writing
+//!
+//! Converts between FIGlet fonts and TDF block fonts so fonts authored in the
+//! `CharFont` editor can be used with figlet/toilet and FIGlet fonts can be edited.
+//! Colors of TDF color fonts are dropped, outline fonts can't be converted.
+//!
+//! Hard blanks are kept as U+00A0 in the glyph lines, like the `CharFont` editor does,
+//! and written with a hardblank character that doesn't collide with the glyph art.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use retrofont::tdf::{TdfFont, TdfFontType};
+use retrofont::{Glyph, GlyphPart};
+
+/// Hard blank inside glyph lines
+pub const HARD_BLANK: char = '\u{00A0}';
+
+/// German characters every FIGlet font has after the ASCII range
+pub const FIGLET_GERMAN_CHARS: [char; 7] = ['Ä', 'Ö', 'Ü', 'ä', 'ö', 'ü', 'ß'];
+
+const HARD_BLANK_CANDIDATES: [char; 8] = ['$', '#', '%', '&', '*', '+', '~', '^'];
+const END_MARK_CANDIDATES: [char; 4] = ['@', '#', '|', '!'];
+
+/// Horizontal layout of a FIGlet font
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FigletLayout {
+    /// Characters are placed at their full width
+    #[default]
+    FullWidth,
+    /// Characters are moved together until they touch (kerning)
+    Fitting,
+    /// Characters overlap by one column, the rule bits (1-32) select how
+    /// the overlapping characters merge, no bits means universal smushing
+    Smushing(u8),
+}
+
+impl FigletLayout {
+    /// `old_layout` header value
+    fn old_layout(self) -> i32 {
+        match self {
+            Self::FullWidth => -1,
+            Self::Fitting => 0,
+            Self::Smushing(rules) => i32::from(rules & 63),
+        }
+    }
+
+    /// `full_layout` header value, horizontal bits only
+    fn full_layout(self) -> i32 {
+        match self {
+            Self::FullWidth => 0,
+            Self::Fitting => 64,
+            Self::Smushing(rules) => 128 | i32::from(rules & 63),
+        }
+    }
+
+    fn from_header(old_layout: i32, full_layout: Option<i32>) -> Self {
+        match full_layout {
+            Some(full) if full & 128 != 0 => Self::Smushing((full & 63) as u8),
+            Some(full) if full & 64 != 0 => Self::Fitting,
+            Some(_) => Self::FullWidth,
+            None if old_layout < 0 => Self::FullWidth,
+            None if old_layout == 0 => Self::Fitting,
+            None => Self::Smushing((old_layout & 63) as u8),
+        }
+    }
+}
+
+/// A FIGlet font, every glyph has `height` lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlfFont {
+    pub name: String,
+    pub height: usize,
+    /// Lines from the top to the baseline
+    pub baseline: usize,
+    pub layout: FigletLayout,
+    /// Comment lines, written after the header
+    pub comments: Vec<String>,
+    pub glyphs: BTreeMap<char, Vec<String>>,
+}
+
+/// Characters a FIGlet font must contain, in file order
+fn required_chars() -> impl Iterator<Item = char> {
+    (' '..='~').chain(FIGLET_GERMAN_CHARS)
+}
+
+impl FlfFont {
+    /// Convert a TDF block or color font, colors are dropped.
+    ///
+    /// TDF fonts have no space glyph, it becomes half as wide as the average glyph. The letter
+    /// spacing of the TDF font is added to every glyph and the font gets `layout`.
+    pub fn from_tdf(font: &TdfFont, layout: FigletLayout) -> anyhow::Result<Self> {
+        if font.font_type == TdfFontType::Outline {
+            anyhow::bail!("Outline fonts can't be converted to FIGlet");
+        }
+        let spacing = font.spacing.max(0) as usize;
+        let mut glyphs: BTreeMap<char, Vec<String>> = BTreeMap::new();
+        for ch in '!'..='~' {
+            if let Some(glyph) = font.glyph(ch) {
+                glyphs.insert(ch, glyph_lines(glyph, spacing));
+            }
+        }
+        if glyphs.is_empty() {
+            anyhow::bail!("Font '{}' has no glyphs", font.name);
+        }
+
+        let height = glyphs.values().map(Vec::len).max().unwrap_or(1);
+        let average_width = glyphs.values().map(|lines| line_width(&lines[0])).sum::<usize>() / glyphs.len();
+        glyphs.insert(' ', vec![" ".repeat((average_width / 2).max(1)); height]);
+        for lines in glyphs.values_mut() {
+            let width = line_width(&lines[0]);
+            lines.resize(height, " ".repeat(width));
+        }
+
+        Ok(Self {
+            name: font.name.clone(),
+            height,
+            baseline: height,
+            layout,
+            comments: vec![font.name.clone()],
+            glyphs,
+        })
+    }
+
+    /// Convert to a TDF block font with the glyphs from `!` to `~`.
+    pub fn to_tdf(&self) -> TdfFont {
+        let mut font = TdfFont::new(self.name.clone(), TdfFontType::Block, 0);
+        for ch in '!'..='~' {
+            let Some(lines) = self.glyphs.get(&ch) else {
+                continue;
+            };
+            let width = lines.iter().map(|line| line_width(line)).max().unwrap_or(0);
+            if width == 0 || lines.iter().all(|line| line.trim_end_matches(' ').is_empty()) {
+                continue;
+            }
+            let mut parts = Vec::new();
+            for (y, line) in lines.iter().enumerate() {
+                if y > 0 {
+                    parts.push(GlyphPart::NewLine);
+                }
+                parts.extend(
+                    line.trim_end_matches(' ')
+                        .chars()
+                        .map(|c| if c == HARD_BLANK { GlyphPart::HardBlank } else { GlyphPart::Char(c) }),
+                );
+            }
+            font.add_glyph(
+                ch,
+                Glyph {
+                    width,
+                    height: lines.len(),
+                    parts,
+                },
+            );
+        }
+        font
+    }
+
+    /// Take the glyphs from `!` to `~` that were edited in `font`, a font made by [`Self::to_tdf`].
+    ///
+    /// Glyphs that weren't changed, the other glyphs, the layout and the comments are kept,
+    /// so a FIGlet font survives a round trip through the `CharFont` editor.
+    pub fn with_tdf_glyphs(&self, font: &TdfFont) -> Self {
+        let original = self.to_tdf();
+        let spacing = font.spacing.max(0) as usize;
+        let mut result = self.clone();
+        for ch in '!'..='~' {
+            let edited = font.glyph(ch);
+            if edited.map(|glyph| glyph_lines(glyph, 0)) == original.glyph(ch).map(|glyph| glyph_lines(glyph, 0)) {
+                continue;
+            }
+            match edited {
+                Some(glyph) => result.glyphs.insert(ch, glyph_lines(glyph, spacing)),
+                None => result.glyphs.remove(&ch),
+            };
+        }
+        result.height = result.glyphs.values().map(Vec::len).max().unwrap_or(1).max(self.height);
+        for lines in result.glyphs.values_mut() {
+            let width = lines.first().map_or(0, |line| line_width(line));
+            lines.resize(result.height, " ".repeat(width));
+        }
+        result
+    }
+
+    /// Parse a FIGlet font, files that aren't valid UTF-8 are read as Latin-1.
+    pub fn from_bytes(data: &[u8]) -> anyhow::Result<Self> {
+        let text = match std::str::from_utf8(data) {
+            Ok(text) => text.to_string(),
+            Err(_) => data.iter().map(|&b| char::from(b)).collect(),
+        };
+        let mut lines = text.lines();
+        let header = lines.next().unwrap_or_default();
+        let Some(rest) = header.strip_prefix("flf2a") else {
+            anyhow::bail!("Not a FIGlet font");
+        };
+        let mut rest = rest.chars();
+        let hard_blank = rest.next().ok_or_else(|| anyhow::anyhow!("FIGlet header without hardblank"))?;
+        let fields: Vec<i32> = rest.as_str().split_whitespace().map_while(|f| f.parse().ok()).collect();
+        if fields.len() < 5 {
+            anyhow::bail!("Invalid FIGlet header '{header}'");
+        }
+        let height = fields[0].max(1) as usize;
+        let baseline = fields[1].clamp(1, height as i32) as usize;
+        let layout = FigletLayout::from_header(fields[3], fields.get(6).copied());
+
+        let comments: Vec<String> = lines.by_ref().take(fields[4].max(0) as usize).map(str::to_string).collect();
+        let mut glyphs = BTreeMap::new();
+        for ch in required_chars() {
+            let Some(glyph) = read_glyph(&mut lines, height, hard_blank) else {
+                break;
+            };
+            glyphs.insert(ch, glyph);
+        }
+        // Code tagged characters: a line with the code followed by the glyph
+        while let Some(tag) = lines.next() {
+            let code = tag.split_whitespace().next().and_then(parse_code);
+            let Some(glyph) = read_glyph(&mut lines, height, hard_blank) else {
+                break;
+            };
+            if let Some(ch) = code.and_then(char::from_u32) {
+                glyphs.insert(ch, glyph);
+            }
+        }
+
+        let name = comments
+            .iter()
+            .map(|c| c.trim())
+            .find(|c| !c.is_empty())
+            .map_or_else(|| "FIGlet".to_string(), str::to_string);
+        Ok(Self {
+            name,
+            height,
+            baseline,
+            layout,
+            comments,
+            glyphs,
+        })
+    }
+
+    /// Write the font as `.flf`.
+    ///
+    /// Required characters without a glyph are written empty, other glyphs are appended
+    /// as code tagged characters.
+    pub fn to_flf_string(&self) -> String {
+        let all_lines = || self.glyphs.values().flatten();
+        let hard_blank = HARD_BLANK_CANDIDATES
+            .into_iter()
+            .find(|c| all_lines().all(|line| !line.contains(*c)))
+            .unwrap_or('$');
+        let end_mark = END_MARK_CANDIDATES
+            .into_iter()
+            .find(|c| *c != hard_blank && all_lines().all(|line| !line.ends_with(*c)))
+            .unwrap_or('@');
+
+        let max_length = all_lines().map(|line| line_width(line)).max().unwrap_or(0) + 2;
+        let code_tagged: Vec<(&char, &Vec<String>)> = self.glyphs.iter().filter(|(ch, _)| !required_chars().any(|r| r == **ch)).collect();
+
+        let mut out = format!(
+            "flf2a{hard_blank} {} {} {max_length} {} {} 0 {} {}\n",
+            self.height,
+            self.baseline,
+            self.layout.old_layout(),
+            self.comments.len(),
+            self.layout.full_layout(),
+            code_tagged.len()
+        );
+        for comment in &self.comments {
+            out.push_str(comment);
+            out.push('\n');
+        }
+
+        let empty = vec![String::new(); self.height];
+        for ch in required_chars() {
+            write_glyph(&mut out, self.glyphs.get(&ch).unwrap_or(&empty), hard_blank, end_mark);
+        }
+        for (ch, lines) in code_tagged {
+            let _ = writeln!(out, "{}  U+{:04X}", *ch as u32, *ch as u32);
+            write_glyph(&mut out, lines, hard_blank, end_mark);
+        }
+        out
+    }
+}
+
+fn read_glyph(lines: &mut std::str::Lines<'_>, height: usize, hard_blank: char) -> Option<Vec<String>> {
+    (0..height)
+        .map(|_| lines.next().map(|line| strip_end_mark(line).replace(hard_blank, &HARD_BLANK.to_string())))
+        .collect()
+}
+
+fn write_glyph(out: &mut String, lines: &[String], hard_blank: char, end_mark: char) {
+    for (i, line) in lines.iter().enumerate() {
+        out.push_str(&line.replace(HARD_BLANK, &hard_blank.to_string()));
+        out.push(end_mark);
+        if i + 1 == lines.len() {
+            out.push(end_mark);
+        }
+        out.push('\n');
+    }
+}
+
+/// Lines of a TDF glyph padded to its width plus `spacing`
+fn glyph_lines(glyph: &Glyph, spacing: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for part in &glyph.parts {
+        match part {
+            GlyphPart::NewLine => lines.push(std::mem::take(&mut line)),
+            GlyphPart::Char(ch) | GlyphPart::AnsiChar { ch, .. } if *ch != '\0' => line.push(*ch),
+            GlyphPart::HardBlank => line.push(HARD_BLANK),
+            _ => line.push(' '),
+        }
+    }
+    lines.push(line);
+    lines.resize(glyph.height.max(lines.len()), String::new());
+    let width = glyph.width.max(lines.iter().map(|line| line_width(line)).max().unwrap_or(0)) + spacing;
+    for line in &mut lines {
+        let pad = width - line_width(line);
+        line.extend(std::iter::repeat_n(' ', pad));
+    }
+    lines
+}
+
+fn line_width(line: &str) -> usize {
+    line.chars().count()
+}
+
+/// Remove the end mark, the last character and all copies of it before
+fn strip_end_mark(line: &str) -> &str {
+    let line = line.trim_end();
+    match line.chars().last() {
+        Some(end_mark) => line.trim_end_matches(end_mark),
+        None => line,
+    }
+}
+
+/// Character code of a code tag, decimal, `0x` hex or `0` octal
+fn parse_code(code: &str) -> Option<u32> {
+    if let Some(hex) = code.strip_prefix("0x").or_else(|| code.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16).ok()
+    } else if code.len() > 1 && code.starts_with('0') {
+        u32::from_str_radix(&code[1..], 8).ok()
+    } else {
+        code.parse().ok()
+    }
+}
@@ -0,0 +1,236 @@
+//! TDF font generation from rasterized glyphs
+//!
+//! Turns grayscale glyph bitmaps (rendered from a TrueType font or cut from an image sheet)
+//! into a multi-cell TheDraw block or color font. Every text cell shows two pixel rows with
+//! half blocks (`▀` `▄` `█`), so a glyph rendered `2 * cell_height` pixels high becomes
+//! `cell_height` lines of text. Each pixel column becomes one text column.
+
+use std::collections::BTreeMap;
+
+use retrofont::tdf::{TdfFont, TdfFontType};
+use retrofont::{Glyph, GlyphPart};
+
+const FULL_BLOCK: char = '█';
+const UPPER_HALF: char = '▀';
+const LOWER_HALF: char = '▄';
+const SHADES: [char; 3] = ['░', '▒', '▓'];
+
+/// Grayscale glyph bitmap, 0 is empty and 255 fully covered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageMap {
+    pub width: usize,
+    pub height: usize,
+    /// Row major coverage values, `width * height` entries
+    pub data: Vec<u8>,
+}
+
+impl CoverageMap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0; width * height],
+        }
+    }
+
+    /// Coverage at `(x, y)`, 0 outside of the map
+    pub fn get(&self, x: i32, y: i32) -> u8 {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return 0;
+        }
+        self.data[y as usize * self.width + x as usize]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, coverage: u8) {
+        if x < self.width && y < self.height {
+            self.data[y * self.width + x] = coverage;
+        }
+    }
+}
+
+/// Options for [`generate_tdf_font`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TdfGeneratorOptions {
+    /// `Block` or `Color`, outline fonts can't be generated
+    pub font_type: TdfFontType,
+    /// Lines per glyph, the coverage maps are expected to be twice as high in pixels
+    pub cell_height: usize,
+    /// Pixels with at least this coverage are set
+    pub threshold: u8,
+    /// Cells without a set pixel but with coverage below the threshold become `░` `▒` `▓`
+    pub shade: bool,
+    /// Color fonts get a ring in `outline_color` around every glyph,
+    /// block fonts have no colors and keep only the edges of the glyphs instead
+    pub outline: bool,
+    /// Foreground color of color fonts
+    pub color: u8,
+    /// Outline color of color fonts, best one of the 8 background colors
+    pub outline_color: u8,
+    /// Letter spacing of the generated font
+    pub spacing: i32,
+}
+
+impl Default for TdfGeneratorOptions {
+    fn default() -> Self {
+        Self {
+            font_type: TdfFontType::Color,
+            cell_height: 6,
+            threshold: 128,
+            shade: false,
+            outline: false,
+            color: 15,
+            // Outline pixels can end up in the background, which only has the low 8 colors
+            outline_color: 7,
+            spacing: 1,
+        }
+    }
+}
+
+/// Classified pixel of a coverage map
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pixel {
+    Empty,
+    Set,
+    Outline,
+}
+
+/// Generate a TDF font from glyph coverage maps.
+///
+/// Glyphs are cropped horizontally to their drawn columns, characters outside of `!`..`~`
+/// and empty glyphs are skipped.
+pub fn generate_tdf_font(name: impl Into<String>, glyphs: &BTreeMap<char, CoverageMap>, options: &TdfGeneratorOptions) -> anyhow::Result<TdfFont> {
+    if options.font_type == TdfFontType::Outline {
+        anyhow::bail!("Outline fonts can't be generated");
+    }
+    if options.cell_height == 0 {
+        anyhow::bail!("Cell height must be at least 1");
+    }
+    let mut font = TdfFont::new(name, options.font_type, options.spacing);
+    let mut count = 0;
+    for (ch, map) in glyphs.range('!'..='~') {
+        if let Some(glyph) = glyph_from_coverage(map, options) {
+            font.add_glyph(*ch, glyph);
+            count += 1;
+        }
+    }
+    if count == 0 {
+        anyhow::bail!("No glyphs could be generated");
+    }
+    Ok(font)
+}
+
+/// Render one coverage map as a glyph of `cell_height` lines, `None` if nothing is drawn.
+pub fn glyph_from_coverage(map: &CoverageMap, options: &TdfGeneratorOptions) -> Option<Glyph> {
+    let pixels = classify(map, options);
+    let width = map.width as i32;
+    let pixel_height = options.cell_height as i32 * 2;
+    let pixel = |x: i32, y: i32| {
+        if x < 0 || y < 0 || x >= width || y as usize >= map.height {
+            Pixel::Empty
+        } else {
+            pixels[y as usize * map.width + x as usize]
+        }
+    };
+
+    let lines: Vec<Vec<GlyphPart>> = (0..pixel_height / 2)
+        .map(|line| {
+            (0..width)
+                .map(|x| {
+                    let top = pixel(x, line * 2);
+                    let bottom = pixel(x, line * 2 + 1);
+                    let coverage = (u16::from(map.get(x, line * 2)) + u16::from(map.get(x, line * 2 + 1))) / 2;
+                    cell_part(top, bottom, coverage, options)
+                })
+                .collect()
+        })
+        .collect();
+    let used = |x: usize| lines.iter().any(|cells| !is_blank(&cells[x]));
+    let first = (0..map.width).find(|x| used(*x))?;
+    let last = (0..map.width).rev().find(|x| used(*x))?;
+
+    let mut parts = Vec::new();
+    for (y, cells) in lines.iter().enumerate() {
+        if y > 0 {
+            parts.push(GlyphPart::NewLine);
+        }
+        let cells = &cells[first..=last];
+        let end = cells.iter().rposition(|cell| !is_blank(cell)).map_or(0, |pos| pos + 1);
+        parts.extend_from_slice(&cells[..end]);
+    }
+
+    Some(Glyph {
+        width: last - first + 1,
+        height: options.cell_height,
+        parts,
+    })
+}
+
+/// Threshold the map and apply the outline option
+fn classify(map: &CoverageMap, options: &TdfGeneratorOptions) -> Vec<Pixel> {
+    let threshold = options.threshold.max(1);
+    let is_set = |x: i32, y: i32| map.get(x, y) >= threshold;
+    let mut pixels = Vec::with_capacity(map.data.len());
+    for y in 0..map.height as i32 {
+        for x in 0..map.width as i32 {
+            let pixel = if !options.outline {
+                if is_set(x, y) {
+                    Pixel::Set
+                } else {
+                    Pixel::Empty
+                }
+            } else if options.font_type == TdfFontType::Color {
+                let near_set = (-1..=1).any(|dy| (-1..=1).any(|dx| is_set(x + dx, y + dy)));
+                if is_set(x, y) {
+                    Pixel::Set
+                } else if near_set {
+                    Pixel::Outline
+                } else {
+                    Pixel::Empty
+                }
+            } else {
+                let is_edge = [(0, -1), (-1, 0), (1, 0), (0, 1)].iter().any(|(dx, dy)| !is_set(x + dx, y + dy));
+                if is_set(x, y) && is_edge {
+                    Pixel::Set
+                } else {
+                    Pixel::Empty
+                }
+            };
+            pixels.push(pixel);
+        }
+    }
+    pixels
+}
+
+/// Text cell for a top and bottom pixel, `coverage` is the average of both
+fn cell_part(top: Pixel, bottom: Pixel, coverage: u16, options: &TdfGeneratorOptions) -> GlyphPart {
+    let color = |pixel: Pixel| match pixel {
+        Pixel::Empty => None,
+        Pixel::Set => Some(options.color),
+        Pixel::Outline => Some(options.outline_color),
+    };
+    let (ch, fg, bg) = match (color(top), color(bottom)) {
+        (None, None) => {
+            let threshold = u16::from(options.threshold.max(1));
+            let level = coverage * 4 / threshold;
+            if options.shade && coverage < threshold && level > 0 {
+                (SHADES[level as usize - 1], options.color, 0)
+            } else {
+                (' ', 7, 0)
+            }
+        }
+        (Some(top), None) => (UPPER_HALF, top, 0),
+        (None, Some(bottom)) => (LOWER_HALF, bottom, 0),
+        (Some(top), Some(bottom)) if top == bottom => (FULL_BLOCK, top, 0),
+        // Backgrounds are limited to the low 8 colors, the lower color goes to the background
+        (Some(top), Some(bottom)) if bottom < top => (UPPER_HALF, top, bottom & 7),
+        (Some(top), Some(bottom)) => (LOWER_HALF, bottom, top & 7),
+    };
+    match options.font_type {
+        TdfFontType::Color => GlyphPart::AnsiChar { ch, fg, bg, blink: false },
+        _ => GlyphPart::Char(ch),
+    }
+}
+
+fn is_blank(part: &GlyphPart) -> bool {
+    matches!(part, GlyphPart::Char(' ') | GlyphPart::AnsiChar { ch: ' ', .. })
+}
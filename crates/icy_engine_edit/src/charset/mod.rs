@@ -3,19 +3,25 @@
 //! Provides the model layer for TheDraw Font (TDF) editing, including:
 //! - `CharSetEditState` - the main state container for TDF font editing
 //! - Undo/redo operations for font editing actions
+//! - FIGlet (`.flf`) reading and writing with TDF conversion
+//! - Generating block and color fonts from rasterized glyphs
 //! - Direct use of `retrofont::Glyph` and `retrofont::tdf::TdfFont`
 //!
 //! This module follows the same pattern as the BitFont editor's `BitFontEditState`,
 //! separating model logic from UI concerns.
 
 mod edit_state;
+mod figlet;
 mod font_type;
+mod generator;
 mod tdf_font;
 mod undo_operations;
 mod undo_stack;
 
 pub use edit_state::*;
+pub use figlet::*;
 pub use font_type::*;
+pub use generator::*;
 pub use tdf_font::*;
 pub use undo_operations::*;
 pub use undo_stack::*;
@@ -7,15 +7,20 @@ use std::path::Path;
 
 pub use retrofont::tdf::TdfFont;
 
+use super::FlfFont;
+
 /// Load TDF fonts from a file path
 pub fn load_tdf_fonts_from_file(path: &Path) -> anyhow::Result<Vec<TdfFont>> {
     let data = std::fs::read(path)?;
     load_tdf_fonts(&data)
 }
 
-/// Load TDF fonts from bytes
+/// Load TDF fonts from bytes, FIGlet fonts are converted to a TDF block font
 pub fn load_tdf_fonts(data: &[u8]) -> anyhow::Result<Vec<TdfFont>> {
     use retrofont::tdf::TdfFontType;
+    if data.starts_with(b"flf2a") {
+        return Ok(vec![FlfFont::from_bytes(data)?.to_tdf()]);
+    }
     let fonts = TdfFont::load(data)?;
     if fonts.is_empty() {
         // Return a default empty font if file had no fonts
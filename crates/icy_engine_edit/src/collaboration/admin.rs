@@ -0,0 +1,260 @@
+//! Admin console commands of the standalone collaboration server.
+//!
+//! Commands are parsed from single lines like `kick main Bob` and executed on a [`RoomHost`],
+//! the result is the text printed back to the admin.
+
+use std::fmt::Write;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use super::rooms::{Room, RoomHost, RoomSettings};
+
+/// Help text listing all commands
+pub const ADMIN_HELP: &str = "\
+rooms                          list rooms and user counts
+users <room>                   list the users of a room
+create <room> [password]       add a room
+remove <room>                  save and close a room
+default <room>|none            room for clients connecting without a path
+kick <room> <nick|id>          disconnect a user
+ban <room> <user>              disconnect a user and refuse its address
+unban <room> <address>         accept an address again
+readonly <room> <user>         forbid drawing from a user's address
+readwrite <room> <user>        allow drawing again
+save                           save all rooms now
+quit                           save all rooms and stop the server
+
+<user> is a nickname, a user id or an address";
+
+/// A parsed admin console line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminCommand {
+    Help,
+    Rooms,
+    Users(String),
+    Create {
+        room: String,
+        password: String,
+    },
+    Remove(String),
+    Default(Option<String>),
+    /// Kick by nickname or user id
+    Kick {
+        room: String,
+        user: String,
+    },
+    /// Ban or unban the address of a connected user or an address
+    Ban {
+        room: String,
+        user: String,
+        banned: bool,
+    },
+    /// Read-only by the address of a connected user or an address
+    ReadOnly {
+        room: String,
+        user: String,
+        read_only: bool,
+    },
+    Save,
+    Quit,
+}
+
+impl FromStr for AdminCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default().to_ascii_lowercase();
+        let mut arg = |what: &str| words.next().map(str::to_string).ok_or_else(|| format!("Missing {what}, see 'help'"));
+        let result = match command.as_str() {
+            "help" | "?" => AdminCommand::Help,
+            "rooms" => AdminCommand::Rooms,
+            "users" => AdminCommand::Users(arg("room")?),
+            "create" => AdminCommand::Create {
+                room: arg("room")?,
+                password: arg("password").unwrap_or_default(),
+            },
+            "remove" => AdminCommand::Remove(arg("room")?),
+            "default" => {
+                let room = arg("room")?;
+                AdminCommand::Default((room != "none").then_some(room))
+            }
+            "kick" => AdminCommand::Kick {
+                room: arg("room")?,
+                user: arg("nick or id")?,
+            },
+            "ban" => AdminCommand::Ban {
+                room: arg("room")?,
+                user: arg("nick, id or address")?,
+                banned: true,
+            },
+            "unban" => AdminCommand::Ban {
+                room: arg("room")?,
+                user: arg("address")?,
+                banned: false,
+            },
+            "readonly" | "readwrite" => AdminCommand::ReadOnly {
+                room: arg("room")?,
+                user: arg("nick, id or address")?,
+                read_only: command == "readonly",
+            },
+            "save" => AdminCommand::Save,
+            "quit" | "exit" => AdminCommand::Quit,
+            "" => return Err(String::new()),
+            other => return Err(format!("Unknown command '{other}', see 'help'")),
+        };
+        Ok(result)
+    }
+}
+
+impl RoomHost {
+    /// Run an admin command, `Quit` is left to the caller.
+    pub async fn execute(&self, command: &AdminCommand) -> Result<String, String> {
+        match command {
+            AdminCommand::Help => Ok(ADMIN_HELP.to_string()),
+            AdminCommand::Rooms => {
+                let default_room = self.default_room();
+                let mut out = String::new();
+                for room in self.rooms() {
+                    let settings = room.settings();
+                    let (columns, rows) = room.state.session.get_dimensions();
+                    let _ = writeln!(
+                        out,
+                        "{}{}  {}x{}  {} users{}",
+                        settings.name,
+                        if default_room.as_deref() == Some(settings.name.as_str()) {
+                            " (default)"
+                        } else {
+                            ""
+                        },
+                        columns,
+                        rows,
+                        room.users().len(),
+                        if settings.has_password() { "  password" } else { "" }
+                    );
+                }
+                if out.is_empty() {
+                    out.push_str("No rooms, add one with 'create <room>'");
+                }
+                Ok(out.trim_end().to_string())
+            }
+            AdminCommand::Users(name) => {
+                let room = self.room(name).ok_or_else(|| format!("Unknown room '{name}'"))?;
+                let mut out = String::new();
+                for user in room.users() {
+                    let nick = match (user.status, user.nick.as_str()) {
+                        (3, _) => "(web viewer)",
+                        (_, "") => "(guest)",
+                        (_, nick) => nick,
+                    };
+                    let address = room.state.address(user.id).await;
+                    let _ = writeln!(
+                        out,
+                        "{:>4}  {}  {}{}",
+                        user.id,
+                        nick,
+                        address.map_or_else(|| "-".to_string(), |a| a.to_string()),
+                        if room.state.is_read_only(user.id).await { "  read-only" } else { "" }
+                    );
+                }
+                if out.is_empty() {
+                    out.push_str("No users");
+                }
+                Ok(out.trim_end().to_string())
+            }
+            AdminCommand::Create { room, password } => {
+                let mut settings = RoomSettings::new(room.clone());
+                settings.set_password(password);
+                self.create_room(settings).await.map_err(|e| e.to_string())?;
+                Ok(format!("Created room '{room}'"))
+            }
+            AdminCommand::Remove(room) => {
+                self.remove_room(room).await.map_err(|e| e.to_string())?;
+                Ok(format!("Removed room '{room}'"))
+            }
+            AdminCommand::Default(room) => {
+                self.set_default_room(room.clone()).map_err(|e| e.to_string())?;
+                Ok(match room {
+                    Some(room) => format!("Clients without a path join '{room}'"),
+                    None => "Clients without a path are refused".to_string(),
+                })
+            }
+            AdminCommand::Kick { room: name, user } => {
+                let room = self.room(name).ok_or_else(|| format!("Unknown room '{name}'"))?;
+                let id = user.parse::<u32>().ok();
+                let mut kicked = 0;
+                for u in room.users() {
+                    if (Some(u.id) == id || u.nick == *user) && room.state.kick(u.id).await {
+                        kicked += 1;
+                    }
+                }
+                if kicked == 0 {
+                    return Err(format!("No user '{user}' in room '{name}'"));
+                }
+                Ok(format!("Kicked {kicked} connection(s)"))
+            }
+            AdminCommand::Ban { room: name, user, banned } => {
+                let room = self.room(name).ok_or_else(|| format!("Unknown room '{name}'"))?;
+                let addresses = addresses_of(&room, user, !*banned).await?;
+                let mut kicked = 0;
+                for address in &addresses {
+                    kicked += self.set_banned(name, *address, *banned).await.map_err(|e| e.to_string())?;
+                }
+                Ok(if *banned {
+                    format!("Banned {}, kicked {kicked} connection(s)", join_addresses(&addresses))
+                } else {
+                    format!("Unbanned {}", join_addresses(&addresses))
+                })
+            }
+            AdminCommand::ReadOnly { room: name, user, read_only } => {
+                let room = self.room(name).ok_or_else(|| format!("Unknown room '{name}'"))?;
+                let addresses = addresses_of(&room, user, false).await?;
+                for address in &addresses {
+                    self.set_read_only(name, *address, *read_only).await.map_err(|e| e.to_string())?;
+                }
+                Ok(if *read_only {
+                    format!("{} can't draw in '{name}'", join_addresses(&addresses))
+                } else {
+                    format!("{} can draw in '{name}'", join_addresses(&addresses))
+                })
+            }
+            AdminCommand::Save => {
+                let failed = self.save_all().await;
+                if failed > 0 {
+                    return Err(format!("{failed} room(s) failed to save"));
+                }
+                Ok("Saved all rooms".to_string())
+            }
+            AdminCommand::Quit => Err("Quit is handled by the server".to_string()),
+        }
+    }
+}
+
+/// Addresses for an admin argument: an address, or the addresses of the connected users with that nick or id.
+async fn addresses_of(room: &Room, user: &str, address_only: bool) -> Result<Vec<IpAddr>, String> {
+    if let Ok(address) = user.parse::<IpAddr>() {
+        return Ok(vec![address]);
+    }
+    if address_only {
+        return Err(format!("'{user}' is no address"));
+    }
+    let id = user.parse::<u32>().ok();
+    let mut addresses = Vec::new();
+    for u in room.users() {
+        if Some(u.id) == id || u.nick == user {
+            if let Some(address) = room.state.address(u.id).await {
+                if !addresses.contains(&address) {
+                    addresses.push(address);
+                }
+            }
+        }
+    }
+    if addresses.is_empty() {
+        return Err(format!("No user '{user}' in room '{}'", room.name()));
+    }
+    Ok(addresses)
+}
+
+fn join_addresses(addresses: &[IpAddr]) -> String {
+    addresses.iter().map(IpAddr::to_string).collect::<Vec<_>>().join(", ")
+}
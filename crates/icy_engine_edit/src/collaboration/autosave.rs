@@ -267,6 +267,12 @@ impl AutosaveManager {
         }
     }
 
+    /// Treat the current document as saved to `path`, e.g. after restoring it from that file.
+    pub async fn mark_saved(&self, path: PathBuf) {
+        *self.last_hash.write().await = Some(self.calculate_hash().await);
+        *self.last_saved_file.write().await = Some(path);
+    }
+
     /// Save the document to a specific path (for shutdown saves).
     pub async fn save_to(&self, path: &Path) -> std::io::Result<()> {
         // Ensure parent directory exists
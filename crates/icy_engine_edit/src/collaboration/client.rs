@@ -94,7 +94,7 @@ impl Default for ClientConfig {
 }
 
 /// Commands that can be sent to the client task.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ClientCommand {
     /// Disconnect from server
     Disconnect,
@@ -106,8 +106,8 @@ pub enum ClientCommand {
     Operation { col: i32, row: i32 },
     /// Hide cursor (when switching to non-editing tools)
     HideCursor,
-    /// Draw a character
-    Draw { col: i32, row: i32, block: Block },
+    /// Draw a character, layers above 0 need a V2 session
+    Draw { layer: usize, col: i32, row: i32, block: Block },
     /// Draw preview (temporary)
     DrawPreview { col: i32, row: i32, block: Block },
     /// Send chat message
@@ -145,6 +145,53 @@ pub enum ClientCommand {
     PasteAsSelection { blocks: Blocks },
     /// Ping
     Ping,
+    /// Replace the palette (V3)
+    SetPalette { colors: Vec<[u8; 3]> },
+    /// Insert a layer (V3)
+    AddLayer { index: usize, layer: Box<icy_engine::Layer> },
+    /// Remove a layer (V3)
+    RemoveLayer { index: usize },
+    /// Move a layer from one stack position to another (V3)
+    ReorderLayer { from: usize, to: usize },
+    /// Replace the properties of a layer (V3)
+    SetLayerProperties { index: usize, properties: icy_engine::LayerProperties },
+    /// Upload a font into a slot, `None` clears it (V3)
+    SetFontSlot { slot: u8, font: Option<Box<icy_engine::BitFont>> },
+    /// Replace all tags (V3)
+    SetTags { tags: Vec<icy_engine::Tag> },
+    /// Set the `.icy` document attributes (V3)
+    SetAttributes { attributes: IcyAttributes },
+}
+
+impl ClientCommand {
+    /// Lowest protocol version the server needs to understand the command.
+    pub fn min_protocol_version(&self) -> ProtocolVersion {
+        match self {
+            ClientCommand::SetPalette { .. }
+            | ClientCommand::AddLayer { .. }
+            | ClientCommand::RemoveLayer { .. }
+            | ClientCommand::ReorderLayer { .. }
+            | ClientCommand::SetLayerProperties { .. }
+            | ClientCommand::SetFontSlot { .. }
+            | ClientCommand::SetTags { .. }
+            | ClientCommand::SetAttributes { .. } => ProtocolVersion::V3,
+            _ => ProtocolVersion::V1,
+        }
+    }
+
+    /// Adapt the command to the negotiated protocol version.
+    ///
+    /// Returns `None` if the session can't express it. V1 sessions draw every layer
+    /// into the single Moebius layer, like before layers were synced.
+    pub fn for_protocol(self, version: ProtocolVersion) -> Option<Self> {
+        if self.min_protocol_version() > version {
+            return None;
+        }
+        match self {
+            ClientCommand::Draw { col, row, block, .. } if version < ProtocolVersion::V2 => Some(ClientCommand::Draw { layer: 0, col, row, block }),
+            cmd => Some(cmd),
+        }
+    }
 }
 
 /// Events received from the server.
@@ -166,8 +213,14 @@ pub enum CollaborationEvent {
     OperationStarted { user_id: UserId, col: i32, row: i32 },
     /// Cursor hidden (user switched to non-editing tool)
     CursorHidden { user_id: UserId },
-    /// Character drawn
-    Draw { col: i32, row: i32, block: Block },
+    /// Character drawn, `layer` is 0 unless the session is V2 or later
+    Draw {
+        user_id: UserId,
+        layer: usize,
+        col: i32,
+        row: i32,
+        block: Block,
+    },
     /// Preview character drawn
     DrawPreview { col: i32, row: i32, block: Block },
     /// Chat message received
@@ -194,6 +247,36 @@ pub enum CollaborationEvent {
     FlipY { user_id: UserId },
     /// Background color changed (Moebius SET_BG=21)
     BackgroundChanged { user_id: UserId, value: u32 },
+    /// Palette replaced (V3)
+    PaletteChanged { user_id: UserId, colors: Vec<[u8; 3]> },
+    /// Layer inserted (V3)
+    LayerAdded {
+        user_id: UserId,
+        index: usize,
+        layer: Box<icy_engine::Layer>,
+    },
+    /// Layer removed (V3)
+    LayerRemoved { user_id: UserId, index: usize },
+    /// Layer moved in the layer stack (V3)
+    LayerReordered { user_id: UserId, from: usize, to: usize },
+    /// Layer properties changed (V3)
+    LayerPropertiesChanged {
+        user_id: UserId,
+        index: usize,
+        properties: icy_engine::LayerProperties,
+    },
+    /// Font slot uploaded or cleared (V3)
+    FontSlotChanged {
+        user_id: UserId,
+        slot: u8,
+        font: Option<Box<icy_engine::BitFont>>,
+    },
+    /// Tags replaced (V3)
+    TagsChanged { user_id: UserId, tags: Vec<icy_engine::Tag> },
+    /// `.icy` document attributes changed (V3)
+    AttributesChanged { user_id: UserId, attributes: IcyAttributes },
+    /// Notice from the server for this user (V3)
+    Notice(ServerNotice),
     /// Connection lost
     Disconnected,
     /// Error occurred
@@ -208,6 +291,7 @@ pub struct ClientHandle {
     command_tx: mpsc::Sender<ClientCommand>,
     state: Arc<RwLock<ConnectionState>>,
     user_id: Arc<RwLock<Option<UserId>>>,
+    protocol_version: Arc<RwLock<ProtocolVersion>>,
     nick: String,
 }
 
@@ -227,6 +311,11 @@ impl ClientHandle {
         *self.user_id.read().await
     }
 
+    /// Get the protocol version negotiated with the server (V1 until connected).
+    pub async fn protocol_version(&self) -> ProtocolVersion {
+        *self.protocol_version.read().await
+    }
+
     /// Get the nickname.
     pub fn nick(&self) -> &str {
         &self.nick
@@ -293,10 +382,10 @@ impl ClientHandle {
             .map_err(|e| ClientError::SendFailed(e.to_string()))
     }
 
-    /// Draw a character at the given position.
+    /// Draw a character at the given position of the first layer.
     pub async fn draw(&self, col: i32, row: i32, block: Block) -> Result<(), ClientError> {
         self.command_tx
-            .send(ClientCommand::Draw { col, row, block })
+            .send(ClientCommand::Draw { layer: 0, col, row, block })
             .await
             .map_err(|e| ClientError::SendFailed(e.to_string()))
     }
@@ -429,16 +518,18 @@ pub async fn connect(config: ClientConfig) -> Result<(ClientHandle, mpsc::Receiv
 
     let state = Arc::new(RwLock::new(ConnectionState::Connecting));
     let user_id = Arc::new(RwLock::new(None));
+    let protocol_version = Arc::new(RwLock::new(ProtocolVersion::V1));
 
     let handle = ClientHandle {
         command_tx,
         state: state.clone(),
         user_id: user_id.clone(),
+        protocol_version: protocol_version.clone(),
         nick: config.nick.clone(),
     };
 
     // Spawn the client task
-    tokio::spawn(run_client(config, command_rx, event_tx, state, user_id));
+    tokio::spawn(run_client(config, command_rx, event_tx, state, user_id, protocol_version));
 
     Ok((handle, event_rx))
 }
@@ -450,6 +541,7 @@ async fn run_client(
     event_tx: mpsc::Sender<CollaborationEvent>,
     state: Arc<RwLock<ConnectionState>>,
     user_id_storage: Arc<RwLock<Option<UserId>>>,
+    protocol_version_storage: Arc<RwLock<ProtocolVersion>>,
 ) {
     let nick = config.nick.clone();
     let group = config.group.clone();
@@ -495,12 +587,14 @@ async fn run_client(
 
     // Send connect message with password
     // Moebius protocol: Client sends CONNECTED (0) to initiate, server responds with CONNECTED (0)
+    // Moebius servers ignore `protocol_version` and answer without one, which keeps us at V1.
     let connect_msg = json!({
         "type": ActionCode::Connected as u8,
         "data": {
             "nick": nick.clone(),
             "group": group.clone(),
             "pass": password.clone(),
+            "protocol_version": ProtocolVersion::LATEST,
         }
     });
 
@@ -512,6 +606,7 @@ async fn run_client(
     }
 
     let mut assigned_user_id: Option<UserId> = None;
+    let mut protocol_version = ProtocolVersion::V1;
 
     // Moebius-like away timers: ACTIVE immediately on activity, then IDLE after 60s, AWAY after 5min.
     // Important: sending STATUS itself must NOT count as activity, otherwise we'd bounce ACTIVE<->IDLE.
@@ -583,6 +678,8 @@ async fn run_client(
                                 if let CollaborationEvent::Connected(ref doc) = event {
                                     *state.write().await = ConnectionState::Connected;
                                     *user_id_storage.write().await = Some(doc.user_id);
+                                    protocol_version = doc.protocol_version;
+                                    *protocol_version_storage.write().await = protocol_version;
                                 }
                                 if matches!(&event, CollaborationEvent::Refused { .. }) {
                                     *state.write().await = ConnectionState::Failed("Authentication failed".to_string());
@@ -616,6 +713,11 @@ async fn run_client(
                         break;
                     }
                     Some(cmd) => {
+                        let Some(cmd) = cmd.for_protocol(protocol_version) else {
+                            log::debug!("Dropping command not supported by protocol {:?}", protocol_version);
+                            continue;
+                        };
+
                         // Track activity like Moebius: any user action (except CONNECTED/STATUS/PING) resets timers.
                         let is_activity = !matches!(
                             cmd,
@@ -699,14 +801,15 @@ pub async fn parse_server_message(msg: &Value, _nick: &str, assigned_id: &mut Op
 
             let mut connected = resp.data.doc.into_connected_document(user_id, resp.data.users).ok()?;
             connected.chat_history = resp.data.chat_history;
+            connected.protocol_version = resp.data.protocol_version;
+            connected.extended = resp.data.extended;
 
             Some(CollaborationEvent::Connected(Box::new(connected)))
         }
         1 => {
             // REFUSED
-            Some(CollaborationEvent::Refused {
-                reason: "Wrong password".to_string(),
-            })
+            let reason = data.and_then(|d| d.get("reason")).and_then(|r| r.as_str()).unwrap_or("Wrong password");
+            Some(CollaborationEvent::Refused { reason: reason.to_string() })
         }
         2 => {
             // JOIN
@@ -758,6 +861,8 @@ pub async fn parse_server_message(msg: &Value, _nick: &str, assigned_id: &mut Op
         9 => {
             // DRAW
             let data = data?;
+            let user_id = data.get("id").and_then(|v| v.as_u64()).unwrap_or(0) as UserId;
+            let layer = data.get("layer").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
             let col = data.get("x")?.as_i64()? as i32;
             let row = data.get("y")?.as_i64()? as i32;
             let block_data = data.get("block")?;
@@ -766,7 +871,13 @@ pub async fn parse_server_message(msg: &Value, _nick: &str, assigned_id: &mut Op
                 fg: block_data.get("fg").and_then(|v| v.as_u64()).unwrap_or(7) as u8,
                 bg: block_data.get("bg").and_then(|v| v.as_u64()).unwrap_or(0) as u8,
             };
-            Some(CollaborationEvent::Draw { col, row, block })
+            Some(CollaborationEvent::Draw {
+                user_id,
+                layer,
+                col,
+                row,
+                block,
+            })
         }
         10 => {
             // CHAT
@@ -864,6 +975,79 @@ pub async fn parse_server_message(msg: &Value, _nick: &str, assigned_id: &mut Op
             let value = data.get("value")?.as_u64()? as u32;
             Some(CollaborationEvent::BackgroundChanged { user_id, value })
         }
+        100 => {
+            // SET_PALETTE (V3)
+            let data: SetPaletteData = serde_json::from_value(data?.clone()).ok()?;
+            Some(CollaborationEvent::PaletteChanged {
+                user_id: data.id,
+                colors: data.colors,
+            })
+        }
+        101 => {
+            // ADD_LAYER (V3)
+            let data: AddLayerData = serde_json::from_value(data?.clone()).ok()?;
+            Some(CollaborationEvent::LayerAdded {
+                user_id: data.id,
+                index: data.index,
+                layer: Box::new(data.layer),
+            })
+        }
+        102 => {
+            // REMOVE_LAYER (V3)
+            let data: RemoveLayerData = serde_json::from_value(data?.clone()).ok()?;
+            Some(CollaborationEvent::LayerRemoved {
+                user_id: data.id,
+                index: data.index,
+            })
+        }
+        103 => {
+            // REORDER_LAYER (V3)
+            let data: ReorderLayerData = serde_json::from_value(data?.clone()).ok()?;
+            Some(CollaborationEvent::LayerReordered {
+                user_id: data.id,
+                from: data.from,
+                to: data.to,
+            })
+        }
+        104 => {
+            // SET_LAYER_PROPERTIES (V3)
+            let data: SetLayerPropertiesData = serde_json::from_value(data?.clone()).ok()?;
+            Some(CollaborationEvent::LayerPropertiesChanged {
+                user_id: data.id,
+                index: data.index,
+                properties: data.properties,
+            })
+        }
+        105 => {
+            // SET_FONT_SLOT (V3)
+            let data: SetFontSlotData = serde_json::from_value(data?.clone()).ok()?;
+            Some(CollaborationEvent::FontSlotChanged {
+                user_id: data.id,
+                slot: data.slot,
+                font: data.font.map(Box::new),
+            })
+        }
+        106 => {
+            // SET_TAGS (V3)
+            let data: SetTagsData = serde_json::from_value(data?.clone()).ok()?;
+            Some(CollaborationEvent::TagsChanged {
+                user_id: data.id,
+                tags: data.tags,
+            })
+        }
+        107 => {
+            // SET_ATTRIBUTES (V3)
+            let data: SetAttributesData = serde_json::from_value(data?.clone()).ok()?;
+            Some(CollaborationEvent::AttributesChanged {
+                user_id: data.id,
+                attributes: data.attributes,
+            })
+        }
+        108 => {
+            // NOTICE (V3), unknown notices of newer servers are ignored
+            let data: NoticeData = serde_json::from_value(data?.clone()).ok()?;
+            Some(CollaborationEvent::Notice(data.notice))
+        }
         _ => None,
     }
 }
@@ -900,15 +1084,22 @@ pub fn command_to_message(cmd: ClientCommand, user_id: Option<UserId>, nick: &st
             "type": ActionCode::HideCursor as u8,
             "data": { "id": id }
         }),
-        ClientCommand::Draw { col, row, block } => json!({
-            "type": ActionCode::Draw as u8,  // Moebius DRAW = 9
-            "data": {
-                "id": id,
-                "x": col,
-                "y": row,
-                "block": { "code": block.code, "fg": block.fg, "bg": block.bg }
+        ClientCommand::Draw { layer, col, row, block } => {
+            let mut msg = json!({
+                "type": ActionCode::Draw as u8,  // Moebius DRAW = 9
+                "data": {
+                    "id": id,
+                    "x": col,
+                    "y": row,
+                    "block": { "code": block.code, "fg": block.fg, "bg": block.bg }
+                }
+            });
+            // Layer 0 stays byte-identical to Moebius
+            if layer > 0 {
+                msg["data"]["layer"] = json!(layer);
             }
-        }),
+            msg
+        }
         // DrawPreview is not part of Moebius; do not send over the network.
         ClientCommand::DrawPreview { .. } => return None,
         ClientCommand::Chat { text } => json!({
@@ -968,6 +1159,38 @@ pub fn command_to_message(cmd: ClientCommand, user_id: Option<UserId>, nick: &st
             "type": ActionCode::PasteAsSelection as u8, // Moebius PASTE_AS_SELECTION = 17
             "data": { "id": id, "blocks": blocks }
         }),
+        ClientCommand::SetPalette { colors } => json!({
+            "type": ActionCode::SetPalette as u8,
+            "data": SetPaletteData { id, colors }
+        }),
+        ClientCommand::AddLayer { index, layer } => json!({
+            "type": ActionCode::AddLayer as u8,
+            "data": AddLayerData { id, index, layer: *layer }
+        }),
+        ClientCommand::RemoveLayer { index } => json!({
+            "type": ActionCode::RemoveLayer as u8,
+            "data": RemoveLayerData { id, index }
+        }),
+        ClientCommand::ReorderLayer { from, to } => json!({
+            "type": ActionCode::ReorderLayer as u8,
+            "data": ReorderLayerData { id, from, to }
+        }),
+        ClientCommand::SetLayerProperties { index, properties } => json!({
+            "type": ActionCode::SetLayerProperties as u8,
+            "data": SetLayerPropertiesData { id, index, properties }
+        }),
+        ClientCommand::SetFontSlot { slot, font } => json!({
+            "type": ActionCode::SetFontSlot as u8,
+            "data": SetFontSlotData { id, slot, font: font.map(|f| *f) }
+        }),
+        ClientCommand::SetTags { tags } => json!({
+            "type": ActionCode::SetTags as u8,
+            "data": SetTagsData { id, tags }
+        }),
+        ClientCommand::SetAttributes { attributes } => json!({
+            "type": ActionCode::SetAttributes as u8,
+            "data": SetAttributesData { id, attributes }
+        }),
         // Ping not in Moebius protocol - skip
         ClientCommand::Ping => return None,
         ClientCommand::Disconnect => return None,
@@ -12,25 +12,36 @@
 //! # Protocol Versioning
 //!
 //! - Version 1 (default, Moebius-compatible): Single layer support only
-//! - Version 2+: Extended features including layer support
+//! - Version 2: Draws on layers above the first one
+//! - Version 3: Palette, layer, font slot, tag and `.icy` attribute sync, per-user undo
 //!
 //! Moebius clients ignore unknown fields, so we use an optional `protocol_version`
 //! field in the CONNECTED message for feature negotiation.
 
+mod admin;
 mod autosave;
 mod client;
 mod compression;
 mod connector;
+mod oplog;
+mod password;
 mod protocol;
+mod rooms;
 mod server;
 mod session;
+mod spectator;
 mod state;
 
+pub use admin::*;
 pub use autosave::*;
 pub use client::*;
 pub use compression::*;
 pub use connector::*;
+pub use oplog::*;
+pub use password::*;
 pub use protocol::*;
+pub use rooms::*;
 pub use server::*;
 pub use session::*;
+pub use spectator::*;
 pub use state::*;
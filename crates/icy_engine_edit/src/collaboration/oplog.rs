@@ -0,0 +1,205 @@
+//! Per-user operation log for selective undo in V3 sessions.
+//!
+//! The local undo stack holds only our own operations, but replaying them blindly would
+//! also revert cells other users painted over in the meantime. The log remembers who wrote
+//! each cell last; undo and redo draws for cells that belong to someone else are dropped and
+//! the local editor puts the other user's content back.
+//!
+//! The log keeps at most [`MAX_CELLS_PER_USER`] cells per user and forgets the oldest ones
+//! first, undoing that far back overwrites whatever is in those cells.
+
+use std::collections::{HashMap, VecDeque};
+
+use super::client::{ClientCommand, CollaborationEvent};
+use super::protocol::Block;
+use super::session::UserId;
+
+/// A cell the local editor has to restore after an undo or redo skipped it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellRestore {
+    pub layer: usize,
+    pub col: i32,
+    pub row: i32,
+    pub block: Block,
+}
+
+/// Number of cells remembered per user.
+pub const MAX_CELLS_PER_USER: usize = 65_536;
+
+type CellKey = (usize, i32, i32);
+
+/// Last writer and content per `(layer, col, row)`.
+#[derive(Debug)]
+pub struct OperationLog {
+    /// Writer, content and write sequence number per cell
+    cells: HashMap<CellKey, (UserId, Block, u64)>,
+    /// Cells each user wrote, oldest first, with the sequence number of the write
+    writes: HashMap<UserId, VecDeque<(CellKey, u64)>>,
+    next_seq: u64,
+    max_cells_per_user: usize,
+}
+
+impl Default for OperationLog {
+    fn default() -> Self {
+        Self::with_limit(MAX_CELLS_PER_USER)
+    }
+}
+
+impl OperationLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log that remembers at most `max_cells_per_user` cells per user.
+    pub fn with_limit(max_cells_per_user: usize) -> Self {
+        Self {
+            cells: HashMap::new(),
+            writes: HashMap::new(),
+            next_seq: 0,
+            max_cells_per_user,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.writes.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Remember that `user` wrote `block` to a cell.
+    pub fn record(&mut self, user: UserId, layer: usize, col: i32, row: i32, block: Block) {
+        let key = (layer, col, row);
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.cells.insert(key, (user, block, seq));
+
+        let writes = self.writes.entry(user).or_default();
+        writes.push_back((key, seq));
+        while writes.len() > self.max_cells_per_user {
+            let Some((key, seq)) = writes.pop_front() else {
+                break;
+            };
+            // Only forget the cell if nobody wrote it since
+            if self.cells.get(&key).is_some_and(|(_, _, s)| *s == seq) {
+                self.cells.remove(&key);
+            }
+        }
+    }
+
+    /// The user who wrote the cell last, if anyone did during the session.
+    pub fn last_writer(&self, layer: usize, col: i32, row: i32) -> Option<UserId> {
+        self.cells.get(&(layer, col, row)).map(|(user, _, _)| *user)
+    }
+
+    /// Number of cells the log remembers.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// A layer was inserted at `index`, layers from there on move up.
+    pub fn insert_layer(&mut self, index: usize) {
+        self.remap(|layer| Some(if layer >= index { layer + 1 } else { layer }));
+    }
+
+    /// The layer at `index` was removed together with its history.
+    pub fn remove_layer(&mut self, index: usize) {
+        self.remap(|layer| match layer.cmp(&index) {
+            std::cmp::Ordering::Less => Some(layer),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(layer - 1),
+        });
+    }
+
+    /// The layer at `from` was moved to `to`.
+    pub fn move_layer(&mut self, from: usize, to: usize) {
+        self.remap(|layer| {
+            Some(if layer == from {
+                to
+            } else if from < to && layer > from && layer <= to {
+                layer - 1
+            } else if to < from && layer >= to && layer < from {
+                layer + 1
+            } else {
+                layer
+            })
+        });
+    }
+
+    fn remap(&mut self, map: impl Fn(usize) -> Option<usize>) {
+        self.cells = self
+            .cells
+            .drain()
+            .filter_map(|((layer, col, row), entry)| map(layer).map(|layer| ((layer, col, row), entry)))
+            .collect();
+        for writes in self.writes.values_mut() {
+            *writes = writes
+                .drain(..)
+                .filter_map(|((layer, col, row), seq)| map(layer).map(|layer| ((layer, col, row), seq)))
+                .collect();
+        }
+    }
+
+    /// Track commands of new local edits: draws become ours, layer operations shift the log.
+    pub fn record_commands(&mut self, user: UserId, commands: &[ClientCommand]) {
+        for cmd in commands {
+            self.track_command(user, cmd);
+        }
+    }
+
+    /// Filter the commands of an undo or redo of our own operations.
+    ///
+    /// Draws on cells someone else wrote last are removed, the returned restores
+    /// give the local editor their content back.
+    pub fn filter_history(&mut self, user: UserId, commands: Vec<ClientCommand>) -> (Vec<ClientCommand>, Vec<CellRestore>) {
+        let mut kept = Vec::with_capacity(commands.len());
+        let mut restores = Vec::new();
+        for cmd in commands {
+            if let ClientCommand::Draw { layer, col, row, .. } = &cmd {
+                if let Some((writer, block, _)) = self.cells.get(&(*layer, *col, *row)) {
+                    if *writer != user {
+                        restores.push(CellRestore {
+                            layer: *layer,
+                            col: *col,
+                            row: *row,
+                            block: block.clone(),
+                        });
+                        continue;
+                    }
+                }
+            }
+            self.track_command(user, &cmd);
+            kept.push(cmd);
+        }
+        (kept, restores)
+    }
+
+    /// Track changes made by other users.
+    pub fn record_event(&mut self, event: &CollaborationEvent) {
+        match event {
+            CollaborationEvent::Draw {
+                user_id,
+                layer,
+                col,
+                row,
+                block,
+            } => self.record(*user_id, *layer, *col, *row, block.clone()),
+            CollaborationEvent::LayerAdded { index, .. } => self.insert_layer(*index),
+            CollaborationEvent::LayerRemoved { index, .. } => self.remove_layer(*index),
+            CollaborationEvent::LayerReordered { from, to, .. } => self.move_layer(*from, *to),
+            _ => {}
+        }
+    }
+
+    fn track_command(&mut self, user: UserId, cmd: &ClientCommand) {
+        match cmd {
+            ClientCommand::Draw { layer, col, row, block } => self.record(user, *layer, *col, *row, block.clone()),
+            ClientCommand::AddLayer { index, .. } => self.insert_layer(*index),
+            ClientCommand::RemoveLayer { index } => self.remove_layer(*index),
+            ClientCommand::ReorderLayer { from, to } => self.move_layer(*from, *to),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Salted password hashes for room passwords kept on disk.
+//!
+//! The format is `sha256$<rounds>$<salt>$<hash>` with salt and hash in hex. The hash is SHA-256 over
+//! the salt and the password, repeated `rounds` times over the previous hash.
+
+use sha2::{Digest, Sha256};
+
+const SCHEME: &str = "sha256";
+const ROUNDS: u32 = 100_000;
+const SALT_LEN: usize = 16;
+
+/// Hash a password with a new random salt.
+pub fn hash_password(password: &str) -> String {
+    let salt: Vec<u8> = (0..SALT_LEN).map(|_| fastrand::u8(..)).collect();
+    format!("{SCHEME}${ROUNDS}${}${}", to_hex(&salt), to_hex(&derive(&salt, password, ROUNDS)))
+}
+
+/// Check a password against a hash from [`hash_password`], malformed hashes match nothing.
+pub fn verify_password(hash: &str, password: &str) -> bool {
+    let mut parts = hash.split('$');
+    let (Some(SCHEME), Some(rounds), Some(salt), Some(expected), None) = (parts.next(), parts.next(), parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    let (Ok(rounds), Some(salt), Some(expected)) = (rounds.parse::<u32>(), from_hex(salt), from_hex(expected)) else {
+        return false;
+    };
+    let actual = derive(&salt, password, rounds);
+    // compare without an early exit
+    actual.len() == expected.len() && actual.iter().zip(&expected).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn derive(salt: &[u8], password: &str, rounds: u32) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(password.as_bytes());
+    let mut digest = hasher.finalize();
+    for _ in 1..rounds {
+        let mut hasher = Sha256::new();
+        hasher.update(salt);
+        hasher.update(digest);
+        digest = hasher.finalize();
+    }
+    digest.to_vec()
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
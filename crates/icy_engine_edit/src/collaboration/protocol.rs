@@ -9,7 +9,7 @@ use serde::{Deserialize, Serialize};
 use super::compression::MoebiusCompressedData;
 
 /// Default EGA 16-color palette (8-bit RGB values).
-const DEFAULT_EGA_PALETTE: [[u8; 3]; 16] = [
+pub(crate) const DEFAULT_EGA_PALETTE: [[u8; 3]; 16] = [
     [0x00, 0x00, 0x00], // 0: Black
     [0x00, 0x00, 0xAA], // 1: Blue
     [0x00, 0xAA, 0x00], // 2: Green
@@ -125,6 +125,26 @@ pub enum ActionCode {
     FlipY = 20,
     /// Set background color for canvas
     SetBackground = 21,
+
+    // V3 extensions, only exchanged between peers that negotiated `ProtocolVersion::V3`.
+    /// Replace the whole palette
+    SetPalette = 100,
+    /// Insert a layer
+    AddLayer = 101,
+    /// Remove a layer
+    RemoveLayer = 102,
+    /// Move a layer to another position in the layer stack
+    ReorderLayer = 103,
+    /// Replace the properties (title, visibility, offset, ...) of a layer
+    SetLayerProperties = 104,
+    /// Upload or clear a font slot
+    SetFontSlot = 105,
+    /// Replace all tags
+    SetTags = 106,
+    /// Set the `.icy` document attributes
+    SetAttributes = 107,
+    /// Server notice to a single client, see [`ServerNotice`]
+    Notice = 108,
 }
 
 impl TryFrom<u8> for ActionCode {
@@ -154,20 +174,86 @@ impl TryFrom<u8> for ActionCode {
             19 => Ok(ActionCode::FlipX),
             20 => Ok(ActionCode::FlipY),
             21 => Ok(ActionCode::SetBackground),
+            100 => Ok(ActionCode::SetPalette),
+            101 => Ok(ActionCode::AddLayer),
+            102 => Ok(ActionCode::RemoveLayer),
+            103 => Ok(ActionCode::ReorderLayer),
+            104 => Ok(ActionCode::SetLayerProperties),
+            105 => Ok(ActionCode::SetFontSlot),
+            106 => Ok(ActionCode::SetTags),
+            107 => Ok(ActionCode::SetAttributes),
+            108 => Ok(ActionCode::Notice),
             _ => Err(value),
         }
     }
 }
 
+impl ActionCode {
+    /// Whether the action changes the document (refused for read-only users).
+    pub fn changes_document(self) -> bool {
+        matches!(
+            self,
+            ActionCode::Draw
+                | ActionCode::Sauce
+                | ActionCode::IceColors
+                | ActionCode::Use9pxFont
+                | ActionCode::ChangeFont
+                | ActionCode::SetCanvasSize
+                | ActionCode::PasteAsSelection
+                | ActionCode::Rotate
+                | ActionCode::FlipX
+                | ActionCode::FlipY
+                | ActionCode::SetBackground
+                | ActionCode::SetPalette
+                | ActionCode::AddLayer
+                | ActionCode::RemoveLayer
+                | ActionCode::ReorderLayer
+                | ActionCode::SetLayerProperties
+                | ActionCode::SetFontSlot
+                | ActionCode::SetTags
+                | ActionCode::SetAttributes
+        )
+    }
+
+    /// Lowest protocol version a peer needs to understand the action.
+    pub fn min_protocol_version(self) -> ProtocolVersion {
+        match self {
+            ActionCode::SetPalette
+            | ActionCode::AddLayer
+            | ActionCode::RemoveLayer
+            | ActionCode::ReorderLayer
+            | ActionCode::SetLayerProperties
+            | ActionCode::SetFontSlot
+            | ActionCode::SetTags
+            | ActionCode::SetAttributes
+            | ActionCode::Notice => ProtocolVersion::V3,
+            _ => ProtocolVersion::V1,
+        }
+    }
+}
+
 /// Protocol version for feature negotiation.
 /// Moebius ignores unknown fields, so this is backwards-compatible.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+///
+/// Both sides send the highest version they support, the session uses the lower one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub enum ProtocolVersion {
     /// Moebius-compatible, single layer only
     #[default]
     V1 = 1,
     /// Extended with layer support
     V2 = 2,
+    /// Document-level sync (palette, layers, font slots, tags, `.icy` attributes) and per-user undo
+    V3 = 3,
+}
+
+impl ProtocolVersion {
+    /// Highest version this implementation speaks.
+    pub const LATEST: ProtocolVersion = ProtocolVersion::V3;
+}
+
+fn is_v1(v: &ProtocolVersion) -> bool {
+    *v == ProtocolVersion::V1
 }
 
 impl Serialize for ProtocolVersion {
@@ -187,6 +273,8 @@ impl<'de> Deserialize<'de> for ProtocolVersion {
         let v = u8::deserialize(deserializer)?;
         match v {
             2 => Ok(ProtocolVersion::V2),
+            // Newer peers fall back to the highest version we know
+            3.. => Ok(ProtocolVersion::V3),
             _ => Ok(ProtocolVersion::V1),
         }
     }
@@ -321,6 +409,115 @@ pub struct SauceData {
     pub comments: String,
 }
 
+/// The `.icy` document attributes that have no Moebius equivalent (V3).
+///
+/// Enums are transferred with their `.icy` header byte values.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct IcyAttributes {
+    pub buffer_type: u8,
+    pub ice_mode: u8,
+    pub font_mode: u8,
+    pub font_width: i32,
+    pub font_height: i32,
+    #[serde(default)]
+    pub letter_spacing: bool,
+    #[serde(default)]
+    pub aspect_ratio: bool,
+}
+
+impl IcyAttributes {
+    pub fn from_buffer(buffer: &icy_engine::TextBuffer) -> Self {
+        let font_size = buffer.font_dimensions();
+        Self {
+            buffer_type: buffer.buffer_type.to_byte(),
+            ice_mode: buffer.ice_mode.to_byte(),
+            font_mode: buffer.font_mode.to_byte(),
+            font_width: font_size.width,
+            font_height: font_size.height,
+            letter_spacing: buffer.use_letter_spacing(),
+            aspect_ratio: buffer.use_aspect_ratio(),
+        }
+    }
+
+    pub fn apply_to(&self, buffer: &mut icy_engine::TextBuffer) {
+        buffer.buffer_type = icy_engine::BufferType::from_byte(self.buffer_type);
+        buffer.ice_mode = icy_engine::IceMode::from_byte(self.ice_mode);
+        buffer.font_mode = icy_engine::FontMode::from_byte(self.font_mode);
+        buffer.set_font_dimensions(icy_engine::Size::new(self.font_width, self.font_height));
+        buffer.set_use_letter_spacing(self.letter_spacing);
+        buffer.set_use_aspect_ratio(self.aspect_ratio);
+    }
+}
+
+/// A font stored in one of the document's font slots (V3).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FontSlot {
+    pub slot: u8,
+    pub font: icy_engine::BitFont,
+}
+
+/// Document state beyond the Moebius document that V3 clients get on connect.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct ExtendedDocument {
+    /// Full palette, may have more than 16 colors
+    #[serde(default)]
+    pub palette: Vec<[u8; 3]>,
+    #[serde(default)]
+    pub fonts: Vec<FontSlot>,
+    #[serde(default)]
+    pub tags: Vec<icy_engine::Tag>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<IcyAttributes>,
+    /// Layer stack kept by the server, empty while it only knows the Moebius document
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<icy_engine::Layer>,
+}
+
+impl ExtendedDocument {
+    pub fn from_buffer(buffer: &icy_engine::TextBuffer) -> Self {
+        let mut fonts: Vec<FontSlot> = buffer
+            .font_iter()
+            .map(|(slot, font)| FontSlot {
+                slot: *slot,
+                font: font.clone(),
+            })
+            .collect();
+        fonts.sort_by_key(|f| f.slot);
+        Self {
+            palette: buffer.palette.color_iter().map(|c| c.clone().into()).collect(),
+            fonts,
+            tags: buffer.tags.clone(),
+            attributes: Some(IcyAttributes::from_buffer(buffer)),
+            layers: Vec::new(),
+        }
+    }
+
+    /// Apply a draw to the layer stack, layer coordinates like the clients use them.
+    pub fn draw(&mut self, layer: usize, col: i32, row: i32, block: &Block) {
+        use icy_engine::TextPane;
+        let Some(layer) = self.layers.get_mut(layer) else {
+            return;
+        };
+        let pos = icy_engine::Position::new(col, row);
+        let mut ch = layer.char_at(pos);
+        ch.ch = char::from_u32(block.code).unwrap_or(' ');
+        ch.attribute.set_foreground(u32::from(block.fg));
+        ch.attribute.set_background(u32::from(block.bg));
+        layer.set_char(pos, ch);
+    }
+
+    /// Crop or extend every layer to a new canvas size, like the clients do on `SET_CANVAS_SIZE`.
+    pub fn resize_layers(&mut self, columns: u32, rows: u32) {
+        for layer in &mut self.layers {
+            layer.set_size((columns as i32, rows as i32));
+            layer.lines.truncate(rows as usize);
+            for line in &mut layer.lines {
+                line.chars.truncate(columns as usize);
+            }
+        }
+    }
+}
+
 /// Data received when successfully connected to a collaboration server.
 /// Contains the initial document state and session information.
 #[derive(Debug, Clone)]
@@ -353,6 +550,10 @@ pub struct ConnectedDocument {
     pub group: String,
     /// SAUCE comments
     pub comments: String,
+    /// Negotiated protocol version (V1 for Moebius servers)
+    pub protocol_version: ProtocolVersion,
+    /// Extended document state, only sent by V3 servers
+    pub extended: Option<ExtendedDocument>,
 }
 
 /// Generic Moebius wire message: `{ "type": <u8>, "data": { ... } }`.
@@ -437,6 +638,8 @@ impl MoebiusDoc {
             author: self.author,
             group: self.group,
             comments: self.comments,
+            protocol_version: ProtocolVersion::V1,
+            extended: None,
         })
     }
 }
@@ -461,13 +664,21 @@ pub struct ConnectData {
     #[serde(default)]
     pub group: String,
     pub pass: String,
+    /// Highest version the client speaks, missing for Moebius clients
+    #[serde(default, skip_serializing_if = "is_v1")]
+    pub protocol_version: ProtocolVersion,
 }
 
 impl ConnectRequest {
     pub fn moebius_compatible(nick: String, group: String, password: String) -> Self {
         Self {
             msg_type: ActionCode::Connected as u8,
-            data: ConnectData { nick, group, pass: password },
+            data: ConnectData {
+                nick,
+                group,
+                pass: password,
+                protocol_version: ProtocolVersion::V1,
+            },
         }
     }
 }
@@ -780,6 +991,96 @@ impl SetBackgroundMessage {
     }
 }
 
+// ============================================================================
+// V3 Document Messages
+// ============================================================================
+//
+// Sent in both directions with the Moebius `{ "type", "data" }` shape. The server
+// relays them to V3 clients only, Moebius peers never see them.
+
+/// SET_PALETTE (100)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetPaletteData {
+    pub id: u32,
+    pub colors: Vec<[u8; 3]>,
+}
+
+/// ADD_LAYER (101), `layer` is inserted at `index`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddLayerData {
+    pub id: u32,
+    pub index: usize,
+    pub layer: icy_engine::Layer,
+}
+
+/// REMOVE_LAYER (102)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoveLayerData {
+    pub id: u32,
+    pub index: usize,
+}
+
+/// REORDER_LAYER (103), removes the layer at `from` and inserts it at `to`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReorderLayerData {
+    pub id: u32,
+    pub from: usize,
+    pub to: usize,
+}
+
+/// SET_LAYER_PROPERTIES (104)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetLayerPropertiesData {
+    pub id: u32,
+    pub index: usize,
+    pub properties: icy_engine::LayerProperties,
+}
+
+/// SET_FONT_SLOT (105), a missing font clears the slot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetFontSlotData {
+    pub id: u32,
+    pub slot: u8,
+    #[serde(default)]
+    pub font: Option<icy_engine::BitFont>,
+}
+
+/// SET_TAGS (106)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetTagsData {
+    pub id: u32,
+    #[serde(default)]
+    pub tags: Vec<icy_engine::Tag>,
+}
+
+/// SET_ATTRIBUTES (107)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetAttributesData {
+    pub id: u32,
+    pub attributes: IcyAttributes,
+}
+
+/// Notices the server sends to a single client. They carry no text, the client shows them in its own language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerNotice {
+    /// The user is read-only, document changes are not shared
+    ReadOnly,
+}
+
+/// NOTICE (108)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoticeData {
+    pub notice: ServerNotice,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoticeMessage {
+    #[serde(rename = "type")]
+    pub msg_type: u8,
+    pub data: NoticeData,
+}
+
 // ============================================================================
 // Server -> Client Messages
 // ============================================================================
@@ -803,6 +1104,11 @@ pub struct ConnectedData {
     pub chat_history: Vec<ChatMessage>,
     #[serde(default)]
     pub status: u8,
+    /// Negotiated version, only present if it's above V1
+    #[serde(default, skip_serializing_if = "is_v1")]
+    pub protocol_version: ProtocolVersion,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extended: Option<ExtendedDocument>,
 }
 
 /// Connection refused response.
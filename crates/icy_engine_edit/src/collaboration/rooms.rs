@@ -0,0 +1,478 @@
+//! Hosting of several named collaboration rooms on one port.
+//!
+//! Clients pick a room with the path of the WebSocket URL (`ws://host:8000/<room>`), an empty path
+//! joins the default room. Browsers opening `http://host:8000/<room>` get the spectator page. Every
+//! room has its own document, password, autosave folder and chat
+//! history. Passwords are stored as salted hashes, read-only and banned users by their address. The room list is kept in `rooms.toml` inside the data directory and each room lives in a
+//! subfolder named after it, so a restarted server continues from the last autosave.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use icy_engine::formats::FileFormat;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+
+use super::autosave::{AutosaveConfig, AutosaveManager};
+use super::password::hash_password;
+use super::protocol::{ChatMessage, User};
+use super::server::{serve_client, ServerConfig, ServerError, ServerState};
+use super::spectator::{peek_request, serve_http};
+
+/// Name of the room list inside the data directory
+pub const ROOMS_FILE: &str = "rooms.toml";
+const CHAT_FILE: &str = "chat.json";
+
+/// Persistent settings of a room.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoomSettings {
+    /// Room name, also the URL path and the folder name
+    pub name: String,
+    /// Hash of the room password from [`hash_password`] (empty for no password)
+    #[serde(default)]
+    pub password_hash: String,
+    /// Plain text password of older room files, hashed when the rooms are opened
+    #[serde(default, rename = "password", skip_serializing)]
+    legacy_password: String,
+    /// Maximum users allowed (0 for unlimited)
+    #[serde(default)]
+    pub max_users: usize,
+    /// Document size of a new room
+    #[serde(default = "default_columns")]
+    pub columns: u32,
+    #[serde(default = "default_rows")]
+    pub rows: u32,
+    /// Minutes between autosaves, 0 only saves on shutdown
+    #[serde(default = "default_autosave_minutes")]
+    pub autosave_minutes: u64,
+    /// Autosave file format extension ("ans" or "xb")
+    #[serde(default = "default_format")]
+    pub format: String,
+    /// Addresses that may watch and chat but not draw
+    #[serde(default)]
+    pub read_only: Vec<IpAddr>,
+    /// Addresses that are refused
+    #[serde(default)]
+    pub banned: Vec<IpAddr>,
+}
+
+fn default_columns() -> u32 {
+    80
+}
+
+fn default_rows() -> u32 {
+    25
+}
+
+fn default_autosave_minutes() -> u64 {
+    60
+}
+
+fn default_format() -> String {
+    "ans".to_string()
+}
+
+impl RoomSettings {
+    /// Settings of a new 80x25 room.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            password_hash: String::new(),
+            legacy_password: String::new(),
+            max_users: 0,
+            columns: default_columns(),
+            rows: default_rows(),
+            autosave_minutes: default_autosave_minutes(),
+            format: default_format(),
+            read_only: Vec::new(),
+            banned: Vec::new(),
+        }
+    }
+
+    /// Set the room password, an empty password removes it.
+    pub fn set_password(&mut self, password: &str) {
+        self.password_hash = if password.is_empty() { String::new() } else { hash_password(password) };
+    }
+
+    pub fn has_password(&self) -> bool {
+        !self.password_hash.is_empty()
+    }
+
+    /// Hash a plain text password read from an older room file, returns true if there was one.
+    fn migrate_password(&mut self) -> bool {
+        if self.legacy_password.is_empty() {
+            return false;
+        }
+        let password = std::mem::take(&mut self.legacy_password);
+        self.set_password(&password);
+        true
+    }
+
+    /// Autosave configuration for the room folder inside `data_dir`.
+    pub fn autosave_config(&self, data_dir: &Path) -> AutosaveConfig {
+        AutosaveConfig::new(data_dir.join(&self.name))
+            .with_interval((self.autosave_minutes > 0).then(|| Duration::from_secs(self.autosave_minutes * 60)))
+            .with_filename(self.name.clone())
+            .with_format(self.format.clone())
+    }
+}
+
+/// Room names end up in URLs and folder names, so only ASCII letters, digits, `-`, `_` and `.` are allowed.
+pub fn is_valid_room_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Content of `rooms.toml`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RoomsFile {
+    /// Room joined by clients connecting without a path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_room: Option<String>,
+    #[serde(default)]
+    rooms: Vec<RoomSettings>,
+}
+
+/// The newest autosave of a room, autosave file names sort by their timestamp.
+pub fn latest_backup(config: &AutosaveConfig) -> Option<PathBuf> {
+    let prefix = format!("{} - ", config.base_filename);
+    let suffix = format!(".{}", config.format);
+    std::fs::read_dir(&config.backup_folder)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with(&prefix) && name.ends_with(&suffix)
+        })
+        .max_by_key(|entry| entry.file_name())
+        .map(|entry| entry.path())
+}
+
+/// A hosted room.
+pub struct Room {
+    settings: Mutex<RoomSettings>,
+    /// Session and document of the room
+    pub state: Arc<ServerState>,
+    autosave: Arc<AutosaveManager>,
+    autosave_task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Room {
+    /// Create the room, continuing from its latest autosave and saved chat if there are any.
+    async fn open(settings: RoomSettings, data_dir: &Path) -> Arc<Self> {
+        let autosave_config = settings.autosave_config(data_dir);
+        let mut config = ServerConfig {
+            password_hash: settings.password_hash.clone(),
+            max_users: settings.max_users,
+            columns: settings.columns,
+            rows: settings.rows,
+            autosave: autosave_config.clone(),
+            ..Default::default()
+        };
+
+        let backup = latest_backup(&autosave_config);
+        if let Some(path) = &backup {
+            match FileFormat::from_path(path).unwrap_or(FileFormat::Ansi).load(path, None) {
+                Ok(loaded) => {
+                    let sauce = loaded.sauce_opt.as_ref().map(icy_sauce::SauceRecord::metadata).unwrap_or_default();
+                    config.set_document(&loaded.screen.buffer, sauce);
+                    log::info!("Room '{}': restored {:?}", settings.name, path);
+                }
+                Err(e) => log::error!("Room '{}': failed to load {:?}: {}", settings.name, path, e),
+            }
+        }
+
+        let state = ServerState::new(config);
+        let chat_path = autosave_config.backup_folder.join(CHAT_FILE);
+        if let Ok(json) = std::fs::read_to_string(&chat_path) {
+            match serde_json::from_str::<Vec<ChatMessage>>(&json) {
+                Ok(history) => state.session.set_chat_history(history),
+                Err(e) => log::error!("Room '{}': invalid chat history {:?}: {}", settings.name, chat_path, e),
+            }
+        }
+        for address in &settings.read_only {
+            state.set_read_only(*address, true).await;
+        }
+        for address in &settings.banned {
+            state.ban(*address).await;
+        }
+
+        let autosave = Arc::new(AutosaveManager::new(autosave_config, state.clone()));
+        if let Some(path) = backup {
+            // don't write the restored document again as the first autosave
+            autosave.mark_saved(path).await;
+        }
+        let autosave_task = autosave.clone().start();
+
+        Arc::new(Self {
+            settings: Mutex::new(settings),
+            state,
+            autosave,
+            autosave_task: Mutex::new(autosave_task),
+        })
+    }
+
+    pub fn name(&self) -> String {
+        self.settings.lock().name.clone()
+    }
+
+    pub fn settings(&self) -> RoomSettings {
+        self.settings.lock().clone()
+    }
+
+    /// Connected users, including web viewers.
+    pub fn users(&self) -> Vec<User> {
+        self.state.session.get_users()
+    }
+
+    /// Save the document if it changed and the chat history, returns the new autosave.
+    pub async fn save(&self) -> std::io::Result<Option<PathBuf>> {
+        let path = self.autosave.save().await;
+        let folder = &self.state.config.autosave.backup_folder;
+        std::fs::create_dir_all(folder)?;
+        let json = serde_json::to_string_pretty(&self.state.session.get_chat_history()).map_err(std::io::Error::other)?;
+        std::fs::write(folder.join(CHAT_FILE), json)?;
+        Ok(path)
+    }
+
+    /// Allow or forbid drawing from an address.
+    async fn set_read_only(&self, address: IpAddr, read_only: bool) {
+        self.state.set_read_only(address, read_only).await;
+        let mut settings = self.settings.lock();
+        settings.read_only.retain(|a| *a != address);
+        if read_only {
+            settings.read_only.push(address);
+        }
+    }
+
+    /// Ban or unban an address, returns the number of kicked connections.
+    async fn set_banned(&self, address: IpAddr, banned: bool) -> usize {
+        let kicked = if banned {
+            self.state.ban(address).await
+        } else {
+            self.state.unban(address).await;
+            0
+        };
+        let mut settings = self.settings.lock();
+        settings.banned.retain(|a| *a != address);
+        if banned {
+            settings.banned.push(address);
+        }
+        kicked
+    }
+
+    /// Disconnect every user and stop the autosave timer.
+    async fn close(&self) {
+        if let Some(task) = self.autosave_task.lock().take() {
+            task.abort();
+        }
+        for user in self.users() {
+            self.state.kick(user.id).await;
+        }
+    }
+}
+
+/// All rooms of a server and their persistent configuration.
+pub struct RoomHost {
+    data_dir: PathBuf,
+    rooms: RwLock<HashMap<String, Arc<Room>>>,
+    default_room: RwLock<Option<String>>,
+}
+
+impl RoomHost {
+    /// Open the rooms listed in `rooms.toml` of `data_dir`, a missing file starts without rooms.
+    pub async fn open(data_dir: impl Into<PathBuf>) -> Result<Arc<Self>, ServerError> {
+        let data_dir = data_dir.into();
+        let rooms_path = data_dir.join(ROOMS_FILE);
+        let file = if rooms_path.exists() {
+            let text = std::fs::read_to_string(&rooms_path).map_err(|e| ServerError::ServerError(format!("{}: {}", rooms_path.display(), e)))?;
+            toml::from_str::<RoomsFile>(&text).map_err(|e| ServerError::ServerError(format!("{}: {}", rooms_path.display(), e)))?
+        } else {
+            RoomsFile::default()
+        };
+
+        let mut rooms = HashMap::new();
+        let mut migrated = false;
+        for mut settings in file.rooms {
+            if !is_valid_room_name(&settings.name) || rooms.contains_key(&settings.name) {
+                log::error!("Skipping room with invalid or duplicate name '{}'", settings.name);
+                continue;
+            }
+            migrated |= settings.migrate_password();
+            let room = Room::open(settings, &data_dir).await;
+            rooms.insert(room.name(), room);
+        }
+
+        let host = Arc::new(Self {
+            data_dir,
+            rooms: RwLock::new(rooms),
+            default_room: RwLock::new(file.default_room),
+        });
+        if migrated {
+            // don't keep the plain text passwords around
+            host.write_rooms_file()?;
+        }
+        Ok(host)
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    pub fn room(&self, name: &str) -> Option<Arc<Room>> {
+        self.rooms.read().get(name).cloned()
+    }
+
+    /// All rooms sorted by name.
+    pub fn rooms(&self) -> Vec<Arc<Room>> {
+        let mut rooms: Vec<_> = self.rooms.read().values().cloned().collect();
+        rooms.sort_by_key(|room| room.name());
+        rooms
+    }
+
+    /// Room for a WebSocket request path.
+    pub fn room_for_path(&self, path: &str) -> Option<Arc<Room>> {
+        let name = path.trim_matches('/');
+        if name.is_empty() {
+            let default_room = self.default_room.read().clone()?;
+            return self.room(&default_room);
+        }
+        self.room(name)
+    }
+
+    pub fn default_room(&self) -> Option<String> {
+        self.default_room.read().clone()
+    }
+
+    /// Set the room clients without a path join.
+    pub fn set_default_room(&self, name: Option<String>) -> Result<(), ServerError> {
+        if let Some(name) = &name {
+            if self.room(name).is_none() {
+                return Err(ServerError::SessionError(format!("Unknown room '{name}'")));
+            }
+        }
+        *self.default_room.write() = name;
+        self.write_rooms_file()
+    }
+
+    /// Add a room and store it in `rooms.toml`.
+    pub async fn create_room(&self, settings: RoomSettings) -> Result<Arc<Room>, ServerError> {
+        if !is_valid_room_name(&settings.name) {
+            return Err(ServerError::SessionError(format!("Invalid room name '{}'", settings.name)));
+        }
+        if self.room(&settings.name).is_some() {
+            return Err(ServerError::SessionError(format!("Room '{}' already exists", settings.name)));
+        }
+        let room = Room::open(settings, &self.data_dir).await;
+        self.rooms.write().insert(room.name(), room.clone());
+        self.write_rooms_file()?;
+        Ok(room)
+    }
+
+    /// Save, close and forget a room. Its folder with the autosaves is kept.
+    pub async fn remove_room(&self, name: &str) -> Result<(), ServerError> {
+        let Some(room) = self.rooms.write().remove(name) else {
+            return Err(ServerError::SessionError(format!("Unknown room '{name}'")));
+        };
+        if let Err(e) = room.save().await {
+            log::error!("Room '{}': failed to save: {}", name, e);
+        }
+        room.close().await;
+        if self.default_room.read().as_deref() == Some(name) {
+            *self.default_room.write() = None;
+        }
+        self.write_rooms_file()
+    }
+
+    /// Allow or forbid drawing from an address in a room, the setting is kept across restarts.
+    pub async fn set_read_only(&self, room: &str, address: IpAddr, read_only: bool) -> Result<(), ServerError> {
+        let Some(room) = self.room(room) else {
+            return Err(ServerError::SessionError(format!("Unknown room '{room}'")));
+        };
+        room.set_read_only(address, read_only).await;
+        self.write_rooms_file()
+    }
+
+    /// Ban or unban an address in a room, the setting is kept across restarts. Returns the number of kicked connections.
+    pub async fn set_banned(&self, room: &str, address: IpAddr, banned: bool) -> Result<usize, ServerError> {
+        let Some(room) = self.room(room) else {
+            return Err(ServerError::SessionError(format!("Unknown room '{room}'")));
+        };
+        let kicked = room.set_banned(address, banned).await;
+        self.write_rooms_file()?;
+        Ok(kicked)
+    }
+
+    /// Save all rooms, returns the number of rooms that failed to save.
+    pub async fn save_all(&self) -> usize {
+        let mut failed = 0;
+        for room in self.rooms() {
+            if let Err(e) = room.save().await {
+                log::error!("Room '{}': failed to save: {}", room.name(), e);
+                failed += 1;
+            }
+        }
+        failed
+    }
+
+    fn write_rooms_file(&self) -> Result<(), ServerError> {
+        let file = RoomsFile {
+            default_room: self.default_room(),
+            rooms: self.rooms().iter().map(|room| room.settings()).collect(),
+        };
+        let text = toml::to_string_pretty(&file).map_err(|e| ServerError::ServerError(e.to_string()))?;
+        std::fs::create_dir_all(&self.data_dir).map_err(|e| ServerError::ServerError(e.to_string()))?;
+        std::fs::write(self.data_dir.join(ROOMS_FILE), text).map_err(|e| ServerError::ServerError(e.to_string()))
+    }
+
+    /// Accept connections until the listener fails.
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> Result<(), ServerError> {
+        loop {
+            let (stream, addr) = listener.accept().await.map_err(|e| ServerError::ServerError(e.to_string()))?;
+            let host = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = host.handle_connection(stream, addr).await {
+                    log::error!("[{}] Connection error: {}", addr, e);
+                }
+            });
+        }
+    }
+
+    /// Refuse the WebSocket handshake for unknown rooms, otherwise serve the client in its room.
+    async fn handle_connection(&self, stream: TcpStream, addr: SocketAddr) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(request) = peek_request(&stream).await? {
+            if !request.websocket {
+                let room = self.room_for_path(request.route().0);
+                serve_http(stream, &request, room.as_ref().map(|room| room.state.as_ref())).await?;
+                return Ok(());
+            }
+        }
+
+        let mut room = None;
+        let callback = |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+            match self.room_for_path(request.uri().path()) {
+                Some(found) => {
+                    room = Some(found);
+                    Ok(response)
+                }
+                None => {
+                    let mut error = ErrorResponse::new(Some("Unknown room".to_string()));
+                    *error.status_mut() = StatusCode::NOT_FOUND;
+                    Err(error)
+                }
+            }
+        };
+        let ws_stream = tokio_tungstenite::accept_hdr_async(stream, callback).await?;
+        let Some(room) = room else {
+            return Ok(());
+        };
+        log::info!("[{}] Joined room '{}'", addr, room.name());
+        serve_client(room.state.clone(), ws_stream, addr).await
+    }
+}
@@ -17,24 +17,36 @@
 //!
 //! - **DRAW**: `broadcast()` - All clients see drawing updates (including web viewers)
 //! - **CHAT**: `broadcast_to_registered()` - Only registered users receive chat messages
-//! - **JOIN/LEAVE**: `broadcast_to_registered()` - Only registered users get notified
+//! - **JOIN/LEAVE, CURSOR, HIDE_CURSOR**: `broadcast()` - Web guests need them for the spectator cursors
 //! - **STATUS**: `broadcast_to_registered_including_self()` - Status echoed back to sender
 //! - **SAUCE, ICE_COLORS, USE_9PX_FONT, CHANGE_FONT, SET_CANVAS_SIZE**: `broadcast()` - Document settings to all
 //!
 //! Web clients (guests) are identified by an empty nickname and receive status `WEB=3`.
-//! They receive drawing updates but not chat, join/leave notifications, or status changes.
+//! They receive drawing updates and presence but not chat, selections or status changes.
+//! Plain HTTP requests on the server port get the spectator page, see [`super::spectator`].
+//!
+//! ## Protocol V3
+//!
+//! Clients announce their highest `protocol_version` on connect; the server answers with
+//! the negotiated one (V1 when `enable_extended_protocol` is off). Palette, layer, font slot,
+//! tag and attribute messages are relayed to V3 clients only, and draws on layers above 0
+//! only reach V2+ clients. The server document is the first layer: it stores palette, fonts,
+//! tags and attributes for late joiners but only relays layer operations. Notices like
+//! "you are read-only" are sent to V3 clients as codes the client localizes.
 
 use futures_util::{SinkExt, StreamExt};
-use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio::sync::{broadcast, mpsc, Notify, RwLock};
 use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
 
 use super::compression::compress_moebius_data;
 use super::protocol::*;
 use super::session::{Session, SessionEvent, SharedSession, UserId};
+use super::spectator::{peek_request, serve_http};
 use crate::SauceMetaData;
 
 // ANSI color codes for server output
@@ -81,6 +93,8 @@ pub struct ServerConfig {
     pub bind_addr: SocketAddr,
     /// Session password (empty for no password)
     pub password: String,
+    /// Salted hash from [`super::password::hash_password`], checked instead of `password` when set
+    pub password_hash: String,
     /// Maximum users allowed (0 for unlimited)
     pub max_users: usize,
     /// Initial document columns
@@ -105,6 +119,8 @@ pub struct ServerConfig {
     pub palette: [[u8; 3]; 16],
     /// SAUCE metadata
     pub sauce: SauceMetaData,
+    /// Initial state for V3 clients, an empty palette falls back to `palette`
+    pub extended: ExtendedDocument,
 
     /// Autosave configuration
     pub autosave: super::autosave::AutosaveConfig,
@@ -135,6 +151,7 @@ impl Default for ServerConfig {
         Self {
             bind_addr: "127.0.0.1:8080".parse().unwrap(),
             password: String::new(),
+            password_hash: String::new(),
             max_users: 0,
             columns: 80,
             rows: 25,
@@ -164,6 +181,7 @@ impl Default for ServerConfig {
                 [0xFF, 0xFF, 0xFF], // 15: White
             ],
             sauce: SauceMetaData::default(),
+            extended: ExtendedDocument::default(),
             autosave: super::autosave::AutosaveConfig::default(),
             // Default English UI strings
             ui_title: "icy_draw Collaboration Server".to_string(),
@@ -179,6 +197,52 @@ impl Default for ServerConfig {
     }
 }
 
+impl ServerConfig {
+    /// Use `buffer` as the initial document, including its size, font, palette and ice mode.
+    ///
+    /// The Moebius protocol only knows palette indices, rgb colors fall back to light gray on black.
+    pub fn set_document(&mut self, buffer: &icy_engine::TextBuffer, sauce: SauceMetaData) {
+        use icy_engine::{AttributeColor, TextPane};
+
+        let columns = buffer.width().max(0) as u32;
+        let rows = buffer.height().max(0) as u32;
+        let to_index = |color: AttributeColor, default: u8| match color {
+            AttributeColor::Palette(n) | AttributeColor::ExtendedPalette(n) => n,
+            AttributeColor::Rgb(_, _, _) => default,
+            AttributeColor::Transparent => 0,
+        };
+
+        // Column-major like the server document
+        let mut document = Vec::with_capacity(columns as usize);
+        for col in 0..columns as i32 {
+            let mut column = Vec::with_capacity(rows as usize);
+            for row in 0..rows as i32 {
+                let ch = buffer.char_at((col, row).into());
+                column.push(Block {
+                    code: ch.ch as u32,
+                    fg: to_index(ch.attribute.foreground_color(), 7),
+                    bg: to_index(ch.attribute.background_color(), 0),
+                });
+            }
+            document.push(column);
+        }
+
+        for (i, color) in self.palette.iter_mut().enumerate() {
+            let (r, g, b) = buffer.palette.rgb(i as u32);
+            *color = [r, g, b];
+        }
+        self.columns = columns;
+        self.rows = rows;
+        self.initial_document = Some(document);
+        self.ice_colors = matches!(buffer.ice_mode, icy_engine::IceMode::Ice);
+        self.use_9px_font = false;
+        self.font_name = buffer.font(0).map_or_else(|| "IBM VGA".to_string(), |f| f.name().to_string());
+        self.sauce = sauce;
+        self.extended = ExtendedDocument::from_buffer(buffer);
+        self.extended.layers = buffer.layers.clone();
+    }
+}
+
 /// Server state tracking connected clients.
 #[derive(Debug)]
 pub struct ServerState {
@@ -192,12 +256,28 @@ pub struct ServerState {
     event_tx: broadcast::Sender<SessionEvent>,
     /// Server configuration
     pub(crate) config: ServerConfig,
+    /// Addresses that may watch and chat but not change the document
+    read_only: RwLock<HashSet<IpAddr>>,
+    /// Addresses that are refused on connect
+    banned: RwLock<HashSet<IpAddr>>,
+    /// Remote address of every connected client
+    addresses: RwLock<HashMap<UserId, IpAddr>>,
+    /// Signals that close the connection of a kicked user
+    kick_signals: RwLock<HashMap<UserId, Arc<Notify>>>,
+    /// Read-only users that were already told their changes are refused
+    read_only_notified: RwLock<HashSet<UserId>>,
+    /// Negotiated protocol version per connected client
+    protocol_versions: RwLock<HashMap<UserId, ProtocolVersion>>,
+    /// Palette, fonts, tags, attributes and layers for V3 clients
+    extended: RwLock<ExtendedDocument>,
 }
 
 impl ServerState {
     /// Create a new server state.
     pub fn new(config: ServerConfig) -> Arc<Self> {
-        let session = Arc::new(Session::with_dimensions(config.password.clone(), config.columns, config.rows));
+        let mut session = Session::with_dimensions(config.password.clone(), config.columns, config.rows);
+        session.password_hash = config.password_hash.clone();
+        let session = Arc::new(session);
 
         // Apply initial settings
         session.set_ice_colors(config.ice_colors);
@@ -217,6 +297,11 @@ impl ServerState {
             doc
         };
 
+        let mut extended = config.extended.clone();
+        if extended.palette.is_empty() {
+            extended.palette = config.palette.to_vec();
+        }
+
         let (event_tx, _) = broadcast::channel(1024);
 
         Arc::new(Self {
@@ -225,6 +310,13 @@ impl ServerState {
             clients: RwLock::new(HashMap::new()),
             event_tx,
             config,
+            read_only: RwLock::new(HashSet::new()),
+            banned: RwLock::new(HashSet::new()),
+            addresses: RwLock::new(HashMap::new()),
+            kick_signals: RwLock::new(HashMap::new()),
+            read_only_notified: RwLock::new(HashSet::new()),
+            protocol_versions: RwLock::new(HashMap::new()),
+            extended: RwLock::new(extended),
         })
     }
 
@@ -260,7 +352,7 @@ impl ServerState {
             author: sauce.author,
             group: sauce.group,
             date: String::new(),
-            palette: self.get_palette_json(),
+            palette: self.get_palette_json().await,
             font_name: self.session.font(),
             ice_colors: self.session.get_ice_colors(),
             use_9px_font: self.session.get_use_9px(),
@@ -270,11 +362,13 @@ impl ServerState {
     }
 
     /// Convert the palette to JSON format for Moebius protocol.
-    /// Returns an array of 16 {r, g, b} objects.
-    fn get_palette_json(&self) -> serde_json::Value {
-        let palette_array: Vec<serde_json::Value> = self
-            .config
-            .palette
+    /// Returns an array of 16 {r, g, b} objects, the first colors of the current palette.
+    async fn get_palette_json(&self) -> serde_json::Value {
+        let mut palette = self.config.palette;
+        for (dst, src) in palette.iter_mut().zip(self.extended.read().await.palette.iter()) {
+            *dst = *src;
+        }
+        let palette_array: Vec<serde_json::Value> = palette
             .iter()
             .map(|[r, g, b]| {
                 serde_json::json!({
@@ -339,6 +433,7 @@ impl ServerState {
             }
             column.truncate(new_rows as usize);
         }
+        self.extended.write().await.resize_layers(new_columns, new_rows);
 
         self.session.set_dimensions(new_columns, new_rows);
     }
@@ -351,14 +446,103 @@ impl ServerState {
     /// Unregister a client connection.
     pub async fn unregister_client(&self, user_id: UserId) {
         self.clients.write().await.remove(&user_id);
+        self.kick_signals.write().await.remove(&user_id);
+        self.read_only_notified.write().await.remove(&user_id);
+        self.protocol_versions.write().await.remove(&user_id);
+        self.addresses.write().await.remove(&user_id);
         self.session.remove_user(user_id);
     }
 
+    /// Remote address of a connected user.
+    pub async fn address(&self, user_id: UserId) -> Option<IpAddr> {
+        self.addresses.read().await.get(&user_id).copied()
+    }
+
+    /// Allow or forbid document changes from an address, takes effect immediately.
+    ///
+    /// The address is used instead of the nickname so a reconnect under another name doesn't lift it.
+    pub async fn set_read_only(&self, address: IpAddr, read_only: bool) {
+        let mut addresses = self.read_only.write().await;
+        if read_only {
+            addresses.insert(address);
+        } else {
+            addresses.remove(&address);
+        }
+    }
+
+    /// Check if the user's document changes are ignored.
+    pub async fn is_read_only(&self, user_id: UserId) -> bool {
+        match self.address(user_id).await {
+            Some(address) => self.read_only.read().await.contains(&address),
+            None => false,
+        }
+    }
+
+    /// Refuse new connections from an address and kick its connected users, returns the number of kicked connections.
+    pub async fn ban(&self, address: IpAddr) -> usize {
+        self.banned.write().await.insert(address);
+        let users: Vec<UserId> = self.addresses.read().await.iter().filter(|(_, a)| **a == address).map(|(id, _)| *id).collect();
+        let mut kicked = 0;
+        for id in users {
+            if self.kick(id).await {
+                kicked += 1;
+            }
+        }
+        kicked
+    }
+
+    /// Accept connections from a banned address again, returns false if it wasn't banned.
+    pub async fn unban(&self, address: IpAddr) -> bool {
+        self.banned.write().await.remove(&address)
+    }
+
+    /// Check if connections from the address are refused.
+    pub async fn is_banned(&self, address: IpAddr) -> bool {
+        self.banned.read().await.contains(&address)
+    }
+
+    /// Tell a read-only user that a document change was refused.
+    ///
+    /// Draws on the base layer are reverted by sending the server's cell back. Other changes can't be
+    /// sent back piecewise, so V3 clients get a [`ServerNotice::ReadOnly`] once per connection and
+    /// show it in their own language.
+    pub async fn refuse_change(&self, user_id: UserId, code: ActionCode, data: &serde_json::Value) {
+        if code == ActionCode::Draw && data.get("layer").and_then(|v| v.as_u64()).unwrap_or(0) == 0 {
+            let x = data.get("x").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+            let y = data.get("y").and_then(|v| v.as_i64()).unwrap_or(0) as i32;
+            if let Some(block) = self.char_at(x, y).await {
+                let msg = DrawMessage::new(0, x, y, block);
+                self.send_to(user_id, &serde_json::to_string(&msg).unwrap()).await;
+            }
+        }
+
+        if self.protocol_version(user_id).await >= ActionCode::Notice.min_protocol_version() && self.read_only_notified.write().await.insert(user_id) {
+            let msg = NoticeMessage {
+                msg_type: ActionCode::Notice as u8,
+                data: NoticeData {
+                    notice: ServerNotice::ReadOnly,
+                },
+            };
+            self.send_to(user_id, &serde_json::to_string(&msg).unwrap()).await;
+        }
+    }
+
+    /// Close the connection of a user, returns false if the user isn't connected.
+    pub async fn kick(&self, user_id: UserId) -> bool {
+        match self.kick_signals.read().await.get(&user_id) {
+            Some(signal) => {
+                signal.notify_one();
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Broadcast a message to all clients except the sender.
     ///
     /// Equivalent to Moebius `send_all_including_guests(ws, type, data)`.
     ///
-    /// Used for: DRAW, SAUCE, ICE_COLORS, USE_9PX_FONT, CHANGE_FONT, SET_CANVAS_SIZE
+    /// Used for: DRAW, SAUCE, ICE_COLORS, USE_9PX_FONT, CHANGE_FONT, SET_CANVAS_SIZE, JOIN, LEAVE, CURSOR, HIDE_CURSOR
     ///
     /// Web clients (guests with status=WEB) receive these messages so they can
     /// see real-time drawing updates even without being logged in.
@@ -375,7 +559,7 @@ impl ServerState {
     ///
     /// Equivalent to Moebius `send_all(ws, type, data)`.
     ///
-    /// Used for: CHAT, JOIN, LEAVE
+    /// Used for: CHAT, SELECTION
     ///
     /// Web clients (guests with status=WEB) do NOT receive these messages.
     /// Only users with a valid nickname (status != WEB) are included.
@@ -407,6 +591,37 @@ impl ServerState {
         }
     }
 
+    /// Broadcast a message to clients that negotiated at least `version`, except the sender.
+    ///
+    /// Used for: V3 document messages, draws on layers above 0
+    pub async fn broadcast_to_version(&self, message: &str, version: ProtocolVersion, except: Option<UserId>) {
+        let versions = self.protocol_versions.read().await;
+        let clients = self.clients.read().await;
+        for (id, sender) in clients.iter() {
+            if except != Some(*id) && versions.get(id).is_some_and(|v| *v >= version) {
+                let _ = sender.send(message.to_string()).await;
+            }
+        }
+    }
+
+    /// Protocol version negotiated with a client, V1 for unknown users.
+    pub async fn protocol_version(&self, user_id: UserId) -> ProtocolVersion {
+        self.protocol_versions.read().await.get(&user_id).copied().unwrap_or_default()
+    }
+
+    /// Snapshot of the state V3 clients get on connect.
+    pub async fn extended_document(&self) -> ExtendedDocument {
+        self.extended.read().await.clone()
+    }
+
+    /// Font of the spectator page: the named font, else the first font slot, else IBM VGA.
+    pub async fn spectator_font(&self) -> icy_engine::BitFont {
+        if let Ok(font) = icy_engine::BitFont::from_sauce_name(&self.session.font()) {
+            return font;
+        }
+        self.extended.read().await.fonts.first().map(|slot| slot.font.clone()).unwrap_or_default()
+    }
+
     /// Broadcast a message to all clients.
     pub async fn broadcast_all(&self, message: &str) {
         self.broadcast(message, None).await;
@@ -438,6 +653,19 @@ impl ServerState {
     /// - Web viewers are identified by a missing `nick` field (server-side `is_web_client=true`).
     /// - Guests can have an empty nickname (""), but are still registered users.
     pub async fn handle_connect(&self, nick: String, password: String, is_web_client: bool) -> Result<(UserId, String), String> {
+        self.handle_connect_with_version(nick, password, is_web_client, ProtocolVersion::V1).await
+    }
+
+    /// Handle a connect request from a client that speaks up to `requested`.
+    ///
+    /// The session uses the lower of both versions, V1 if the extended protocol is disabled.
+    pub async fn handle_connect_with_version(
+        &self,
+        nick: String,
+        password: String,
+        is_web_client: bool,
+        requested: ProtocolVersion,
+    ) -> Result<(UserId, String), String> {
         // Check password
         if !self.session.check_password(&password) {
             let response = RefusedResponse {
@@ -463,16 +691,24 @@ impl ServerState {
 
         // Add user
         let user_id = self.session.add_user(nick.clone());
+        let protocol_version = if self.config.enable_extended_protocol && !is_web_client {
+            requested.min(ProtocolVersion::LATEST)
+        } else {
+            ProtocolVersion::V1
+        };
+        self.protocol_versions.write().await.insert(user_id, protocol_version);
 
         // Build connected response
         // Moebius sends different payloads for web clients vs regular clients.
         let response_json = if is_web_client {
-            // Web client: minimal response (only {id, doc})
+            // Web client: minimal response ({id, doc}), plus the users for the spectator cursors
+            let users: Vec<User> = existing_users.into_iter().filter(|u| u.status != super::state::user_status::WEB).collect();
             serde_json::json!({
                 "type": ActionCode::Connected as u8,
                 "data": {
                     "id": user_id,
-                    "doc": self.get_compressed_document().await
+                    "doc": self.get_compressed_document().await,
+                    "users": users
                 }
             })
         } else {
@@ -485,6 +721,12 @@ impl ServerState {
                     users: existing_users,
                     chat_history,
                     status: 0, // ACTIVE status
+                    protocol_version,
+                    extended: if protocol_version >= ProtocolVersion::V3 {
+                        Some(self.extended_document().await)
+                    } else {
+                        None
+                    },
                 },
             };
             serde_json::to_value(&response).unwrap()
@@ -494,13 +736,19 @@ impl ServerState {
     }
 
     /// Handle a draw message.
+    ///
+    /// Only draws on the first layer change the Moebius document. Draws on the other layers are
+    /// relayed to V3 clients, older clients don't get the layer operations and don't have them.
     pub async fn handle_draw(&self, user_id: UserId, msg: DrawMessage) {
-        // Update document
-        self.set_char(msg.data.x, msg.data.y, msg.data.block.clone()).await;
-
-        // Broadcast to other clients
         let broadcast_msg = serde_json::to_string(&msg).unwrap();
-        self.broadcast(&broadcast_msg, Some(user_id)).await;
+        let layer = msg.data.layer.unwrap_or(0);
+        self.extended.write().await.draw(layer, msg.data.x, msg.data.y, &msg.data.block);
+        if layer == 0 {
+            self.set_char(msg.data.x, msg.data.y, msg.data.block.clone()).await;
+            self.broadcast(&broadcast_msg, Some(user_id)).await;
+        } else {
+            self.broadcast_to_version(&broadcast_msg, ProtocolVersion::V3, Some(user_id)).await;
+        }
 
         // Emit event
         self.emit_event(SessionEvent::Draw {
@@ -515,7 +763,7 @@ impl ServerState {
     pub async fn handle_cursor(&self, user_id: UserId, col: i32, row: i32) {
         self.session.update_cursor(user_id, col, row);
 
-        // Moebius forwards cursor updates via `send_all` (registered users only, excluding sender)
+        // Moebius forwards cursor updates via `send_all`, we include web guests for the spectator page
         // Wire format: {"type": 4, "data": {"id": <u32>, "x": <i32>, "y": <i32>}}
         #[derive(serde::Serialize)]
         struct CursorBroadcast {
@@ -535,11 +783,110 @@ impl ServerState {
             data: CursorBroadcastData { id: user_id, x: col, y: row },
         };
         let json = serde_json::to_string(&msg).unwrap();
-        self.broadcast_to_registered(&json, Some(user_id)).await;
+        self.broadcast(&json, Some(user_id)).await;
 
         self.emit_event(SessionEvent::CursorMoved { id: user_id, col, row });
     }
 
+    /// Handle a V3 document message: remember what late joiners need and relay it to V3 clients.
+    ///
+    /// Messages from clients that didn't negotiate V3 or that don't parse are dropped.
+    pub async fn handle_document_message(&self, user_id: UserId, code: ActionCode, mut data: serde_json::Value) {
+        if self.protocol_version(user_id).await < ProtocolVersion::V3 {
+            return;
+        }
+
+        // The layer stack starts from the Moebius document when the server wasn't given one
+        let base_layer = if code == ActionCode::AddLayer && self.extended.read().await.layers.is_empty() {
+            Some(self.base_layer().await)
+        } else {
+            None
+        };
+
+        let valid = {
+            let mut guard = self.extended.write().await;
+            let extended = &mut *guard;
+            let layers = &mut extended.layers;
+            match code {
+                ActionCode::SetPalette => serde_json::from_value::<SetPaletteData>(data.clone())
+                    .map(|d| extended.palette = d.colors)
+                    .is_ok(),
+                ActionCode::SetFontSlot => serde_json::from_value::<SetFontSlotData>(data.clone())
+                    .map(|d| {
+                        extended.fonts.retain(|f| f.slot != d.slot);
+                        if let Some(font) = d.font {
+                            extended.fonts.push(FontSlot { slot: d.slot, font });
+                            extended.fonts.sort_by_key(|f| f.slot);
+                        }
+                    })
+                    .is_ok(),
+                ActionCode::SetTags => serde_json::from_value::<SetTagsData>(data.clone()).map(|d| extended.tags = d.tags).is_ok(),
+                ActionCode::SetAttributes => serde_json::from_value::<SetAttributesData>(data.clone())
+                    .map(|d| extended.attributes = Some(d.attributes))
+                    .is_ok(),
+                ActionCode::AddLayer => serde_json::from_value::<AddLayerData>(data.clone())
+                    .map(|d| {
+                        if layers.is_empty() {
+                            layers.extend(base_layer);
+                        }
+                        layers.insert(d.index.min(layers.len()), d.layer);
+                    })
+                    .is_ok(),
+                ActionCode::RemoveLayer => serde_json::from_value::<RemoveLayerData>(data.clone())
+                    .map(|d| {
+                        if d.index < layers.len() {
+                            layers.remove(d.index);
+                        }
+                    })
+                    .is_ok(),
+                ActionCode::ReorderLayer => serde_json::from_value::<ReorderLayerData>(data.clone())
+                    .map(|d| {
+                        if d.from < layers.len() && d.to < layers.len() {
+                            let layer = layers.remove(d.from);
+                            layers.insert(d.to, layer);
+                        }
+                    })
+                    .is_ok(),
+                ActionCode::SetLayerProperties => serde_json::from_value::<SetLayerPropertiesData>(data.clone())
+                    .map(|d| {
+                        if let Some(layer) = layers.get_mut(d.index) {
+                            layer.properties = d.properties;
+                        }
+                    })
+                    .is_ok(),
+                _ => false,
+            }
+        };
+        if !valid {
+            log::debug!("Ignoring malformed {:?} from user {}", code, user_id);
+            return;
+        }
+
+        data["id"] = serde_json::json!(user_id);
+        let msg = serde_json::json!({ "type": code as u8, "data": data });
+        self.broadcast_to_version(&msg.to_string(), ProtocolVersion::V3, Some(user_id)).await;
+    }
+
+    /// The Moebius document as a layer, the way clients build their first layer from it.
+    async fn base_layer(&self) -> icy_engine::Layer {
+        let (columns, rows) = self.session.get_dimensions();
+        let mut layer = icy_engine::Layer::new("Layer 1", (columns as i32, rows as i32));
+        layer.preallocate_lines(columns as i32, rows as i32);
+        let doc = self.document.read().await;
+        for (col, column) in doc.iter().enumerate().take(columns as usize) {
+            for (row, block) in column.iter().enumerate().take(rows as usize) {
+                let mut ch = icy_engine::AttributedChar {
+                    ch: char::from_u32(block.code).unwrap_or(' '),
+                    ..Default::default()
+                };
+                ch.attribute.set_foreground(u32::from(block.fg));
+                ch.attribute.set_background(u32::from(block.bg));
+                layer.set_char((col as i32, row as i32), ch);
+            }
+        }
+        layer
+    }
+
     /// Handle a chat message.
     pub async fn handle_chat(&self, user_id: UserId, text: String) {
         let user = self.session.get_user(user_id);
@@ -885,10 +1232,32 @@ async fn handle_connection(state: Arc<ServerState>, stream: TcpStream, addr: Soc
     use anstream::println;
     use colors::*;
 
+    if let Some(request) = peek_request(&stream).await? {
+        if !request.websocket {
+            // A single session only lives at the root path
+            let found = request.route().0.is_empty().then_some(state.as_ref());
+            serve_http(stream, &request, found).await?;
+            return Ok(());
+        }
+    }
+
     println!("{BLUE}[{addr}]{RESET} New connection", BLUE = BLUE, addr = addr, RESET = RESET);
 
     let ws_stream = tokio_tungstenite::accept_async(stream).await?;
+    serve_client(state, ws_stream, addr).await
+}
+
+/// Exchange messages with a client until it disconnects or is kicked.
+pub(crate) async fn serve_client(
+    state: Arc<ServerState>,
+    ws_stream: WebSocketStream<TcpStream>,
+    addr: SocketAddr,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use anstream::println;
+    use colors::*;
+
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+    let kick = Arc::new(Notify::new());
 
     // Channel for outgoing messages
     let (tx, mut rx) = mpsc::channel::<String>(256);
@@ -907,7 +1276,17 @@ async fn handle_connection(state: Arc<ServerState>, stream: TcpStream, addr: Soc
     });
 
     // Handle incoming messages
-    while let Some(msg_result) = ws_receiver.next().await {
+    loop {
+        let msg_result = tokio::select! {
+            msg = ws_receiver.next() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            () = kick.notified() => {
+                println!("{YELLOW}[{addr}]{RESET} Kicked", YELLOW = YELLOW, addr = addr, RESET = RESET);
+                break;
+            }
+        };
         let msg = match msg_result {
             Ok(m) => m,
             Err(e) => {
@@ -919,9 +1298,17 @@ async fn handle_connection(state: Arc<ServerState>, stream: TcpStream, addr: Soc
         match msg {
             Message::Text(text) => {
                 let text_str: &str = text.as_ref();
+                let was_connected = user_id.is_some();
                 if let Err(e) = handle_message(&state, &tx, &mut user_id, &mut user_nick, text_str, addr).await {
                     log::warn!("[{}] Message handling error: {}", addr, e);
                 }
+                if let (false, Some(id)) = (was_connected, user_id) {
+                    state.kick_signals.write().await.insert(id, kick.clone());
+                    // a ban during the connect didn't find the signal yet
+                    if state.is_banned(addr.ip()).await {
+                        kick.notify_one();
+                    }
+                }
             }
             Message::Close(_) => {
                 println!("{YELLOW}[{addr}]{RESET} Client requested close", YELLOW = YELLOW, addr = addr, RESET = RESET);
@@ -938,13 +1325,13 @@ async fn handle_connection(state: Arc<ServerState>, stream: TcpStream, addr: Soc
     if let Some(id) = user_id {
         state.unregister_client(id).await;
 
-        // Broadcast leave message to registered users only (Moebius uses send_all)
+        // Broadcast leave message to all clients, web guests drop the cursor
         let leave_msg = LeaveMessage {
             msg_type: ActionCode::Leave as u8,
             data: LeaveData { id },
         };
         if let Ok(json) = serde_json::to_string(&leave_msg) {
-            state.broadcast_to_registered(&json, Some(id)).await;
+            state.broadcast(&json, Some(id)).await;
         }
 
         let user_count = state.client_count().await;
@@ -985,6 +1372,14 @@ pub async fn handle_message(
 
     let data = json.get("data").cloned().unwrap_or(serde_json::Value::Null);
 
+    if let (Some(id), Some(Ok(code))) = (*user_id, action_code.map(ActionCode::try_from)) {
+        if code.changes_document() && state.is_read_only(id).await {
+            log::debug!("[{}] Refusing {:?} from read-only user {}", addr, code, user_nick);
+            state.refuse_change(id, code, &data).await;
+            return Ok(());
+        }
+    }
+
     match action_code {
         Some(0) => {
             // CONNECT request (client wants to join)
@@ -995,11 +1390,34 @@ pub async fn handle_message(
             let nick = nick_opt.unwrap_or("").to_string();
             let password = data.get("pass").and_then(|v| v.as_str()).unwrap_or("").to_string();
             let group = data.get("group").and_then(|v| v.as_str()).unwrap_or("").to_string();
+            let requested_version = data
+                .get("protocol_version")
+                .and_then(|v| serde_json::from_value::<ProtocolVersion>(v.clone()).ok())
+                .unwrap_or_default();
+
+            if state.is_banned(addr.ip()).await {
+                let response = RefusedResponse {
+                    msg_type: ActionCode::Refused as u8,
+                    data: serde_json::json!({"reason": "Banned"}),
+                };
+                let _ = tx.send(serde_json::to_string(&response)?).await;
+                println!(
+                    "{RED}[{addr}]{RESET} Banned address refused",
+                    RED = colors::RED,
+                    addr = addr,
+                    RESET = colors::RESET
+                );
+                return Ok(());
+            }
 
-            match state.handle_connect(nick.clone(), password, is_web_client).await {
+            match state
+                .handle_connect_with_version(nick.clone(), password, is_web_client, requested_version)
+                .await
+            {
                 Ok((id, response)) => {
                     *user_id = Some(id);
                     *user_nick = nick.clone();
+                    state.addresses.write().await.insert(id, addr.ip());
 
                     // Update user with group if provided
                     if !group.is_empty() {
@@ -1015,7 +1433,7 @@ pub async fn handle_message(
                     // Send CONNECTED response
                     let _ = tx.send(response).await;
 
-                    // Broadcast JOIN to other registered clients only (Moebius uses send_all)
+                    // Broadcast JOIN to all other clients, web guests track nicknames for the cursors
                     let join_msg = JoinMessage {
                         msg_type: ActionCode::Join as u8,
                         data: JoinData {
@@ -1026,7 +1444,7 @@ pub async fn handle_message(
                         },
                     };
                     if let Ok(json) = serde_json::to_string(&join_msg) {
-                        state.broadcast_to_registered(&json, Some(id)).await;
+                        state.broadcast(&json, Some(id)).await;
                     }
 
                     let user_count = state.client_count().await;
@@ -1125,8 +1543,8 @@ pub async fn handle_message(
                     data: HideCursorData { id },
                 };
                 if let Ok(json) = serde_json::to_string(&msg) {
-                    // Moebius default forwarding uses send_all (registered only, excluding sender)
-                    state.broadcast_to_registered(&json, Some(id)).await;
+                    // Web guests get it too, the spectator page shows cursors
+                    state.broadcast(&json, Some(id)).await;
                 }
             }
         }
@@ -1384,6 +1802,17 @@ pub async fn handle_message(
             }
         }
 
+        Some(100..=107) => {
+            // V3 document messages
+            if let (Some(id), Some(Ok(code))) = (*user_id, action_code.map(ActionCode::try_from)) {
+                state.handle_document_message(id, code, data).await;
+            }
+        }
+
+        Some(108) => {
+            // NOTICE only goes from the server to clients
+        }
+
         _ => {
             // Moebius behavior: forward unhandled actions via `send_all` (registered users only, excluding sender).
             // Exception: SET_BG is ignored in Moebius.
@@ -7,17 +7,23 @@ use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use super::password::verify_password;
 use super::protocol::{Block, ChatMessage, SauceData, ServerStatus, User};
 use crate::SauceMetaData;
 
 /// Unique identifier for a user in the session.
 pub type UserId = u32;
 
+/// Number of chat messages kept for users that join later, the same limit Moebius uses.
+pub const CHAT_HISTORY_LIMIT: usize = 32;
+
 /// Collaboration session state shared between connections.
 #[derive(Debug)]
 pub struct Session {
     /// Session password (empty string means no password)
     pub password: String,
+    /// Salted hash from [`hash_password`], checked instead of `password` when set
+    pub password_hash: String,
     /// Users currently in the session
     users: RwLock<HashMap<UserId, User>>,
     /// Next user ID to assign
@@ -44,6 +50,7 @@ impl Session {
     pub fn new(password: String) -> Self {
         Self {
             password,
+            password_hash: String::new(),
             users: RwLock::new(HashMap::new()),
             next_user_id: RwLock::new(1),
             chat_history: RwLock::new(Vec::new()),
@@ -68,6 +75,9 @@ impl Session {
 
     /// Check if the provided password matches.
     pub fn check_password(&self, password: &str) -> bool {
+        if !self.password_hash.is_empty() {
+            return verify_password(&self.password_hash, password);
+        }
         self.password.is_empty() || self.password == password
     }
 
@@ -181,7 +191,7 @@ impl Session {
     }
 
     /// Add a chat message to the history.
-    /// Moebius limits chat history to 32 messages.
+    /// The oldest messages are dropped beyond [`CHAT_HISTORY_LIMIT`].
     pub fn add_chat_message(&self, id: UserId, nick: String, text: String) {
         let msg = ChatMessage {
             id,
@@ -195,8 +205,7 @@ impl Session {
         };
         let mut history = self.chat_history.write();
         history.push(msg);
-        // Moebius limits chat history to 32 messages
-        if history.len() > 32 {
+        if history.len() > CHAT_HISTORY_LIMIT {
             history.remove(0);
         }
     }
@@ -206,6 +215,14 @@ impl Session {
         self.chat_history.read().clone()
     }
 
+    /// Replace the chat history, e.g. with the one saved before a server restart.
+    pub fn set_chat_history(&self, mut history: Vec<ChatMessage>) {
+        if history.len() > CHAT_HISTORY_LIMIT {
+            history.drain(..history.len() - CHAT_HISTORY_LIMIT);
+        }
+        *self.chat_history.write() = history;
+    }
+
     /// Set the server status.
     pub fn set_status(&self, id: u32, status: u8) {
         let mut s = self.status.write();
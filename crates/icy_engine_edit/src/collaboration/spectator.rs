@@ -0,0 +1,328 @@
+//! Read-only spectator web view.
+//!
+//! The server answers plain HTTP requests on its WebSocket port with a self-contained page that
+//! joins the session as web guest (a connect without `nick`) and renders the live canvas:
+//!
+//! | Request | Response |
+//! |---------|----------|
+//! | `GET /<room>` | The spectator page, it connects to `ws://host/<room>` |
+//! | `GET /<room>/font.png` | Glyph atlas of the session font, 16x16 glyphs, white on transparent |
+//!
+//! Web guests receive draws, document settings, joins, leaves and cursor updates but no chat.
+//! [`SpectatorView`] applies these messages the same way the page does and works as headless
+//! client.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use icy_engine::BitFont;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use super::compression::{uncompress_moebius_data, MoebiusCompressedData};
+use super::protocol::{parse_moebius_palette, ActionCode, Block, DEFAULT_EGA_PALETTE};
+use super::server::ServerState;
+use super::state::user_status;
+
+/// The spectator page, palette and document come over the WebSocket, the font from `font.png`.
+pub const SPECTATOR_PAGE: &str = include_str!("spectator.html");
+
+/// Path suffix of the glyph atlas
+pub const FONT_ATLAS_PATH: &str = "font.png";
+
+/// Requests with longer headers are dropped
+const MAX_REQUEST_HEAD: usize = 8192;
+
+/// Render the 256 glyphs of `font` into a 16x16 grid PNG, set pixels white, others transparent.
+///
+/// With `letter_spacing` glyphs are 9 pixels wide and the box drawing range `0xC0..=0xDF`
+/// repeats the 8th column like VGA does.
+pub fn glyph_atlas_png(font: &BitFont, letter_spacing: bool) -> Result<Vec<u8>, png::EncodingError> {
+    let glyph_width = font.width as usize + usize::from(letter_spacing);
+    let glyph_height = font.height as usize;
+    let width = glyph_width * 16;
+    let height = glyph_height * 16;
+
+    let mut rgba = vec![0u8; width * height * 4];
+    for (ch, glyph) in font.glyphs().iter().enumerate() {
+        let (cell_x, cell_y) = ((ch % 16) * glyph_width, (ch / 16) * glyph_height);
+        for y in 0..glyph_height {
+            for x in 0..glyph_width {
+                let set = if x < font.width as usize {
+                    glyph.get_pixel(x, y)
+                } else {
+                    (0xC0..=0xDF).contains(&ch) && glyph.get_pixel(font.width as usize - 1, y)
+                };
+                if set {
+                    let offset = ((cell_y + y) * width + cell_x + x) * 4;
+                    rgba[offset..offset + 4].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+                }
+            }
+        }
+    }
+
+    let mut png_data = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut png_data, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&rgba)?;
+    }
+    Ok(png_data)
+}
+
+/// Request line and the headers the server needs to route a connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct HttpRequest {
+    pub method: String,
+    /// Path without query string
+    pub path: String,
+    /// True for WebSocket handshakes
+    pub websocket: bool,
+    /// Length of the request head including the empty line
+    head_len: usize,
+}
+
+impl HttpRequest {
+    fn parse(head: &str) -> Option<Self> {
+        let mut lines = head.split("\r\n");
+        let mut request_line = lines.next()?.split_whitespace();
+        let method = request_line.next()?.to_string();
+        let target = request_line.next()?;
+        let path = target.split(['?', '#']).next().unwrap_or("/").to_string();
+        let websocket = lines.any(|line| {
+            line.split_once(':')
+                .is_some_and(|(name, value)| name.trim().eq_ignore_ascii_case("upgrade") && value.trim().eq_ignore_ascii_case("websocket"))
+        });
+        Some(Self {
+            method,
+            path,
+            websocket,
+            head_len: head.len(),
+        })
+    }
+
+    /// Split the path into the room path and whether the glyph atlas is requested.
+    pub fn route(&self) -> (&str, bool) {
+        let path = self.path.trim_end_matches('/');
+        match path.strip_suffix(FONT_ATLAS_PATH).and_then(|p| p.strip_suffix('/')) {
+            Some(room) => (room, true),
+            None => (path, false),
+        }
+    }
+}
+
+/// Read the request head without consuming it, so a WebSocket handshake can still take the stream.
+///
+/// Returns `None` if the connection closed or didn't send a valid request head in time.
+pub(crate) async fn peek_request(stream: &TcpStream) -> std::io::Result<Option<HttpRequest>> {
+    let mut buf = vec![0u8; MAX_REQUEST_HEAD];
+    for _ in 0..500 {
+        let len = stream.peek(&mut buf).await?;
+        if len == 0 {
+            return Ok(None);
+        }
+        if let Some(end) = buf[..len].windows(4).position(|w| w == b"\r\n\r\n") {
+            return Ok(std::str::from_utf8(&buf[..end + 4]).ok().and_then(HttpRequest::parse));
+        }
+        if len == buf.len() {
+            return Ok(None);
+        }
+        // peek returns what arrived so far, wait for the rest of the head
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    Ok(None)
+}
+
+/// Answer a plain HTTP request with the spectator page or the font of `state`, 404 without a room.
+pub(crate) async fn serve_http(mut stream: TcpStream, request: &HttpRequest, state: Option<&ServerState>) -> std::io::Result<()> {
+    let mut head = vec![0u8; request.head_len];
+    stream.read_exact(&mut head).await?;
+
+    let (_, atlas) = request.route();
+    let (status, content_type, body) = match state {
+        _ if request.method != "GET" && request.method != "HEAD" => ("405 Method Not Allowed", "text/plain", b"Method not allowed".to_vec()),
+        None => ("404 Not Found", "text/plain", b"Unknown room".to_vec()),
+        Some(state) if atlas => {
+            let font = state.spectator_font().await;
+            match glyph_atlas_png(&font, state.session.get_use_9px()) {
+                Ok(png) => ("200 OK", "image/png", png),
+                Err(e) => ("500 Internal Server Error", "text/plain", e.to_string().into_bytes()),
+            }
+        }
+        Some(_) => ("200 OK", "text/html; charset=utf-8", SPECTATOR_PAGE.as_bytes().to_vec()),
+    };
+
+    let header = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(header.as_bytes()).await?;
+    if request.method != "HEAD" {
+        stream.write_all(&body).await?;
+    }
+    stream.shutdown().await
+}
+
+/// Headless spectator: the session as the web page sees it.
+#[derive(Debug, Clone, Default)]
+pub struct SpectatorView {
+    /// Own user id, 0 before CONNECTED
+    pub user_id: u32,
+    pub columns: u32,
+    pub rows: u32,
+    /// Row-major cells of the first layer
+    pub blocks: Vec<Block>,
+    pub palette: [[u8; 3]; 16],
+    pub font_name: String,
+    pub ice_colors: bool,
+    pub use_9px_font: bool,
+    /// Nicknames of the registered users
+    pub users: HashMap<u32, String>,
+    /// Cursor positions of the registered users
+    pub cursors: HashMap<u32, (i32, i32)>,
+    /// Reason the server refused the connection
+    pub refused: Option<String>,
+}
+
+impl SpectatorView {
+    /// CONNECT message of a web guest: no nickname.
+    pub fn connect_message(password: &str) -> String {
+        serde_json::json!({ "type": ActionCode::Connected as u8, "data": { "pass": password } }).to_string()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.user_id != 0
+    }
+
+    pub fn block(&self, col: i32, row: i32) -> Option<&Block> {
+        if col < 0 || row < 0 || col as u32 >= self.columns || row as u32 >= self.rows {
+            return None;
+        }
+        self.blocks.get(row as usize * self.columns as usize + col as usize)
+    }
+
+    /// Apply a server message, unknown and malformed messages are ignored.
+    pub fn handle_message(&mut self, text: &str) {
+        let Ok(msg) = serde_json::from_str::<serde_json::Value>(text) else {
+            return;
+        };
+        let Some(code) = msg
+            .get("type")
+            .or_else(|| msg.get("action"))
+            .and_then(serde_json::Value::as_u64)
+            .and_then(|c| ActionCode::try_from(c as u8).ok())
+        else {
+            return;
+        };
+        // Resize messages carry their fields at the top level
+        let data = msg.get("data").unwrap_or(&msg);
+        let id = || data.get("id").and_then(serde_json::Value::as_u64).map(|id| id as u32);
+        let int = |key: &str| data.get(key).and_then(serde_json::Value::as_i64);
+        let flag = || data.get("value").and_then(serde_json::Value::as_bool).unwrap_or(false);
+
+        match code {
+            ActionCode::Connected => self.handle_connected(data),
+            ActionCode::Refused => {
+                let reason = data.get("reason").and_then(serde_json::Value::as_str).unwrap_or_default();
+                self.refused = Some(reason.to_string());
+            }
+            ActionCode::Join => {
+                let status = int("status").unwrap_or_default();
+                if let (Some(id), true) = (id(), status != i64::from(user_status::WEB)) {
+                    let nick = data.get("nick").and_then(serde_json::Value::as_str).unwrap_or_default();
+                    self.users.insert(id, nick.to_string());
+                }
+            }
+            ActionCode::Leave => {
+                if let Some(id) = id() {
+                    self.users.remove(&id);
+                    self.cursors.remove(&id);
+                }
+            }
+            ActionCode::Cursor => {
+                if let (Some(id), Some(x), Some(y)) = (id(), int("x"), int("y")) {
+                    self.cursors.insert(id, (x as i32, y as i32));
+                }
+            }
+            ActionCode::HideCursor => {
+                if let Some(id) = id() {
+                    self.cursors.remove(&id);
+                }
+            }
+            ActionCode::Draw => {
+                let layer = int("layer").unwrap_or(0);
+                let block = data.get("block").and_then(|b| serde_json::from_value::<Block>(b.clone()).ok());
+                if let (0, Some(x), Some(y), Some(block)) = (layer, int("x"), int("y"), block) {
+                    let (x, y) = (x as i32, y as i32);
+                    if self.block(x, y).is_some() {
+                        let index = y as usize * self.columns as usize + x as usize;
+                        self.blocks[index] = block;
+                    }
+                }
+            }
+            ActionCode::IceColors => self.ice_colors = flag(),
+            ActionCode::Use9pxFont => self.use_9px_font = flag(),
+            ActionCode::ChangeFont => {
+                if let Some(name) = data.get("font_name").and_then(serde_json::Value::as_str) {
+                    self.font_name = name.to_string();
+                }
+            }
+            ActionCode::SetCanvasSize => {
+                let columns = int("columns").map_or(self.columns, |c| c.max(0) as u32);
+                let rows = int("rows").map_or(self.rows, |r| r.max(0) as u32);
+                self.resize(columns, rows);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_connected(&mut self, data: &serde_json::Value) {
+        let Some(doc) = data.get("doc") else {
+            return;
+        };
+        let columns = doc.get("columns").and_then(serde_json::Value::as_u64).unwrap_or(80) as u32;
+        let rows = doc.get("rows").and_then(serde_json::Value::as_u64).unwrap_or(25) as u32;
+        let blocks = match doc.get("compressed_data") {
+            Some(compressed) => serde_json::from_value::<MoebiusCompressedData>(compressed.clone())
+                .ok()
+                .and_then(|c| uncompress_moebius_data(columns, rows, &c).ok()),
+            None => doc.get("data").and_then(|d| serde_json::from_value::<Vec<Block>>(d.clone()).ok()),
+        };
+
+        self.user_id = data.get("id").and_then(serde_json::Value::as_u64).unwrap_or_default() as u32;
+        self.columns = columns;
+        self.rows = rows;
+        self.blocks = blocks.unwrap_or_else(|| vec![Block::default(); columns as usize * rows as usize]);
+        self.palette = doc.get("palette").map(parse_moebius_palette).unwrap_or(DEFAULT_EGA_PALETTE);
+        self.font_name = doc.get("font_name").and_then(serde_json::Value::as_str).unwrap_or_default().to_string();
+        self.ice_colors = doc.get("ice_colors").and_then(serde_json::Value::as_bool).unwrap_or_default();
+        self.use_9px_font = doc.get("use_9px_font").and_then(serde_json::Value::as_bool).unwrap_or_default();
+        self.users.clear();
+        self.cursors.clear();
+        for user in data.get("users").and_then(serde_json::Value::as_array).into_iter().flatten() {
+            let id = user.get("id").and_then(serde_json::Value::as_u64);
+            let status = user.get("status").and_then(serde_json::Value::as_u64).unwrap_or_default();
+            if let (Some(id), true) = (id, status != u64::from(user_status::WEB)) {
+                let nick = user.get("nick").and_then(serde_json::Value::as_str).unwrap_or_default();
+                self.users.insert(id as u32, nick.to_string());
+            }
+        }
+    }
+
+    /// Resize keeping the cells that still fit, like the server document.
+    fn resize(&mut self, columns: u32, rows: u32) {
+        let mut blocks = vec![Block::default(); columns as usize * rows as usize];
+        for row in 0..rows.min(self.rows) as i32 {
+            for col in 0..columns.min(self.columns) as i32 {
+                if let Some(block) = self.block(col, row) {
+                    blocks[row as usize * columns as usize + col as usize] = block.clone();
+                }
+            }
+        }
+        self.columns = columns;
+        self.rows = rows;
+        self.blocks = blocks;
+    }
+}
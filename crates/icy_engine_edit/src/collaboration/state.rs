@@ -1,7 +1,10 @@
-use crate::EditorUndoStack;
+use crate::{EditorUndoOp, EditorUndoStack, LayerProperties, Size, TextBuffer, TextPane};
 use std::collections::HashMap;
 
-use super::{ChatMessage, ClientCommand, ConnectedDocument, ServerStatus, User, UserId};
+use super::{
+    CellRestore, ChatMessage, ClientCommand, CollaborationEvent, ConnectedDocument, ExtendedDocument, IcyAttributes, OperationLog, ProtocolVersion,
+    ServerStatus, User, UserId,
+};
 
 /// User status constants.
 pub mod user_status {
@@ -61,6 +64,69 @@ pub struct RemoteUser {
     pub status: u8,
 }
 
+/// Document state last sent to or received from the session (V3).
+#[derive(Debug)]
+struct DocumentSnapshot {
+    extended: ExtendedDocument,
+    layer_properties: Vec<LayerProperties>,
+    layer_sizes: Vec<Size>,
+}
+
+impl DocumentSnapshot {
+    fn from_buffer(buffer: &TextBuffer) -> Self {
+        Self {
+            extended: ExtendedDocument::from_buffer(buffer),
+            layer_properties: buffer.layers.iter().map(|l| l.properties.clone()).collect(),
+            layer_sizes: buffer.layers.iter().map(|l| l.size()).collect(),
+        }
+    }
+
+    /// Follow a layer stack change sent to the session, so the layers stay comparable by index
+    fn apply_layer_command(&mut self, cmd: &ClientCommand) {
+        match cmd {
+            ClientCommand::AddLayer { index, layer } if *index <= self.layer_properties.len() => {
+                self.layer_properties.insert(*index, layer.properties.clone());
+                self.layer_sizes.insert(*index, layer.size());
+            }
+            ClientCommand::RemoveLayer { index } if *index < self.layer_properties.len() => {
+                self.layer_properties.remove(*index);
+                self.layer_sizes.remove(*index);
+            }
+            ClientCommand::ReorderLayer { from, to } if *from < self.layer_properties.len() && *to < self.layer_properties.len() => {
+                let properties = self.layer_properties.remove(*from);
+                self.layer_properties.insert(*to, properties);
+                let size = self.layer_sizes.remove(*from);
+                self.layer_sizes.insert(*to, size);
+            }
+            _ => {}
+        }
+    }
+
+    fn palette_differs(&self, buffer: &TextBuffer) -> bool {
+        buffer.palette.len() != self.extended.palette.len()
+            || buffer
+                .palette
+                .color_iter()
+                .zip(&self.extended.palette)
+                .any(|(c, [r, g, b])| c.rgb() != (*r, *g, *b))
+    }
+
+    fn font_differs(&self, buffer: &TextBuffer, slot: u8) -> bool {
+        buffer.font(slot) != self.extended.fonts.iter().find(|f| f.slot == slot).map(|f| &f.font)
+    }
+
+    fn differs(&self, buffer: &TextBuffer) -> bool {
+        self.palette_differs(buffer)
+            || buffer.font_count() != self.extended.fonts.len()
+            || buffer.font_iter().any(|(slot, _)| self.font_differs(buffer, *slot))
+            || buffer.layers.len() != self.layer_properties.len()
+            || buffer.layers.iter().zip(&self.layer_properties).any(|(l, p)| l.properties != *p)
+            || buffer.layers.iter().zip(&self.layer_sizes).any(|(l, size)| l.size() != *size)
+            || buffer.tags != self.extended.tags
+            || self.extended.attributes.as_ref() != Some(&IcyAttributes::from_buffer(buffer))
+    }
+}
+
 /// UI-free collaboration state.
 ///
 /// Keeps the data model for collaboration sessions (users/chat/doc fields) and
@@ -85,6 +151,17 @@ pub struct CollaborationCoreState {
     pub ice_colors: bool,
     /// Font name
     pub font: String,
+    /// Negotiated protocol version
+    pub protocol_version: ProtocolVersion,
+
+    /// Who wrote which cell, for per-user undo (V3)
+    operation_log: OperationLog,
+    /// Cells skipped by the last undo/redo that the editor has to restore
+    pending_restores: Vec<CellRestore>,
+    /// `EditorUndoStack::redone_count` at last sync, to tell redos from new edits
+    redone_count: usize,
+    /// Document state at last `sync_document`
+    document_snapshot: Option<DocumentSnapshot>,
 
     /// Sync pointer into undo stack - tracks which operations have been synced
     /// This points to the undo_stack length at last sync
@@ -111,6 +188,7 @@ impl CollaborationCoreState {
         self.use_9px = doc.use_9px;
         self.ice_colors = doc.ice_colors;
         self.font = doc.font.clone();
+        self.protocol_version = doc.protocol_version;
         self.remote_users.clear();
         self.chat_messages = doc.chat_history.clone();
         self.server_status = None;
@@ -119,6 +197,7 @@ impl CollaborationCoreState {
 
     pub fn end_session(&mut self) {
         self.our_user_id = None;
+        self.protocol_version = ProtocolVersion::V1;
         self.remote_users.clear();
         self.server_status = None;
         self.reset_sync_pointer();
@@ -239,37 +318,72 @@ impl CollaborationCoreState {
         (((r + m) * 255.0_f32) as u8, ((g + m) * 255.0_f32) as u8, ((b + m) * 255.0_f32) as u8)
     }
 
+    /// Whether undo only reverts our own edits, which needs a V3 session.
+    pub fn has_selective_undo(&self) -> bool {
+        self.protocol_version >= ProtocolVersion::V3 && self.our_user_id.is_some()
+    }
+
+    /// Track a change made by another user (draws and layer operations).
+    pub fn record_remote_event(&mut self, event: &CollaborationEvent) {
+        if self.has_selective_undo() {
+            self.operation_log.record_event(event);
+        }
+    }
+
+    /// Cells the editor has to set back to another user's content after undo/redo skipped them.
+    pub fn take_pending_restores(&mut self) -> Vec<CellRestore> {
+        std::mem::take(&mut self.pending_restores)
+    }
+
     /// Synchronize with the undo stack and return pending operations to send to the server.
     ///
+    /// In V3 sessions undo and redo leave cells alone that another user changed after us,
+    /// see [`Self::take_pending_restores`].
+    ///
     /// See `icy_draw::CollaborationState::sync_from_undo_stack()` for the sending wrapper.
     pub fn sync_from_undo_stack(&mut self, undo_stack: &EditorUndoStack, caret_pos: (i32, i32), selecting: bool) -> Vec<ClientCommand> {
         let current_len = undo_stack.undo_stack().len();
+        let redo_len = undo_stack.redo_stack().len();
+        // Operations taken from the redo stack since the last sync are the first ones pushed back
+        let redone = undo_stack.redone_count().saturating_sub(self.redone_count);
+        self.redone_count = undo_stack.redone_count();
         let mut commands: Vec<ClientCommand> = Vec::new();
 
         if !self.sync_initialized {
             self.sync_initialized = true;
             self.sync_pointer = current_len;
         } else if current_len > self.sync_pointer {
-            for op in &undo_stack.undo_stack()[self.sync_pointer..current_len] {
-                if let Some(cmds) = op.redo_client_commands() {
-                    commands.extend(cmds);
-                }
+            let added: Vec<&EditorUndoOp> = undo_stack.undo_stack()[self.sync_pointer..current_len].iter().collect();
+            let (redone_ops, new_ops) = added.split_at(redone.min(added.len()));
+            let redo_commands = EditorUndoOp::redo_client_commands_of(redone_ops);
+            let new_commands = EditorUndoOp::redo_client_commands_of(new_ops);
+            if let Some(user) = self.our_user_id.filter(|_| self.has_selective_undo()) {
+                commands = self.filter_history(user, redo_commands);
+                self.operation_log.record_commands(user, &new_commands);
+            } else {
+                commands = redo_commands;
             }
+            commands.extend(new_commands);
             self.sync_pointer = current_len;
         } else if current_len < self.sync_pointer {
             let undone_count = self.sync_pointer - current_len;
-            let redo_stack = undo_stack.redo_stack();
-            let redo_len = redo_stack.len();
             if undone_count <= redo_len {
-                for op in redo_stack.iter().rev().take(undone_count) {
-                    if let Some(cmds) = op.undo_client_commands() {
-                        commands.extend(cmds);
-                    }
-                }
+                // The redo stack holds the undone operations, the last undone one on top
+                let undone: Vec<&EditorUndoOp> = undo_stack.redo_stack()[redo_len - undone_count..].iter().rev().collect();
+                commands = EditorUndoOp::undo_client_commands_of(&undone);
+            }
+            if let Some(user) = self.our_user_id.filter(|_| self.has_selective_undo()) {
+                commands = self.filter_history(user, commands);
             }
             self.sync_pointer = current_len;
         }
 
+        if let Some(snapshot) = self.document_snapshot.as_mut() {
+            for cmd in &commands {
+                snapshot.apply_layer_command(cmd);
+            }
+        }
+
         // Moebius protocol: Send either CURSOR or SELECTION, not both.
         // - SELECTION (5): when actively creating/extending a selection (selecting=true)
         // - CURSOR (4): when in normal editing mode (selecting=false)
@@ -311,11 +425,96 @@ impl CollaborationCoreState {
         commands
     }
 
+    fn filter_history(&mut self, user: UserId, commands: Vec<ClientCommand>) -> Vec<ClientCommand> {
+        let (kept, restores) = self.operation_log.filter_history(user, commands);
+        self.pending_restores.extend(restores);
+        kept
+    }
+
+    /// Compare the document with the last synced state and return V3 commands for what changed.
+    ///
+    /// Covers palette, font slots, layer properties and sizes, tags and `.icy` attributes. The first
+    /// call only takes the snapshot. Layer insertions and removals come from the undo stack and are
+    /// applied to the snapshot as they're sent, layers are only compared while the layer count matches
+    /// (it doesn't while a floating paste layer exists). No command resizes a layer, so a resized
+    /// layer is sent again as a whole.
+    pub fn sync_document(&mut self, buffer: &TextBuffer) -> Vec<ClientCommand> {
+        let mut commands = Vec::new();
+        if self.protocol_version < ProtocolVersion::V3 {
+            return commands;
+        }
+        let Some(old) = self.document_snapshot.take() else {
+            self.document_snapshot = Some(DocumentSnapshot::from_buffer(buffer));
+            return commands;
+        };
+        if !old.differs(buffer) {
+            self.document_snapshot = Some(old);
+            return commands;
+        }
+
+        if old.palette_differs(buffer) {
+            commands.push(ClientCommand::SetPalette {
+                colors: buffer.palette.color_iter().map(|c| c.clone().into()).collect(),
+            });
+        }
+        for (slot, font) in buffer.font_iter() {
+            if old.font_differs(buffer, *slot) {
+                commands.push(ClientCommand::SetFontSlot {
+                    slot: *slot,
+                    font: Some(Box::new(font.clone())),
+                });
+            }
+        }
+        for removed in old.extended.fonts.iter().filter(|f| buffer.font(f.slot).is_none()) {
+            commands.push(ClientCommand::SetFontSlot {
+                slot: removed.slot,
+                font: None,
+            });
+        }
+        if buffer.layers.len() == old.layer_properties.len() {
+            for (index, ((layer, properties), size)) in buffer.layers.iter().zip(&old.layer_properties).zip(&old.layer_sizes).enumerate() {
+                if layer.size() != *size {
+                    commands.push(ClientCommand::RemoveLayer { index });
+                    commands.push(ClientCommand::AddLayer {
+                        index,
+                        layer: Box::new(layer.clone()),
+                    });
+                } else if layer.properties != *properties {
+                    commands.push(ClientCommand::SetLayerProperties {
+                        index,
+                        properties: layer.properties.clone(),
+                    });
+                }
+            }
+        }
+        if buffer.tags != old.extended.tags {
+            commands.push(ClientCommand::SetTags { tags: buffer.tags.clone() });
+        }
+        let attributes = IcyAttributes::from_buffer(buffer);
+        if old.extended.attributes.as_ref() != Some(&attributes) {
+            commands.push(ClientCommand::SetAttributes { attributes });
+        }
+
+        self.document_snapshot = Some(DocumentSnapshot::from_buffer(buffer));
+        commands
+    }
+
+    /// Take the document as synced, call after applying remote document changes so they aren't sent back.
+    pub fn remember_document(&mut self, buffer: &TextBuffer) {
+        if self.protocol_version >= ProtocolVersion::V3 {
+            self.document_snapshot = Some(DocumentSnapshot::from_buffer(buffer));
+        }
+    }
+
     pub fn reset_sync_pointer(&mut self) {
         self.sync_pointer = 0;
         self.sync_initialized = false;
         self.last_cursor = None;
         self.last_selection = None;
+        self.redone_count = 0;
+        self.operation_log.clear();
+        self.pending_restores.clear();
+        self.document_snapshot = None;
     }
 
     pub fn set_sync_pointer(&mut self, len: usize) {
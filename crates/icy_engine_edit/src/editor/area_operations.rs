@@ -7,11 +7,11 @@ use std::{
 
 use i18n_embed_fl::fl;
 
-use crate::{AttributedChar, Position, Rectangle, Result, Selection, TextPane};
+use crate::{AttributedChar, Layer, Position, Rectangle, Result, Selection, TextPane};
 
-use super::{undo_operation::EditorUndoOp, EditState};
+use super::{undo_operation::EditorUndoOp, EditState, TextAlignment};
 
-fn get_area(sel: Option<Selection>, layer: Rectangle) -> Rectangle {
+pub(super) fn get_area(sel: Option<Selection>, layer: Rectangle) -> Rectangle {
     if let Some(selection) = sel {
         let rect = selection.as_rectangle();
         rect.intersect(&layer) - layer.start
@@ -20,123 +20,62 @@ fn get_area(sel: Option<Selection>, layer: Rectangle) -> Rectangle {
     }
 }
 
-impl EditState {
-    pub fn justify_left(&mut self) -> Result<()> {
-        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-justify-left"));
-        let sel = self.selection();
-        if let Some(layer) = self.get_cur_layer_mut() {
-            let area = get_area(sel, layer.rectangle());
-            let old_layer = crate::chars_from_area(layer, area);
-            for y in area.y_range() {
-                let mut removed_chars = 0;
-                let len = area.width();
-                while removed_chars < len {
-                    let ch = layer.char_at((area.left() + removed_chars, y).into());
-                    if ch.is_visible() && !ch.is_transparent() {
-                        break;
-                    }
-                    removed_chars += 1;
-                }
-                if len <= removed_chars {
-                    continue;
-                }
-                for x in area.x_range() {
-                    let ch = if x + removed_chars < area.right() {
-                        layer.char_at((x + removed_chars, y).into())
-                    } else {
-                        AttributedChar::invisible()
-                    };
-                    layer.set_char(Position::new(x, y), ch);
-                }
-            }
-            let new_layer = crate::chars_from_area(layer, area);
-            let op = EditorUndoOp::LayerChange {
-                layer: self.get_current_layer()?,
-                pos: area.start,
-                old_chars: old_layer,
-                new_chars: new_layer,
+/// Start column of a row's content that has `free` blank cells around it
+///
+/// Shared by the justify commands and text boxes, `Justify` rows start at the left like `Left` ones.
+pub(super) fn aligned_start(free: i32, alignment: TextAlignment) -> i32 {
+    match alignment {
+        TextAlignment::Left | TextAlignment::Justify => 0,
+        TextAlignment::Center => free / 2,
+        TextAlignment::Right => free,
+    }
+}
+
+/// Moves the content of every row in `area` to its aligned position, blank rows stay untouched
+fn align_area(layer: &mut Layer, area: Rectangle, alignment: TextAlignment) {
+    let is_blank = |ch: AttributedChar| !ch.is_visible() || ch.is_transparent();
+    for y in area.y_range() {
+        let row: Vec<AttributedChar> = area.x_range().map(|x| layer.char_at((x, y).into())).collect();
+        let Some(first) = row.iter().position(|&ch| !is_blank(ch)) else {
+            continue;
+        };
+        let last = row.iter().rposition(|&ch| !is_blank(ch)).unwrap_or(first);
+        let free = (row.len() - (last - first + 1)) as i32;
+        let shift = aligned_start(free, alignment) - first as i32;
+        for (i, x) in area.x_range().enumerate() {
+            let src = i as i32 - shift;
+            let ch = if src >= 0 && (src as usize) < row.len() {
+                row[src as usize]
+            } else {
+                AttributedChar::invisible()
             };
-            self.push_plain_undo(op)
-        } else {
-            Err(crate::EngineError::Generic("Current layer is invalid".to_string()))
+            layer.set_char(Position::new(x, y), ch);
         }
     }
+}
 
-    pub fn center(&mut self) -> Result<()> {
-        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-center"));
-        let sel = self.selection();
-        self.justify_left()?;
-        if let Some(layer) = self.get_cur_layer_mut() {
-            let area = get_area(sel, layer.rectangle());
-            let old_layer = crate::chars_from_area(layer, area);
+impl EditState {
+    pub fn justify_left(&mut self) -> Result<()> {
+        self.align(fl!(crate::LANGUAGE_LOADER, "undo-justify-left"), TextAlignment::Left)
+    }
 
-            for y in area.y_range() {
-                let mut removed_chars = 0;
-                let len = area.width();
-                while removed_chars < len {
-                    let ch = layer.char_at((area.right() - removed_chars - 1, y).into());
-                    if ch.is_visible() && !ch.is_transparent() {
-                        break;
-                    }
-                    removed_chars += 1;
-                }
-                if len == removed_chars {
-                    continue;
-                }
-                let removed_chars = removed_chars / 2;
-                for x in area.x_range().rev() {
-                    let ch = if x - removed_chars >= area.left() {
-                        layer.char_at((x - removed_chars, y).into())
-                    } else {
-                        AttributedChar::invisible()
-                    };
-
-                    layer.set_char((x, y), ch);
-                }
-            }
-            let new_layer = crate::chars_from_area(layer, area);
-            let op = EditorUndoOp::LayerChange {
-                layer: self.get_current_layer()?,
-                pos: area.start,
-                old_chars: old_layer,
-                new_chars: new_layer,
-            };
-            self.push_plain_undo(op)
-        } else {
-            Err(crate::EngineError::Generic("Current layer is invalid".to_string()))
-        }
+    pub fn center(&mut self) -> Result<()> {
+        self.align(fl!(crate::LANGUAGE_LOADER, "undo-center"), TextAlignment::Center)
     }
 
     pub fn justify_right(&mut self) -> Result<()> {
-        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-justify-right"));
+        self.align(fl!(crate::LANGUAGE_LOADER, "undo-justify-right"), TextAlignment::Right)
+    }
+
+    /// Aligns the rows of the selection or current layer
+    fn align(&mut self, description: String, alignment: TextAlignment) -> Result<()> {
+        self.check_layer_unlocked(self.get_current_layer()?)?;
+        let _undo = self.begin_atomic_undo(description);
         let sel = self.selection();
         if let Some(layer) = self.get_cur_layer_mut() {
             let area = get_area(sel, layer.rectangle());
             let old_layer = crate::chars_from_area(layer, area);
-
-            for y in area.y_range() {
-                let mut removed_chars = 0;
-                let len = area.width();
-                while removed_chars < len {
-                    let ch = layer.char_at((area.right() - removed_chars - 1, y).into());
-                    if ch.is_visible() && !ch.is_transparent() {
-                        break;
-                    }
-                    removed_chars += 1;
-                }
-                if len == removed_chars {
-                    continue;
-                }
-                for x in area.x_range().rev() {
-                    let ch = if x - removed_chars >= area.left() {
-                        layer.char_at((x - removed_chars, y).into())
-                    } else {
-                        AttributedChar::invisible()
-                    };
-
-                    layer.set_char((x, y), ch);
-                }
-            }
+            align_area(layer, area, alignment);
             let new_layer = crate::chars_from_area(layer, area);
             let op = EditorUndoOp::LayerChange {
                 layer: self.get_current_layer()?,
@@ -151,6 +90,7 @@ impl EditState {
     }
 
     pub fn flip_x(&mut self) -> Result<()> {
+        self.check_layer_unlocked(self.get_current_layer()?)?;
         let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-flip-x"));
         let sel = self.selection();
         let mut flip_tables = HashMap::new();
@@ -177,6 +117,7 @@ impl EditState {
     }
 
     pub fn flip_y(&mut self) -> Result<()> {
+        self.check_layer_unlocked(self.get_current_layer()?)?;
         let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-flip-y"));
         let sel = self.selection();
 
@@ -284,6 +225,7 @@ impl EditState {
     }
 
     pub fn scroll_area_up(&mut self) -> Result<()> {
+        self.check_layer_unlocked(self.get_current_layer()?)?;
         let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-justify-left"));
         let sel = self.selection();
         if let Some(layer) = self.get_cur_layer_mut() {
@@ -332,6 +274,7 @@ impl EditState {
     }
 
     pub fn scroll_area_down(&mut self) -> Result<()> {
+        self.check_layer_unlocked(self.get_current_layer()?)?;
         let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-justify-left"));
         let sel = self.selection();
         if let Some(layer) = self.get_cur_layer_mut() {
@@ -379,6 +322,7 @@ impl EditState {
     }
 
     pub fn scroll_area_left(&mut self) -> Result<()> {
+        self.check_layer_unlocked(self.get_current_layer()?)?;
         let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-justify-left"));
         let sel = self.selection();
         if let Some(layer) = self.get_cur_layer_mut() {
@@ -409,6 +353,7 @@ impl EditState {
     }
 
     pub fn scroll_area_right(&mut self) -> Result<()> {
+        self.check_layer_unlocked(self.get_current_layer()?)?;
         let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-justify-left"));
         let sel = self.selection();
         if let Some(layer) = self.get_cur_layer_mut() {
@@ -18,19 +18,6 @@ impl EditState {
             let pos = pos.into();
             let old = layer.char_at(pos);
 
-            if self.mirror_mode {
-                let mirror_pos = Position::new(layer.width() - pos.x - 1, pos.y);
-                let mirror_old = layer.char_at(mirror_pos);
-                self.push_undo_action(EditorUndoOp::SetChar {
-                    pos: mirror_pos,
-                    layer: self.get_current_layer()?,
-                    old: mirror_old,
-                    new: attributed_char,
-                    undo_caret: None,
-                    redo_caret: None,
-                })?;
-            }
-
             self.push_undo_action(EditorUndoOp::SetChar {
                 pos,
                 layer: self.get_current_layer()?,
@@ -38,12 +25,32 @@ impl EditState {
                 new: attributed_char,
                 undo_caret: None,
                 redo_caret: None,
-            })
+            })?;
+            self.set_mirrored_chars(pos, attributed_char)
         } else {
             Err(crate::EngineError::Generic("Current layer is invalid".to_string()))
         }
     }
 
+    /// Writes the symmetry copies of a character set at `pos`
+    fn set_mirrored_chars(&mut self, pos: Position, attributed_char: AttributedChar) -> Result<()> {
+        let layer = self.get_current_layer()?;
+        for (mirror_pos, mirror_char) in self.mirrored_chars(pos, attributed_char) {
+            let Some(old) = self.get_cur_layer().map(|l| l.char_at(mirror_pos)) else {
+                continue;
+            };
+            self.push_undo_action(EditorUndoOp::SetChar {
+                pos: mirror_pos,
+                layer,
+                old,
+                new: mirror_char,
+                undo_caret: None,
+                redo_caret: None,
+            })?;
+        }
+        Ok(())
+    }
+
     /// Set a character without starting its own atomic undo group.
     ///
     /// This is intended for tools that manage their own `AtomicUndoGuard` (e.g. brush strokes)
@@ -53,19 +60,6 @@ impl EditState {
             let pos = pos.into();
             let old = layer.char_at(pos);
 
-            if self.mirror_mode {
-                let mirror_pos = Position::new(layer.width() - pos.x - 1, pos.y);
-                let mirror_old = layer.char_at(mirror_pos);
-                self.push_undo_action(EditorUndoOp::SetChar {
-                    pos: mirror_pos,
-                    layer: self.get_current_layer()?,
-                    old: mirror_old,
-                    new: attributed_char,
-                    undo_caret: None,
-                    redo_caret: None,
-                })?;
-            }
-
             self.push_undo_action(EditorUndoOp::SetChar {
                 pos,
                 layer: self.get_current_layer()?,
@@ -73,7 +67,8 @@ impl EditState {
                 new: attributed_char,
                 undo_caret: None,
                 redo_caret: None,
-            })
+            })?;
+            self.set_mirrored_chars(pos, attributed_char)
         } else {
             Err(crate::EngineError::Generic("Current layer is invalid".to_string()))
         }
@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+
+use i18n_embed_fl::fl;
+
+use crate::{
+    brushes::{pattern_char_at, Gradient},
+    AttributedChar, Position, Rectangle, Result, TextPane,
+};
+
+use super::{undo_operation::EditorUndoOp, EditState};
+
+impl EditState {
+    /// Layer local cells a fill at `pos` (layer local) covers
+    ///
+    /// With a selection these are all selected cells of the current layer, otherwise the connected
+    /// cells with the same character as the one at `pos` - with `exact` also the same attribute.
+    pub fn fill_region(&self, pos: Position, exact: bool) -> Vec<Position> {
+        let Some(layer) = self.get_cur_layer() else {
+            return Vec::new();
+        };
+        let offset = layer.offset();
+        if self.is_something_selected() {
+            let mut cells = Vec::new();
+            for y in 0..layer.height() {
+                for x in 0..layer.width() {
+                    let p = Position::new(x, y);
+                    if self.is_selected(p + offset) {
+                        cells.push(p);
+                    }
+                }
+            }
+            return cells;
+        }
+
+        if !layer.rectangle().is_inside(pos + offset) {
+            return Vec::new();
+        }
+        let base = layer.char_at(pos);
+        let mut cells = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![pos];
+        while let Some(p) = stack.pop() {
+            if p.x < 0 || p.y < 0 || p.x >= layer.width() || p.y >= layer.height() || !visited.insert(p) {
+                continue;
+            }
+            let cur = layer.char_at(p);
+            if (exact && cur != base) || (!exact && cur.ch != base.ch) {
+                continue;
+            }
+            cells.push(p);
+            stack.push(p + Position::new(-1, 0));
+            stack.push(p + Position::new(1, 0));
+            stack.push(p + Position::new(0, -1));
+            stack.push(p + Position::new(0, 1));
+        }
+        cells
+    }
+
+    /// Fills the layer local `cells` with a gradient running from `start` to `end` (layer local)
+    pub fn fill_gradient(&mut self, cells: &[Position], gradient: &Gradient, start: Position, end: Position) -> Result<()> {
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-gradient_fill"));
+        self.fill_cells(cells, |pos, ch| gradient.apply(start, end, pos, ch))
+    }
+
+    /// Fills the layer local `cells` by repeating `tile`, its top left corner is placed at `origin`
+    pub fn fill_pattern(&mut self, cells: &[Position], tile: &[Vec<AttributedChar>], origin: Position) -> Result<()> {
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-pattern_fill"));
+        self.fill_cells(cells, |pos, ch| pattern_char_at(tile, origin, pos).unwrap_or(ch))
+    }
+
+    fn fill_cells(&mut self, cells: &[Position], fill: impl Fn(Position, AttributedChar) -> AttributedChar) -> Result<()> {
+        let layer_idx = self.get_current_layer()?;
+        let Some(layer) = self.get_cur_layer() else {
+            return Err(crate::EngineError::Generic("Current layer is invalid".to_string()));
+        };
+        let Some(first) = cells.first() else {
+            return Ok(());
+        };
+        let (min, max) = cells.iter().fold((*first, *first), |(min, max), p| (min.min(*p), max.max(*p)));
+        let area = Rectangle::from_min_size(min, (max.x - min.x + 1, max.y - min.y + 1)).intersect(&Rectangle::from_min_size((0, 0), layer.size()));
+        if area.is_empty() {
+            return Ok(());
+        }
+        let old_chars = crate::chars_from_area(layer, area);
+        let mut new_chars = old_chars.clone();
+        for &pos in cells {
+            if area.is_inside(pos) {
+                let local = pos - area.start;
+                let cell = &mut new_chars[local.y as usize][local.x as usize];
+                *cell = fill(pos, *cell);
+            }
+        }
+        if new_chars == old_chars {
+            return Ok(());
+        }
+        self.push_undo_action(EditorUndoOp::LayerChange {
+            layer: layer_idx,
+            pos: area.start,
+            old_chars,
+            new_chars,
+        })
+    }
+}
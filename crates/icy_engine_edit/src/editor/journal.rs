@@ -0,0 +1,532 @@
+//! Timestamped edit journal for replaying how a document was made.
+//!
+//! [`JournalRecorder`] watches the undo stack like the collaboration sync does: operations are
+//! journaled as they show up, undos together with the operation they revert. Changes that bypass
+//! the undo stack, like draws of other users in a collaboration session, are journaled as cell
+//! changes and layer, palette and document changes of other users as the V3 commands they came
+//! with. [`JournalPlayer`] replays a journal from its start document and is used for scrubbing
+//! and for rendering timelapses.
+
+use std::marker::PhantomData;
+use std::time::Instant;
+
+use icy_engine::{AttributeColor, BitFont, Color, EngineError, FileFormat, Layer, LayerProperties, Palette, Position, SaveOptions, Tag, TextBuffer, TextPane};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::{EditState, EditorUndoOp, EditorUndoStack};
+use crate::collaboration::{Block, IcyAttributes};
+use crate::Result;
+
+/// File extension of journals saved next to a document.
+pub const JOURNAL_EXTENSION: &str = "icyjournal";
+
+/// Version of the journal format, bump it whenever [`JournalEvent`] or [`EditJournal`] change.
+///
+/// Engine types like [`EditorUndoOp`] are stored as [`JournalData`] and don't need a bump
+/// as long as their serde representation stays compatible.
+pub const JOURNAL_VERSION: u32 = 2;
+
+/// An engine value stored as JSON, by variant and field names.
+///
+/// Keeps journals readable when the engine types gain variants or fields with defaults,
+/// which a positional encoding of the whole journal wouldn't survive.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JournalData<T> {
+    json: String,
+    #[serde(skip)]
+    _value: PhantomData<T>,
+}
+
+impl<T> Clone for JournalData<T> {
+    fn clone(&self) -> Self {
+        Self {
+            json: self.json.clone(),
+            _value: PhantomData,
+        }
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> JournalData<T> {
+    pub fn new(value: &T) -> Result<Self> {
+        Ok(Self {
+            json: serde_json::to_string(value).map_err(EngineError::generic)?,
+            _value: PhantomData,
+        })
+    }
+
+    pub fn get(&self) -> Result<T> {
+        serde_json::from_str(&self.json).map_err(EngineError::generic)
+    }
+}
+
+/// A recorded change.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum JournalEvent {
+    /// A new edit or a redo.
+    Apply(JournalData<EditorUndoOp>),
+    /// An undo, with the operation it reverted.
+    Undo(JournalData<EditorUndoOp>),
+    /// A cell changed outside the undo stack, e.g. by another user in a collaboration session.
+    SetCell { layer: usize, col: i32, row: i32, block: Block },
+    /// Palette replaced by another user
+    SetPalette { colors: Vec<[u8; 3]> },
+    /// Layer inserted by another user
+    AddLayer { index: usize, layer: JournalData<Layer> },
+    /// Layer removed by another user
+    RemoveLayer { index: usize },
+    /// Layer moved by another user
+    ReorderLayer { from: usize, to: usize },
+    /// Layer properties changed by another user
+    SetLayerProperties { index: usize, properties: JournalData<LayerProperties> },
+    /// Font slot uploaded or cleared by another user
+    SetFontSlot { slot: u8, font: Option<JournalData<BitFont>> },
+    /// Tags replaced by another user
+    SetTags { tags: JournalData<Vec<Tag>> },
+    /// `.icy` document attributes changed by another user
+    SetAttributes { attributes: JournalData<IcyAttributes> },
+}
+
+impl JournalEvent {
+    fn replay(&self, state: &mut EditState) -> Result<()> {
+        let buffer = state.get_buffer_mut();
+        match self {
+            JournalEvent::Apply(op) => return op.get()?.redo(state),
+            JournalEvent::Undo(op) => return op.get()?.undo(state),
+            JournalEvent::SetCell { layer, col, row, block } => {
+                if let Some(layer) = buffer.layers.get_mut(*layer) {
+                    let pos = Position::new(*col, *row);
+                    let mut ch = layer.char_at(pos);
+                    ch.ch = char::from_u32(block.code).unwrap_or(' ');
+                    ch.attribute.set_foreground(u32::from(block.fg));
+                    ch.attribute.set_background(u32::from(block.bg));
+                    layer.set_char(pos, ch);
+                }
+            }
+            JournalEvent::SetPalette { colors } => {
+                let colors: Vec<Color> = colors.iter().map(|c| (*c).into()).collect();
+                buffer.palette = Palette::from_slice(&colors);
+            }
+            JournalEvent::AddLayer { index, layer } => {
+                let index = (*index).min(buffer.layers.len());
+                buffer.layers.insert(index, layer.get()?);
+            }
+            JournalEvent::RemoveLayer { index } => {
+                if *index < buffer.layers.len() {
+                    buffer.layers.remove(*index);
+                }
+            }
+            JournalEvent::ReorderLayer { from, to } => {
+                if *from < buffer.layers.len() && *to < buffer.layers.len() {
+                    let layer = buffer.layers.remove(*from);
+                    buffer.layers.insert(*to, layer);
+                }
+            }
+            JournalEvent::SetLayerProperties { index, properties } => {
+                if let Some(layer) = buffer.layers.get_mut(*index) {
+                    layer.properties = properties.get()?;
+                }
+            }
+            JournalEvent::SetFontSlot { slot, font } => match font {
+                Some(font) => buffer.set_font(*slot, font.get()?),
+                None => {
+                    buffer.remove_font(*slot);
+                }
+            },
+            JournalEvent::SetTags { tags } => buffer.tags = tags.get()?,
+            JournalEvent::SetAttributes { attributes } => attributes.get()?.apply_to(buffer),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Milliseconds since the recording started, time between editing sessions doesn't count
+    pub time_ms: u64,
+    pub event: JournalEvent,
+}
+
+/// Edit history of a document, starting at the document as it was when recording began.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EditJournal {
+    pub version: u32,
+    /// Start document in `IcyDraw` format
+    start_document: Vec<u8>,
+    entries: Vec<JournalEntry>,
+    /// [`document_checksum`] of the document the entries lead to, set when the journal is saved
+    end_checksum: Option<u64>,
+    /// Recording was turned off, the journal can be replayed but isn't continued
+    finished: bool,
+}
+
+impl EditJournal {
+    pub fn new(start: &TextBuffer) -> Result<Self> {
+        Ok(Self {
+            version: JOURNAL_VERSION,
+            start_document: FileFormat::IcyDraw.to_bytes(start, &SaveOptions::default())?,
+            entries: Vec::new(),
+            end_checksum: None,
+            finished: false,
+        })
+    }
+
+    /// Remember `buffer` as the document the journal leads to.
+    pub fn set_end_document(&mut self, buffer: &TextBuffer) {
+        self.end_checksum = Some(document_checksum(buffer));
+    }
+
+    /// Whether replaying the journal leads to `buffer`, so recording can continue on it.
+    ///
+    /// False when the document was changed elsewhere or saved to a format that lost data.
+    pub fn leads_to(&self, buffer: &TextBuffer) -> bool {
+        self.end_checksum == Some(document_checksum(buffer))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Stop recording, the journal stays available for replaying.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// Continue recording a finished journal.
+    pub fn resume(&mut self) {
+        self.finished = false;
+    }
+
+    /// The document as it was when the recording started.
+    pub fn start_buffer(&self) -> Result<TextBuffer> {
+        Ok(FileFormat::IcyDraw.from_bytes(&self.start_document, None)?.screen.buffer)
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Recorded editing time in milliseconds.
+    pub fn duration_ms(&self) -> u64 {
+        self.entries.last().map_or(0, |e| e.time_ms)
+    }
+
+    pub fn push(&mut self, time_ms: u64, event: JournalEvent) {
+        self.entries.push(JournalEntry { time_ms, event });
+    }
+
+    /// Number of entries recorded up to and including `time_ms`.
+    pub fn entries_until(&self, time_ms: u64) -> usize {
+        self.entries.partition_point(|e| e.time_ms <= time_ms)
+    }
+
+    /// Journal positions to take timelapse frames at, always starting with the start document
+    /// and ending with the last entry.
+    pub fn timelapse_positions(&self, step: TimelapseStep) -> Vec<usize> {
+        let mut positions = vec![0];
+        match step {
+            TimelapseStep::Edits(count) => {
+                positions.extend((1..=self.len() / count.max(1)).map(|i| i * count.max(1)));
+            }
+            TimelapseStep::Millis(ms) => {
+                let ms = ms.max(1);
+                for i in 1..=self.duration_ms().div_ceil(ms) {
+                    let position = self.entries_until(i * ms);
+                    if positions.last() != Some(&position) {
+                        positions.push(position);
+                    }
+                }
+            }
+        }
+        if positions.last() != Some(&self.len()) {
+            positions.push(self.len());
+        }
+        positions
+    }
+}
+
+/// Checksum of the document content: size, palette, layer positions and cells.
+///
+/// Uses FNV-1a to stay the same across builds and platforms.
+pub fn document_checksum(buffer: &TextBuffer) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut add = |value: u32| {
+        for byte in value.to_le_bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+    add(buffer.width() as u32);
+    add(buffer.height() as u32);
+    for color in buffer.palette.color_iter() {
+        let (r, g, b) = color.rgb();
+        add(u32::from_le_bytes([r, g, b, 0]));
+    }
+    add(buffer.layers.len() as u32);
+    for layer in &buffer.layers {
+        let offset = layer.offset();
+        add(offset.x as u32);
+        add(offset.y as u32);
+        add(layer.width() as u32);
+        add(layer.height() as u32);
+        for y in 0..layer.height() {
+            for x in 0..layer.width() {
+                let ch = layer.char_at(Position::new(x, y));
+                add(ch.ch as u32);
+                add(color_key(ch.attribute.foreground_color()));
+                add(color_key(ch.attribute.background_color()));
+                add(u32::from(ch.attribute.font_page()));
+                add(u32::from(ch.attribute.attr));
+            }
+        }
+    }
+    hash
+}
+
+fn color_key(color: AttributeColor) -> u32 {
+    match color {
+        AttributeColor::Palette(n) => u32::from(n),
+        AttributeColor::ExtendedPalette(n) => 0x0100_0000 | u32::from(n),
+        AttributeColor::Rgb(r, g, b) => u32::from_le_bytes([r, g, b, 2]),
+        AttributeColor::Transparent => 0x0300_0000,
+    }
+}
+
+/// How far apart timelapse frames are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimelapseStep {
+    /// A frame every n journal entries
+    Edits(usize),
+    /// A frame every n milliseconds of editing time
+    Millis(u64),
+}
+
+/// Records an [`EditJournal`] by following the undo stack.
+pub struct JournalRecorder {
+    journal: EditJournal,
+    started: Instant,
+    /// Journal time when this editing session started
+    time_offset: u64,
+    initialized: bool,
+    /// Mirror of the undo stack: sequence id and journal entry of each slot,
+    /// no entry for slots that were journaled as separate operations
+    slots: Vec<(u64, Option<usize>)>,
+    redo_len: usize,
+}
+
+impl JournalRecorder {
+    /// Continue recording `journal`, the undo stack at the first sync is taken as already journaled.
+    pub fn new(journal: EditJournal) -> Self {
+        Self {
+            time_offset: journal.duration_ms(),
+            journal,
+            started: Instant::now(),
+            initialized: false,
+            slots: Vec::new(),
+            redo_len: 0,
+        }
+    }
+
+    pub fn journal(&self) -> &EditJournal {
+        &self.journal
+    }
+
+    pub fn into_journal(self) -> EditJournal {
+        self.journal
+    }
+
+    fn now(&self) -> u64 {
+        self.time_offset + self.started.elapsed().as_millis() as u64
+    }
+
+    fn push(&mut self, event: JournalEvent) -> usize {
+        let time_ms = self.now();
+        self.journal.push(time_ms, event);
+        self.journal.len() - 1
+    }
+
+    fn push_op(&mut self, op: &EditorUndoOp, event: fn(JournalData<EditorUndoOp>) -> JournalEvent) -> Option<usize> {
+        match JournalData::new(op) {
+            Ok(data) => Some(self.push(event(data))),
+            Err(e) => {
+                log::error!("Failed to journal {op:?}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Journal what changed on the undo stack since the last call.
+    pub fn sync_from_undo_stack(&mut self, undo_stack: &EditorUndoStack) {
+        let stack = undo_stack.undo_stack();
+        let ids = undo_stack.undo_ids();
+        let redo_stack = undo_stack.redo_stack();
+        if !self.initialized {
+            self.initialized = true;
+            self.slots = ids.iter().map(|id| (*id, None)).collect();
+            self.redo_len = redo_stack.len();
+            return;
+        }
+
+        let pointer = self.slots.len();
+        if stack.len() < pointer && redo_stack.len() == self.redo_len + (pointer - stack.len()) {
+            // Undo moved the operations to the redo stack, first undone first
+            let undone = pointer - stack.len();
+            for op in &redo_stack[redo_stack.len() - undone..] {
+                self.push_op(op, JournalEvent::Undo);
+            }
+            self.slots.truncate(stack.len());
+        } else {
+            let mut keep = pointer.min(stack.len());
+            if keep > 0 && self.slots[keep - 1].0 != ids[keep - 1] {
+                let base = keep - 1;
+                let journaled = pointer - base;
+                match &stack[base] {
+                    // An atomic group ended and collected operations journaled one by one
+                    EditorUndoOp::Atomic { operations, .. } if operations.len() >= journaled => {
+                        for op in &operations[journaled..] {
+                            self.push_op(op, JournalEvent::Apply);
+                        }
+                        self.slots.truncate(base);
+                        self.slots.push((ids[base], None));
+                    }
+                    _ => keep = base,
+                }
+            }
+            // Operations removed without going to the redo stack were reverted, e.g. a cancelled paste
+            for (_, entry) in self.slots.drain(keep.min(self.slots.len())..).rev().collect::<Vec<_>>() {
+                let op = entry.and_then(|i| match &self.journal.entries[i].event {
+                    JournalEvent::Apply(op) => Some(op.clone()),
+                    _ => None,
+                });
+                if let Some(op) = op {
+                    self.push(JournalEvent::Undo(op));
+                }
+            }
+            for (op, id) in stack[self.slots.len()..].iter().zip(&ids[self.slots.len()..]) {
+                let entry = self.push_op(op, JournalEvent::Apply);
+                self.slots.push((*id, entry));
+            }
+        }
+        self.redo_len = redo_stack.len();
+    }
+
+    /// Journal a cell change that bypassed the undo stack.
+    pub fn record_cell(&mut self, layer: usize, col: i32, row: i32, block: Block) {
+        self.push(JournalEvent::SetCell { layer, col, row, block });
+    }
+
+    /// Journal another change that bypassed the undo stack, like a V3 command of another user.
+    pub fn record(&mut self, event: JournalEvent) {
+        self.push(event);
+    }
+
+    /// Remember the current document as the end of the journal, called before it's saved.
+    pub fn set_end_document(&mut self, buffer: &TextBuffer) {
+        self.journal.set_end_document(buffer);
+    }
+}
+
+/// Replays an [`EditJournal`].
+pub struct JournalPlayer {
+    journal: EditJournal,
+    start: TextBuffer,
+    state: EditState,
+    /// Number of entries applied
+    position: usize,
+}
+
+impl JournalPlayer {
+    pub fn new(journal: EditJournal) -> Result<Self> {
+        let start = journal.start_buffer()?;
+        Ok(Self {
+            state: EditState::from_buffer(start.clone()),
+            start,
+            journal,
+            position: 0,
+        })
+    }
+
+    pub fn journal(&self) -> &EditJournal {
+        &self.journal
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn len(&self) -> usize {
+        self.journal.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.journal.is_empty()
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.position >= self.journal.len()
+    }
+
+    /// Editing time of the current position.
+    pub fn time_ms(&self) -> u64 {
+        self.position.checked_sub(1).map_or(0, |i| self.journal.entries[i].time_ms)
+    }
+
+    /// The document at the current position.
+    pub fn buffer(&self) -> &TextBuffer {
+        self.state.get_buffer()
+    }
+
+    /// Apply the next entry, returns false at the end of the journal.
+    pub fn step(&mut self) -> bool {
+        let Some(entry) = self.journal.entries.get(self.position) else {
+            return false;
+        };
+        if let Err(e) = entry.event.replay(&mut self.state) {
+            log::warn!("Journal entry {} failed to replay: {e}", self.position);
+        }
+        self.position += 1;
+        true
+    }
+
+    /// Move to `position`, going back replays from the start document.
+    pub fn seek(&mut self, position: usize) {
+        let position = position.min(self.journal.len());
+        if position < self.position {
+            self.state = EditState::from_buffer(self.start.clone());
+            self.position = 0;
+        }
+        while self.position < position {
+            self.step();
+        }
+        self.state.get_buffer_mut().mark_dirty();
+    }
+
+    /// Move to the last entry recorded at or before `time_ms`.
+    pub fn seek_time(&mut self, time_ms: u64) {
+        self.seek(self.journal.entries_until(time_ms));
+    }
+}
+
+/// `.icyanim` script that plays frame files as a timelapse.
+pub fn timelapse_script(frame_files: &[String], delay_ms: u32) -> String {
+    let mut script = String::from("-- Timelapse generated from an edit journal\n\n");
+    script.push_str(&format!("set_delay({delay_ms})\n"));
+    script.push_str("local frames = {\n");
+    for file in frame_files {
+        script.push_str(&format!("    \"{}\",\n", file.replace('\\', "\\\\").replace('"', "\\\"")));
+    }
+    script.push_str("}\n\n");
+    script.push_str("for _, file in ipairs(frames) do\n");
+    script.push_str("    next_frame(load_buffer(file))\n");
+    script.push_str("end\n");
+    script
+}
@@ -1,5 +1,5 @@
 #![allow(clippy::missing_errors_doc)]
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range};
 
 use i18n_embed_fl::fl;
 
@@ -12,6 +12,8 @@ impl EditState {
         let size = self.screen.buffer.size();
         let mut new_layer = Layer::new(fl!(crate::LANGUAGE_LOADER, "layer-new-name"), size);
         new_layer.properties.has_alpha_channel = true;
+        // Added above a group member it joins the group, above a group header it's the group's sibling
+        new_layer.properties.depth = self.screen.buffer.layers.get(layer).map_or(0, |l| l.properties.depth);
         let idx = (layer + 1).clamp(0, self.screen.buffer.layers.len());
         let op = EditorUndoOp::AddLayer {
             index: idx,
@@ -21,29 +23,61 @@ impl EditState {
         self.screen.current_layer = idx;
         Ok(())
     }
-    //
+    /// Fails if the layer or one of the groups containing it is locked
+    pub(crate) fn check_layer_unlocked(&self, layer: usize) -> Result<()> {
+        if layer < self.screen.buffer.layers.len() && self.screen.buffer.is_layer_locked(layer) {
+            return Err(crate::EngineError::Generic(format!("Layer {layer} is locked")));
+        }
+        Ok(())
+    }
+
+    /// Removes a layer, for a group header the whole group
     pub fn remove_layer(&mut self, layer: usize) -> Result<()> {
         if layer >= self.screen.buffer.layers.len() {
             return Err(crate::EngineError::Generic(format!("Invalid layer index: {layer}")));
         }
-        let removed = self.screen.buffer.layers[layer].clone();
-        let op = EditorUndoOp::RemoveLayer {
-            layer_index: layer,
-            layer: Box::new(removed),
-        };
-        self.push_undo_action(op)
+        let block = self.screen.buffer.layer_block(layer);
+        for i in block.clone() {
+            self.check_layer_unlocked(i)?;
+        }
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-remove_layer"));
+        for i in block.rev() {
+            let removed = self.screen.buffer.layers[i].clone();
+            let op = EditorUndoOp::RemoveLayer {
+                layer_index: i,
+                layer: Box::new(removed),
+            };
+            self.push_undo_action(op)?;
+        }
+        Ok(())
     }
 
+    /// Swaps a layer - for a group the whole group - with the sibling above it
+    ///
+    /// Fails for the top layer of a group, it stays inside of it.
     pub fn raise_layer(&mut self, layer: usize) -> Result<()> {
         if layer + 1 >= self.screen.buffer.layers.len() {
             return Err(crate::EngineError::Generic(format!("Invalid layer index: {layer}")));
         }
-        let op = EditorUndoOp::RaiseLayer { layer_index: layer };
-        self.push_undo_action(op)?;
-        self.screen.current_layer = layer + 1;
+        let buffer = &self.screen.buffer;
+        let depth = buffer.layers[layer].properties.depth;
+        let Some(above) = (layer + 1..buffer.layers.len())
+            .find(|&i| buffer.layers[i].properties.depth <= depth)
+            .filter(|&i| buffer.layers[i].properties.depth == depth)
+        else {
+            return Err(crate::EngineError::Generic(format!("Layer {layer} is the top layer of its group")));
+        };
+        let block = buffer.layer_block(layer);
+        let sibling = buffer.layer_block(above);
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-raise_layer"));
+        self.swap_layer_blocks(block, sibling, true)?;
+        self.screen.current_layer = above;
         Ok(())
     }
 
+    /// Swaps a layer - for a group the whole group - with the sibling below it
+    ///
+    /// Fails for the bottom layer of a group, it stays inside of it.
     pub fn lower_layer(&mut self, layer: usize) -> Result<()> {
         if layer == 0 {
             return Ok(());
@@ -51,25 +85,118 @@ impl EditState {
         if layer >= self.screen.buffer.layers.len() {
             return Err(crate::EngineError::Generic(format!("Invalid layer index: {layer}")));
         }
+        let buffer = &self.screen.buffer;
+        let depth = buffer.layers[layer].properties.depth;
+        let block = buffer.layer_block(layer);
+        let Some(below) = block.start.checked_sub(1) else {
+            return Ok(());
+        };
+        if buffer.layers[below].properties.depth != depth {
+            return Err(crate::EngineError::Generic(format!("Layer {layer} is the bottom layer of its group")));
+        }
+        let sibling = buffer.layer_block(below);
+        let new_index = sibling.start + block.len() - 1;
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-lower_layer"));
+        self.swap_layer_blocks(sibling, block, false)?;
+        self.screen.current_layer = new_index;
+        Ok(())
+    }
 
-        let op = EditorUndoOp::LowerLayer { layer_index: layer };
-        self.push_undo_action(op)?;
-        self.screen.current_layer = layer - 1;
+    /// Swaps two adjacent blocks of layers, `upper` starts where `lower` ends
+    ///
+    /// Uses single layer swaps, so collaboration peers can follow with layer reorders.
+    /// `raise` moves the layers of `lower` up, otherwise the layers of `upper` move down.
+    fn swap_layer_blocks(&mut self, lower: Range<usize>, upper: Range<usize>, raise: bool) -> Result<()> {
+        if raise {
+            for i in lower.rev() {
+                for layer_index in i..i + upper.len() {
+                    self.push_undo_action(EditorUndoOp::RaiseLayer { layer_index })?;
+                }
+            }
+        } else {
+            for i in upper {
+                for layer_index in (i + 1 - lower.len()..=i).rev() {
+                    self.push_undo_action(EditorUndoOp::LowerLayer { layer_index })?;
+                }
+            }
+        }
         Ok(())
     }
 
-    pub fn duplicate_layer(&mut self, layer: usize) -> Result<()> {
+    /// Puts a layer - for a group the whole group - into a new group
+    pub fn group_layer(&mut self, layer: usize) -> Result<()> {
         if layer >= self.screen.buffer.layers.len() {
             return Err(crate::EngineError::Generic(format!("Invalid layer index: {layer}")));
         }
-        let mut new_layer = self.screen.buffer.layers[layer].clone();
-        new_layer.properties.title = fl!(crate::LANGUAGE_LOADER, "layer-duplicate-name", name = new_layer.properties.title);
-        let op = EditorUndoOp::AddLayer {
-            index: layer + 1,
-            layer: Box::new(new_layer),
+        let block = self.screen.buffer.layer_block(layer);
+        let depth = self.screen.buffer.layers[layer].properties.depth;
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-group_layer"));
+        for i in block.clone() {
+            let mut new_properties = self.screen.buffer.layers[i].properties.clone();
+            new_properties.depth = new_properties.depth.saturating_add(1);
+            self.update_layer_properties(i, new_properties)?;
+        }
+        let mut group = Layer::new_group(fl!(crate::LANGUAGE_LOADER, "layer-group-name"));
+        group.properties.depth = depth;
+        self.push_undo_action(EditorUndoOp::AddLayer {
+            index: block.end,
+            layer: Box::new(group),
+        })?;
+        self.screen.current_layer = block.end;
+        Ok(())
+    }
+
+    /// Removes a group header, its members move up one level
+    pub fn ungroup_layer(&mut self, group: usize) -> Result<()> {
+        if !self.screen.buffer.layers.get(group).is_some_and(Layer::is_group) {
+            return Err(crate::EngineError::Generic(format!("Layer {group} is not a group")));
+        }
+        let children = self.screen.buffer.group_children(group);
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-ungroup_layer"));
+        for i in children {
+            let mut new_properties = self.screen.buffer.layers[i].properties.clone();
+            new_properties.depth = new_properties.depth.saturating_sub(1);
+            self.update_layer_properties(i, new_properties)?;
+        }
+        let removed = self.screen.buffer.layers[group].clone();
+        self.push_undo_action(EditorUndoOp::RemoveLayer {
+            layer_index: group,
+            layer: Box::new(removed),
+        })?;
+        self.screen.current_layer = group.saturating_sub(1);
+        self.clamp_current_layer();
+        Ok(())
+    }
+
+    /// Collapses or expands a group in the layer list
+    pub fn toggle_group_collapsed(&mut self, group: usize) -> Result<()> {
+        let Some(layer) = self.screen.buffer.layers.get(group).filter(|l| l.is_group()) else {
+            return Err(crate::EngineError::Generic(format!("Layer {group} is not a group")));
         };
-        self.push_undo_action(op)?;
-        self.screen.current_layer = layer + 1;
+        let mut new_properties = layer.properties.clone();
+        new_properties.is_collapsed = !new_properties.is_collapsed;
+        self.update_layer_properties(group, new_properties)
+    }
+
+    pub fn duplicate_layer(&mut self, layer: usize) -> Result<()> {
+        if layer >= self.screen.buffer.layers.len() {
+            return Err(crate::EngineError::Generic(format!("Invalid layer index: {layer}")));
+        }
+        // A group is copied with its members, the copy goes directly above the original
+        let block = self.screen.buffer.layer_block(layer);
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-add_layer"));
+        for (n, i) in block.clone().enumerate() {
+            let mut new_layer = self.screen.buffer.layers[i].clone();
+            if i == layer {
+                new_layer.properties.title = fl!(crate::LANGUAGE_LOADER, "layer-duplicate-name", name = new_layer.properties.title);
+            }
+            let op = EditorUndoOp::AddLayer {
+                index: block.end + n,
+                layer: Box::new(new_layer),
+            };
+            self.push_undo_action(op)?;
+        }
+        self.screen.current_layer = layer + block.len();
         Ok(())
     }
 
@@ -158,6 +285,15 @@ impl EditState {
             return Err(crate::EngineError::Generic("Cannot merge down image layer".to_string()));
         }
         println!("3");
+        let (cur, below) = (&self.screen.buffer.layers[layer], &self.screen.buffer.layers[layer - 1]);
+        if cur.is_group() || below.is_group() {
+            return Err(crate::EngineError::Generic("Cannot merge layer groups".to_string()));
+        }
+        if cur.properties.depth != below.properties.depth {
+            return Err(crate::EngineError::Generic("Cannot merge down out of a layer group".to_string()));
+        }
+        self.check_layer_unlocked(layer)?;
+        self.check_layer_unlocked(layer - 1)?;
 
         let base_layer = &self.screen.buffer.layers[layer - 1];
         let cur_layer = &self.screen.buffer.layers[layer];
@@ -279,6 +415,7 @@ impl EditState {
         if layer_idx == 0 {
             return Err(crate::EngineError::Generic("Cannot stamp down base layer".to_string()));
         }
+        self.check_layer_unlocked(layer_idx - 1)?;
 
         let (src_offset, src_size) = {
             let src = self
@@ -501,6 +638,7 @@ impl EditState {
     ///
     /// This function will return an error if .
     pub fn make_layer_transparent(&mut self) -> Result<()> {
+        self.check_layer_unlocked(self.get_current_layer()?)?;
         let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-make_transparent"));
         let layer_idx = self.screen.current_layer;
         if let Some(layer) = self.get_cur_layer_mut() {
@@ -559,7 +697,7 @@ pub fn map_char_u8<S: ::std::hash::BuildHasher>(mut ch: AttributedChar, table: &
 }
 
 lazy_static::lazy_static! {
-    static ref ROTATE_TABLE: HashMap<u8, u8> = HashMap::from([
+    pub(super) static ref ROTATE_TABLE: HashMap<u8, u8> = HashMap::from([
         // block
         (220, 221),
         (221, 223),
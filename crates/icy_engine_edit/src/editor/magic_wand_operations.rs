@@ -0,0 +1,206 @@
+//! Magic wand, select by color and grow/shrink/border of the selection
+//!
+//! All of these compute a new selection mask (document coordinates) from the current layer and
+//! replace the active selection with it, as one undo step.
+
+use i18n_embed_fl::fl;
+
+use icy_engine::{AttributeColor, XTERM_256_PALETTE};
+
+use crate::{AddType, AttributedChar, Position, Result, TextPane};
+
+use super::{undo_operation::EditorUndoOp, EditState};
+
+/// Which properties of a cell have to match for the magic wand
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellMatch {
+    pub ch: bool,
+    pub foreground: bool,
+    pub background: bool,
+    pub font_page: bool,
+    /// Maximum difference per RGB channel for colors to count as equal, 0 compares exactly
+    pub tolerance: u8,
+}
+
+impl Default for CellMatch {
+    fn default() -> Self {
+        Self {
+            ch: true,
+            foreground: false,
+            background: false,
+            font_page: false,
+            tolerance: 0,
+        }
+    }
+}
+
+impl EditState {
+    /// Selects the cells matching the cell at `pos` (document coordinates) on the current layer
+    ///
+    /// With `contiguous` only the connected region around `pos` is selected, otherwise all matching cells.
+    pub fn magic_wand_select(&mut self, pos: Position, criteria: CellMatch, contiguous: bool, add_type: AddType) -> Result<()> {
+        let Some(layer) = self.get_cur_layer() else {
+            return Err(crate::EngineError::Generic("Current layer is invalid".to_string()));
+        };
+        let offset = layer.offset();
+        let base = layer.char_at(pos - offset);
+        let (width, height) = (self.screen.buffer.width(), self.screen.buffer.height());
+        let matches = |ch: AttributedChar| self.cells_match(base, ch, criteria);
+
+        let mut cells = vec![false; (width * height) as usize];
+        if contiguous {
+            let mut visited = vec![false; cells.len()];
+            let mut stack = vec![pos];
+            while let Some(p) = stack.pop() {
+                if p.x < 0 || p.y < 0 || p.x >= width || p.y >= height {
+                    continue;
+                }
+                let idx = (p.y * width + p.x) as usize;
+                if std::mem::replace(&mut visited[idx], true) || !matches(layer.char_at(p - offset)) {
+                    continue;
+                }
+                cells[idx] = true;
+                stack.extend([
+                    Position::new(p.x - 1, p.y),
+                    Position::new(p.x + 1, p.y),
+                    Position::new(p.x, p.y - 1),
+                    Position::new(p.x, p.y + 1),
+                ]);
+            }
+        } else {
+            for y in 0..height {
+                for x in 0..width {
+                    cells[(y * width + x) as usize] = matches(layer.char_at(Position::new(x, y) - offset));
+                }
+            }
+        }
+        self.apply_selection_cells(fl!(crate::LANGUAGE_LOADER, "undo-magic_wand"), &cells, add_type)
+    }
+
+    /// Selects all cells of the current layer that use `color` as foreground or background
+    pub fn select_color(&mut self, color: AttributeColor, add_type: AddType) -> Result<()> {
+        let Some(layer) = self.get_cur_layer() else {
+            return Err(crate::EngineError::Generic("Current layer is invalid".to_string()));
+        };
+        let offset = layer.offset();
+        let (width, height) = (self.screen.buffer.width(), self.screen.buffer.height());
+        let mut cells = vec![false; (width * height) as usize];
+        for y in 0..height {
+            for x in 0..width {
+                let ch = layer.char_at(Position::new(x, y) - offset);
+                cells[(y * width + x) as usize] = ch.is_visible() && (ch.attribute.foreground_color() == color || ch.attribute.background_color() == color);
+            }
+        }
+        self.apply_selection_cells(fl!(crate::LANGUAGE_LOADER, "undo-select_color"), &cells, add_type)
+    }
+
+    /// Extends the selection by `amount` cells in every direction
+    pub fn grow_selection(&mut self, amount: i32) -> Result<()> {
+        let cells = self.selected_cells();
+        let grown = self.dilate(&cells, amount);
+        self.apply_selection_cells(fl!(crate::LANGUAGE_LOADER, "undo-grow_selection"), &grown, AddType::Default)
+    }
+
+    /// Removes `amount` cells from the edges of the selection, the buffer border doesn't count as edge
+    pub fn shrink_selection(&mut self, amount: i32) -> Result<()> {
+        let cells = self.selected_cells();
+        let shrunk = self.erode(&cells, amount);
+        self.apply_selection_cells(fl!(crate::LANGUAGE_LOADER, "undo-shrink_selection"), &shrunk, AddType::Default)
+    }
+
+    /// Keeps only the outermost `width` cells of the selection
+    pub fn border_selection(&mut self, width: i32) -> Result<()> {
+        let cells = self.selected_cells();
+        let inner = self.erode(&cells, width);
+        let border: Vec<bool> = cells.iter().zip(&inner).map(|(c, i)| *c && !*i).collect();
+        self.apply_selection_cells(fl!(crate::LANGUAGE_LOADER, "undo-border_selection"), &border, AddType::Default)
+    }
+
+    fn cells_match(&self, a: AttributedChar, b: AttributedChar, criteria: CellMatch) -> bool {
+        (!criteria.ch || a.ch == b.ch)
+            && (!criteria.foreground || self.colors_match(a.attribute.foreground_color(), b.attribute.foreground_color(), criteria.tolerance))
+            && (!criteria.background || self.colors_match(a.attribute.background_color(), b.attribute.background_color(), criteria.tolerance))
+            && (!criteria.font_page || a.font_page() == b.font_page())
+    }
+
+    fn colors_match(&self, a: AttributeColor, b: AttributeColor, tolerance: u8) -> bool {
+        if a == b {
+            return true;
+        }
+        if tolerance == 0 {
+            return false;
+        }
+        match (self.color_rgb(a), self.color_rgb(b)) {
+            (Some((r1, g1, b1)), Some((r2, g2, b2))) => r1.abs_diff(r2) <= tolerance && g1.abs_diff(g2) <= tolerance && b1.abs_diff(b2) <= tolerance,
+            _ => false,
+        }
+    }
+
+    fn color_rgb(&self, color: AttributeColor) -> Option<(u8, u8, u8)> {
+        match color {
+            AttributeColor::Palette(index) => Some(self.screen.buffer.palette.rgb(index as u32)),
+            AttributeColor::ExtendedPalette(index) => Some(XTERM_256_PALETTE[index as usize].1.rgb()),
+            AttributeColor::Rgb(r, g, b) => Some((r, g, b)),
+            AttributeColor::Transparent => None,
+        }
+    }
+
+    /// The selection (rectangle and mask) as row major cell flags of the buffer
+    fn selected_cells(&self) -> Vec<bool> {
+        let (width, height) = (self.screen.buffer.width(), self.screen.buffer.height());
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| Position::new(x, y)))
+            .map(|p| self.is_selected(p))
+            .collect()
+    }
+
+    /// Selects every cell within `amount` cells (chebyshev distance) of a selected one
+    fn dilate(&self, cells: &[bool], amount: i32) -> Vec<bool> {
+        let (width, height) = (self.screen.buffer.width(), self.screen.buffer.height());
+        // Separable: first along the rows, then along the columns
+        let mut rows = vec![false; cells.len()];
+        for y in 0..height {
+            for x in 0..width {
+                rows[(y * width + x) as usize] = ((x - amount).max(0)..=(x + amount).min(width - 1)).any(|sx| cells[(y * width + sx) as usize]);
+            }
+        }
+        let mut result = vec![false; cells.len()];
+        for y in 0..height {
+            for x in 0..width {
+                result[(y * width + x) as usize] = ((y - amount).max(0)..=(y + amount).min(height - 1)).any(|sy| rows[(sy * width + x) as usize]);
+            }
+        }
+        result
+    }
+
+    fn erode(&self, cells: &[bool], amount: i32) -> Vec<bool> {
+        let inverted: Vec<bool> = cells.iter().map(|c| !c).collect();
+        self.dilate(&inverted, amount).into_iter().map(|c| !c).collect()
+    }
+
+    /// Replaces the selection with `cells` combined with the current selection according to `add_type`
+    fn apply_selection_cells(&mut self, description: String, cells: &[bool], add_type: AddType) -> Result<()> {
+        let width = self.screen.buffer.width();
+        let current = if add_type == AddType::Default { Vec::new() } else { self.selected_cells() };
+        let old = self.selection_mask.clone();
+        let mut new = old.clone();
+        new.clear();
+        for (idx, &cell) in cells.iter().enumerate() {
+            let selected = match add_type {
+                AddType::Default => cell,
+                AddType::Add => cell || current[idx],
+                AddType::Subtract => !cell && current[idx],
+            };
+            if selected {
+                new.set_is_selected(Position::new(idx as i32 % width, idx as i32 / width), true);
+            }
+        }
+
+        let _undo = self.begin_atomic_undo(description.clone());
+        self.deselect()?;
+        if new != old {
+            self.push_undo_action(EditorUndoOp::SetSelectionMask { description, old, new })?;
+        }
+        Ok(())
+    }
+}
@@ -9,6 +9,12 @@ pub use undo_operation::EditorUndoOp;
 pub mod session_state;
 pub use session_state::AnsiEditorSessionState;
 
+pub mod journal;
+pub use journal::{
+    document_checksum, timelapse_script, EditJournal, JournalData, JournalEntry, JournalEvent, JournalPlayer, JournalRecorder, TimelapseStep,
+    JOURNAL_EXTENSION, JOURNAL_VERSION,
+};
+
 mod editor_error;
 pub use editor_error::*;
 
@@ -20,9 +26,22 @@ mod area_operations;
 pub(crate) use area_operations::{flip_layer_x, flip_layer_y};
 pub use area_operations::{generate_flipx_table, generate_flipy_table};
 mod edit_operations;
+mod fill_operations;
 mod font_operations;
+mod magic_wand_operations;
+pub use magic_wand_operations::CellMatch;
+mod recolor_operations;
+pub use recolor_operations::{ColorRemap, HueFamily, RemapPreset};
+mod replace_operations;
+pub use replace_operations::{CellPattern, ReplaceScope};
 mod selection_operations;
+mod symmetry_operations;
+pub use symmetry_operations::Symmetry;
 mod tag_operations;
+mod text_box;
+pub use text_box::{layout_text, TextAlignment, TextBox};
+mod transform_operations;
+pub use transform_operations::ScaleMode;
 
 mod tdf_renderer;
 pub use tdf_renderer::TdfEditStateRenderer;
@@ -151,7 +170,8 @@ pub struct EditState {
     current_tag: usize,
 
     outline_style: usize,
-    mirror_mode: bool,
+    symmetry: Symmetry,
+    symmetry_flip_tables: symmetry_operations::FlipTables,
 
     /// Serializable undo stack (wrapped in Arc<Mutex> for atomic operations)
     undo_stack: Arc<Mutex<EditorUndoStack>>,
@@ -379,7 +399,8 @@ impl Default for EditState {
             undo_stack: Arc::new(Mutex::new(EditorUndoStack::new())),
             current_tag: 0,
             outline_style: 0,
-            mirror_mode: false,
+            symmetry: Symmetry::default(),
+            symmetry_flip_tables: Default::default(),
             tool_overlay_mask,
             is_palette_dirty: false,
             sauce_meta: SauceMetaData::default(),
@@ -699,6 +720,9 @@ impl EditState {
 
     /// Push and execute an undo operation
     pub(crate) fn push_undo_action(&mut self, mut op: EditorUndoOp) -> Result<()> {
+        if let Some(layer) = op.edited_layer() {
+            self.check_layer_unlocked(layer)?;
+        }
         op.redo(self)?;
         self.push_plain_undo(op)
     }
@@ -754,12 +778,18 @@ impl EditState {
         Some((offset.x, offset.y))
     }
 
+    /// Whether any symmetry mode is active
     pub fn get_mirror_mode(&self) -> bool {
-        self.mirror_mode
+        self.symmetry.is_enabled()
     }
 
+    /// Turns symmetry off, or on with the classic horizontal mirroring if it was off
     pub fn set_mirror_mode(&mut self, mirror_mode: bool) {
-        self.mirror_mode = mirror_mode;
+        if !mirror_mode {
+            self.set_symmetry(Symmetry::default());
+        } else if !self.symmetry.is_enabled() {
+            self.set_symmetry(Symmetry::new(crate::brushes::MirrorMode::Horizontal));
+        }
     }
 
     /// Set the selection mask (without undo, just plain operation)
@@ -0,0 +1,381 @@
+//! Recoloring of cells with a color mapping
+//!
+//! A `ColorRemap` maps foreground and background colors separately, colors without an entry stay as they are.
+//! `RemapPreset` computes the usual mappings, a remap of several layers ends up in one undo step.
+//! The preview is drawn on the buffer's preview layer, the document isn't touched until the remap is applied.
+
+use i18n_embed_fl::fl;
+
+use icy_engine::{AttributeColor, XTERM_256_PALETTE};
+
+use crate::{AttributedChar, CharGrid, Layer, Palette, Position, Rectangle, Result, TextPane};
+
+use super::{undo_operation::EditorUndoOp, EditState, ReplaceScope};
+
+/// Hue ranges of 60° centered on the primary and secondary colors
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HueFamily {
+    #[default]
+    Red,
+    Yellow,
+    Green,
+    Cyan,
+    Blue,
+    Magenta,
+}
+
+impl HueFamily {
+    pub const ALL: [HueFamily; 6] = [
+        HueFamily::Red,
+        HueFamily::Yellow,
+        HueFamily::Green,
+        HueFamily::Cyan,
+        HueFamily::Blue,
+        HueFamily::Magenta,
+    ];
+
+    /// Family of a hue in degrees
+    pub fn of(hue: f32) -> Self {
+        Self::ALL[(((hue + 30.0).rem_euclid(360.0)) / 60.0) as usize % 6]
+    }
+
+    fn center(self) -> f32 {
+        match self {
+            HueFamily::Red => 0.0,
+            HueFamily::Yellow => 60.0,
+            HueFamily::Green => 120.0,
+            HueFamily::Cyan => 180.0,
+            HueFamily::Blue => 240.0,
+            HueFamily::Magenta => 300.0,
+        }
+    }
+}
+
+/// Ready made color mappings
+#[derive(Clone, Debug)]
+pub enum RemapPreset {
+    /// Exchanges two hue families, lightness and saturation are kept
+    SwapHue(HueFamily, HueFamily),
+    Grayscale,
+    /// Dark colors become light and the other way round, the hue is kept
+    InvertBrightness,
+    /// The nearest color of another palette, which replaces the document palette
+    NearestInPalette(Palette),
+}
+
+impl RemapPreset {
+    /// Palette the mapped palette indices refer to if it isn't the document palette
+    pub fn target_palette(&self) -> Option<&Palette> {
+        match self {
+            RemapPreset::NearestInPalette(palette) => Some(palette),
+            _ => None,
+        }
+    }
+
+    /// Where `color` of a document using `palette` ends up
+    ///
+    /// Palette and extended colors are snapped to the nearest color of their palette, rgb colors stay rgb
+    /// except for [`RemapPreset::NearestInPalette`] which maps everything into the target palette.
+    pub fn map_color(&self, palette: &Palette, color: AttributeColor) -> AttributeColor {
+        let Some(rgb) = resolve_rgb(palette, color) else {
+            return color;
+        };
+        if let Some(target) = self.target_palette() {
+            return AttributeColor::Palette(nearest_index(target.color_iter().map(|c| c.rgb()), rgb));
+        }
+        let (r, g, b) = self.transform(rgb);
+        match color {
+            AttributeColor::Palette(_) => AttributeColor::Palette(nearest_index(palette.color_iter().map(|c| c.rgb()), (r, g, b))),
+            AttributeColor::ExtendedPalette(_) => AttributeColor::ExtendedPalette(nearest_index(XTERM_256_PALETTE.iter().map(|(_, c)| c.rgb()), (r, g, b))),
+            _ => AttributeColor::Rgb(r, g, b),
+        }
+    }
+
+    fn transform(&self, (r, g, b): (u8, u8, u8)) -> (u8, u8, u8) {
+        match self {
+            RemapPreset::SwapHue(a, b_family) => {
+                let (h, s, l) = rgb_to_hsl((r, g, b));
+                // grays have no hue to swap
+                if s < 0.1 {
+                    return (r, g, b);
+                }
+                let family = HueFamily::of(h);
+                let h = if family == *a {
+                    h - a.center() + b_family.center()
+                } else if family == *b_family {
+                    h - b_family.center() + a.center()
+                } else {
+                    return (r, g, b);
+                };
+                hsl_to_rgb((h.rem_euclid(360.0), s, l))
+            }
+            RemapPreset::Grayscale => {
+                let y = ((299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000) as u8;
+                (y, y, y)
+            }
+            RemapPreset::InvertBrightness => {
+                let (h, s, l) = rgb_to_hsl((r, g, b));
+                hsl_to_rgb((h, s, 1.0 - l))
+            }
+            RemapPreset::NearestInPalette(_) => (r, g, b),
+        }
+    }
+}
+
+fn resolve_rgb(palette: &Palette, color: AttributeColor) -> Option<(u8, u8, u8)> {
+    match color {
+        AttributeColor::Palette(index) => Some(palette.rgb(index as u32)),
+        AttributeColor::ExtendedPalette(index) => Some(XTERM_256_PALETTE[index as usize].1.rgb()),
+        AttributeColor::Rgb(r, g, b) => Some((r, g, b)),
+        AttributeColor::Transparent => None,
+    }
+}
+
+/// Index of the nearest color, only the first 256 colors are candidates so the index fits an attribute
+fn nearest_index(colors: impl Iterator<Item = (u8, u8, u8)>, (r, g, b): (u8, u8, u8)) -> u8 {
+    colors
+        .take(256)
+        .enumerate()
+        .min_by_key(|(_, (cr, cg, cb))| {
+            let dr = *cr as i32 - r as i32;
+            let dg = *cg as i32 - g as i32;
+            let db = *cb as i32 - b as i32;
+            dr * dr + dg * dg + db * db
+        })
+        .map_or(0, |(i, _)| i as u8)
+}
+
+/// Hue in degrees, saturation and lightness in 0..=1
+fn rgb_to_hsl((r, g, b): (u8, u8, u8)) -> (f32, f32, f32) {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+    if d == 0.0 {
+        return (0.0, 0.0, l);
+    }
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+    (h, s, l)
+}
+
+fn hsl_to_rgb((h, s, l): (f32, f32, f32)) -> (u8, u8, u8) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = c * (1.0 - ((h / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let m = l - c / 2.0;
+    let (r, g, b) = match (h / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let to_u8 = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+/// Foreground and background color mapping, colors without an entry are kept
+#[derive(Clone, Debug, Default)]
+pub struct ColorRemap {
+    pub foreground: Vec<(AttributeColor, AttributeColor)>,
+    pub background: Vec<(AttributeColor, AttributeColor)>,
+    /// Palette the target indices refer to, replaces the document palette
+    ///
+    /// A remap with a palette always covers the whole document.
+    pub palette: Option<Palette>,
+}
+
+impl ColorRemap {
+    /// Maps `foreground` and `background` colors of a document using `palette` with `preset`
+    pub fn from_preset(preset: &RemapPreset, palette: &Palette, foreground: &[AttributeColor], background: &[AttributeColor]) -> Self {
+        let map = |colors: &[AttributeColor]| colors.iter().map(|c| (*c, preset.map_color(palette, *c))).collect();
+        Self {
+            foreground: map(foreground),
+            background: map(background),
+            palette: preset.target_palette().cloned(),
+        }
+    }
+
+    /// Whether applying the remap changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.palette.is_none() && self.foreground.iter().chain(&self.background).all(|(from, to)| from == to)
+    }
+
+    pub fn apply(&self, mut ch: AttributedChar) -> AttributedChar {
+        if let Some((_, to)) = self.foreground.iter().find(|(from, _)| *from == ch.attribute.foreground_color()) {
+            ch.attribute.set_foreground_color(*to);
+        }
+        if let Some((_, to)) = self.background.iter().find(|(from, _)| *from == ch.attribute.background_color()) {
+            ch.attribute.set_background_color(*to);
+        }
+        ch
+    }
+}
+
+impl EditState {
+    /// Distinct foreground and background colors of the visible cells in `scope`
+    pub fn used_colors(&self, scope: ReplaceScope) -> (Vec<AttributeColor>, Vec<AttributeColor>) {
+        let mut foreground = Vec::new();
+        let mut background = Vec::new();
+        for layer_idx in self.scope_layers(scope) {
+            let layer = &self.screen.buffer.layers[layer_idx];
+            let offset = layer.offset();
+            for y in 0..layer.height() {
+                for x in 0..layer.width() {
+                    let pos = Position::new(x, y);
+                    if scope == ReplaceScope::Selection && !self.is_selected(pos + offset) {
+                        continue;
+                    }
+                    let ch = layer.char_at(pos);
+                    if !ch.is_visible() {
+                        continue;
+                    }
+                    for (color, colors) in [
+                        (ch.attribute.foreground_color(), &mut foreground),
+                        (ch.attribute.background_color(), &mut background),
+                    ] {
+                        if !color.is_transparent() && !colors.contains(&color) {
+                            colors.push(color);
+                        }
+                    }
+                }
+            }
+        }
+        foreground.sort_by_key(|c| c.to_u32());
+        background.sort_by_key(|c| c.to_u32());
+        (foreground, background)
+    }
+
+    /// Recolors the cells in `scope` as one undo step, returns the number of changed cells
+    ///
+    /// Fails without changing anything if a layer to recolor is locked, a palette change needs all layers unlocked.
+    pub fn remap_colors(&mut self, remap: &ColorRemap, scope: ReplaceScope) -> Result<usize> {
+        let (changes, count) = self.color_remap_changes(remap, scope);
+        if changes.is_empty() && remap.palette.is_none() {
+            return Ok(0);
+        }
+        if remap.palette.is_some() {
+            for layer in 0..self.screen.buffer.layers.len() {
+                self.check_layer_unlocked(layer)?;
+            }
+        }
+        for (layer, ..) in &changes {
+            self.check_layer_unlocked(*layer)?;
+        }
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-remap_colors"));
+        for (layer, pos, old_chars, new_chars) in changes {
+            self.push_undo_action(EditorUndoOp::LayerChange {
+                layer,
+                pos,
+                old_chars,
+                new_chars,
+            })?;
+        }
+        if let Some(pal) = &remap.palette {
+            self.push_undo_action(EditorUndoOp::SwitchPalettte { pal: pal.clone() })?;
+        }
+        Ok(count)
+    }
+
+    /// Shows the remap on the preview layer without changing the document, remove it with [`Self::end_color_remap_preview`]
+    ///
+    /// The preview holds the remapped cells as they are seen, with rgb colors so a new palette shows as well.
+    pub fn preview_color_remap(&mut self, remap: &ColorRemap, scope: ReplaceScope) {
+        let (changes, _) = self.color_remap_changes(remap, scope);
+        let mut remapped = self.screen.buffer.clone();
+        remapped.preview_layer = None;
+        let mut area: Option<Rectangle> = None;
+        for (layer, pos, _, new_chars) in &changes {
+            let size = (new_chars.first().map_or(0, Vec::len) as i32, new_chars.len() as i32);
+            let rect = Rectangle::from_min_size(*pos + remapped.layers[*layer].offset(), size);
+            area = Some(area.map_or(rect, |area| area.union(&rect)));
+            crate::stamp_char_grid(&mut remapped.layers[*layer], *pos, new_chars);
+        }
+        if let Some(pal) = &remap.palette {
+            remapped.palette = pal.clone();
+            area = Some(Rectangle::from_min_size((0, 0), remapped.size()));
+        }
+        let area = area.map(|area| area.intersect(&Rectangle::from_min_size((0, 0), remapped.size())));
+        let Some(area) = area.filter(|area| !area.is_empty()) else {
+            self.end_color_remap_preview();
+            return;
+        };
+
+        let mut preview = Layer::new("Recolor", area.size());
+        preview.set_offset(area.start);
+        for y in 0..area.height() {
+            for x in 0..area.width() {
+                let mut ch = remapped.char_at(area.start + Position::new(x, y));
+                if let Some((r, g, b)) = resolve_rgb(&remapped.palette, ch.attribute.foreground_color()) {
+                    ch.attribute.set_foreground_color(AttributeColor::Rgb(r, g, b));
+                }
+                if let Some((r, g, b)) = resolve_rgb(&remapped.palette, ch.attribute.background_color()) {
+                    ch.attribute.set_background_color(AttributeColor::Rgb(r, g, b));
+                }
+                preview.set_char(Position::new(x, y), ch);
+            }
+        }
+        self.screen.buffer.preview_layer = Some(preview);
+        self.mark_buffer_dirty();
+    }
+
+    /// Removes the preview of a remap
+    pub fn end_color_remap_preview(&mut self) {
+        if self.screen.buffer.preview_layer.take().is_some() {
+            self.mark_buffer_dirty();
+        }
+    }
+
+    /// Changed area of each layer as layer, position, old and new cells, plus the number of changed cells
+    ///
+    /// A remap with a palette covers every layer, hidden and locked ones included, as the palette applies to all of them.
+    fn color_remap_changes(&self, remap: &ColorRemap, scope: ReplaceScope) -> (Vec<(usize, Position, CharGrid, CharGrid)>, usize) {
+        let (scope, layers) = if remap.palette.is_some() {
+            (ReplaceScope::Document, (0..self.screen.buffer.layers.len()).collect())
+        } else {
+            (scope, self.scope_layers(scope))
+        };
+        let mut changes = Vec::new();
+        let mut count = 0;
+        for layer_idx in layers {
+            let layer = &self.screen.buffer.layers[layer_idx];
+            let offset = layer.offset();
+            let mut cells = Vec::new();
+            for y in 0..layer.height() {
+                for x in 0..layer.width() {
+                    let pos = Position::new(x, y);
+                    if scope == ReplaceScope::Selection && !self.is_selected(pos + offset) {
+                        continue;
+                    }
+                    let ch = layer.char_at(pos);
+                    let new_ch = remap.apply(ch);
+                    if ch.is_visible() && new_ch != ch {
+                        cells.push((pos, new_ch));
+                    }
+                }
+            }
+            let Some((first, _)) = cells.first() else {
+                continue;
+            };
+            count += cells.len();
+            let (min, max) = cells.iter().fold((*first, *first), |(min, max), (p, _)| (min.min(*p), max.max(*p)));
+            let area = Rectangle::from_min_size(min, (max.x - min.x + 1, max.y - min.y + 1));
+            let old_chars = crate::chars_from_area(layer, area);
+            let mut new_chars = old_chars.clone();
+            for (pos, ch) in cells {
+                let local = pos - area.start;
+                new_chars[local.y as usize][local.x as usize] = ch;
+            }
+            changes.push((layer_idx, area.start, old_chars, new_chars));
+        }
+        (changes, count)
+    }
+}
@@ -0,0 +1,152 @@
+//! Find and replace of characters, colors and attributes
+//!
+//! A `CellPattern` describes the cells to look for, every `None` field is a wildcard. Matching cells
+//! get the `Some` fields of a replacement pattern written to them, all layers touched end up in one undo step.
+//! Document wide searches skip hidden and locked layers.
+
+use i18n_embed_fl::fl;
+
+use icy_engine::AttributeColor;
+
+use crate::{AttributedChar, Position, Rectangle, Result, TextPane};
+
+use super::{undo_operation::EditorUndoOp, EditState};
+
+/// Cell properties to search for or to write, `None` matches anything and keeps the value of the cell
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CellPattern {
+    pub ch: Option<char>,
+    pub foreground: Option<AttributeColor>,
+    pub background: Option<AttributeColor>,
+    /// Blink attribute, in ice color mode this is the high intensity background bit
+    pub blink: Option<bool>,
+    pub font_page: Option<u8>,
+}
+
+impl CellPattern {
+    /// Invisible cells never match
+    pub fn matches(&self, ch: AttributedChar) -> bool {
+        ch.is_visible()
+            && self.ch.is_none_or(|c| c == ch.ch)
+            && self.foreground.is_none_or(|c| c == ch.attribute.foreground_color())
+            && self.background.is_none_or(|c| c == ch.attribute.background_color())
+            && self.blink.is_none_or(|b| b == ch.attribute.is_blinking())
+            && self.font_page.is_none_or(|p| p == ch.font_page())
+    }
+
+    /// Writes every property the pattern specifies
+    pub fn apply(&self, mut ch: AttributedChar) -> AttributedChar {
+        if let Some(c) = self.ch {
+            ch.ch = c;
+        }
+        if let Some(color) = self.foreground {
+            ch.attribute.set_foreground_color(color);
+        }
+        if let Some(color) = self.background {
+            ch.attribute.set_background_color(color);
+        }
+        if let Some(blink) = self.blink {
+            ch.attribute.set_is_blinking(blink);
+        }
+        if let Some(page) = self.font_page {
+            ch.set_font_page(page);
+        }
+        ch
+    }
+}
+
+/// Part of the document a find / replace looks at
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReplaceScope {
+    /// The selected cells of the current layer
+    Selection,
+    /// The whole current layer
+    #[default]
+    Layer,
+    /// All layers
+    Document,
+}
+
+impl EditState {
+    /// Scope used when none is given: the selection if there is one, otherwise the current layer
+    pub fn default_replace_scope(&self) -> ReplaceScope {
+        if self.is_something_selected() {
+            ReplaceScope::Selection
+        } else {
+            ReplaceScope::Layer
+        }
+    }
+
+    /// All cells matching `pattern` in `scope` as layer index and layer local position
+    pub fn find_cells(&self, pattern: &CellPattern, scope: ReplaceScope) -> Vec<(usize, Position)> {
+        let mut result = Vec::new();
+        for layer_idx in self.scope_layers(scope) {
+            let layer = &self.screen.buffer.layers[layer_idx];
+            let offset = layer.offset();
+            for y in 0..layer.height() {
+                for x in 0..layer.width() {
+                    let pos = Position::new(x, y);
+                    if scope == ReplaceScope::Selection && !self.is_selected(pos + offset) {
+                        continue;
+                    }
+                    if pattern.matches(layer.char_at(pos)) {
+                        result.push((layer_idx, pos));
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Applies `replacement` to all cells matching `pattern` in `scope`, returns the number of matches
+    ///
+    /// Fails without changing anything if a layer with matches is locked.
+    pub fn replace_cells(&mut self, pattern: &CellPattern, replacement: &CellPattern, scope: ReplaceScope) -> Result<usize> {
+        let matches = self.find_cells(pattern, scope);
+        if matches.is_empty() {
+            return Ok(0);
+        }
+        for layer_idx in self.scope_layers(scope) {
+            if matches.iter().any(|(l, _)| *l == layer_idx) {
+                self.check_layer_unlocked(layer_idx)?;
+            }
+        }
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-find_replace"));
+        for layer_idx in self.scope_layers(scope) {
+            let cells: Vec<Position> = matches.iter().filter(|(l, _)| *l == layer_idx).map(|(_, p)| *p).collect();
+            let Some(first) = cells.first() else {
+                continue;
+            };
+            let (min, max) = cells.iter().fold((*first, *first), |(min, max), p| (min.min(*p), max.max(*p)));
+            let area = Rectangle::from_min_size(min, (max.x - min.x + 1, max.y - min.y + 1));
+            let old_chars = crate::chars_from_area(&self.screen.buffer.layers[layer_idx], area);
+            let mut new_chars = old_chars.clone();
+            for pos in cells {
+                let local = pos - area.start;
+                let cell = &mut new_chars[local.y as usize][local.x as usize];
+                *cell = replacement.apply(*cell);
+            }
+            if new_chars != old_chars {
+                self.push_undo_action(EditorUndoOp::LayerChange {
+                    layer: layer_idx,
+                    pos: area.start,
+                    old_chars,
+                    new_chars,
+                })?;
+            }
+        }
+        Ok(matches.len())
+    }
+
+    /// Layers searched in `scope`, a document wide search skips hidden and locked layers
+    pub(super) fn scope_layers(&self, scope: ReplaceScope) -> Vec<usize> {
+        let buffer = &self.screen.buffer;
+        match scope {
+            ReplaceScope::Document => (0..buffer.layers.len())
+                .filter(|&i| buffer.is_layer_visible(i) && !buffer.is_layer_locked(i))
+                .collect(),
+            ReplaceScope::Selection if !self.is_something_selected() => Vec::new(),
+            _ => self.get_current_layer().ok().into_iter().collect(),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Symmetry drawing
+//!
+//! With symmetry enabled every character written through `set_char` / `set_char_in_atomic` is
+//! repeated at its mirrored positions, so all brush tools (pencil, line, shapes, fill) paint
+//! symmetric without knowing about it. Directional glyphs are mapped with the flip tables, the
+//! mirrored writes end up in the same undo entry as the stroke.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{brushes::MirrorMode, AttributedChar, Position, Rectangle, TextPane};
+
+use super::{
+    area_operations::{generate_flipx_table, generate_flipy_table, map_char},
+    EditState,
+};
+
+pub(crate) type FlipTables = HashMap<u8, [BTreeMap<char, (bool, char)>; 2]>;
+
+/// Symmetry settings of the editor
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Symmetry {
+    pub mode: MirrorMode,
+    /// Mirror axes in half cells (document coordinates): `x = 2 * column` is the line left of `column`,
+    /// `x = 2 * column + 1` runs through its center. `None` mirrors around the center of the current layer.
+    pub axis: Option<Position>,
+}
+
+impl Symmetry {
+    pub fn new(mode: MirrorMode) -> Self {
+        Self { mode, axis: None }
+    }
+
+    /// Places the axes through the center of the cell at `pos` (document coordinates)
+    pub fn with_axis_at_cell(mut self, pos: Position) -> Self {
+        self.axis = Some(Position::new(2 * pos.x + 1, 2 * pos.y + 1));
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.mode != MirrorMode::None
+    }
+
+    /// Mirrored copies of `pos` around `axis` (in half units of `pos`), excluding `pos` itself.
+    /// Each entry tells whether it's flipped in x and y.
+    pub fn mirror_positions(&self, pos: Position, axis: Position) -> Vec<(Position, bool, bool)> {
+        let (flip_x, flip_y) = match self.mode {
+            MirrorMode::None => return Vec::new(),
+            MirrorMode::Horizontal => (true, false),
+            MirrorMode::Vertical => (false, true),
+            MirrorMode::Both => (true, true),
+        };
+        let mirror = Position::new(axis.x - pos.x - 1, axis.y - pos.y - 1);
+        let mut result: Vec<(Position, bool, bool)> = Vec::new();
+        for (mx, my) in [(true, false), (false, true), (true, true)] {
+            if (mx && !flip_x) || (my && !flip_y) {
+                continue;
+            }
+            let target = Position::new(if mx { mirror.x } else { pos.x }, if my { mirror.y } else { pos.y });
+            if target != pos && !result.iter().any(|(p, _, _)| *p == target) {
+                result.push((target, mx, my));
+            }
+        }
+        result
+    }
+}
+
+impl EditState {
+    pub fn symmetry(&self) -> Symmetry {
+        self.symmetry
+    }
+
+    pub fn set_symmetry(&mut self, symmetry: Symmetry) {
+        self.symmetry = symmetry;
+        self.symmetry_flip_tables.clear();
+    }
+
+    /// The symmetry axes of the current layer in layer local half cells
+    pub fn symmetry_axis(&self) -> Option<Position> {
+        let layer = self.get_cur_layer()?;
+        Some(match self.symmetry.axis {
+            Some(axis) => axis - layer.offset() - layer.offset(),
+            None => Position::new(layer.width(), layer.height()),
+        })
+    }
+
+    /// The mirrored copies of `ch` at the layer local `pos`, excluding `pos` itself
+    pub(crate) fn mirrored_chars(&mut self, pos: Position, ch: AttributedChar) -> Vec<(Position, AttributedChar)> {
+        if !self.symmetry.is_enabled() {
+            return Vec::new();
+        }
+        let (Some(axis), Some(layer)) = (self.symmetry_axis(), self.get_cur_layer()) else {
+            return Vec::new();
+        };
+        let bounds = Rectangle::from_min_size((0, 0), layer.size());
+        self.symmetry
+            .mirror_positions(pos, axis)
+            .into_iter()
+            .filter(|(target, _, _)| bounds.is_inside(*target))
+            .map(|(target, flip_x, flip_y)| (target, self.flip_char(ch, flip_x, flip_y)))
+            .collect()
+    }
+
+    fn flip_char(&mut self, mut ch: AttributedChar, flip_x: bool, flip_y: bool) -> AttributedChar {
+        let page = ch.font_page();
+        if !self.symmetry_flip_tables.contains_key(&page) {
+            let Some(font) = self.screen.buffer.font(page) else {
+                return ch;
+            };
+            let tables = [generate_flipx_table(font), generate_flipy_table(font)];
+            self.symmetry_flip_tables.insert(page, tables);
+        }
+        let [x_table, y_table] = &self.symmetry_flip_tables[&page];
+        if flip_x {
+            ch = map_char(ch, x_table);
+        }
+        if flip_y {
+            ch = map_char(ch, y_table);
+        }
+        ch
+    }
+}
@@ -7,7 +7,7 @@
 //! - Backspace and delete handling
 //! - Tab navigation (8-column stops)
 //! - New line handling (insert mode: insert row, then move)
-//! - Mirror mode support (same char at mirrored position)
+//! - Symmetry support (flipped char at the mirrored positions)
 //!
 //! # Usage
 //! ```ignore
@@ -34,13 +34,12 @@ impl EditState {
     /// In overwrite mode, the character is simply replaced.
     /// Caret moves right after typing (unless in a special "overwrite_mode" where it stays).
     ///
-    /// Mirror mode: if enabled, the same character is placed at the mirrored x position.
+    /// Symmetry: if enabled, the (flipped) character is placed at the mirrored positions as well.
     pub fn type_key(&mut self, char_code: char) -> Result<()> {
         let pos = self.get_caret().position();
         let layer_idx = self.get_current_layer()?;
         let insert_mode = self.get_caret().insert_mode;
         let caret_attr = self.get_caret().attribute;
-        let mirror_mode = self.mirror_mode;
 
         // Get layer dimensions and characters we need
         let (layer_width, old_char, chars_to_shift) = {
@@ -102,24 +101,17 @@ impl EditState {
             redo_caret: Some(redo_pos),
         })?;
 
-        // Handle mirror mode: set same character at mirrored position
-        if mirror_mode {
-            let mirror_x = layer_width - pos.x - 1;
-            if mirror_x != pos.x && mirror_x >= 0 {
-                let mirror_pos = Position::new(mirror_x, pos.y);
-                // Need to get mirror_old from layer
-                let mirror_old = self.get_cur_layer().map(|l| l.char_at(mirror_pos)).unwrap_or_default();
-                // For now, use the same character (no flip_code_x)
-                // TODO: Implement character mirroring table for box drawing chars
-                self.push_undo_action(EditorUndoOp::SetChar {
-                    pos: mirror_pos,
-                    layer: layer_idx,
-                    old: mirror_old,
-                    new: new_char,
-                    undo_caret: None,
-                    redo_caret: None,
-                })?;
-            }
+        // Symmetry: repeat the character at the mirrored positions
+        for (mirror_pos, mirror_char) in self.mirrored_chars(pos, new_char) {
+            let mirror_old = self.get_cur_layer().map(|l| l.char_at(mirror_pos)).unwrap_or_default();
+            self.push_undo_action(EditorUndoOp::SetChar {
+                pos: mirror_pos,
+                layer: layer_idx,
+                old: mirror_old,
+                new: mirror_char,
+                undo_caret: None,
+                redo_caret: None,
+            })?;
         }
 
         // Caret movement is now handled by the SetChar undo/redo
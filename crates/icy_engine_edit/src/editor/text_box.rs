@@ -0,0 +1,200 @@
+//! Word wrapped text inside a rectangle
+//!
+//! A `TextBox` stays editable while it is previewed: the preview is the buffer's preview layer, which is
+//! drawn above the document but never part of it. Committing writes the text to the current layer as one undo step.
+
+use i18n_embed_fl::fl;
+
+use crate::{AttributedChar, CharGrid, Layer, Position, Rectangle, Result, TextAttribute, TextPane};
+
+use super::{area_operations::aligned_start, undo_operation::EditorUndoOp, EditState};
+
+/// Horizontal placement of the lines inside the box
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlignment {
+    /// Like [`EditState::justify_left`]
+    #[default]
+    Left,
+    /// Like [`EditState::center`]
+    Center,
+    /// Like [`EditState::justify_right`]
+    Right,
+    /// Stretches the gaps between words to the box width, the last line of a paragraph stays left aligned
+    Justify,
+}
+
+/// Wraps `text` to `width` columns, returns each line with its x offset
+///
+/// `\n` starts a new paragraph, words longer than `width` are split.
+pub fn layout_text(text: &str, width: usize, alignment: TextAlignment) -> Vec<(usize, String)> {
+    let mut result = Vec::new();
+    if width == 0 {
+        return result;
+    }
+    for paragraph in text.split('\n') {
+        let lines = wrap_paragraph(paragraph, width);
+        let last = lines.len() - 1;
+        for (i, words) in lines.into_iter().enumerate() {
+            if alignment == TextAlignment::Justify && i < last && words.len() > 1 {
+                result.push((0, justify_line(&words, width)));
+                continue;
+            }
+            let line = words.join(" ");
+            let free = width - line.chars().count();
+            result.push((aligned_start(free as i32, alignment) as usize, line));
+        }
+    }
+    result
+}
+
+/// Greedy word wrap of a single paragraph, always returns at least one (maybe empty) line
+fn wrap_paragraph(paragraph: &str, width: usize) -> Vec<Vec<String>> {
+    let mut lines = Vec::new();
+    let mut line: Vec<String> = Vec::new();
+    let mut line_len = 0;
+    for word in paragraph.split(' ').filter(|w| !w.is_empty()) {
+        let chars: Vec<char> = word.chars().collect();
+        for chunk in chars.chunks(width) {
+            let needed = if line.is_empty() { chunk.len() } else { line_len + 1 + chunk.len() };
+            if needed > width {
+                lines.push(std::mem::take(&mut line));
+                line_len = 0;
+            }
+            line_len += if line.is_empty() { chunk.len() } else { 1 + chunk.len() };
+            line.push(chunk.iter().collect());
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+fn justify_line(words: &[String], width: usize) -> String {
+    let text_len: usize = words.iter().map(|w| w.chars().count()).sum();
+    let gaps = words.len() - 1;
+    let spaces = width - text_len;
+    let mut line = String::new();
+    for (i, word) in words.iter().enumerate() {
+        line.push_str(word);
+        if i < gaps {
+            // the leftmost gaps take the remainder
+            let gap = spaces / gaps + usize::from(i < spaces % gaps);
+            line.push_str(&" ".repeat(gap));
+        }
+    }
+    line
+}
+
+/// Text that is laid out inside a rectangle of the current layer
+#[derive(Clone, Debug, Default)]
+pub struct TextBox {
+    /// Box area in layer coordinates
+    pub rect: Rectangle,
+    pub text: String,
+    pub alignment: TextAlignment,
+    pub attribute: TextAttribute,
+}
+
+impl TextBox {
+    pub fn new(rect: Rectangle, attribute: TextAttribute) -> Self {
+        Self {
+            rect,
+            attribute,
+            ..Default::default()
+        }
+    }
+
+    /// The wrapped lines that fit into the box
+    pub fn lines(&self) -> Vec<(usize, String)> {
+        let mut lines = layout_text(&self.text, self.rect.width().max(0) as usize, self.alignment);
+        lines.truncate(self.rect.height().max(0) as usize);
+        lines
+    }
+
+    /// Number of rows the text needs at the current width
+    pub fn needed_height(&self) -> usize {
+        layout_text(&self.text, self.rect.width().max(0) as usize, self.alignment).len()
+    }
+
+    /// `original` with the laid out text written over it, `area` is the part of the box inside the layer
+    fn render(&self, area: Rectangle, original: &CharGrid, convert: impl Fn(char) -> char) -> CharGrid {
+        let mut chars = original.clone();
+        for (y, (offset, line)) in self.lines().into_iter().enumerate() {
+            for (x, ch) in line.chars().enumerate() {
+                let pos = self.rect.start + Position::new((offset + x) as i32, y as i32) - area.start;
+                if pos.x < 0 || pos.y < 0 || pos.x >= area.width() || pos.y >= area.height() {
+                    continue;
+                }
+                chars[pos.y as usize][pos.x as usize] = AttributedChar::new(convert(ch), self.attribute);
+            }
+        }
+        chars
+    }
+}
+
+impl EditState {
+    /// Part of the text box inside the current layer, in layer coordinates
+    fn text_box_area(&self, text_box: &TextBox) -> Result<(usize, Rectangle)> {
+        let layer_idx = self.get_current_layer()?;
+        let layer = &self.screen.buffer.layers[layer_idx];
+        Ok((layer_idx, text_box.rect.intersect(&Rectangle::from_min_size((0, 0), layer.size()))))
+    }
+
+    /// Shows the text box above the current layer without changing the document
+    ///
+    /// Call again after changing text, size or alignment to reflow it.
+    pub fn preview_text_box(&mut self, text_box: &TextBox) -> Result<()> {
+        let (layer_idx, area) = self.text_box_area(text_box)?;
+        if area.is_empty() {
+            self.end_text_box_preview();
+            return Ok(());
+        }
+        let buffer_type = self.screen.buffer.buffer_type;
+        let empty = vec![vec![AttributedChar::invisible(); area.width() as usize]; area.height() as usize];
+        let chars = text_box.render(area, &empty, |ch| buffer_type.convert_from_unicode(ch));
+
+        let mut preview = Layer::new("Text box", area.size());
+        preview.properties.has_alpha_channel = true;
+        preview.set_offset(self.screen.buffer.layers[layer_idx].offset() + area.start);
+        crate::stamp_char_grid(&mut preview, Position::default(), &chars);
+        self.screen.buffer.preview_layer = Some(preview);
+        self.mark_buffer_dirty();
+        Ok(())
+    }
+
+    /// Removes the text box preview
+    pub fn end_text_box_preview(&mut self) {
+        if self.screen.buffer.preview_layer.take().is_some() {
+            self.mark_buffer_dirty();
+        }
+    }
+
+    /// Whether a text box preview is shown
+    pub fn is_text_box_previewed(&self) -> bool {
+        self.screen.buffer.preview_layer.is_some()
+    }
+
+    /// Writes the text box to the current layer as one undo step
+    ///
+    /// Returns whether the layer changed.
+    pub fn commit_text_box(&mut self, text_box: &TextBox) -> Result<bool> {
+        self.end_text_box_preview();
+        let (layer_idx, area) = self.text_box_area(text_box)?;
+        if area.is_empty() || text_box.text.is_empty() {
+            return Ok(false);
+        }
+        let buffer_type = self.screen.buffer.buffer_type;
+        let old_chars = crate::chars_from_area(&self.screen.buffer.layers[layer_idx], area);
+        let new_chars = text_box.render(area, &old_chars, |ch| buffer_type.convert_from_unicode(ch));
+        if new_chars == old_chars {
+            return Ok(false);
+        }
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-text_box"));
+        self.push_undo_action(EditorUndoOp::LayerChange {
+            layer: layer_idx,
+            pos: area.start,
+            old_chars,
+            new_chars,
+        })?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,238 @@
+//! Scaling and rotation of the selection or the whole current layer
+//!
+//! Without a selection the current layer is transformed and resized to the new content size.
+//! With a selection the content is transformed in place, anchored at the selection's top left
+//! corner and clipped to the layer. Either way it's one undo step, the content change is a
+//! `LayerChange` so collaboration peers receive it as cell draws.
+
+use i18n_embed_fl::fl;
+
+use icy_engine::{paint::HalfBlock, AttributeColor};
+
+use crate::{AttributedChar, CharGrid, Position, Rectangle, Result, Size, TextPane};
+
+use super::{area_operations::get_area, layer_operations::ROTATE_TABLE, map_char_u8, undo_operation::EditorUndoOp, EditState};
+
+const FULL_BLOCK: char = 219 as char;
+const HALF_BLOCK_TOP: char = 223 as char;
+
+/// How cells are resampled when scaling
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode {
+    /// Every target cell copies the nearest source cell
+    #[default]
+    Nearest,
+    /// Half block art (▀▄█ and space) is resampled per half cell so outlines stay intact,
+    /// other characters fall back to nearest neighbour
+    HalfBlock,
+}
+
+impl EditState {
+    /// Size of the area `scale` and the rotations work on: the selection clipped to the current layer,
+    /// without a selection the whole layer
+    pub fn transform_size(&self) -> Size {
+        self.get_cur_layer()
+            .map_or(Size::new(0, 0), |layer| get_area(self.selection(), layer.rectangle()).size())
+    }
+
+    /// Scales the selection or current layer by the given factors
+    pub fn scale(&mut self, factor_x: f32, factor_y: f32, mode: ScaleMode) -> Result<()> {
+        let Some(layer) = self.get_cur_layer() else {
+            return Err(crate::EngineError::Generic("Current layer is invalid".to_string()));
+        };
+        let area = get_area(self.selection(), layer.rectangle());
+        let size = Size::new(
+            ((area.width() as f32 * factor_x).round() as i32).max(1),
+            ((area.height() as f32 * factor_y).round() as i32).max(1),
+        );
+        self.scale_to_size(size, mode)
+    }
+
+    /// Scales the selection or current layer to `size` cells
+    pub fn scale_to_size(&mut self, size: impl Into<Size>, mode: ScaleMode) -> Result<()> {
+        let size = size.into();
+        if size.width <= 0 || size.height <= 0 {
+            return Err(crate::EngineError::Generic(format!("Invalid scale size: {}x{}", size.width, size.height)));
+        }
+        self.transform_area(fl!(crate::LANGUAGE_LOADER, "undo-scale"), |chars| match mode {
+            ScaleMode::Nearest => scale_nearest(chars, size),
+            ScaleMode::HalfBlock => scale_half_block(chars, size),
+        })
+    }
+
+    /// Rotates the selection or current layer 90° clockwise
+    pub fn rotate_cw(&mut self) -> Result<()> {
+        self.transform_area(fl!(crate::LANGUAGE_LOADER, "undo-rotate_cw"), |chars| rotate_grid(chars, 1))
+    }
+
+    /// Rotates the selection or current layer 90° counter clockwise
+    pub fn rotate_ccw(&mut self) -> Result<()> {
+        self.transform_area(fl!(crate::LANGUAGE_LOADER, "undo-rotate_ccw"), |chars| rotate_grid(chars, 3))
+    }
+
+    /// Rotates the selection or current layer by 180°
+    pub fn rotate_180(&mut self) -> Result<()> {
+        self.transform_area(fl!(crate::LANGUAGE_LOADER, "undo-rotate_180"), |chars| rotate_grid(chars, 2))
+    }
+
+    /// Rotate the floating paste layer 90° counter clockwise.
+    /// Moebius only knows clockwise rotation, so this sends three collaboration ROTATE commands.
+    pub fn paste_rotate_ccw(&mut self) -> Result<()> {
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-rotate_ccw"));
+        for _ in 0..3 {
+            self.paste_rotate()?;
+        }
+        Ok(())
+    }
+
+    /// Rotate the floating paste layer by 180°, sent as two collaboration ROTATE commands.
+    pub fn paste_rotate_180(&mut self) -> Result<()> {
+        let _undo = self.begin_atomic_undo(fl!(crate::LANGUAGE_LOADER, "undo-rotate_180"));
+        for _ in 0..2 {
+            self.paste_rotate()?;
+        }
+        Ok(())
+    }
+
+    fn transform_area(&mut self, description: String, transform: impl FnOnce(&CharGrid) -> CharGrid) -> Result<()> {
+        let _undo = self.begin_atomic_undo(description);
+        let sel = self.selection();
+        let layer_idx = self.get_current_layer()?;
+        let Some(layer) = self.get_cur_layer() else {
+            return Err(crate::EngineError::Generic("Current layer is invalid".to_string()));
+        };
+        let area = get_area(sel, layer.rectangle());
+        if area.is_empty() {
+            return Ok(());
+        }
+        let layer_size = layer.size();
+        let layer_offset = layer.offset();
+        let new_chars = transform(&crate::chars_from_area(layer, area));
+        let new_size = Size::new(new_chars.first().map_or(0, Vec::len) as i32, new_chars.len() as i32);
+
+        if sel.is_none() {
+            // Grow the layer so the change covers old and new content, then shrink to the result
+            let union = Size::new(layer_size.width.max(new_size.width), layer_size.height.max(new_size.height));
+            if union != layer_size {
+                self.push_undo_action(EditorUndoOp::SetLayerSize {
+                    index: layer_idx,
+                    from: layer_size,
+                    to: union,
+                })?;
+            }
+            let union_rect = Rectangle::from_min_size((0, 0), union);
+            self.push_area_change(layer_idx, union_rect, Rectangle::from_min_size((0, 0), layer_size), &new_chars)?;
+            if new_size != union {
+                self.push_undo_action(EditorUndoOp::SetLayerSize {
+                    index: layer_idx,
+                    from: union,
+                    to: new_size,
+                })?;
+            }
+            Ok(())
+        } else {
+            let target = Rectangle::from_min_size(area.start, new_size).intersect(&Rectangle::from_min_size((0, 0), layer_size));
+            self.push_area_change(layer_idx, area.union(&target), area, &new_chars)?;
+            self.set_selection(target + layer_offset)
+        }
+    }
+
+    /// Clears `cleared` and writes `chars` at its top left corner, recorded as one change of `region`
+    fn push_area_change(&mut self, layer_idx: usize, region: Rectangle, cleared: Rectangle, chars: &CharGrid) -> Result<()> {
+        let Some(layer) = self.get_buffer().layers.get(layer_idx) else {
+            return Err(crate::EngineError::Generic(format!("Invalid layer: {layer_idx}")));
+        };
+        let old_chars = crate::chars_from_area(layer, region);
+        let mut new_chars = old_chars.clone();
+        for y in cleared.y_range() {
+            for x in cleared.x_range() {
+                new_chars[(y - region.top()) as usize][(x - region.left()) as usize] = AttributedChar::invisible();
+            }
+        }
+        for (y, row) in chars.iter().enumerate() {
+            for (x, ch) in row.iter().enumerate() {
+                let pos = cleared.start + Position::new(x as i32, y as i32) - region.start;
+                if pos.y < region.height() && pos.x < region.width() {
+                    new_chars[pos.y as usize][pos.x as usize] = *ch;
+                }
+            }
+        }
+        self.push_undo_action(EditorUndoOp::LayerChange {
+            layer: layer_idx,
+            pos: region.start,
+            old_chars,
+            new_chars,
+        })
+    }
+}
+
+/// Source index for target index `i` when mapping `src_len` cells onto `dst_len`, sampling at cell centers
+fn sample(i: i32, src_len: i32, dst_len: i32) -> usize {
+    (((2 * i + 1) * src_len) / (2 * dst_len)).clamp(0, src_len - 1) as usize
+}
+
+fn scale_nearest(chars: &CharGrid, size: Size) -> CharGrid {
+    let (src_w, src_h) = (chars[0].len() as i32, chars.len() as i32);
+    (0..size.height)
+        .map(|y| {
+            let row = &chars[sample(y, src_h, size.height)];
+            (0..size.width).map(|x| row[sample(x, src_w, size.width)]).collect()
+        })
+        .collect()
+}
+
+/// Color of the half cell pixel `(x, py)` with two pixels per cell row
+fn half_block_pixel(chars: &CharGrid, x: usize, py: usize) -> AttributeColor {
+    let ch = chars[py / 2][x];
+    let block = HalfBlock::from_char(ch, Position::new(x as i32, py as i32));
+    if !block.is_blocky() {
+        ch.attribute.background_color()
+    } else if py % 2 == 0 {
+        block.upper_block_color
+    } else {
+        block.lower_block_color
+    }
+}
+
+fn scale_half_block(chars: &CharGrid, size: Size) -> CharGrid {
+    let (src_w, src_h) = (chars[0].len() as i32, chars.len() as i32);
+    (0..size.height)
+        .map(|y| {
+            (0..size.width)
+                .map(|x| {
+                    let sx = sample(x, src_w, size.width);
+                    let ch = chars[sample(y, src_h, size.height)][sx];
+                    if !ch.is_visible() || !HalfBlock::from_char(ch, Position::new(x, 0)).is_blocky() {
+                        return ch;
+                    }
+                    let upper = half_block_pixel(chars, sx, sample(2 * y, 2 * src_h, 2 * size.height));
+                    let lower = half_block_pixel(chars, sx, sample(2 * y + 1, 2 * src_h, 2 * size.height));
+                    let mut attribute = ch.attribute;
+                    if upper == lower {
+                        if upper == attribute.background_color() {
+                            return AttributedChar::new(' ', attribute);
+                        }
+                        attribute.set_foreground_color(upper);
+                        return AttributedChar::new(FULL_BLOCK, attribute);
+                    }
+                    attribute.set_foreground_color(upper);
+                    attribute.set_background_color(lower);
+                    AttributedChar::new(HALF_BLOCK_TOP, attribute)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Rotates by `quarter_turns` × 90° clockwise, box drawing and block glyphs are rotated as well
+fn rotate_grid(chars: &CharGrid, quarter_turns: usize) -> CharGrid {
+    let mut result = chars.clone();
+    for _ in 0..quarter_turns % 4 {
+        let height = result.len();
+        let width = result.first().map_or(0, Vec::len);
+        result = (0..width)
+            .map(|x| (0..height).rev().map(|y| map_char_u8(result[y][x], &ROTATE_TABLE)).collect())
+            .collect();
+    }
+    result
+}
@@ -375,6 +375,28 @@ impl EditorUndoOp {
         Some(self.clone())
     }
 
+    /// The layer whose content or placement this operation changes, refused while the layer is locked
+    pub fn edited_layer(&self) -> Option<usize> {
+        match self {
+            EditorUndoOp::SetChar { layer, .. }
+            | EditorUndoOp::SwapChar { layer, .. }
+            | EditorUndoOp::LayerChange { layer, .. }
+            | EditorUndoOp::DeleteRow { layer, .. }
+            | EditorUndoOp::InsertRow { layer, .. }
+            | EditorUndoOp::DeleteColumn { layer, .. }
+            | EditorUndoOp::InsertColumn { layer, .. }
+            | EditorUndoOp::ScrollWholeLayerUp { layer }
+            | EditorUndoOp::ScrollWholeLayerDown { layer }
+            | EditorUndoOp::PasteRotate { layer, .. }
+            | EditorUndoOp::PasteRotateImage { layer, .. }
+            | EditorUndoOp::PasteFlipX { layer, .. }
+            | EditorUndoOp::PasteFlipY { layer, .. } => Some(*layer),
+            EditorUndoOp::RemoveLayer { layer_index, .. } | EditorUndoOp::ClearLayer { layer_index, .. } => Some(*layer_index),
+            EditorUndoOp::MoveLayer { index, .. } | EditorUndoOp::SetLayerSize { index, .. } => Some(*index),
+            _ => None,
+        }
+    }
+
     /// Whether this operation changes data (affects dirty flag)
     pub fn changes_data(&self) -> bool {
         match self {
@@ -784,10 +806,11 @@ impl EditorUndoOp {
                 old_properties,
                 new_properties,
             } => {
-                std::mem::swap(old_properties, new_properties);
+                // Redo left the previous properties in `new_properties`, restore them and swap back
                 if let Some(l) = edit_state.get_buffer_mut().layers.get_mut(*index) {
                     l.properties = new_properties.clone();
                 }
+                std::mem::swap(old_properties, new_properties);
                 edit_state.get_buffer_mut().mark_dirty();
                 Ok(())
             }
@@ -949,7 +972,10 @@ impl EditorUndoOp {
             }
             EditorUndoOp::Paste { layer, current_layer } => {
                 // Insert a clone of the stored layer (dereference the Box)
-                edit_state.get_buffer_mut().layers.insert(*current_layer + 1, (*layer).as_ref().clone());
+                let mut layer = (*layer).as_ref().clone();
+                // It joins the group of the layer it gets anchored to
+                layer.properties.depth = edit_state.get_buffer().layers.get(*current_layer).map_or(0, |l| l.properties.depth);
+                edit_state.get_buffer_mut().layers.insert(*current_layer + 1, layer);
                 edit_state.set_current_layer(*current_layer + 1);
                 edit_state.get_buffer_mut().mark_dirty();
                 Ok(())
@@ -1335,18 +1361,40 @@ mod collab_mapping {
     use super::EditorUndoOp;
     use crate::collaboration::{Block, ClientCommand};
 
+    /// Anchoring merges the floating layer, which only exists locally, peers get the following `PasteAnchor` draws.
+    fn is_anchor_merge(ops: &[&EditorUndoOp], i: usize) -> bool {
+        matches!(ops[i], EditorUndoOp::MergeLayerDown { .. }) && matches!(ops.get(i + 1), Some(EditorUndoOp::PasteAnchor { .. }))
+    }
+
     impl EditorUndoOp {
+        /// ClientCommands to send when `ops` are redone, in the order they were done.
+        pub fn redo_client_commands_of(ops: &[&EditorUndoOp]) -> Vec<ClientCommand> {
+            let mut cmds = Vec::new();
+            for (i, op) in ops.iter().enumerate() {
+                if !is_anchor_merge(ops, i) {
+                    cmds.extend(op.redo_client_commands().unwrap_or_default());
+                }
+            }
+            cmds
+        }
+
+        /// ClientCommands to send when `ops` are undone, `ops` in the order they were done.
+        pub fn undo_client_commands_of(ops: &[&EditorUndoOp]) -> Vec<ClientCommand> {
+            let mut cmds = Vec::new();
+            for (i, op) in ops.iter().enumerate().rev() {
+                if !is_anchor_merge(ops, i) {
+                    cmds.extend(op.undo_client_commands().unwrap_or_default());
+                }
+            }
+            cmds
+        }
+
         /// Get ClientCommands to send when this operation is redone (forward direction).
         /// Returns None for operations that don't need network sync.
         pub fn redo_client_commands(&self) -> Option<Vec<ClientCommand>> {
             match self {
                 EditorUndoOp::Atomic { operations, .. } => {
-                    let mut cmds = Vec::new();
-                    for op in operations {
-                        if let Some(sub_cmds) = op.redo_client_commands() {
-                            cmds.extend(sub_cmds);
-                        }
-                    }
+                    let cmds = Self::redo_client_commands_of(&operations.iter().collect::<Vec<_>>());
                     if cmds.is_empty() {
                         None
                     } else {
@@ -1354,7 +1402,8 @@ mod collab_mapping {
                     }
                 }
 
-                EditorUndoOp::SetChar { pos, layer: _, new, .. } => Some(vec![ClientCommand::Draw {
+                EditorUndoOp::SetChar { pos, layer, new, .. } => Some(vec![ClientCommand::Draw {
+                    layer: *layer,
                     col: pos.x,
                     row: pos.y,
                     block: Block {
@@ -1366,11 +1415,12 @@ mod collab_mapping {
 
                 EditorUndoOp::SwapChar { .. } => None, // Complex, skip for now
 
-                EditorUndoOp::LayerChange { pos, new_chars, .. } => {
+                EditorUndoOp::LayerChange { layer, pos, new_chars, .. } => {
                     let mut cmds = Vec::new();
                     for (y, row) in new_chars.iter().enumerate() {
                         for (x, ch) in row.iter().enumerate() {
                             cmds.push(ClientCommand::Draw {
+                                layer: *layer,
                                 col: pos.x + x as i32,
                                 row: pos.y + y as i32,
                                 block: Block {
@@ -1423,6 +1473,7 @@ mod collab_mapping {
                         let col = *x + (idx as i32 % blocks.columns as i32);
                         let row = *y + (idx as i32 / blocks.columns as i32);
                         cmds.push(ClientCommand::Draw {
+                            layer: 0,
                             col,
                             row,
                             block: block.clone(),
@@ -1433,12 +1484,36 @@ mod collab_mapping {
 
                 EditorUndoOp::SetBackground { new_value, .. } => Some(vec![ClientCommand::SetBackground { value: *new_value }]),
 
-                // Operations that don't map to collaboration commands
-                EditorUndoOp::AddLayer { .. }
-                | EditorUndoOp::RemoveLayer { .. }
-                | EditorUndoOp::RaiseLayer { .. }
-                | EditorUndoOp::LowerLayer { .. }
-                | EditorUndoOp::MergeLayerDown { .. }
+                // Layer stack changes (V3); properties, sizes, palette, fonts and tags are synced from the document state
+                EditorUndoOp::AddLayer { index, layer } => Some(vec![ClientCommand::AddLayer {
+                    index: *index,
+                    layer: layer.clone(),
+                }]),
+                EditorUndoOp::RemoveLayer { layer_index, .. } => Some(vec![ClientCommand::RemoveLayer { index: *layer_index }]),
+                EditorUndoOp::RaiseLayer { layer_index } => Some(vec![ClientCommand::ReorderLayer {
+                    from: *layer_index,
+                    to: *layer_index + 1,
+                }]),
+                EditorUndoOp::LowerLayer { layer_index } => Some(vec![ClientCommand::ReorderLayer {
+                    from: *layer_index,
+                    to: *layer_index - 1,
+                }]),
+                EditorUndoOp::MergeLayerDown {
+                    index,
+                    merged_layer: Some(merged),
+                    ..
+                } => Some(vec![
+                    ClientCommand::RemoveLayer { index: *index },
+                    ClientCommand::RemoveLayer { index: *index - 1 },
+                    ClientCommand::AddLayer {
+                        index: *index - 1,
+                        layer: Box::new(merged.clone()),
+                    },
+                ]),
+
+                // Operations that don't map to collaboration commands, layer properties,
+                // offsets and sizes are synced from the document state
+                EditorUndoOp::MergeLayerDown { .. }
                 | EditorUndoOp::ToggleLayerVisibility { .. }
                 | EditorUndoOp::MoveLayer { .. }
                 | EditorUndoOp::SetLayerSize { .. }
@@ -1484,13 +1559,7 @@ mod collab_mapping {
         pub fn undo_client_commands(&self) -> Option<Vec<ClientCommand>> {
             match self {
                 EditorUndoOp::Atomic { operations, .. } => {
-                    let mut cmds = Vec::new();
-                    // Reverse order for undo
-                    for op in operations.iter().rev() {
-                        if let Some(sub_cmds) = op.undo_client_commands() {
-                            cmds.extend(sub_cmds);
-                        }
-                    }
+                    let cmds = Self::undo_client_commands_of(&operations.iter().collect::<Vec<_>>());
                     if cmds.is_empty() {
                         None
                     } else {
@@ -1498,7 +1567,8 @@ mod collab_mapping {
                     }
                 }
 
-                EditorUndoOp::SetChar { pos, layer: _, old, .. } => Some(vec![ClientCommand::Draw {
+                EditorUndoOp::SetChar { pos, layer, old, .. } => Some(vec![ClientCommand::Draw {
+                    layer: *layer,
                     col: pos.x,
                     row: pos.y,
                     block: Block {
@@ -1510,11 +1580,12 @@ mod collab_mapping {
 
                 EditorUndoOp::SwapChar { .. } => None,
 
-                EditorUndoOp::LayerChange { pos, old_chars, .. } => {
+                EditorUndoOp::LayerChange { layer, pos, old_chars, .. } => {
                     let mut cmds = Vec::new();
                     for (y, row) in old_chars.iter().enumerate() {
                         for (x, ch) in row.iter().enumerate() {
                             cmds.push(ClientCommand::Draw {
+                                layer: *layer,
                                 col: pos.x + x as i32,
                                 row: pos.y + y as i32,
                                 block: Block {
@@ -1566,6 +1637,34 @@ mod collab_mapping {
 
                 EditorUndoOp::SetBackground { old_value, .. } => Some(vec![ClientCommand::SetBackground { value: *old_value }]),
 
+                EditorUndoOp::AddLayer { index, .. } => Some(vec![ClientCommand::RemoveLayer { index: *index }]),
+                EditorUndoOp::RemoveLayer { layer_index, layer } => Some(vec![ClientCommand::AddLayer {
+                    index: *layer_index,
+                    layer: layer.clone(),
+                }]),
+                EditorUndoOp::RaiseLayer { layer_index } => Some(vec![ClientCommand::ReorderLayer {
+                    from: *layer_index + 1,
+                    to: *layer_index,
+                }]),
+                EditorUndoOp::LowerLayer { layer_index } => Some(vec![ClientCommand::ReorderLayer {
+                    from: *layer_index - 1,
+                    to: *layer_index,
+                }]),
+                EditorUndoOp::MergeLayerDown {
+                    index,
+                    orig_layers: Some(orig_layers),
+                    ..
+                } => {
+                    let mut cmds = vec![ClientCommand::RemoveLayer { index: *index - 1 }];
+                    for (i, layer) in orig_layers.iter().enumerate() {
+                        cmds.push(ClientCommand::AddLayer {
+                            index: *index - 1 + i,
+                            layer: Box::new(layer.clone()),
+                        });
+                    }
+                    Some(cmds)
+                }
+
                 // Operations that don't map to collaboration commands
                 _ => None,
             }
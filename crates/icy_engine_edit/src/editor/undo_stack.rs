@@ -64,16 +64,48 @@ pub trait UndoOperation: Send + Sync {
 
 /// Serializable undo stack for editor operations
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(from = "EditorUndoStackData")]
 pub struct EditorUndoStack {
     /// Undo operations
     undo_stack: Vec<EditorUndoOp>,
     /// Redo operations
     redo_stack: Vec<EditorUndoOp>,
     /// Index of last save (operations before this don't need to be serialized for session)
+    last_save_index: usize,
+    /// Number of operations taken from the redo stack so far, tells redos from new edits
+    #[serde(skip)]
+    redone_count: usize,
+    /// Sequence id of each undo operation, an operation that replaces another one gets a new id
+    #[serde(skip)]
+    undo_ids: Vec<u64>,
+    /// Sequence id of the next pushed operation
+    #[serde(skip)]
+    next_id: u64,
+}
+
+/// Serialized part of an [`EditorUndoStack`]
+#[derive(Deserialize)]
+struct EditorUndoStackData {
+    undo_stack: Vec<EditorUndoOp>,
+    redo_stack: Vec<EditorUndoOp>,
     #[serde(default)]
     last_save_index: usize,
 }
 
+impl From<EditorUndoStackData> for EditorUndoStack {
+    fn from(data: EditorUndoStackData) -> Self {
+        let len = data.undo_stack.len() as u64;
+        Self {
+            undo_stack: data.undo_stack,
+            redo_stack: data.redo_stack,
+            last_save_index: data.last_save_index,
+            redone_count: 0,
+            undo_ids: (0..len).collect(),
+            next_id: len,
+        }
+    }
+}
+
 impl EditorUndoStack {
     /// Create a new empty undo stack
     pub fn new() -> Self {
@@ -82,13 +114,15 @@ impl EditorUndoStack {
 
     /// Push an operation onto the undo stack (clears redo stack)
     pub fn push(&mut self, op: EditorUndoOp) {
-        self.undo_stack.push(op);
+        self.push_undo(op);
         self.redo_stack.clear();
     }
 
     /// Push an operation onto the undo stack without clearing redo
     pub fn push_undo(&mut self, op: EditorUndoOp) {
         self.undo_stack.push(op);
+        self.undo_ids.push(self.next_id);
+        self.next_id += 1;
     }
 
     /// Clear the redo stack
@@ -98,6 +132,7 @@ impl EditorUndoStack {
 
     /// Pop an operation from the undo stack
     pub fn pop_undo(&mut self) -> Option<EditorUndoOp> {
+        self.undo_ids.pop();
         self.undo_stack.pop()
     }
 
@@ -108,7 +143,16 @@ impl EditorUndoStack {
 
     /// Pop an operation from the redo stack
     pub fn pop_redo(&mut self) -> Option<EditorUndoOp> {
-        self.redo_stack.pop()
+        let op = self.redo_stack.pop();
+        if op.is_some() {
+            self.redone_count += 1;
+        }
+        op
+    }
+
+    /// Number of operations taken from the redo stack since the stack was created
+    pub fn redone_count(&self) -> usize {
+        self.redone_count
     }
 
     /// Get the number of undo operations
@@ -190,6 +234,11 @@ impl EditorUndoStack {
         &self.undo_stack
     }
 
+    /// Sequence ids of the undo stack operations, tells a replaced operation from the one before
+    pub fn undo_ids(&self) -> &[u64] {
+        &self.undo_ids
+    }
+
     /// Get direct read access to the redo stack for collaboration sync
     pub fn redo_stack(&self) -> &[EditorUndoOp] {
         &self.redo_stack
@@ -198,6 +247,7 @@ impl EditorUndoStack {
     /// Clear all operations
     pub fn clear(&mut self) {
         self.undo_stack.clear();
+        self.undo_ids.clear();
         self.redo_stack.clear();
         self.last_save_index = 0;
     }
@@ -38,8 +38,12 @@ pub enum Tool {
     Pipette,
     /// Flood fill area
     Fill,
+    /// Gradient or pattern fill of the selection or a flood region
+    Gradient,
     /// TDF/Figlet font rendering
     Font,
+    /// Rectangle of word wrapped text
+    TextBox,
     /// Tag tool for annotations
     Tag,
 }
@@ -91,9 +95,9 @@ pub const TOOL_SLOTS: [ToolPair; 10] = [
     ToolPair::single(Tool::Line),
     ToolPair::new(Tool::RectangleOutline, Tool::RectangleFilled),
     ToolPair::new(Tool::EllipseOutline, Tool::EllipseFilled),
-    ToolPair::single(Tool::Fill),
+    ToolPair::new(Tool::Fill, Tool::Gradient),
     ToolPair::single(Tool::Pipette),
-    ToolPair::single(Tool::Font),
+    ToolPair::new(Tool::Font, Tool::TextBox),
     ToolPair::single(Tool::Tag),
 ];
 
@@ -110,8 +114,10 @@ impl Tool {
             Tool::EllipseOutline => "ellipse_outline",
             Tool::EllipseFilled => "ellipse_filled",
             Tool::Fill => "fill",
+            Tool::Gradient => "gradient",
             Tool::Pipette => "dropper",
             Tool::Font => "font",
+            Tool::TextBox => "text_box",
             Tool::Tag => "tag",
         }
     }
@@ -128,8 +134,10 @@ impl Tool {
             Tool::EllipseOutline => "Ellipse",
             Tool::EllipseFilled => "Filled Ellipse",
             Tool::Fill => "Fill",
+            Tool::Gradient => "Gradient",
             Tool::Pipette => "Pipette",
             Tool::Font => "Font",
+            Tool::TextBox => "Text Box",
             Tool::Tag => "Tag",
         }
     }
@@ -145,9 +153,11 @@ impl Tool {
             Tool::RectangleFilled => "Draw filled rectangle (click again for Outline)",
             Tool::EllipseOutline => "Draw ellipse outline (click again for Filled)",
             Tool::EllipseFilled => "Draw filled ellipse (click again for Outline)",
-            Tool::Fill => "Flood fill area",
+            Tool::Fill => "Flood fill area (click again for Gradient)",
+            Tool::Gradient => "Gradient or pattern fill, drag from start to end (click again for Fill)",
             Tool::Pipette => "Pick color/character",
-            Tool::Font => "TDF/Figlet font rendering",
+            Tool::Font => "TDF/Figlet font rendering (click again for Text Box)",
+            Tool::TextBox => "Word wrapped text in a rectangle (click again for Font)",
             Tool::Tag => "Add annotation tags",
         }
     }
@@ -170,9 +180,9 @@ impl Tool {
             Tool::Line => Some('l'),
             Tool::RectangleOutline | Tool::RectangleFilled => Some('r'),
             Tool::EllipseOutline | Tool::EllipseFilled => Some('o'),
-            Tool::Fill => Some('f'),
+            Tool::Fill | Tool::Gradient => Some('f'),
             Tool::Pipette => Some('i'),
-            Tool::Font => Some('t'),
+            Tool::Font | Tool::TextBox => Some('t'),
             Tool::Tag => Some('g'),
         }
     }
@@ -209,7 +219,15 @@ impl Tool {
     pub fn needs_drag(&self) -> bool {
         matches!(
             self,
-            Tool::Select | Tool::Pencil | Tool::Line | Tool::RectangleOutline | Tool::RectangleFilled | Tool::EllipseOutline | Tool::EllipseFilled
+            Tool::Select
+                | Tool::Pencil
+                | Tool::Line
+                | Tool::RectangleOutline
+                | Tool::RectangleFilled
+                | Tool::EllipseOutline
+                | Tool::EllipseFilled
+                | Tool::Gradient
+                | Tool::TextBox
         )
     }
 }
@@ -266,8 +284,23 @@ mod tests {
 
     #[test]
     fn test_single_tool_no_toggle() {
-        // Fill is slot 6
-        assert_eq!(click_tool_slot(6, Tool::Fill), Tool::Fill);
+        // Pipette is slot 7
+        assert_eq!(click_tool_slot(7, Tool::Pipette), Tool::Pipette);
+    }
+
+    #[test]
+    fn test_toggle_fill_gradient() {
+        assert_eq!(click_tool_slot(6, Tool::Fill), Tool::Gradient);
+        assert_eq!(click_tool_slot(6, Tool::Gradient), Tool::Fill);
+        assert_eq!(Tool::Gradient.slot_index(), 6);
+    }
+
+    #[test]
+    fn test_toggle_font_text_box() {
+        let slot = Tool::Font.slot_index();
+        assert_eq!(click_tool_slot(slot, Tool::Font), Tool::TextBox);
+        assert_eq!(click_tool_slot(slot, Tool::TextBox), Tool::Font);
+        assert_eq!(Tool::TextBox.slot_index(), slot);
     }
 
     #[test]
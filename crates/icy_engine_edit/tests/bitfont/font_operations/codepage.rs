@@ -0,0 +1,69 @@
+//! Codepage tests
+//!
+//! Tests switching the codepage of fonts with Unicode glyphs.
+
+use std::collections::HashMap;
+
+use icy_engine::{BitFont, CompactGlyph, FontCodepage};
+use icy_engine_edit::bitfont::{BitFontEditState, BitFontUndoState};
+
+const ZHE: char = 'Ж';
+const CP866_ZHE: char = '\u{86}';
+
+fn unicode_font() -> BitFont {
+    let mut pixels = vec![vec![false; 8]; 16];
+    for row in pixels.iter_mut().skip(4).take(8) {
+        row[1] = true;
+        row[6] = true;
+    }
+    let mut font = BitFont::default();
+    font.set_unicode_glyphs(HashMap::from([(ZHE, CompactGlyph::from_bitmap_pixels(&pixels, 8, 16))]));
+    font
+}
+
+#[test]
+fn test_set_codepage_fills_slots_from_unicode_glyphs() {
+    let mut state = BitFontEditState::from_font(unicode_font());
+    assert!(state.has_unicode_glyphs());
+    assert_eq!(state.codepage(), FontCodepage::Cp437);
+    let a = state.get_glyph_pixels('A').clone();
+
+    state.set_codepage(FontCodepage::Cp866).unwrap();
+
+    assert_eq!(state.codepage(), FontCodepage::Cp866);
+    assert!(state.get_glyph_pixels(CP866_ZHE)[4][1]);
+    assert_eq!(state.get_glyph_pixels('A'), &a);
+
+    state.undo().unwrap();
+    assert_eq!(state.codepage(), FontCodepage::Cp437);
+    assert!(!state.get_glyph_pixels(CP866_ZHE)[4][1]);
+}
+
+#[test]
+fn test_set_codepage_keeps_edited_glyphs() {
+    let mut state = BitFontEditState::from_font(unicode_font());
+    state.set_pixel('A', 0, 0, true).unwrap();
+
+    state.set_codepage(FontCodepage::Cp866).unwrap();
+
+    assert!(state.get_glyph_pixels('A')[0][0]);
+}
+
+#[test]
+fn test_build_font_keeps_unicode_glyphs() {
+    let mut state = BitFontEditState::from_font(unicode_font());
+    state.set_pixel('A', 0, 0, true).unwrap();
+
+    let font = state.build_font();
+
+    assert!(font.unicode_glyphs.contains_key(&ZHE));
+    assert_eq!(font.unicode_glyphs[&'A'].data, font.glyph('A').data);
+    assert_eq!(font.unicode_glyphs[&'A'].data[0] & 0x80, 0x80);
+}
+
+#[test]
+fn test_build_font_without_unicode_glyphs() {
+    let state = BitFontEditState::new();
+    assert!(!state.has_unicode_glyphs());
+    assert!(state.build_font().unicode_glyphs.is_empty());
+}
@@ -1,5 +1,6 @@
 //! Font-wide operation tests for BitFont Editor
 
+mod codepage;
 mod insert_delete_column;
 mod insert_delete_line;
 mod resize;
@@ -0,0 +1,192 @@
+//! Tests for box drawing aware lines
+
+use icy_engine::{AttributedChar, Position, TextAttribute};
+use icy_engine_edit::brushes::{
+    draw_box_lines, erase_box_lines, get_box_line_points, get_box_rectangle_points, get_line_points, BoxArms, BoxCharset, BoxLineStyle, DrawTarget, LineWeight,
+};
+
+struct GridTarget {
+    width: i32,
+    height: i32,
+    chars: Vec<AttributedChar>,
+}
+
+impl GridTarget {
+    fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            chars: vec![AttributedChar::new(' ', TextAttribute::default()); (width * height) as usize],
+        }
+    }
+
+    fn ch(&self, x: i32, y: i32) -> char {
+        self.chars[(y * self.width + x) as usize].ch
+    }
+}
+
+impl DrawTarget for GridTarget {
+    fn width(&self) -> i32 {
+        self.width
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
+
+    fn char_at(&self, pos: Position) -> Option<AttributedChar> {
+        self.is_valid(pos).then(|| self.chars[(pos.y * self.width + pos.x) as usize])
+    }
+
+    fn set_char(&mut self, pos: Position, ch: AttributedChar) {
+        if self.is_valid(pos) {
+            self.chars[(pos.y * self.width + pos.x) as usize] = ch;
+        }
+    }
+}
+
+fn line(target: &mut GridTarget, charset: BoxCharset, style: BoxLineStyle, p0: (i32, i32), p1: (i32, i32)) {
+    let points = get_box_line_points(Position::new(p0.0, p0.1), Position::new(p1.0, p1.1));
+    draw_box_lines(target, charset, style, &points, TextAttribute::default());
+}
+
+#[test]
+fn test_glyph_normalizes_arms() {
+    let east = BoxArms {
+        east: LineWeight::Single,
+        ..Default::default()
+    };
+    assert_eq!(BoxCharset::Cp437.glyph(east), Some(0xC4 as char));
+    assert_eq!(BoxCharset::Cp437.glyph(BoxArms::default()), None);
+
+    let mixed = BoxArms::new(LineWeight::Single, LineWeight::Double, LineWeight::None, LineWeight::None);
+    assert_eq!(BoxCharset::Cp437.glyph(mixed), Some(0xD4 as char));
+    assert_eq!(BoxCharset::Unicode.glyph(mixed), Some('╘'));
+    assert_eq!(BoxCharset::Unicode.arms('╘'), Some(mixed));
+    assert_eq!(BoxCharset::Cp437.arms('A'), None);
+}
+
+#[test]
+fn test_crossing_lines_join() {
+    let mut target = GridTarget::new(5, 5);
+    line(&mut target, BoxCharset::Cp437, BoxLineStyle::SINGLE, (2, 0), (2, 4));
+    line(&mut target, BoxCharset::Cp437, BoxLineStyle::SINGLE, (0, 2), (4, 2));
+
+    assert_eq!(target.ch(2, 0), 0xB3 as char);
+    assert_eq!(target.ch(2, 2), 0xC5 as char);
+    assert_eq!(target.ch(0, 2), 0xC4 as char);
+}
+
+#[test]
+fn test_line_ending_next_to_line_makes_junction() {
+    let mut target = GridTarget::new(6, 5);
+    line(&mut target, BoxCharset::Cp437, BoxLineStyle::SINGLE, (2, 0), (2, 4));
+    line(&mut target, BoxCharset::Cp437, BoxLineStyle::SINGLE, (3, 2), (5, 2));
+
+    assert_eq!(target.ch(2, 2), 0xC3 as char);
+    assert_eq!(target.ch(3, 2), 0xC4 as char);
+}
+
+#[test]
+fn test_parallel_lines_stay_apart() {
+    let mut target = GridTarget::new(5, 2);
+    line(&mut target, BoxCharset::Cp437, BoxLineStyle::SINGLE, (0, 0), (4, 0));
+    line(&mut target, BoxCharset::Cp437, BoxLineStyle::SINGLE, (0, 1), (4, 1));
+
+    for x in 0..5 {
+        assert_eq!(target.ch(x, 0), 0xC4 as char);
+        assert_eq!(target.ch(x, 1), 0xC4 as char);
+    }
+}
+
+#[test]
+fn test_double_line_over_single_frame() {
+    let mut target = GridTarget::new(5, 5);
+    let frame = get_box_rectangle_points(Position::new(0, 0), Position::new(4, 4));
+    draw_box_lines(&mut target, BoxCharset::Cp437, BoxLineStyle::SINGLE, &frame, TextAttribute::default());
+    assert_eq!(target.ch(0, 0), 0xDA as char);
+    assert_eq!(target.ch(4, 0), 0xBF as char);
+    assert_eq!(target.ch(0, 4), 0xC0 as char);
+    assert_eq!(target.ch(4, 4), 0xD9 as char);
+
+    line(&mut target, BoxCharset::Cp437, BoxLineStyle::DOUBLE, (0, 2), (4, 2));
+    assert_eq!(target.ch(0, 2), 0xC6 as char);
+    assert_eq!(target.ch(2, 2), 0xCD as char);
+    assert_eq!(target.ch(4, 2), 0xB5 as char);
+}
+
+#[test]
+fn test_erase_repairs_junctions() {
+    let mut target = GridTarget::new(5, 5);
+    line(&mut target, BoxCharset::Cp437, BoxLineStyle::SINGLE, (2, 0), (2, 4));
+    line(&mut target, BoxCharset::Cp437, BoxLineStyle::SINGLE, (0, 2), (4, 2));
+
+    let erased = AttributedChar::new(' ', TextAttribute::default());
+    erase_box_lines(
+        &mut target,
+        BoxCharset::Cp437,
+        &get_line_points(Position::new(0, 2), Position::new(1, 2)),
+        erased,
+    );
+    assert_eq!(target.ch(1, 2), ' ');
+    assert_eq!(target.ch(2, 2), 0xC3 as char);
+
+    erase_box_lines(&mut target, BoxCharset::Cp437, &[Position::new(3, 2)], erased);
+    assert_eq!(target.ch(2, 2), 0xB3 as char);
+    assert_eq!(target.ch(4, 2), 0xC4 as char);
+}
+
+#[test]
+fn test_petscii_uses_single_lines() {
+    let mut target = GridTarget::new(5, 5);
+    line(&mut target, BoxCharset::Petscii, BoxLineStyle::DOUBLE, (2, 0), (2, 4));
+    line(&mut target, BoxCharset::Petscii, BoxLineStyle::DOUBLE, (0, 2), (4, 2));
+
+    assert_eq!(target.ch(2, 0), 0x5D as char);
+    assert_eq!(target.ch(0, 2), 0x40 as char);
+    assert_eq!(target.ch(2, 2), 0x5B as char);
+}
+
+#[test]
+fn test_box_line_points_are_orthogonally_connected() {
+    let points = get_box_line_points(Position::new(0, 0), Position::new(3, 2));
+    assert_eq!(points.first(), Some(&Position::new(0, 0)));
+    assert_eq!(points.last(), Some(&Position::new(3, 2)));
+    for pair in points.windows(2) {
+        assert_eq!((pair[1].x - pair[0].x).abs() + (pair[1].y - pair[0].y).abs(), 1);
+    }
+}
+
+#[test]
+fn test_narrow_rectangle_sides_stay_apart() {
+    let mut target = GridTarget::new(2, 4);
+    let frame = get_box_rectangle_points(Position::new(0, 0), Position::new(1, 3));
+    draw_box_lines(&mut target, BoxCharset::Cp437, BoxLineStyle::SINGLE, &frame, TextAttribute::default());
+
+    assert_eq!(target.ch(0, 0), 0xDA as char);
+    assert_eq!(target.ch(1, 0), 0xBF as char);
+    for y in 1..3 {
+        assert_eq!(target.ch(0, y), 0xB3 as char);
+        assert_eq!(target.ch(1, y), 0xB3 as char);
+    }
+    assert_eq!(target.ch(0, 3), 0xC0 as char);
+    assert_eq!(target.ch(1, 3), 0xD9 as char);
+}
+
+#[test]
+fn test_path_passing_next_to_itself_has_no_junction() {
+    // A U-turn: along the top row, down one and back
+    let mut target = GridTarget::new(4, 2);
+    let path: Vec<Position> = [(0, 0), (1, 0), (2, 0), (3, 0), (3, 1), (2, 1), (1, 1), (0, 1)]
+        .into_iter()
+        .map(|(x, y)| Position::new(x, y))
+        .collect();
+    draw_box_lines(&mut target, BoxCharset::Cp437, BoxLineStyle::SINGLE, &path, TextAttribute::default());
+
+    for x in 0..3 {
+        assert_eq!(target.ch(x, 0), 0xC4 as char);
+        assert_eq!(target.ch(x, 1), 0xC4 as char);
+    }
+    assert_eq!(target.ch(3, 0), 0xBF as char);
+    assert_eq!(target.ch(3, 1), 0xD9 as char);
+}
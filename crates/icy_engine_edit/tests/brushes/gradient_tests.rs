@@ -0,0 +1,84 @@
+//! Tests for gradient and pattern fills
+
+use icy_engine::{AttributedChar, Position, TextAttribute};
+use icy_engine_edit::brushes::{pattern_char_at, Gradient, GradientShape, SHADE_RAMP};
+
+fn gradient(shape: GradientShape) -> Gradient {
+    Gradient::from_ramp(shape, &[1, 2], &[], false)
+}
+
+#[test]
+fn test_linear_value() {
+    let g = gradient(GradientShape::Linear);
+    let (start, end) = (Position::new(0, 0), Position::new(10, 0));
+    assert_eq!(g.value_at(start, end, Position::new(5, 3)), 0.5);
+    assert_eq!(g.value_at(start, end, Position::new(-3, 0)), 0.0);
+    assert_eq!(g.value_at(start, end, Position::new(20, 0)), 1.0);
+}
+
+#[test]
+fn test_radial_value_uses_cell_aspect() {
+    let g = gradient(GradientShape::Radial);
+    let (start, end) = (Position::new(0, 0), Position::new(10, 0));
+    assert_eq!(g.value_at(start, end, Position::new(0, 5)), 1.0);
+    assert!((g.value_at(start, end, Position::new(0, 2)) - 0.4).abs() < 0.001);
+}
+
+#[test]
+fn test_angular_value() {
+    let g = gradient(GradientShape::Angular);
+    let (start, end) = (Position::new(0, 0), Position::new(10, 0));
+    assert_eq!(g.value_at(start, end, Position::new(10, 0)), 0.0);
+    assert!((g.value_at(start, end, Position::new(0, 10)) - 0.25).abs() < 0.001);
+    assert!((g.value_at(start, end, Position::new(-10, 0)) - 0.5).abs() < 0.001);
+}
+
+#[test]
+fn test_color_and_shade_ramp() {
+    let g = Gradient::from_ramp(GradientShape::Linear, &[0, 4, 12], &SHADE_RAMP, false);
+    // Five shades for the first pair, the second pair skips its first (blank) shade
+    assert_eq!(g.steps.len(), 9);
+    assert_eq!(g.steps[0].ch, Some(' '));
+    assert_eq!(g.steps[0].background, Some(0));
+    assert_eq!(g.steps[4].ch, Some(219 as char));
+    assert_eq!(g.steps[4].foreground, Some(4));
+    assert_eq!(g.steps[5].ch, Some(SHADE_RAMP[1]));
+    assert_eq!(g.steps[5].foreground, Some(12));
+    assert_eq!(g.steps[5].background, Some(4));
+}
+
+#[test]
+fn test_apply_keeps_unset_values() {
+    let g = Gradient::from_ramp(GradientShape::Linear, &[], &SHADE_RAMP, false);
+    let ch = AttributedChar::new('A', TextAttribute::new(3, 5));
+    let filled = g.apply(Position::new(0, 0), Position::new(4, 0), Position::new(4, 0), ch);
+    assert_eq!(filled.ch, 219 as char);
+    assert_eq!(filled.attribute.foreground(), 3);
+    assert_eq!(filled.attribute.background(), 5);
+}
+
+#[test]
+fn test_dither_mixes_neighbouring_steps() {
+    let plain = gradient(GradientShape::Linear);
+    let dithered = Gradient::from_ramp(GradientShape::Linear, &[1, 2], &[], true);
+    let (start, end) = (Position::new(0, 0), Position::new(10, 0));
+
+    // A horizontal gradient has the same value (0.4) along the whole column
+    let plain_colors: Vec<_> = (0..4).map(|y| plain.step_at(start, end, Position::new(4, y)).unwrap().foreground).collect();
+    let dithered_colors: Vec<_> = (0..4).map(|y| dithered.step_at(start, end, Position::new(4, y)).unwrap().foreground).collect();
+    assert!(plain_colors.iter().all(|c| *c == Some(1)));
+    assert!(dithered_colors.contains(&Some(1)));
+    assert!(dithered_colors.contains(&Some(2)));
+}
+
+#[test]
+fn test_pattern_wraps_around() {
+    let a = AttributedChar::new('A', TextAttribute::default());
+    let b = AttributedChar::new('B', TextAttribute::default());
+    let tile = vec![vec![a, b]];
+    let origin = Position::new(1, 0);
+    assert_eq!(pattern_char_at(&tile, origin, Position::new(1, 0)).unwrap().ch, 'A');
+    assert_eq!(pattern_char_at(&tile, origin, Position::new(2, 5)).unwrap().ch, 'B');
+    assert_eq!(pattern_char_at(&tile, origin, Position::new(0, 0)).unwrap().ch, 'B');
+    assert!(pattern_char_at(&[], origin, origin).is_none());
+}
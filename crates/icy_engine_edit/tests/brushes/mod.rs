@@ -1,9 +1,11 @@
 //! Tests for the brushes module
 
+mod box_drawing_tests;
 mod color_mode_tests;
 mod draw_context_tests;
 mod ellipse_tests;
 mod expand_points_tests;
+mod gradient_tests;
 mod line_tests;
 mod point_role_tests;
 mod rectangle_tests;
@@ -0,0 +1,183 @@
+//! FIGlet font tests
+//!
+//! Tests reading and writing `.flf` files and the conversion from and to TDF fonts.
+
+use icy_engine_edit::charset::{load_tdf_fonts, CharSetEditState, FigletLayout, FlfFont, TdfFont, TdfFontType, HARD_BLANK};
+use retrofont::{Glyph, GlyphPart};
+
+/// Two line font with 'A', 'B' and a code tagged '€', missing required chars are empty
+fn small_flf() -> String {
+    let mut flf = String::from("flf2a$ 2 2 6 15 1 0 143 1\nSmall Font\n");
+    for ch in (' '..='~').chain(['Ä', 'Ö', 'Ü', 'ä', 'ö', 'ü', 'ß']) {
+        match ch {
+            ' ' => flf.push_str("$$@\n$$@@\n"),
+            'A' => flf.push_str("/\\@\n/\\@@\n"),
+            'B' => flf.push_str("|)#\n|)##\n"),
+            _ => flf.push_str("@\n@@\n"),
+        }
+    }
+    flf.push_str("8364  EURO SIGN\nC=@\nC=@@\n");
+    flf
+}
+
+#[test]
+fn test_parse_flf() {
+    let font = FlfFont::from_bytes(small_flf().as_bytes()).unwrap();
+    assert_eq!(font.name, "Small Font");
+    assert_eq!((font.height, font.baseline), (2, 2));
+    assert_eq!(font.layout, FigletLayout::Smushing(15));
+    assert_eq!(font.glyphs[&'A'], vec!["/\\", "/\\"]);
+    // Every line may use its own end mark
+    assert_eq!(font.glyphs[&'B'], vec!["|)", "|)"]);
+    assert_eq!(font.glyphs[&' '], vec![HARD_BLANK.to_string().repeat(2); 2]);
+    assert_eq!(font.glyphs[&'€'], vec!["C=", "C="]);
+    assert_eq!(font.glyphs[&'ß'], vec![""; 2]);
+}
+
+#[test]
+fn test_flf_roundtrip() {
+    let font = FlfFont::from_bytes(small_flf().as_bytes()).unwrap();
+    let written = font.to_flf_string();
+    assert!(written.starts_with("flf2a$ 2 2 4 15 1 0 143 1\n"));
+    assert!(written.contains("\n8364  U+20AC\nC=@\nC=@@\n"));
+    assert_eq!(FlfFont::from_bytes(written.as_bytes()).unwrap(), font);
+}
+
+#[test]
+fn test_flf_avoids_colliding_marks() {
+    let mut font = FlfFont::from_bytes(small_flf().as_bytes()).unwrap();
+    font.glyphs.insert('$', vec!["$@".to_string(), "@$".to_string()]);
+    let written = font.to_flf_string();
+    assert!(written.starts_with("flf2a# "));
+    assert_eq!(FlfFont::from_bytes(written.as_bytes()).unwrap().glyphs[&'$'], vec!["$@", "@$"]);
+}
+
+fn color_tdf() -> TdfFont {
+    let mut font = TdfFont::new("Logo", TdfFontType::Color, 1);
+    let part = |ch| GlyphPart::AnsiChar {
+        ch,
+        fg: 14,
+        bg: 1,
+        blink: false,
+    };
+    font.add_glyph(
+        'A',
+        Glyph {
+            width: 3,
+            height: 2,
+            parts: vec![part('▄'), part('█'), part('▄'), GlyphPart::NewLine, part('█'), GlyphPart::HardBlank, part('█')],
+        },
+    );
+    font.add_glyph(
+        'i',
+        Glyph {
+            width: 1,
+            height: 1,
+            parts: vec![part('█')],
+        },
+    );
+    font
+}
+
+#[test]
+fn test_tdf_to_figlet() {
+    let font = FlfFont::from_tdf(&color_tdf(), FigletLayout::FullWidth).unwrap();
+    assert_eq!(font.name, "Logo");
+    assert_eq!(font.height, 2);
+    assert_eq!(font.layout, FigletLayout::FullWidth);
+    // Colors are dropped, the letter spacing is added to every glyph
+    assert_eq!(font.glyphs[&'A'], vec!["▄█▄ ".to_string(), format!("█{HARD_BLANK}█ ")]);
+    assert_eq!(font.glyphs[&'i'], vec!["█ ", "  "]);
+    assert_eq!(font.glyphs[&' '], vec![" "; 2]);
+
+    let written = font.to_flf_string();
+    assert!(written.starts_with("flf2a$ 2 2 6 -1 1 0 0 0\nLogo\n"));
+    assert!(written.contains("\n▄█▄ @\n█$█ @@\n"));
+    let parsed = FlfFont::from_bytes(written.as_bytes()).unwrap();
+    assert_eq!(parsed.glyphs[&'Ä'], vec![""; 2]);
+    assert_eq!(parsed.glyphs[&'A'], font.glyphs[&'A']);
+}
+
+#[test]
+fn test_outline_tdf_is_rejected() {
+    let mut font = TdfFont::new("Outline", TdfFontType::Outline, 0);
+    font.add_glyph(
+        'A',
+        Glyph {
+            width: 1,
+            height: 1,
+            parts: vec![GlyphPart::OutlinePlaceholder(b'A')],
+        },
+    );
+    assert!(FlfFont::from_tdf(&font, FigletLayout::FullWidth).is_err());
+}
+
+#[test]
+fn test_figlet_loads_as_tdf_block_font() {
+    let fonts = load_tdf_fonts(small_flf().as_bytes()).unwrap();
+    assert_eq!(fonts.len(), 1);
+    let font = &fonts[0];
+    assert_eq!(font.name, "Small Font");
+    assert_eq!(font.font_type, TdfFontType::Block);
+
+    let glyph = font.glyph('A').unwrap();
+    assert_eq!((glyph.width, glyph.height), (2, 2));
+    assert_eq!(glyph.parts.len(), 5);
+    assert!(matches!(glyph.parts[2], GlyphPart::NewLine));
+    // Glyphs outside the TDF range and empty glyphs are dropped
+    assert!(font.glyph('C').is_none());
+}
+
+#[test]
+fn test_tdf_to_figlet_with_smushing() {
+    let font = FlfFont::from_tdf(&color_tdf(), FigletLayout::Smushing(15)).unwrap();
+    assert!(font.to_flf_string().starts_with("flf2a$ 2 2 6 15 1 0 143 0\n"));
+}
+
+#[test]
+fn test_unchanged_tdf_keeps_figlet_font() {
+    let flf = FlfFont::from_bytes(small_flf().as_bytes()).unwrap();
+    assert_eq!(flf.with_tdf_glyphs(&flf.to_tdf()), flf);
+}
+
+#[test]
+fn test_edited_tdf_glyphs_replace_figlet_glyphs() {
+    let flf = FlfFont::from_bytes(small_flf().as_bytes()).unwrap();
+    let mut tdf = flf.to_tdf();
+    tdf.add_glyph(
+        'C',
+        Glyph {
+            width: 1,
+            height: 3,
+            parts: vec![
+                GlyphPart::Char('('),
+                GlyphPart::NewLine,
+                GlyphPart::Char('('),
+                GlyphPart::NewLine,
+                GlyphPart::Char('('),
+            ],
+        },
+    );
+
+    let merged = flf.with_tdf_glyphs(&tdf);
+    assert_eq!(merged.height, 3);
+    assert_eq!(merged.glyphs[&'C'], vec!["("; 3]);
+    assert_eq!(merged.glyphs[&'A'], vec!["/\\", "/\\", "  "]);
+    assert_eq!(merged.glyphs[&'€'], vec!["C=", "C=", "  "]);
+    assert_eq!(merged.layout, FigletLayout::Smushing(15));
+    assert_eq!(merged.comments, flf.comments);
+}
+
+#[test]
+fn test_save_figlet_in_place_keeps_data() {
+    let path = std::env::temp_dir().join(format!("icy_figlet_{}.flf", std::process::id()));
+    std::fs::write(&path, small_flf()).unwrap();
+
+    let mut state = CharSetEditState::load_from_file(path.clone()).unwrap();
+    assert_eq!(state.figlet_layout(), FigletLayout::Smushing(15));
+    state.save(&path).unwrap();
+    let saved = FlfFont::from_bytes(&std::fs::read(&path).unwrap()).unwrap();
+    let _ = std::fs::remove_file(&path);
+
+    assert_eq!(saved, FlfFont::from_bytes(small_flf().as_bytes()).unwrap());
+}
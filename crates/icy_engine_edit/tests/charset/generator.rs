@@ -0,0 +1,183 @@
+//! TDF font generator tests
+//!
+//! Tests the half-block rendering of coverage maps and the threshold, shade and outline options.
+
+use std::collections::BTreeMap;
+
+use icy_engine_edit::charset::{generate_tdf_font, glyph_from_coverage, CharSetEditState, CoverageMap, TdfFontType, TdfGeneratorOptions};
+use retrofont::{Glyph, GlyphPart};
+
+/// Coverage map from rows of `#` (255), `+` (100), `.` (40) and space (0)
+fn map(rows: &[&str]) -> CoverageMap {
+    let mut map = CoverageMap::new(rows[0].len(), rows.len());
+    for (y, row) in rows.iter().enumerate() {
+        for (x, ch) in row.chars().enumerate() {
+            let coverage = match ch {
+                '#' => 255,
+                '+' => 100,
+                '.' => 40,
+                _ => 0,
+            };
+            map.set(x, y, coverage);
+        }
+    }
+    map
+}
+
+fn block_options(cell_height: usize) -> TdfGeneratorOptions {
+    TdfGeneratorOptions {
+        font_type: TdfFontType::Block,
+        cell_height,
+        ..Default::default()
+    }
+}
+
+/// Glyph text, one string per line
+fn lines(glyph: &Glyph) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for part in &glyph.parts {
+        match part {
+            GlyphPart::NewLine => lines.push(String::new()),
+            GlyphPart::Char(ch) | GlyphPart::AnsiChar { ch, .. } => lines.last_mut().unwrap().push(*ch),
+            _ => panic!("unexpected part {part:?}"),
+        }
+    }
+    lines
+}
+
+/// Foreground and background colors of a color glyph
+fn colors(glyph: &Glyph) -> Vec<(char, u8, u8)> {
+    glyph
+        .parts
+        .iter()
+        .filter_map(|part| match part {
+            GlyphPart::AnsiChar { ch, fg, bg, .. } => Some((*ch, *fg, *bg)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_half_block_rendering() {
+    let map = map(&["  #  ", " ### ", " # # ", " ### "]);
+    let glyph = glyph_from_coverage(&map, &block_options(2)).unwrap();
+    assert_eq!((glyph.width, glyph.height), (3, 2));
+    assert_eq!(lines(&glyph), vec!["▄█▄", "█▄█"]);
+    assert!(glyph.parts.iter().all(|part| matches!(part, GlyphPart::Char(_) | GlyphPart::NewLine)));
+}
+
+#[test]
+fn test_trailing_blanks_trimmed_and_empty_skipped() {
+    let map = map(&["#  #", "#   ", "    ", "    "]);
+    let glyph = glyph_from_coverage(&map, &block_options(2)).unwrap();
+    assert_eq!(glyph.width, 4);
+    assert_eq!(lines(&glyph), vec!["█  ▀", ""]);
+
+    assert!(glyph_from_coverage(&CoverageMap::new(4, 4), &block_options(2)).is_none());
+}
+
+#[test]
+fn test_threshold_and_shade() {
+    let map = map(&["#+.", "#+."]);
+    let glyph = glyph_from_coverage(&map, &block_options(1)).unwrap();
+    assert_eq!(lines(&glyph), vec!["█"]);
+
+    let shaded = TdfGeneratorOptions {
+        shade: true,
+        ..block_options(1)
+    };
+    assert_eq!(lines(&glyph_from_coverage(&map, &shaded).unwrap()), vec!["█▓░"]);
+
+    let low_threshold = TdfGeneratorOptions {
+        threshold: 90,
+        ..block_options(1)
+    };
+    assert_eq!(lines(&glyph_from_coverage(&map, &low_threshold).unwrap()), vec!["██"]);
+}
+
+#[test]
+fn test_color_outline() {
+    let options = TdfGeneratorOptions {
+        cell_height: 2,
+        outline: true,
+        color: 14,
+        outline_color: 4,
+        ..Default::default()
+    };
+    let map = map(&["    ", "    ", " #  ", "    "]);
+    let glyph = glyph_from_coverage(&map, &options).unwrap();
+    assert_eq!(lines(&glyph), vec!["▄▄▄", "█▀█"]);
+    assert_eq!(
+        colors(&glyph),
+        vec![('▄', 4, 0), ('▄', 4, 0), ('▄', 4, 0), ('█', 4, 0), ('▀', 14, 4), ('█', 4, 0),]
+    );
+}
+
+#[test]
+fn test_default_outline_keeps_its_background() {
+    let options = TdfGeneratorOptions {
+        cell_height: 2,
+        outline: true,
+        ..Default::default()
+    };
+    let map = map(&["    ", " #  ", "    ", "    "]);
+    let glyph = glyph_from_coverage(&map, &options).unwrap();
+    // The lower outline color is the background under the set pixel, not black
+    assert_eq!(
+        colors(&glyph),
+        vec![('█', 7, 0), ('▄', 15, 7), ('█', 7, 0), ('▀', 7, 0), ('▀', 7, 0), ('▀', 7, 0)]
+    );
+}
+
+#[test]
+fn test_block_outline_is_hollow() {
+    let options = TdfGeneratorOptions {
+        outline: true,
+        ..block_options(2)
+    };
+    let map = map(&["###", "###", "###", "   "]);
+    let glyph = glyph_from_coverage(&map, &options).unwrap();
+    assert_eq!(lines(&glyph), vec!["█▀█", "▀▀▀"]);
+}
+
+#[test]
+fn test_generate_font() {
+    let mut glyphs = BTreeMap::new();
+    glyphs.insert('A', map(&["##", "##"]));
+    glyphs.insert('B', CoverageMap::new(2, 2));
+    glyphs.insert(' ', map(&["##", "##"]));
+    let options = TdfGeneratorOptions {
+        spacing: 2,
+        ..block_options(1)
+    };
+    let font = generate_tdf_font("Generated", &glyphs, &options).unwrap();
+    assert_eq!(font.name, "Generated");
+    assert_eq!(font.font_type, TdfFontType::Block);
+    assert_eq!(font.spacing, 2);
+    assert_eq!(lines(font.glyph('A').unwrap()), vec!["██"]);
+    assert!(font.glyph('B').is_none());
+    assert!(font.glyph(' ').is_none());
+
+    let outline = TdfGeneratorOptions {
+        font_type: TdfFontType::Outline,
+        ..options
+    };
+    assert!(generate_tdf_font("Outline", &glyphs, &outline).is_err());
+    glyphs.remove(&'A');
+    assert!(generate_tdf_font("Empty", &glyphs, &options).is_err());
+}
+
+#[test]
+fn test_generate_into_edit_state() {
+    let mut state = CharSetEditState::new();
+    let mut glyphs = BTreeMap::new();
+    glyphs.insert('x', map(&["# #", " # ", "# #", "   "]));
+    state.generate_font("Generated".to_string(), &glyphs, &TdfGeneratorOptions::default()).unwrap();
+    assert_eq!(state.font_count(), 2);
+    assert_eq!(state.selected_font_index(), 1);
+    assert_eq!(state.selected_font().unwrap().font_type, TdfFontType::Color);
+    assert!(state.selected_font().unwrap().glyph('x').is_some());
+
+    assert!(state.undo());
+    assert_eq!(state.font_count(), 1);
+}
@@ -0,0 +1,2 @@
+mod figlet;
+mod generator;
@@ -208,3 +208,123 @@ fn status_message_does_not_include_group() {
     assert!(data.get("nick").is_none());
     assert!(data.get("group").is_none());
 }
+
+// ========================================================================
+// Protocol V3 Tests
+// ========================================================================
+
+#[test]
+fn for_protocol_drops_v3_commands_on_older_sessions() {
+    let cmd = ClientCommand::SetPalette { colors: vec![[1, 2, 3]] };
+    assert_eq!(cmd.clone().for_protocol(ProtocolVersion::V3), Some(cmd.clone()));
+    assert_eq!(cmd.clone().for_protocol(ProtocolVersion::V2), None);
+    assert_eq!(cmd.for_protocol(ProtocolVersion::V1), None);
+
+    let cmd = ClientCommand::RemoveLayer { index: 1 };
+    assert_eq!(cmd.for_protocol(ProtocolVersion::V1), None);
+
+    let cmd = ClientCommand::SetIceColors { value: true };
+    assert_eq!(cmd.clone().for_protocol(ProtocolVersion::V1), Some(cmd));
+}
+
+#[test]
+fn for_protocol_flattens_layer_draws_on_v1() {
+    let block = Block { code: 65, fg: 7, bg: 0 };
+    let cmd = ClientCommand::Draw {
+        layer: 2,
+        col: 1,
+        row: 1,
+        block: block.clone(),
+    };
+    assert_eq!(cmd.clone().for_protocol(ProtocolVersion::V2), Some(cmd.clone()));
+    assert_eq!(
+        cmd.for_protocol(ProtocolVersion::V1),
+        Some(ClientCommand::Draw {
+            layer: 0,
+            col: 1,
+            row: 1,
+            block
+        })
+    );
+}
+
+#[test]
+fn serialize_draw_includes_layer_only_above_zero() {
+    let block = Block { code: 65, fg: 7, bg: 0 };
+    let msg = command_to_message(
+        ClientCommand::Draw {
+            layer: 0,
+            col: 1,
+            row: 2,
+            block: block.clone(),
+        },
+        Some(3),
+        "n",
+        "",
+    )
+    .expect("expected message");
+    let v: Value = serde_json::from_str(&msg).expect("valid json");
+    assert!(v["data"].get("layer").is_none());
+
+    let msg = command_to_message(
+        ClientCommand::Draw {
+            layer: 2,
+            col: 1,
+            row: 2,
+            block,
+        },
+        Some(3),
+        "n",
+        "",
+    )
+    .expect("expected message");
+    let v: Value = serde_json::from_str(&msg).expect("valid json");
+    assert_eq!(v["data"]["layer"].as_u64(), Some(2));
+}
+
+#[test]
+fn serialize_reorder_layer() {
+    let msg = command_to_message(ClientCommand::ReorderLayer { from: 1, to: 2 }, Some(5), "n", "").expect("expected message");
+    let v: Value = serde_json::from_str(&msg).expect("valid json");
+    assert_eq!(v["type"].as_u64(), Some(103));
+    assert_eq!(v["data"]["id"].as_u64(), Some(5));
+    assert_eq!(v["data"]["from"].as_u64(), Some(1));
+    assert_eq!(v["data"]["to"].as_u64(), Some(2));
+}
+
+#[tokio::test]
+async fn parse_v3_document_messages() {
+    let mut assigned_id: Option<UserId> = None;
+
+    let palette = json!({"type": 100, "data": {"id": 4, "colors": [[1, 2, 3]]}});
+    let reorder = json!({"type": 103, "data": {"id": 4, "from": 0, "to": 1}});
+    let font = json!({"type": 105, "data": {"id": 4, "slot": 2, "font": null}});
+    let layer_draw = json!({"type": 9, "data": {"id": 4, "x": 1, "y": 2, "layer": 3, "block": {"code": 65, "fg": 7, "bg": 0}}});
+
+    match parse_server_message(&palette, "", &mut assigned_id).await {
+        Some(CollaborationEvent::PaletteChanged { user_id: 4, colors }) => assert_eq!(colors, vec![[1, 2, 3]]),
+        other => panic!("Expected PaletteChanged, got {other:?}"),
+    }
+    assert!(matches!(
+        parse_server_message(&reorder, "", &mut assigned_id).await,
+        Some(CollaborationEvent::LayerReordered { user_id: 4, from: 0, to: 1 })
+    ));
+    assert!(matches!(
+        parse_server_message(&font, "", &mut assigned_id).await,
+        Some(CollaborationEvent::FontSlotChanged {
+            user_id: 4,
+            slot: 2,
+            font: None
+        })
+    ));
+    assert!(matches!(
+        parse_server_message(&layer_draw, "", &mut assigned_id).await,
+        Some(CollaborationEvent::Draw {
+            user_id: 4,
+            layer: 3,
+            col: 1,
+            row: 2,
+            ..
+        })
+    ));
+}
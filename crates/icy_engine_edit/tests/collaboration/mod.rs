@@ -12,7 +12,8 @@
 //! # Protocol Versioning
 //!
 //! - Version 1 (default, Moebius-compatible): Single layer support only
-//! - Version 2+: Extended features including layer support
+//! - Version 2: Draws on layers other than the first
+//! - Version 3: Palette, layer, font slot, tag and attribute sync plus per-user undo
 //!
 //! Moebius clients ignore unknown fields, so we use an optional `protocol_version`
 //! field in the CONNECTED message for feature negotiation.
@@ -47,7 +48,10 @@ pub const DEFAULT_EGA_PALETTE: [[u8; 3]; 16] = [
 mod client;
 mod compression;
 mod connector;
+mod oplog;
 mod protocol;
+mod rooms;
 mod server;
 mod session;
+mod spectator;
 mod state;
@@ -0,0 +1,118 @@
+use super::*;
+
+fn block(code: u32) -> Block {
+    Block { code, fg: 7, bg: 0 }
+}
+
+fn draw(layer: usize, col: i32, row: i32, code: u32) -> ClientCommand {
+    ClientCommand::Draw {
+        layer,
+        col,
+        row,
+        block: block(code),
+    }
+}
+
+#[test]
+fn record_tracks_last_writer() {
+    let mut log = OperationLog::new();
+    assert!(log.is_empty());
+
+    log.record(1, 0, 5, 5, block(65));
+    log.record(2, 0, 5, 5, block(66));
+    assert_eq!(log.last_writer(0, 5, 5), Some(2));
+    assert_eq!(log.last_writer(1, 5, 5), None);
+
+    log.clear();
+    assert!(log.is_empty());
+}
+
+#[test]
+fn filter_history_drops_cells_of_other_users() {
+    let mut log = OperationLog::new();
+    log.record_commands(1, &[draw(0, 0, 0, 65), draw(0, 1, 0, 66)]);
+    log.record(2, 0, 1, 0, block(90));
+
+    let (kept, restores) = log.filter_history(1, vec![draw(0, 0, 0, 32), draw(0, 1, 0, 32)]);
+    assert_eq!(kept, vec![draw(0, 0, 0, 32)]);
+    assert_eq!(
+        restores,
+        vec![CellRestore {
+            layer: 0,
+            col: 1,
+            row: 0,
+            block: block(90),
+        }]
+    );
+    // The other user's cell is left alone
+    assert_eq!(log.last_writer(0, 1, 0), Some(2));
+}
+
+#[test]
+fn filter_history_keeps_non_draw_commands() {
+    let mut log = OperationLog::new();
+    log.record(2, 0, 0, 0, block(90));
+
+    let (kept, restores) = log.filter_history(1, vec![ClientCommand::SetIceColors { value: true }]);
+    assert_eq!(kept, vec![ClientCommand::SetIceColors { value: true }]);
+    assert!(restores.is_empty());
+}
+
+#[test]
+fn layer_operations_remap_history() {
+    let mut log = OperationLog::new();
+    log.record(1, 0, 0, 0, block(65));
+    log.record(2, 1, 0, 0, block(66));
+    log.record(3, 2, 0, 0, block(67));
+
+    log.insert_layer(1);
+    assert_eq!(log.last_writer(0, 0, 0), Some(1));
+    assert_eq!(log.last_writer(1, 0, 0), None);
+    assert_eq!(log.last_writer(2, 0, 0), Some(2));
+    assert_eq!(log.last_writer(3, 0, 0), Some(3));
+
+    log.remove_layer(2);
+    assert_eq!(log.last_writer(1, 0, 0), None);
+    assert_eq!(log.last_writer(2, 0, 0), Some(3));
+
+    log.move_layer(0, 2);
+    assert_eq!(log.last_writer(2, 0, 0), Some(1));
+    assert_eq!(log.last_writer(1, 0, 0), Some(3));
+    assert_eq!(log.last_writer(0, 0, 0), None);
+}
+
+#[test]
+fn record_event_tracks_remote_changes() {
+    let mut log = OperationLog::new();
+    log.record_event(&CollaborationEvent::Draw {
+        user_id: 4,
+        layer: 1,
+        col: 2,
+        row: 3,
+        block: block(65),
+    });
+    assert_eq!(log.last_writer(1, 2, 3), Some(4));
+
+    log.record_event(&CollaborationEvent::LayerRemoved { user_id: 4, index: 0 });
+    assert_eq!(log.last_writer(0, 2, 3), Some(4));
+}
+
+#[test]
+fn log_forgets_oldest_cells_per_user() {
+    let mut log = OperationLog::with_limit(2);
+    log.record(1, 0, 0, 0, block(65));
+    log.record(2, 0, 9, 9, block(90));
+    log.record(1, 0, 1, 0, block(66));
+    log.record(1, 0, 2, 0, block(67));
+    assert_eq!(log.last_writer(0, 0, 0), None);
+    assert_eq!(log.last_writer(0, 1, 0), Some(1));
+    assert_eq!(log.last_writer(0, 2, 0), Some(1));
+    // Other users keep their cells
+    assert_eq!(log.last_writer(0, 9, 9), Some(2));
+    assert_eq!(log.len(), 3);
+
+    // Rewriting a cell doesn't let an older write of it evict the new one
+    log.record(1, 0, 1, 0, block(68));
+    log.record(1, 0, 1, 0, block(69));
+    assert_eq!(log.last_writer(0, 1, 0), Some(1));
+}
@@ -222,12 +222,14 @@ fn client_command_cursor() {
 fn client_command_draw() {
     let block = Block { code: 65, fg: 7, bg: 0 };
     let cmd = ClientCommand::Draw {
+        layer: 0,
         col: 5,
         row: 10,
         block: block.clone(),
     };
 
-    if let ClientCommand::Draw { col, row, block: b } = cmd {
+    if let ClientCommand::Draw { layer, col, row, block: b } = cmd {
+        assert_eq!(layer, 0);
         assert_eq!(col, 5);
         assert_eq!(row, 10);
         assert_eq!(b.code, 65);
@@ -334,6 +336,8 @@ fn connected_document_fields() {
         author: "Author".to_string(),
         group: "Group".to_string(),
         comments: "Comments".to_string(),
+        protocol_version: ProtocolVersion::V1,
+        extended: None,
     };
 
     assert_eq!(doc.user_id, 42);
@@ -394,6 +398,8 @@ fn buffer_layer_size_consistency_after_remote_document() {
         author: String::new(),
         group: String::new(),
         comments: String::new(),
+        protocol_version: ProtocolVersion::V1,
+        extended: None,
     };
 
     // Simulate what icy_draw does: create a buffer with default 80x25
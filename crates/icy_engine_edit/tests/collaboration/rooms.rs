@@ -0,0 +1,442 @@
+use super::*;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::json;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use tokio::net::{TcpListener, TcpSocket, TcpStream};
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{client_async, connect_async, MaybeTlsStream, WebSocketStream};
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Fresh, empty data folder for a test.
+fn data_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("icy_rooms_{}_{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+/// Room without the autosave timer, tests save explicitly.
+fn room_settings(name: &str) -> RoomSettings {
+    let mut settings = RoomSettings::new(name);
+    settings.autosave_minutes = 0;
+    settings
+}
+
+/// Serve the host on a random local port, returns the `ws://` base url.
+async fn serve(host: &Arc<RoomHost>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    tokio::spawn(host.clone().serve(listener));
+    url
+}
+
+/// Second loopback address for users that need an address of their own.
+const OTHER_ADDRESS: &str = "127.0.0.2";
+
+/// Connect to a room from a local address as a V3 client, returns the client and the CONNECTED or REFUSED answer.
+async fn connect_from(url: &str, room: &str, nick: &str, local: &str) -> (Client, Value) {
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.bind(format!("{local}:0").parse().unwrap()).expect("bind local address");
+    let stream = socket.connect(url.trim_start_matches("ws://").parse().unwrap()).await.unwrap();
+    let (mut client, _) = client_async(format!("{url}/{room}"), MaybeTlsStream::Plain(stream)).await.expect("connect");
+    let connect = json!({"type": 0, "data": {"nick": nick, "group": "", "pass": "", "protocol_version": 3}}).to_string();
+    client.send(Message::text(connect)).await.unwrap();
+    let answer = recv_json(&mut client).await.expect("CONNECTED or REFUSED");
+    (client, answer)
+}
+
+/// Connect to a room from a local address and wait for CONNECTED, returns the client and its user id.
+async fn join_from(url: &str, room: &str, nick: &str, local: &str) -> (Client, u32) {
+    let (client, msg) = connect_from(url, room, nick, local).await;
+    assert_eq!(msg["type"], 0, "expected CONNECTED: {msg}");
+    let id = msg["data"]["id"].as_u64().expect("user id") as u32;
+    (client, id)
+}
+
+async fn join(url: &str, room: &str, nick: &str) -> (Client, u32) {
+    join_from(url, room, nick, "127.0.0.1").await
+}
+
+/// Next JSON message, `None` on timeout or when the connection closed.
+///
+/// The timeout only guards against hanging tests, checks that nothing arrived use [`sync`].
+async fn recv_json(client: &mut Client) -> Option<Value> {
+    loop {
+        match timeout(Duration::from_secs(5), client.next()).await.ok()?? {
+            Ok(Message::Text(text)) => return serde_json::from_str(&text).ok(),
+            Ok(Message::Close(_)) | Err(_) => return None,
+            Ok(_) => {}
+        }
+    }
+}
+
+/// True if the connection gets closed within five seconds.
+async fn wait_closed(client: &mut Client) -> bool {
+    let closed = async {
+        loop {
+            match client.next().await {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            }
+        }
+    };
+    timeout(Duration::from_secs(5), closed).await.is_ok()
+}
+
+async fn draw(client: &mut Client, id: u32, x: i32, y: i32, code: u32) {
+    let msg = json!({"type": 9, "data": {"id": id, "x": x, "y": y, "block": {"code": code, "fg": 7, "bg": 0}}}).to_string();
+    client.send(Message::text(msg)).await.unwrap();
+}
+
+/// Send a status update from `client` and expect its echo as the next message on `client` and `others`.
+///
+/// The server handles the messages of a connection in order and echoes the status to every user, so
+/// once the echo arrived the server is done with everything `client` sent before, and anything those
+/// messages sent to `others` would have arrived before the echo.
+async fn sync(client: &mut Client, id: u32, others: &mut [&mut Client]) {
+    let status = json!({"type": 11, "data": {"id": id, "status": 0}}).to_string();
+    client.send(Message::text(status)).await.unwrap();
+    for receiver in std::iter::once(client).chain(others.iter_mut().map(|c| &mut **c)) {
+        let msg = recv_json(receiver).await.expect("status echo");
+        assert_eq!(
+            (msg["type"].clone(), msg["data"]["id"].clone()),
+            (json!(11), json!(id)),
+            "expected the status echo, got {msg}"
+        );
+    }
+}
+
+fn other_address() -> IpAddr {
+    OTHER_ADDRESS.parse().unwrap()
+}
+
+#[test]
+fn test_room_names() {
+    assert!(is_valid_room_name("main"));
+    assert!(is_valid_room_name("Room_2.v-1"));
+    assert!(!is_valid_room_name(""));
+    assert!(!is_valid_room_name(".hidden"));
+    assert!(!is_valid_room_name("a/b"));
+    assert!(!is_valid_room_name("with space"));
+}
+
+#[test]
+fn test_admin_command_parsing() {
+    assert_eq!("help".parse::<AdminCommand>(), Ok(AdminCommand::Help));
+    assert_eq!("ROOMS".parse::<AdminCommand>(), Ok(AdminCommand::Rooms));
+    assert_eq!(
+        "create art secret".parse::<AdminCommand>(),
+        Ok(AdminCommand::Create {
+            room: "art".to_string(),
+            password: "secret".to_string()
+        })
+    );
+    assert_eq!(
+        "create art".parse::<AdminCommand>(),
+        Ok(AdminCommand::Create {
+            room: "art".to_string(),
+            password: String::new()
+        })
+    );
+    assert_eq!("default none".parse::<AdminCommand>(), Ok(AdminCommand::Default(None)));
+    assert_eq!("default art".parse::<AdminCommand>(), Ok(AdminCommand::Default(Some("art".to_string()))));
+    assert_eq!(
+        "kick art 3".parse::<AdminCommand>(),
+        Ok(AdminCommand::Kick {
+            room: "art".to_string(),
+            user: "3".to_string()
+        })
+    );
+    assert_eq!(
+        "readwrite art Bob".parse::<AdminCommand>(),
+        Ok(AdminCommand::ReadOnly {
+            room: "art".to_string(),
+            user: "Bob".to_string(),
+            read_only: false
+        })
+    );
+    assert_eq!(
+        "ban art Bob".parse::<AdminCommand>(),
+        Ok(AdminCommand::Ban {
+            room: "art".to_string(),
+            user: "Bob".to_string(),
+            banned: true
+        })
+    );
+    assert_eq!(
+        "unban art 10.0.0.1".parse::<AdminCommand>(),
+        Ok(AdminCommand::Ban {
+            room: "art".to_string(),
+            user: "10.0.0.1".to_string(),
+            banned: false
+        })
+    );
+    assert_eq!("exit".parse::<AdminCommand>(), Ok(AdminCommand::Quit));
+
+    assert_eq!("   ".parse::<AdminCommand>(), Err(String::new()));
+    assert!("kick art".parse::<AdminCommand>().is_err());
+    assert!("dance".parse::<AdminCommand>().is_err());
+}
+
+#[tokio::test]
+async fn test_create_and_remove_rooms() {
+    let dir = data_dir("create");
+    let host = RoomHost::open(&dir).await.unwrap();
+    assert!(host.rooms().is_empty());
+
+    host.create_room(room_settings("b")).await.unwrap();
+    host.create_room(room_settings("a")).await.unwrap();
+    assert!(host.create_room(room_settings("a")).await.is_err(), "duplicate room");
+    assert!(host.create_room(room_settings("../up")).await.is_err(), "invalid name");
+    let names: Vec<String> = host.rooms().iter().map(|r| r.name()).collect();
+    assert_eq!(names, vec!["a", "b"]);
+    assert!(dir.join("rooms.toml").exists());
+
+    assert!(host.room_for_path("/a").is_some());
+    assert!(host.room_for_path("/").is_none(), "no default room yet");
+    host.set_default_room(Some("b".to_string())).unwrap();
+    assert_eq!(host.room_for_path("/").unwrap().name(), "b");
+    assert!(host.set_default_room(Some("missing".to_string())).is_err());
+
+    host.remove_room("b").await.unwrap();
+    assert!(host.room("b").is_none());
+    assert_eq!(host.default_room(), None);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_join_room_by_path() {
+    let dir = data_dir("join");
+    let host = RoomHost::open(&dir).await.unwrap();
+    host.create_room(room_settings("main")).await.unwrap();
+    let url = serve(&host).await;
+
+    let (mut client, id) = join(&url, "main", "Alice").await;
+    sync(&mut client, id, &mut []).await;
+    let users = host.room("main").unwrap().users();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].id, id);
+    assert_eq!(users[0].nick, "Alice");
+
+    assert!(connect_async(format!("{url}/nope")).await.is_err(), "unknown room must be refused");
+    assert!(connect_async(url.clone()).await.is_err(), "no default room");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_rooms_are_isolated() {
+    let dir = data_dir("isolated");
+    let host = RoomHost::open(&dir).await.unwrap();
+    host.create_room(room_settings("a")).await.unwrap();
+    host.create_room(room_settings("b")).await.unwrap();
+    let url = serve(&host).await;
+
+    let (mut alice, alice_id) = join(&url, "a", "Alice").await;
+    let (mut bob, bob_id) = join(&url, "b", "Bob").await;
+    // bob's JOIN went out before his own status echo, alice's echo must be her next message
+    sync(&mut bob, bob_id, &mut []).await;
+    sync(&mut alice, alice_id, &mut []).await;
+
+    draw(&mut alice, alice_id, 1, 1, 'A' as u32).await;
+    sync(&mut alice, alice_id, &mut []).await;
+    // the draw is handled, bob's echo must be his next message
+    sync(&mut bob, bob_id, &mut []).await;
+
+    let room_a = host.room("a").unwrap();
+    let room_b = host.room("b").unwrap();
+    assert_eq!(room_a.state.char_at(1, 1).await.unwrap().code, 'A' as u32);
+    assert_ne!(room_b.state.char_at(1, 1).await.unwrap().code, 'A' as u32);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+#[cfg_attr(target_os = "macos", ignore = "needs 127.0.0.2 on the loopback interface")]
+async fn test_read_only_user_cannot_draw() {
+    let dir = data_dir("read_only");
+    let host = RoomHost::open(&dir).await.unwrap();
+    host.create_room(room_settings("main")).await.unwrap();
+    let url = serve(&host).await;
+
+    let (mut alice, _) = join(&url, "main", "Alice").await;
+    let (mut viewer, viewer_id) = join_from(&url, "main", "Viewer", OTHER_ADDRESS).await;
+    assert_eq!(recv_json(&mut alice).await.expect("JOIN")["type"], 2);
+
+    host.execute(&"readonly main Viewer".parse().unwrap()).await.unwrap();
+    assert_eq!(host.room("main").unwrap().settings().read_only, vec![other_address()]);
+
+    // The viewer gets the cell reverted and is told once that it is read-only
+    draw(&mut viewer, viewer_id, 2, 3, 'X' as u32).await;
+    let revert = recv_json(&mut viewer).await.expect("reverted draw");
+    assert_eq!(
+        (revert["type"].clone(), revert["data"]["x"].clone(), revert["data"]["y"].clone()),
+        (json!(9), json!(2), json!(3))
+    );
+    assert_ne!(revert["data"]["block"]["code"], 'X' as u32);
+    let notice = recv_json(&mut viewer).await.expect("read-only notice");
+    assert_eq!(notice, json!({"type": 108, "data": {"notice": "read_only"}}));
+    sync(&mut viewer, viewer_id, &mut [&mut alice]).await;
+    let room = host.room("main").unwrap();
+    assert_ne!(room.state.char_at(2, 3).await.unwrap().code, 'X' as u32);
+
+    draw(&mut viewer, viewer_id, 2, 3, 'X' as u32).await;
+    assert_eq!(recv_json(&mut viewer).await.expect("reverted draw")["type"], 9);
+    // the notice is only sent once
+    sync(&mut viewer, viewer_id, &mut []).await;
+
+    // Chat is still allowed
+    let chat = json!({"type": 10, "data": {"id": viewer_id, "nick": "Viewer", "text": "hi"}}).to_string();
+    viewer.send(Message::text(chat)).await.unwrap();
+    let msg = recv_json(&mut alice).await.expect("chat");
+    assert_eq!(msg["type"], 10);
+
+    // Another nickname from the same address is still read-only
+    drop(viewer);
+    assert_eq!(recv_json(&mut alice).await.expect("LEAVE")["type"], 3);
+    let (mut viewer, viewer_id) = join_from(&url, "main", "NotTheViewer", OTHER_ADDRESS).await;
+    draw(&mut viewer, viewer_id, 2, 3, 'X' as u32).await;
+    assert_eq!(recv_json(&mut viewer).await.expect("reverted draw")["type"], 9);
+    assert_eq!(recv_json(&mut viewer).await.expect("read-only notice")["type"], 108);
+
+    host.execute(&"readwrite main NotTheViewer".parse().unwrap()).await.unwrap();
+    assert!(room.settings().read_only.is_empty());
+    draw(&mut viewer, viewer_id, 2, 3, 'X' as u32).await;
+    sync(&mut viewer, viewer_id, &mut []).await;
+    assert_eq!(room.state.char_at(2, 3).await.unwrap().code, 'X' as u32);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_kick_closes_connection() {
+    let dir = data_dir("kick");
+    let host = RoomHost::open(&dir).await.unwrap();
+    host.create_room(room_settings("main")).await.unwrap();
+    let url = serve(&host).await;
+
+    let (mut alice, _) = join(&url, "main", "Alice").await;
+    let (mut bob, _) = join(&url, "main", "Bob").await;
+    let _ = recv_json(&mut alice).await; // JOIN
+
+    assert!(host.execute(&"kick main Nobody".parse().unwrap()).await.is_err());
+    host.execute(&"kick main Bob".parse().unwrap()).await.unwrap();
+    assert!(wait_closed(&mut bob).await, "kicked client must be disconnected");
+
+    let leave = recv_json(&mut alice).await.expect("LEAVE");
+    assert_eq!(leave["type"], 3);
+    let users = host.room("main").unwrap().users();
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].nick, "Alice");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+#[cfg_attr(target_os = "macos", ignore = "needs 127.0.0.2 on the loopback interface")]
+async fn test_ban_refuses_reconnect() {
+    let dir = data_dir("ban");
+    let host = RoomHost::open(&dir).await.unwrap();
+    host.create_room(room_settings("main")).await.unwrap();
+    let url = serve(&host).await;
+
+    let (mut alice, _) = join(&url, "main", "Alice").await;
+    let (mut bob, _) = join_from(&url, "main", "Bob", OTHER_ADDRESS).await;
+    assert_eq!(recv_json(&mut alice).await.expect("JOIN")["type"], 2);
+
+    host.execute(&"ban main Bob".parse().unwrap()).await.unwrap();
+    assert!(wait_closed(&mut bob).await, "banned client must be disconnected");
+    assert_eq!(recv_json(&mut alice).await.expect("LEAVE")["type"], 3);
+    assert_eq!(host.room("main").unwrap().settings().banned, vec![other_address()]);
+
+    // A new nickname doesn't help, other addresses still get in
+    let (_, refused) = connect_from(&url, "main", "NotBob", OTHER_ADDRESS).await;
+    assert_eq!(refused, json!({"type": 1, "data": {"reason": "Banned"}}));
+    join(&url, "main", "Carol").await;
+
+    assert!(host.execute(&"unban main Bob".parse().unwrap()).await.is_err(), "unban needs an address");
+    host.execute(&format!("unban main {OTHER_ADDRESS}").parse().unwrap()).await.unwrap();
+    assert!(host.room("main").unwrap().settings().banned.is_empty());
+    join_from(&url, "main", "Bob", OTHER_ADDRESS).await;
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_rooms_persist_across_restart() {
+    let dir = data_dir("persist");
+    {
+        let host = RoomHost::open(&dir).await.unwrap();
+        let mut settings = room_settings("art");
+        settings.set_password("secret");
+        host.create_room(settings).await.unwrap();
+        host.set_default_room(Some("art".to_string())).unwrap();
+        host.set_read_only("art", "10.0.0.7".parse().unwrap(), true).await.unwrap();
+        host.set_banned("art", "10.0.0.8".parse().unwrap(), true).await.unwrap();
+
+        let room = host.room("art").unwrap();
+        room.state
+            .set_char(
+                3,
+                2,
+                Block {
+                    code: 'Z' as u32,
+                    fg: 4,
+                    bg: 1,
+                },
+            )
+            .await;
+        room.state.session.add_chat_message(1, "Alice".to_string(), "see you tomorrow".to_string());
+        assert_eq!(host.save_all().await, 0);
+        // nothing changed, no new backup
+        assert_eq!(room.save().await.unwrap(), None);
+    }
+
+    let host = RoomHost::open(&dir).await.unwrap();
+    assert_eq!(host.default_room(), Some("art".to_string()));
+    let room = host.room("art").expect("room restored");
+    let settings = room.settings();
+    assert!(verify_password(&settings.password_hash, "secret"));
+    assert!(!std::fs::read_to_string(dir.join(ROOMS_FILE)).unwrap().contains("secret"));
+    assert_eq!(settings.read_only, vec!["10.0.0.7".parse::<IpAddr>().unwrap()]);
+    assert_eq!(settings.banned, vec!["10.0.0.8".parse::<IpAddr>().unwrap()]);
+    assert!(room.state.is_banned("10.0.0.8".parse().unwrap()).await);
+
+    let block = room.state.char_at(3, 2).await.unwrap();
+    assert_eq!(
+        block,
+        Block {
+            code: 'Z' as u32,
+            fg: 4,
+            bg: 1
+        }
+    );
+    let chat = room.state.session.get_chat_history();
+    assert_eq!(chat.len(), 1);
+    assert_eq!(chat[0].text, "see you tomorrow");
+
+    // the restored document counts as saved
+    assert_eq!(room.save().await.unwrap(), None);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_plain_text_passwords_are_hashed() {
+    let dir = data_dir("legacy_password");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join(ROOMS_FILE), "[[rooms]]\nname = \"old\"\npassword = \"secret\"\nautosave_minutes = 0\n").unwrap();
+
+    let host = RoomHost::open(&dir).await.unwrap();
+    let room = host.room("old").expect("room");
+    assert!(verify_password(&room.settings().password_hash, "secret"));
+    assert!(room.state.session.check_password("secret"));
+    assert!(!room.state.session.check_password("wrong"));
+    let text = std::fs::read_to_string(dir.join(ROOMS_FILE)).unwrap();
+    assert!(!text.contains("secret"), "rooms.toml must not keep the plain text password: {text}");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
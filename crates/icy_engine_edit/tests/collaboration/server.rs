@@ -503,3 +503,205 @@ async fn test_broadcast_flip_y() {
     // Sender should NOT receive their own message
     expect_no_message(&mut user2.rx).await;
 }
+
+// ============================================================================
+// Protocol V3 tests
+// ============================================================================
+
+/// Connect a user announcing `version`, returns the user and the CONNECTED message.
+async fn connect_with_version(state: &Arc<ServerState>, nick: &str, version: Option<u8>) -> (TestUser, serde_json::Value) {
+    let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+    let (tx, mut rx) = mpsc::channel::<String>(32);
+    let mut id: Option<UserId> = None;
+    let mut user_nick = String::new();
+    let mut data = json!({"nick": nick, "group": "", "pass": ""});
+    if let Some(version) = version {
+        data["protocol_version"] = json!(version);
+    }
+    let msg = json!({"type": 0, "data": data}).to_string();
+    handle_message(state, &tx, &mut id, &mut user_nick, &msg, addr).await.unwrap();
+    let connected = recv_json(&mut rx).await;
+    let user = TestUser {
+        id: id.unwrap(),
+        nick: user_nick,
+        tx,
+        rx,
+    };
+    (user, connected)
+}
+
+#[tokio::test]
+async fn test_protocol_version_negotiation() {
+    let state = ServerState::new(ServerConfig::default());
+
+    let (v3, connected) = connect_with_version(&state, "New", Some(3)).await;
+    assert_eq!(connected["data"]["protocol_version"].as_u64(), Some(3));
+    assert!(connected["data"]["extended"]["palette"].is_array());
+    assert_eq!(state.protocol_version(v3.id).await, ProtocolVersion::V3);
+
+    // Future versions are capped at what the server speaks
+    let (future, connected) = connect_with_version(&state, "Future", Some(9)).await;
+    assert_eq!(connected["data"]["protocol_version"].as_u64(), Some(3));
+    assert_eq!(state.protocol_version(future.id).await, ProtocolVersion::V3);
+
+    // Moebius clients don't send a version and get a plain Moebius response
+    let (moebius, connected) = connect_with_version(&state, "Moebius", None).await;
+    assert!(connected["data"].get("protocol_version").is_none());
+    assert!(connected["data"].get("extended").is_none());
+    assert_eq!(state.protocol_version(moebius.id).await, ProtocolVersion::V1);
+}
+
+#[tokio::test]
+async fn test_protocol_version_disabled_extended_protocol() {
+    let config = ServerConfig {
+        enable_extended_protocol: false,
+        ..Default::default()
+    };
+    let state = ServerState::new(config);
+
+    let (user, connected) = connect_with_version(&state, "New", Some(3)).await;
+    assert!(connected["data"].get("protocol_version").is_none());
+    assert_eq!(state.protocol_version(user.id).await, ProtocolVersion::V1);
+}
+
+#[tokio::test]
+async fn test_document_messages_relayed_to_v3_only() {
+    let state = ServerState::new(ServerConfig::default());
+    let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+
+    let (mut sender, _) = connect_with_version(&state, "Sender", Some(3)).await;
+    let (mut other, _) = connect_with_version(&state, "Other", Some(3)).await;
+    let _ = recv_json(&mut sender.rx).await; // JOIN for other
+    let (mut moebius, _) = connect_with_version(&state, "Moebius", None).await;
+    let _ = recv_json(&mut sender.rx).await; // JOIN for moebius
+    let _ = recv_json(&mut other.rx).await; // JOIN for moebius
+
+    let msg = json!({"type": 100, "data": {"colors": [[1, 2, 3], [4, 5, 6]]}}).to_string();
+    handle_message(&state, &sender.tx, &mut Some(sender.id), &mut sender.nick, &msg, addr)
+        .await
+        .unwrap();
+
+    let received = recv_json(&mut other.rx).await;
+    assert_eq!(received["type"].as_u64(), Some(100));
+    assert_eq!(received["data"]["id"].as_u64(), Some(u64::from(sender.id)));
+    expect_no_message(&mut moebius.rx).await;
+    expect_no_message(&mut sender.rx).await;
+
+    // Late joiners get the palette with the CONNECTED message
+    assert_eq!(state.extended_document().await.palette, vec![[1, 2, 3], [4, 5, 6]]);
+    let (_late, connected) = connect_with_version(&state, "Late", Some(3)).await;
+    assert_eq!(connected["data"]["extended"]["palette"], json!([[1, 2, 3], [4, 5, 6]]));
+}
+
+#[tokio::test]
+async fn test_document_messages_from_v1_clients_are_ignored() {
+    let state = ServerState::new(ServerConfig::default());
+    let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+
+    let (mut v3, _) = connect_with_version(&state, "New", Some(3)).await;
+    let (mut moebius, _) = connect_with_version(&state, "Moebius", None).await;
+    let _ = recv_json(&mut v3.rx).await; // JOIN for moebius
+
+    let msg = json!({"type": 102, "data": {"index": 0}}).to_string();
+    handle_message(&state, &moebius.tx, &mut Some(moebius.id), &mut moebius.nick, &msg, addr)
+        .await
+        .unwrap();
+    expect_no_message(&mut v3.rx).await;
+}
+
+#[tokio::test]
+async fn test_layer_draw_does_not_change_document() {
+    let state = ServerState::new(ServerConfig::default());
+    let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+
+    let (mut sender, _) = connect_with_version(&state, "Sender", Some(3)).await;
+    let (mut other, _) = connect_with_version(&state, "Other", Some(3)).await;
+    let _ = recv_json(&mut sender.rx).await; // JOIN for other
+    let (mut moebius, _) = connect_with_version(&state, "Moebius", None).await;
+    let _ = recv_json(&mut sender.rx).await; // JOIN for moebius
+    let _ = recv_json(&mut other.rx).await; // JOIN for moebius
+
+    let msg = r#"{"type":9,"data":{"id":1,"x":1,"y":2,"layer":1,"block":{"code":65,"fg":7,"bg":0}}}"#;
+    handle_message(&state, &sender.tx, &mut Some(sender.id), &mut sender.nick, msg, addr)
+        .await
+        .unwrap();
+
+    let received = recv_json(&mut other.rx).await;
+    assert_eq!(received["data"]["layer"].as_u64(), Some(1));
+    expect_no_message(&mut moebius.rx).await;
+    assert_ne!(state.char_at(1, 2).await.map(|b| b.code), Some(65));
+}
+
+#[tokio::test]
+async fn test_layer_draw_is_not_sent_to_v2_clients() {
+    let state = ServerState::new(ServerConfig::default());
+    let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+
+    let (mut sender, _) = connect_with_version(&state, "Sender", Some(3)).await;
+    let (mut v2, _) = connect_with_version(&state, "Older", Some(2)).await;
+    let _ = recv_json(&mut sender.rx).await; // JOIN for v2
+
+    let msg = r#"{"type":9,"data":{"id":1,"x":1,"y":2,"layer":1,"block":{"code":65,"fg":7,"bg":0}}}"#;
+    handle_message(&state, &sender.tx, &mut Some(sender.id), &mut sender.nick, msg, addr)
+        .await
+        .unwrap();
+    expect_no_message(&mut v2.rx).await;
+
+    // Draws on the first layer still reach everyone
+    let msg = r#"{"type":9,"data":{"id":1,"x":1,"y":2,"layer":0,"block":{"code":66,"fg":7,"bg":0}}}"#;
+    handle_message(&state, &sender.tx, &mut Some(sender.id), &mut sender.nick, msg, addr)
+        .await
+        .unwrap();
+    let received = recv_json(&mut v2.rx).await;
+    assert_eq!(received["data"]["block"]["code"].as_u64(), Some(66));
+}
+
+#[tokio::test]
+async fn test_late_joiners_get_the_layer_stack() {
+    use icy_engine::TextPane;
+
+    let state = ServerState::new(ServerConfig::default());
+    let addr: SocketAddr = "127.0.0.1:12345".parse().unwrap();
+    let (mut sender, _) = connect_with_version(&state, "Sender", Some(3)).await;
+    let send = |msg: String| {
+        let state = state.clone();
+        let tx = sender.tx.clone();
+        let id = sender.id;
+        let mut nick = sender.nick.clone();
+        async move { handle_message(&state, &tx, &mut Some(id), &mut nick, &msg, addr).await.unwrap() }
+    };
+
+    // Layer 0 comes from the Moebius document
+    state
+        .set_char(
+            0,
+            0,
+            Block {
+                code: 'B' as u32,
+                fg: 7,
+                bg: 0,
+            },
+        )
+        .await;
+    let layer = icy_engine::Layer::new("Top", (80, 25));
+    send(json!({"type": 101, "data": {"index": 1, "layer": layer}}).to_string()).await;
+    send(r#"{"type":9,"data":{"id":1,"x":3,"y":4,"layer":1,"block":{"code":65,"fg":4,"bg":0}}}"#.to_string()).await;
+    let mut properties = layer.properties.clone();
+    properties.title = "Renamed".to_string();
+    send(json!({"type": 104, "data": {"index": 1, "properties": properties}}).to_string()).await;
+    send(json!({"type": 103, "data": {"from": 1, "to": 0}}).to_string()).await;
+
+    let layers = state.extended_document().await.layers;
+    assert_eq!(layers.len(), 2);
+    assert_eq!(layers[0].properties.title, "Renamed");
+    assert_eq!(layers[0].char_at(icy_engine::Position::new(3, 4)).ch, 'A');
+    assert_eq!(layers[1].char_at(icy_engine::Position::new(0, 0)).ch, 'B');
+
+    let (_late, connected) = connect_with_version(&state, "Late", Some(3)).await;
+    let extended: ExtendedDocument = serde_json::from_value(connected["data"]["extended"].clone()).unwrap();
+    assert_eq!(extended.layers, layers);
+
+    send(json!({"type": 102, "data": {"index": 0}}).to_string()).await;
+    assert_eq!(state.extended_document().await.layers.len(), 1);
+    let _ = recv_json(&mut sender.rx).await; // JOIN for late
+}
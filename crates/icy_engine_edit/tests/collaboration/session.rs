@@ -14,6 +14,21 @@ fn test_empty_password() {
     assert!(session.check_password("anything"));
 }
 
+#[test]
+fn test_password_hash() {
+    let hash = hash_password("secret");
+    assert!(!hash.contains("secret"));
+    assert_ne!(hash, hash_password("secret"), "every hash gets its own salt");
+    assert!(verify_password(&hash, "secret"));
+    assert!(!verify_password(&hash, "wrong"));
+    assert!(!verify_password("secret", "secret"), "malformed hashes match nothing");
+
+    let mut session = Session::new(String::new());
+    session.password_hash = hash;
+    assert!(session.check_password("secret"));
+    assert!(!session.check_password(""));
+}
+
 #[test]
 fn test_user_management() {
     let session = Session::new(String::new());
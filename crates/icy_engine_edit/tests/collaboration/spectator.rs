@@ -0,0 +1,223 @@
+use super::*;
+use futures_util::{SinkExt, StreamExt};
+use icy_engine::BitFont;
+use serde_json::json;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+type Client = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Host with a single room "main" on a random local port, returns the host and its address.
+async fn serve_room(test: &str, password: &str) -> (Arc<RoomHost>, PathBuf, String) {
+    let dir = std::env::temp_dir().join(format!("icy_spectator_{}_{}", test, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let host = RoomHost::open(&dir).await.unwrap();
+    let mut settings = RoomSettings::new("main");
+    settings.autosave_minutes = 0;
+    settings.set_password(password);
+    host.create_room(settings).await.unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    tokio::spawn(host.clone().serve(listener));
+    (host, dir, addr)
+}
+
+/// Plain HTTP GET, returns the response head and body.
+async fn http_get(addr: &str, path: &str) -> (String, Vec<u8>) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!("GET {path} HTTP/1.1\r\nHost: {addr}\r\nAccept: */*\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = Vec::new();
+    timeout(Duration::from_secs(2), stream.read_to_end(&mut response)).await.unwrap().unwrap();
+    let split = response.windows(4).position(|w| w == b"\r\n\r\n").expect("response head");
+    let head = String::from_utf8_lossy(&response[..split]).to_string();
+    (head, response[split + 4..].to_vec())
+}
+
+/// Next text message, `None` on timeout or when the connection closed.
+async fn recv_text(client: &mut Client) -> Option<String> {
+    loop {
+        match timeout(Duration::from_millis(200), client.next()).await.ok()?? {
+            Ok(Message::Text(text)) => return Some(text.to_string()),
+            Ok(Message::Close(_)) | Err(_) => return None,
+            Ok(_) => {}
+        }
+    }
+}
+
+/// Apply all messages that arrive until the connection is quiet.
+async fn drain(client: &mut Client, view: &mut SpectatorView) {
+    while let Some(text) = recv_text(client).await {
+        view.handle_message(&text);
+    }
+}
+
+async fn join(addr: &str, nick: &str) -> (Client, u32) {
+    let (mut client, _) = connect_async(format!("ws://{addr}/main")).await.expect("connect");
+    let connect = json!({"type": 0, "data": {"nick": nick, "group": "", "pass": ""}}).to_string();
+    client.send(Message::text(connect)).await.unwrap();
+    loop {
+        let msg: Value = serde_json::from_str(&recv_text(&mut client).await.expect("CONNECTED")).unwrap();
+        if msg["type"] == 0 {
+            return (client, msg["data"]["id"].as_u64().unwrap() as u32);
+        }
+    }
+}
+
+async fn send(client: &mut Client, msg: Value) {
+    client.send(Message::text(msg.to_string())).await.unwrap();
+}
+
+/// Decode an atlas, returns width, height and RGBA pixels.
+fn decode_png(data: Vec<u8>) -> (usize, usize, Vec<u8>) {
+    let decoder = png::Decoder::new(std::io::Cursor::new(data));
+    let mut reader = decoder.read_info().unwrap();
+    let (width, height) = (reader.info().width as usize, reader.info().height as usize);
+    let mut rgba = vec![0; width * height * 4];
+    reader.next_frame(&mut rgba).unwrap();
+    (width, height, rgba)
+}
+
+#[test]
+fn test_glyph_atlas() {
+    let font = BitFont::default();
+    let (width, height, rgba) = decode_png(glyph_atlas_png(&font, false).unwrap());
+    assert_eq!((width, height), (font.width as usize * 16, font.height as usize * 16));
+
+    let glyph = &font.glyphs()[b'A' as usize];
+    let (cell_x, cell_y) = ((b'A' as usize % 16) * font.width as usize, (b'A' as usize / 16) * font.height as usize);
+    for y in 0..font.height as usize {
+        for x in 0..font.width as usize {
+            let alpha = rgba[((cell_y + y) * width + cell_x + x) * 4 + 3];
+            assert_eq!(alpha == 0xFF, glyph.get_pixel(x, y), "pixel {x},{y} of 'A'");
+        }
+    }
+}
+
+#[test]
+fn test_glyph_atlas_letter_spacing() {
+    let font = BitFont::default();
+    let (width, _, rgba) = decode_png(glyph_atlas_png(&font, true).unwrap());
+    let glyph_width = font.width as usize + 1;
+    assert_eq!(width, glyph_width * 16);
+
+    let ninth_column_set = |ch: usize| {
+        let (cell_x, cell_y) = ((ch % 16) * glyph_width, (ch / 16) * font.height as usize);
+        (0..font.height as usize).any(|y| rgba[((cell_y + y) * width + cell_x + font.width as usize) * 4 + 3] == 0xFF)
+    };
+    // Box drawing characters connect, everything else gets a gap
+    assert!(ninth_column_set(0xC4));
+    assert!(!ninth_column_set(0xDB));
+    assert!(!ninth_column_set(b'A' as usize));
+}
+
+#[tokio::test]
+async fn test_serves_page_and_font() {
+    let (host, dir, addr) = serve_room("http", "").await;
+
+    let (head, body) = http_get(&addr, "/main").await;
+    assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+    assert!(head.contains("text/html"));
+    assert_eq!(body, SPECTATOR_PAGE.as_bytes());
+
+    let (head, body) = http_get(&addr, "/main/font.png?v=2").await;
+    assert!(head.starts_with("HTTP/1.1 200"), "{head}");
+    assert!(head.contains("image/png"));
+    let font = host.room("main").unwrap().state.spectator_font().await;
+    assert_eq!(body, glyph_atlas_png(&font, false).unwrap());
+
+    let (head, _) = http_get(&addr, "/nope").await;
+    assert!(head.starts_with("HTTP/1.1 404"), "{head}");
+    let (head, _) = http_get(&addr, "/").await;
+    assert!(head.starts_with("HTTP/1.1 404"), "no default room: {head}");
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_spectator_follows_session() {
+    let (host, dir, addr) = serve_room("follow", "").await;
+    let (mut alice, alice_id) = join(&addr, "Alice").await;
+
+    let (mut guest, _) = connect_async(format!("ws://{addr}/main")).await.expect("connect");
+    guest.send(Message::text(SpectatorView::connect_message(""))).await.unwrap();
+    let mut view = SpectatorView::default();
+    drain(&mut guest, &mut view).await;
+    assert!(view.is_connected());
+    assert_eq!((view.columns, view.rows), (80, 25));
+    assert_eq!(view.users.get(&alice_id).map(String::as_str), Some("Alice"));
+    let guest_user = host.room("main").unwrap().users().into_iter().find(|u| u.id == view.user_id).unwrap();
+    assert_eq!(guest_user.status, user_status::WEB);
+
+    let block = json!({"code": 'A' as u32, "fg": 14, "bg": 1});
+    send(&mut alice, json!({"type": 9, "data": {"id": alice_id, "x": 2, "y": 3, "block": block}})).await;
+    send(&mut alice, json!({"type": 4, "data": {"id": alice_id, "x": 5, "y": 6}})).await;
+    send(
+        &mut alice,
+        json!({"type": 10, "data": {"id": alice_id, "nick": "Alice", "group": "", "text": "secret"}}),
+    )
+    .await;
+    send(&mut alice, json!({"type": 13, "data": {"id": alice_id, "value": true}})).await;
+    let mut messages = Vec::new();
+    while let Some(text) = recv_text(&mut guest).await {
+        view.handle_message(&text);
+        messages.push(text);
+    }
+    assert!(!messages.iter().any(|m| m.contains("secret")), "web guests don't get chat");
+    assert_eq!(
+        view.block(2, 3),
+        Some(&Block {
+            code: 'A' as u32,
+            fg: 14,
+            bg: 1
+        })
+    );
+    assert_eq!(view.cursors.get(&alice_id), Some(&(5, 6)));
+    assert!(view.ice_colors);
+
+    let (_bob, bob_id) = join(&addr, "Bob").await;
+    drain(&mut guest, &mut view).await;
+    assert_eq!(view.users.get(&bob_id).map(String::as_str), Some("Bob"));
+
+    drop(alice);
+    drain(&mut guest, &mut view).await;
+    assert!(!view.users.contains_key(&alice_id));
+    assert!(!view.cursors.contains_key(&alice_id));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn test_spectator_refused() {
+    let (_host, dir, addr) = serve_room("refused", "secret").await;
+
+    let (mut guest, _) = connect_async(format!("ws://{addr}/main")).await.expect("connect");
+    guest.send(Message::text(SpectatorView::connect_message("wrong"))).await.unwrap();
+    let mut view = SpectatorView::default();
+    drain(&mut guest, &mut view).await;
+    assert!(!view.is_connected());
+    assert_eq!(view.refused.as_deref(), Some("Invalid password"));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_spectator_resize_keeps_content() {
+    let mut view = SpectatorView::default();
+    view.handle_message(
+        &json!({"type": 0, "data": {"id": 7, "doc": {"columns": 4, "rows": 2, "data": vec![json!({"code": 66, "fg": 7, "bg": 0}); 8]}}}).to_string(),
+    );
+    assert_eq!(view.user_id, 7);
+    assert_eq!(view.block(3, 1).map(|b| b.code), Some(66));
+
+    view.handle_message(&json!({"action": 16, "columns": 2, "rows": 3}).to_string());
+    assert_eq!((view.columns, view.rows), (2, 3));
+    assert_eq!(view.block(1, 1).map(|b| b.code), Some(66));
+    assert_eq!(view.block(1, 2).map(|b| b.code), Some(0));
+    assert_eq!(view.block(3, 1), None);
+}
@@ -16,6 +16,8 @@ fn dummy_doc(user_id: u32) -> ConnectedDocument {
         author: String::new(),
         group: String::new(),
         comments: String::new(),
+        protocol_version: ProtocolVersion::V1,
+        extended: None,
     }
 }
 
@@ -281,6 +283,8 @@ fn start_session_with_users_and_chat() {
         author: "Author".to_string(),
         group: "Group".to_string(),
         comments: "Comments".to_string(),
+        protocol_version: ProtocolVersion::V1,
+        extended: None,
     };
 
     core.start_session(&doc);
@@ -428,3 +432,300 @@ fn reset_sync_pointer() {
     let cmds = core.sync_from_undo_stack(&undo, (5, 5), false);
     assert!(cmds.contains(&ClientCommand::Cursor { col: 5, row: 5 }));
 }
+
+// ========================================================================
+// Per-user Undo Tests (V3)
+// ========================================================================
+
+fn v3_doc(user_id: u32) -> ConnectedDocument {
+    ConnectedDocument {
+        protocol_version: ProtocolVersion::V3,
+        ..dummy_doc(user_id)
+    }
+}
+
+fn set_char(x: i32, y: i32, ch: char) -> EditorUndoOp {
+    EditorUndoOp::SetChar {
+        pos: icy_engine_edit::Position::new(x, y),
+        layer: 0,
+        old: icy_engine_edit::AttributedChar::default(),
+        new: icy_engine_edit::AttributedChar::new(ch, icy_engine_edit::TextAttribute::default()),
+        undo_caret: None,
+        redo_caret: None,
+    }
+}
+
+fn draws(cmds: &[ClientCommand]) -> Vec<(i32, i32)> {
+    cmds.iter()
+        .filter_map(|cmd| match cmd {
+            ClientCommand::Draw { col, row, .. } => Some((*col, *row)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn remote_draw(user_id: UserId, col: i32, row: i32, code: u32) -> CollaborationEvent {
+    CollaborationEvent::Draw {
+        user_id,
+        layer: 0,
+        col,
+        row,
+        block: Block { code, fg: 4, bg: 1 },
+    }
+}
+
+fn undo_last(undo: &mut EditorUndoStack) {
+    let op = undo.pop_undo().unwrap();
+    undo.push_redo(op);
+}
+
+fn redo_last(undo: &mut EditorUndoStack) {
+    let op = undo.pop_redo().unwrap();
+    undo.push_undo(op);
+}
+
+#[test]
+fn selective_undo_skips_cells_changed_by_others() {
+    let mut core = CollaborationCoreState::new();
+    core.start_session(&v3_doc(1));
+    let mut undo = EditorUndoStack::new();
+    let _ = core.sync_from_undo_stack(&undo, (0, 0), false);
+
+    undo.push(set_char(0, 0, 'A'));
+    undo.push(set_char(1, 0, 'B'));
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert_eq!(draws(&cmds), vec![(0, 0), (1, 0)]);
+
+    // User 2 paints over our first cell
+    core.record_remote_event(&remote_draw(2, 0, 0, 88));
+
+    // Undoing our cell nobody touched is sent as usual
+    undo_last(&mut undo);
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert_eq!(draws(&cmds), vec![(1, 0)]);
+    assert!(core.take_pending_restores().is_empty());
+
+    // Undoing the overwritten cell keeps user 2's content
+    undo_last(&mut undo);
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert!(draws(&cmds).is_empty());
+    assert_eq!(
+        core.take_pending_restores(),
+        vec![CellRestore {
+            layer: 0,
+            col: 0,
+            row: 0,
+            block: Block { code: 88, fg: 4, bg: 1 },
+        }]
+    );
+}
+
+#[test]
+fn selective_redo_skips_cells_but_new_edits_overwrite() {
+    let mut core = CollaborationCoreState::new();
+    core.start_session(&v3_doc(1));
+    let mut undo = EditorUndoStack::new();
+    let _ = core.sync_from_undo_stack(&undo, (0, 0), false);
+
+    undo.push(set_char(0, 0, 'A'));
+    let _ = core.sync_from_undo_stack(&undo, (0, 0), false);
+    undo_last(&mut undo);
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert_eq!(draws(&cmds), vec![(0, 0)]);
+
+    core.record_remote_event(&remote_draw(2, 0, 0, 88));
+
+    // Redo would overwrite user 2's cell
+    redo_last(&mut undo);
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert!(draws(&cmds).is_empty());
+    assert_eq!(core.take_pending_restores().len(), 1);
+
+    // A new edit always goes through and makes the cell ours again
+    undo.push(set_char(0, 0, 'C'));
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert_eq!(draws(&cmds), vec![(0, 0)]);
+    undo_last(&mut undo);
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert_eq!(draws(&cmds), vec![(0, 0)]);
+    assert!(core.take_pending_restores().is_empty());
+}
+
+#[test]
+fn v1_session_undoes_everything() {
+    let mut core = CollaborationCoreState::new();
+    core.start_session(&dummy_doc(1));
+    let mut undo = EditorUndoStack::new();
+    let _ = core.sync_from_undo_stack(&undo, (0, 0), false);
+
+    undo.push(set_char(0, 0, 'A'));
+    let _ = core.sync_from_undo_stack(&undo, (0, 0), false);
+    core.record_remote_event(&remote_draw(2, 0, 0, 88));
+
+    undo_last(&mut undo);
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert_eq!(draws(&cmds), vec![(0, 0)]);
+    assert!(core.take_pending_restores().is_empty());
+}
+
+#[test]
+fn sync_document_sends_changes_in_v3_only() {
+    let mut buffer = icy_engine_edit::TextBuffer::new((80, 25));
+
+    let mut v1 = CollaborationCoreState::new();
+    v1.start_session(&dummy_doc(1));
+    assert!(v1.sync_document(&buffer).is_empty());
+    buffer.palette.set_color_rgb(1, 10, 20, 30);
+    assert!(v1.sync_document(&buffer).is_empty());
+
+    let mut core = CollaborationCoreState::new();
+    core.start_session(&v3_doc(1));
+    // First call only takes the snapshot
+    assert!(core.sync_document(&buffer).is_empty());
+    assert!(core.sync_document(&buffer).is_empty());
+
+    buffer.palette.set_color_rgb(2, 40, 50, 60);
+    let cmds = core.sync_document(&buffer);
+    assert_eq!(cmds.len(), 1);
+    let ClientCommand::SetPalette { colors } = &cmds[0] else {
+        panic!("Expected SetPalette command");
+    };
+    assert_eq!(colors[2], [40, 50, 60]);
+    assert!(core.sync_document(&buffer).is_empty());
+
+    buffer.layers[0].properties.is_visible = false;
+    let cmds = core.sync_document(&buffer);
+    assert!(matches!(cmds.as_slice(), [ClientCommand::SetLayerProperties { index: 0, .. }]));
+}
+
+#[test]
+fn remembered_document_is_not_sent_back() {
+    let mut buffer = icy_engine_edit::TextBuffer::new((80, 25));
+    let mut core = CollaborationCoreState::new();
+    core.start_session(&v3_doc(1));
+    let _ = core.sync_document(&buffer);
+
+    // A remote palette change applied locally
+    buffer.palette.set_color_rgb(3, 1, 2, 3);
+    core.remember_document(&buffer);
+    assert!(core.sync_document(&buffer).is_empty());
+}
+
+#[test]
+fn identical_new_edit_is_not_taken_for_redo() {
+    let mut core = CollaborationCoreState::new();
+    core.start_session(&v3_doc(1));
+    let mut undo = EditorUndoStack::new();
+    let _ = core.sync_from_undo_stack(&undo, (0, 0), false);
+
+    undo.push(set_char(0, 0, 'A'));
+    let _ = core.sync_from_undo_stack(&undo, (0, 0), false);
+    undo_last(&mut undo);
+    let _ = core.sync_from_undo_stack(&undo, (0, 0), false);
+
+    core.record_remote_event(&remote_draw(2, 0, 0, 88));
+
+    // Same edit as the one on the redo stack, but a new one
+    undo.push(set_char(0, 0, 'A'));
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert_eq!(draws(&cmds), vec![(0, 0)]);
+    assert!(core.take_pending_restores().is_empty());
+}
+
+fn test_layer(title: &str) -> icy_engine_edit::Layer {
+    icy_engine_edit::Layer::new(title, (10, 5))
+}
+
+#[test]
+fn merge_down_is_sent_as_layer_commands() {
+    let mut core = CollaborationCoreState::new();
+    core.start_session(&v3_doc(1));
+    let mut undo = EditorUndoStack::new();
+    let _ = core.sync_from_undo_stack(&undo, (0, 0), false);
+
+    undo.push(EditorUndoOp::MergeLayerDown {
+        index: 1,
+        merged_layer: Some(test_layer("merged")),
+        orig_layers: Some(vec![test_layer("below"), test_layer("above")]),
+    });
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert!(matches!(
+        cmds.as_slice(),
+        [
+            ClientCommand::RemoveLayer { index: 1 },
+            ClientCommand::RemoveLayer { index: 0 },
+            ClientCommand::AddLayer { index: 0, .. },
+            ..
+        ]
+    ));
+
+    undo_last(&mut undo);
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    let added: Vec<(usize, String)> = cmds
+        .iter()
+        .filter_map(|cmd| match cmd {
+            ClientCommand::AddLayer { index, layer } => Some((*index, layer.properties.title.clone())),
+            _ => None,
+        })
+        .collect();
+    assert!(matches!(cmds[0], ClientCommand::RemoveLayer { index: 0 }));
+    assert_eq!(added, vec![(0, "below".to_string()), (1, "above".to_string())]);
+}
+
+#[test]
+fn anchoring_a_paste_sends_only_draws() {
+    let mut core = CollaborationCoreState::new();
+    core.start_session(&v3_doc(1));
+    let mut undo = EditorUndoStack::new();
+    let _ = core.sync_from_undo_stack(&undo, (0, 0), false);
+
+    undo.push(EditorUndoOp::MergeLayerDown {
+        index: 1,
+        merged_layer: Some(test_layer("merged")),
+        orig_layers: Some(vec![test_layer("below"), test_layer("floating")]),
+    });
+    undo.push(EditorUndoOp::PasteAnchor {
+        x: 2,
+        y: 3,
+        blocks: Blocks {
+            columns: 1,
+            rows: 1,
+            data: vec![Block { code: 65, fg: 7, bg: 0 }],
+        },
+    });
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert!(cmds.iter().all(|cmd| matches!(cmd, ClientCommand::Draw { .. } | ClientCommand::Cursor { .. })));
+    assert_eq!(draws(&cmds), vec![(2, 3)]);
+}
+
+#[test]
+fn sync_document_follows_layer_commands() {
+    let mut buffer = icy_engine_edit::TextBuffer::new((80, 25));
+    let mut core = CollaborationCoreState::new();
+    core.start_session(&v3_doc(1));
+    let mut undo = EditorUndoStack::new();
+    let _ = core.sync_from_undo_stack(&undo, (0, 0), false);
+    let _ = core.sync_document(&buffer);
+
+    // A layer added and the base layer hidden in the same edit
+    let layer = test_layer("new");
+    buffer.layers.push(layer.clone());
+    buffer.layers[0].properties.is_visible = false;
+    undo.push(EditorUndoOp::AddLayer {
+        index: 1,
+        layer: Box::new(layer),
+    });
+    let cmds = core.sync_from_undo_stack(&undo, (0, 0), false);
+    assert!(matches!(cmds[0], ClientCommand::AddLayer { index: 1, .. }));
+    let cmds = core.sync_document(&buffer);
+    assert!(matches!(cmds.as_slice(), [ClientCommand::SetLayerProperties { index: 0, .. }]));
+
+    // Layer sizes have no command of their own, the layer is sent again
+    buffer.layers[1].set_size((20, 5));
+    let cmds = core.sync_document(&buffer);
+    assert!(matches!(
+        cmds.as_slice(),
+        [ClientCommand::RemoveLayer { index: 1 }, ClientCommand::AddLayer { index: 1, .. }]
+    ));
+    assert!(core.sync_document(&buffer).is_empty());
+}
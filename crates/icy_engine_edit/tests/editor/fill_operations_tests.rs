@@ -0,0 +1,78 @@
+//! Tests for gradient and pattern fill operations
+
+use icy_engine::{AttributedChar, Position, Selection, Shape, TextAttribute, TextPane};
+use icy_engine_edit::brushes::{Gradient, GradientShape};
+use icy_engine_edit::{EditState, UndoState};
+
+fn create_test_state(width: i32, height: i32) -> EditState {
+    let buffer = icy_engine::TextBuffer::create((width, height));
+    EditState::from_buffer(buffer)
+}
+
+fn set_char(state: &mut EditState, x: i32, y: i32, ch: char) {
+    if let Some(layer) = state.get_cur_layer_mut() {
+        layer.set_char(Position::new(x, y), AttributedChar::new(ch, TextAttribute::default()));
+    }
+}
+
+fn char_at(state: &EditState, x: i32, y: i32) -> AttributedChar {
+    state.get_buffer().layers[0].char_at(Position::new(x, y))
+}
+
+#[test]
+fn test_flood_region_stops_at_other_chars() {
+    let mut state = create_test_state(10, 3);
+    for y in 0..3 {
+        set_char(&mut state, 4, y, '#');
+    }
+    let region = state.fill_region(Position::new(0, 0), false);
+    assert_eq!(region.len(), 12);
+    assert!(region.iter().all(|p| p.x < 4));
+}
+
+#[test]
+fn test_region_is_selection_when_selected() {
+    let mut state = create_test_state(10, 5);
+    let mut sel = Selection::new(Position::new(2, 1));
+    sel.lead = Position::new(4, 2);
+    sel.shape = Shape::Rectangle;
+    state.set_selection(sel).unwrap();
+
+    let region = state.fill_region(Position::new(0, 0), false);
+    assert_eq!(region.len(), 6);
+    assert!(region.contains(&Position::new(2, 1)));
+    assert!(region.contains(&Position::new(4, 2)));
+}
+
+#[test]
+fn test_fill_gradient_is_one_undo_step() {
+    let mut state = create_test_state(10, 2);
+    let gradient = Gradient::from_ramp(GradientShape::Linear, &[1, 2, 3], &[], false);
+    let region = state.fill_region(Position::new(0, 0), false);
+    let before = char_at(&state, 9, 0);
+
+    let initial_undo_len = state.undo_stack_len();
+    state.fill_gradient(&region, &gradient, Position::new(0, 0), Position::new(9, 0)).unwrap();
+    assert_eq!(state.undo_stack_len(), initial_undo_len + 1);
+
+    assert_eq!(char_at(&state, 0, 0).attribute.foreground(), 1);
+    assert_eq!(char_at(&state, 5, 1).attribute.foreground(), 2);
+    assert_eq!(char_at(&state, 9, 0).attribute.foreground(), 3);
+
+    state.undo().unwrap();
+    assert_eq!(char_at(&state, 9, 0), before);
+}
+
+#[test]
+fn test_fill_pattern_repeats_tile() {
+    let mut state = create_test_state(6, 2);
+    let tile = vec![vec![
+        AttributedChar::new('X', TextAttribute::default()),
+        AttributedChar::new('O', TextAttribute::default()),
+    ]];
+    let region = state.fill_region(Position::new(0, 0), false);
+    state.fill_pattern(&region, &tile, Position::new(0, 0)).unwrap();
+
+    let row: String = (0..6).map(|x| char_at(&state, x, 1).ch).collect();
+    assert_eq!(row, "XOXOXO");
+}
@@ -0,0 +1,300 @@
+//! Tests for the edit journal (recording, replay and timelapse frames)
+
+use icy_engine::{AttributedChar, Layer, Position, TextAttribute, TextPane};
+use icy_engine_edit::collaboration::Block;
+use icy_engine_edit::{timelapse_script, EditJournal, EditState, JournalData, JournalEvent, JournalPlayer, JournalRecorder, TimelapseStep, UndoState};
+
+fn create_test_state(width: i32, height: i32) -> EditState {
+    let buffer = icy_engine::TextBuffer::create((width, height));
+    EditState::from_buffer(buffer)
+}
+
+fn start_recording(state: &EditState) -> JournalRecorder {
+    let mut recorder = JournalRecorder::new(EditJournal::new(state.get_buffer()).unwrap());
+    sync(&mut recorder, state);
+    recorder
+}
+
+fn sync(recorder: &mut JournalRecorder, state: &EditState) {
+    let stack = state.get_undo_stack();
+    recorder.sync_from_undo_stack(&stack.lock().unwrap());
+}
+
+fn set_char(state: &mut EditState, x: i32, y: i32, ch: char) {
+    state.set_char(Position::new(x, y), AttributedChar::new(ch, TextAttribute::default())).unwrap();
+}
+
+fn char_at(buffer: &icy_engine::TextBuffer, x: i32, y: i32) -> char {
+    buffer.layers[0].char_at(Position::new(x, y)).ch
+}
+
+/// Replays the journal to its end and compares every cell with the editor
+fn assert_replay_matches(recorder: &JournalRecorder, state: &EditState) {
+    let mut player = JournalPlayer::new(recorder.journal().clone()).unwrap();
+    player.seek(player.len());
+    let expected = state.get_buffer();
+    for y in 0..expected.height() {
+        for x in 0..expected.width() {
+            assert_eq!(char_at(player.buffer(), x, y), char_at(expected, x, y), "cell {x},{y}");
+        }
+    }
+}
+
+#[test]
+fn test_journal_records_edits_undo_and_redo() {
+    let mut state = create_test_state(20, 10);
+    let mut recorder = start_recording(&state);
+
+    set_char(&mut state, 1, 1, 'A');
+    sync(&mut recorder, &state);
+    set_char(&mut state, 2, 1, 'B');
+    sync(&mut recorder, &state);
+    state.undo().unwrap();
+    sync(&mut recorder, &state);
+    state.redo().unwrap();
+    state.undo().unwrap();
+    sync(&mut recorder, &state);
+
+    let events = recorder.journal().entries();
+    assert_eq!(events.len(), 3);
+    assert!(matches!(events[0].event, JournalEvent::Apply(_)));
+    assert!(matches!(events[1].event, JournalEvent::Apply(_)));
+    assert!(matches!(events[2].event, JournalEvent::Undo(_)));
+    assert_replay_matches(&recorder, &state);
+}
+
+#[test]
+fn test_journal_ignores_history_before_recording() {
+    let mut state = create_test_state(20, 10);
+    set_char(&mut state, 0, 0, 'X');
+
+    let mut recorder = start_recording(&state);
+    assert!(recorder.journal().is_empty());
+
+    set_char(&mut state, 1, 0, 'Y');
+    sync(&mut recorder, &state);
+    assert_eq!(recorder.journal().len(), 1);
+
+    let mut player = JournalPlayer::new(recorder.journal().clone()).unwrap();
+    assert_eq!(char_at(player.buffer(), 0, 0), 'X');
+    player.seek(1);
+    assert_eq!(char_at(player.buffer(), 1, 0), 'Y');
+}
+
+#[test]
+fn test_journal_follows_atomic_group_synced_midway() {
+    let mut state = create_test_state(20, 10);
+    let mut recorder = start_recording(&state);
+
+    {
+        let _guard = state.begin_atomic_undo("Group");
+        set_char(&mut state, 0, 0, 'A');
+        set_char(&mut state, 1, 0, 'B');
+        sync(&mut recorder, &state);
+        set_char(&mut state, 2, 0, 'C');
+    }
+    sync(&mut recorder, &state);
+    assert_eq!(recorder.journal().len(), 3);
+    assert_replay_matches(&recorder, &state);
+
+    // Undoing the group reverts all three cells
+    state.undo().unwrap();
+    sync(&mut recorder, &state);
+    assert_replay_matches(&recorder, &state);
+    assert_eq!(char_at(state.get_buffer(), 2, 0), ' ');
+}
+
+#[test]
+fn test_journal_notices_replaced_operation_of_the_same_kind() {
+    let mut state = create_test_state(20, 10);
+    let mut recorder = start_recording(&state);
+
+    set_char(&mut state, 1, 1, 'A');
+    sync(&mut recorder, &state);
+    // Same stack length and operation kind as before the sync, only the sequence id differs
+    state.undo().unwrap();
+    set_char(&mut state, 2, 1, 'B');
+    sync(&mut recorder, &state);
+
+    assert_replay_matches(&recorder, &state);
+    assert_eq!(char_at(state.get_buffer(), 1, 1), ' ');
+}
+
+#[test]
+fn test_journal_reverts_discarded_operations() {
+    let mut state = create_test_state(20, 10);
+    let mut recorder = start_recording(&state);
+
+    {
+        let mut guard = state.begin_atomic_undo("Paste");
+        state.paste_text("Test").unwrap();
+        sync(&mut recorder, &state);
+        guard.discard_and_undo(&mut state);
+    }
+    sync(&mut recorder, &state);
+
+    let mut player = JournalPlayer::new(recorder.journal().clone()).unwrap();
+    player.seek(player.len());
+    assert_eq!(player.buffer().layers.len(), state.get_buffer().layers.len());
+}
+
+#[test]
+fn test_journal_records_cells_outside_undo_stack() {
+    let state = create_test_state(20, 10);
+    let mut recorder = start_recording(&state);
+    recorder.record_cell(
+        0,
+        3,
+        4,
+        Block {
+            code: 'Z' as u32,
+            fg: 4,
+            bg: 1,
+        },
+    );
+
+    let mut player = JournalPlayer::new(recorder.journal().clone()).unwrap();
+    player.seek(1);
+    let ch = player.buffer().layers[0].char_at(Position::new(3, 4));
+    assert_eq!(ch.ch, 'Z');
+    assert_eq!(ch.attribute.foreground(), 4);
+}
+
+#[test]
+fn test_journal_replays_remote_layer_and_palette_changes() {
+    let state = create_test_state(20, 10);
+    let mut recorder = start_recording(&state);
+
+    let mut layer = Layer::new("Remote", (20, 10));
+    layer.set_char((1, 1), AttributedChar::new('R', TextAttribute::default()));
+    recorder.record(JournalEvent::AddLayer {
+        index: 1,
+        layer: JournalData::new(&layer).unwrap(),
+    });
+    let mut properties = layer.properties.clone();
+    properties.title = "Renamed".to_string();
+    properties.is_visible = false;
+    recorder.record(JournalEvent::SetLayerProperties {
+        index: 1,
+        properties: JournalData::new(&properties).unwrap(),
+    });
+    recorder.record(JournalEvent::ReorderLayer { from: 1, to: 0 });
+    recorder.record(JournalEvent::SetPalette {
+        colors: vec![[1, 2, 3], [4, 5, 6]],
+    });
+
+    let mut player = JournalPlayer::new(recorder.journal().clone()).unwrap();
+    player.seek(player.len());
+    let buffer = player.buffer();
+    assert_eq!(buffer.layers.len(), 2);
+    assert_eq!(buffer.layers[0].properties.title, "Renamed");
+    assert!(!buffer.layers[0].properties.is_visible);
+    assert_eq!(buffer.layers[0].char_at(Position::new(1, 1)).ch, 'R');
+    assert_eq!(buffer.palette.len(), 2);
+    assert_eq!(buffer.palette.color(1).rgb(), (4, 5, 6));
+
+    recorder.record(JournalEvent::RemoveLayer { index: 0 });
+    let mut player = JournalPlayer::new(recorder.journal().clone()).unwrap();
+    player.seek(player.len());
+    assert_eq!(player.buffer().layers.len(), 1);
+}
+
+#[test]
+fn test_journal_stores_operations_as_json() {
+    let mut state = create_test_state(20, 10);
+    let mut recorder = start_recording(&state);
+    set_char(&mut state, 1, 1, 'A');
+    sync(&mut recorder, &state);
+
+    let JournalEvent::Apply(op) = &recorder.journal().entries()[0].event else {
+        panic!("expected an applied operation");
+    };
+    let json = serde_json::to_string(op).unwrap();
+    assert!(json.starts_with('"'), "operation isn't stored as a JSON string: {json}");
+    assert!(op.get().is_ok());
+}
+
+#[test]
+fn test_journal_end_checksum() {
+    let mut state = create_test_state(20, 10);
+    let mut recorder = start_recording(&state);
+    assert!(!recorder.journal().leads_to(state.get_buffer()));
+
+    set_char(&mut state, 1, 1, 'A');
+    sync(&mut recorder, &state);
+    recorder.set_end_document(state.get_buffer());
+    assert!(recorder.journal().leads_to(state.get_buffer()));
+
+    // Replaying leads to the same document
+    let mut player = JournalPlayer::new(recorder.journal().clone()).unwrap();
+    player.seek(player.len());
+    assert!(recorder.journal().leads_to(player.buffer()));
+
+    // A document changed outside the journal doesn't match
+    set_char(&mut state, 2, 1, 'B');
+    assert!(!recorder.journal().leads_to(state.get_buffer()));
+    let mut other = state.get_buffer().clone();
+    other.layers[0].set_char((1, 1), AttributedChar::new('A', TextAttribute::new(4, 0)));
+    assert!(!recorder.journal().leads_to(&other));
+}
+
+#[test]
+fn test_journal_finish() {
+    let state = create_test_state(20, 10);
+    let mut journal = EditJournal::new(state.get_buffer()).unwrap();
+    assert!(!journal.is_finished());
+    journal.finish();
+    assert!(journal.is_finished());
+    journal.resume();
+    assert!(!journal.is_finished());
+}
+
+#[test]
+fn test_player_seeks_backwards() {
+    let mut state = create_test_state(20, 10);
+    let mut recorder = start_recording(&state);
+    for (i, ch) in "ABC".chars().enumerate() {
+        set_char(&mut state, i as i32, 0, ch);
+        sync(&mut recorder, &state);
+    }
+
+    let mut player = JournalPlayer::new(recorder.journal().clone()).unwrap();
+    player.seek(3);
+    assert!(player.is_at_end());
+    assert_eq!(char_at(player.buffer(), 2, 0), 'C');
+
+    player.seek(1);
+    assert_eq!(player.position(), 1);
+    assert_eq!(char_at(player.buffer(), 0, 0), 'A');
+    assert_eq!(char_at(player.buffer(), 1, 0), ' ');
+}
+
+#[test]
+fn test_timelapse_positions() {
+    let state = create_test_state(20, 10);
+    let mut journal = EditJournal::new(state.get_buffer()).unwrap();
+    for time_ms in [100, 200, 250, 900, 1000] {
+        journal.push(
+            time_ms,
+            JournalEvent::SetCell {
+                layer: 0,
+                col: 0,
+                row: 0,
+                block: Block::default(),
+            },
+        );
+    }
+
+    assert_eq!(journal.timelapse_positions(TimelapseStep::Edits(2)), vec![0, 2, 4, 5]);
+    assert_eq!(journal.timelapse_positions(TimelapseStep::Millis(500)), vec![0, 3, 5]);
+    assert_eq!(journal.entries_until(250), 3);
+}
+
+#[test]
+fn test_timelapse_script_lists_frames() {
+    let script = timelapse_script(&["a_0001.icy".to_string(), "a \"2\".icy".to_string()], 80);
+    assert!(script.contains("set_delay(80)"));
+    assert!(script.contains("\"a_0001.icy\","));
+    assert!(script.contains("\"a \\\"2\\\".icy\","));
+    assert!(script.contains("next_frame(load_buffer(file))"));
+}
@@ -1,7 +1,7 @@
 //! Tests for layer operations (add, remove, raise, lower, duplicate, merge, etc.)
 
 use icy_engine::{AttributedChar, LayerProperties, Position, Role, Sixel, Size, TextAttribute, TextPane};
-use icy_engine_edit::EditState;
+use icy_engine_edit::{EditState, UndoState};
 
 /// Helper to create an EditState with a given size
 fn create_test_state(width: i32, height: i32) -> EditState {
@@ -555,6 +555,20 @@ fn test_update_layer_properties() {
     assert_eq!(state.undo_stack_len(), initial_undo_len + 1);
 }
 
+#[test]
+fn test_update_layer_properties_undo_redo() {
+    let mut state = create_test_state(20, 10);
+    let mut new_props = state.get_buffer().layers[0].properties.clone();
+    new_props.title = "Renamed".to_string();
+    let old_title = state.get_buffer().layers[0].properties.title.clone();
+
+    state.update_layer_properties(0, new_props).unwrap();
+    state.undo().unwrap();
+    assert_eq!(state.get_buffer().layers[0].properties.title, old_title);
+    state.redo().unwrap();
+    assert_eq!(state.get_buffer().layers[0].properties.title, "Renamed");
+}
+
 // ============================================================================
 // Paste Cancel Tests
 // ============================================================================
@@ -649,3 +663,119 @@ fn test_discard_and_undo_reverts_multiple_operations() {
     // Everything should be reverted
     assert_eq!(state.get_buffer().layers.len(), initial_layer_count, "All operations should be reverted");
 }
+
+// ============================================================================
+// Layer Group Tests
+// ============================================================================
+
+/// Background, a group around one layer and a layer on top: [0, 1 (in group), group, 3]
+fn create_grouped_state() -> EditState {
+    let mut state = create_test_state(20, 10);
+    state.add_new_layer(0).unwrap();
+    state.add_new_layer(1).unwrap();
+    state.group_layer(1).unwrap();
+    state
+}
+
+fn depths(state: &EditState) -> Vec<u8> {
+    state.get_buffer().layers.iter().map(|l| l.properties.depth).collect()
+}
+
+#[test]
+fn test_group_layer_and_undo() {
+    let mut state = create_test_state(20, 10);
+    state.add_new_layer(0).unwrap();
+    let undo_len = state.undo_stack_len();
+
+    state.group_layer(1).unwrap();
+    assert_eq!(state.undo_stack_len(), undo_len + 1);
+    assert_eq!(depths(&state), vec![0, 1, 0]);
+    assert!(state.get_buffer().layers[2].is_group());
+    assert_eq!(state.get_current_layer().unwrap(), 2);
+
+    state.undo().unwrap();
+    assert_eq!(depths(&state), vec![0, 0]);
+}
+
+#[test]
+fn test_ungroup_layer_and_undo() {
+    let mut state = create_grouped_state();
+    let undo_len = state.undo_stack_len();
+
+    state.ungroup_layer(2).unwrap();
+    assert_eq!(state.undo_stack_len(), undo_len + 1);
+    assert_eq!(depths(&state), vec![0, 0, 0]);
+    assert!(!state.get_buffer().layers.iter().any(|l| l.is_group()));
+
+    state.undo().unwrap();
+    assert_eq!(depths(&state), vec![0, 1, 0, 0]);
+    assert!(state.get_buffer().layers[2].is_group());
+
+    assert!(state.ungroup_layer(0).is_err());
+}
+
+#[test]
+fn test_locked_group_protects_members() {
+    let mut state = create_grouped_state();
+    let mut properties = state.get_buffer().layers[2].properties.clone();
+    properties.is_locked = true;
+    state.update_layer_properties(2, properties).unwrap();
+
+    state.set_current_layer(1);
+    let undo_len = state.undo_stack_len();
+    assert!(state.set_char(Position::new(0, 0), AttributedChar::new('A', TextAttribute::default())).is_err());
+    assert!(state.remove_layer(2).is_err());
+    assert_eq!(state.undo_stack_len(), undo_len);
+    assert_eq!(state.get_buffer().layers.len(), 4);
+
+    state.set_current_layer(3);
+    state.set_char(Position::new(0, 0), AttributedChar::new('A', TextAttribute::default())).unwrap();
+}
+
+#[test]
+fn test_remove_group_removes_members() {
+    let mut state = create_grouped_state();
+    let undo_len = state.undo_stack_len();
+
+    state.remove_layer(2).unwrap();
+    assert_eq!(state.undo_stack_len(), undo_len + 1);
+    assert_eq!(depths(&state), vec![0, 0]);
+
+    state.undo().unwrap();
+    assert_eq!(depths(&state), vec![0, 1, 0, 0]);
+}
+
+#[test]
+fn test_raise_and_lower_move_whole_group() {
+    let mut state = create_grouped_state();
+    let title = |state: &EditState, i: usize| state.get_buffer().layers[i].properties.title.clone();
+    let top = title(&state, 3);
+    let group = title(&state, 2);
+
+    state.raise_layer(2).unwrap();
+    assert_eq!(depths(&state), vec![0, 0, 1, 0]);
+    assert_eq!(title(&state, 1), top);
+    assert_eq!(title(&state, 3), group);
+    assert_eq!(state.get_current_layer().unwrap(), 3);
+
+    state.lower_layer(3).unwrap();
+    assert_eq!(depths(&state), vec![0, 1, 0, 0]);
+    assert_eq!(title(&state, 2), group);
+    assert_eq!(state.get_current_layer().unwrap(), 2);
+
+    // The only member stays inside of its group
+    let undo_len = state.undo_stack_len();
+    assert!(state.raise_layer(1).is_err());
+    assert!(state.lower_layer(1).is_err());
+    assert_eq!(depths(&state), vec![0, 1, 0, 0]);
+    assert_eq!(state.undo_stack_len(), undo_len);
+}
+
+#[test]
+fn test_merge_down_refuses_groups() {
+    let mut state = create_grouped_state();
+    assert!(state.merge_layer_down(1).is_err());
+    assert!(state.merge_layer_down(2).is_err());
+    assert!(state.merge_layer_down(3).is_err());
+    assert_eq!(state.get_buffer().layers.len(), 4);
+}
@@ -0,0 +1,121 @@
+//! Tests for magic wand and selection mask operations
+
+use icy_engine::{AddType, AttributeColor, AttributedChar, Position, Selection, Shape, TextAttribute};
+use icy_engine_edit::{CellMatch, EditState, UndoState};
+
+fn create_test_state(width: i32, height: i32) -> EditState {
+    let buffer = icy_engine::TextBuffer::create((width, height));
+    EditState::from_buffer(buffer)
+}
+
+fn set_char(state: &mut EditState, x: i32, y: i32, ch: char, fg: u32, bg: u32) {
+    if let Some(layer) = state.get_cur_layer_mut() {
+        layer.set_char(Position::new(x, y), AttributedChar::new(ch, TextAttribute::new(fg, bg)));
+    }
+}
+
+fn selected_row(state: &EditState, y: i32, width: i32) -> String {
+    (0..width).map(|x| if state.is_selected(Position::new(x, y)) { '#' } else { '.' }).collect()
+}
+
+/// Row 0: "AA.AA" with a gap, the last A in red
+fn setup_row(state: &mut EditState) {
+    for x in [0, 1, 3] {
+        set_char(state, x, 0, 'A', 7, 0);
+    }
+    set_char(state, 4, 0, 'A', 4, 0);
+}
+
+#[test]
+fn test_magic_wand_contiguous() {
+    let mut state = create_test_state(5, 2);
+    setup_row(&mut state);
+
+    let initial_undo_len = state.undo_stack_len();
+    state
+        .magic_wand_select(Position::new(0, 0), CellMatch::default(), true, AddType::Default)
+        .unwrap();
+    assert_eq!(state.undo_stack_len(), initial_undo_len + 1);
+    assert_eq!(selected_row(&state, 0, 5), "##...");
+    assert_eq!(selected_row(&state, 1, 5), ".....");
+
+    state.undo().unwrap();
+    assert!(!state.is_something_selected());
+}
+
+#[test]
+fn test_magic_wand_global_with_colors() {
+    let mut state = create_test_state(5, 2);
+    setup_row(&mut state);
+
+    state
+        .magic_wand_select(Position::new(0, 0), CellMatch::default(), false, AddType::Default)
+        .unwrap();
+    assert_eq!(selected_row(&state, 0, 5), "##.##");
+
+    let criteria = CellMatch {
+        foreground: true,
+        ..Default::default()
+    };
+    state.magic_wand_select(Position::new(0, 0), criteria, false, AddType::Default).unwrap();
+    assert_eq!(selected_row(&state, 0, 5), "##.#.");
+
+    // Red (170, 0, 0) and light gray (170, 170, 170) are apart by 170 in green and blue
+    let criteria = CellMatch { tolerance: 170, ..criteria };
+    state.magic_wand_select(Position::new(0, 0), criteria, false, AddType::Default).unwrap();
+    assert_eq!(selected_row(&state, 0, 5), "##.##");
+}
+
+#[test]
+fn test_magic_wand_add_and_subtract() {
+    let mut state = create_test_state(5, 2);
+    setup_row(&mut state);
+    let criteria = CellMatch {
+        ch: false,
+        foreground: true,
+        ..Default::default()
+    };
+
+    state.magic_wand_select(Position::new(4, 0), criteria, false, AddType::Default).unwrap();
+    assert_eq!(selected_row(&state, 0, 5), "....#");
+    state.magic_wand_select(Position::new(0, 0), CellMatch::default(), true, AddType::Add).unwrap();
+    assert_eq!(selected_row(&state, 0, 5), "##..#");
+    state
+        .magic_wand_select(Position::new(1, 0), CellMatch::default(), false, AddType::Subtract)
+        .unwrap();
+    assert_eq!(selected_row(&state, 0, 5), ".....");
+}
+
+#[test]
+fn test_select_color() {
+    let mut state = create_test_state(5, 2);
+    setup_row(&mut state);
+    set_char(&mut state, 2, 1, 'B', 7, 4);
+
+    state.select_color(AttributeColor::Palette(4), AddType::Default).unwrap();
+    assert_eq!(selected_row(&state, 0, 5), "....#");
+    assert_eq!(selected_row(&state, 1, 5), "..#..");
+}
+
+#[test]
+fn test_grow_shrink_border() {
+    let mut state = create_test_state(7, 7);
+    let mut sel = Selection::new(Position::new(2, 2));
+    sel.lead = Position::new(4, 4);
+    sel.shape = Shape::Rectangle;
+    state.set_selection(sel).unwrap();
+
+    state.grow_selection(1).unwrap();
+    assert!(state.selection().is_none());
+    assert_eq!(selected_row(&state, 0, 7), ".......");
+    assert_eq!(selected_row(&state, 1, 7), ".#####.");
+
+    state.border_selection(1).unwrap();
+    assert_eq!(selected_row(&state, 1, 7), ".#####.");
+    assert_eq!(selected_row(&state, 3, 7), ".#...#.");
+
+    state.undo().unwrap();
+    state.shrink_selection(2).unwrap();
+    assert_eq!(selected_row(&state, 2, 7), ".......");
+    assert_eq!(selected_row(&state, 3, 7), "...#...");
+}
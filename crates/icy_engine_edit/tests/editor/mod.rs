@@ -7,4 +7,12 @@
 
 mod area_operations_tests;
 mod edit_operations_tests;
+mod fill_operations_tests;
+mod journal_tests;
 mod layer_operations_tests;
+mod magic_wand_operations_tests;
+mod recolor_operations_tests;
+mod replace_operations_tests;
+mod symmetry_operations_tests;
+mod text_box_tests;
+mod transform_operations_tests;
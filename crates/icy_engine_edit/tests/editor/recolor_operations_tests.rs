@@ -0,0 +1,157 @@
+//! Tests for the color remap
+
+use icy_engine::{AttributeColor, AttributedChar, Color, Palette, Position, Rectangle, TextAttribute, TextPane};
+use icy_engine_edit::{ColorRemap, EditState, HueFamily, RemapPreset, ReplaceScope, UndoState};
+
+fn create_test_state(width: i32, height: i32) -> EditState {
+    let buffer = icy_engine::TextBuffer::create((width, height));
+    EditState::from_buffer(buffer)
+}
+
+fn set_char(state: &mut EditState, x: i32, y: i32, fg: u32, bg: u32) {
+    state.get_buffer_mut().layers[0].set_char(Position::new(x, y), AttributedChar::new('A', TextAttribute::new(fg, bg)));
+}
+
+fn colors_at(state: &EditState, x: i32, y: i32) -> (AttributeColor, AttributeColor) {
+    let ch = state.get_buffer().layers[0].char_at(Position::new(x, y));
+    (ch.attribute.foreground_color(), ch.attribute.background_color())
+}
+
+fn map(preset: &RemapPreset, color: u8) -> AttributeColor {
+    preset.map_color(&Palette::dos_default(), AttributeColor::Palette(color))
+}
+
+#[test]
+fn test_presets_on_dos_palette() {
+    let swap = RemapPreset::SwapHue(HueFamily::Red, HueFamily::Blue);
+    assert_eq!(map(&swap, 4), AttributeColor::Palette(1));
+    assert_eq!(map(&swap, 9), AttributeColor::Palette(12));
+    // green and grays are not touched
+    assert_eq!(map(&swap, 2), AttributeColor::Palette(2));
+    assert_eq!(map(&swap, 7), AttributeColor::Palette(7));
+
+    assert_eq!(map(&RemapPreset::Grayscale, 14), AttributeColor::Palette(15));
+    assert_eq!(map(&RemapPreset::InvertBrightness, 0), AttributeColor::Palette(15));
+    assert_eq!(map(&RemapPreset::InvertBrightness, 9), AttributeColor::Palette(1));
+}
+
+#[test]
+fn test_rgb_colors_stay_rgb() {
+    let color = RemapPreset::Grayscale.map_color(&Palette::dos_default(), AttributeColor::Rgb(255, 0, 0));
+    assert_eq!(color, AttributeColor::Rgb(76, 76, 76));
+    assert_eq!(
+        RemapPreset::Grayscale.map_color(&Palette::dos_default(), AttributeColor::Transparent),
+        AttributeColor::Transparent
+    );
+}
+
+#[test]
+fn test_remap_foreground_and_background_separately() {
+    let mut state = create_test_state(4, 1);
+    set_char(&mut state, 0, 0, 4, 4);
+    set_char(&mut state, 1, 0, 2, 4);
+    let remap = ColorRemap {
+        foreground: vec![(AttributeColor::Palette(4), AttributeColor::Palette(14))],
+        background: vec![(AttributeColor::Palette(4), AttributeColor::Palette(1))],
+        palette: None,
+    };
+
+    let initial_undo_len = state.undo_stack_len();
+    assert_eq!(state.remap_colors(&remap, ReplaceScope::Layer).unwrap(), 2);
+    assert_eq!(state.undo_stack_len(), initial_undo_len + 1);
+    assert_eq!(colors_at(&state, 0, 0), (AttributeColor::Palette(14), AttributeColor::Palette(1)));
+    assert_eq!(colors_at(&state, 1, 0), (AttributeColor::Palette(2), AttributeColor::Palette(1)));
+
+    state.undo().unwrap();
+    assert_eq!(colors_at(&state, 0, 0), (AttributeColor::Palette(4), AttributeColor::Palette(4)));
+}
+
+#[test]
+fn test_remap_in_selection() {
+    let mut state = create_test_state(4, 1);
+    for x in 0..4 {
+        set_char(&mut state, x, 0, 7, 0);
+    }
+    let (foreground, background) = state.used_colors(ReplaceScope::Layer);
+    assert_eq!(foreground, vec![AttributeColor::Palette(7)]);
+    assert_eq!(background, vec![AttributeColor::Palette(0)]);
+
+    let remap = ColorRemap::from_preset(&RemapPreset::InvertBrightness, &state.get_buffer().palette, &foreground, &background);
+    state.set_selection(Rectangle::from(1, 0, 2, 1)).unwrap();
+    assert_eq!(state.remap_colors(&remap, ReplaceScope::Selection).unwrap(), 2);
+    assert_eq!(colors_at(&state, 0, 0).1, AttributeColor::Palette(0));
+    assert_eq!(colors_at(&state, 1, 0).1, AttributeColor::Palette(15));
+    assert_eq!(colors_at(&state, 3, 0).1, AttributeColor::Palette(0));
+}
+
+#[test]
+fn test_nearest_in_palette_replaces_palette() {
+    let mut state = create_test_state(2, 1);
+    set_char(&mut state, 0, 0, 12, 0);
+    let target = Palette::from_slice(&[Color::new(0, 0, 0), Color::new(255, 255, 255), Color::new(200, 0, 0)]);
+    let preset = RemapPreset::NearestInPalette(target);
+    let (foreground, background) = state.used_colors(ReplaceScope::Selection);
+    assert!(foreground.is_empty() && background.is_empty());
+
+    let (foreground, background) = state.used_colors(ReplaceScope::Document);
+    let remap = ColorRemap::from_preset(&preset, &state.get_buffer().palette, &foreground, &background);
+    // the palette switch covers the whole document whatever the scope
+    state.remap_colors(&remap, ReplaceScope::Selection).unwrap();
+    assert_eq!(colors_at(&state, 0, 0), (AttributeColor::Palette(2), AttributeColor::Palette(0)));
+    assert_eq!(state.get_buffer().palette.len(), 3);
+
+    state.undo().unwrap();
+    assert_eq!(colors_at(&state, 0, 0), (AttributeColor::Palette(12), AttributeColor::Palette(0)));
+    assert_eq!(state.get_buffer().palette.len(), 16);
+}
+
+#[test]
+fn test_preview_leaves_document_untouched() {
+    let mut state = create_test_state(2, 1);
+    set_char(&mut state, 0, 0, 4, 0);
+    state.get_buffer_mut().layers[0].properties.is_locked = true;
+    let remap = ColorRemap {
+        foreground: vec![(AttributeColor::Palette(4), AttributeColor::Palette(1))],
+        ..Default::default()
+    };
+
+    let initial_undo_len = state.undo_stack_len();
+    state.preview_color_remap(&remap, ReplaceScope::Layer);
+    assert_eq!(colors_at(&state, 0, 0).0, AttributeColor::Palette(4));
+    let preview = state.get_buffer().preview_layer.as_ref().unwrap();
+    let (r, g, b) = Palette::dos_default().rgb(1);
+    assert_eq!(preview.char_at(Position::new(0, 0)).attribute.foreground_color(), AttributeColor::Rgb(r, g, b));
+
+    state.end_color_remap_preview();
+    assert!(state.get_buffer().preview_layer.is_none());
+    assert_eq!(state.undo_stack_len(), initial_undo_len);
+}
+
+#[test]
+fn test_remap_on_locked_layer_changes_nothing() {
+    let mut state = create_test_state(2, 1);
+    set_char(&mut state, 0, 0, 4, 0);
+    state.get_buffer_mut().layers[0].properties.is_locked = true;
+    let remap = ColorRemap {
+        foreground: vec![(AttributeColor::Palette(4), AttributeColor::Palette(1))],
+        palette: Some(Palette::dos_default()),
+        ..Default::default()
+    };
+
+    let initial_undo_len = state.undo_stack_len();
+    assert!(state.remap_colors(&remap, ReplaceScope::Layer).is_err());
+    assert_eq!(colors_at(&state, 0, 0).0, AttributeColor::Palette(4));
+    assert_eq!(state.undo_stack_len(), initial_undo_len);
+}
+
+#[test]
+fn test_nearest_in_large_palette_stays_in_range() {
+    let mut colors = vec![Color::new(0, 0, 0); 300];
+    colors[299] = Color::new(255, 0, 0);
+    colors[7] = Color::new(200, 0, 0);
+    let preset = RemapPreset::NearestInPalette(Palette::from_slice(&colors));
+    assert_eq!(
+        preset.map_color(&Palette::dos_default(), AttributeColor::Rgb(255, 0, 0)),
+        AttributeColor::Palette(7)
+    );
+}
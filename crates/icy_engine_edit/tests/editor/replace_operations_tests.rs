@@ -0,0 +1,178 @@
+//! Tests for find and replace
+
+use icy_engine::{AttributeColor, AttributedChar, Position, Rectangle, TextAttribute, TextPane};
+use icy_engine_edit::{CellPattern, EditState, ReplaceScope, UndoState};
+
+fn create_test_state(width: i32, height: i32) -> EditState {
+    let buffer = icy_engine::TextBuffer::create((width, height));
+    EditState::from_buffer(buffer)
+}
+
+fn set_char(state: &mut EditState, layer: usize, x: i32, y: i32, ch: char, fg: u32, bg: u32) {
+    state.get_buffer_mut().layers[layer].set_char(Position::new(x, y), AttributedChar::new(ch, TextAttribute::new(fg, bg)));
+}
+
+fn char_at(state: &EditState, layer: usize, x: i32, y: i32) -> AttributedChar {
+    state.get_buffer().layers[layer].char_at(Position::new(x, y))
+}
+
+/// Row 0: light blue `░` on black at x = 0..3, the one at x = 3 on red
+fn setup_row(state: &mut EditState) {
+    for x in 0..3 {
+        set_char(state, 0, x, 0, '░', 9, 0);
+    }
+    set_char(state, 0, 3, 0, '░', 9, 4);
+}
+
+fn light_blue_on_black() -> CellPattern {
+    CellPattern {
+        ch: Some('░'),
+        foreground: Some(AttributeColor::Palette(9)),
+        background: Some(AttributeColor::Palette(0)),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_find_cells_with_wildcards() {
+    let mut state = create_test_state(5, 2);
+    setup_row(&mut state);
+
+    assert_eq!(state.find_cells(&light_blue_on_black(), ReplaceScope::Layer).len(), 3);
+
+    let any_shade = CellPattern {
+        ch: Some('░'),
+        ..Default::default()
+    };
+    let found = state.find_cells(&any_shade, ReplaceScope::Layer);
+    assert_eq!(found.len(), 4);
+    assert!(found.contains(&(0, Position::new(3, 0))));
+
+    let blinking = CellPattern {
+        blink: Some(true),
+        ..any_shade
+    };
+    assert!(state.find_cells(&blinking, ReplaceScope::Layer).is_empty());
+}
+
+#[test]
+fn test_replace_cells_is_one_undo_step() {
+    let mut state = create_test_state(5, 2);
+    setup_row(&mut state);
+    let replacement = CellPattern {
+        ch: Some('▒'),
+        foreground: Some(AttributeColor::Palette(14)),
+        ..Default::default()
+    };
+
+    let initial_undo_len = state.undo_stack_len();
+    let count = state.replace_cells(&light_blue_on_black(), &replacement, ReplaceScope::Layer).unwrap();
+    assert_eq!(count, 3);
+    assert_eq!(state.undo_stack_len(), initial_undo_len + 1);
+    let ch = char_at(&state, 0, 1, 0);
+    assert_eq!(ch.ch, '▒');
+    assert_eq!(ch.attribute.foreground_color(), AttributeColor::Palette(14));
+    assert_eq!(ch.attribute.background_color(), AttributeColor::Palette(0));
+    assert_eq!(char_at(&state, 0, 3, 0).ch, '░');
+
+    state.undo().unwrap();
+    assert_eq!(char_at(&state, 0, 1, 0).ch, '░');
+    assert_eq!(char_at(&state, 0, 1, 0).attribute.foreground_color(), AttributeColor::Palette(9));
+}
+
+#[test]
+fn test_replace_without_matches_pushes_no_undo() {
+    let mut state = create_test_state(5, 2);
+    setup_row(&mut state);
+    let pattern = CellPattern {
+        ch: Some('X'),
+        ..Default::default()
+    };
+
+    let initial_undo_len = state.undo_stack_len();
+    let count = state.replace_cells(&pattern, &CellPattern::default(), ReplaceScope::Document).unwrap();
+    assert_eq!(count, 0);
+    assert_eq!(state.undo_stack_len(), initial_undo_len);
+}
+
+#[test]
+fn test_replace_in_selection() {
+    let mut state = create_test_state(5, 2);
+    setup_row(&mut state);
+    let replacement = CellPattern {
+        ch: Some('▓'),
+        ..Default::default()
+    };
+
+    // Nothing selected, nothing to replace
+    assert_eq!(state.default_replace_scope(), ReplaceScope::Layer);
+    assert_eq!(state.replace_cells(&light_blue_on_black(), &replacement, ReplaceScope::Selection).unwrap(), 0);
+
+    state.set_selection(Rectangle::from(1, 0, 2, 1)).unwrap();
+    assert_eq!(state.default_replace_scope(), ReplaceScope::Selection);
+    assert_eq!(state.replace_cells(&light_blue_on_black(), &replacement, ReplaceScope::Selection).unwrap(), 2);
+    assert_eq!(char_at(&state, 0, 0, 0).ch, '░');
+    assert_eq!(char_at(&state, 0, 1, 0).ch, '▓');
+    assert_eq!(char_at(&state, 0, 2, 0).ch, '▓');
+}
+
+#[test]
+fn test_replace_in_document_covers_all_layers() {
+    let mut state = create_test_state(5, 2);
+    setup_row(&mut state);
+    state.add_new_layer(0).unwrap();
+    set_char(&mut state, 1, 4, 1, '░', 9, 0);
+    let replacement = CellPattern {
+        blink: Some(true),
+        ..Default::default()
+    };
+
+    assert_eq!(state.find_cells(&light_blue_on_black(), ReplaceScope::Layer), vec![(1, Position::new(4, 1))]);
+
+    let initial_undo_len = state.undo_stack_len();
+    let count = state.replace_cells(&light_blue_on_black(), &replacement, ReplaceScope::Document).unwrap();
+    assert_eq!(count, 4);
+    assert_eq!(state.undo_stack_len(), initial_undo_len + 1);
+    assert!(char_at(&state, 0, 0, 0).attribute.is_blinking());
+    assert!(char_at(&state, 1, 4, 1).attribute.is_blinking());
+}
+
+#[test]
+fn test_document_scope_skips_hidden_and_locked_layers() {
+    let mut state = create_test_state(5, 2);
+    setup_row(&mut state);
+    state.add_new_layer(0).unwrap();
+    set_char(&mut state, 1, 4, 1, '░', 9, 0);
+    state.add_new_layer(1).unwrap();
+    set_char(&mut state, 2, 4, 0, '░', 9, 0);
+    state.get_buffer_mut().layers[1].properties.is_locked = true;
+    state.get_buffer_mut().layers[2].properties.is_visible = false;
+
+    let matches = state.find_cells(&light_blue_on_black(), ReplaceScope::Document);
+    assert!(matches.iter().all(|(layer, _)| *layer == 0));
+
+    let replacement = CellPattern {
+        blink: Some(true),
+        ..Default::default()
+    };
+    assert_eq!(state.replace_cells(&light_blue_on_black(), &replacement, ReplaceScope::Document).unwrap(), 3);
+    assert!(char_at(&state, 0, 0, 0).attribute.is_blinking());
+    assert!(!char_at(&state, 1, 4, 1).attribute.is_blinking());
+    assert!(!char_at(&state, 2, 4, 0).attribute.is_blinking());
+}
+
+#[test]
+fn test_replace_on_locked_layer_changes_nothing() {
+    let mut state = create_test_state(5, 2);
+    setup_row(&mut state);
+    state.get_buffer_mut().layers[0].properties.is_locked = true;
+    let replacement = CellPattern {
+        ch: Some('▓'),
+        ..Default::default()
+    };
+
+    let initial_undo_len = state.undo_stack_len();
+    assert!(state.replace_cells(&light_blue_on_black(), &replacement, ReplaceScope::Layer).is_err());
+    assert_eq!(state.undo_stack_len(), initial_undo_len);
+    assert_eq!(char_at(&state, 0, 0, 0).ch, '░');
+}
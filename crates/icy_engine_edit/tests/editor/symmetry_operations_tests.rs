@@ -0,0 +1,89 @@
+//! Tests for symmetry drawing
+
+use icy_engine::{AttributedChar, Position, TextAttribute};
+use icy_engine_edit::{brushes::MirrorMode, EditState, Symmetry, UndoState};
+
+fn create_test_state(width: i32, height: i32) -> EditState {
+    let buffer = icy_engine::TextBuffer::create((width, height));
+    EditState::from_buffer(buffer)
+}
+
+fn char_at(state: &EditState, x: i32, y: i32) -> char {
+    state.get_buffer().layers[0].char_at(Position::new(x, y)).ch
+}
+
+fn ch(c: char) -> AttributedChar {
+    AttributedChar::new(c, TextAttribute::default())
+}
+
+#[test]
+fn test_horizontal_symmetry_around_layer_center() {
+    let mut state = create_test_state(10, 4);
+    state.set_symmetry(Symmetry::new(MirrorMode::Horizontal));
+
+    state.set_char(Position::new(1, 2), ch(218 as char)).unwrap();
+    assert_eq!(char_at(&state, 1, 2), 218 as char);
+    // ┌ is mirrored to ┐
+    assert_eq!(char_at(&state, 8, 2), 191 as char);
+    assert_ne!(char_at(&state, 1, 1), 218 as char);
+}
+
+#[test]
+fn test_four_way_symmetry_flips_glyphs() {
+    let mut state = create_test_state(10, 4);
+    state.set_symmetry(Symmetry::new(MirrorMode::Both));
+
+    state.set_char(Position::new(0, 0), ch(218 as char)).unwrap();
+    assert_eq!(char_at(&state, 0, 0), 218 as char);
+    assert_eq!(char_at(&state, 9, 0), 191 as char);
+    assert_eq!(char_at(&state, 0, 3), 192 as char);
+    assert_eq!(char_at(&state, 9, 3), 217 as char);
+}
+
+#[test]
+fn test_symmetry_axis_through_cell() {
+    let mut state = create_test_state(20, 5);
+    state.set_symmetry(Symmetry::new(MirrorMode::Horizontal).with_axis_at_cell(Position::new(5, 0)));
+
+    state.set_char(Position::new(3, 1), ch('A')).unwrap();
+    assert_eq!(char_at(&state, 7, 1), 'A');
+
+    // A cell on the axis is its own mirror, mirrors outside the layer are dropped
+    let initial_undo_len = state.undo_stack_len();
+    state.set_char(Position::new(5, 1), ch('B')).unwrap();
+    state.set_char(Position::new(15, 1), ch('C')).unwrap();
+    assert_eq!(state.undo_stack_len(), initial_undo_len + 2);
+    assert_eq!(char_at(&state, 5, 1), 'B');
+    assert_eq!(char_at(&state, 15, 1), 'C');
+}
+
+#[test]
+fn test_symmetry_stroke_is_one_undo_step() {
+    let mut state = create_test_state(10, 4);
+    state.set_symmetry(Symmetry::new(MirrorMode::Vertical));
+
+    let initial_undo_len = state.undo_stack_len();
+    {
+        let _undo = state.begin_atomic_undo("Pencil stroke".to_string());
+        for x in 0..3 {
+            state.set_char_in_atomic(Position::new(x, 0), ch('#')).unwrap();
+        }
+    }
+    assert_eq!(state.undo_stack_len(), initial_undo_len + 1);
+    assert_eq!(char_at(&state, 2, 3), '#');
+
+    state.undo().unwrap();
+    assert_ne!(char_at(&state, 0, 0), '#');
+    assert_ne!(char_at(&state, 2, 3), '#');
+}
+
+#[test]
+fn test_mirror_mode_maps_to_symmetry() {
+    let mut state = create_test_state(10, 4);
+    state.set_mirror_mode(true);
+    assert_eq!(state.symmetry().mode, MirrorMode::Horizontal);
+    assert!(state.get_mirror_mode());
+
+    state.set_mirror_mode(false);
+    assert!(!state.symmetry().is_enabled());
+}
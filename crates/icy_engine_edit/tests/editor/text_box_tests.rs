@@ -0,0 +1,149 @@
+//! Tests for the text box layout and preview / commit
+
+use icy_engine::{Position, Rectangle, TextAttribute, TextPane};
+use icy_engine_edit::{layout_text, EditState, TextAlignment, TextBox, UndoState};
+
+fn create_test_state(width: i32, height: i32) -> EditState {
+    let buffer = icy_engine::TextBuffer::create((width, height));
+    EditState::from_buffer(buffer)
+}
+
+fn row_text(state: &EditState, y: i32) -> String {
+    let layer = &state.get_buffer().layers[0];
+    (0..layer.width()).map(|x| layer.char_at(Position::new(x, y)).ch).collect()
+}
+
+#[test]
+fn test_layout_wraps_words() {
+    let lines = layout_text("the quick brown fox", 10, TextAlignment::Left);
+    assert_eq!(lines, vec![(0, "the quick".to_string()), (0, "brown fox".to_string())]);
+}
+
+#[test]
+fn test_layout_splits_long_words_and_paragraphs() {
+    let lines = layout_text("abcdefgh\n\nab", 5, TextAlignment::Left);
+    assert_eq!(
+        lines,
+        vec![(0, "abcde".to_string()), (0, "fgh".to_string()), (0, String::new()), (0, "ab".to_string())]
+    );
+}
+
+#[test]
+fn test_layout_alignment() {
+    assert_eq!(layout_text("abc", 8, TextAlignment::Right), vec![(5, "abc".to_string())]);
+    // same rounding as EditState::center
+    assert_eq!(layout_text("abc", 8, TextAlignment::Center), vec![(2, "abc".to_string())]);
+}
+
+#[test]
+fn test_layout_matches_justify_commands() {
+    for (alignment, width) in [(TextAlignment::Center, 9), (TextAlignment::Center, 10), (TextAlignment::Right, 9)] {
+        let mut state = create_test_state(width, 1);
+        for (x, ch) in "abc".chars().enumerate() {
+            state.set_char((x as i32, 0), ch.into()).unwrap();
+        }
+        match alignment {
+            TextAlignment::Center => state.center().unwrap(),
+            _ => state.justify_right().unwrap(),
+        }
+        let (offset, _) = layout_text("abc", width as usize, alignment)[0].clone();
+        assert_eq!(row_text(&state, 0).find('a'), Some(offset), "{alignment:?} at width {width}");
+    }
+}
+
+#[test]
+fn test_layout_justify_keeps_last_line() {
+    let lines = layout_text("a b c dd", 6, TextAlignment::Justify);
+    assert_eq!(lines, vec![(0, "a  b c".to_string()), (0, "dd".to_string())]);
+}
+
+/// Text of a row of the preview layer, in document coordinates
+fn preview_row_text(state: &EditState, y: i32) -> String {
+    let preview = state.get_buffer().preview_layer.as_ref().unwrap();
+    (0..state.get_buffer().width())
+        .map(|x| {
+            let ch = preview.char_at(Position::new(x, y) - preview.offset());
+            if ch.is_visible() {
+                ch.ch
+            } else {
+                ' '
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_preview_reflows_and_restores() {
+    let mut state = create_test_state(10, 3);
+    let mut text_box = TextBox::new(Rectangle::from(0, 0, 10, 3), TextAttribute::default());
+    text_box.text = "hello world".to_string();
+    state.preview_text_box(&text_box).unwrap();
+    assert_eq!(preview_row_text(&state, 0).trim_end(), "hello");
+    assert_eq!(preview_row_text(&state, 1).trim_end(), "world");
+
+    text_box.rect = Rectangle::from(0, 0, 8, 3);
+    text_box.alignment = TextAlignment::Right;
+    state.preview_text_box(&text_box).unwrap();
+    assert_eq!(preview_row_text(&state, 0), "   hello  ");
+
+    state.end_text_box_preview();
+    assert!(!state.is_text_box_previewed());
+    assert_eq!(row_text(&state, 0).trim_end(), "");
+    assert_eq!(row_text(&state, 1).trim_end(), "");
+}
+
+#[test]
+fn test_preview_leaves_document_untouched() {
+    let mut state = create_test_state(10, 3);
+    let mut text_box = TextBox::new(Rectangle::from(0, 0, 10, 3), TextAttribute::default());
+    text_box.text = "abc".to_string();
+    let initial_undo_len = state.undo_stack_len();
+    state.preview_text_box(&text_box).unwrap();
+    assert_eq!(state.undo_stack_len(), initial_undo_len);
+    assert_eq!(row_text(&state, 0).trim_end(), "");
+}
+
+#[test]
+fn test_preview_follows_layer_offset() {
+    let mut state = create_test_state(10, 3);
+    state.get_buffer_mut().layers[0].set_offset((2, 1));
+    let mut text_box = TextBox::new(Rectangle::from(1, 0, 5, 2), TextAttribute::default());
+    text_box.text = "ab".to_string();
+    state.preview_text_box(&text_box).unwrap();
+    assert_eq!(state.get_buffer().preview_layer.as_ref().unwrap().offset(), Position::new(3, 1));
+}
+
+#[test]
+fn test_commit_is_one_undo_step() {
+    let mut state = create_test_state(10, 3);
+    let mut text_box = TextBox::new(Rectangle::from(1, 1, 5, 2), TextAttribute::default());
+    text_box.text = "ab cd ef".to_string();
+    state.preview_text_box(&text_box).unwrap();
+
+    let initial_undo_len = state.undo_stack_len();
+    assert!(state.commit_text_box(&text_box).unwrap());
+    assert!(!state.is_text_box_previewed());
+    assert_eq!(state.undo_stack_len(), initial_undo_len + 1);
+    assert_eq!(row_text(&state, 1).trim_end(), " ab cd");
+    assert_eq!(row_text(&state, 2).trim_end(), " ef");
+
+    state.undo().unwrap();
+    assert_eq!(row_text(&state, 1).trim_end(), "");
+    assert_eq!(row_text(&state, 2).trim_end(), "");
+
+    // Nothing to write, nothing committed
+    text_box.text.clear();
+    assert!(!state.commit_text_box(&text_box).unwrap());
+    assert_eq!(state.undo_stack_len(), initial_undo_len);
+}
+
+#[test]
+fn test_text_is_clipped_to_box_height() {
+    let mut state = create_test_state(10, 3);
+    let mut text_box = TextBox::new(Rectangle::from(0, 0, 3, 1), TextAttribute::default());
+    text_box.text = "abc def".to_string();
+    assert_eq!(text_box.needed_height(), 2);
+    state.commit_text_box(&text_box).unwrap();
+    assert_eq!(row_text(&state, 0).trim_end(), "abc");
+    assert_eq!(row_text(&state, 1).trim_end(), "");
+}
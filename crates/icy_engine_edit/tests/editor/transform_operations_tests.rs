@@ -0,0 +1,139 @@
+//! Tests for scale and rotate operations
+
+use icy_engine::{AttributedChar, Position, Selection, Shape, Size, TextAttribute, TextPane};
+use icy_engine_edit::{EditState, ScaleMode, UndoState};
+
+fn create_test_state(width: i32, height: i32) -> EditState {
+    let buffer = icy_engine::TextBuffer::create((width, height));
+    EditState::from_buffer(buffer)
+}
+
+fn set_char(state: &mut EditState, x: i32, y: i32, ch: char) {
+    if let Some(layer) = state.get_cur_layer_mut() {
+        layer.set_char(Position::new(x, y), AttributedChar::new(ch, TextAttribute::default()));
+    }
+}
+
+fn char_at(state: &EditState, x: i32, y: i32) -> char {
+    state.get_buffer().layers[0].char_at(Position::new(x, y)).ch
+}
+
+fn rect_selection(x: i32, y: i32, w: i32, h: i32) -> Selection {
+    let mut sel = Selection::new(Position::new(x, y));
+    sel.lead = Position::new(x + w - 1, y + h - 1);
+    sel.shape = Shape::Rectangle;
+    sel
+}
+
+#[test]
+fn test_scale_selection_nearest() {
+    let mut state = create_test_state(20, 10);
+    for (i, ch) in "ABCDEF".chars().enumerate() {
+        set_char(&mut state, 2 + i as i32 % 3, 1 + i as i32 / 3, ch);
+    }
+    state.set_selection(rect_selection(2, 1, 3, 2)).unwrap();
+
+    let initial_undo_len = state.undo_stack_len();
+    state.scale(2.0, 2.0, ScaleMode::Nearest).unwrap();
+    assert_eq!(state.undo_stack_len(), initial_undo_len + 1);
+
+    let rows: Vec<String> = (1..5).map(|y| (2..8).map(|x| char_at(&state, x, y)).collect()).collect();
+    assert_eq!(rows, vec!["AABBCC", "AABBCC", "DDEEFF", "DDEEFF"]);
+    assert_eq!(state.selection().unwrap().as_rectangle().size, Size::new(6, 4));
+
+    state.undo().unwrap();
+    assert_eq!(char_at(&state, 2, 1), 'A');
+    assert_eq!(char_at(&state, 3, 1), 'B');
+    assert_ne!(char_at(&state, 5, 1), 'C');
+    assert_ne!(char_at(&state, 2, 3), 'D');
+}
+
+#[test]
+fn test_scale_down_clears_old_area() {
+    let mut state = create_test_state(10, 10);
+    for y in 0..4 {
+        for x in 0..4 {
+            set_char(&mut state, x, y, 'X');
+        }
+    }
+    state.set_selection(rect_selection(0, 0, 4, 4)).unwrap();
+    state.scale_to_size((2, 2), ScaleMode::Nearest).unwrap();
+
+    assert_eq!(char_at(&state, 1, 1), 'X');
+    assert_ne!(char_at(&state, 2, 0), 'X');
+    assert_ne!(char_at(&state, 3, 3), 'X');
+}
+
+#[test]
+fn test_scale_half_block_resamples_sub_cells() {
+    let mut state = create_test_state(1, 1);
+    if let Some(layer) = state.get_cur_layer_mut() {
+        layer.set_char(Position::new(0, 0), AttributedChar::new(223 as char, TextAttribute::new(4, 0)));
+    }
+
+    state.scale_to_size((1, 2), ScaleMode::HalfBlock).unwrap();
+
+    let layer = &state.get_buffer().layers[0];
+    assert_eq!(layer.size(), Size::new(1, 2));
+    // The upper half becomes a full red cell, the black lower half an empty one
+    assert_eq!(layer.char_at(Position::new(0, 0)).ch, 219 as char);
+    assert_eq!(layer.char_at(Position::new(0, 0)).attribute.foreground(), 4);
+    assert_eq!(layer.char_at(Position::new(0, 1)).ch, ' ');
+
+    state.undo().unwrap();
+    let layer = &state.get_buffer().layers[0];
+    assert_eq!(layer.size(), Size::new(1, 1));
+    assert_eq!(layer.char_at(Position::new(0, 0)).ch, 223 as char);
+}
+
+#[test]
+fn test_rotate_selection_cw_and_ccw() {
+    let mut state = create_test_state(10, 10);
+    set_char(&mut state, 0, 0, 'A');
+    set_char(&mut state, 1, 0, 196 as char);
+    set_char(&mut state, 2, 0, 'C');
+    state.set_selection(rect_selection(0, 0, 3, 1)).unwrap();
+
+    state.rotate_cw().unwrap();
+    let column: String = (0..3).map(|y| char_at(&state, 0, y)).collect();
+    assert_eq!(column, format!("A{}C", 179 as char));
+    assert_eq!(state.selection().unwrap().as_rectangle().size, Size::new(1, 3));
+
+    state.undo().unwrap();
+    state.set_selection(rect_selection(0, 0, 3, 1)).unwrap();
+    let initial_undo_len = state.undo_stack_len();
+    state.rotate_ccw().unwrap();
+    assert_eq!(state.undo_stack_len(), initial_undo_len + 1);
+    let column: String = (0..3).map(|y| char_at(&state, 0, y)).collect();
+    assert_eq!(column, format!("C{}A", 179 as char));
+}
+
+#[test]
+fn test_rotate_layer_180() {
+    let mut state = create_test_state(3, 2);
+    set_char(&mut state, 0, 0, 'A');
+    set_char(&mut state, 1, 0, 220 as char);
+
+    state.rotate_180().unwrap();
+    assert_eq!(char_at(&state, 2, 1), 'A');
+    assert_eq!(char_at(&state, 1, 1), 223 as char);
+    assert_eq!(state.get_buffer().layers[0].size(), Size::new(3, 2));
+
+    state.undo().unwrap();
+    assert_eq!(char_at(&state, 0, 0), 'A');
+    assert_eq!(char_at(&state, 1, 0), 220 as char);
+}
+
+#[test]
+fn test_rotate_layer_ccw_resizes_layer() {
+    let mut state = create_test_state(4, 2);
+    set_char(&mut state, 3, 0, 'X');
+
+    state.rotate_ccw().unwrap();
+    assert_eq!(state.get_buffer().layers[0].size(), Size::new(2, 4));
+    assert_eq!(char_at(&state, 0, 0), 'X');
+
+    state.undo().unwrap();
+    assert_eq!(state.get_buffer().layers[0].size(), Size::new(4, 2));
+    assert_eq!(char_at(&state, 3, 0), 'X');
+}
@@ -1,5 +1,6 @@
 pub mod bitfont;
 pub mod brushes;
+pub mod charset;
 pub mod editor;
 
 pub mod collaboration;
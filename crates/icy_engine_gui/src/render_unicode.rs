@@ -109,6 +109,25 @@ pub fn render_unicode_to_rgba(buf: &dyn Screen, opts: &RenderUnicodeOptions) ->
                 continue;
             }
 
+            // Bitmap fonts with Unicode glyphs render pixel exact, the TTF covers all others
+            // and characters the bitmap font has no glyph for
+            let bitmap_glyph = buf
+                .font(ch_attr.font_page() as usize)
+                .filter(|font| font.has_unicode_glyphs())
+                .and_then(|font| font.find_unicode_glyph(ch));
+            if let Some(glyph) = bitmap_glyph {
+                for cy in 0..(glyph.height as usize).min(cell_h) {
+                    let row_byte = glyph.data[cy];
+                    let off = (cell_px_y + cy) * (px_w * 4) + cell_px_x * 4;
+                    for cx in 0..(glyph.width as usize).min(cell_w) {
+                        if row_byte & (0x80 >> cx) != 0 {
+                            rgba[off + cx * 4..off + cx * 4 + 3].copy_from_slice(&[fr, fg, fb]);
+                        }
+                    }
+                }
+                continue;
+            }
+
             // let units_em = glyph_cache.units_per_em() as f32;
             // let scale = px_size / units_em;
             // let ascender_px = glyph_cache.ascender() as f32 * scale;
@@ -0,0 +1,241 @@
+use icy_parser_core::{AnsiParser, AvatarParser, CommandParser, CtrlAParser, PcBoardParser, RenegadeParser};
+
+const ESC: u8 = 0x1B;
+const CTRL_A: u8 = 0x01;
+const AVATAR_COMMAND: u8 = 0x16;
+
+/// FidoNet/QWKE kludge lines start with `^A` as well; they must not be mistaken for
+/// Synchronet Ctrl-A color codes.
+const KLUDGES: &[&[u8]] = &[
+    b"MSGID", b"REPLY", b"PID", b"TID", b"INTL", b"FMPT", b"TOPT", b"CHRS", b"CHARSET", b"TZUTC", b"FLAGS", b"Via", b"PATH", b"RFC-",
+];
+
+/// Color/control dialect a message body is written in.
+///
+/// Every dialect parser wraps the ANSI parser, so embedded escape sequences render in all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyMarkup {
+    #[default]
+    Plain,
+    Ansi,
+    Avatar,
+    /// PCBoard `@X` color codes.
+    PcBoard,
+    /// Synchronet `^A` codes.
+    CtrlA,
+    /// Renegade/Mystic `|nn` pipe codes.
+    Renegade,
+}
+
+impl BodyMarkup {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Plain => "Text",
+            Self::Ansi => "ANSI",
+            Self::Avatar => "Avatar",
+            Self::PcBoard => "PCBoard",
+            Self::CtrlA => "Ctrl-A",
+            Self::Renegade => "Pipe codes",
+        }
+    }
+
+    pub fn create_parser(self) -> Box<dyn CommandParser> {
+        match self {
+            Self::Plain | Self::Ansi => Box::new(AnsiParser::new()),
+            Self::Avatar => Box::new(AvatarParser::new()),
+            Self::PcBoard => Box::new(PcBoardParser::new()),
+            Self::CtrlA => Box::new(CtrlAParser::new()),
+            Self::Renegade => Box::new(RenegadeParser::new()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyEncoding {
+    #[default]
+    Cp437,
+    Utf8,
+}
+
+impl BodyEncoding {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Cp437 => "CP437",
+            Self::Utf8 => "UTF-8",
+        }
+    }
+}
+
+/// A message body prepared for the terminal view.
+pub struct MessageBody {
+    /// Body without kludge lines and BOM.
+    pub data: Vec<u8>,
+    pub markup: BodyMarkup,
+    pub encoding: BodyEncoding,
+}
+
+impl MessageBody {
+    pub fn new(text: &[u8]) -> Self {
+        let encoding = detect_encoding(text);
+        let text = text.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(text);
+        let data = strip_kludges(text);
+        let markup = detect_markup(&data);
+        Self { data, markup, encoding }
+    }
+}
+
+/// Picks the dialect with the strongest evidence; dialect codes win over plain ANSI because
+/// their parsers handle escape sequences too.
+#[must_use]
+pub fn detect_markup(data: &[u8]) -> BodyMarkup {
+    let mut ansi = 0;
+    let mut avatar = 0;
+    let mut pcboard = 0;
+    let mut ctrla = 0;
+    let mut pipe = 0;
+
+    for (i, byte) in data.iter().enumerate() {
+        let next = data.get(i + 1).copied().unwrap_or(0);
+        match *byte {
+            ESC if next == b'[' => ansi += 1,
+            AVATAR_COMMAND if (1..=0x19).contains(&next) => avatar += 1,
+            CTRL_A if is_ctrla_code(next) => ctrla += 1,
+            b'@' if matches!(next, b'X' | b'x') && data.get(i + 2..i + 4).is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit)) => {
+                pcboard += 1;
+            }
+            b'|' if (b'0'..=b'2').contains(&next) && data.get(i + 2).is_some_and(u8::is_ascii_digit) => {
+                let code = (next - b'0') * 10 + (data[i + 2] - b'0');
+                if code < 24 {
+                    pipe += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    [
+        (avatar, BodyMarkup::Avatar),
+        (ctrla, BodyMarkup::CtrlA),
+        (pcboard, BodyMarkup::PcBoard),
+        (pipe, BodyMarkup::Renegade),
+    ]
+    .into_iter()
+    .filter(|(count, _)| *count > 0)
+    .max_by_key(|(count, _)| *count)
+    .map(|(_, markup)| markup)
+    .unwrap_or(if ansi > 0 { BodyMarkup::Ansi } else { BodyMarkup::Plain })
+}
+
+/// A `CHRS`/`CHARSET` kludge wins; otherwise non-ASCII text that decodes as UTF-8 is taken as
+/// UTF-8, since CP437 art practically never forms valid multi-byte sequences.
+#[must_use]
+pub fn detect_encoding(data: &[u8]) -> BodyEncoding {
+    if data.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return BodyEncoding::Utf8;
+    }
+
+    for line in data.split(|b| *b == b'\n') {
+        let Some(rest) = line.strip_prefix(&[CTRL_A]) else {
+            continue;
+        };
+        let Some(value) = rest.strip_prefix(b"CHRS:").or_else(|| rest.strip_prefix(b"CHARSET:")) else {
+            continue;
+        };
+        let value = String::from_utf8_lossy(value).trim().to_ascii_uppercase();
+        if value.starts_with("UTF-8") || value.starts_with("UTF8") {
+            return BodyEncoding::Utf8;
+        }
+        return BodyEncoding::Cp437;
+    }
+
+    if !data.is_ascii() && std::str::from_utf8(data).is_ok() {
+        BodyEncoding::Utf8
+    } else {
+        BodyEncoding::Cp437
+    }
+}
+
+/// Removes `^AKLUDGE` lines, keeping everything else byte for byte.
+fn strip_kludges(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    for line in data.split_inclusive(|b| *b == b'\n') {
+        if !is_kludge(line) {
+            out.extend_from_slice(line);
+        }
+    }
+    out
+}
+
+fn is_kludge(line: &[u8]) -> bool {
+    let Some(rest) = line.strip_prefix(&[CTRL_A]) else {
+        return false;
+    };
+    KLUDGES.iter().any(|kludge| {
+        rest.strip_prefix(*kludge)
+            .is_some_and(|tail| kludge.ends_with(b"-") || matches!(tail.first(), Some(b':' | b' ') | None))
+    })
+}
+
+fn is_ctrla_code(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'<' | b'>' | b'\'' | b']' | b'|')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_text_stays_plain() {
+        assert_eq!(detect_markup(b"Hello world\nmail me at sysop@example.com | thanks"), BodyMarkup::Plain);
+    }
+
+    #[test]
+    fn escape_sequences_are_ansi() {
+        assert_eq!(detect_markup(b"\x1b[1;33mHello\x1b[0m"), BodyMarkup::Ansi);
+    }
+
+    #[test]
+    fn dialect_codes_are_detected() {
+        assert_eq!(detect_markup(b"@X0FWhite @X1EYellow on blue"), BodyMarkup::PcBoard);
+        assert_eq!(detect_markup(b"\x01hHigh \x01rRed"), BodyMarkup::CtrlA);
+        assert_eq!(detect_markup(b"|15White |04Red |17on blue"), BodyMarkup::Renegade);
+        assert_eq!(detect_markup(b"\x16\x01\x1fBlue"), BodyMarkup::Avatar);
+    }
+
+    #[test]
+    fn dialect_wins_over_embedded_ansi() {
+        assert_eq!(detect_markup(b"\x1b[2J|14Signature |07"), BodyMarkup::Renegade);
+    }
+
+    #[test]
+    fn pipe_code_out_of_range_is_ignored() {
+        assert_eq!(detect_markup(b"score |99 and |3x"), BodyMarkup::Plain);
+    }
+
+    #[test]
+    fn kludges_are_stripped_and_not_ctrla() {
+        let body = MessageBody::new(b"\x01MSGID: 1:2/3 abcd\n\x01PID: Mystic\nHello\n");
+        assert_eq!(body.data, b"Hello\n");
+        assert_eq!(body.markup, BodyMarkup::Plain);
+    }
+
+    #[test]
+    fn encoding_follows_chrs_kludge() {
+        assert_eq!(detect_encoding(b"\x01CHRS: UTF-8 4\nplain"), BodyEncoding::Utf8);
+        assert_eq!(detect_encoding("\x01CHRS: CP437 2\n\u{2591}".as_bytes()), BodyEncoding::Cp437);
+    }
+
+    #[test]
+    fn encoding_falls_back_to_utf8_validation() {
+        assert_eq!(detect_encoding("caf\u{e9} \u{2588}".as_bytes()), BodyEncoding::Utf8);
+        assert_eq!(detect_encoding(b"\xB0\xB1\xB2\xDB"), BodyEncoding::Cp437);
+        assert_eq!(detect_encoding(b"ascii only"), BodyEncoding::Cp437);
+    }
+
+    #[test]
+    fn bom_is_utf8_and_removed() {
+        let body = MessageBody::new(b"\xEF\xBB\xBFhi");
+        assert_eq!(body.encoding, BodyEncoding::Utf8);
+        assert_eq!(body.data, b"hi");
+    }
+}
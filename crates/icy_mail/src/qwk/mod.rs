@@ -9,6 +9,8 @@ use std::sync::{Arc, Mutex};
 
 use crate::Res;
 
+pub mod markup;
+
 #[cfg(test)]
 pub mod tests;
 
@@ -307,8 +307,21 @@ impl MainWindow {
             ]
         };
 
+        // Detected format plus the raw toggle, so a misdetected dialect can still be read.
+        let (markup, encoding) = self.body_format;
+        let raw = self.show_raw_body;
+        let format = row![
+            text(format!("{} \u{00B7} {}", markup.label(), encoding.label())).size(TEXT_SIZE),
+            button(text("Raw").size(TEXT_SIZE))
+                .on_press(Message::ToggleRawBody)
+                .padding([2, 8])
+                .style(move |theme: &icy_ui::Theme, status| segmented_style(theme, status, raw)),
+        ]
+        .spacing(6)
+        .align_y(Alignment::Center);
+
         let header = column![
-            field("Subject: ", &info.subject),
+            row![field("Subject: ", &info.subject), Space::new().width(Length::Fill), format].align_y(Alignment::Center),
             row![
                 field("From: ", &info.from),
                 Space::new().width(16),
@@ -1,9 +1,10 @@
 use std::sync::Arc;
 
+use crate::qwk::markup::{BodyEncoding, BodyMarkup, MessageBody};
 use crate::qwk::QwkPackage;
 use crate::ui::threading::{self, Row};
 use crate::ui::{ConferenceColumn, Message, MessageColumn, NavigateDirection, Pane, SortDirection, ViewMode};
-use icy_engine::{EditableScreen, Screen, Size, TextScreen};
+use icy_engine::{BufferType, EditableScreen, Screen, Size, TextScreen};
 use icy_engine_gui::{MonitorSettings, Terminal};
 use icy_ui::widget::{button, column, container, operation, progress_bar, text, Space};
 use icy_ui::{window, Alignment, Element, Length, Task, Theme};
@@ -62,6 +63,11 @@ pub struct MainWindow {
 
     pub terminal: Terminal,
     pub monitor_settings: Arc<MonitorSettings>,
+
+    /// Dialect and encoding detected for the message on screen.
+    pub body_format: (BodyMarkup, BodyEncoding),
+    /// Shows the undecoded body, control codes as glyphs, instead of the rendered markup.
+    pub show_raw_body: bool,
 }
 
 impl MainWindow {
@@ -89,6 +95,8 @@ impl MainWindow {
             message_view: (0.0, 0.0),
             terminal: empty_terminal(),
             monitor_settings: Arc::new(MonitorSettings::default()),
+            body_format: (BodyMarkup::Plain, BodyEncoding::Cp437),
+            show_raw_body: false,
         }
     }
 
@@ -254,6 +262,12 @@ impl MainWindow {
                 Task::none()
             }
 
+            Message::ToggleRawBody => {
+                self.show_raw_body = !self.show_raw_body;
+                self.load_selected_message();
+                self.terminal.scroll_to_content(Some(0.0), Some(0.0))
+            }
+
             Message::Refresh => {
                 self.rebuild_conferences();
                 self.rebuild_messages();
@@ -443,14 +457,17 @@ impl MainWindow {
             self.terminal = empty_terminal();
             return;
         };
-        let text = message.text.clone();
-        self.load_message_to_screen(&text);
+        let body = MessageBody::new(&message.text);
+        self.body_format = (body.markup, body.encoding);
+        self.load_message_to_screen(&body);
     }
 
-    /// Renders the message body through the ANSI parser into a terminal screen.
-    fn load_message_to_screen(&mut self, data: &[u8]) {
+    /// Renders the message body through the parser for its detected dialect into a terminal
+    /// screen, or through the plain ASCII parser when the raw view is on.
+    fn load_message_to_screen(&mut self, body: &MessageBody) {
         use icy_engine::load_with_parser;
-        use icy_parser_core::AnsiParser;
+        use icy_parser_core::CommandParser;
+        let data = &body.data;
         let _timer = crate::perf::Timer::with("load_message_to_screen", format!("{} bytes", data.len()));
 
         // QWK stores bare LF line ends; the ANSI parser needs the CR to return to column 0.
@@ -465,9 +482,16 @@ impl MainWindow {
         let height = normalized.iter().filter(|b| **b == b'\n').count().max(24) + 1;
         let mut text_screen = TextScreen::new(Size::new(80, height as i32));
         text_screen.terminal_state_mut().is_terminal_buffer = false;
+        if body.encoding == BodyEncoding::Utf8 {
+            text_screen.buffer.buffer_type = BufferType::Unicode;
+        }
 
-        let mut parser = AnsiParser::new();
-        let _ = load_with_parser(&mut text_screen, &mut parser, &normalized, true, -1);
+        let mut parser: Box<dyn CommandParser> = if self.show_raw_body {
+            Box::new(icy_parser_core::AsciiParser::new())
+        } else {
+            body.markup.create_parser()
+        };
+        let _ = load_with_parser(&mut text_screen, parser.as_mut(), &normalized, true, -1);
 
         let screen: Box<dyn Screen> = Box::new(text_screen);
         self.terminal = Terminal::new(Arc::new(Mutex::new(screen)));
@@ -548,6 +572,12 @@ impl MainWindow {
             return None;
         }
 
+        // Ctrl+U toggles between rendered markup and the raw body, like "view source".
+        // Checked after `captured` so the filter input keeps its own Ctrl+U.
+        if modifiers.command() && matches!(key, Key::Character(c) if c.as_str() == "u") {
+            return Some(Message::ToggleRawBody);
+        }
+
         match key {
             Key::Named(Named::ArrowUp) => Some(Message::Navigate(NavigateDirection::Up)),
             Key::Named(Named::ArrowDown) => Some(Message::Navigate(NavigateDirection::Down)),
@@ -795,4 +825,18 @@ mod tests {
         let _ = window.update(Message::FilterChanged("no match at all".to_string()));
         let _ = window.view();
     }
+
+    #[test]
+    fn raw_toggle_keeps_the_selected_message() {
+        let (_dir, mut window) = loaded();
+        let selected = window.selected_message;
+
+        let _ = window.update(Message::ToggleRawBody);
+        assert!(window.show_raw_body);
+        assert_eq!(window.selected_message, selected);
+        let _ = window.view();
+
+        let _ = window.update(Message::ToggleRawBody);
+        assert!(!window.show_raw_body);
+    }
 }
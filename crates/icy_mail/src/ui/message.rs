@@ -83,6 +83,8 @@ pub enum Message {
     SetViewMode(ViewMode),
     NewMessage,
     Refresh,
+    /// Flips the reading pane between rendered markup and the raw message body.
+    ToggleRawBody,
 
     FilterChanged(String),
     ClearFilter,
@@ -121,6 +123,7 @@ impl std::fmt::Debug for Message {
             Message::SetViewMode(mode) => write!(f, "SetViewMode({mode:?})"),
             Message::NewMessage => write!(f, "NewMessage"),
             Message::Refresh => write!(f, "Refresh"),
+            Message::ToggleRawBody => write!(f, "ToggleRawBody"),
             Message::FilterChanged(s) => write!(f, "FilterChanged({s})"),
             Message::ClearFilter => write!(f, "ClearFilter"),
             Message::SortMessagesBy(c) => write!(f, "SortMessagesBy({c:?})"),
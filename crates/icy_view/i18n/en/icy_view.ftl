@@ -8,6 +8,8 @@ arg-auto-help = Enable auto-scrolling animation
 arg-bps-help = Baud rate emulation (e.g., 9600, 19200, 38400)
 arg-portable-help = Run in portable mode (config saved next to executable)
 arg-config-dir-help = Custom configuration directory path
+arg-warm-thumbnails-help = Render thumbnails of the given folders into the disk cache and exit
+arg-mirror-16colors-help = Download 16colo.rs years or packs (e.g. 1996 or 1996/acid-0596) into the local mirror and exit
 heading-title=Title
 heading-author=Author
 heading-group=Group
@@ -160,6 +162,9 @@ settings-paths-category=Paths
 settings-paths-header=Application Paths
 settings-paths-user-header=User Paths
 settings-paths-export-path=Export path:
+settings-paths-index-folders=Search index:
+settings-paths-index-folders-placeholder=Folders to index, separated by ';'
+settings-paths-sixteencolors-mirror=16colo.rs mirror:
 settings-paths-config-dir=Config directory:
 settings-paths-config-file=Config file:
 settings-paths-log-file=Log file:
@@ -170,6 +175,22 @@ settings-commands-section = External Programs
 settings-commands-placeholder = Command (e.g. icy_draw %F)
 settings-commands-description = Use %F for file name
 
+duplicates-title = Find Duplicates
+duplicates-folders = Folders:
+duplicates-folders-placeholder = Folders to scan, separated by ';'
+duplicates-similarity = Similarity:
+duplicates-scan = Scan
+duplicates-stop = Stop
+duplicates-scanning = Scanning… { $files } files
+duplicates-none = No duplicates found.
+duplicates-found = { $groups } duplicate groups
+duplicates-group-exact = { $name } ({ $count } identical)
+duplicates-group-similar = { $name } ({ $count } similar, ≥{ $percent }%)
+duplicates-keep-only = Keep only
+duplicates-delete-marked = Move { $count } marked to trash
+duplicates-delete-title = Move Duplicates to Trash
+duplicates-delete-message = Move { $count } marked files to the trash? At least one file of every group is kept.
+
 export-no-file-selected = No file selected to export
 export-no-screen-available = No screen available to export
 export-success = Exported to { $path }
@@ -272,6 +293,8 @@ cmd-dialog-export-action = Export
 cmd-dialog-export-desc = Export file to image
 cmd-dialog-filter-action = Filter
 cmd-dialog-filter-desc = Toggle filter input
+cmd-dialog-duplicates-action = Find Duplicates
+cmd-dialog-duplicates-desc = Find duplicate and near-duplicate art
 
 # Playback commands (icy_view specific)
 cmd-playback-toggle_scroll-action = Auto Scroll
@@ -39,6 +39,7 @@ pub mod cmd {
         DIALOG_SAUCE = "dialog.sauce",
         DIALOG_EXPORT = "dialog.export",
         DIALOG_FILTER = "dialog.filter",
+        DIALOG_DUPLICATES = "dialog.duplicates",
 
         // Playback
         PLAYBACK_TOGGLE_SCROLL = "playback.toggle_scroll",
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use icy_engine::formats::FileFormat;
+use parking_lot::Mutex;
+use tokio_util::sync::CancellationToken;
+use unarc_rs::unified::ArchiveFormat;
+use walkdir::WalkDir;
+
+use super::ArtFingerprint;
+use crate::items::archive::parse_archive;
+
+/// Nested archives deeper than this are not opened
+const MAX_ARCHIVE_DEPTH: usize = 3;
+
+/// A fingerprinted file - on disk or inside a (possibly nested) archive
+#[derive(Debug, Clone)]
+pub struct ScannedFile {
+    /// Full path with forward slashes, files inside archives continue the archive path
+    pub path: String,
+    pub size: u64,
+    pub fingerprint: ArtFingerprint,
+}
+
+/// One file of a duplicate group
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateMember {
+    pub path: String,
+    pub size: u64,
+    /// Similarity to the first member of the group, 1.0 for exact duplicates
+    pub similarity: f32,
+    /// Same content hash as the first member
+    pub exact: bool,
+}
+
+impl DuplicateMember {
+    /// Whether this is a plain file on disk (and not inside an archive)
+    pub fn is_disk_file(&self) -> bool {
+        Path::new(&self.path).is_file()
+    }
+}
+
+/// Files showing the same (or nearly the same) picture
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    pub members: Vec<DuplicateMember>,
+}
+
+impl DuplicateGroup {
+    /// Whether all members are exact duplicates of each other
+    pub fn is_exact(&self) -> bool {
+        self.members.iter().all(|m| m.exact)
+    }
+}
+
+/// Group files whose content is equal or at least `threshold` (0.0 - 1.0) similar
+///
+/// Exact duplicates are found by hash. Near duplicates are compared pairwise, but only
+/// between pieces whose amount of non-blank cells allows reaching the threshold.
+pub fn group_duplicates(files: &[ScannedFile], threshold: f32) -> Vec<DuplicateGroup> {
+    // Collapse exact duplicates first, near duplicate search only needs one of each
+    let mut by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (i, file) in files.iter().enumerate() {
+        by_hash.entry(file.fingerprint.hash).or_default().push(i);
+    }
+    let mut representatives: Vec<usize> = by_hash.values().map(|members| members[0]).collect();
+
+    let mut parent: Vec<usize> = (0..files.len()).collect();
+    for members in by_hash.values() {
+        for &member in &members[1..] {
+            union(&mut parent, members[0], member);
+        }
+    }
+
+    if threshold < 1.0 {
+        representatives.sort_by_key(|&i| files[i].fingerprint.non_blank());
+        for (a_pos, &a) in representatives.iter().enumerate() {
+            let fa = &files[a].fingerprint;
+            for &b in &representatives[a_pos + 1..] {
+                let fb = &files[b].fingerprint;
+                // Sorted by non-blank count - once b is too large nothing later can match
+                if (fa.non_blank() as f32) < threshold * fb.non_blank() as f32 {
+                    break;
+                }
+                if fa.similarity_above(fb, threshold).is_some() {
+                    union(&mut parent, a, b);
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..files.len() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(i);
+    }
+
+    let mut result: Vec<DuplicateGroup> = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|mut members| {
+            members.sort_by(|a, b| files[*a].path.cmp(&files[*b].path));
+            let first = &files[members[0]].fingerprint;
+            DuplicateGroup {
+                members: members
+                    .iter()
+                    .map(|&i| DuplicateMember {
+                        path: files[i].path.clone(),
+                        size: files[i].size,
+                        similarity: first.similarity(&files[i].fingerprint),
+                        exact: files[i].fingerprint.hash == first.hash,
+                    })
+                    .collect(),
+            }
+        })
+        .collect();
+    result.sort_by(|a, b| a.members[0].path.cmp(&b.members[0].path));
+    result
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    if a != b {
+        parent[b] = a;
+    }
+}
+
+/// Background scan of folders (and the archives in them) for duplicate art
+///
+/// Poll [`DuplicateScanner::take_result`] until it returns the groups. Dropping the
+/// scanner cancels the scan.
+pub struct DuplicateScanner {
+    cancel_token: CancellationToken,
+    scanned: Arc<AtomicUsize>,
+    running: Arc<AtomicBool>,
+    result: Arc<Mutex<Option<Vec<DuplicateGroup>>>>,
+}
+
+impl DuplicateScanner {
+    pub fn spawn(roots: Vec<PathBuf>, threshold: f32) -> Self {
+        let cancel_token = CancellationToken::new();
+        let scanned = Arc::new(AtomicUsize::new(0));
+        let running = Arc::new(AtomicBool::new(true));
+        let result = Arc::new(Mutex::new(None));
+
+        let thread_cancel = cancel_token.clone();
+        let thread_scanned = scanned.clone();
+        let thread_running = running.clone();
+        let thread_result = result.clone();
+        let spawned = std::thread::Builder::new().name("duplicate-scanner".to_string()).spawn(move || {
+            let files = scan_files(&roots, &thread_cancel, &thread_scanned);
+            if !thread_cancel.is_cancelled() {
+                let groups = group_duplicates(&files, threshold);
+                log::info!("Duplicate scan: {} files, {} groups", files.len(), groups.len());
+                *thread_result.lock() = Some(groups);
+            }
+            thread_running.store(false, Ordering::Relaxed);
+        });
+        if let Err(err) = spawned {
+            log::error!("Failed to start duplicate scanner: {}", err);
+            running.store(false, Ordering::Relaxed);
+            *result.lock() = Some(Vec::new());
+        }
+
+        Self {
+            cancel_token,
+            scanned,
+            running,
+            result,
+        }
+    }
+
+    /// Number of files examined so far
+    pub fn scanned(&self) -> usize {
+        self.scanned.load(Ordering::Relaxed)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// The duplicate groups, once the scan is done
+    pub fn take_result(&self) -> Option<Vec<DuplicateGroup>> {
+        self.result.lock().take()
+    }
+
+    pub fn cancel(&self) {
+        self.cancel_token.cancel();
+    }
+}
+
+impl Drop for DuplicateScanner {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Fingerprint every loadable file below the roots, using all cores
+pub fn scan_files(roots: &[PathBuf], cancel_token: &CancellationToken, scanned: &AtomicUsize) -> Vec<ScannedFile> {
+    let files: Vec<PathBuf> = roots
+        .iter()
+        .flat_map(|root| WalkDir::new(root).follow_links(true).into_iter().filter_map(|e| e.ok()))
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk_size = files.len().div_ceil(threads).max(1);
+    let result = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for chunk in files.chunks(chunk_size) {
+            let result = &result;
+            scope.spawn(move || {
+                let mut found = Vec::new();
+                for file in chunk {
+                    if cancel_token.is_cancelled() {
+                        return;
+                    }
+                    scanned.fetch_add(1, Ordering::Relaxed);
+                    let Ok(data) = std::fs::read(file) else {
+                        continue;
+                    };
+                    let path = file.to_string_lossy().replace('\\', "/");
+                    match FileFormat::from_path(file) {
+                        Some(FileFormat::Archive(format)) => scan_archive(data, format, &path, 0, cancel_token, &mut found),
+                        _ => push_file(path, &data, &mut found),
+                    }
+                }
+                result.lock().extend(found);
+            });
+        }
+    });
+
+    result.into_inner()
+}
+
+fn scan_archive(data: Vec<u8>, format: ArchiveFormat, path: &str, depth: usize, cancel_token: &CancellationToken, found: &mut Vec<ScannedFile>) {
+    let Some((files, _)) = parse_archive(data, format, cancel_token.clone()) else {
+        return;
+    };
+    for (name, file_data) in files {
+        let inner_path = format!("{path}/{name}");
+        match FileFormat::from_path(Path::new(&name)) {
+            Some(FileFormat::Archive(nested)) if depth < MAX_ARCHIVE_DEPTH => {
+                scan_archive(file_data, nested, &inner_path, depth + 1, cancel_token, found);
+            }
+            _ => push_file(inner_path, &file_data, found),
+        }
+    }
+}
+
+fn push_file(path: String, data: &[u8], found: &mut Vec<ScannedFile>) {
+    let ext = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext)
+        .unwrap_or_default();
+    // Empty files would all count as duplicates of each other
+    if let Some(fingerprint) = ArtFingerprint::from_bytes(data, ext).filter(|f| f.height > 0) {
+        found.push(ScannedFile {
+            path,
+            size: data.len() as u64,
+            fingerprint,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, text: &str) -> ScannedFile {
+        ScannedFile {
+            path: path.to_string(),
+            size: text.len() as u64,
+            fingerprint: ArtFingerprint::from_bytes(text.as_bytes(), "ans").unwrap(),
+        }
+    }
+
+    #[test]
+    fn groups_exact_and_near_duplicates() {
+        let files = vec![
+            file("/a/LOGO.ANS", "ABCDEFGHIJ\r\nKLMNOPQRST\r\n"),
+            file("/b/pack.zip/LOGO.ANS", "ABCDEFGHIJ\r\nKLMNOPQRST\r\n\r\n\r\n"),
+            file("/c/LOGO2.ANS", "ABCDEFGHIJ\r\nKLMNOPQRSx\r\n"),
+            file("/d/OTHER.ANS", "completely different"),
+        ];
+
+        let exact = group_duplicates(&files, 1.0);
+        assert_eq!(exact.len(), 1);
+        assert_eq!(exact[0].members.len(), 2);
+        assert!(exact[0].is_exact());
+
+        let near = group_duplicates(&files, 0.9);
+        assert_eq!(near.len(), 1);
+        let paths: Vec<&str> = near[0].members.iter().map(|m| m.path.as_str()).collect();
+        assert_eq!(paths, vec!["/a/LOGO.ANS", "/b/pack.zip/LOGO.ANS", "/c/LOGO2.ANS"]);
+        assert!(!near[0].is_exact());
+        assert!(near[0].members[2].similarity < 1.0);
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use icy_engine::formats::{FileFormat, LoadData};
+use icy_engine::{AttributeColor, AttributedChar, Palette, Position, TextBuffer, TextPane};
+
+/// Rows beyond this are hashed but not kept for the similarity comparison
+const MAX_SIGNATURE_ROWS: i32 = 200;
+
+/// Content fingerprint of a piece of text art
+///
+/// Built from the loaded buffer, so SAUCE records, EOF padding and other byte level
+/// differences don't matter - only the visible cells (character, attributes and the
+/// resolved palette colors) do. Trailing blank rows are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArtFingerprint {
+    pub width: i32,
+    pub height: i32,
+    /// Hash over all cells - equal hashes mean equal content
+    pub hash: u64,
+    /// One byte per cell of the first rows (0 = blank), row-major with `width` columns
+    cells: Vec<u8>,
+    /// Number of non-blank entries in `cells`
+    non_blank: usize,
+}
+
+impl ArtFingerprint {
+    /// Load a file with the format matching its extension and fingerprint it
+    pub fn from_bytes(data: &[u8], ext: &str) -> Option<Self> {
+        let format = FileFormat::from_extension(ext)?;
+        if matches!(format, FileFormat::Archive(_) | FileFormat::Image(_)) {
+            return None;
+        }
+        let load_data = LoadData::new(None, None).with_max_height(icy_engine::limits::MAX_BUFFER_HEIGHT);
+        let loaded_doc = format.from_bytes(data, Some(load_data)).ok()?;
+        Some(Self::from_buffer(&loaded_doc.screen.buffer))
+    }
+
+    pub fn from_buffer(buffer: &TextBuffer) -> Self {
+        let width = buffer.width().max(0);
+        let mut height = buffer.height().max(0);
+        while height > 0 && (0..width).all(|x| is_blank(&buffer.palette, buffer.char_at(Position::new(x, height - 1)))) {
+            height -= 1;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        (width, height).hash(&mut hasher);
+        let signature_rows = height.min(MAX_SIGNATURE_ROWS);
+        let mut cells = Vec::with_capacity((width * signature_rows) as usize);
+        let mut non_blank = 0;
+        for y in 0..height {
+            for x in 0..width {
+                let value = cell_value(&buffer.palette, buffer.char_at(Position::new(x, y)));
+                value.hash(&mut hasher);
+                if y < signature_rows {
+                    let cell = if value == 0 { 0 } else { (value % 255) as u8 + 1 };
+                    if cell != 0 {
+                        non_blank += 1;
+                    }
+                    cells.push(cell);
+                }
+            }
+        }
+
+        Self {
+            width,
+            height,
+            hash: hasher.finish(),
+            cells,
+            non_blank,
+        }
+    }
+
+    /// Number of non-blank cells taking part in the similarity comparison
+    pub fn non_blank(&self) -> usize {
+        self.non_blank
+    }
+
+    fn cell(&self, x: i32, y: i32) -> u8 {
+        if x >= self.width {
+            return 0;
+        }
+        self.cells.get((y * self.width + x) as usize).copied().unwrap_or(0)
+    }
+
+    /// Share of equal cells among the cells that are non-blank in either piece (0.0 - 1.0)
+    pub fn similarity(&self, other: &Self) -> f32 {
+        self.similarity_above(other, 0.0).unwrap_or(0.0)
+    }
+
+    /// Like [`Self::similarity`], but gives up early once `threshold` can't be reached
+    pub fn similarity_above(&self, other: &Self, threshold: f32) -> Option<f32> {
+        if self.hash == other.hash {
+            return Some(1.0);
+        }
+        let (min, max) = (self.non_blank.min(other.non_blank), self.non_blank.max(other.non_blank));
+        if max == 0 {
+            return Some(1.0);
+        }
+        // Matching cells are non-blank in both, so the share can't exceed min / max
+        if (min as f32) < threshold * max as f32 {
+            return None;
+        }
+        // similarity >= threshold <=> mismatches <= matches * (1 - t) / t <= min * (1 - t) / t
+        let max_mismatches = if threshold > 0.0 {
+            min as f32 * (1.0 - threshold) / threshold
+        } else {
+            f32::MAX
+        };
+
+        let width = self.width.max(other.width);
+        let rows = (self.cells.len() as i32 / self.width.max(1)).max(other.cells.len() as i32 / other.width.max(1));
+        let mut matches = 0usize;
+        let mut mismatches = 0usize;
+        for y in 0..rows {
+            for x in 0..width {
+                let (a, b) = (self.cell(x, y), other.cell(x, y));
+                if a == 0 && b == 0 {
+                    continue;
+                }
+                if a == b {
+                    matches += 1;
+                } else {
+                    mismatches += 1;
+                    if mismatches as f32 > max_mismatches {
+                        return None;
+                    }
+                }
+            }
+        }
+        let similarity = matches as f32 / (matches + mismatches).max(1) as f32;
+        (similarity >= threshold).then_some(similarity)
+    }
+}
+
+fn is_blank(palette: &Palette, ch: AttributedChar) -> bool {
+    matches!(ch.ch, ' ' | '\0' | '\u{FF}') && color_value(palette, ch.attribute.background_color()) == 0
+}
+
+/// Hash value of a cell, 0 for blank cells so they compare equal regardless of their foreground
+fn cell_value(palette: &Palette, ch: AttributedChar) -> u64 {
+    if is_blank(palette, ch) {
+        return 0;
+    }
+    let mut hasher = DefaultHasher::new();
+    ch.ch.hash(&mut hasher);
+    color_value(palette, ch.attribute.foreground_color()).hash(&mut hasher);
+    color_value(palette, ch.attribute.background_color()).hash(&mut hasher);
+    ch.attribute.attr.hash(&mut hasher);
+    ch.attribute.font_page().hash(&mut hasher);
+    hasher.finish().max(1)
+}
+
+/// Resolve a color through the palette, so a re-saved file with an equal palette matches
+fn color_value(palette: &Palette, color: AttributeColor) -> u32 {
+    match color {
+        AttributeColor::Palette(index) => {
+            let (r, g, b) = palette.rgb(index as u32);
+            (r as u32) << 16 | (g as u32) << 8 | b as u32
+        }
+        AttributeColor::ExtendedPalette(index) => 1 << 24 | index as u32,
+        AttributeColor::Rgb(r, g, b) => (r as u32) << 16 | (g as u32) << 8 | b as u32,
+        AttributeColor::Transparent => 2 << 24,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(text: &str) -> ArtFingerprint {
+        ArtFingerprint::from_bytes(text.as_bytes(), "ans").unwrap()
+    }
+
+    #[test]
+    fn sauce_and_padding_are_ignored() {
+        let plain = fingerprint("\x1b[1;31mHello\r\n\x1b[0;44mWorld\x1b[0m\r\n");
+        let padded = fingerprint("\x1b[1;31mHello\r\n\x1b[0;44mWorld\x1b[0m\r\n\r\n\r\n\x1a\0\0\0");
+        assert_eq!(plain.hash, padded.hash);
+        assert_eq!(plain.similarity(&padded), 1.0);
+    }
+
+    #[test]
+    fn attributes_change_the_hash() {
+        let red = fingerprint("\x1b[31mHello World");
+        let blue = fingerprint("\x1b[34mHello World");
+        assert_ne!(red.hash, blue.hash);
+        assert_eq!(red.similarity(&blue), 0.0);
+    }
+
+    #[test]
+    fn near_duplicates_are_similar() {
+        let original = fingerprint("ABCDEFGHIJ\r\nKLMNOPQRST\r\n");
+        let edited = fingerprint("ABCDEFGHIJ\r\nKLMNOPQRSx\r\n");
+        assert_ne!(original.hash, edited.hash);
+        let similarity = original.similarity(&edited);
+        assert!(similarity > 0.9 && similarity < 1.0, "{similarity}");
+        assert!(original.similarity_above(&edited, 0.99).is_none());
+        assert!(original.similarity_above(&fingerprint("something else"), 0.5).is_none());
+    }
+}
@@ -0,0 +1,5 @@
+mod finder;
+mod fingerprint;
+
+pub use finder::*;
+pub use fingerprint::*;
@@ -5,13 +5,18 @@ use icy_ui::Task;
 use thiserror::Error;
 
 mod archive;
+mod duplicates;
 mod files;
 mod provider;
+mod search;
 mod sixteencolors;
 
+pub(crate) use archive::parse_archive;
 pub use archive::ArchiveContainer;
+pub use duplicates::*;
 pub use files::*;
 pub use provider::*;
+pub use search::*;
 pub use sixteencolors::*;
 
 use crate::thumbnail::{RgbaData, THUMBNAIL_MAX_HEIGHT, THUMBNAIL_RENDER_WIDTH};
@@ -1,10 +1,12 @@
-/// Type of provider - only File (local filesystem + ZIP) or Web (16colors)
+/// Type of provider - File (local filesystem + ZIP), Web (16colors) or Search (SAUCE index)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProviderType {
     /// Local filesystem (can also navigate into ZIP files)
     File,
     /// Web browsing (16colo.rs)
     Web,
+    /// Results of a SAUCE index query - a flat virtual folder
+    Search,
 }
 
 /// Simple navigation point
@@ -12,6 +14,7 @@ pub enum ProviderType {
 /// Paths are absolute:
 /// - File: `/home/user/folder` or `/home/user/archive.zip/folder/file.ans`
 /// - Web: `16colo.rs/pack/name`
+/// - Search: the query string (e.g. `author:xyz year:1996`)
 #[derive(Debug, Clone, PartialEq)]
 pub struct NavPoint {
     /// Type of provider
@@ -41,6 +44,15 @@ impl NavPoint {
         }
     }
 
+    /// Create a new search NavPoint
+    pub fn search(query: impl Into<String>) -> Self {
+        Self {
+            provider_type: ProviderType::Search,
+            path: query.into(),
+            selected_item: None,
+        }
+    }
+
     /// Navigate to a new path
     pub fn navigate_to(&mut self, path: String) {
         self.path = path;
@@ -80,6 +92,8 @@ impl NavPoint {
                     true
                 }
             }
+            // Search results have no parent
+            ProviderType::Search => false,
         }
     }
 
@@ -88,6 +102,7 @@ impl NavPoint {
         match self.provider_type {
             ProviderType::File => std::path::Path::new(&self.path).parent().map(|p| !p.as_os_str().is_empty()).unwrap_or(false),
             ProviderType::Web => !self.path.is_empty(),
+            ProviderType::Search => false,
         }
     }
 
@@ -103,6 +118,7 @@ impl NavPoint {
                     format!("/{}", self.path)
                 }
             }
+            ProviderType::Search => format!("{}{}", super::SEARCH_PREFIX, self.path),
         }
     }
 
@@ -110,4 +126,9 @@ impl NavPoint {
     pub fn is_web(&self) -> bool {
         self.provider_type == ProviderType::Web
     }
+
+    /// Check if this is a search result folder
+    pub fn is_search(&self) -> bool {
+        self.provider_type == ProviderType::Search
+    }
 }
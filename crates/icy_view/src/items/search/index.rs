@@ -0,0 +1,189 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use icy_sauce::{Capabilities, SauceRecord};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use super::SauceQuery;
+use crate::options::get_config_dir;
+
+/// Index file name inside the config directory
+const INDEX_FILE: &str = "sauce_index.json";
+
+/// Size and modification time of a file on disk - an entry is re-read when either changes
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FileStamp {
+    pub size: u64,
+    pub modified: u64,
+}
+
+impl FileStamp {
+    pub fn from_metadata(metadata: &std::fs::Metadata) -> Self {
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            size: metadata.len(),
+            modified,
+        }
+    }
+}
+
+/// SAUCE fields of one indexed file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct IndexEntry {
+    /// Full path with forward slashes; files inside archives continue the archive path
+    /// (e.g. `/art/acid-96.zip/ACID-LOGO.ANS`)
+    pub path: String,
+    pub size: u64,
+    pub title: String,
+    pub author: String,
+    pub group: String,
+    /// SAUCE date as `CCYYMMDD`, empty if unset
+    pub date: String,
+    pub comments: Vec<String>,
+    pub font: String,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl IndexEntry {
+    pub fn from_sauce(path: String, size: u64, sauce: &SauceRecord) -> Self {
+        let date = sauce.date().to_string().trim().to_string();
+        let (width, height, font) = match sauce.capabilities() {
+            Some(Capabilities::Character(caps)) => (caps.columns as u16, caps.lines as u16, caps.font().map(|f| f.to_string()).unwrap_or_default()),
+            Some(Capabilities::Binary(caps)) => (caps.columns as u16, caps.lines as u16, caps.font().map(|f| f.to_string()).unwrap_or_default()),
+            _ => (0, 0, String::new()),
+        };
+
+        Self {
+            path,
+            size,
+            title: sauce.title().to_string().trim().to_string(),
+            author: sauce.author().to_string().trim().to_string(),
+            group: sauce.group().to_string().trim().to_string(),
+            date: if date == "00000000" { String::new() } else { date },
+            comments: sauce
+                .comments()
+                .iter()
+                .map(|c| c.to_string().trim().to_string())
+                .filter(|c| !c.is_empty())
+                .collect(),
+            font: font.trim().to_string(),
+            width,
+            height,
+        }
+    }
+
+    /// File name part of the path
+    pub fn file_name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    /// Year from the SAUCE date, if it has one
+    pub fn year(&self) -> Option<u32> {
+        self.date.get(0..4)?.parse().ok()
+    }
+}
+
+/// Persistent SAUCE index over local art collections
+#[derive(Serialize, Deserialize, Default)]
+pub struct SauceIndex {
+    /// Stamps of every file visited on disk (archives included), keyed by path
+    stamps: HashMap<String, FileStamp>,
+    /// Files carrying a SAUCE record, keyed by full path
+    entries: BTreeMap<String, IndexEntry>,
+    #[serde(skip)]
+    dirty: bool,
+}
+
+impl SauceIndex {
+    /// Default location of the index file
+    pub fn default_path() -> PathBuf {
+        get_config_dir().join(INDEX_FILE)
+    }
+
+    /// Load an index, starting empty if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read(path) {
+            Ok(data) => match serde_json::from_slice(&data) {
+                Ok(index) => index,
+                Err(err) => {
+                    log::error!("Discarding unreadable SAUCE index {:?}: {}", path, err);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Write the index if it changed since the last save
+    pub fn save(&mut self, path: &Path) -> std::io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let data = serde_json::to_vec(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, data)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Whether `path` was indexed with exactly this stamp
+    pub fn is_current(&self, path: &str, stamp: FileStamp) -> bool {
+        self.stamps.get(path) == Some(&stamp)
+    }
+
+    /// Drop everything recorded for a disk file, including the contents of an archive
+    pub fn remove_file(&mut self, path: &str) {
+        self.stamps.remove(path);
+        let prefix = format!("{path}/");
+        self.entries.retain(|key, _| key != path && !key.starts_with(&prefix));
+        self.dirty = true;
+    }
+
+    /// Replace everything known about a disk file with freshly read entries
+    pub fn update_file(&mut self, path: String, stamp: FileStamp, entries: Vec<IndexEntry>) {
+        self.remove_file(&path);
+        for entry in entries {
+            self.entries.insert(entry.path.clone(), entry);
+        }
+        self.stamps.insert(path, stamp);
+    }
+
+    /// Remove disk files below `root` that no longer exist
+    pub fn prune(&mut self, root: &str, still_present: impl Fn(&str) -> bool) {
+        let prefix = format!("{}/", root.trim_end_matches('/'));
+        let gone: Vec<String> = self
+            .stamps
+            .keys()
+            .filter(|path| path.starts_with(&prefix) && !still_present(path))
+            .cloned()
+            .collect();
+        for path in gone {
+            self.remove_file(&path);
+        }
+    }
+
+    /// Number of files carrying a SAUCE record
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All entries matching the query, ordered by path
+    pub fn search(&self, query: &SauceQuery) -> Vec<IndexEntry> {
+        self.entries.values().filter(|entry| query.matches(entry)).cloned().collect()
+    }
+}
+
+/// Shared SAUCE index type
+pub type SharedSauceIndex = Arc<RwLock<SauceIndex>>;
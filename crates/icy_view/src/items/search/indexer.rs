@@ -0,0 +1,176 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use icy_engine::formats::FileFormat;
+use icy_sauce::SauceRecord;
+use tokio_util::sync::CancellationToken;
+use unarc_rs::unified::ArchiveFormat;
+use walkdir::WalkDir;
+
+use super::{FileStamp, IndexEntry, SauceIndex, SharedSauceIndex};
+use crate::items::archive::parse_archive;
+
+/// How often a running scan writes the index to disk
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Nested archives deeper than this are not opened
+const MAX_ARCHIVE_DEPTH: usize = 3;
+
+/// Background thread keeping a [`SauceIndex`] in sync with a set of folders
+///
+/// Unchanged files (same size and modification time) are skipped, so rescans after the
+/// first one are cheap. The scan stops when the indexer is dropped.
+pub struct SauceIndexer {
+    cancel_token: CancellationToken,
+    scanned: Arc<AtomicUsize>,
+    running: Arc<AtomicBool>,
+    roots: Vec<PathBuf>,
+}
+
+impl SauceIndexer {
+    pub fn spawn(index: SharedSauceIndex, roots: Vec<PathBuf>) -> Self {
+        let cancel_token = CancellationToken::new();
+        let scanned = Arc::new(AtomicUsize::new(0));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread_roots = roots.clone();
+        let thread_cancel = cancel_token.clone();
+        let thread_scanned = scanned.clone();
+        let thread_running = running.clone();
+        let spawned = std::thread::Builder::new().name("sauce-indexer".to_string()).spawn(move || {
+            let index_path = SauceIndex::default_path();
+            let mut last_save = Instant::now();
+            for root in &thread_roots {
+                if thread_cancel.is_cancelled() {
+                    break;
+                }
+                scan_root(&index, root, &thread_cancel, &thread_scanned, &index_path, &mut last_save);
+            }
+            if let Err(err) = index.write().save(&index_path) {
+                log::error!("Failed to save SAUCE index {:?}: {}", index_path, err);
+            }
+            thread_running.store(false, Ordering::Relaxed);
+            log::info!(
+                "SAUCE index: {} files scanned, {} entries",
+                thread_scanned.load(Ordering::Relaxed),
+                index.read().len()
+            );
+        });
+        if let Err(err) = spawned {
+            log::error!("Failed to start SAUCE indexer: {}", err);
+            running.store(false, Ordering::Relaxed);
+        }
+
+        Self {
+            cancel_token,
+            scanned,
+            running,
+            roots,
+        }
+    }
+
+    /// Folders this indexer was started for
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Number of files examined so far
+    pub fn scanned(&self) -> usize {
+        self.scanned.load(Ordering::Relaxed)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel(&self) {
+        self.cancel_token.cancel();
+    }
+}
+
+impl Drop for SauceIndexer {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+fn scan_root(index: &SharedSauceIndex, root: &Path, cancel_token: &CancellationToken, scanned: &AtomicUsize, index_path: &Path, last_save: &mut Instant) {
+    let root_str = normalize_path(root);
+    let mut seen = std::collections::HashSet::new();
+
+    for entry in WalkDir::new(root).follow_links(true).into_iter().filter_map(|e| e.ok()) {
+        if cancel_token.is_cancelled() {
+            return;
+        }
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let path = normalize_path(entry.path());
+        seen.insert(path.clone());
+        scanned.fetch_add(1, Ordering::Relaxed);
+
+        let stamp = FileStamp::from_metadata(&metadata);
+        if index.read().is_current(&path, stamp) {
+            continue;
+        }
+
+        let entries = read_entries(entry.path(), &path, cancel_token);
+        if cancel_token.is_cancelled() {
+            return;
+        }
+        index.write().update_file(path, stamp, entries);
+
+        if last_save.elapsed() > SAVE_INTERVAL {
+            if let Err(err) = index.write().save(index_path) {
+                log::error!("Failed to save SAUCE index {:?}: {}", index_path, err);
+            }
+            *last_save = Instant::now();
+        }
+    }
+
+    // Only prune after a complete walk, a cancelled one has not seen everything
+    index.write().prune(&root_str, |path| seen.contains(path));
+}
+
+/// Read SAUCE entries of a disk file - one for a plain file, any number for an archive
+fn read_entries(fs_path: &Path, path: &str, cancel_token: &CancellationToken) -> Vec<IndexEntry> {
+    let Ok(data) = std::fs::read(fs_path) else {
+        return Vec::new();
+    };
+    let mut entries = Vec::new();
+    match FileFormat::from_path(fs_path) {
+        Some(FileFormat::Archive(format)) => read_archive_entries(data, format, path, 0, cancel_token, &mut entries),
+        _ => push_entry(path.to_string(), &data, &mut entries),
+    }
+    entries
+}
+
+fn read_archive_entries(data: Vec<u8>, format: ArchiveFormat, path: &str, depth: usize, cancel_token: &CancellationToken, entries: &mut Vec<IndexEntry>) {
+    let Some((files, _)) = parse_archive(data, format, cancel_token.clone()) else {
+        return;
+    };
+    for (name, file_data) in files {
+        let inner_path = format!("{path}/{name}");
+        match FileFormat::from_path(Path::new(&name)) {
+            Some(FileFormat::Archive(nested)) if depth < MAX_ARCHIVE_DEPTH => {
+                read_archive_entries(file_data, nested, &inner_path, depth + 1, cancel_token, entries);
+            }
+            _ => push_entry(inner_path, &file_data, entries),
+        }
+    }
+}
+
+fn push_entry(path: String, data: &[u8], entries: &mut Vec<IndexEntry>) {
+    if let Some(sauce) = SauceRecord::from_bytes(data).ok().flatten() {
+        entries.push(IndexEntry::from_sauce(path, data.len() as u64, &sauce));
+    }
+}
+
+fn normalize_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
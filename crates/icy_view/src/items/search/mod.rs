@@ -0,0 +1,42 @@
+mod index;
+mod indexer;
+mod query;
+mod search_item;
+
+pub use index::*;
+pub use indexer::*;
+pub use query::*;
+pub use search_item::*;
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use parking_lot::RwLock;
+
+/// Prefix marking a search query in the navigation bar path
+pub const SEARCH_PREFIX: &str = "search:";
+
+/// Global index instance - loaded from disk on first use and shared by all windows
+pub(crate) fn get_sauce_index() -> SharedSauceIndex {
+    static INDEX: OnceLock<SharedSauceIndex> = OnceLock::new();
+    INDEX
+        .get_or_init(|| Arc::new(RwLock::new(SauceIndex::load(&SauceIndex::default_path()))))
+        .clone()
+}
+
+/// Start the global indexer for `roots`, replacing a running one for a different folder list
+pub(crate) fn start_indexer(roots: Vec<PathBuf>) {
+    static INDEXER: Mutex<Option<SauceIndexer>> = Mutex::new(None);
+    let Ok(mut indexer) = INDEXER.lock() else {
+        return;
+    };
+    if indexer.as_ref().is_some_and(|i| i.roots() == roots.as_slice()) {
+        return;
+    }
+    // Dropping the old indexer cancels its scan
+    *indexer = if roots.is_empty() {
+        None
+    } else {
+        Some(SauceIndexer::spawn(get_sauce_index(), roots))
+    };
+}
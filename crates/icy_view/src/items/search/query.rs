@@ -0,0 +1,225 @@
+use super::IndexEntry;
+
+/// Comparison used by numeric query terms (`width:>80`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    fn split(value: &str) -> (Self, &str) {
+        if let Some(rest) = value.strip_prefix(">=") {
+            (Self::GreaterOrEqual, rest)
+        } else if let Some(rest) = value.strip_prefix("<=") {
+            (Self::LessOrEqual, rest)
+        } else if let Some(rest) = value.strip_prefix('>') {
+            (Self::Greater, rest)
+        } else if let Some(rest) = value.strip_prefix('<') {
+            (Self::Less, rest)
+        } else {
+            (Self::Equal, value.strip_prefix('=').unwrap_or(value))
+        }
+    }
+
+    fn test(self, lhs: u32, rhs: u32) -> bool {
+        match self {
+            Self::Equal => lhs == rhs,
+            Self::Less => lhs < rhs,
+            Self::LessOrEqual => lhs <= rhs,
+            Self::Greater => lhs > rhs,
+            Self::GreaterOrEqual => lhs >= rhs,
+        }
+    }
+}
+
+/// A single query term
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryTerm {
+    /// Matches title, author, group, comments and file name
+    Text(String),
+    Title(String),
+    Author(String),
+    Group(String),
+    Comment(String),
+    Font(String),
+    Name(String),
+    /// Prefix of the `CCYYMMDD` date (dashes are ignored, so `1996-05` works)
+    Date(String),
+    Year(Comparison, u32),
+    Width(Comparison, u32),
+    Height(Comparison, u32),
+}
+
+impl QueryTerm {
+    fn parse(token: &str) -> Option<Self> {
+        let Some((field, value)) = token.split_once(':') else {
+            return Some(Self::Text(token.to_lowercase()));
+        };
+        let value = value.trim_matches('"');
+        if value.is_empty() {
+            return None;
+        }
+        let text = value.to_lowercase();
+        let term = match field.to_ascii_lowercase().as_str() {
+            "title" => Self::Title(text),
+            "author" | "artist" => Self::Author(text),
+            "group" => Self::Group(text),
+            "comment" | "comments" => Self::Comment(text),
+            "font" => Self::Font(text),
+            "name" | "file" => Self::Name(text),
+            "date" => Self::Date(text.replace('-', "")),
+            "year" | "width" | "height" => {
+                let (cmp, number) = Comparison::split(value);
+                let number = number.parse().ok()?;
+                match field.to_ascii_lowercase().as_str() {
+                    "year" => Self::Year(cmp, number),
+                    "width" => Self::Width(cmp, number),
+                    _ => Self::Height(cmp, number),
+                }
+            }
+            // Not a known field - treat `foo:bar` as plain text
+            _ => Self::Text(token.to_lowercase()),
+        };
+        Some(term)
+    }
+
+    fn matches(&self, entry: &IndexEntry) -> bool {
+        let contains = |haystack: &str, needle: &str| haystack.to_lowercase().contains(needle);
+        match self {
+            Self::Text(text) => {
+                contains(&entry.title, text)
+                    || contains(&entry.author, text)
+                    || contains(&entry.group, text)
+                    || contains(entry.file_name(), text)
+                    || entry.comments.iter().any(|c| contains(c, text))
+            }
+            Self::Title(text) => contains(&entry.title, text),
+            Self::Author(text) => contains(&entry.author, text),
+            Self::Group(text) => contains(&entry.group, text),
+            Self::Comment(text) => entry.comments.iter().any(|c| contains(c, text)),
+            Self::Font(text) => contains(&entry.font, text),
+            Self::Name(text) => contains(entry.file_name(), text),
+            Self::Date(prefix) => entry.date.starts_with(prefix.as_str()),
+            Self::Year(cmp, year) => entry.year().is_some_and(|y| cmp.test(y, *year)),
+            Self::Width(cmp, width) => entry.width > 0 && cmp.test(entry.width as u32, *width),
+            Self::Height(cmp, height) => entry.height > 0 && cmp.test(entry.height as u32, *height),
+        }
+    }
+}
+
+/// Parsed search query
+///
+/// Syntax: whitespace separated terms, all of which must match.
+/// `field:value` restricts a term to one SAUCE field, `"quoted values"` may contain spaces
+/// and a leading `-` negates a term. Numeric fields (`year`, `width`, `height`) accept
+/// `>`, `>=`, `<`, `<=` and `=`.
+///
+/// Example: `author:xyz group:acid year:1996 width:>80 -title:"no title"`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SauceQuery {
+    terms: Vec<(bool, QueryTerm)>,
+}
+
+impl SauceQuery {
+    pub fn parse(query: &str) -> Self {
+        let terms = tokenize(query)
+            .into_iter()
+            .filter_map(|token| {
+                let (negated, token) = match token.strip_prefix('-') {
+                    Some(rest) if !rest.is_empty() => (true, rest),
+                    _ => (false, token.as_str()),
+                };
+                QueryTerm::parse(token).map(|term| (negated, term))
+            })
+            .collect();
+        Self { terms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, entry: &IndexEntry) -> bool {
+        !self.terms.is_empty() && self.terms.iter().all(|(negated, term)| term.matches(entry) != *negated)
+    }
+}
+
+/// Split at whitespace outside of double quotes
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for ch in query.chars() {
+        match ch {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(ch);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+        .into_iter()
+        .map(|t| if t.contains(':') { t } else { t.trim_matches('"').to_string() })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry() -> IndexEntry {
+        IndexEntry {
+            path: "/art/acid-9605.zip/XYZ-LOGO.ANS".to_string(),
+            size: 12345,
+            title: "Acid Logo".to_string(),
+            author: "xyz".to_string(),
+            group: "ACiD Productions".to_string(),
+            date: "19960512".to_string(),
+            comments: vec!["greets to ice".to_string()],
+            font: "IBM VGA".to_string(),
+            width: 160,
+            height: 50,
+        }
+    }
+
+    #[test]
+    fn combined_fields_match() {
+        assert!(SauceQuery::parse("author:xyz group:acid year:1996 width:>80").matches(&entry()));
+        assert!(!SauceQuery::parse("author:xyz year:1997").matches(&entry()));
+        assert!(!SauceQuery::parse("width:<=80").matches(&entry()));
+    }
+
+    #[test]
+    fn free_text_searches_all_fields() {
+        assert!(SauceQuery::parse("greets").matches(&entry()));
+        assert!(SauceQuery::parse("logo.ans").matches(&entry()));
+        assert!(!SauceQuery::parse("blocktronics").matches(&entry()));
+    }
+
+    #[test]
+    fn quotes_and_negation() {
+        assert!(SauceQuery::parse("title:\"acid logo\"").matches(&entry()));
+        assert!(!SauceQuery::parse("-group:acid").matches(&entry()));
+        assert!(SauceQuery::parse("-font:amiga date:1996-05").matches(&entry()));
+    }
+
+    #[test]
+    fn empty_query_matches_nothing() {
+        assert!(SauceQuery::parse("   ").is_empty());
+        assert!(!SauceQuery::parse("").matches(&entry()));
+        assert!(SauceQuery::parse("width:>abc").is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use icy_engine::formats::FileFormat;
+use tokio_util::sync::CancellationToken;
+
+use super::IndexEntry;
+use crate::items::archive::parse_archive;
+use crate::items::{Item, ItemError};
+
+/// A search hit - a file on disk or inside a (possibly nested) archive
+pub struct IndexedItem {
+    entry: IndexEntry,
+}
+
+impl IndexedItem {
+    pub fn new(entry: IndexEntry) -> Self {
+        Self { entry }
+    }
+
+    pub fn entry(&self) -> &IndexEntry {
+        &self.entry
+    }
+}
+
+#[async_trait]
+impl Item for IndexedItem {
+    fn get_label(&self) -> String {
+        self.entry.file_name().to_string()
+    }
+
+    fn get_file_path(&self) -> String {
+        // Search results come from many folders, the full path is the only unique name
+        self.entry.path.clone()
+    }
+
+    fn get_full_path(&self) -> Option<String> {
+        Some(self.entry.path.clone())
+    }
+
+    fn size(&self) -> Option<u64> {
+        Some(self.entry.size)
+    }
+
+    async fn read_data(&self) -> Result<Vec<u8>, ItemError> {
+        let path = self.entry.path.clone();
+        tokio::task::spawn_blocking(move || read_indexed_file(&path))
+            .await
+            .map_err(|e| ItemError::Other(e.to_string()))?
+    }
+
+    fn clone_box(&self) -> Box<dyn Item> {
+        Box::new(IndexedItem { entry: self.entry.clone() })
+    }
+}
+
+/// Read a file by its index path, extracting it from archives along the way
+pub(crate) fn read_indexed_file(path: &str) -> Result<Vec<u8>, ItemError> {
+    // Find the longest prefix that exists on disk, the rest is inside archives
+    let mut fs_path = PathBuf::new();
+    let mut components = path.split('/').peekable();
+    if path.starts_with('/') {
+        fs_path.push("/");
+        components.next();
+    } else if let Some(drive) = components.next_if(|c| c.ends_with(':')) {
+        // Windows drive root (`C:/...`)
+        fs_path.push(format!("{drive}/"));
+    }
+    while let Some(component) = components.peek() {
+        let next = fs_path.join(component);
+        if !next.exists() {
+            break;
+        }
+        fs_path = next;
+        components.next();
+        if fs_path.is_file() {
+            break;
+        }
+    }
+    let mut data = std::fs::read(&fs_path).map_err(|e| ItemError::Io(format!("Failed to read file {:?}: {}", fs_path, e)))?;
+    let rest: Vec<&str> = components.collect();
+    if rest.is_empty() {
+        return Ok(data);
+    }
+
+    let mut archive_name = fs_path;
+    let mut rest = rest.as_slice();
+    while !rest.is_empty() {
+        let Some(FileFormat::Archive(format)) = FileFormat::from_path(&archive_name) else {
+            return Err(ItemError::NotFound(path.to_string()));
+        };
+        let (mut files, _) =
+            parse_archive(data, format, CancellationToken::new()).ok_or_else(|| ItemError::Parse(format!("Failed to open archive {:?}", archive_name)))?;
+
+        // The inner name is either the complete remainder or the path of a nested archive
+        let mut found = None;
+        for len in (1..=rest.len()).rev() {
+            let name = rest[..len].join("/");
+            if let Some(file_data) = files.remove(&name) {
+                found = Some((len, name, file_data));
+                break;
+            }
+        }
+        let Some((len, name, file_data)) = found else {
+            return Err(ItemError::NotFound(path.to_string()));
+        };
+        data = file_data;
+        archive_name = Path::new(&name).to_path_buf();
+        rest = &rest[len..];
+    }
+    Ok(data)
+}
@@ -0,0 +1,335 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use i18n_embed_fl::fl;
+use icy_engine::formats::FileFormat;
+use icy_engine_gui::ui::FileIcon;
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use crate::items::{load_image_to_rgba, sort_folder, ArchiveContainer, Item, ItemError};
+use crate::thumbnail::{scale_to_thumbnail_width, RgbaData};
+use crate::LANGUAGE_LOADER;
+
+use super::{render_year_thumbnail, MirrorLayout, SixteenColorsPack, SixteenColorsProvider, SixteenColorsYear};
+
+/// Offline view of a directory written by [`super::SixteenColorsMirror`]
+///
+/// Offers the same year/pack/file hierarchy as the live provider. Years and packs listed in
+/// the mirrored metadata but not downloaded themselves are returned as live items, so a
+/// partial mirror still shows everything while online.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalMirror {
+    layout: MirrorLayout,
+}
+
+impl LocalMirror {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            layout: MirrorLayout::new(root),
+        }
+    }
+
+    pub fn layout(&self) -> &MirrorLayout {
+        &self.layout
+    }
+
+    fn read_json(path: &std::path::Path) -> Option<Value> {
+        let data = std::fs::read(path).ok()?;
+        match serde_json::from_slice(&data) {
+            Ok(json) => Some(json),
+            Err(err) => {
+                log::error!("Invalid mirror metadata {:?}: {}", path, err);
+                None
+            }
+        }
+    }
+
+    /// Whether the mirror has the metadata for a 16colo.rs path
+    pub fn contains(&self, path: &str) -> bool {
+        match SixteenColorsProvider::parse_path(path) {
+            (None, None) => self.layout.years_json().exists(),
+            (Some(year), None) => self.layout.packs_json(year).exists(),
+            (Some(year), Some(pack)) => self.layout.pack_json(year, &pack).exists(),
+            (None, Some(_)) => false,
+        }
+    }
+
+    /// Items at a 16colo.rs path, None if that part is not mirrored
+    pub fn get_items(&self, path: &str) -> Option<Vec<Box<dyn Item>>> {
+        match SixteenColorsProvider::parse_path(path) {
+            (None, None) => self.year_items(),
+            (Some(year), None) => self.pack_items(year),
+            (Some(year), Some(pack)) => self.file_items(year, &pack),
+            (None, Some(_)) => None,
+        }
+    }
+
+    fn year_items(&self) -> Option<Vec<Box<dyn Item>>> {
+        let json = Self::read_json(&self.layout.years_json())?;
+        let mut result: Vec<Box<dyn Item>> = Vec::new();
+        for year_data in json.as_array().into_iter().flatten() {
+            let year = year_data["year"].as_u64().unwrap_or(0);
+            let packs = year_data["packs"].as_u64().unwrap_or(0);
+            if self.layout.packs_json(year).exists() {
+                result.push(Box::new(LocalSixteenColorsYear::new(self.clone(), year, packs)));
+            } else {
+                result.push(Box::new(SixteenColorsYear::new(year, packs)));
+            }
+        }
+        result.reverse();
+        Some(result)
+    }
+
+    fn pack_items(&self, year: u64) -> Option<Vec<Box<dyn Item>>> {
+        let json = Self::read_json(&self.layout.packs_json(year))?;
+        let mut result: Vec<Box<dyn Item>> = Vec::new();
+        for pack in json.as_array().into_iter().flatten() {
+            let filename = pack["filename"].as_str().unwrap_or_default().to_string();
+            let month = pack["month"].as_u64().unwrap_or(0);
+            let pack_year = pack["year"].as_u64().unwrap_or(0);
+            let name = pack["name"].as_str().unwrap_or_default().to_string();
+            if self.layout.pack_json(year, &name).exists() {
+                result.push(Box::new(LocalSixteenColorsPack::new(self.clone(), year, name)));
+            } else {
+                result.push(Box::new(SixteenColorsPack::new(filename, month, pack_year, name)));
+            }
+        }
+        sort_folder(&mut result);
+        Some(result)
+    }
+
+    fn file_items(&self, year: u64, pack: &str) -> Option<Vec<Box<dyn Item>>> {
+        let json = Self::read_json(&self.layout.pack_json(year, pack))?;
+        let mut result: Vec<Box<dyn Item>> = Vec::new();
+        for file in json["files"].as_array().into_iter().flatten() {
+            let filename = file["filename"].as_str().unwrap_or_default().to_string();
+            let location = file["file_location"].as_str().unwrap_or_default().to_string();
+            let Some(path) = self.layout.uri_path(year, pack, file["uri"].as_str().unwrap_or_default()) else {
+                continue;
+            };
+            let thumbnail = self.layout.uri_path(year, pack, file["thumbnail"].as_str().unwrap_or_default());
+            let item = LocalSixteenColorsFile::new(filename, location, path, thumbnail);
+            if let Some(FileFormat::Archive(format)) = FileFormat::from_extension(&item.filename) {
+                result.push(Box::new(ArchiveContainer::new(Box::new(item), format)));
+            } else {
+                result.push(Box::new(item));
+            }
+        }
+        sort_folder(&mut result);
+        Some(result)
+    }
+}
+
+/// A mirrored year folder
+pub struct LocalSixteenColorsYear {
+    mirror: LocalMirror,
+    pub year: u64,
+    pub packs: u64,
+}
+
+impl LocalSixteenColorsYear {
+    pub fn new(mirror: LocalMirror, year: u64, packs: u64) -> Self {
+        Self { mirror, year, packs }
+    }
+}
+
+#[async_trait]
+impl Item for LocalSixteenColorsYear {
+    fn get_label(&self) -> String {
+        fl!(crate::LANGUAGE_LOADER, "label-sixteencolors_year", year = self.year, packs = self.packs)
+            .chars()
+            .filter(|c| c.is_ascii())
+            .collect::<String>()
+    }
+
+    fn get_file_path(&self) -> String {
+        self.year.to_string()
+    }
+
+    fn is_container(&self) -> bool {
+        true
+    }
+
+    fn get_file_icon(&self) -> FileIcon {
+        FileIcon::FolderData
+    }
+
+    async fn get_thumbnail_preview(&self, _cancel_token: &CancellationToken) -> Option<RgbaData> {
+        Some(render_year_thumbnail(self.year))
+    }
+
+    async fn get_subitems(&self, _cancel_token: &CancellationToken) -> Result<Vec<Box<dyn Item>>, ItemError> {
+        self.mirror
+            .pack_items(self.year)
+            .ok_or_else(|| ItemError::NotFound(format!("Year {} is not mirrored", self.year)))
+    }
+
+    fn clone_box(&self) -> Box<dyn Item> {
+        Box::new(LocalSixteenColorsYear::new(self.mirror.clone(), self.year, self.packs))
+    }
+}
+
+/// A mirrored release pack
+pub struct LocalSixteenColorsPack {
+    mirror: LocalMirror,
+    pub year: u64,
+    pub name: String,
+}
+
+impl LocalSixteenColorsPack {
+    pub fn new(mirror: LocalMirror, year: u64, name: String) -> Self {
+        Self { mirror, year, name }
+    }
+}
+
+#[async_trait]
+impl Item for LocalSixteenColorsPack {
+    fn get_label(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_file_path(&self) -> String {
+        self.name.clone()
+    }
+
+    fn get_full_path(&self) -> Option<String> {
+        Some(self.mirror.layout.pack_dir(self.year, &self.name).to_string_lossy().replace('\\', "/"))
+    }
+
+    fn is_container(&self) -> bool {
+        true
+    }
+
+    fn get_file_icon(&self) -> FileIcon {
+        FileIcon::FolderData
+    }
+
+    async fn get_thumbnail_preview(&self, _cancel_token: &CancellationToken) -> Option<RgbaData> {
+        for tn in ["FILE_ID.DIZ.png", "FILE_ID.ANS.png"] {
+            let path = self.mirror.layout.pack_dir(self.year, &self.name).join("tn").join(tn);
+            if let Some(rgba) = std::fs::read(path).ok().and_then(|data| load_image_to_rgba(&data)) {
+                return Some(rgba);
+            }
+        }
+        let text = fl!(LANGUAGE_LOADER, "thumbnail-no-diz");
+        Some(scale_to_thumbnail_width(crate::items::create_text_preview(&text)))
+    }
+
+    async fn get_subitems(&self, _cancel_token: &CancellationToken) -> Result<Vec<Box<dyn Item>>, ItemError> {
+        self.mirror
+            .file_items(self.year, &self.name)
+            .ok_or_else(|| ItemError::NotFound(format!("Pack {} is not mirrored", self.name)))
+    }
+
+    fn clone_box(&self) -> Box<dyn Item> {
+        Box::new(LocalSixteenColorsPack::new(self.mirror.clone(), self.year, self.name.clone()))
+    }
+}
+
+/// A file of a mirrored pack
+pub struct LocalSixteenColorsFile {
+    pub filename: String,
+    pub location: String,
+    path: PathBuf,
+    thumbnail: Option<PathBuf>,
+}
+
+impl LocalSixteenColorsFile {
+    pub fn new(filename: String, location: String, path: PathBuf, thumbnail: Option<PathBuf>) -> Self {
+        Self {
+            filename,
+            location,
+            path,
+            thumbnail,
+        }
+    }
+}
+
+#[async_trait]
+impl Item for LocalSixteenColorsFile {
+    fn get_label(&self) -> String {
+        self.filename.clone()
+    }
+
+    fn get_file_path(&self) -> String {
+        // Same as the live file, so selections survive switching between live and mirror
+        format!("{}/{}", self.location, self.filename)
+    }
+
+    fn get_full_path(&self) -> Option<String> {
+        Some(self.path.to_string_lossy().replace('\\', "/"))
+    }
+
+    fn size(&self) -> Option<u64> {
+        std::fs::metadata(&self.path).ok().map(|m| m.len())
+    }
+
+    async fn get_thumbnail_preview(&self, _cancel_token: &CancellationToken) -> Option<RgbaData> {
+        // Without a mirrored thumbnail the preview is rendered from the file itself
+        let data = tokio::fs::read(self.thumbnail.as_ref()?).await.ok()?;
+        load_image_to_rgba(&data)
+    }
+
+    async fn read_data(&self) -> Result<Vec<u8>, ItemError> {
+        tokio::fs::read(&self.path)
+            .await
+            .map_err(|e| ItemError::Io(format!("Failed to read mirrored file {:?}: {}", self.path, e)))
+    }
+
+    fn clone_box(&self) -> Box<dyn Item> {
+        Box::new(LocalSixteenColorsFile::new(
+            self.filename.clone(),
+            self.location.clone(),
+            self.path.clone(),
+            self.thumbnail.clone(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn browses_partial_mirror() {
+        let dir = std::env::temp_dir().join(format!("icy_view_local_mirror_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let layout = MirrorLayout::new(&dir);
+        std::fs::create_dir_all(layout.pack_dir(1996, "acid-0596").join("raw")).unwrap();
+        std::fs::write(layout.years_json(), r#"[{"year":1996,"packs":2},{"year":1997,"packs":1}]"#).unwrap();
+        std::fs::write(
+            layout.packs_json(1996),
+            r#"[{"filename":"acid-0596.zip","month":5,"year":1996,"name":"acid-0596"},{"filename":"ice-9605.zip","month":5,"year":1996,"name":"ice-9605"}]"#,
+        )
+        .unwrap();
+        std::fs::write(
+            layout.pack_json(1996, "acid-0596"),
+            r#"{"files":[{"filename":"LOGO.ANS","file_location":"acid-0596","uri":"/pack/acid-0596/raw/LOGO.ANS","thumbnail":""}]}"#,
+        )
+        .unwrap();
+        std::fs::write(layout.pack_dir(1996, "acid-0596").join("raw/LOGO.ANS"), b"logo").unwrap();
+
+        let mirror = LocalMirror::new(&dir);
+        assert!(mirror.contains(""));
+        assert!(mirror.contains("1996/acid-0596"));
+        assert!(!mirror.contains("1996/ice-9605"));
+        assert!(!mirror.contains("1997"));
+
+        let years = mirror.get_items("").unwrap();
+        assert_eq!(years.len(), 2);
+        assert_eq!(years[0].get_file_path(), "1997");
+
+        let packs = mirror.get_items("/1996").unwrap();
+        assert_eq!(packs.len(), 2);
+
+        let files = mirror.get_items("1996/acid-0596").unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].get_label(), "LOGO.ANS");
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        assert_eq!(runtime.block_on(files[0].read_data()).unwrap(), b"logo");
+
+        assert!(mirror.get_items("1997").is_none());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
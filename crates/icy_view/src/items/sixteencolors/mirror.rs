@@ -0,0 +1,465 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use crate::items::ItemError;
+
+use super::{SixteenColorsProvider, API_PATH, MAIN_PATH};
+
+/// Timeout for a single mirror download - pack archives can be several megabytes
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Directory layout of a local 16colo.rs mirror
+///
+/// ```text
+/// <root>/years.json                    API response of /year
+/// <root>/<year>/packs.json             API response of /year/<year>
+/// <root>/<year>/<pack>/pack.json       API response of /pack/<pack>
+/// <root>/<year>/<pack>/<archive>       original release archive
+/// <root>/<year>/<pack>/raw/, tn/       files and thumbnails, by their URI below /pack/<pack>/
+/// ```
+///
+/// JSON responses are stored unmodified so the local provider can read the same metadata
+/// the live provider gets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirrorLayout {
+    root: PathBuf,
+}
+
+impl MirrorLayout {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn years_json(&self) -> PathBuf {
+        self.root.join("years.json")
+    }
+
+    pub fn year_dir(&self, year: u64) -> PathBuf {
+        self.root.join(year.to_string())
+    }
+
+    pub fn packs_json(&self, year: u64) -> PathBuf {
+        self.year_dir(year).join("packs.json")
+    }
+
+    pub fn pack_dir(&self, year: u64, pack: &str) -> PathBuf {
+        self.year_dir(year).join(pack)
+    }
+
+    pub fn pack_json(&self, year: u64, pack: &str) -> PathBuf {
+        self.pack_dir(year, pack).join("pack.json")
+    }
+
+    /// Location of the original release archive
+    pub fn archive_path(&self, year: u64, pack: &str, filename: &str) -> Option<PathBuf> {
+        let name = safe_relative_path(filename)?;
+        Some(self.pack_dir(year, pack).join(name))
+    }
+
+    /// Location of a file or thumbnail URI (`/pack/<pack>/raw/FILE.ANS`) inside the pack folder
+    ///
+    /// Returns None for URIs that would leave the pack folder.
+    pub fn uri_path(&self, year: u64, pack: &str, uri: &str) -> Option<PathBuf> {
+        let uri = uri.split(['?', '#']).next().unwrap_or_default();
+        let prefix = format!("/pack/{}/", pack);
+        let relative = uri.strip_prefix(&prefix)?;
+        Some(self.pack_dir(year, pack).join(safe_relative_path(relative)?))
+    }
+}
+
+/// Turn a `/` separated path into a relative path, rejecting anything that could escape
+fn safe_relative_path(path: &str) -> Option<PathBuf> {
+    let mut result = PathBuf::new();
+    for component in path.split('/') {
+        if component.is_empty() || component == "." || component == ".." || component.contains(['\\', ':']) {
+            return None;
+        }
+        result.push(component);
+    }
+    if result.as_os_str().is_empty() {
+        None
+    } else {
+        Some(result)
+    }
+}
+
+/// Counters of a mirror run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MirrorStats {
+    /// Packs whose metadata was written
+    pub packs: usize,
+    /// Files, thumbnails and archives downloaded
+    pub downloaded: usize,
+    /// Files already on disk from an earlier run
+    pub skipped: usize,
+    /// Downloads that failed - running the mirror again retries them
+    pub failed: usize,
+}
+
+impl std::ops::AddAssign for MirrorStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.packs += rhs.packs;
+        self.downloaded += rhs.downloaded;
+        self.skipped += rhs.skipped;
+        self.failed += rhs.failed;
+    }
+}
+
+/// Downloads years or single packs of 16colo.rs into a [`MirrorLayout`]
+///
+/// Files already present are not downloaded again, so an interrupted mirror can simply be
+/// restarted. Metadata is always refreshed.
+pub struct SixteenColorsMirror {
+    layout: MirrorLayout,
+    api_path: String,
+    main_path: String,
+    archives: bool,
+    thumbnails: bool,
+    client: reqwest::Client,
+}
+
+impl SixteenColorsMirror {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(DOWNLOAD_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+        Self {
+            layout: MirrorLayout::new(root),
+            api_path: API_PATH.to_string(),
+            main_path: MAIN_PATH.to_string(),
+            archives: true,
+            thumbnails: true,
+            client,
+        }
+    }
+
+    /// Use another server, e.g. a local stand-in
+    pub fn with_urls(mut self, api_path: impl Into<String>, main_path: impl Into<String>) -> Self {
+        self.api_path = api_path.into();
+        self.main_path = main_path.into();
+        self
+    }
+
+    /// Whether the original release archives are downloaded (default: true)
+    pub fn with_archives(mut self, archives: bool) -> Self {
+        self.archives = archives;
+        self
+    }
+
+    /// Whether the 16colo.rs thumbnails are downloaded (default: true)
+    pub fn with_thumbnails(mut self, thumbnails: bool) -> Self {
+        self.thumbnails = thumbnails;
+        self
+    }
+
+    pub fn layout(&self) -> &MirrorLayout {
+        &self.layout
+    }
+
+    /// Mirror a 16colo.rs path - `1996` mirrors a whole year, `1996/acid-0596` a single pack
+    pub async fn mirror_path(&self, path: &str, cancel_token: &CancellationToken) -> Result<MirrorStats, ItemError> {
+        match SixteenColorsProvider::parse_path(path) {
+            (Some(year), None) => self.mirror_year(year, cancel_token).await,
+            (Some(year), Some(pack)) => self.mirror_pack(year, &pack, cancel_token).await,
+            _ => Err(ItemError::NotFound(format!("Not a year or pack: {}", path))),
+        }
+    }
+
+    /// Mirror every pack of a year
+    pub async fn mirror_year(&self, year: u64, cancel_token: &CancellationToken) -> Result<MirrorStats, ItemError> {
+        let packs = self.mirror_year_metadata(year).await?;
+        let mut stats = MirrorStats::default();
+        for pack in packs.as_array().into_iter().flatten() {
+            if cancel_token.is_cancelled() {
+                return Err(ItemError::Cancelled);
+            }
+            let name = pack["name"].as_str().unwrap_or_default();
+            let filename = pack["filename"].as_str().unwrap_or_default();
+            if name.is_empty() {
+                continue;
+            }
+            match self.mirror_pack_files(year, name, filename, cancel_token).await {
+                Ok(pack_stats) => stats += pack_stats,
+                Err(ItemError::Cancelled) => return Err(ItemError::Cancelled),
+                Err(err) => {
+                    log::error!("Failed to mirror pack {}: {}", name, err);
+                    stats.failed += 1;
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Mirror a single pack (and the metadata of its year, so it can be browsed)
+    pub async fn mirror_pack(&self, year: u64, name: &str, cancel_token: &CancellationToken) -> Result<MirrorStats, ItemError> {
+        let packs = self.mirror_year_metadata(year).await?;
+        let Some(pack) = packs.as_array().into_iter().flatten().find(|p| p["name"].as_str() == Some(name)) else {
+            return Err(ItemError::NotFound(format!("Pack {} not found in {}", name, year)));
+        };
+        let filename = pack["filename"].as_str().unwrap_or_default();
+        self.mirror_pack_files(year, name, filename, cancel_token).await
+    }
+
+    /// Write years.json and `<year>/packs.json`, returning the pack list
+    async fn mirror_year_metadata(&self, year: u64) -> Result<Value, ItemError> {
+        let years = self.fetch(&format!("{}/year?rows=0", self.api_path)).await?;
+        parse_json(&years)?;
+        write_file(&self.layout.years_json(), &years)?;
+
+        let packs = self.fetch(&format!("{}/year/{}?rows=0", self.api_path, year)).await?;
+        let json = parse_json(&packs)?;
+        write_file(&self.layout.packs_json(year), &packs)?;
+        Ok(json)
+    }
+
+    async fn mirror_pack_files(&self, year: u64, name: &str, filename: &str, cancel_token: &CancellationToken) -> Result<MirrorStats, ItemError> {
+        let mut stats = MirrorStats::default();
+        let data = self.fetch(&format!("{}/pack/{}?rows=0", self.api_path, name)).await?;
+        let json = parse_json(&data)?;
+
+        if self.archives && !filename.is_empty() {
+            if let Some(path) = self.layout.archive_path(year, name, filename) {
+                let url = format!("{}/archive/{}/{}", self.main_path, year, filename);
+                self.download(&url, &path, &mut stats).await;
+            }
+        }
+
+        for file in json["files"].as_array().into_iter().flatten() {
+            if cancel_token.is_cancelled() {
+                return Err(ItemError::Cancelled);
+            }
+            let mut uris = vec![file["uri"].as_str().unwrap_or_default()];
+            if self.thumbnails {
+                uris.push(file["thumbnail"].as_str().unwrap_or_default());
+            }
+            for uri in uris.into_iter().filter(|uri| !uri.is_empty()) {
+                let Some(path) = self.layout.uri_path(year, name, uri) else {
+                    log::warn!("Skipping 16colo.rs URI outside of pack {}: {}", name, uri);
+                    continue;
+                };
+                self.download(&format!("{}{}", self.main_path, uri), &path, &mut stats).await;
+            }
+        }
+
+        if self.thumbnails {
+            // Pack thumbnails, shown for the pack folder - most packs only have one of them
+            for tn in ["FILE_ID.DIZ.png", "FILE_ID.ANS.png"] {
+                let uri = format!("/pack/{}/tn/{}", name, tn);
+                if let Some(path) = self.layout.uri_path(year, name, &uri) {
+                    let mut tn_stats = MirrorStats::default();
+                    self.download(&format!("{}{}", self.main_path, uri), &path, &mut tn_stats).await;
+                    stats.downloaded += tn_stats.downloaded;
+                    stats.skipped += tn_stats.skipped;
+                }
+            }
+        }
+
+        // pack.json is written last - the local provider treats a pack as mirrored once it exists
+        write_file(&self.layout.pack_json(year, name), &data)?;
+        stats.packs += 1;
+        log::info!(
+            "Mirrored pack {} ({} downloaded, {} skipped, {} failed)",
+            name,
+            stats.downloaded,
+            stats.skipped,
+            stats.failed
+        );
+        Ok(stats)
+    }
+
+    async fn download(&self, url: &str, path: &Path, stats: &mut MirrorStats) {
+        if path.exists() {
+            stats.skipped += 1;
+            return;
+        }
+        match self.fetch(url).await.and_then(|data| write_file(path, &data)) {
+            Ok(()) => stats.downloaded += 1,
+            Err(err) => {
+                log::warn!("Failed to mirror {}: {}", url, err);
+                stats.failed += 1;
+            }
+        }
+    }
+
+    async fn fetch(&self, url: &str) -> Result<Vec<u8>, ItemError> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| ItemError::Network(format!("Connection error: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(ItemError::Network(format!("HTTP {} for {}", response.status(), url)));
+        }
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| ItemError::Network(format!("Failed to read response: {}", e)))?;
+        Ok(bytes.to_vec())
+    }
+}
+
+fn parse_json(data: &[u8]) -> Result<Value, ItemError> {
+    serde_json::from_slice(data).map_err(|e| ItemError::Parse(format!("Invalid 16colo.rs JSON: {}", e)))
+}
+
+/// Write through a temporary file so an interrupted run never leaves a truncated file behind
+fn write_file(path: &Path, data: &[u8]) -> Result<(), ItemError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| ItemError::Io(format!("Failed to create {:?}: {}", parent, e)))?;
+    }
+    let tmp = path.with_extension("part");
+    std::fs::write(&tmp, data).map_err(|e| ItemError::Io(format!("Failed to write {:?}: {}", tmp, e)))?;
+    std::fs::rename(&tmp, path).map_err(|e| ItemError::Io(format!("Failed to write {:?}: {}", path, e)))
+}
+
+/// Mirror the given 16colo.rs paths into `root`, blocking until done
+pub fn run_mirror(root: &Path, paths: &[String]) -> Result<MirrorStats, ItemError> {
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| ItemError::Other(format!("Failed to start runtime: {}", e)))?;
+    let mirror = SixteenColorsMirror::new(root);
+    let cancel_token = CancellationToken::new();
+    runtime.block_on(async {
+        let mut stats = MirrorStats::default();
+        for path in paths {
+            stats += mirror.mirror_path(path, &cancel_token).await?;
+        }
+        Ok(stats)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    /// Minimal HTTP server answering GET requests from a fixed table
+    fn serve(routes: HashMap<String, Vec<u8>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    continue;
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                if reader.read_line(&mut request_line).is_err() {
+                    continue;
+                }
+                // Skip the headers
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                    line.clear();
+                }
+                let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+                let response = match routes.get(&path) {
+                    Some(body) => {
+                        let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+                        response.extend_from_slice(body);
+                        response
+                    }
+                    None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                };
+                let _ = stream.write_all(&response);
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("icy_view_mirror_test_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn routes() -> HashMap<String, Vec<u8>> {
+        let mut routes = HashMap::new();
+        routes.insert("/v0/year?rows=0".to_string(), br#"[{"year":1996,"packs":2}]"#.to_vec());
+        routes.insert(
+            "/v0/year/1996?rows=0".to_string(),
+            br#"[{"filename":"acid-0596.zip","month":5,"year":1996,"name":"acid-0596"},{"filename":"ice-9605.zip","month":5,"year":1996,"name":"ice-9605"}]"#
+                .to_vec(),
+        );
+        routes.insert(
+            "/v0/pack/acid-0596?rows=0".to_string(),
+            br#"{"files":[{"filename":"LOGO.ANS","file_location":"acid-0596","uri":"/pack/acid-0596/raw/LOGO.ANS","thumbnail":"/pack/acid-0596/tn/LOGO.ANS.png"},{"filename":"EVIL.ANS","file_location":"acid-0596","uri":"/pack/acid-0596/../../EVIL.ANS","thumbnail":""}]}"#.to_vec(),
+        );
+        routes.insert(
+            "/v0/pack/ice-9605?rows=0".to_string(),
+            br#"{"files":[{"filename":"ICE.ANS","file_location":"ice-9605","uri":"/pack/ice-9605/raw/ICE.ANS","thumbnail":""}]}"#.to_vec(),
+        );
+        routes.insert("/archive/1996/acid-0596.zip".to_string(), b"PK-archive".to_vec());
+        routes.insert("/pack/acid-0596/raw/LOGO.ANS".to_string(), b"logo data".to_vec());
+        routes.insert("/pack/acid-0596/tn/LOGO.ANS.png".to_string(), b"png".to_vec());
+        routes.insert("/pack/ice-9605/raw/ICE.ANS".to_string(), b"ice data".to_vec());
+        routes
+    }
+
+    #[test]
+    fn uri_paths_stay_inside_pack() {
+        let layout = MirrorLayout::new("/mirror");
+        assert_eq!(
+            layout.uri_path(1996, "acid-0596", "/pack/acid-0596/raw/LOGO.ANS"),
+            Some(PathBuf::from("/mirror/1996/acid-0596/raw/LOGO.ANS"))
+        );
+        assert_eq!(layout.uri_path(1996, "acid-0596", "/pack/acid-0596/../x"), None);
+        assert_eq!(layout.uri_path(1996, "acid-0596", "/pack/other/raw/LOGO.ANS"), None);
+        assert_eq!(layout.archive_path(1996, "acid-0596", "../acid.zip"), None);
+    }
+
+    #[test]
+    fn mirrors_pack_and_resumes() {
+        let base = serve(routes());
+        let dir = test_dir("pack");
+        let mirror = SixteenColorsMirror::new(&dir).with_urls(format!("{}/v0", base), base.clone());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let cancel_token = CancellationToken::new();
+
+        let stats = runtime.block_on(mirror.mirror_path("1996/acid-0596", &cancel_token)).unwrap();
+        assert_eq!(stats.packs, 1);
+        // archive, file and file thumbnail - the pack thumbnails are missing on the server
+        assert_eq!(stats.downloaded, 3);
+        assert_eq!(std::fs::read(dir.join("1996/acid-0596/raw/LOGO.ANS")).unwrap(), b"logo data");
+        assert_eq!(std::fs::read(dir.join("1996/acid-0596/acid-0596.zip")).unwrap(), b"PK-archive");
+        assert!(dir.join("years.json").exists());
+        assert!(dir.join("1996/packs.json").exists());
+        assert!(dir.join("1996/acid-0596/pack.json").exists());
+        assert!(!dir.join("1996/ice-9605").exists());
+        assert!(!dir.join("EVIL.ANS").exists());
+
+        let again = runtime.block_on(mirror.mirror_path("1996/acid-0596", &cancel_token)).unwrap();
+        assert_eq!(again.downloaded, 0);
+        assert_eq!(again.skipped, 3);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn mirrors_whole_year() {
+        let base = serve(routes());
+        let dir = test_dir("year");
+        let mirror = SixteenColorsMirror::new(&dir)
+            .with_urls(format!("{}/v0", base), base.clone())
+            .with_archives(false)
+            .with_thumbnails(false);
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let stats = runtime.block_on(mirror.mirror_path("1996", &CancellationToken::new())).unwrap();
+        assert_eq!(stats.packs, 2);
+        assert_eq!(stats.downloaded, 2);
+        assert!(dir.join("1996/ice-9605/raw/ICE.ANS").exists());
+        assert!(!dir.join("1996/acid-0596/acid-0596.zip").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
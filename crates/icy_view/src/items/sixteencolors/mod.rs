@@ -1,5 +1,7 @@
 mod cache;
 mod file;
+mod local;
+mod mirror;
 mod pack;
 mod provider;
 mod root;
@@ -7,6 +9,8 @@ mod year;
 
 pub use cache::*;
 pub use file::*;
+pub use local::*;
+pub use mirror::*;
 pub use pack::*;
 pub use provider::*;
 pub use root::*;
@@ -23,3 +27,16 @@ pub(crate) fn get_cache() -> SharedSixteenColorsCache {
     static CACHE: OnceLock<SharedSixteenColorsCache> = OnceLock::new();
     CACHE.get_or_init(create_shared_cache).clone()
 }
+
+/// Mirror directory preferred over the live site for everything it contains
+static MIRROR: parking_lot::RwLock<Option<LocalMirror>> = parking_lot::RwLock::new(None);
+
+/// Set the local mirror directory, None disables the mirror
+pub(crate) fn set_mirror_dir(dir: Option<std::path::PathBuf>) {
+    *MIRROR.write() = dir.filter(|dir| dir.is_dir()).map(LocalMirror::new);
+}
+
+/// The local mirror, if one is configured
+pub(crate) fn get_mirror() -> Option<LocalMirror> {
+    MIRROR.read().clone()
+}
@@ -2,7 +2,7 @@ use icy_engine::formats::FileFormat;
 
 use crate::items::{sort_folder, ArchiveContainer, Item, ItemError};
 
-use super::{cache::fetch_json_async, get_cache, SixteenColorsFile, SixteenColorsPack, SixteenColorsYear, API_PATH};
+use super::{cache::fetch_json_async, get_cache, get_mirror, SixteenColorsFile, SixteenColorsPack, SixteenColorsYear, API_PATH};
 
 /// Provider for 16colors.rs web browsing
 /// Uses the global cache for all API responses
@@ -17,7 +17,7 @@ impl SixteenColorsProvider {
     /// "" or "/" -> root (years)
     /// "2024" or "/2024" -> year (packs)
     /// "2024/pack_name" or "/2024/pack_name" -> pack (files)
-    fn parse_path(path: &str) -> (Option<u64>, Option<String>) {
+    pub(super) fn parse_path(path: &str) -> (Option<u64>, Option<String>) {
         let trimmed = path.trim_matches('/');
         if trimmed.is_empty() {
             return (None, None);
@@ -141,6 +141,9 @@ impl SixteenColorsProvider {
     /// Returns true if the path is valid or could be valid (not yet cached)
     /// Returns false only if we have cached data that proves the path is invalid
     pub fn validate_path(path: &str) -> bool {
+        if get_mirror().is_some_and(|mirror| mirror.contains(path)) {
+            return true;
+        }
         let (year, pack) = Self::parse_path(path);
 
         match (year, pack) {
@@ -193,6 +196,10 @@ impl SixteenColorsProvider {
 
     /// Get items at a given 16colors path
     pub async fn get_items(&self, path: &str) -> Result<Vec<Box<dyn Item>>, ItemError> {
+        // Prefer the local mirror - it works offline
+        if let Some(items) = get_mirror().and_then(|mirror| mirror.get_items(path)) {
+            return Ok(items);
+        }
         let (year, pack) = Self::parse_path(path);
 
         match (year, pack) {
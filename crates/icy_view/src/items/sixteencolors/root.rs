@@ -4,7 +4,7 @@ use tokio_util::sync::CancellationToken;
 use crate::items::{FileIcon, Item, ItemError};
 use crate::thumbnail::{scale_to_thumbnail_width, RgbaData};
 
-use super::{cache::fetch_json_async, get_cache, SixteenColorsYear, API_PATH};
+use super::{cache::fetch_json_async, get_cache, get_mirror, SixteenColorsYear, API_PATH};
 
 /// Root folder for 16colors.rs browsing
 pub struct SixteenColorsRoot {}
@@ -43,6 +43,9 @@ impl Item for SixteenColorsRoot {
     }
 
     async fn get_subitems(&self, _cancel_token: &CancellationToken) -> Result<Vec<Box<dyn Item>>, ItemError> {
+        if let Some(items) = get_mirror().and_then(|mirror| mirror.get_items("")) {
+            return Ok(items);
+        }
         let url = format!("{}/year?rows=0", API_PATH);
         let cache = get_cache();
         let json = fetch_json_async(&cache, &url).await?;
@@ -19,7 +19,7 @@ lazy_static::lazy_static! {
 }
 
 /// Render text using TDF font to a TextBuffer and return as RgbaData
-fn render_year_thumbnail(year: u64) -> RgbaData {
+pub(super) fn render_year_thumbnail(year: u64) -> RgbaData {
     let fonts = &TDF_FONTS;
     if fonts.is_empty() {
         if let Err(err) = Font::load(ZETRAX_TDF) {
@@ -169,6 +169,14 @@ pub struct Args {
     /// Custom configuration directory path
     #[clap(long, value_name = "DIR", help = i18n_embed_fl::fl!(LANGUAGE_LOADER, "arg-config-dir-help"))]
     config_dir: Option<PathBuf>,
+
+    /// Render thumbnails of these folders into the disk cache and exit
+    #[clap(long, value_name = "DIR", num_args = 1.., help = i18n_embed_fl::fl!(LANGUAGE_LOADER, "arg-warm-thumbnails-help"))]
+    warm_thumbnails: Vec<PathBuf>,
+
+    /// Download 16colo.rs years or packs into the local mirror and exit
+    #[clap(long = "mirror-16colors", value_name = "YEAR[/PACK]", num_args = 1.., help = i18n_embed_fl::fl!(LANGUAGE_LOADER, "arg-mirror-16colors-help"))]
+    mirror_16colors: Vec<String>,
 }
 
 fn main() {
@@ -192,6 +200,28 @@ fn main() {
 
     log::info!("Starting iCY VIEW {}", *VERSION);
 
+    if !args.warm_thumbnails.is_empty() {
+        let rendered = ui::warm_disk_cache(&args.warm_thumbnails);
+        log::info!("Rendered {} thumbnails into the disk cache", rendered);
+        return;
+    }
+
+    if !args.mirror_16colors.is_empty() {
+        let mirror_dir = Options::load_options().sixteencolors_mirror();
+        match items::run_mirror(&mirror_dir, &args.mirror_16colors) {
+            Ok(stats) => log::info!(
+                "Mirrored {} packs into {:?}: {} files downloaded, {} already present, {} failed",
+                stats.packs,
+                mirror_dir,
+                stats.downloaded,
+                stats.skipped,
+                stats.failed
+            ),
+            Err(err) => log::error!("16colo.rs mirror failed: {}", err),
+        }
+        return;
+    }
+
     icy_ui::daemon(
         move || {
             if let Some(ref path) = args.path {
@@ -249,6 +249,14 @@ pub struct Options {
     /// Default path for file export
     #[serde(default)]
     pub export_path: String,
+
+    /// Folders kept in the SAUCE search index
+    #[serde(default)]
+    pub index_folders: Vec<String>,
+
+    /// Local 16colo.rs mirror, browsed instead of the live site where it has content
+    #[serde(default)]
+    pub sixteencolors_mirror: String,
 }
 
 impl Default for Options {
@@ -263,6 +271,8 @@ impl Default for Options {
             monitor_settings: MonitorSettings::default(),
             external_commands: Default::default(),
             export_path: String::new(),
+            index_folders: Vec::new(),
+            sixteencolors_mirror: String::new(),
         }
     }
 }
@@ -372,6 +382,31 @@ impl Options {
         }
     }
 
+    /// Returns the folders to index for SAUCE search
+    pub fn index_folders(&self) -> Vec<PathBuf> {
+        self.index_folders
+            .iter()
+            .filter(|f| !f.trim().is_empty())
+            .map(|f| PathBuf::from(f.trim()))
+            .collect()
+    }
+
+    /// Returns the 16colo.rs mirror directory, falling back to default if not set
+    pub fn sixteencolors_mirror(&self) -> PathBuf {
+        if self.sixteencolors_mirror.trim().is_empty() {
+            Self::default_sixteencolors_mirror()
+        } else {
+            PathBuf::from(self.sixteencolors_mirror.trim())
+        }
+    }
+
+    /// Returns the default 16colo.rs mirror directory (inside the user's data folder)
+    pub fn default_sixteencolors_mirror() -> PathBuf {
+        directories::BaseDirs::new()
+            .map(|dirs| dirs.data_dir().join("icy_view").join("16colors"))
+            .unwrap_or_else(|| get_config_dir().join("16colors"))
+    }
+
     /// Returns the default export directory (user's documents folder)
     pub fn default_export_directory() -> PathBuf {
         if let Some(user_dirs) = directories::UserDirs::new() {
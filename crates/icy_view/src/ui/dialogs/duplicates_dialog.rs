@@ -0,0 +1,596 @@
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+
+use i18n_embed_fl::fl;
+use icy_engine::formats::{FileFormat, LoadData};
+use icy_engine::{Rectangle, RenderOptions, Selection, TextPane};
+use icy_engine_gui::settings::left_label;
+use icy_engine_gui::ui::{
+    browse_button, button_row_with_left, confirm_delete, danger_button, dialog_area, modal_container, primary_button, secondary_button, section_header,
+    separator, DialogResult, StateResult, DIALOG_SPACING, TEXT_SIZE_NORMAL, TEXT_SIZE_SMALL,
+};
+use icy_engine_gui::{dialog_wrapper, Dialog, DialogAction};
+use icy_ui::{
+    widget::{button, checkbox, column, container, image as iced_image, row, scrollable, slider, text, text_input, Space},
+    Alignment, Element, Event, Length,
+};
+
+use crate::items::{read_indexed_file, DuplicateGroup, DuplicateScanner};
+
+/// Wider than the other dialogs - two previews are shown side by side
+const DIALOG_WIDTH: f32 = 900.0;
+const LIST_HEIGHT: f32 = 220.0;
+const PREVIEW_HEIGHT: f32 = 260.0;
+/// Only the top of a piece is rendered for the comparison
+const PREVIEW_MAX_ROWS: i32 = 100;
+
+#[derive(Debug, Clone)]
+pub enum DuplicatesDialogMessage {
+    UpdateFolders(String),
+    BrowseFolder,
+    UpdateThreshold(f32),
+    Scan,
+    StopScan,
+    SelectGroup(usize),
+    SelectMember(usize),
+    ToggleDelete(usize, bool),
+    KeepOnly(usize),
+    /// Ask before moving the marked files to the trash
+    DeleteMarked,
+    /// Answer of the confirmation, `true` moves the marked files to the trash
+    ConfirmDelete(bool),
+    Close,
+}
+
+/// Rendered preview for (group, preview slot)
+type PreviewResult = (usize, usize, Option<iced_image::Handle>);
+
+#[dialog_wrapper]
+pub struct DuplicatesDialogState {
+    folders: String,
+    /// Similarity threshold in percent
+    threshold: f32,
+    scanner: Option<DuplicateScanner>,
+    groups: Vec<DuplicateGroup>,
+    /// Delete marks, parallel to `groups[..].members`
+    marked: Vec<Vec<bool>>,
+    /// Whether a member is a plain file on disk, checked once per scan
+    disk_files: Vec<Vec<bool>>,
+    selected_group: Option<usize>,
+    /// Member compared against the first one of the selected group
+    compare_member: usize,
+    previews: [Option<iced_image::Handle>; 2],
+    preview_rx: Option<Receiver<PreviewResult>>,
+    scanned_once: bool,
+}
+
+impl DuplicatesDialogState {
+    pub fn new(folders: Vec<PathBuf>) -> Self {
+        Self {
+            folders: folders.iter().map(|f| f.to_string_lossy().to_string()).collect::<Vec<_>>().join(";"),
+            threshold: 90.0,
+            scanner: None,
+            groups: Vec::new(),
+            marked: Vec::new(),
+            disk_files: Vec::new(),
+            selected_group: None,
+            compare_member: 1,
+            previews: [None, None],
+            preview_rx: None,
+            scanned_once: false,
+        }
+    }
+
+    fn folders(&self) -> Vec<PathBuf> {
+        self.folders.split(';').map(str::trim).filter(|f| !f.is_empty()).map(PathBuf::from).collect()
+    }
+
+    fn is_scanning(&self) -> bool {
+        self.scanner.as_ref().is_some_and(|s| s.is_running())
+    }
+
+    fn marked_count(&self) -> usize {
+        self.marked.iter().flatten().filter(|m| **m).count()
+    }
+
+    pub fn handle_message(&mut self, message: DuplicatesDialogMessage) -> StateResult<()> {
+        match message {
+            DuplicatesDialogMessage::UpdateFolders(folders) => {
+                self.folders = folders;
+            }
+            DuplicatesDialogMessage::BrowseFolder => {
+                if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                    let mut folders = self.folders();
+                    folders.push(folder);
+                    self.folders = folders.iter().map(|f| f.to_string_lossy().to_string()).collect::<Vec<_>>().join(";");
+                }
+            }
+            DuplicatesDialogMessage::UpdateThreshold(threshold) => {
+                self.threshold = threshold;
+            }
+            DuplicatesDialogMessage::Scan => {
+                let folders = self.folders();
+                if !folders.is_empty() {
+                    self.set_groups(Vec::new());
+                    self.scanner = Some(DuplicateScanner::spawn(folders, self.threshold / 100.0));
+                    self.scanned_once = true;
+                }
+            }
+            DuplicatesDialogMessage::StopScan => {
+                self.scanner = None;
+            }
+            DuplicatesDialogMessage::SelectGroup(group) => {
+                self.select(group, 1);
+            }
+            DuplicatesDialogMessage::SelectMember(member) => {
+                if let Some(group) = self.selected_group {
+                    if member > 0 {
+                        self.select(group, member);
+                    }
+                }
+            }
+            DuplicatesDialogMessage::ToggleDelete(member, delete) => {
+                if let Some(marks) = self.selected_group.and_then(|g| self.marked.get_mut(g)) {
+                    // Every group keeps at least one file
+                    let keeps_other = marks.iter().enumerate().any(|(i, mark)| i != member && !mark);
+                    if let Some(mark) = marks.get_mut(member) {
+                        *mark = delete && keeps_other;
+                    }
+                }
+            }
+            DuplicatesDialogMessage::KeepOnly(keep) => {
+                if let Some(group) = self.selected_group {
+                    for (i, is_disk_file) in self.disk_files[group].iter().enumerate() {
+                        self.marked[group][i] = i != keep && *is_disk_file;
+                    }
+                }
+            }
+            DuplicatesDialogMessage::DeleteMarked => {
+                // The wrapper asks for confirmation first
+            }
+            DuplicatesDialogMessage::ConfirmDelete(confirmed) => {
+                if confirmed {
+                    self.delete_marked();
+                }
+            }
+            DuplicatesDialogMessage::Close => return StateResult::Close,
+        }
+        StateResult::None
+    }
+
+    fn set_groups(&mut self, groups: Vec<DuplicateGroup>) {
+        self.marked = groups.iter().map(|g| vec![false; g.members.len()]).collect();
+        self.disk_files = groups.iter().map(|g| g.members.iter().map(|m| m.is_disk_file()).collect()).collect();
+        self.groups = groups;
+        self.selected_group = None;
+        self.previews = [None, None];
+        self.preview_rx = None;
+        if !self.groups.is_empty() {
+            self.select(0, 1);
+        }
+    }
+
+    /// Select a group and start rendering the previews of its first and `member`th file
+    fn select(&mut self, group: usize, member: usize) {
+        let Some(members) = self.groups.get(group).map(|g| &g.members) else {
+            return;
+        };
+        let member = member.min(members.len() - 1);
+        if self.selected_group != Some(group) {
+            self.previews[0] = None;
+        }
+        self.previews[1] = None;
+        let mut paths = vec![(1, members[member].path.clone())];
+        if self.previews[0].is_none() {
+            paths.push((0, members[0].path.clone()));
+        }
+        self.selected_group = Some(group);
+        self.compare_member = member;
+
+        let (tx, rx) = channel();
+        self.preview_rx = Some(rx);
+        std::thread::spawn(move || {
+            for (slot, path) in paths {
+                if tx.send((group, slot, render_preview(&path))).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Move the marked files to the trash, groups with every file marked are left alone
+    fn delete_marked(&mut self) {
+        let mut groups = std::mem::take(&mut self.groups);
+        for (group, marks) in groups.iter_mut().zip(&self.marked) {
+            if marks.iter().all(|m| *m) {
+                log::warn!("Not deleting all copies of {}", group.members[0].path);
+                continue;
+            }
+            let mut keep = marks.iter().map(|m| !m);
+            group.members.retain(|member| {
+                if keep.next().unwrap_or(true) {
+                    return true;
+                }
+                match trash::delete(&member.path) {
+                    Ok(()) => {
+                        log::info!("Moved duplicate {} to the trash", member.path);
+                        false
+                    }
+                    Err(err) => {
+                        log::error!("Failed to move {} to the trash: {}", member.path, err);
+                        true
+                    }
+                }
+            });
+        }
+        groups.retain(|g| g.members.len() > 1);
+        let selected = self.selected_group;
+        self.set_groups(groups);
+        if let Some(group) = selected.filter(|g| *g < self.groups.len()) {
+            self.select(group, 1);
+        }
+    }
+
+    /// Pick up scan results and rendered previews
+    fn poll(&mut self) {
+        if let Some(groups) = self.scanner.as_ref().and_then(|s| s.take_result()) {
+            self.scanner = None;
+            self.set_groups(groups);
+        }
+        if let Some(rx) = &self.preview_rx {
+            loop {
+                match rx.try_recv() {
+                    Ok((group, slot, handle)) => {
+                        if Some(group) == self.selected_group {
+                            self.previews[slot] = handle;
+                        }
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.preview_rx = None;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    fn needs_polling(&self) -> bool {
+        self.scanner.is_some() || self.preview_rx.is_some()
+    }
+
+    fn status_text(&self) -> String {
+        if let Some(scanner) = &self.scanner {
+            fl!(crate::LANGUAGE_LOADER, "duplicates-scanning", files = scanner.scanned())
+        } else if !self.scanned_once {
+            String::new()
+        } else if self.groups.is_empty() {
+            fl!(crate::LANGUAGE_LOADER, "duplicates-none")
+        } else {
+            fl!(crate::LANGUAGE_LOADER, "duplicates-found", groups = self.groups.len())
+        }
+    }
+
+    fn group_label(group: &DuplicateGroup) -> String {
+        let name = file_name(&group.members[0].path).to_string();
+        let count = group.members.len();
+        if group.is_exact() {
+            fl!(crate::LANGUAGE_LOADER, "duplicates-group-exact", name = name, count = count)
+        } else {
+            let percent = group.members.iter().map(|m| m.similarity).fold(1.0f32, f32::min) * 100.0;
+            fl!(
+                crate::LANGUAGE_LOADER,
+                "duplicates-group-similar",
+                name = name,
+                count = count,
+                percent = (percent.floor() as u32)
+            )
+        }
+    }
+
+    pub fn view<'a, M: Clone + 'static>(&'a self, on_message: impl Fn(DuplicatesDialogMessage) -> M + Clone + 'static) -> Element<'a, M> {
+        let on_msg = on_message.clone();
+        let scan_button = if self.is_scanning() {
+            secondary_button(
+                fl!(crate::LANGUAGE_LOADER, "duplicates-stop"),
+                Some(on_message(DuplicatesDialogMessage::StopScan)),
+            )
+        } else {
+            primary_button(
+                fl!(crate::LANGUAGE_LOADER, "duplicates-scan"),
+                (!self.folders().is_empty()).then(|| on_message(DuplicatesDialogMessage::Scan)),
+            )
+        };
+
+        let settings = column![
+            row![
+                left_label(fl!(crate::LANGUAGE_LOADER, "duplicates-folders")),
+                text_input(&fl!(crate::LANGUAGE_LOADER, "duplicates-folders-placeholder"), &self.folders)
+                    .size(TEXT_SIZE_NORMAL)
+                    .width(Length::Fill)
+                    .on_input(move |s| on_msg(DuplicatesDialogMessage::UpdateFolders(s))),
+                browse_button(on_message(DuplicatesDialogMessage::BrowseFolder)),
+            ]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center),
+            row![
+                left_label(fl!(crate::LANGUAGE_LOADER, "duplicates-similarity")),
+                slider(50.0..=100.0, self.threshold, {
+                    let on_msg = on_message.clone();
+                    move |value| on_msg(DuplicatesDialogMessage::UpdateThreshold(value))
+                })
+                .step(1.0)
+                .width(Length::Fill),
+                text(format!("{}%", self.threshold.round() as u32)).width(Length::Fixed(48.0)),
+                scan_button,
+            ]
+            .spacing(DIALOG_SPACING)
+            .align_y(Alignment::Center),
+            text(self.status_text()).size(TEXT_SIZE_SMALL),
+        ]
+        .spacing(DIALOG_SPACING);
+
+        // Group list
+        let mut group_list = column![].spacing(2);
+        for (i, group) in self.groups.iter().enumerate() {
+            let is_selected = self.selected_group == Some(i);
+            group_list = group_list.push(
+                button(text(Self::group_label(group)).size(TEXT_SIZE_SMALL))
+                    .width(Length::Fill)
+                    .style(move |theme: &icy_ui::Theme, status| list_button_style(theme, status, is_selected))
+                    .on_press(on_message(DuplicatesDialogMessage::SelectGroup(i))),
+            );
+        }
+
+        // Members of the selected group
+        let mut member_list = column![].spacing(2);
+        if let Some(group_idx) = self.selected_group {
+            let group = &self.groups[group_idx];
+            for (i, member) in group.members.iter().enumerate() {
+                let is_compared = i == 0 || i == self.compare_member;
+                let label = if member.exact {
+                    member.path.clone()
+                } else {
+                    format!("{} ({}%)", member.path, (member.similarity * 100.0).floor() as u32)
+                };
+                let on_msg = on_message.clone();
+                let delete_box = if self.disk_files[group_idx][i] {
+                    checkbox(self.marked[group_idx][i]).on_toggle(move |checked| on_msg(DuplicatesDialogMessage::ToggleDelete(i, checked)))
+                } else {
+                    // Files inside archives can't be deleted individually
+                    checkbox(false)
+                };
+                member_list = member_list.push(
+                    row![
+                        delete_box.size(16),
+                        button(text(label).size(TEXT_SIZE_SMALL))
+                            .width(Length::Fill)
+                            .style(move |theme: &icy_ui::Theme, status| list_button_style(theme, status, is_compared))
+                            .on_press(on_message(DuplicatesDialogMessage::SelectMember(i))),
+                        secondary_button(
+                            fl!(crate::LANGUAGE_LOADER, "duplicates-keep-only"),
+                            Some(on_message(DuplicatesDialogMessage::KeepOnly(i)))
+                        ),
+                    ]
+                    .spacing(DIALOG_SPACING)
+                    .align_y(Alignment::Center),
+                );
+            }
+        }
+
+        let lists = row![
+            container(scrollable(group_list).height(Length::Fixed(LIST_HEIGHT))).width(Length::FillPortion(2)),
+            container(scrollable(member_list).height(Length::Fixed(LIST_HEIGHT))).width(Length::FillPortion(3)),
+        ]
+        .spacing(DIALOG_SPACING);
+
+        let previews = row![preview_image(&self.previews[0]), preview_image(&self.previews[1])].spacing(DIALOG_SPACING);
+
+        let content = column![
+            section_header(fl!(crate::LANGUAGE_LOADER, "duplicates-title")),
+            settings,
+            Space::new().height(DIALOG_SPACING),
+            lists,
+            Space::new().height(DIALOG_SPACING),
+            previews,
+        ]
+        .spacing(DIALOG_SPACING);
+
+        let marked = self.marked_count();
+        let delete_button = danger_button(
+            fl!(crate::LANGUAGE_LOADER, "duplicates-delete-marked", count = marked),
+            (marked > 0 && !self.is_scanning()).then(|| on_message(DuplicatesDialogMessage::DeleteMarked)),
+        );
+        let close_button = primary_button(
+            format!("{}", icy_engine_gui::ButtonType::Close),
+            Some(on_message(DuplicatesDialogMessage::Close)),
+        );
+        let buttons = button_row_with_left(vec![delete_button.into()], vec![close_button.into()]);
+
+        modal_container(
+            column![container(dialog_area(content.into())).height(Length::Shrink), separator(), dialog_area(buttons)].into(),
+            DIALOG_WIDTH,
+        )
+        .into()
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn preview_image<'a, M: 'a>(handle: &Option<iced_image::Handle>) -> Element<'a, M> {
+    let content: Element<'a, M> = match handle {
+        Some(handle) => iced_image::Image::new(handle.clone())
+            .content_fit(icy_ui::ContentFit::Contain)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into(),
+        None => Space::new().into(),
+    };
+    container(content).width(Length::Fill).height(Length::Fixed(PREVIEW_HEIGHT)).into()
+}
+
+fn list_button_style(theme: &icy_ui::Theme, status: button::Status, selected: bool) -> button::Style {
+    let (bg, text_color) = match status {
+        _ if selected => (theme.accent.base, theme.accent.on),
+        button::Status::Hovered => (theme.accent.selected, theme.accent.on),
+        button::Status::Pressed => (theme.accent.base, theme.accent.on),
+        _ => (icy_ui::Color::TRANSPARENT, theme.primary.on),
+    };
+    button::Style {
+        background: Some(icy_ui::Background::Color(bg)),
+        text_color,
+        border: icy_ui::Border {
+            color: icy_ui::Color::TRANSPARENT,
+            width: 0.0,
+            radius: 4.0.into(),
+        },
+        ..Default::default()
+    }
+}
+
+/// Render the top of a file for the side-by-side comparison
+fn render_preview(path: &str) -> Option<iced_image::Handle> {
+    let data = read_indexed_file(path).ok()?;
+    let ext = file_name(path).rsplit_once('.').map(|(_, ext)| ext)?;
+    let load_data = LoadData::new(None, None).with_max_height(PREVIEW_MAX_ROWS);
+    let loaded_doc = FileFormat::from_extension(ext)?.from_bytes(&data, Some(load_data)).ok()?;
+    let buffer = &loaded_doc.screen.buffer;
+    let opts = RenderOptions {
+        rect: Selection::from(Rectangle::from(0, 0, buffer.width(), buffer.height().min(PREVIEW_MAX_ROWS))),
+        blink_on: true,
+        selection: None,
+        selection_fg: None,
+        selection_bg: None,
+        override_scan_lines: Some(false),
+    };
+    let (size, rgba) = buffer.render_to_rgba(&opts, false);
+    Some(iced_image::Handle::from_rgba(size.width as u32, size.height as u32, rgba))
+}
+
+// ============================================================================
+// Builder functions for the duplicates dialog
+// ============================================================================
+
+/// Creates a duplicates dialog using a tuple of (on_message, extract_message),
+/// see `sauce_dialog_from_msg`.
+pub fn duplicates_dialog_from_msg<M, F, E>(folders: Vec<PathBuf>, msg_tuple: (F, E)) -> DuplicatesDialogWrapperWithScan<M, F, E>
+where
+    M: Clone + Send + 'static,
+    F: Fn(DuplicatesDialogMessage) -> M + Clone + 'static,
+    E: Fn(&M) -> Option<&DuplicatesDialogMessage> + Clone + 'static,
+{
+    DuplicatesDialogWrapperWithScan {
+        inner: DuplicatesDialogWrapper::new(DuplicatesDialogState::new(folders), msg_tuple.0, msg_tuple.1),
+    }
+}
+
+/// A wrapper around DuplicatesDialogWrapper that polls the background scan.
+/// This is needed because the dialog_wrapper macro doesn't forward animation ticks.
+pub struct DuplicatesDialogWrapperWithScan<M, F, E>
+where
+    M: Clone + Send + 'static,
+    F: Fn(DuplicatesDialogMessage) -> M + Clone + 'static,
+    E: Fn(&M) -> Option<&DuplicatesDialogMessage> + Clone + 'static,
+{
+    inner: DuplicatesDialogWrapper<M, F, E>,
+}
+
+impl<M, F, E> DuplicatesDialogWrapperWithScan<M, F, E>
+where
+    M: Clone + Send + 'static,
+    F: Fn(DuplicatesDialogMessage) -> M + Clone + 'static,
+    E: Fn(&M) -> Option<&DuplicatesDialogMessage> + Clone + 'static,
+{
+    /// Set callback for cancel/close.
+    pub fn on_cancel<G>(mut self, callback: G) -> Self
+    where
+        G: Fn() -> M + Send + 'static,
+    {
+        self.inner = self.inner.on_cancel(callback);
+        self
+    }
+}
+
+impl<M, F, E> Dialog<M> for DuplicatesDialogWrapperWithScan<M, F, E>
+where
+    M: Clone + Send + 'static,
+    F: Fn(DuplicatesDialogMessage) -> M + Clone + Send + 'static,
+    E: Fn(&M) -> Option<&DuplicatesDialogMessage> + Clone + Send + 'static,
+{
+    fn view(&self) -> Element<'_, M> {
+        self.inner.view()
+    }
+
+    fn update(&mut self, message: &M) -> Option<DialogAction<M>> {
+        if let Some(DuplicatesDialogMessage::DeleteMarked) = (self.inner.extract_message)(message) {
+            let count = self.inner.state.marked_count();
+            if count == 0 {
+                return Some(DialogAction::None);
+            }
+            let on_message = self.inner.on_message.clone();
+            return Some(DialogAction::push(confirm_delete(
+                fl!(crate::LANGUAGE_LOADER, "duplicates-delete-title"),
+                fl!(crate::LANGUAGE_LOADER, "duplicates-delete-message", count = count),
+                move |result| on_message(DuplicatesDialogMessage::ConfirmDelete(result == DialogResult::Delete)),
+            )));
+        }
+        self.inner.update(message)
+    }
+
+    fn request_cancel(&mut self) -> DialogAction<M> {
+        self.inner.request_cancel()
+    }
+
+    fn request_confirm(&mut self) -> DialogAction<M> {
+        self.inner.request_confirm()
+    }
+
+    fn handle_event(&mut self, event: &Event) -> Option<DialogAction<M>> {
+        self.inner.handle_event(event)
+    }
+
+    fn close_on_blur(&self) -> bool {
+        false
+    }
+
+    fn needs_animation(&self) -> bool {
+        self.inner.state.needs_polling()
+    }
+
+    fn update_animation(&mut self) {
+        self.inner.state.poll();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::DuplicateMember;
+
+    fn member(path: &str) -> DuplicateMember {
+        DuplicateMember {
+            path: path.to_string(),
+            size: 1,
+            similarity: 1.0,
+            exact: true,
+        }
+    }
+
+    #[test]
+    fn keeps_one_copy_per_group() {
+        let mut state = DuplicatesDialogState::new(Vec::new());
+        state.set_groups(vec![DuplicateGroup {
+            members: vec![member("a.ans"), member("b.ans"), member("c.ans")],
+        }]);
+        let _ = state.handle_message(DuplicatesDialogMessage::ToggleDelete(0, true));
+        let _ = state.handle_message(DuplicatesDialogMessage::ToggleDelete(1, true));
+        let _ = state.handle_message(DuplicatesDialogMessage::ToggleDelete(2, true));
+        assert_eq!(state.marked[0], vec![true, true, false]);
+
+        let _ = state.handle_message(DuplicatesDialogMessage::ToggleDelete(0, false));
+        let _ = state.handle_message(DuplicatesDialogMessage::ToggleDelete(2, true));
+        assert_eq!(state.marked[0], vec![false, true, true]);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod about_dialog;
+pub mod duplicates_dialog;
 pub mod help_dialog;
 pub mod sauce_dialog;
 pub mod settings_dialog;
@@ -47,6 +47,10 @@ pub enum SettingsDialogMessage {
     ExternalCommandChanged(usize, String),
     UpdateExportPath(String),
     BrowseExportPath,
+    UpdateIndexFolders(String),
+    BrowseIndexFolder,
+    UpdateMirrorPath(String),
+    BrowseMirrorPath,
     OpenSettingsFolder,
     OpenLogFile,
     Save,
@@ -139,6 +143,29 @@ impl SettingsDialogState {
                 }
                 StateResult::None
             }
+            SettingsDialogMessage::UpdateIndexFolders(folders) => {
+                self.temp_options.lock().index_folders = folders.split(';').map(|f| f.to_string()).collect();
+                StateResult::None
+            }
+            SettingsDialogMessage::BrowseIndexFolder => {
+                if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                    let mut opt = self.temp_options.lock();
+                    opt.index_folders.retain(|f| !f.trim().is_empty());
+                    opt.index_folders.push(folder.to_string_lossy().to_string());
+                }
+                StateResult::None
+            }
+            SettingsDialogMessage::UpdateMirrorPath(path) => {
+                self.temp_options.lock().sixteencolors_mirror = path;
+                StateResult::None
+            }
+            SettingsDialogMessage::BrowseMirrorPath => {
+                let mut opt = self.temp_options.lock();
+                if let Some(folder) = rfd::FileDialog::new().set_directory(opt.sixteencolors_mirror()).pick_folder() {
+                    opt.sixteencolors_mirror = folder.to_string_lossy().to_string();
+                }
+                StateResult::None
+            }
         }
     }
 
@@ -209,8 +236,11 @@ impl SettingsDialogState {
                 command_settings::commands_settings_content_generic(commands, on_message.clone())
             }
             SettingsCategory::Paths => {
-                let export_path = self.temp_options.lock().export_path.clone();
-                paths_settings::paths_settings_content_generic(export_path, on_message.clone())
+                let (export_path, index_folders, mirror_path) = {
+                    let opt = self.temp_options.lock();
+                    (opt.export_path.clone(), opt.index_folders.join(";"), opt.sixteencolors_mirror.clone())
+                };
+                paths_settings::paths_settings_content_generic(export_path, index_folders, mirror_path, on_message.clone())
             }
         };
 
@@ -14,6 +14,8 @@ use crate::{options::get_config_dir, Options};
 
 pub fn paths_settings_content_generic<M: Clone + 'static>(
     export_path: String,
+    index_folders: String,
+    mirror_path: String,
     on_message: impl Fn(SettingsDialogMessage) -> M + Clone + 'static,
 ) -> Element<'static, M> {
     let config_dir = get_config_dir().display().to_string();
@@ -24,6 +26,10 @@ pub fn paths_settings_content_generic<M: Clone + 'static>(
     let on_msg_2 = on_message.clone();
     let on_msg_3 = on_message.clone();
     let on_msg_4 = on_message.clone();
+    let on_msg_5 = on_message.clone();
+    let on_msg_6 = on_message.clone();
+    let on_msg_7 = on_message.clone();
+    let on_msg_8 = on_message.clone();
 
     let content = column![
         // System Paths (read-only)
@@ -76,6 +82,28 @@ pub fn paths_settings_content_generic<M: Clone + 'static>(
                 ]
                 .spacing(DIALOG_SPACING)
                 .align_y(Alignment::Center),
+                // Folders for the SAUCE search index (';' separated, browse appends)
+                row![
+                    left_label(fl!(crate::LANGUAGE_LOADER, "settings-paths-index-folders")),
+                    text_input(&fl!(crate::LANGUAGE_LOADER, "settings-paths-index-folders-placeholder"), &index_folders)
+                        .size(TEXT_SIZE_NORMAL)
+                        .width(Length::Fill)
+                        .on_input(move |s| on_msg_5(SettingsDialogMessage::UpdateIndexFolders(s))),
+                    browse_button(on_msg_6(SettingsDialogMessage::BrowseIndexFolder)),
+                ]
+                .spacing(DIALOG_SPACING)
+                .align_y(Alignment::Center),
+                // Local 16colo.rs mirror (editable with browse button)
+                row![
+                    left_label(fl!(crate::LANGUAGE_LOADER, "settings-paths-sixteencolors-mirror")),
+                    text_input(&Options::default_sixteencolors_mirror().to_string_lossy(), &mirror_path)
+                        .size(TEXT_SIZE_NORMAL)
+                        .width(Length::Fill)
+                        .on_input(move |s| on_msg_7(SettingsDialogMessage::UpdateMirrorPath(s))),
+                    browse_button(on_msg_8(SettingsDialogMessage::BrowseMirrorPath)),
+                ]
+                .spacing(DIALOG_SPACING)
+                .align_y(Alignment::Center),
             ]
             .spacing(DIALOG_SPACING)
             .into()
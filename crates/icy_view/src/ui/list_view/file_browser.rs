@@ -11,7 +11,10 @@ use icy_ui::{
 use super::file_list_view::{FileListView, FileListViewMessage};
 use super::sauce_loader::SharedSauceCache;
 use crate::items::Item;
-use crate::items::{get_items_at_path, is_directory, path_exists, sort_items, ItemError, NavPoint, ProviderType, SixteenColorsProvider};
+use crate::items::{
+    get_items_at_path, is_directory, path_exists, sort_items, IndexedItem, ItemError, NavPoint, ProviderType, SauceQuery, SharedSauceIndex,
+    SixteenColorsProvider,
+};
 use crate::sort_order::SortOrder;
 use icy_engine_gui::{focus, list_focus_style};
 
@@ -69,6 +72,8 @@ pub struct FileBrowser {
     is_loading: bool,
     /// Item label to select after async web navigation completes
     pending_select_label: Option<String>,
+    /// SAUCE index used for search folders
+    search_index: Option<SharedSauceIndex>,
 }
 
 impl FileBrowser {
@@ -139,6 +144,7 @@ impl FileBrowser {
             sauce_cache: None,
             is_loading: false,
             pending_select_label: None,
+            search_index: None,
         };
 
         // If we have a file to select, find and select it
@@ -248,6 +254,15 @@ impl FileBrowser {
                 self.list_view.invalidate();
                 return Self::load_web_items(path);
             }
+            ProviderType::Search => {
+                if let Some(index) = &self.search_index {
+                    let query = SauceQuery::parse(&self.nav_point.path);
+                    let results = index.read().search(&query);
+                    self.files
+                        .extend(results.into_iter().map(|entry| Box::new(IndexedItem::new(entry)) as Box<dyn Item>));
+                    sort_items(&mut self.files, self.sort_order);
+                }
+            }
         }
 
         self.update_visible_indices();
@@ -410,7 +425,11 @@ impl FileBrowser {
     }
 
     /// Get the current path
+    /// Search folders have none - their items carry full paths
     pub fn current_path(&self) -> Option<PathBuf> {
+        if self.nav_point.is_search() {
+            return None;
+        }
         Some(PathBuf::from(&self.nav_point.path))
     }
 
@@ -495,6 +514,17 @@ impl FileBrowser {
         self.refresh()
     }
 
+    /// Show the results of a SAUCE index query
+    pub fn navigate_to_search(&mut self, query: &str) {
+        self.nav_point = NavPoint::search(query.trim());
+        let _ = self.refresh();
+    }
+
+    /// Set the SAUCE index used for search folders
+    pub fn set_search_index(&mut self, index: SharedSauceIndex) {
+        self.search_index = Some(index);
+    }
+
     /// Select an item by label when the next async web navigation completes.
     pub fn select_by_label_after_load(&mut self, label: String) {
         self.pending_select_label = Some(label);
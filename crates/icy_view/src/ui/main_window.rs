@@ -18,13 +18,17 @@ use tokio_util::sync::CancellationToken;
 
 use crate::{
     commands::{cmd, create_icy_view_commands},
-    items::{load_item_data, load_subitems, Item, ItemError, ProviderType, SixteenColorsProvider, SixteenColorsRoot},
+    items::{
+        get_sauce_index, load_item_data, load_subitems, set_mirror_dir, start_indexer, Item, ItemError, ProviderType, SixteenColorsProvider, SixteenColorsRoot,
+        SEARCH_PREFIX,
+    },
     Options, ScrollSpeed, ViewMode, DEFAULT_TITLE, VERSION,
 };
 use icy_engine::formats::FileFormat;
 
 use super::{
     dialogs::about_dialog::{about_dialog, AboutDialogMessage},
+    dialogs::duplicates_dialog::{duplicates_dialog_from_msg, DuplicatesDialogMessage},
     dialogs::help_dialog::help_dialog,
     dialogs::sauce_dialog::{sauce_dialog_from_msg, SauceDialogMessage},
     dialogs::settings_dialog::{settings_dialog_from_msg, SettingsDialogMessage},
@@ -48,6 +52,7 @@ command_handler!(MainWindowCommands, create_icy_view_commands(), => Message {
     cmd::HELP_ABOUT => Message::ShowAbout,
     cmd::DIALOG_EXPORT => Message::ShowExportDialog,
     cmd::DIALOG_FILTER => Message::ToggleFilterPopup,
+    cmd::DIALOG_DUPLICATES => Message::ShowDuplicatesDialog,
     // Edit
     cmd::EDIT_COPY => Message::Copy,
     // External commands
@@ -143,6 +148,10 @@ pub enum Message {
     ShowExportDialog,
     /// Export dialog messages
     ExportDialog(ExportDialogMessage),
+    /// Show duplicate finder dialog
+    ShowDuplicatesDialog,
+    /// Duplicate finder dialog messages
+    DuplicatesDialog(DuplicatesDialogMessage),
     /// Copy selection to clipboard
     Copy,
     /// Copy operation completed
@@ -278,6 +287,10 @@ impl MainWindow {
         let (mut file_browser, file_to_preview) = FileBrowser::new(initial_path.clone());
         // Apply saved sort order
         file_browser.set_sort_order(sort_order);
+        // Search folders query the shared SAUCE index, kept up to date in the background
+        file_browser.set_search_index(get_sauce_index());
+        start_indexer(options.lock().index_folders());
+        set_mirror_dir(Some(options.lock().sixteencolors_mirror()));
 
         let mut history = NavigationHistory::new();
         // Initialize history with current state
@@ -612,6 +625,27 @@ impl MainWindow {
                         self.folder_preview_path = None;
 
                         let path_str = self.navigation_bar.path_input.clone();
+
+                        // "search:<query>" (or "?<query>") shows matching files from the SAUCE index
+                        if let Some(query) = path_str.strip_prefix(SEARCH_PREFIX).or_else(|| path_str.strip_prefix('?')) {
+                            if query.trim().is_empty() {
+                                self.navigation_bar.set_path_valid(false);
+                                return Task::none();
+                            }
+                            self.navigation_bar.set_16colors_mode(false);
+                            self.file_browser.navigate_to_search(query);
+                            self.reset_sauce_loader_for_navigation();
+                            self.navigation_bar.set_path_input(self.file_browser.get_display_path());
+                            self.file_list_toolbar.set_can_go_up(false);
+                            if self.view_mode() == ViewMode::Tiles {
+                                let items = self.file_browser.get_items();
+                                self.tile_grid.set_items_from_items(items);
+                            }
+                            let point = self.current_history_point();
+                            self.history.navigate_to(point);
+                            return Task::none();
+                        }
+
                         // Check if it's a 16colors path (starts with / and we're in 16colors mode, or explicit 16colors prefix)
                         let is_16colors_path = path_str.starts_with("16colors://")
                             || path_str.starts_with("/16colors/")
@@ -1474,6 +1508,25 @@ impl MainWindow {
                 // Dialog is closed via DialogStack's request_cancel/confirm
                 // Refresh cached monitor settings in case they were changed
                 self.cached_monitor_settings = Arc::new(self.options.lock().monitor_settings.clone());
+                // Pick up changed search index folders
+                start_indexer(self.options.lock().index_folders());
+                set_mirror_dir(Some(self.options.lock().sixteencolors_mirror()));
+                Task::none()
+            }
+            Message::ShowDuplicatesDialog => {
+                // Default to the folder currently shown in the browser
+                let folders = self.file_browser.current_path().filter(|p| p.is_dir()).into_iter().collect();
+                self.dialogs.push(
+                    duplicates_dialog_from_msg(folders, dialog_msg!(Message::DuplicatesDialog))
+                        // Deleted files should disappear from the file list
+                        .on_cancel(|| Message::Navigation(NavigationBarMessage::Refresh)),
+                );
+                Task::none()
+            }
+            Message::DuplicatesDialog(ref _msg) => {
+                if let Some(task) = self.dialogs.update(&message) {
+                    return task;
+                }
                 Task::none()
             }
             Message::ShowHelp => {
@@ -1608,6 +1661,9 @@ impl MainWindow {
                     tasks.push(self.advance_shuffle_if_ready());
                 }
 
+                // Let dialogs poll their background work
+                self.dialogs.update_animation();
+
                 // Forward tick to file browser's list view
                 let _ = self.file_browser.update(FileBrowserMessage::ListView(FileListViewMessage::Tick));
                 // Poll tile grid results if in tiles mode
@@ -2512,6 +2568,7 @@ impl MainWindow {
             || (self.view_mode() == ViewMode::Tiles && self.tile_grid.needs_animation())
             || (self.view_mode() == ViewMode::List && self.folder_preview_path.is_some() && self.folder_preview.needs_animation())
             || self.shuffle_mode.needs_animation()
+            || self.dialogs.needs_animation()
     }
 
     pub fn handle_event(&mut self, event: &Event) -> (Option<Message>, Task<Message>) {
@@ -2649,9 +2706,10 @@ impl MainWindow {
         } else {
             None
         };
-        if !is_web {
-            // For file, navigate to filesystem path
-            self.file_browser.navigate_to(PathBuf::from(&point.path));
+        match point.provider {
+            ProviderType::File => self.file_browser.navigate_to(PathBuf::from(&point.path)),
+            ProviderType::Search => self.file_browser.navigate_to_search(&point.path),
+            ProviderType::Web => {}
         }
 
         // 3. Update navigation bar
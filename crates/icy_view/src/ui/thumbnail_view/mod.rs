@@ -3,15 +3,18 @@
 //! This module contains all components for rendering file thumbnails in a grid layout:
 //! - `thumbnail` - Data structures for thumbnails (RgbaData, Thumbnail, ThumbnailState)
 //! - `thumbnail_loader` - Background loading of thumbnails with worker threads
+//! - `thumbnail_cache` - Persistent on-disk cache of rendered thumbnails
 //! - `tile_shader` - GPU shader-based tile rendering with wgpu
 //! - `tile_grid_view` - Main tile grid view component
 //! - `masonry_layout` - Masonry layout algorithm for the grid
 
 mod masonry_layout;
 mod thumbnail;
+mod thumbnail_cache;
 mod thumbnail_loader;
 mod tile_grid_view;
 pub mod tile_shader;
 
 // Re-export public API
+pub use thumbnail_loader::warm_disk_cache;
 pub use tile_grid_view::{TileGridMessage, TileGridView};
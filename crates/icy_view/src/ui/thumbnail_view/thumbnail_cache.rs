@@ -0,0 +1,480 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use directories::BaseDirs;
+use icy_sauce::{Capabilities, LetterSpacing, SauceRecord};
+use image::{codecs::png::PngEncoder, ExtendedColorType, ImageEncoder};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::thumbnail::{RgbaData, ThumbnailResult, ThumbnailState, THUMBNAIL_MAX_HEIGHT, THUMBNAIL_RENDER_WIDTH};
+
+/// Bump when the thumbnail renderer output changes - old entries simply stop matching
+const CACHE_VERSION: u32 = 2;
+
+/// Size cap of the cache directory, least recently used entries are evicted beyond it
+const MAX_CACHE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Eviction trims down to this fraction of the cap, so it does not run on every insert
+const EVICT_TARGET_PERCENT: u64 = 90;
+
+const ENTRY_MAGIC: &[u8; 4] = b"ICYT";
+const ENTRY_EXTENSION: &str = "thumb";
+const PATH_INDEX_FILE: &str = "paths.json";
+const PATH_INDEX_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Get the thumbnail cache directory
+fn get_cache_dir() -> Option<PathBuf> {
+    BaseDirs::new().map(|dirs| dirs.cache_dir().join("icy_view").join("thumbnails"))
+}
+
+/// Render settings that change the pixels of a thumbnail besides the raw content
+///
+/// Font, ice colors and 9px mode come from the SAUCE record.
+struct RenderSettings {
+    version: u32,
+    render_width: u32,
+    max_height: u32,
+    font: Option<String>,
+    ice_colors: bool,
+    nine_px: bool,
+}
+
+impl RenderSettings {
+    fn from_sauce(sauce: Option<&SauceRecord>) -> Self {
+        let (font, ice_colors, nine_px) = match sauce.and_then(|s| s.capabilities()) {
+            Some(Capabilities::Character(caps)) => (
+                caps.font().map(|f| f.to_string()),
+                caps.ice_colors,
+                caps.letter_spacing == LetterSpacing::NinePixel,
+            ),
+            Some(Capabilities::Binary(caps)) => (
+                caps.font().map(|f| f.to_string()),
+                caps.ice_colors,
+                caps.letter_spacing == LetterSpacing::NinePixel,
+            ),
+            _ => (None, false, false),
+        };
+        Self {
+            version: CACHE_VERSION,
+            render_width: THUMBNAIL_RENDER_WIDTH,
+            max_height: THUMBNAIL_MAX_HEIGHT,
+            font,
+            ice_colors,
+            nine_px,
+        }
+    }
+
+    /// Feed the settings into the key hash, every field with a fixed size or length prefix
+    fn update(&self, hasher: &mut Sha256) {
+        hasher.update(self.version.to_le_bytes());
+        hasher.update(self.render_width.to_le_bytes());
+        hasher.update(self.max_height.to_le_bytes());
+        match &self.font {
+            Some(font) => {
+                hasher.update([1]);
+                hasher.update((font.len() as u64).to_le_bytes());
+                hasher.update(font.as_bytes());
+            }
+            None => hasher.update([0]),
+        }
+        hasher.update([u8::from(self.ice_colors), u8::from(self.nine_px)]);
+    }
+}
+
+/// Cache key for file content under the current render settings
+///
+/// Keys name files on disk, so they come from SHA-256 - `DefaultHasher` output may change
+/// between Rust releases.
+pub fn content_key(data: &[u8]) -> u64 {
+    let sauce = SauceRecord::from_bytes(data).ok().flatten();
+    let mut hasher = Sha256::new();
+    RenderSettings::from_sauce(sauce.as_ref()).update(&mut hasher);
+    hasher.update(data);
+    let digest = hasher.finalize();
+    let mut key = [0u8; 8];
+    key.copy_from_slice(&digest[..8]);
+    u64::from_le_bytes(key)
+}
+
+/// A rendered thumbnail as stored on disk - the label tag is cheap and rendered on load
+pub struct CachedThumbnail {
+    /// One frame for static images, two for blinking content
+    pub frames: Vec<RgbaData>,
+    pub width_multiplier: u32,
+    pub sauce: Option<SauceRecord>,
+}
+
+impl CachedThumbnail {
+    /// Take the cacheable parts of a render result - placeholders and errors are not cached
+    pub fn from_result(result: &ThumbnailResult) -> Option<Self> {
+        let frames = match &result.state {
+            ThumbnailState::Ready { rgba } => vec![rgba.clone()],
+            ThumbnailState::Animated { frames, .. } => frames.clone(),
+            _ => return None,
+        };
+        Some(Self {
+            frames,
+            width_multiplier: result.width_multiplier,
+            sauce: result.sauce_info.clone(),
+        })
+    }
+
+    pub fn into_result(self, path: String, label_rgba: Option<RgbaData>) -> ThumbnailResult {
+        let state = if self.frames.len() > 1 {
+            ThumbnailState::Animated {
+                frames: self.frames,
+                current_frame: 0,
+            }
+        } else {
+            ThumbnailState::Ready {
+                rgba: self.frames.into_iter().next().unwrap_or_else(|| RgbaData::new(Vec::new(), 0, 0)),
+            }
+        };
+        ThumbnailResult {
+            path,
+            state,
+            sauce_info: self.sauce,
+            width_multiplier: self.width_multiplier,
+            label_rgba,
+        }
+    }
+
+    fn encode(&self) -> Option<Vec<u8>> {
+        let mut sauce_bytes = Vec::new();
+        if let Some(sauce) = &self.sauce {
+            sauce.write(&mut sauce_bytes).ok()?;
+        }
+
+        let mut out = Vec::new();
+        out.extend_from_slice(ENTRY_MAGIC);
+        out.extend_from_slice(&CACHE_VERSION.to_le_bytes());
+        out.extend_from_slice(&self.width_multiplier.to_le_bytes());
+        out.extend_from_slice(&(sauce_bytes.len() as u32).to_le_bytes());
+        out.extend_from_slice(&sauce_bytes);
+        out.extend_from_slice(&(self.frames.len() as u32).to_le_bytes());
+        for frame in &self.frames {
+            // PNG keeps tall renders small; fast compression is still far cheaper than re-rendering
+            let mut png = Vec::new();
+            let encoder = PngEncoder::new_with_quality(&mut png, image::codecs::png::CompressionType::Fast, image::codecs::png::FilterType::Adaptive);
+            encoder.write_image(&frame.data, frame.width, frame.height, ExtendedColorType::Rgba8).ok()?;
+            out.extend_from_slice(&(png.len() as u32).to_le_bytes());
+            out.extend_from_slice(&png);
+        }
+        Some(out)
+    }
+
+    fn decode(data: &[u8]) -> Option<Self> {
+        let mut reader = ByteReader { data, pos: 0 };
+        if reader.bytes(4)? != ENTRY_MAGIC || reader.u32()? != CACHE_VERSION {
+            return None;
+        }
+        let width_multiplier = reader.u32()?;
+        let sauce_len = reader.u32()? as usize;
+        let sauce = if sauce_len > 0 {
+            SauceRecord::from_bytes(reader.bytes(sauce_len)?).ok().flatten()
+        } else {
+            None
+        };
+        let frame_count = reader.u32()? as usize;
+        let mut frames = Vec::with_capacity(frame_count);
+        for _ in 0..frame_count {
+            let len = reader.u32()? as usize;
+            let png = reader.bytes(len)?;
+            let img = image::load(Cursor::new(png), image::ImageFormat::Png).ok()?.to_rgba8();
+            let (width, height) = img.dimensions();
+            frames.push(RgbaData::new(img.into_raw(), width, height));
+        }
+        if frames.is_empty() {
+            return None;
+        }
+        Some(Self {
+            frames,
+            width_multiplier,
+            sauce,
+        })
+    }
+}
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let slice = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(slice)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.bytes(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+/// Size and modification time of a file on disk, mapped to the content key it had
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+struct PathStamp {
+    size: u64,
+    modified: u64,
+    key: u64,
+}
+
+fn file_stamp(path: &str) -> Option<(u64, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some((metadata.len(), modified))
+}
+
+struct CacheState {
+    /// Disk files whose content key is known - lets unchanged files skip reading and hashing
+    paths: HashMap<String, PathStamp>,
+    total_bytes: u64,
+    dirty: bool,
+    last_save: Instant,
+}
+
+/// Persistent, content addressed thumbnail cache
+///
+/// Entries are keyed by a hash of the file content and the render settings. For files on disk
+/// the last known size/mtime is remembered as well, a changed stamp falls back to hashing.
+pub struct ThumbnailDiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    state: Mutex<CacheState>,
+}
+
+impl ThumbnailDiskCache {
+    /// Shared cache instance, None if there is no usable cache directory
+    pub fn global() -> Option<&'static ThumbnailDiskCache> {
+        static CACHE: OnceLock<Option<ThumbnailDiskCache>> = OnceLock::new();
+        CACHE.get_or_init(|| get_cache_dir().and_then(|dir| Self::open(dir, MAX_CACHE_BYTES))).as_ref()
+    }
+
+    pub fn open(dir: PathBuf, max_bytes: u64) -> Option<Self> {
+        if let Err(err) = std::fs::create_dir_all(&dir) {
+            log::warn!("Failed to create thumbnail cache directory {:?}: {}", dir, err);
+            return None;
+        }
+        let paths = std::fs::read(dir.join(PATH_INDEX_FILE))
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        let total_bytes = entry_files(&dir).iter().map(|(_, len, _)| *len).sum();
+        Some(Self {
+            dir,
+            max_bytes,
+            state: Mutex::new(CacheState {
+                paths,
+                total_bytes,
+                dirty: false,
+                last_save: Instant::now(),
+            }),
+        })
+    }
+
+    fn entry_path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{:016x}.{}", key, ENTRY_EXTENSION))
+    }
+
+    /// Content key of a disk file if it is unchanged since it was cached
+    pub fn key_for_path(&self, path: &str) -> Option<u64> {
+        let stamp = *self.state.lock().paths.get(path)?;
+        let (size, modified) = file_stamp(path)?;
+        (stamp.size == size && stamp.modified == modified).then_some(stamp.key)
+    }
+
+    /// Remember the content key of a disk file (no-op for files inside archives)
+    pub fn remember_path(&self, path: &str, key: u64) {
+        let Some((size, modified)) = file_stamp(path) else {
+            return;
+        };
+        let mut state = self.state.lock();
+        let stamp = PathStamp { size, modified, key };
+        if state.paths.get(path) != Some(&stamp) {
+            state.paths.insert(path.to_string(), stamp);
+            state.dirty = true;
+        }
+        if state.dirty && state.last_save.elapsed() > PATH_INDEX_SAVE_INTERVAL {
+            self.save_paths(&mut state);
+        }
+    }
+
+    pub fn contains(&self, key: u64) -> bool {
+        self.entry_path(key).exists()
+    }
+
+    pub fn get(&self, key: u64) -> Option<CachedThumbnail> {
+        let path = self.entry_path(key);
+        let data = std::fs::read(&path).ok()?;
+        let Some(thumbnail) = CachedThumbnail::decode(&data) else {
+            log::debug!("Dropping unreadable thumbnail cache entry {:?}", path);
+            let _ = std::fs::remove_file(&path);
+            return None;
+        };
+        // The modification time doubles as the LRU timestamp
+        if let Ok(file) = std::fs::File::options().write(true).open(&path) {
+            let _ = file.set_modified(SystemTime::now());
+        }
+        Some(thumbnail)
+    }
+
+    pub fn insert(&self, key: u64, thumbnail: &CachedThumbnail) {
+        let Some(data) = thumbnail.encode() else {
+            return;
+        };
+        let path = self.entry_path(key);
+        let old_len = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        // Write to a temporary file first so concurrent readers never see a partial entry
+        let tmp_path = self.dir.join(format!("{:016x}.{:016x}.tmp", key, fastrand::u64(..)));
+        if let Err(err) = std::fs::write(&tmp_path, &data).and_then(|_| std::fs::rename(&tmp_path, &path)) {
+            log::debug!("Failed to write thumbnail cache entry {:?}: {}", path, err);
+            let _ = std::fs::remove_file(&tmp_path);
+            return;
+        }
+
+        let mut state = self.state.lock();
+        state.total_bytes = state.total_bytes.saturating_sub(old_len) + data.len() as u64;
+        if state.total_bytes > self.max_bytes {
+            self.evict(&mut state);
+        }
+    }
+
+    /// Write the path index if it changed
+    pub fn flush(&self) {
+        let mut state = self.state.lock();
+        if state.dirty {
+            self.save_paths(&mut state);
+        }
+    }
+
+    fn save_paths(&self, state: &mut CacheState) {
+        match serde_json::to_vec(&state.paths) {
+            Ok(data) => {
+                if let Err(err) = std::fs::write(self.dir.join(PATH_INDEX_FILE), data) {
+                    log::debug!("Failed to write thumbnail path index: {}", err);
+                }
+            }
+            Err(err) => log::debug!("Failed to serialize thumbnail path index: {}", err),
+        }
+        state.dirty = false;
+        state.last_save = Instant::now();
+    }
+
+    /// Delete least recently used entries until the cache is below the target size
+    fn evict(&self, state: &mut CacheState) {
+        let mut entries = entry_files(&self.dir);
+        entries.sort_by_key(|(_, _, modified)| *modified);
+
+        let target = self.max_bytes / 100 * EVICT_TARGET_PERCENT;
+        let mut total: u64 = entries.iter().map(|(_, len, _)| *len).sum();
+        let mut removed = 0;
+        for (path, len, _) in entries {
+            if total <= target {
+                break;
+            }
+            if std::fs::remove_file(&path).is_ok() {
+                total -= len;
+                removed += 1;
+            }
+        }
+        state.total_bytes = total;
+        log::debug!("Evicted {} thumbnail cache entries, {} bytes remain", removed, total);
+    }
+}
+
+/// All entry files of a cache directory with their size and modification time
+fn entry_files(dir: &Path) -> Vec<(PathBuf, u64, SystemTime)> {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    read_dir
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == ENTRY_EXTENSION))
+        .filter_map(|e| {
+            let metadata = e.metadata().ok()?;
+            Some((e.path(), metadata.len(), metadata.modified().unwrap_or(UNIX_EPOCH)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache(name: &str, max_bytes: u64) -> ThumbnailDiskCache {
+        let dir = std::env::temp_dir().join(format!("icy_view_thumb_test_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        ThumbnailDiskCache::open(dir, max_bytes).unwrap()
+    }
+
+    fn thumbnail(fill: u8) -> CachedThumbnail {
+        CachedThumbnail {
+            frames: vec![RgbaData::new(vec![fill; 8 * 4 * 4], 8, 4)],
+            width_multiplier: 2,
+            sauce: None,
+        }
+    }
+
+    #[test]
+    fn entries_round_trip() {
+        let cache = temp_cache("round_trip", MAX_CACHE_BYTES);
+        let key = content_key(b"\x1b[1;31mHello");
+        cache.insert(key, &thumbnail(7));
+
+        let loaded = cache.get(key).unwrap();
+        assert_eq!(loaded.width_multiplier, 2);
+        assert_eq!(loaded.frames.len(), 1);
+        assert_eq!((loaded.frames[0].width, loaded.frames[0].height), (8, 4));
+        assert!(loaded.frames[0].data.iter().all(|b| *b == 7));
+        assert!(cache.get(key.wrapping_add(1)).is_none());
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn content_key_depends_on_content() {
+        assert_eq!(content_key(b"abc"), content_key(b"abc"));
+        assert_ne!(content_key(b"abc"), content_key(b"abd"));
+    }
+
+    #[test]
+    fn content_key_is_stable() {
+        // Changes only with the render settings, existing cache entries depend on it
+        assert_eq!(content_key(b"abc"), 0x78d2_bdc5_b03a_eff5);
+    }
+
+    #[test]
+    fn changed_file_invalidates_path_stamp() {
+        let cache = temp_cache("stamp", MAX_CACHE_BYTES);
+        let file = cache.dir.join("art.ans");
+        std::fs::write(&file, b"first").unwrap();
+        let path = file.to_string_lossy().to_string();
+
+        cache.remember_path(&path, 42);
+        assert_eq!(cache.key_for_path(&path), Some(42));
+
+        std::fs::write(&file, b"second version").unwrap();
+        assert_eq!(cache.key_for_path(&path), None);
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn eviction_keeps_cache_below_cap() {
+        let probe = thumbnail(0).encode().unwrap().len() as u64;
+        let cache = temp_cache("evict", probe * 3);
+        for key in 0..10u64 {
+            cache.insert(key, &thumbnail(key as u8));
+        }
+        let total: u64 = entry_files(&cache.dir).iter().map(|(_, len, _)| *len).sum();
+        assert!(total <= probe * 3);
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+}
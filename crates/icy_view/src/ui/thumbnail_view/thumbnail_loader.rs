@@ -17,6 +17,7 @@ use crate::items::Item;
 use crate::ui::preview::prepare_parser_data;
 
 use super::thumbnail::{get_width_multiplier, RgbaData, ThumbnailResult, ThumbnailState, THUMBNAIL_MAX_HEIGHT, THUMBNAIL_RENDER_WIDTH};
+use super::thumbnail_cache::{content_key, CachedThumbnail, ThumbnailDiskCache};
 
 /// Maximum characters per line for label tag
 const TAG_MAX_CHARS_PER_LINE: usize = 28;
@@ -81,6 +82,30 @@ impl ThumbnailLoader {
             }
 
             let label = item.get_label();
+            let full_path = item.get_full_path();
+
+            // Step 0: Unchanged files on disk come straight from the thumbnail cache,
+            // without reading or parsing them
+            if let (Some(cache), Some(full_path)) = (ThumbnailDiskCache::global(), full_path.clone()) {
+                let path_clone = path.clone();
+                let label_clone = label.clone();
+                let cached = tokio::task::spawn_blocking(move || {
+                    let key = cache.key_for_path(&full_path)?;
+                    cache_lookup(cache, key, path_clone, &label_clone)
+                })
+                .await
+                .ok()
+                .flatten();
+                if let Some(thumbnail_result) = cached {
+                    if !cancel_token.is_cancelled() {
+                        debug!("[ThumbnailLoader] Disk cache hit: {:?}", path);
+                        if let Err(e) = result_tx.send(thumbnail_result) {
+                            warn!("[ThumbnailLoader] Failed to send result: {}", e);
+                        }
+                    }
+                    return;
+                }
+            }
 
             // Step 1: Async I/O - get thumbnail preview or read data
             let render_input = get_render_input(&*item, &cancel_token).await;
@@ -118,9 +143,30 @@ impl ThumbnailLoader {
                         })
                     }
                     RenderInput::FileData(data) => {
+                        // Same content may already be cached under another path (or before a touch)
+                        let cache = ThumbnailDiskCache::global();
+                        let key = cache.map(|_| content_key(&data));
+                        if let (Some(cache), Some(key)) = (cache, key) {
+                            if let Some(result) = cache_lookup(cache, key, path_clone.clone(), &label_clone) {
+                                if let Some(full_path) = &full_path {
+                                    cache.remember_path(full_path, key);
+                                }
+                                return Some(result);
+                            }
+                        }
+
                         // Need to render from file data
                         // Returns None if format not supported - show unsupported placeholder
-                        match render_thumbnail(&path_clone, &data, &label_clone, &cancel_clone) {
+                        let rendered = render_thumbnail(&path_clone, &data, &label_clone, &cancel_clone);
+                        if let (Some(cache), Some(key), Some(result)) = (cache, key, &rendered) {
+                            if let Some(thumbnail) = CachedThumbnail::from_result(result) {
+                                cache.insert(key, &thumbnail);
+                                if let Some(full_path) = &full_path {
+                                    cache.remember_path(full_path, key);
+                                }
+                            }
+                        }
+                        match rendered {
                             Some(result) => Some(result),
                             None => {
                                 // Format not supported - show unsupported placeholder with label
@@ -203,6 +249,95 @@ impl ThumbnailLoader {
     }
 }
 
+impl Drop for ThumbnailLoader {
+    fn drop(&mut self) {
+        // Persist path stamps recorded since the last periodic save
+        if let Some(cache) = ThumbnailDiskCache::global() {
+            cache.flush();
+        }
+    }
+}
+
+/// Build a result from a disk cache entry, rendering only the label tag
+fn cache_lookup(cache: &ThumbnailDiskCache, key: u64, path: String, label: &str) -> Option<ThumbnailResult> {
+    let thumbnail = cache.get(key)?;
+    let label_rgba = render_label_tag(label, thumbnail.width_multiplier);
+    Some(thumbnail.into_result(path, label_rgba))
+}
+
+/// Render thumbnails for all files below `roots` into the disk cache without opening a window.
+/// Archives are rendered entry by entry. Returns the number of newly rendered thumbnails.
+pub fn warm_disk_cache(roots: &[PathBuf]) -> usize {
+    let Some(cache) = ThumbnailDiskCache::global() else {
+        log::error!("No thumbnail cache directory available");
+        return 0;
+    };
+
+    let files: Vec<PathBuf> = roots
+        .iter()
+        .flat_map(|root| walkdir::WalkDir::new(root).follow_links(true).into_iter().filter_map(|e| e.ok()))
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let chunk_size = files.len().div_ceil(threads).max(1);
+    let rendered = std::sync::atomic::AtomicUsize::new(0);
+    let cancel_token = CancellationToken::new();
+
+    std::thread::scope(|scope| {
+        for chunk in files.chunks(chunk_size) {
+            let rendered = &rendered;
+            let cancel_token = &cancel_token;
+            scope.spawn(move || {
+                for file in chunk {
+                    let full_path = file.to_string_lossy().replace('\\', "/");
+                    if cache.key_for_path(&full_path).is_some() {
+                        continue;
+                    }
+                    let Ok(data) = std::fs::read(file) else {
+                        continue;
+                    };
+                    if let Some(FileFormat::Archive(format)) = FileFormat::from_path(file) {
+                        if let Some((entries, _)) = crate::items::parse_archive(data, format, cancel_token.clone()) {
+                            for (name, entry_data) in entries {
+                                let entry_path = format!("{full_path}/{name}");
+                                if let Some((_, true)) = warm_entry(cache, &entry_path, &entry_data, cancel_token) {
+                                    rendered.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                }
+                            }
+                        }
+                        continue;
+                    }
+                    if let Some((key, newly_rendered)) = warm_entry(cache, &full_path, &data, cancel_token) {
+                        cache.remember_path(&full_path, key);
+                        if newly_rendered {
+                            rendered.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    cache.flush();
+    rendered.into_inner()
+}
+
+/// Render one file into the cache unless its content is already there.
+/// Returns the content key and whether a new thumbnail was rendered.
+fn warm_entry(cache: &ThumbnailDiskCache, path: &str, data: &[u8], cancel_token: &CancellationToken) -> Option<(u64, bool)> {
+    let key = content_key(data);
+    if cache.contains(key) {
+        return Some((key, false));
+    }
+    let label = path.rsplit('/').next().unwrap_or(path);
+    let result = render_thumbnail(&path.to_string(), data, label, cancel_token)?;
+    cache.insert(key, &CachedThumbnail::from_result(&result)?);
+    log::info!("Cached thumbnail for {}", path);
+    Some((key, true))
+}
+
 /// Input for the rendering step - either pre-rendered data or raw file data
 enum RenderInput {
     /// Already rendered thumbnail image (e.g., folder placeholder, API thumbnail)